@@ -0,0 +1,59 @@
+//! Allocation counting for benchmarks (`alloc-stats` feature).
+//!
+//! Install [`CountingAllocator`] as the global allocator of a benchmark or
+//! test binary, then read [`AllocStats::current`](crate::AllocStats::current)
+//! before and after the code to measure:
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOC: CountingAllocator = CountingAllocator;
+//!
+//! let before = AllocStats::current();
+//! let tokens: Vec<_> = lexer.collect();
+//! println!("{:?}", AllocStats::current().since(before));
+//! ```
+//!
+//! Counts are kept per thread, so tests running in parallel do not see
+//! each other's allocations. Without the allocator installed they stay 0.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+thread_local! {
+    pub(crate) static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+    pub(crate) static BYTES: Cell<u64> = const { Cell::new(0) };
+}
+
+/// A global allocator that forwards to [`System`] and counts allocations
+/// and requested bytes on the calling thread. Frees are not counted.
+pub struct CountingAllocator;
+
+fn record(bytes: usize) {
+    // `try_with` fails only while the thread is shutting down.
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+    let _ = BYTES.try_with(|total| total.set(total.get() + bytes as u64));
+}
+
+// SAFETY: every method forwards to `System` with the caller's arguments
+// unchanged; counting only touches thread-local integers and never
+// allocates.
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
@@ -0,0 +1,216 @@
+//! Results of the `bench_support` helpers in the lexer and parser crates.
+
+use std::fmt;
+use std::ops::AddAssign;
+use std::time::Duration;
+
+/// Allocations counted by `CountingAllocator` (`alloc-stats` feature).
+///
+/// Always 0 unless the feature is enabled and the allocator is installed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    /// Number of allocations and reallocations.
+    pub allocations: u64,
+    /// Bytes requested by them.
+    pub bytes: u64,
+}
+
+impl AllocStats {
+    /// Returns the calling thread's totals so far; always 0 without the
+    /// `alloc-stats` feature.
+    pub fn current() -> Self {
+        #[cfg(feature = "alloc-stats")]
+        {
+            use crate::alloc_stats::{ALLOCATIONS, BYTES};
+            Self {
+                allocations: ALLOCATIONS.with(std::cell::Cell::get),
+                bytes: BYTES.with(std::cell::Cell::get),
+            }
+        }
+        #[cfg(not(feature = "alloc-stats"))]
+        Self::default()
+    }
+
+    /// Runs `f` and returns its result with the allocations it made.
+    pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Self) {
+        let before = Self::current();
+        let result = f();
+        (result, Self::current().since(before))
+    }
+
+    /// Returns what was allocated between `earlier` and `self`.
+    pub fn since(self, earlier: AllocStats) -> Self {
+        Self {
+            allocations: self.allocations.saturating_sub(earlier.allocations),
+            bytes: self.bytes.saturating_sub(earlier.bytes),
+        }
+    }
+}
+
+impl AddAssign for AllocStats {
+    fn add_assign(&mut self, other: Self) {
+        self.allocations += other.allocations;
+        self.bytes += other.bytes;
+    }
+}
+
+/// How often one rule was tried during a benchmark run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleStats {
+    /// The rule's name, as returned by its `name()` method.
+    pub name: String,
+    /// Number of times the rule was tried.
+    pub attempts: u64,
+    /// Number of attempts that produced a token or node.
+    pub matches: u64,
+    /// Number of times tokens the rule consumed were put back. Always 0
+    /// for lexing rules.
+    pub backtracks: u64,
+    /// Allocations made while the rule ran, including the token or node it
+    /// built.
+    pub allocations: AllocStats,
+}
+
+impl RuleStats {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the share of attempts that matched, from 0.0 to 1.0.
+    pub fn hit_rate(&self) -> f64 {
+        if self.attempts == 0 {
+            0.0
+        } else {
+            self.matches as f64 / self.attempts as f64
+        }
+    }
+}
+
+/// Timing and rule statistics for one rule set over one input.
+///
+/// `input` and `output` are counted in the units of the stage measured:
+/// bytes in and tokens out for the lexer, tokens in and nodes out for the
+/// parser.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchReport {
+    /// Number of timed runs.
+    pub iterations: u32,
+    /// Total time of the timed runs, excluding setup.
+    pub elapsed: Duration,
+    /// Size of the input of one run.
+    pub input: usize,
+    /// Number of items one run produced.
+    pub output: usize,
+    /// Per-rule counts from one extra, untimed run, in the order the rules
+    /// are tried.
+    pub rules: Vec<RuleStats>,
+    /// Allocations made by that run.
+    pub allocations: AllocStats,
+}
+
+impl BenchReport {
+    /// Returns the mean time of one run.
+    pub fn per_iteration(&self) -> Duration {
+        self.elapsed / self.iterations.max(1)
+    }
+
+    /// Returns input units processed per second.
+    pub fn input_per_sec(&self) -> f64 {
+        self.rate(self.input)
+    }
+
+    /// Returns items produced per second.
+    pub fn output_per_sec(&self) -> f64 {
+        self.rate(self.output)
+    }
+
+    /// Returns how many times faster this run was than `baseline` (below
+    /// 1.0 if slower), comparing time per input unit.
+    pub fn speedup_over(&self, baseline: &BenchReport) -> f64 {
+        let ours = self.input_per_sec();
+        if ours == 0.0 {
+            return 0.0;
+        }
+        ours / baseline.input_per_sec()
+    }
+
+    /// Returns the statistics of the rule called `name`.
+    pub fn rule(&self, name: &str) -> Option<&RuleStats> {
+        self.rules.iter().find(|rule| rule.name == name)
+    }
+
+    fn rate(&self, units: usize) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds == 0.0 {
+            0.0
+        } else {
+            units as f64 * f64::from(self.iterations) / seconds
+        }
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} iterations, {:?} each, {:.0} in/s, {:.0} out/s",
+            self.iterations,
+            self.per_iteration(),
+            self.input_per_sec(),
+            self.output_per_sec()
+        )?;
+        if self.allocations != AllocStats::default() {
+            writeln!(
+                f,
+                "  {} allocations, {} bytes",
+                self.allocations.allocations, self.allocations.bytes
+            )?;
+        }
+        for rule in &self.rules {
+            write!(
+                f,
+                "  {:<24} {:>10} tried {:>10} matched {:>8} backtracked",
+                rule.name, rule.attempts, rule.matches, rule.backtracks
+            )?;
+            if self.allocations != AllocStats::default() {
+                write!(f, " {:>8} allocations", rule.allocations.allocations)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rates_scale_with_iterations() {
+        let report = BenchReport {
+            iterations: 4,
+            elapsed: Duration::from_secs(2),
+            input: 100,
+            output: 10,
+            rules: vec![RuleStats {
+                attempts: 4,
+                matches: 1,
+                ..RuleStats::new("Number")
+            }],
+            allocations: AllocStats::default(),
+        };
+        assert_eq!(report.per_iteration(), Duration::from_millis(500));
+        assert_eq!(report.input_per_sec(), 200.0);
+        assert_eq!(report.output_per_sec(), 20.0);
+        assert_eq!(report.rule("Number").unwrap().hit_rate(), 0.25);
+
+        let slower = BenchReport {
+            elapsed: Duration::from_secs(4),
+            ..report.clone()
+        };
+        assert_eq!(report.speedup_over(&slower), 2.0);
+    }
+}
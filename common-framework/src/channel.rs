@@ -0,0 +1,58 @@
+use std::fmt;
+
+/// The channel a token is routed to, as in ANTLR.
+///
+/// Parsers read [`DEFAULT`](Self::DEFAULT). Tokens on any other channel,
+/// such as comments on [`HIDDEN`](Self::HIDDEN), are kept beside the token
+/// stream instead of being filtered out, so tools like doc extractors and
+/// formatters can still find them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Channel(u16);
+
+impl Channel {
+    /// The channel parsers read.
+    pub const DEFAULT: Channel = Channel(0);
+    /// Tokens kept out of the parser's way, e.g. comments and whitespace.
+    pub const HIDDEN: Channel = Channel(1);
+
+    /// Creates a user-defined channel. Ids 0 and 1 are
+    /// [`DEFAULT`](Self::DEFAULT) and [`HIDDEN`](Self::HIDDEN).
+    pub const fn new(id: u16) -> Self {
+        Channel(id)
+    }
+
+    /// Returns the channel's id.
+    pub const fn id(self) -> u16 {
+        self.0
+    }
+
+    /// Returns true for [`DEFAULT`](Self::DEFAULT).
+    pub const fn is_default(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Channel::DEFAULT => f.write_str("default"),
+            Channel::HIDDEN => f.write_str("hidden"),
+            Channel(id) => write!(f, "channel {}", id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_names() {
+        assert_eq!(Channel::default(), Channel::DEFAULT);
+        assert!(Channel::DEFAULT.is_default());
+        assert!(!Channel::HIDDEN.is_default());
+        assert_eq!(Channel::new(1), Channel::HIDDEN);
+        assert_eq!(Channel::HIDDEN.to_string(), "hidden");
+        assert_eq!(Channel::new(7).to_string(), "channel 7");
+    }
+}
@@ -1,4 +1,33 @@
 use crate::Position;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Identifies the context a [`Checkpoint`] was taken from.
+///
+/// Every context gets a fresh id when it is created, and stamps it into its
+/// checkpoints so that `restore` can catch checkpoints from another context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ContextId(u64);
+
+impl ContextId {
+    /// The id of checkpoints built by hand with [`Checkpoint::new`]; such
+    /// checkpoints are accepted by every context.
+    pub const UNOWNED: ContextId = ContextId(0);
+
+    /// Returns a new id, distinct from every id handed out before.
+    pub fn fresh() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        ContextId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Default for ContextId {
+    fn default() -> Self {
+        Self::fresh()
+    }
+}
 
 /// A checkpoint for saving and restoring parsing/lexing state.
 ///
@@ -11,12 +40,63 @@ pub struct Checkpoint {
     index: usize,
     /// The position in the source at this checkpoint.
     position: Position,
+    /// The context that created this checkpoint.
+    context: ContextId,
+    /// Optional name shown in trace output.
+    label: Option<&'static str>,
 }
 
 impl Checkpoint {
     /// Creates a new checkpoint with the given index and position.
     pub fn new(index: usize, position: Position) -> Self {
-        Self { index, position }
+        Self {
+            index,
+            position,
+            context: ContextId::UNOWNED,
+            label: None,
+        }
+    }
+
+    /// Marks the checkpoint as taken from the context identified by `context`.
+    pub fn with_context(mut self, context: ContextId) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Attaches a human-readable label, e.g. the rule that took it.
+    pub fn with_label(mut self, label: &'static str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    /// Returns the id of the context that created this checkpoint.
+    pub fn context(&self) -> ContextId {
+        self.context
+    }
+
+    /// Returns the label, if one was attached.
+    pub fn label(&self) -> Option<&'static str> {
+        self.label
+    }
+
+    /// Returns true if this checkpoint may be restored into the context
+    /// identified by `context`.
+    pub fn belongs_to(&self, context: ContextId) -> bool {
+        self.context == ContextId::UNOWNED || self.context == context
+    }
+
+    /// Debug-asserts that this checkpoint may be restored into `context`.
+    ///
+    /// Called by every `restore` implementation; restoring a checkpoint from
+    /// another context would otherwise silently corrupt the cursor.
+    #[track_caller]
+    pub fn debug_assert_context(&self, context: ContextId) {
+        debug_assert!(
+            self.belongs_to(context),
+            "{} restored into a different context ({:?})",
+            self,
+            context
+        );
     }
 
     /// Returns the index stored in this checkpoint.
@@ -43,3 +123,159 @@ impl Checkpoint {
         self.index
     }
 }
+
+impl fmt::Display for Checkpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.label {
+            Some(label) => write!(f, "checkpoint '{}'", label)?,
+            None => write!(f, "checkpoint")?,
+        }
+        write!(
+            f,
+            " @{} ({}:{}, {:?})",
+            self.index, self.position.line, self.position.column, self.context
+        )
+    }
+}
+
+/// The live [`PinnedCheckpoint`]s of one context.
+///
+/// A plain [`Checkpoint`] is `Copy`, so a context cannot tell whether anyone
+/// still holds it and has to keep history until an explicit `commit`.
+/// Pinned checkpoints are counted instead: streaming contexts keep history
+/// only from the lowest [`floor`](Self::floor) on, and release it as soon as
+/// the last pin below the committed point is dropped.
+#[derive(Debug, Clone, Default)]
+pub struct CheckpointPins {
+    // Pinned index -> number of live pins at that index.
+    live: Arc<Mutex<BTreeMap<usize, usize>>>,
+}
+
+impl CheckpointPins {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pins `checkpoint` until the returned handle (and all its clones) are
+    /// dropped.
+    pub fn pin(&self, checkpoint: Checkpoint) -> PinnedCheckpoint {
+        acquire(&self.live, checkpoint.index());
+        PinnedCheckpoint {
+            checkpoint,
+            live: Arc::clone(&self.live),
+        }
+    }
+
+    /// Returns the lowest pinned index, or `None` if nothing is pinned.
+    pub fn floor(&self) -> Option<usize> {
+        let live = self.live.lock().expect("checkpoint pins poisoned");
+        live.keys().next().copied()
+    }
+
+    /// Returns the number of live pins.
+    pub fn len(&self) -> usize {
+        let live = self.live.lock().expect("checkpoint pins poisoned");
+        live.values().sum()
+    }
+
+    /// Returns true if nothing is pinned.
+    pub fn is_empty(&self) -> bool {
+        self.floor().is_none()
+    }
+}
+
+fn acquire(live: &Mutex<BTreeMap<usize, usize>>, index: usize) {
+    *live
+        .lock()
+        .expect("checkpoint pins poisoned")
+        .entry(index)
+        .or_default() += 1;
+}
+
+/// A [`Checkpoint`] that keeps the history it needs alive while it exists.
+///
+/// Obtained from a streaming context's `pin_checkpoint`; restore it with
+/// `ctx.restore(pinned.checkpoint())`. Clones count as separate pins.
+#[derive(Debug)]
+pub struct PinnedCheckpoint {
+    checkpoint: Checkpoint,
+    live: Arc<Mutex<BTreeMap<usize, usize>>>,
+}
+
+impl PinnedCheckpoint {
+    /// Returns the pinned checkpoint.
+    pub fn checkpoint(&self) -> Checkpoint {
+        self.checkpoint
+    }
+}
+
+impl Clone for PinnedCheckpoint {
+    fn clone(&self) -> Self {
+        acquire(&self.live, self.checkpoint.index());
+        Self {
+            checkpoint: self.checkpoint,
+            live: Arc::clone(&self.live),
+        }
+    }
+}
+
+impl Drop for PinnedCheckpoint {
+    fn drop(&mut self) {
+        // Never panic in drop, even if another pin panicked mid-update.
+        let mut live = match self.live.lock() {
+            Ok(live) => live,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let index = self.checkpoint.index();
+        if let Some(count) = live.get_mut(&index) {
+            *count -= 1;
+            if *count == 0 {
+                live.remove(&index);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_ids_are_unique() {
+        assert_ne!(ContextId::fresh(), ContextId::fresh());
+        assert_ne!(ContextId::fresh(), ContextId::UNOWNED);
+    }
+
+    #[test]
+    fn test_checkpoint_ownership() {
+        let owner = ContextId::fresh();
+        let cp = Checkpoint::new(3, Position::new()).with_context(owner);
+        assert!(cp.belongs_to(owner));
+        assert!(!cp.belongs_to(ContextId::fresh()));
+        assert!(Checkpoint::new(3, Position::new()).belongs_to(owner));
+    }
+
+    #[test]
+    fn test_checkpoint_display() {
+        let cp = Checkpoint::new(7, Position::at(2, 4, 7)).with_label("args");
+        assert!(cp.to_string().starts_with("checkpoint 'args' @7 (2:4"));
+    }
+
+    #[test]
+    fn test_pins_track_the_lowest_live_checkpoint() {
+        let pins = CheckpointPins::new();
+        let late = pins.pin(Checkpoint::new(9, Position::new()));
+        let early = pins.pin(Checkpoint::new(4, Position::new()));
+        let copy = early.clone();
+        assert_eq!((pins.floor(), pins.len()), (Some(4), 3));
+
+        drop(early);
+        assert_eq!(pins.floor(), Some(4));
+        drop(copy);
+        assert_eq!(pins.floor(), Some(9));
+        assert_eq!(late.checkpoint().index(), 9);
+        drop(late);
+        assert!(pins.is_empty());
+    }
+}
@@ -0,0 +1,245 @@
+use crate::text_slice::TextSlice;
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// Longest text, in bytes, that [`CompactText`] stores inline.
+pub const INLINE_CAPACITY: usize = 23;
+
+/// Token text that keeps short strings inline and spills longer ones to a
+/// [`TextSlice`].
+///
+/// Most identifiers, keywords and numbers are only a few bytes long. Keeping
+/// them inline means a token owns its text without an allocation, and
+/// dropping or cloning it does not touch a reference count, so the token
+/// does not keep the lexer's whole input buffer alive. Text longer than
+/// [`INLINE_CAPACITY`] stays a shared slice.
+///
+/// Like [`TextSlice`], it derefs to `str` and compares and hashes by text.
+///
+/// # Examples
+/// ```
+/// use common_framework::{CompactText, TextSlice};
+/// use std::sync::Arc;
+///
+/// let buffer: Arc<str> = Arc::from("let answer = 42;");
+/// let ident = CompactText::from(TextSlice::new(buffer, 4, 10));
+/// assert!(ident.is_inline());
+/// assert_eq!(ident, "answer");
+/// ```
+#[derive(Clone)]
+pub struct CompactText(Repr);
+
+#[derive(Clone)]
+enum Repr {
+    Inline {
+        len: u8,
+        bytes: [u8; INLINE_CAPACITY],
+    },
+    Shared(TextSlice),
+}
+
+impl CompactText {
+    /// Creates compact text from `text`, copying it into a new shared buffer
+    /// if it does not fit inline.
+    pub fn new(text: &str) -> Self {
+        Self::inline(text)
+            .unwrap_or_else(|| Self(Repr::Shared(TextSlice::from_arc(Arc::from(text)))))
+    }
+
+    fn inline(text: &str) -> Option<Self> {
+        if text.len() > INLINE_CAPACITY {
+            return None;
+        }
+        let mut bytes = [0; INLINE_CAPACITY];
+        bytes[..text.len()].copy_from_slice(text.as_bytes());
+        Some(Self(Repr::Inline {
+            len: text.len() as u8,
+            bytes,
+        }))
+    }
+
+    /// Returns the text.
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            Repr::Inline { len, bytes } => std::str::from_utf8(&bytes[..*len as usize])
+                .expect("inline text is copied from a `str`"),
+            Repr::Shared(slice) => slice,
+        }
+    }
+
+    /// Returns `true` if the text is stored inline.
+    pub fn is_inline(&self) -> bool {
+        matches!(self.0, Repr::Inline { .. })
+    }
+
+    /// Returns the shared slice backing long text, or `None` for inline text.
+    pub fn as_slice(&self) -> Option<&TextSlice> {
+        match &self.0 {
+            Repr::Inline { .. } => None,
+            Repr::Shared(slice) => Some(slice),
+        }
+    }
+}
+
+impl From<TextSlice> for CompactText {
+    /// Copies short slices inline and keeps long ones as they are.
+    fn from(slice: TextSlice) -> Self {
+        Self::inline(&slice).unwrap_or(Self(Repr::Shared(slice)))
+    }
+}
+
+impl From<&str> for CompactText {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<String> for CompactText {
+    fn from(text: String) -> Self {
+        Self::inline(&text)
+            .unwrap_or_else(|| Self(Repr::Shared(TextSlice::from_arc(Arc::from(text)))))
+    }
+}
+
+impl Default for CompactText {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl fmt::Debug for CompactText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for CompactText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Deref for CompactText {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for CompactText {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl Borrow<str> for CompactText {
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl PartialEq<str> for CompactText {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for CompactText {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for CompactText {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<TextSlice> for CompactText {
+    fn eq(&self, other: &TextSlice) -> bool {
+        self.as_str() == other.deref()
+    }
+}
+
+impl PartialEq for CompactText {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for CompactText {}
+
+impl Hash for CompactText {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Must match `str`'s hash for `Borrow<str>` lookups.
+        self.as_str().hash(state);
+    }
+}
+
+impl PartialOrd for CompactText {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CompactText {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+/// Serializes as a plain string.
+#[cfg(feature = "serde")]
+impl serde::Serialize for CompactText {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CompactText {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_compact_text_inlines_short_text() {
+        let buffer: Arc<str> = Arc::from("let x_Variable = 1");
+        let ident = CompactText::from(TextSlice::new(buffer.clone(), 4, 14));
+        assert!(ident.is_inline());
+        assert_eq!(ident, "x_Variable");
+        // The token no longer holds on to the input buffer.
+        assert_eq!(Arc::strong_count(&buffer), 1);
+
+        let exact = "a".repeat(INLINE_CAPACITY);
+        assert!(CompactText::new(&exact).is_inline());
+        assert_eq!(CompactText::new(&exact), exact);
+        assert!(CompactText::new("变量名_甲").is_inline());
+    }
+
+    #[test]
+    fn test_compact_text_spills_long_text() {
+        let long = "a_rather_long_identifier_name";
+        let buffer: Arc<str> = Arc::from(long);
+        let text = CompactText::from(TextSlice::from_arc(buffer.clone()));
+        assert!(!text.is_inline());
+        assert!(Arc::ptr_eq(&text.as_slice().unwrap().buffer(), &buffer));
+        assert_eq!(text, CompactText::from(long.to_string()));
+
+        let names: HashSet<CompactText> = [text, CompactText::new("x")].into_iter().collect();
+        assert!(names.contains(long));
+        assert!(names.contains("x"));
+    }
+}
@@ -0,0 +1,300 @@
+use crate::Position;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// How serious a [`Diagnostic`] is. Ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Severity {
+    /// Extra information, e.g. where something was first defined.
+    Note,
+    /// Suspicious input that still lexes or parses.
+    Warning,
+    /// Input the grammar rejects.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// A message about a span of the input, reported by a lexing or parsing
+/// rule through its context's `report`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// Where the span starts.
+    pub start: Position,
+    /// Where the span ends; equal to `start` for a single point.
+    pub end: Position,
+}
+
+impl Diagnostic {
+    /// Creates a diagnostic about the `(start, end)` span.
+    pub fn new(severity: Severity, span: (Position, Position), message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            start: span.0,
+            end: span.1,
+        }
+    }
+
+    /// Creates a note about `span`.
+    pub fn note(span: (Position, Position), message: impl Into<String>) -> Self {
+        Self::new(Severity::Note, span, message)
+    }
+
+    /// Creates a warning about `span`.
+    pub fn warning(span: (Position, Position), message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, span, message)
+    }
+
+    /// Creates an error about `span`.
+    pub fn error(span: (Position, Position), message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, span, message)
+    }
+
+    /// Returns the `(start, end)` span.
+    pub fn span(&self) -> (Position, Position) {
+        (self.start, self.end)
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} at {}:{}",
+            self.severity, self.message, self.start.line, self.start.column
+        )
+    }
+}
+
+/// Where reported [`Diagnostic`]s go.
+///
+/// [`Diagnostics`] filters and caps them before they reach the sink. Use a
+/// `Vec<Diagnostic>` to collect them, [`CallbackSink`] to handle each as it
+/// arrives, or [`IgnoreSink`] to drop them.
+pub trait DiagnosticSink {
+    /// Receives one diagnostic.
+    fn emit(&mut self, diagnostic: Diagnostic);
+
+    /// Removes and returns the diagnostics held so far. Sinks that do not
+    /// keep them return nothing, the default.
+    fn take(&mut self) -> Vec<Diagnostic> {
+        Vec::new()
+    }
+}
+
+impl DiagnosticSink for Vec<Diagnostic> {
+    fn emit(&mut self, diagnostic: Diagnostic) {
+        self.push(diagnostic);
+    }
+
+    fn take(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(self)
+    }
+}
+
+/// A sink handing each diagnostic to a callback, e.g. to print it.
+pub struct CallbackSink<F>(pub F);
+
+impl<F> DiagnosticSink for CallbackSink<F>
+where
+    F: FnMut(Diagnostic),
+{
+    fn emit(&mut self, diagnostic: Diagnostic) {
+        (self.0)(diagnostic);
+    }
+}
+
+/// A sink dropping every diagnostic.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IgnoreSink;
+
+impl DiagnosticSink for IgnoreSink {
+    fn emit(&mut self, _diagnostic: Diagnostic) {}
+}
+
+/// The diagnostics configuration of a run: a [`DiagnosticSink`] with a
+/// minimum severity and a cap on how many diagnostics get through.
+///
+/// Clones share the sink and the count, so the lexer and parser of one
+/// pipeline can report into the same place under one cap. Contexts keep
+/// it in their state map; without one configured, they collect into a
+/// `Vec` with no filter. The cap keeps pathological inputs (one error per
+/// character, say) from using unbounded memory; diagnostics past it are
+/// only counted.
+#[derive(Clone)]
+pub struct Diagnostics {
+    inner: Arc<Mutex<DiagnosticsInner>>,
+}
+
+struct DiagnosticsInner {
+    sink: Box<dyn DiagnosticSink + Send>,
+    min_severity: Severity,
+    limit: Option<usize>,
+    emitted: usize,
+    suppressed: usize,
+}
+
+impl Diagnostics {
+    fn inner(&self) -> MutexGuard<'_, DiagnosticsInner> {
+        // A sink that panicked leaves the counts consistent.
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Sends diagnostics of every severity to `sink`, without a cap.
+    pub fn new(sink: impl DiagnosticSink + Send + 'static) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(DiagnosticsInner {
+                sink: Box::new(sink),
+                min_severity: Severity::Note,
+                limit: None,
+                emitted: 0,
+                suppressed: 0,
+            })),
+        }
+    }
+
+    /// Collects diagnostics for [`take`](Self::take).
+    pub fn collect() -> Self {
+        Self::new(Vec::new())
+    }
+
+    /// Hands each diagnostic to `callback` as it is reported.
+    pub fn callback<F>(callback: F) -> Self
+    where
+        F: FnMut(Diagnostic) + Send + 'static,
+    {
+        Self::new(CallbackSink(callback))
+    }
+
+    /// Drops every diagnostic.
+    pub fn ignore() -> Self {
+        Self::new(IgnoreSink)
+    }
+
+    /// Drops diagnostics less severe than `severity`.
+    pub fn with_min_severity(self, severity: Severity) -> Self {
+        self.inner().min_severity = severity;
+        self
+    }
+
+    /// Lets at most `max` diagnostics through; later ones are counted in
+    /// [`suppressed`](Self::suppressed).
+    pub fn with_limit(self, max: usize) -> Self {
+        self.inner().limit = Some(max);
+        self
+    }
+
+    /// Filters `diagnostic` and passes it on to the sink.
+    pub fn report(&self, diagnostic: Diagnostic) {
+        let mut inner = self.inner();
+        if diagnostic.severity < inner.min_severity {
+            return;
+        }
+        if inner.limit.is_some_and(|limit| inner.emitted >= limit) {
+            inner.suppressed += 1;
+            return;
+        }
+        inner.emitted += 1;
+        inner.sink.emit(diagnostic);
+    }
+
+    /// Removes and returns the diagnostics the sink holds.
+    pub fn take(&self) -> Vec<Diagnostic> {
+        self.inner().sink.take()
+    }
+
+    /// Returns how many diagnostics reached the sink.
+    pub fn emitted(&self) -> usize {
+        self.inner().emitted
+    }
+
+    /// Returns how many diagnostics were dropped for exceeding the cap.
+    pub fn suppressed(&self) -> usize {
+        self.inner().suppressed
+    }
+}
+
+impl Default for Diagnostics {
+    fn default() -> Self {
+        Self::collect()
+    }
+}
+
+impl fmt::Debug for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.inner();
+        f.debug_struct("Diagnostics")
+            .field("min_severity", &inner.min_severity)
+            .field("limit", &inner.limit)
+            .field("emitted", &inner.emitted)
+            .field("suppressed", &inner.suppressed)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnostic_display() {
+        let start = Position::at(2, 5, 12);
+        let end = Position::at(2, 12, 19);
+        let warning = Diagnostic::warning((start, end), "heading level above 6");
+        assert_eq!(warning.span(), (start, end));
+        assert_eq!(warning.to_string(), "warning: heading level above 6 at 2:5");
+    }
+
+    #[test]
+    fn test_severity_order() {
+        assert!(Severity::Note < Severity::Warning);
+        assert!(Severity::Warning < Severity::Error);
+    }
+
+    #[test]
+    fn test_diagnostics_filter_and_cap() {
+        let span = (Position::new(), Position::new());
+        let diagnostics = Diagnostics::collect()
+            .with_min_severity(Severity::Warning)
+            .with_limit(2);
+        let shared = diagnostics.clone();
+
+        diagnostics.report(Diagnostic::note(span, "ignored"));
+        diagnostics.report(Diagnostic::warning(span, "first"));
+        shared.report(Diagnostic::error(span, "second"));
+        shared.report(Diagnostic::error(span, "over the cap"));
+
+        let taken = diagnostics.take();
+        assert_eq!(taken.len(), 2);
+        assert_eq!(taken[1].message, "second");
+        assert_eq!(shared.emitted(), 2);
+        assert_eq!(shared.suppressed(), 1);
+    }
+
+    #[test]
+    fn test_diagnostics_callback() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        let diagnostics =
+            Diagnostics::callback(move |d: Diagnostic| log.lock().unwrap().push(d.message));
+        diagnostics.report(Diagnostic::warning(
+            (Position::new(), Position::new()),
+            "hi",
+        ));
+        assert_eq!(*seen.lock().unwrap(), vec!["hi".to_string()]);
+        assert!(diagnostics.take().is_empty());
+    }
+}
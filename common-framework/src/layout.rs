@@ -0,0 +1,142 @@
+//! Positions for hand-built token fixtures.
+
+use crate::Position;
+
+/// Lays out tokens one after another and hands out their start positions,
+/// so test fixtures need not compute lines, columns and offsets by hand.
+///
+/// ```
+/// use common_framework::{PositionAssigner, Position};
+///
+/// // let x =
+/// //   1
+/// let mut layout = PositionAssigner::new();
+/// let let_kw = layout.token("let");
+/// layout.skip(" ");
+/// let x = layout.token("x");
+/// layout.skip(" ");
+/// let eq = layout.token("=");
+/// layout.skip("\n  ");
+/// let one = layout.token("1");
+/// assert_eq!(let_kw, Position::new());
+/// assert_eq!(x, Position::at(1, 5, 4));
+/// assert_eq!(eq, Position::at(1, 7, 6));
+/// assert_eq!(one, Position::at(2, 3, 10));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PositionAssigner {
+    position: Position,
+}
+
+impl PositionAssigner {
+    /// Starts at the beginning of the input.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts at `position`, e.g. to lay out a fragment of a larger input.
+    pub fn starting_at(position: Position) -> Self {
+        Self { position }
+    }
+
+    /// Returns where the next token starts.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Returns the start of a token with the text `text` and moves past it.
+    pub fn token(&mut self, text: &str) -> Position {
+        let start = self.position;
+        self.skip(text);
+        start
+    }
+
+    /// Returns the start of a single-line token `len` ASCII characters long
+    /// and moves past it.
+    pub fn token_len(&mut self, len: usize) -> Position {
+        let start = self.position;
+        self.position.column += len;
+        self.position.offset += len;
+        start
+    }
+
+    /// Moves past `text` without a token, e.g. whitespace or a comment.
+    pub fn skip(&mut self, text: &str) -> &mut Self {
+        for ch in text.chars() {
+            self.position.offset += ch.len_utf8();
+            if ch == '\n' {
+                self.position.line += 1;
+                self.position.column = 1;
+            } else {
+                self.position.column += 1;
+            }
+        }
+        self
+    }
+
+    /// Moves to the start of the next line.
+    pub fn newline(&mut self) -> &mut Self {
+        self.skip("\n")
+    }
+}
+
+/// Returns the start positions of tokens of the given lengths, laid out
+/// back to back on one line: `pos_seq(&[2, 1, 2])` for `12+34`.
+///
+/// Use [`positions_of`] when the fixture has whitespace between tokens.
+pub fn pos_seq(lengths: &[usize]) -> Vec<Position> {
+    let mut layout = PositionAssigner::new();
+    lengths.iter().map(|&len| layout.token_len(len)).collect()
+}
+
+/// Returns the start position of every whitespace-separated word of
+/// `source`: `positions_of("12 +\n 34")` gives one position each for `12`,
+/// `+` and `34`.
+///
+/// Write the fixture's source with spaces between the tokens and use the
+/// positions in the same order.
+pub fn positions_of(source: &str) -> Vec<Position> {
+    let mut layout = PositionAssigner::new();
+    let mut positions = Vec::new();
+    let mut in_word = false;
+    for ch in source.chars() {
+        let word = !ch.is_whitespace();
+        if word && !in_word {
+            positions.push(layout.position());
+        }
+        in_word = word;
+        let mut buf = [0; 4];
+        layout.skip(ch.encode_utf8(&mut buf));
+    }
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pos_seq_lays_out_back_to_back() {
+        assert_eq!(
+            pos_seq(&[2, 1, 2]),
+            vec![
+                Position::at(1, 1, 0),
+                Position::at(1, 3, 2),
+                Position::at(1, 4, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_positions_of_words() {
+        assert_eq!(
+            positions_of("12 +\n  é 3"),
+            vec![
+                Position::at(1, 1, 0),
+                Position::at(1, 4, 3),
+                Position::at(2, 3, 7),
+                Position::at(2, 5, 10)
+            ]
+        );
+    }
+}
@@ -1,20 +1,71 @@
 //! Common Framework
 //!
 //! Shared building blocks for the lexer and parser frameworks:
-//!  - [`Position`]: consistent line/column/offset markers.
-//!  - [`Checkpoint`]: progress snapshots used for backtracking and lazy evaluation.
+//!  - [`Position`]: consistent line/column/offset markers, laid out for test fixtures by
+//!    [`PositionAssigner`], [`pos_seq`] and [`positions_of`].
+//!  - [`CompactPos`] / [`LineIndex`]: 4-byte offsets resolved to positions on demand.
+//!  - [`SourceText`]: an input shared with its line table, mapping offsets to lines and columns and back.
+//!  - [`BenchReport`] / [`RuleStats`]: throughput and per-rule counts from the `bench_support` helpers,
+//!    with [`AllocStats`] filled in by `CountingAllocator` (`alloc-stats` feature).
+//!  - [`Diagnostic`] / [`Severity`]: warnings and errors reported by rules alongside their output,
+//!    routed through [`Diagnostics`] to a [`DiagnosticSink`] with severity filtering and a cap.
+//!  - [`Channel`]: the token channel a lexing rule routes its tokens to; parsers read the default one.
+//!  - [`Checkpoint`]: progress snapshots used for backtracking and lazy evaluation;
+//!    [`PinnedCheckpoint`]s are reference-counted so streaming contexts can prune around them.
 //!  - [`TextSlice`]: reference-counted immutable string slices.
-//!  - [`StreamingSignal`] / [`Inbound`] / [`Outbound`]: protocol primitives for real-time/incremental pipelines.
+//!  - [`CompactText`]: token text stored inline when short, spilling to a [`TextSlice`].
+//!  - [`RuleId`]: stable identifiers for lexing/parsing rules.
+//!  - [`RuleGroups`]: named rule groups that can be toggled at runtime.
+//!  - [`ScopeStack`]: nested scopes with shadowing, for symbol tables.
+//!  - [`SourceMap`]: the files of one compilation, each with its own global offset range.
+//!  - [`StateMap`]: per-context user state keyed by type.
+//!  - [`StreamingSignal`] / [`Inbound`] / [`Outbound`]: protocol primitives for real-time/incremental pipelines,
+//!    with [`Feedback`] payloads flowing from parser back to lexer.
+//!  - [`TriviaPolicy`] / [`TriviaMode`]: which tokens a lexer may drop and a parser skips between rules,
+//!    and per-rule overrides.
 //!
 //! These types are lightweight and do not depend on concrete lexer/parser implementations,
 //! so they can be reused in custom projects as well.
 
+#[cfg(feature = "alloc-stats")]
+pub mod alloc_stats;
+pub mod bench_report;
+pub mod channel;
 pub mod checkpoint;
+pub mod compact_text;
+pub mod diagnostic;
+pub mod layout;
+pub mod line_index;
 pub mod position;
+pub mod rule_group;
+pub mod rule_id;
+pub mod scope;
+pub mod source_map;
+pub mod source_text;
+pub mod state;
 pub mod streaming;
 pub mod text_slice;
+pub mod trivia;
 
-pub use checkpoint::Checkpoint;
+#[cfg(feature = "alloc-stats")]
+pub use alloc_stats::CountingAllocator;
+pub use bench_report::{AllocStats, BenchReport, RuleStats};
+pub use channel::Channel;
+pub use checkpoint::{Checkpoint, CheckpointPins, ContextId, PinnedCheckpoint};
+pub use compact_text::CompactText;
+pub use diagnostic::{CallbackSink, Diagnostic, DiagnosticSink, Diagnostics, IgnoreSink, Severity};
+pub use layout::{pos_seq, positions_of, PositionAssigner};
+pub use line_index::{CompactPos, LineIndex};
 pub use position::Position;
-pub use streaming::{Inbound, Outbound, StreamingSignal};
+pub use rule_group::RuleGroups;
+pub use rule_id::{short_type_name, RuleId};
+pub use scope::ScopeStack;
+pub use source_map::{SourceFile, SourceId, SourceMap};
+pub use source_text::SourceText;
+pub use state::StateMap;
+pub use streaming::{Feedback, Inbound, Outbound, Progress, StreamingSignal};
 pub use text_slice::TextSlice;
+pub use trivia::{TriviaMode, TriviaPolicy};
+
+/// This crate's version, as recorded in pipeline run metadata.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,170 @@
+use crate::position::Position;
+use std::sync::Arc;
+
+/// A source position stored as a 4-byte byte offset.
+///
+/// [`Position`] carries line, column and offset, three `usize`s, inside
+/// every token. Tokens that store a `CompactPos` instead stay small, and a
+/// [`LineIndex`] built once per input turns the offset back into a full
+/// `Position` when a diagnostic needs one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompactPos(u32);
+
+impl CompactPos {
+    /// Creates a position at byte `offset`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` does not fit in a `u32` (inputs of 4 GiB or more).
+    pub fn new(offset: usize) -> Self {
+        Self(u32::try_from(offset).expect("CompactPos: offset exceeds u32::MAX"))
+    }
+
+    /// Returns the byte offset.
+    pub fn offset(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl From<Position> for CompactPos {
+    fn from(position: Position) -> Self {
+        Self::new(position.offset)
+    }
+}
+
+/// Line start table for one input, reconstructing line and column from a
+/// byte offset on demand.
+///
+/// Lookups binary-search the line starts and count characters from the start
+/// of the line, so columns match the ones the lexer cursor reports.
+///
+/// # Examples
+/// ```
+/// use common_framework::{CompactPos, LineIndex, Position};
+///
+/// let index = LineIndex::new("let a = 1;\nlet b = 2;");
+/// assert_eq!(index.line_count(), 2);
+/// assert_eq!(index.position(CompactPos::new(15)), Position::at(2, 5, 15));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    pub(crate) text: Arc<str>,
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    /// Indexes the lines of `text`.
+    pub fn new(text: impl Into<Arc<str>>) -> Self {
+        let text = text.into();
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        Self { text, line_starts }
+    }
+
+    /// Returns the indexed text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the number of lines. Text ending in a newline has an empty
+    /// last line.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Returns the byte offset where the 1-indexed `line` starts.
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        self.line_starts.get(line.checked_sub(1)?).copied()
+    }
+
+    /// Returns the full position of `pos`.
+    pub fn position(&self, pos: CompactPos) -> Position {
+        self.position_at(pos.offset())
+    }
+
+    /// Returns the full position of byte `offset`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` is past the end of the text or not on a character
+    /// boundary.
+    pub fn position_at(&self, offset: usize) -> Position {
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let line_start = self.line_starts[line - 1];
+        Position {
+            line,
+            column: self.text[line_start..offset].chars().count() + 1,
+            offset,
+        }
+    }
+
+    /// Returns the byte offset of the 1-indexed `line` and `column`, or
+    /// `None` if the line does not exist or is shorter than `column - 1`
+    /// characters.
+    pub fn offset_at(&self, line: usize, column: usize) -> Option<usize> {
+        let start = self.line_start(line)?;
+        let end = self
+            .line_start(line + 1)
+            .map_or(self.text.len(), |next| next - 1);
+        let line_text = &self.text[start..end];
+        let skip = column.checked_sub(1)?;
+        line_text
+            .char_indices()
+            .map(|(idx, _)| idx)
+            .chain(std::iter::once(line_text.len()))
+            .nth(skip)
+            .map(|idx| start + idx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_index_matches_cursor_positions() {
+        let index = LineIndex::new("ab\n变量 x\n\nend\n");
+        assert_eq!(index.position_at(0), Position::new());
+        assert_eq!(index.position_at(2), Position::at(1, 3, 2));
+        assert_eq!(index.position_at(3), Position::at(2, 1, 3));
+        // Columns count characters, not bytes.
+        assert_eq!(index.position_at(10), Position::at(2, 4, 10));
+        assert_eq!(index.position_at(12), Position::at(3, 1, 12));
+        assert_eq!(index.position_at(17), Position::at(5, 1, 17));
+        assert_eq!(index.line_count(), 5);
+        assert_eq!(index.line_start(4), Some(13));
+        assert_eq!(index.line_start(0), None);
+    }
+
+    #[test]
+    fn test_offset_at_inverts_position_at() {
+        let index = LineIndex::new(
+            "ab
+变量 x
+
+end",
+        );
+        for offset in [0, 2, 3, 6, 10, 12, 13, 16] {
+            let position = index.position_at(offset);
+            assert_eq!(
+                index.offset_at(position.line, position.column),
+                Some(offset)
+            );
+        }
+        assert_eq!(index.offset_at(1, 4), None);
+        assert_eq!(index.offset_at(1, 0), None);
+        assert_eq!(index.offset_at(5, 1), None);
+    }
+
+    #[test]
+    fn test_compact_pos_round_trip() {
+        let index = LineIndex::new("one\ntwo three");
+        let position = Position::at(2, 5, 8);
+        let compact = CompactPos::from(position);
+        assert_eq!(compact.offset(), 8);
+        assert_eq!(index.position(compact), position);
+        assert_eq!(std::mem::size_of::<CompactPos>(), 4);
+    }
+}
@@ -3,6 +3,7 @@
 /// This is used by both the lexer and parser frameworks to track
 /// the location of tokens and AST nodes in the source code.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position {
     /// Line number (1-indexed)
     pub line: usize,
@@ -30,6 +31,23 @@ impl Position {
             offset,
         }
     }
+
+    /// Converts a position relative to a part of the input that starts at
+    /// `base` into a position relative to the whole input.
+    ///
+    /// Used when several sources are lexed one after another and reported
+    /// as one stream.
+    pub fn rebase(self, base: Position) -> Self {
+        Self {
+            line: base.line + self.line - 1,
+            column: if self.line == 1 {
+                base.column + self.column - 1
+            } else {
+                self.column
+            },
+            offset: base.offset + self.offset,
+        }
+    }
 }
 
 impl Default for Position {
@@ -64,6 +82,14 @@ mod tests {
         assert_eq!(pos, Position::new());
     }
 
+    #[test]
+    fn test_position_rebase() {
+        let base = Position::at(3, 5, 20);
+        assert_eq!(Position::new().rebase(base), base);
+        assert_eq!(Position::at(1, 4, 3).rebase(base), Position::at(3, 8, 23));
+        assert_eq!(Position::at(2, 4, 9).rebase(base), Position::at(4, 4, 29));
+    }
+
     #[test]
     fn test_position_equality() {
         let pos1 = Position::at(1, 2, 3);
@@ -0,0 +1,96 @@
+/// Named groups of rules that can be switched on and off together.
+///
+/// Lexers and parsers record which group (if any) each rule belongs to;
+/// rules outside any group are always enabled. Toggling a group bumps
+/// [`version`](Self::version) so owners can rebuild cached dispatch tables
+/// lazily.
+#[derive(Debug, Clone, Default)]
+pub struct RuleGroups {
+    names: Vec<String>,
+    enabled: Vec<bool>,
+    version: u64,
+}
+
+impl RuleGroups {
+    /// Creates an empty set of groups.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the index of the group called `name`, registering it
+    /// (enabled) if it does not exist yet.
+    pub fn get_or_insert(&mut self, name: &str) -> usize {
+        if let Some(index) = self.index_of(name) {
+            return index;
+        }
+        self.names.push(name.to_string());
+        self.enabled.push(true);
+        self.version += 1;
+        self.names.len() - 1
+    }
+
+    /// Returns the index of the group called `name`.
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|group| group == name)
+    }
+
+    /// Enables or disables the group called `name`. Returns false if there
+    /// is no such group.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        let Some(index) = self.index_of(name) else {
+            return false;
+        };
+        if self.enabled[index] != enabled {
+            self.enabled[index] = enabled;
+            self.version += 1;
+        }
+        true
+    }
+
+    /// Returns whether the group called `name` is enabled, or `None` if
+    /// there is no such group.
+    pub fn is_enabled(&self, name: &str) -> Option<bool> {
+        self.index_of(name).map(|index| self.enabled[index])
+    }
+
+    /// Returns whether a rule in group `group` (`None` for ungrouped rules)
+    /// should be tried.
+    pub fn is_active(&self, group: Option<usize>) -> bool {
+        group.is_none_or(|index| self.enabled[index])
+    }
+
+    /// Iterates over all groups as `(name, enabled)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, bool)> + '_ {
+        self.names
+            .iter()
+            .map(String::as_str)
+            .zip(self.enabled.iter().copied())
+    }
+
+    /// Returns a counter that changes whenever a group is added or toggled.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_groups_toggle() {
+        let mut groups = RuleGroups::new();
+        let gfm = groups.get_or_insert("gfm");
+        assert_eq!(groups.get_or_insert("gfm"), gfm);
+        assert!(groups.is_active(Some(gfm)));
+
+        let version = groups.version();
+        assert!(groups.set_enabled("gfm", false));
+        assert!(!groups.is_active(Some(gfm)));
+        assert!(groups.is_active(None));
+        assert_ne!(groups.version(), version);
+
+        assert!(!groups.set_enabled("missing", true));
+        assert_eq!(groups.is_enabled("missing"), None);
+    }
+}
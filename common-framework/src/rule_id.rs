@@ -0,0 +1,64 @@
+use std::fmt;
+
+/// A stable identifier for a lexing or parsing rule.
+///
+/// Derived from the rule's name with FNV-1a, so the same rule gets the same
+/// id across runs and builds, which makes ids usable as keys in persisted
+/// statistics or trace filters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RuleId(u64);
+
+impl RuleId {
+    /// Computes the id for a rule called `name`.
+    pub const fn of(name: &str) -> Self {
+        let bytes = name.as_bytes();
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut i = 0;
+        while i < bytes.len() {
+            hash ^= bytes[i] as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+            i += 1;
+        }
+        RuleId(hash)
+    }
+
+    /// Returns the raw id value.
+    pub fn value(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for RuleId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:016x}", self.0)
+    }
+}
+
+/// Returns the name of `T` without its module path or generic arguments,
+/// e.g. `NumberRule` for `my_lang::rules::NumberRule<Ctx>`.
+///
+/// Used as the default rule name.
+pub fn short_type_name<T: ?Sized>() -> &'static str {
+    let full = std::any::type_name::<T>();
+    let base = full.split('<').next().unwrap_or(full);
+    base.rsplit("::").next().unwrap_or(base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct SampleRule<T>(T);
+
+    #[test]
+    fn test_rule_id_is_deterministic() {
+        assert_eq!(RuleId::of("NumberRule"), RuleId::of("NumberRule"));
+        assert_ne!(RuleId::of("NumberRule"), RuleId::of("StringRule"));
+    }
+
+    #[test]
+    fn test_short_type_name() {
+        assert_eq!(short_type_name::<SampleRule<u8>>(), "SampleRule");
+        assert_eq!(short_type_name::<u8>(), "u8");
+    }
+}
@@ -0,0 +1,134 @@
+/// A stack of nested scopes mapping names to values, e.g. for symbol tables.
+///
+/// Lookups search from the innermost scope outwards, so inner bindings
+/// shadow outer ones. The outermost (global) scope is never exited.
+#[derive(Debug, Clone)]
+pub struct ScopeStack<K, V> {
+    // All bindings, innermost scope last.
+    entries: Vec<(K, V)>,
+    // Index into `entries` where each scope above the global one starts.
+    starts: Vec<usize>,
+}
+
+impl<K, V> ScopeStack<K, V>
+where
+    K: PartialEq,
+{
+    /// Creates a stack holding only the empty global scope.
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            starts: Vec::new(),
+        }
+    }
+
+    /// Opens a new innermost scope.
+    pub fn enter(&mut self) {
+        self.starts.push(self.entries.len());
+    }
+
+    /// Closes the innermost scope and returns its bindings in insertion
+    /// order, or `None` if only the global scope is open.
+    pub fn exit(&mut self) -> Option<Vec<(K, V)>> {
+        let start = self.starts.pop()?;
+        Some(self.entries.split_off(start))
+    }
+
+    /// Binds `key` in the innermost scope. Earlier bindings of the same key
+    /// in this or outer scopes are shadowed, not replaced.
+    pub fn insert(&mut self, key: K, value: V) {
+        self.entries.push((key, value));
+    }
+
+    /// Returns the innermost binding of `key`.
+    pub fn lookup(&self, key: &K) -> Option<&V> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    /// Returns the binding of `key` in the innermost scope only, e.g. to
+    /// report redefinitions.
+    pub fn lookup_local(&self, key: &K) -> Option<&V> {
+        self.entries[self.current_start()..]
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    /// Returns the number of open scopes, including the global one.
+    pub fn depth(&self) -> usize {
+        self.starts.len() + 1
+    }
+
+    /// Returns the bindings of the innermost scope in insertion order.
+    pub fn current(&self) -> &[(K, V)] {
+        &self.entries[self.current_start()..]
+    }
+
+    /// Removes the most recent binding of the innermost scope. Used to undo
+    /// an [`insert`](Self::insert) when backtracking.
+    pub fn pop_binding(&mut self) -> Option<(K, V)> {
+        if self.entries.len() == self.current_start() {
+            return None;
+        }
+        self.entries.pop()
+    }
+
+    /// Reopens a scope with `bindings`. Used to undo an
+    /// [`exit`](Self::exit) when backtracking.
+    pub fn reenter(&mut self, bindings: Vec<(K, V)>) {
+        self.enter();
+        self.entries.extend(bindings);
+    }
+
+    fn current_start(&self) -> usize {
+        self.starts.last().copied().unwrap_or(0)
+    }
+}
+
+impl<K, V> Default for ScopeStack<K, V>
+where
+    K: PartialEq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_shadowing() {
+        let mut scopes = ScopeStack::new();
+        scopes.insert("x", 1);
+        scopes.enter();
+        assert_eq!(scopes.lookup(&"x"), Some(&1));
+        assert_eq!(scopes.lookup_local(&"x"), None);
+
+        scopes.insert("x", 2);
+        assert_eq!(scopes.lookup(&"x"), Some(&2));
+        assert_eq!(scopes.depth(), 2);
+
+        assert_eq!(scopes.exit(), Some(vec![("x", 2)]));
+        assert_eq!(scopes.lookup(&"x"), Some(&1));
+        assert_eq!(scopes.exit(), None);
+    }
+
+    #[test]
+    fn test_scope_undo() {
+        let mut scopes = ScopeStack::new();
+        scopes.enter();
+        scopes.insert("y", 1);
+        let bindings = scopes.exit().unwrap();
+        scopes.reenter(bindings);
+        assert_eq!(scopes.lookup_local(&"y"), Some(&1));
+        assert_eq!(scopes.pop_binding(), Some(("y", 1)));
+        assert_eq!(scopes.pop_binding(), None);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::line_index::LineIndex;
+use crate::position::Position;
+use std::sync::Arc;
+
+/// Identifies a file in a [`SourceMap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SourceId(usize);
+
+impl SourceId {
+    /// Returns the index of the file in the order it was added.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// A file registered in a [`SourceMap`].
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    name: String,
+    start: usize,
+    lines: LineIndex,
+}
+
+impl SourceFile {
+    /// Returns the name the file was added under, e.g. its path.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the file's text.
+    pub fn text(&self) -> &str {
+        self.lines.text()
+    }
+
+    /// Returns the global offset of the file's first byte.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the global offset just past the file's last byte.
+    pub fn end(&self) -> usize {
+        self.start + self.text().len()
+    }
+
+    /// Converts a position within this file into a global one: the offset
+    /// is shifted into the file's range, line and column are kept.
+    pub fn to_global(&self, local: Position) -> Position {
+        Position {
+            offset: self.start + local.offset,
+            ..local
+        }
+    }
+
+    /// Returns the line index of the file.
+    pub fn lines(&self) -> &LineIndex {
+        &self.lines
+    }
+}
+
+/// The files that make up one compilation, e.g. a main file and everything
+/// it includes.
+///
+/// Each file gets its own range of global offsets, so a token's
+/// [`Position::offset`] alone identifies the file it came from even after
+/// tokens of several files are spliced into one stream.
+/// [`lookup`](Self::lookup) maps a global offset back to the file and the
+/// position inside it.
+///
+/// # Examples
+/// ```
+/// use common_framework::{Position, SourceMap};
+///
+/// let mut sources = SourceMap::new();
+/// let main = sources.add("main.c", "#include \"a.h\"\nint x;");
+/// let header = sources.add("a.h", "int y;");
+/// let global = sources.file(header).to_global(Position::at(1, 5, 4));
+/// assert_eq!(sources.lookup(global.offset), Some((header, Position::at(1, 5, 4))));
+/// assert_eq!(sources.lookup(15).map(|(id, _)| id), Some(main));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    /// Creates an empty source map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file and returns its id.
+    pub fn add(&mut self, name: impl Into<String>, text: impl Into<Arc<str>>) -> SourceId {
+        // Leave a one-byte gap so a file's end offset is not the next
+        // file's start.
+        let start = self.files.last().map_or(0, |file| file.end() + 1);
+        self.files.push(SourceFile {
+            name: name.into(),
+            start,
+            lines: LineIndex::new(text),
+        });
+        SourceId(self.files.len() - 1)
+    }
+
+    /// Returns the file with the given id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id belongs to another source map.
+    pub fn file(&self, id: SourceId) -> &SourceFile {
+        &self.files[id.0]
+    }
+
+    /// Returns the id of the first file added under `name`.
+    pub fn find(&self, name: &str) -> Option<SourceId> {
+        self.files
+            .iter()
+            .position(|file| file.name == name)
+            .map(SourceId)
+    }
+
+    /// Returns all files with their ids.
+    pub fn files(&self) -> impl Iterator<Item = (SourceId, &SourceFile)> {
+        self.files
+            .iter()
+            .enumerate()
+            .map(|(index, file)| (SourceId(index), file))
+    }
+
+    /// Returns the file containing the global `offset` and the position
+    /// inside that file. A file's end offset belongs to the file.
+    pub fn lookup(&self, offset: usize) -> Option<(SourceId, Position)> {
+        let index = self.files.partition_point(|file| file.start <= offset);
+        let file = self.files.get(index.checked_sub(1)?)?;
+        (offset <= file.end()).then(|| {
+            (
+                SourceId(index - 1),
+                file.lines.position_at(offset - file.start),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_map_lookup_across_files() {
+        let mut sources = SourceMap::new();
+        let a = sources.add("a", "one\ntwo");
+        let b = sources.add("b", "");
+        let c = sources.add("c", "x\ny");
+        assert_eq!(sources.file(b).start(), 8);
+        assert_eq!(sources.file(c).start(), 9);
+
+        assert_eq!(sources.lookup(5), Some((a, Position::at(2, 2, 5))));
+        assert_eq!(sources.lookup(7), Some((a, Position::at(2, 4, 7))));
+        assert_eq!(sources.lookup(8), Some((b, Position::new())));
+        assert_eq!(sources.lookup(11), Some((c, Position::at(2, 1, 2))));
+        assert_eq!(sources.lookup(13), None);
+        assert_eq!(sources.find("c"), Some(c));
+    }
+}
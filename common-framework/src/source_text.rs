@@ -0,0 +1,102 @@
+use crate::line_index::LineIndex;
+use crate::position::Position;
+use std::sync::Arc;
+
+/// An input text with its line table, shared by everything that needs to
+/// convert between byte offsets and line/column positions.
+///
+/// Build it once per input and hand clones to the lexer, parser and
+/// diagnostics instead of each of them recomputing line starts. Lexers
+/// created from a `SourceText` (see `Lexer::from_source` in the lexer
+/// framework) can skip per-character line tracking and leave positions to
+/// [`resolve`](Self::resolve).
+///
+/// # Examples
+/// ```
+/// use common_framework::{Position, SourceText};
+///
+/// let source = SourceText::new("let a = 1;\nlet b = 2;");
+/// assert_eq!(source.position_at(15), Some(Position::at(2, 5, 15)));
+/// assert_eq!(source.offset_at(2, 5), Some(15));
+/// assert_eq!(source.offset_at(3, 1), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SourceText {
+    index: Arc<LineIndex>,
+}
+
+impl SourceText {
+    /// Indexes the lines of `text`.
+    pub fn new(text: impl Into<Arc<str>>) -> Self {
+        Self::from_index(Arc::new(LineIndex::new(text)))
+    }
+
+    /// Wraps an existing line index.
+    pub fn from_index(index: Arc<LineIndex>) -> Self {
+        Self { index }
+    }
+
+    /// Returns the text.
+    pub fn text(&self) -> &str {
+        self.index.text()
+    }
+
+    /// Returns the text's shared buffer, e.g. to build a cursor without
+    /// copying.
+    pub fn shared_text(&self) -> Arc<str> {
+        self.index.text.clone()
+    }
+
+    /// Returns the length of the text in bytes.
+    pub fn len(&self) -> usize {
+        self.text().len()
+    }
+
+    /// Returns true if the text is empty.
+    pub fn is_empty(&self) -> bool {
+        self.text().is_empty()
+    }
+
+    /// Returns the line index, e.g. for `Located` tokens.
+    pub fn line_index(&self) -> &Arc<LineIndex> {
+        &self.index
+    }
+
+    /// Returns the position of byte `offset`, or `None` if it is past the
+    /// end of the text or inside a character.
+    pub fn position_at(&self, offset: usize) -> Option<Position> {
+        self.text()
+            .is_char_boundary(offset)
+            .then(|| self.index.position_at(offset))
+    }
+
+    /// Returns the byte offset of the 1-indexed `line` and `column`, or
+    /// `None` if there is no such place. The column just past the last
+    /// character of a line is valid.
+    pub fn offset_at(&self, line: usize, column: usize) -> Option<usize> {
+        self.index.offset_at(line, column)
+    }
+
+    /// Fills in the line and column of `position` from its offset, for
+    /// positions reported by a lexer that does not track lines.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the offset is not a valid position in the text.
+    pub fn resolve(&self, position: Position) -> Position {
+        self.position_at(position.offset)
+            .expect("SourceText: offset is outside the text")
+    }
+}
+
+impl From<&str> for SourceText {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<String> for SourceText {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
@@ -0,0 +1,102 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A map holding at most one value of each type.
+///
+/// Contexts carry one so rules can share ad-hoc state ("saw a shebang",
+/// the current indentation level, feature flags) without a custom context
+/// type. Each piece of state is keyed by its type, so wrapping it in a
+/// dedicated newtype keeps unrelated rules from colliding. Values must be
+/// `Send` so contexts can move to another thread.
+#[derive(Default)]
+pub struct StateMap {
+    values: HashMap<TypeId, Box<dyn Any + Send>>,
+}
+
+impl StateMap {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the `T` in the map, inserting `T::default()` first if there
+    /// is none.
+    pub fn get_or_default<T: Default + Send + 'static>(&mut self) -> &mut T {
+        self.values
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(T::default()))
+            .downcast_mut()
+            .expect("state map entries are keyed by their type")
+    }
+
+    /// Returns the `T` in the map, if any.
+    pub fn get<T: 'static>(&self) -> Option<&T> {
+        self.values.get(&TypeId::of::<T>())?.downcast_ref()
+    }
+
+    /// Returns the `T` in the map mutably, if any.
+    pub fn get_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        self.values.get_mut(&TypeId::of::<T>())?.downcast_mut()
+    }
+
+    /// Stores `value`, returning the `T` it replaces.
+    pub fn insert<T: Any + Send>(&mut self, value: T) -> Option<T> {
+        let old = self.values.insert(TypeId::of::<T>(), Box::new(value))?;
+        old.downcast().ok().map(|old| *old)
+    }
+
+    /// Removes and returns the `T` in the map.
+    pub fn remove<T: 'static>(&mut self) -> Option<T> {
+        let old = self.values.remove(&TypeId::of::<T>())?;
+        old.downcast().ok().map(|old| *old)
+    }
+
+    /// Returns the number of stored values.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if no values are stored.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl fmt::Debug for StateMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StateMap")
+            .field("len", &self.values.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Indent(usize);
+
+    #[test]
+    fn test_state_map_by_type() {
+        let mut state = StateMap::new();
+        state.get_or_default::<Indent>().0 = 4;
+        *state.get_or_default::<bool>() = true;
+
+        assert_eq!(state.get::<Indent>(), Some(&Indent(4)));
+        assert_eq!(state.get::<bool>(), Some(&true));
+        assert_eq!(state.len(), 2);
+
+        assert_eq!(state.insert(Indent(8)), Some(Indent(4)));
+        assert_eq!(state.remove::<Indent>(), Some(Indent(8)));
+        assert_eq!(state.get::<Indent>(), None);
+    }
+
+    #[test]
+    fn test_state_map_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<StateMap>();
+        assert_send::<crate::Diagnostics>();
+    }
+}
@@ -6,6 +6,10 @@
 //! and status updates (produced ASTs, finished, blocked). [`Inbound`] and [`Outbound`]
 //! provide the “receive”/“emit” roles for any component that wants to plug into the stream.
 
+use std::any::{self, Any};
+use std::fmt;
+use std::sync::Arc;
+
 /// Protocol-level signals that flow through the streaming pipeline.
 ///
 /// This enum is inspired by message-based P2P protocols: every controller command
@@ -18,6 +22,12 @@ pub enum StreamingSignal<Tok, Ast> {
     RequestToken(usize),
     /// Lexer supplies a token produced from the input stream.
     SupplyToken(Tok),
+    /// Controller asks the lexer for a batch of up to `n` tokens, to be
+    /// answered with one [`SupplyTokens`](Self::SupplyTokens).
+    RequestTokens(usize),
+    /// Lexer supplies a batch of tokens, in order. Saves the per-signal
+    /// overhead of [`SupplyToken`](Self::SupplyToken) on large inputs.
+    SupplyTokens(Vec<Tok>),
     /// Controller confirms a token was delivered to the parser.
     TokenDelivered,
     /// Parser reports it produced `n` AST nodes.
@@ -30,8 +40,75 @@ pub enum StreamingSignal<Tok, Ast> {
     Blocked(String),
     /// Controller signals that the input stream is finished.
     EndOfInput,
+    /// Controller reopens a stage after [`EndOfInput`](Self::EndOfInput), so
+    /// input supplied afterwards starts a new stretch instead of being
+    /// ignored, e.g. the next line typed into a REPL.
+    Resume,
+    /// Lexer has used up the input it holds, but the input has not ended.
+    /// Answered with [`SupplyInput`](Self::SupplyInput), or with
+    /// [`EndOfInput`](Self::EndOfInput) once there is no more.
+    NeedInput,
+    /// Controller supplies more source text to a lexer that needs input.
+    SupplyInput(String),
     /// Controller forces the pipeline to abort, optionally with reason.
     Abort(String),
+    /// Parser passes a user payload back to the lexer, e.g. a newly declared
+    /// type name that changes how later identifiers are lexed.
+    Feedback(Feedback),
+    /// A stage reports how far it has got, e.g. for a progress bar.
+    Progress(Progress),
+}
+
+/// Work done so far by a streaming pipeline, carried by
+/// [`StreamingSignal::Progress`].
+///
+/// Each stage fills in what it knows (a lexer the bytes and tokens, a
+/// controller the nodes) and leaves the rest 0.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// Bytes of input consumed.
+    pub bytes_consumed: usize,
+    /// Tokens produced by the lexer.
+    pub tokens_emitted: usize,
+    /// AST nodes produced by the parser.
+    pub nodes_emitted: usize,
+}
+
+/// A type-erased payload sent from parser rules to lexer rules through
+/// [`StreamingSignal::Feedback`].
+///
+/// Receivers recover the value with [`downcast_ref`](Self::downcast_ref), so
+/// each kind of feedback should get its own type.
+#[derive(Clone)]
+pub struct Feedback {
+    value: Arc<dyn Any + Send + Sync>,
+    type_name: &'static str,
+}
+
+impl Feedback {
+    /// Wraps `value` for sending.
+    pub fn new<T: Any + Send + Sync>(value: T) -> Self {
+        Self {
+            value: Arc::new(value),
+            type_name: any::type_name::<T>(),
+        }
+    }
+
+    /// Returns the payload if it is a `T`.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.value.downcast_ref()
+    }
+
+    /// Returns `true` if the payload is a `T`.
+    pub fn is<T: Any>(&self) -> bool {
+        self.value.is::<T>()
+    }
+}
+
+impl fmt::Debug for Feedback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Feedback").field(&self.type_name).finish()
+    }
 }
 
 /// Trait implemented by components that can **receive** streaming signals.
@@ -50,3 +127,19 @@ pub trait Inbound<Tok, Ast> {
 pub trait Outbound<Tok, Ast> {
     fn next_signal(&mut self) -> Option<StreamingSignal<Tok, Ast>>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct TypeName(&'static str);
+
+    #[test]
+    fn test_feedback_downcast() {
+        let feedback = Feedback::new(TypeName("size_t"));
+        assert!(feedback.is::<TypeName>());
+        assert_eq!(feedback.clone().downcast_ref(), Some(&TypeName("size_t")));
+        assert_eq!(feedback.downcast_ref::<String>(), None);
+    }
+}
@@ -1,11 +1,23 @@
-use std::ops::Deref;
+use crate::position::Position;
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::ops::{Bound, Deref, RangeBounds};
 use std::sync::Arc;
 
 /// Immutable slice referencing a shared text buffer.
 ///
 /// The slice keeps an `Arc<str>` alive so that it can be freely cloned and
 /// moved around without worrying about lifetimes. It implements [`Deref`]
-/// and [`Display`], so it behaves like `&str` in most contexts.
+/// and [`Display`](std::fmt::Display), so it behaves like `&str` in most
+/// contexts: `str` methods such as `char_indices()` and `parse::<T>()` work
+/// directly on a slice.
+/// Methods that narrow the text ([`subslice`](Self::subslice),
+/// [`split_at`](Self::split_at), [`trim`](Self::trim)) return new slices of
+/// the same buffer instead of copying.
+///
+/// Slices compare and hash by their text, like `str`, regardless of which
+/// buffer they point into.
 ///
 /// # Examples
 /// ```
@@ -65,6 +77,73 @@ impl TextSlice {
     pub fn end(&self) -> usize {
         self.end
     }
+
+    /// Returns the part of the slice covering `range`, in bytes relative to
+    /// the slice's start.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or does not fall on character
+    /// boundaries, like indexing a `str`.
+    pub fn subslice<R: RangeBounds<usize>>(&self, range: R) -> TextSlice {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len(),
+        };
+        // Validates the range with `str`'s own checks and messages.
+        let _ = &self.deref()[start..end];
+        Self::new(self.buffer(), self.start + start, self.start + end)
+    }
+
+    /// Splits the slice in two at byte `mid`, without copying.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid` is out of bounds or not on a character boundary.
+    pub fn split_at(&self, mid: usize) -> (TextSlice, TextSlice) {
+        (self.subslice(..mid), self.subslice(mid..))
+    }
+
+    /// Returns the slice without leading and trailing whitespace.
+    pub fn trim(&self) -> TextSlice {
+        self.trim_start().trim_end()
+    }
+
+    /// Returns the slice without leading whitespace.
+    pub fn trim_start(&self) -> TextSlice {
+        let trimmed = self.deref().trim_start();
+        self.subslice(self.len() - trimmed.len()..)
+    }
+
+    /// Returns the slice without trailing whitespace.
+    pub fn trim_end(&self) -> TextSlice {
+        self.subslice(..self.deref().trim_end().len())
+    }
+
+    /// Joins two slices if `other` starts where `self` ends in the same
+    /// buffer, e.g. to merge adjacent tokens without copying.
+    pub fn try_concat(&self, other: &TextSlice) -> Option<TextSlice> {
+        (Arc::ptr_eq(&self.buffer, &other.buffer) && self.end == other.start)
+            .then(|| Self::new(self.buffer(), self.start, other.end))
+    }
+
+    /// Returns the line, column and offset of the slice's start within the
+    /// whole buffer, counting columns in characters like the lexer cursor.
+    pub fn start_position(&self) -> Position {
+        let before = &self.buffer[..self.start];
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+        Position {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            offset: self.start,
+        }
+    }
 }
 
 impl std::fmt::Display for TextSlice {
@@ -87,6 +166,24 @@ impl AsRef<str> for TextSlice {
     }
 }
 
+impl Borrow<str> for TextSlice {
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl PartialEq<str> for TextSlice {
+    fn eq(&self, other: &str) -> bool {
+        self.deref() == other
+    }
+}
+
+impl PartialEq<String> for TextSlice {
+    fn eq(&self, other: &String) -> bool {
+        self.deref() == other
+    }
+}
+
 impl PartialEq<&str> for TextSlice {
     fn eq(&self, other: &&str) -> bool {
         self.deref() == *other
@@ -101,11 +198,84 @@ impl PartialEq<TextSlice> for &str {
 
 impl PartialEq for TextSlice {
     fn eq(&self, other: &Self) -> bool {
-        self.start == other.start
-            && self.end == other.end
-            && Arc::ptr_eq(&self.buffer, &other.buffer)
-            && self.deref() == other.deref()
+        self.deref() == other.deref()
     }
 }
 
 impl Eq for TextSlice {}
+
+impl Hash for TextSlice {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Must match `str`'s hash for `Borrow<str>` lookups.
+        self.deref().hash(state);
+    }
+}
+
+impl PartialOrd for TextSlice {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TextSlice {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.deref().cmp(other.deref())
+    }
+}
+
+/// Serializes as a plain string; deserializing copies the text into a new
+/// buffer.
+#[cfg(feature = "serde")]
+impl serde::Serialize for TextSlice {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TextSlice {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Ok(Self::from_arc(Arc::from(text)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn slice(text: &str) -> TextSlice {
+        TextSlice::from_arc(Arc::from(text))
+    }
+
+    #[test]
+    fn test_text_slice_narrowing_shares_buffer() {
+        let text = slice("  key = 42  ");
+        let trimmed = text.trim();
+        assert_eq!(trimmed, "key = 42");
+        assert_eq!((trimmed.start(), trimmed.end()), (2, 10));
+
+        let (key, rest) = trimmed.split_at(3);
+        assert_eq!(key, "key");
+        assert_eq!(rest.subslice(3..), "42");
+        assert_eq!(rest.subslice(3..).parse::<i32>(), Ok(42));
+        assert!(Arc::ptr_eq(&key.buffer(), &text.buffer()));
+
+        assert_eq!(key.try_concat(&rest), Some(trimmed));
+        assert_eq!(rest.try_concat(&key), None);
+    }
+
+    #[test]
+    fn test_text_slice_compares_by_text() {
+        let a = slice("let x").subslice(4..);
+        let b = slice("x");
+        assert_eq!(a, b);
+        assert_eq!(a, *"x");
+        assert_eq!(a, "x".to_string());
+
+        let names: HashSet<TextSlice> = [a].into_iter().collect();
+        assert!(names.contains(&b));
+        assert!(names.contains("x"));
+    }
+}
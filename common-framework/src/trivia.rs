@@ -0,0 +1,129 @@
+use std::fmt;
+
+/// Which tokens are trivia: whitespace the grammar never looks at, and
+/// line breaks unless they are significant.
+///
+/// Configured once and shared: a lexer can drop trivia with it
+/// (`lexer.filter_tokens(|t| policy.is_significant(t))`), and a parser
+/// skips it before every rule it tries, so grammar rules need no "skip
+/// whitespace" loops of their own. Newlines are significant by default,
+/// for grammars that end statements at line breaks.
+pub struct TriviaPolicy<Tok> {
+    is_whitespace: fn(&Tok) -> bool,
+    is_newline: fn(&Tok) -> bool,
+    skip_newlines: bool,
+}
+
+impl<Tok> TriviaPolicy<Tok> {
+    /// Treats tokens accepted by `is_whitespace` as trivia, and those
+    /// accepted by `is_newline` as significant. For lexer tokens, pass
+    /// `LexToken::is_whitespace` and `LexToken::is_newline`.
+    pub fn new(is_whitespace: fn(&Tok) -> bool, is_newline: fn(&Tok) -> bool) -> Self {
+        Self {
+            is_whitespace,
+            is_newline,
+            skip_newlines: false,
+        }
+    }
+
+    /// Sets whether newlines are trivia too, as in free-form languages.
+    pub fn with_newlines_skipped(mut self, skip: bool) -> Self {
+        self.skip_newlines = skip;
+        self
+    }
+
+    /// Returns true if newlines are trivia.
+    pub fn skips_newlines(&self) -> bool {
+        self.skip_newlines
+    }
+
+    /// Returns true if `token` is whitespace.
+    pub fn is_whitespace(&self, token: &Tok) -> bool {
+        (self.is_whitespace)(token)
+    }
+
+    /// Returns true if `token` is a line break.
+    pub fn is_newline(&self, token: &Tok) -> bool {
+        (self.is_newline)(token)
+    }
+
+    /// Returns true if `token` should be skipped.
+    pub fn is_trivia(&self, token: &Tok) -> bool {
+        self.is_whitespace(token) || (self.skip_newlines && self.is_newline(token))
+    }
+
+    /// Returns true if `token` should be kept; the opposite of
+    /// [`is_trivia`](Self::is_trivia).
+    pub fn is_significant(&self, token: &Tok) -> bool {
+        !self.is_trivia(token)
+    }
+
+    /// Returns the policy in effect under `mode`, or `None` if nothing is
+    /// trivia.
+    pub fn for_mode(self, mode: TriviaMode) -> Option<Self> {
+        match mode {
+            TriviaMode::Inherit => Some(self),
+            TriviaMode::Raw => None,
+            TriviaMode::KeepNewlines => Some(self.with_newlines_skipped(false)),
+        }
+    }
+}
+
+/// How one rule overrides the [`TriviaPolicy`] for the tokens it parses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum TriviaMode {
+    /// Skip what the policy treats as trivia.
+    #[default]
+    Inherit,
+    /// Skip nothing, so the rule sees every token, e.g. for the whitespace
+    /// inside a string template or before a token that must be adjacent.
+    Raw,
+    /// Skip whitespace but keep newlines, even where the policy skips them,
+    /// e.g. for an indentation-sensitive block in a free-form language.
+    KeepNewlines,
+}
+
+impl<Tok> Clone for TriviaPolicy<Tok> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Tok> Copy for TriviaPolicy<Tok> {}
+
+impl<Tok> fmt::Debug for TriviaPolicy<Tok> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TriviaPolicy")
+            .field("skip_newlines", &self.skip_newlines)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newlines_are_trivia_only_when_skipped() {
+        let policy = TriviaPolicy::new(|c: &char| *c == ' ', |c: &char| *c == '\n');
+        assert!(policy.is_trivia(&' '));
+        assert!(!policy.is_trivia(&'\n'));
+        assert!(policy.is_significant(&'x'));
+
+        let policy = policy.with_newlines_skipped(true);
+        assert!(policy.skips_newlines());
+        assert!(policy.is_trivia(&'\n'));
+        assert!(!policy.is_trivia(&'x'));
+    }
+
+    #[test]
+    fn test_modes_override_the_policy() {
+        let policy = TriviaPolicy::new(|c: &char| *c == ' ', |c: &char| *c == '\n')
+            .with_newlines_skipped(true);
+        assert!(policy.for_mode(TriviaMode::Inherit).unwrap().skips_newlines());
+        assert!(policy.for_mode(TriviaMode::Raw).is_none());
+        let keep = policy.for_mode(TriviaMode::KeepNewlines).unwrap();
+        assert!(keep.is_trivia(&' '));
+        assert!(!keep.is_trivia(&'\n'));
+    }
+}
@@ -0,0 +1,55 @@
+//! The whole framework behind one dependency.
+//!
+//! Each layer stays available under its own module (`common`, `lexer`,
+//! `parser`, `pipeline`), and [`prelude`] gathers the items most grammars
+//! need. Features (`streaming`, `alloc-stats`, `encoding`, `serde`,
+//! `watch`) are passed through to the crates that provide them.
+//!
+//! ```ignore
+//! use compiler_framework::prelude::*;
+//!
+//! let nodes = BatchPipeline::run(input, lexer_rules(), parser_rules());
+//! ```
+
+pub use common_framework as common;
+pub use lexer_framework as lexer;
+pub use parser_framework as parser;
+pub use pipeline_core as pipeline;
+
+/// The items most grammars need, for a glob import.
+///
+/// The lexer's and parser's `DefaultContext` are renamed to
+/// `LexDefaultContext` and `ParseDefaultContext` so both fit.
+pub mod prelude {
+    // Shared types
+    pub use common_framework::{Checkpoint, Position, SourceText, TextSlice};
+
+    // Lexing
+    pub use lexer_framework::{
+        DefaultContext as LexDefaultContext, GenericRule, GenericToken, LexContext, LexToken,
+        Lexer, LexingRule, TokenKind, WordToken,
+    };
+
+    // Parsing
+    pub use parser_framework::{
+        AstNode, DefaultContext as ParseDefaultContext, DynNode, ParseContext, Parser, ParsingRule,
+    };
+
+    // Pipelines
+    pub use pipeline_core::BatchPipeline;
+    #[cfg(feature = "streaming")]
+    pub use pipeline_core::{PipelineBuilder, StreamingPipeline};
+
+    // Streaming stages
+    #[cfg(feature = "streaming")]
+    pub use lexer_framework::{StreamingLexContext, TokenProducer};
+    #[cfg(feature = "streaming")]
+    pub use parser_framework::{StreamingParseContext, TokenConsumer};
+
+    // Diagnostics
+    pub use common_framework::{
+        Diagnostic, Diagnostics, Severity, SourceFile, SourceId, SourceMap,
+    };
+    pub use lexer_framework::{EscapeError, EscapeErrorKind};
+    pub use parser_framework::{Ambiguity, IncompleteParse};
+}
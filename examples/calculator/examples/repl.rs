@@ -0,0 +1,50 @@
+use std::io::{self, Write};
+
+use calculator::Session;
+use parser_framework::{dump_dot, to_sexpr};
+
+fn main() {
+    println!("=== Calculator REPL ===");
+    println!("Enter an expression per line, e.g. `(1 + 2) * -3 ^ 2`.");
+    println!("Prefix a line with :ast to print its syntax tree, or :dot for Graphviz. Type :quit to exit.\n");
+
+    let stdin = io::stdin();
+    let mut session = Session::new();
+    loop {
+        print!("calc> ");
+        io::stdout().flush().expect("flush stdout");
+
+        let mut line = String::new();
+        match stdin.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line == ":quit" || line == ":exit" {
+            break;
+        }
+        if let Some(source) = line.strip_prefix(":dot") {
+            for expr in session.parse_line(source) {
+                print!("{}", dump_dot(&expr));
+            }
+            continue;
+        }
+        if let Some(source) = line.strip_prefix(":ast") {
+            for expr in session.parse_line(source) {
+                println!("{}", to_sexpr(&expr));
+            }
+            continue;
+        }
+
+        for result in session.evaluate_line(line) {
+            match result {
+                Ok(value) => println!("= {}", value),
+                Err(message) => println!("error: {}", message),
+            }
+        }
+    }
+}
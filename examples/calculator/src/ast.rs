@@ -0,0 +1,88 @@
+use common_framework::Position;
+use parser_framework::{AstNode, Children, DumpNode};
+
+/// Calculator expression tree.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number {
+        value: f64,
+        position: Position,
+    },
+    Negate {
+        operand: Box<Expr>,
+        position: Position,
+    },
+    Binary {
+        op: BinaryOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+        position: Position,
+    },
+    /// Produced by error recovery: the offending tokens were skipped.
+    Error {
+        message: String,
+        position: Position,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+}
+
+impl BinaryOp {
+    /// Returns `(left_binding_power, right_binding_power)`.
+    /// `^` binds tighter than `*`/`/` and is right-associative.
+    pub fn binding_power(self) -> (u8, u8) {
+        match self {
+            BinaryOp::Add | BinaryOp::Subtract => (10, 11),
+            BinaryOp::Multiply | BinaryOp::Divide => (20, 21),
+            BinaryOp::Power => (31, 30),
+        }
+    }
+}
+
+impl AstNode for Expr {
+    fn position(&self) -> Option<Position> {
+        Some(match self {
+            Expr::Number { position, .. }
+            | Expr::Negate { position, .. }
+            | Expr::Binary { position, .. }
+            | Expr::Error { position, .. } => *position,
+        })
+    }
+}
+
+impl Children for Expr {
+    fn children(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Negate { operand, .. } => vec![operand],
+            Expr::Binary { left, right, .. } => vec![left, right],
+            Expr::Number { .. } | Expr::Error { .. } => Vec::new(),
+        }
+    }
+}
+
+impl DumpNode for Expr {
+    fn kind(&self) -> &str {
+        match self {
+            Expr::Number { .. } => "Number",
+            Expr::Negate { .. } => "Negate",
+            Expr::Binary { .. } => "Binary",
+            Expr::Error { .. } => "Error",
+        }
+    }
+
+    fn attributes(&self) -> Vec<(&str, String)> {
+        match self {
+            Expr::Number { value, .. } => vec![("value", value.to_string())],
+            Expr::Binary { op, .. } => vec![("op", format!("{:?}", op))],
+            Expr::Error { message, .. } => vec![("message", message.clone())],
+            Expr::Negate { .. } => Vec::new(),
+        }
+    }
+}
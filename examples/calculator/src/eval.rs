@@ -0,0 +1,25 @@
+use crate::ast::{BinaryOp, Expr};
+
+/// Evaluates an expression tree.
+///
+/// Arithmetic follows IEEE-754 semantics (`1 / 0` is infinity). Error nodes
+/// evaluate to `NaN`; use [`crate::evaluate`] to get them reported instead.
+pub fn eval(expr: &Expr) -> f64 {
+    match expr {
+        Expr::Number { value, .. } => *value,
+        Expr::Negate { operand, .. } => -eval(operand),
+        Expr::Binary {
+            op, left, right, ..
+        } => {
+            let (left, right) = (eval(left), eval(right));
+            match op {
+                BinaryOp::Add => left + right,
+                BinaryOp::Subtract => left - right,
+                BinaryOp::Multiply => left * right,
+                BinaryOp::Divide => left / right,
+                BinaryOp::Power => left.powf(right),
+            }
+        }
+        Expr::Error { .. } => f64::NAN,
+    }
+}
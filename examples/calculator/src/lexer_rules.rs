@@ -0,0 +1,128 @@
+use crate::token::CalcToken;
+use lexer_framework::{LexContext, LexingRule};
+
+/// Matches floating-point numbers such as `42`, `3.14` and `.5`.
+pub struct NumberRule;
+
+impl<Ctx> LexingRule<Ctx, CalcToken> for NumberRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char.is_some_and(|ch| ch.is_ascii_digit() || ch == '.'))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<CalcToken> {
+        let position = ctx.position();
+        let mut literal = String::new();
+        literal.push_str(&ctx.consume_while(|ch| ch.is_ascii_digit()));
+
+        if ctx.peek() == Some('.') {
+            literal.push('.');
+            ctx.advance();
+            literal.push_str(&ctx.consume_while(|ch| ch.is_ascii_digit()));
+        }
+
+        literal
+            .parse::<f64>()
+            .ok()
+            .map(|value| CalcToken::Number { value, position })
+    }
+
+    fn priority(&self) -> i32 {
+        15
+    }
+}
+
+/// Matches arithmetic operators and parentheses.
+pub struct OperatorRule;
+
+impl<Ctx> LexingRule<Ctx, CalcToken> for OperatorRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        match first_char? {
+            '+' | '-' | '*' | '/' | '^' | '(' | ')' => Some(true),
+            _ => Some(false),
+        }
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<CalcToken> {
+        let position = ctx.position();
+        let token = match ctx.peek()? {
+            '+' => CalcToken::Plus { position },
+            '-' => CalcToken::Minus { position },
+            '*' => CalcToken::Multiply { position },
+            '/' => CalcToken::Divide { position },
+            '^' => CalcToken::Power { position },
+            '(' => CalcToken::LeftParen { position },
+            ')' => CalcToken::RightParen { position },
+            _ => return None,
+        };
+        ctx.advance();
+        Some(token)
+    }
+
+    fn priority(&self) -> i32 {
+        10
+    }
+}
+
+/// Matches runs of whitespace.
+pub struct WhitespaceRule;
+
+impl<Ctx> LexingRule<Ctx, CalcToken> for WhitespaceRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char.is_some_and(char::is_whitespace))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<CalcToken> {
+        let position = ctx.position();
+        let run = ctx.consume_while(char::is_whitespace);
+        if run.is_empty() {
+            None
+        } else {
+            Some(CalcToken::Whitespace { position })
+        }
+    }
+
+    fn priority(&self) -> i32 {
+        1
+    }
+}
+
+/// Fallback rule: turns any other character into an `Unknown` token so the
+/// lexer never stalls and the parser can report the offending input.
+pub struct UnknownRule;
+
+impl<Ctx> LexingRule<Ctx, CalcToken> for UnknownRule
+where
+    Ctx: LexContext,
+{
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<CalcToken> {
+        let position = ctx.position();
+        let ch = ctx.advance()?;
+        Some(CalcToken::Unknown { ch, position })
+    }
+
+    fn priority(&self) -> i32 {
+        -10
+    }
+}
+
+/// Builds the calculator lexer rules for any lex context.
+pub fn build_lexer_rules<Ctx>() -> Vec<Box<dyn LexingRule<Ctx, CalcToken>>>
+where
+    Ctx: LexContext,
+{
+    vec![
+        Box::new(NumberRule),
+        Box::new(OperatorRule),
+        Box::new(WhitespaceRule),
+        Box::new(UnknownRule),
+    ]
+}
@@ -0,0 +1,87 @@
+//! Calculator example built on the streaming pipeline.
+//!
+//! [`PipelineBuilder`] wires the lexer and parser rules into a streaming
+//! pipeline, and the resulting expressions are evaluated with
+//! [`eval`](fn@eval). A [`Session`] keeps one pipeline open across lines.
+
+pub mod ast;
+pub mod eval;
+pub mod lexer_rules;
+pub mod parser_rules;
+pub mod token;
+
+pub use ast::{BinaryOp, Expr};
+pub use eval::eval;
+pub use lexer_rules::build_lexer_rules;
+pub use parser_rules::build_parser_rules;
+pub use token::CalcToken;
+
+use pipeline_core::{BuiltPipeline, PipelineBuilder};
+
+/// Parses `input` with the streaming pipeline and returns the expressions found.
+pub fn parse(input: &str) -> Vec<Expr> {
+    PipelineBuilder::new()
+        .input(input)
+        .lexer(build_lexer_rules())
+        .parser(build_parser_rules())
+        .build()
+        .run()
+}
+
+/// Parses and evaluates every expression in `input`.
+///
+/// Syntax errors are recovered from, so one bad expression does not hide the
+/// results of the others.
+pub fn evaluate(input: &str) -> Vec<Result<f64, String>> {
+    parse(input).iter().map(evaluate_expr).collect()
+}
+
+/// Evaluates `expr`, turning an [`Expr::Error`] into its message.
+fn evaluate_expr(expr: &Expr) -> Result<f64, String> {
+    match expr {
+        Expr::Error { message, position } => Err(format!(
+            "{} at {}:{}",
+            message, position.line, position.column
+        )),
+        expr => Ok(eval(expr)),
+    }
+}
+
+/// One streaming pipeline fed line by line, as the REPL does.
+///
+/// Each line is pushed into the open pipeline and then flushed, so its
+/// expressions are complete before the next line arrives. Positions count
+/// from the start of the session: the third line reports errors at `3:_`.
+pub struct Session {
+    pipeline: BuiltPipeline<CalcToken, Expr>,
+}
+
+impl Session {
+    /// Starts a session with no input yet.
+    pub fn new() -> Self {
+        Self {
+            pipeline: PipelineBuilder::new()
+                .lexer(build_lexer_rules())
+                .parser(build_parser_rules())
+                .build(),
+        }
+    }
+
+    /// Parses `line` and returns the expressions on it.
+    pub fn parse_line(&mut self, line: &str) -> Vec<Expr> {
+        let mut exprs = self.pipeline.feed_str(&format!("{}\n", line));
+        exprs.extend(self.pipeline.flush());
+        exprs
+    }
+
+    /// Parses and evaluates every expression on `line`.
+    pub fn evaluate_line(&mut self, line: &str) -> Vec<Result<f64, String>> {
+        self.parse_line(line).iter().map(evaluate_expr).collect()
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
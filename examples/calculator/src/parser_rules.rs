@@ -0,0 +1,202 @@
+use crate::ast::{BinaryOp, Expr};
+use crate::token::CalcToken;
+use common_framework::Position;
+use parser_framework::{AstNode, ParseContext, ParsingRule};
+
+/// Binding power of unary minus: tighter than `*`, looser than `^` (`-2^2 == -4`).
+const NEGATE_BP: u8 = 25;
+
+/// Why an expression could not be parsed.
+enum Failure {
+    /// The token stream ran dry but more tokens may still arrive.
+    Incomplete,
+    /// A token (or the end of a finished stream) that cannot appear here.
+    Unexpected { message: String, position: Position },
+}
+
+/// Parses one complete arithmetic expression.
+///
+/// In a streaming context the rule waits (returns `None`) until the expression
+/// can no longer be extended, i.e. until a non-operator token arrives or the
+/// stream is finished. Malformed input is recovered from by skipping up to
+/// and including the offending token and producing an [`Expr::Error`] node.
+pub struct ExpressionRule;
+
+impl<Ctx> ParsingRule<Ctx, CalcToken, Expr> for ExpressionRule
+where
+    Ctx: ParseContext<CalcToken>,
+{
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Expr> {
+        let checkpoint = ctx.checkpoint();
+
+        // Nothing but whitespace left: there is no expression to parse.
+        skip_whitespace(ctx);
+        if ctx.peek().is_none() {
+            ctx.restore(checkpoint);
+            return None;
+        }
+
+        match parse_expression(ctx, 0) {
+            Ok(expr) => Some(expr),
+            Err(Failure::Incomplete) => {
+                ctx.restore(checkpoint);
+                None
+            }
+            Err(Failure::Unexpected { message, position }) => {
+                ctx.advance();
+                Some(Expr::Error { message, position })
+            }
+        }
+    }
+
+    fn priority(&self) -> i32 {
+        100
+    }
+}
+
+/// Builds the calculator parser rules for any parse context.
+pub fn build_parser_rules<Ctx>() -> Vec<Box<dyn ParsingRule<Ctx, CalcToken, Expr>>>
+where
+    Ctx: ParseContext<CalcToken>,
+{
+    vec![Box::new(ExpressionRule)]
+}
+
+fn skip_whitespace<Ctx>(ctx: &mut Ctx)
+where
+    Ctx: ParseContext<CalcToken>,
+{
+    while matches!(ctx.peek(), Some(CalcToken::Whitespace { .. })) {
+        ctx.advance();
+    }
+}
+
+/// Returns the next significant token, `None` at the end of a finished stream,
+/// or `Failure::Incomplete` if the stream may still grow.
+fn peek_significant<Ctx>(ctx: &mut Ctx) -> Result<Option<CalcToken>, Failure>
+where
+    Ctx: ParseContext<CalcToken>,
+{
+    skip_whitespace(ctx);
+    if let Some(token) = ctx.peek() {
+        return Ok(Some(token.clone()));
+    }
+    if ctx.is_eof() {
+        Ok(None)
+    } else {
+        Err(Failure::Incomplete)
+    }
+}
+
+fn parse_expression<Ctx>(ctx: &mut Ctx, min_bp: u8) -> Result<Expr, Failure>
+where
+    Ctx: ParseContext<CalcToken>,
+{
+    let mut left = parse_prefix(ctx)?;
+
+    while let Some(token) = peek_significant(ctx)? {
+        let op = match binary_op(&token) {
+            Some(op) => op,
+            None => break,
+        };
+        let (l_bp, r_bp) = op.binding_power();
+        if l_bp < min_bp {
+            break;
+        }
+        ctx.advance();
+
+        let right = parse_expression(ctx, r_bp)?;
+        let position = left.position().unwrap_or_default();
+        left = Expr::Binary {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+            position,
+        };
+    }
+
+    Ok(left)
+}
+
+fn parse_prefix<Ctx>(ctx: &mut Ctx) -> Result<Expr, Failure>
+where
+    Ctx: ParseContext<CalcToken>,
+{
+    let token = match peek_significant(ctx)? {
+        Some(token) => token,
+        None => {
+            return Err(Failure::Unexpected {
+                message: "unexpected end of input".to_string(),
+                position: ctx.position(),
+            })
+        }
+    };
+
+    match token {
+        CalcToken::Number { value, position } => {
+            ctx.advance();
+            Ok(Expr::Number { value, position })
+        }
+        CalcToken::Minus { position } => {
+            ctx.advance();
+            let operand = parse_expression(ctx, NEGATE_BP)?;
+            Ok(Expr::Negate {
+                operand: Box::new(operand),
+                position,
+            })
+        }
+        CalcToken::LeftParen { position } => {
+            ctx.advance();
+            let expr = parse_expression(ctx, 0)?;
+            match peek_significant(ctx)? {
+                Some(CalcToken::RightParen { .. }) => {
+                    ctx.advance();
+                    Ok(expr)
+                }
+                Some(other) => Err(unexpected(ctx, &other, "expected ')'")),
+                None => Err(Failure::Unexpected {
+                    message: "unclosed '('".to_string(),
+                    position,
+                }),
+            }
+        }
+        other => Err(unexpected(ctx, &other, "expected a number or '('")),
+    }
+}
+
+/// Reports `token`, the one under the cursor, at the context's position.
+fn unexpected<Ctx>(ctx: &Ctx, token: &CalcToken, expectation: &str) -> Failure
+where
+    Ctx: ParseContext<CalcToken>,
+{
+    Failure::Unexpected {
+        message: format!("{}, found {}", expectation, describe(token)),
+        position: ctx.position(),
+    }
+}
+
+fn describe(token: &CalcToken) -> String {
+    match token {
+        CalcToken::Number { value, .. } => format!("number {}", value),
+        CalcToken::Plus { .. } => "'+'".to_string(),
+        CalcToken::Minus { .. } => "'-'".to_string(),
+        CalcToken::Multiply { .. } => "'*'".to_string(),
+        CalcToken::Divide { .. } => "'/'".to_string(),
+        CalcToken::Power { .. } => "'^'".to_string(),
+        CalcToken::LeftParen { .. } => "'('".to_string(),
+        CalcToken::RightParen { .. } => "')'".to_string(),
+        CalcToken::Whitespace { .. } => "whitespace".to_string(),
+        CalcToken::Unknown { ch, .. } => format!("{:?}", ch),
+    }
+}
+
+fn binary_op(token: &CalcToken) -> Option<BinaryOp> {
+    match token {
+        CalcToken::Plus { .. } => Some(BinaryOp::Add),
+        CalcToken::Minus { .. } => Some(BinaryOp::Subtract),
+        CalcToken::Multiply { .. } => Some(BinaryOp::Multiply),
+        CalcToken::Divide { .. } => Some(BinaryOp::Divide),
+        CalcToken::Power { .. } => Some(BinaryOp::Power),
+        _ => None,
+    }
+}
@@ -0,0 +1,39 @@
+use common_framework::Position;
+use lexer_framework::LexToken;
+
+/// Calculator tokens.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CalcToken {
+    Number { value: f64, position: Position },
+    Plus { position: Position },
+    Minus { position: Position },
+    Multiply { position: Position },
+    Divide { position: Position },
+    Power { position: Position },
+    LeftParen { position: Position },
+    RightParen { position: Position },
+    Whitespace { position: Position },
+    /// A character no rule recognises; kept so the parser can report it.
+    Unknown { ch: char, position: Position },
+}
+
+impl LexToken for CalcToken {
+    fn position(&self) -> Option<Position> {
+        Some(match self {
+            CalcToken::Number { position, .. }
+            | CalcToken::Plus { position }
+            | CalcToken::Minus { position }
+            | CalcToken::Multiply { position }
+            | CalcToken::Divide { position }
+            | CalcToken::Power { position }
+            | CalcToken::LeftParen { position }
+            | CalcToken::RightParen { position }
+            | CalcToken::Whitespace { position }
+            | CalcToken::Unknown { position, .. } => *position,
+        })
+    }
+
+    fn is_whitespace(&self) -> bool {
+        matches!(self, CalcToken::Whitespace { .. })
+    }
+}
@@ -0,0 +1,71 @@
+//! Evaluation and error recovery tests.
+
+use calculator::{eval, evaluate, parse, Expr};
+use common_framework::Position;
+
+fn value(input: &str) -> f64 {
+    let exprs = parse(input);
+    assert_eq!(exprs.len(), 1, "{input:?} parsed to {exprs:?}");
+    eval(&exprs[0])
+}
+
+#[test]
+fn test_precedence_and_associativity() {
+    assert_eq!(value("1 + 2 * 3"), 7.0);
+    assert_eq!(value("(1 + 2) * 3"), 9.0);
+    assert_eq!(value("8 - 3 - 2"), 3.0);
+    assert_eq!(value("2 ^ 3 ^ 2"), 512.0);
+}
+
+#[test]
+fn test_negation_binds_looser_than_power() {
+    assert_eq!(value("-2 ^ 2"), -4.0);
+    assert_eq!(value("(-2) ^ 2"), 4.0);
+    assert_eq!(value("2 * -3"), -6.0);
+}
+
+#[test]
+fn test_division_follows_ieee_754() {
+    assert_eq!(value("1 / 0"), f64::INFINITY);
+    assert!(value("0 / 0").is_nan());
+}
+
+#[test]
+fn test_error_nodes_evaluate_to_nan() {
+    let error = Expr::Error {
+        message: "bad".to_string(),
+        position: Position::new(),
+    };
+    assert!(eval(&error).is_nan());
+}
+
+#[test]
+fn test_recovers_after_unexpected_token() {
+    assert_eq!(
+        evaluate("1 + * 2"),
+        vec![
+            Err("expected a number or '(', found '*' at 1:5".to_string()),
+            Ok(2.0)
+        ]
+    );
+    assert_eq!(
+        evaluate("1 $ 2"),
+        vec![
+            Ok(1.0),
+            Err("expected a number or '(', found '$' at 1:3".to_string()),
+            Ok(2.0)
+        ]
+    );
+}
+
+#[test]
+fn test_reports_unfinished_expressions() {
+    assert_eq!(
+        evaluate("1 +"),
+        vec![Err("unexpected end of input at 1:3".to_string())]
+    );
+    assert_eq!(
+        evaluate("(1 + 2"),
+        vec![Err("unclosed '(' at 1:1".to_string())]
+    );
+}
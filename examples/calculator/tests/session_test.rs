@@ -0,0 +1,46 @@
+//! REPL session tests (`Session`).
+
+use calculator::Session;
+
+#[test]
+fn test_each_line_is_evaluated_when_fed() {
+    let mut session = Session::new();
+    assert_eq!(session.evaluate_line("1 + 2"), vec![Ok(3.0)]);
+    assert_eq!(session.evaluate_line("2 ^ 3"), vec![Ok(8.0)]);
+    assert_eq!(session.evaluate_line("4 5"), vec![Ok(4.0), Ok(5.0)]);
+    assert!(session.evaluate_line("").is_empty());
+}
+
+#[test]
+fn test_expressions_do_not_continue_across_lines() {
+    let mut session = Session::new();
+    assert_eq!(
+        session.evaluate_line("(1"),
+        vec![Err("unclosed '(' at 1:1".to_string())]
+    );
+    assert_eq!(
+        session.evaluate_line("+ 2)"),
+        vec![
+            Err("expected a number or '(', found '+' at 2:1".to_string()),
+            Ok(2.0),
+            Err("expected a number or '(', found ')' at 2:4".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_session_recovers_after_an_error_line() {
+    let mut session = Session::new();
+    assert_eq!(
+        session.evaluate_line("1 +"),
+        vec![Err("unexpected end of input at 1:4".to_string())]
+    );
+    assert_eq!(session.evaluate_line("(1 + 2) * 3"), vec![Ok(9.0)]);
+}
+
+#[test]
+fn test_parse_line_returns_the_trees() {
+    let mut session = Session::new();
+    assert_eq!(session.parse_line("1 + 2").len(), 1);
+    assert_eq!(session.parse_line("3 4").len(), 2);
+}
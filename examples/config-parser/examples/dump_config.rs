@@ -0,0 +1,53 @@
+use config_parser::parse_config;
+
+const SAMPLE: &str = r#"# Global settings
+name = demo app ; inline comment
+debug = true
+
+# Database connection
+[database]
+host = "db.example.com"
+port = 5432
+port = 5433
+password = "bad \q escape"
+
+[server]
+= missing key
+# listen on all interfaces
+bind = "0.0.0.0"
+
+# end of file
+"#;
+
+fn main() {
+    let (document, diagnostics) = parse_config(SAMPLE);
+
+    for section in &document.sections {
+        match &section.name {
+            Some(name) => println!("[{}]", name),
+            None => println!("(root)"),
+        }
+        for comment in &section.comments.leading {
+            println!("  # {}", comment);
+        }
+        for entry in &section.entries {
+            for comment in &entry.comments.leading {
+                println!("  # {}", comment);
+            }
+            print!("  {} = {:?}", entry.key, entry.value);
+            if let Some(trailing) = &entry.comments.trailing {
+                print!("  ; {}", trailing);
+            }
+            println!();
+        }
+    }
+
+    if !document.trailing_comments.is_empty() {
+        println!("trailing comments: {:?}", document.trailing_comments);
+    }
+
+    println!("\nDiagnostics:");
+    for diagnostic in &diagnostics {
+        println!("  {}", diagnostic);
+    }
+}
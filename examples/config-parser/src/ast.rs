@@ -0,0 +1,72 @@
+use common_framework::Position;
+use parser_framework::AstNode;
+
+/// Comments attached to a section header or entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Trivia {
+    /// Comment lines directly above the item.
+    pub leading: Vec<String>,
+    /// A comment on the same line, after the item.
+    pub trailing: Option<String>,
+}
+
+/// An entry value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    /// A quoted string (escapes decoded).
+    String(String),
+    /// An unquoted value such as `42`, `true` or `hello world`.
+    Bare(String),
+}
+
+impl Value {
+    /// Returns the textual value regardless of quoting.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Value::String(s) | Value::Bare(s) => s,
+        }
+    }
+}
+
+/// A `key = value` line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub key: String,
+    pub value: Value,
+    pub comments: Trivia,
+    pub position: Position,
+}
+
+/// Top-level nodes produced by the parser, one per logical line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigNode {
+    Section {
+        name: String,
+        comments: Trivia,
+        position: Position,
+    },
+    Entry(Entry),
+    /// Comments at the end of the file that precede no item.
+    Dangling {
+        comments: Vec<String>,
+        position: Position,
+    },
+    /// A line that could not be parsed; the whole line was skipped. The
+    /// comments above it are kept for the next item.
+    Error {
+        message: String,
+        comments: Vec<String>,
+        position: Position,
+    },
+}
+
+impl AstNode for ConfigNode {
+    fn position(&self) -> Option<Position> {
+        Some(match self {
+            ConfigNode::Section { position, .. }
+            | ConfigNode::Dangling { position, .. }
+            | ConfigNode::Error { position, .. } => *position,
+            ConfigNode::Entry(entry) => entry.position,
+        })
+    }
+}
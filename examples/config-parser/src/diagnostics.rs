@@ -0,0 +1,47 @@
+use common_framework::Position;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A problem found while parsing or validating a config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub position: Position,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>, position: Position) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+            position,
+        }
+    }
+
+    pub fn warning(message: impl Into<String>, position: Position) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+            position,
+        }
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(
+            f,
+            "{}: {} at {}:{}",
+            label, self.message, self.position.line, self.position.column
+        )
+    }
+}
@@ -0,0 +1,119 @@
+use crate::ast::{ConfigNode, Entry, Trivia, Value};
+use crate::diagnostics::Diagnostic;
+use common_framework::Position;
+
+/// A section and its entries. The implicit root section has no name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Section {
+    pub name: Option<String>,
+    pub comments: Trivia,
+    pub entries: Vec<Entry>,
+    pub position: Position,
+}
+
+impl Section {
+    /// Looks up an entry value by key (the last definition wins).
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|entry| entry.key == key)
+            .map(|entry| &entry.value)
+    }
+}
+
+/// A parsed config file: the root section followed by named sections.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigDocument {
+    pub sections: Vec<Section>,
+    /// Comments at the end of the file that are attached to no item.
+    pub trailing_comments: Vec<String>,
+}
+
+impl ConfigDocument {
+    /// Returns the section with the given name, or the root section for `None`.
+    pub fn section(&self, name: Option<&str>) -> Option<&Section> {
+        self.sections
+            .iter()
+            .find(|section| section.name.as_deref() == name)
+    }
+
+    /// Looks up `key` in the named section (or the root section for `None`).
+    pub fn get(&self, section: Option<&str>, key: &str) -> Option<&Value> {
+        self.section(section)?.get(key)
+    }
+
+    /// Groups parsed nodes into sections, turning error nodes and duplicate
+    /// keys into diagnostics. Comments above a malformed line go to the next
+    /// item, or to the trailing comments if none follows.
+    pub fn from_nodes(nodes: Vec<ConfigNode>) -> (Self, Vec<Diagnostic>) {
+        let mut diagnostics = Vec::new();
+        // Comments of skipped lines, waiting for the next item.
+        let mut orphaned = Vec::new();
+        let mut document = ConfigDocument {
+            sections: vec![Section::default()],
+            trailing_comments: Vec::new(),
+        };
+
+        for node in nodes {
+            match node {
+                ConfigNode::Section {
+                    name,
+                    mut comments,
+                    position,
+                } => {
+                    adopt(&mut orphaned, &mut comments);
+                    if document.section(Some(&name)).is_some() {
+                        diagnostics.push(Diagnostic::warning(
+                            format!("section `{}` is defined more than once", name),
+                            position,
+                        ));
+                    }
+                    document.sections.push(Section {
+                        name: Some(name),
+                        comments,
+                        entries: Vec::new(),
+                        position,
+                    });
+                }
+                ConfigNode::Entry(mut entry) => {
+                    adopt(&mut orphaned, &mut entry.comments);
+                    let current = document
+                        .sections
+                        .last_mut()
+                        .expect("root section always exists");
+                    if current.get(&entry.key).is_some() {
+                        diagnostics.push(Diagnostic::warning(
+                            format!("duplicate key `{}`; the last value wins", entry.key),
+                            entry.position,
+                        ));
+                    }
+                    current.entries.push(entry);
+                }
+                ConfigNode::Dangling { comments, .. } => {
+                    document.trailing_comments.append(&mut orphaned);
+                    document.trailing_comments.extend(comments);
+                }
+                ConfigNode::Error {
+                    message,
+                    comments,
+                    position,
+                } => {
+                    orphaned.extend(comments);
+                    diagnostics.push(Diagnostic::error(message, position));
+                }
+            }
+        }
+        document.trailing_comments.append(&mut orphaned);
+
+        (document, diagnostics)
+    }
+}
+
+/// Puts the `orphaned` comments in front of an item's leading comments.
+fn adopt(orphaned: &mut Vec<String>, comments: &mut Trivia) {
+    if !orphaned.is_empty() {
+        orphaned.append(&mut comments.leading);
+        comments.leading = std::mem::take(orphaned);
+    }
+}
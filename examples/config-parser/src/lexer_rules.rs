@@ -0,0 +1,215 @@
+use crate::token::ConfigToken;
+use lexer_framework::{decode_escape, LexContext, LexingRule};
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || matches!(ch, '_' | '-' | '.' | '+')
+}
+
+/// Matches `[`, `]` and `=`.
+pub struct PunctuationRule;
+
+impl<Ctx> LexingRule<Ctx, ConfigToken> for PunctuationRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(matches!(first_char, Some('[' | ']' | '=')))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<ConfigToken> {
+        let position = ctx.position();
+        let token = match ctx.peek()? {
+            '[' => ConfigToken::LeftBracket { position },
+            ']' => ConfigToken::RightBracket { position },
+            '=' => ConfigToken::Equals { position },
+            _ => return None,
+        };
+        ctx.advance();
+        Some(token)
+    }
+
+    fn priority(&self) -> i32 {
+        20
+    }
+}
+
+/// Matches `#` and `;` comments up to the end of the line.
+pub struct CommentRule;
+
+impl<Ctx> LexingRule<Ctx, ConfigToken> for CommentRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(matches!(first_char, Some('#' | ';')))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<ConfigToken> {
+        let position = ctx.position();
+        if !matches!(ctx.peek()?, '#' | ';') {
+            return None;
+        }
+        ctx.advance();
+        let text = ctx.consume_while(|ch| ch != '\n');
+        Some(ConfigToken::Comment {
+            text: text.trim().to_string(),
+            position,
+        })
+    }
+
+    fn priority(&self) -> i32 {
+        20
+    }
+}
+
+/// Matches double-quoted strings, decoding escapes.
+pub struct StringRule;
+
+impl<Ctx> LexingRule<Ctx, ConfigToken> for StringRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char == Some('"'))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<ConfigToken> {
+        let position = ctx.position();
+        if ctx.peek() != Some('"') {
+            return None;
+        }
+        ctx.advance();
+
+        let mut value = String::new();
+        loop {
+            match ctx.peek() {
+                None | Some('\n') => {
+                    return Some(ConfigToken::Invalid {
+                        message: "unterminated string".to_string(),
+                        position,
+                    })
+                }
+                Some('"') => {
+                    ctx.advance();
+                    return Some(ConfigToken::String { value, position });
+                }
+                Some('\\') => match decode_escape(ctx) {
+                    Ok(ch) => value.push(ch),
+                    Err(err) => {
+                        ctx.consume_while(|ch| ch != '"' && ch != '\n');
+                        if ctx.peek() == Some('"') {
+                            ctx.advance();
+                        }
+                        return Some(ConfigToken::Invalid {
+                            message: err.kind.to_string(),
+                            position: err.position,
+                        });
+                    }
+                },
+                Some(ch) => {
+                    value.push(ch);
+                    ctx.advance();
+                }
+            }
+        }
+    }
+
+    fn priority(&self) -> i32 {
+        15
+    }
+}
+
+/// Matches bare words.
+pub struct WordRule;
+
+impl<Ctx> LexingRule<Ctx, ConfigToken> for WordRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char.is_some_and(is_word_char))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<ConfigToken> {
+        let position = ctx.position();
+        let text = ctx.consume_while(is_word_char);
+        if text.is_empty() {
+            None
+        } else {
+            Some(ConfigToken::Word {
+                text: text.to_string(),
+                position,
+            })
+        }
+    }
+
+    fn priority(&self) -> i32 {
+        10
+    }
+}
+
+/// Matches line breaks and other whitespace separately, since newlines end entries.
+pub struct WhitespaceRule;
+
+impl<Ctx> LexingRule<Ctx, ConfigToken> for WhitespaceRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char.is_some_and(char::is_whitespace))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<ConfigToken> {
+        let position = ctx.position();
+        if ctx.peek() == Some('\n') {
+            ctx.advance();
+            return Some(ConfigToken::Newline { position });
+        }
+        let run = ctx.consume_while(|ch| ch.is_whitespace() && ch != '\n');
+        if run.is_empty() {
+            None
+        } else {
+            Some(ConfigToken::Whitespace { position })
+        }
+    }
+
+    fn priority(&self) -> i32 {
+        5
+    }
+}
+
+/// Fallback: any other character becomes an `Invalid` token.
+pub struct InvalidCharRule;
+
+impl<Ctx> LexingRule<Ctx, ConfigToken> for InvalidCharRule
+where
+    Ctx: LexContext,
+{
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<ConfigToken> {
+        let position = ctx.position();
+        let ch = ctx.advance()?;
+        Some(ConfigToken::Invalid {
+            message: format!("unexpected character {:?}", ch),
+            position,
+        })
+    }
+
+    fn priority(&self) -> i32 {
+        -10
+    }
+}
+
+/// Builds the config lexer rules.
+pub fn build_lexer_rules<Ctx>() -> Vec<Box<dyn LexingRule<Ctx, ConfigToken>>>
+where
+    Ctx: LexContext,
+{
+    vec![
+        Box::new(PunctuationRule),
+        Box::new(CommentRule),
+        Box::new(StringRule),
+        Box::new(WordRule),
+        Box::new(WhitespaceRule),
+        Box::new(InvalidCharRule),
+    ]
+}
@@ -0,0 +1,32 @@
+//! INI/TOML-like config parser example.
+//!
+//! Supports `[sections]`, `key = value` entries with quoted or bare values, and
+//! `#`/`;` comments. Comments are kept as trivia attached to the following item
+//! (or as a trailing same-line comment), and malformed lines are skipped and
+//! reported as [`Diagnostic`]s instead of aborting the parse.
+
+pub mod ast;
+pub mod diagnostics;
+pub mod document;
+pub mod lexer_rules;
+pub mod parser_rules;
+pub mod token;
+
+pub use ast::{ConfigNode, Entry, Trivia, Value};
+pub use diagnostics::{Diagnostic, Severity};
+pub use document::{ConfigDocument, Section};
+pub use lexer_rules::build_lexer_rules;
+pub use parser_rules::build_parser_rules;
+pub use token::ConfigToken;
+
+use pipeline_core::BatchPipeline;
+
+/// Parses a config file into a document plus any diagnostics found.
+pub fn parse_config(input: &str) -> (ConfigDocument, Vec<Diagnostic>) {
+    let nodes = BatchPipeline::<ConfigToken, ConfigNode>::run(
+        input,
+        build_lexer_rules(),
+        build_parser_rules(),
+    );
+    ConfigDocument::from_nodes(nodes)
+}
@@ -0,0 +1,238 @@
+use crate::ast::{ConfigNode, Entry, Trivia, Value};
+use crate::token::ConfigToken;
+use common_framework::Position;
+use lexer_framework::LexToken;
+use parser_framework::{ParseContext, ParsingRule};
+
+fn skip_whitespace<Ctx>(ctx: &mut Ctx)
+where
+    Ctx: ParseContext<ConfigToken>,
+{
+    while matches!(ctx.peek(), Some(ConfigToken::Whitespace { .. })) {
+        ctx.advance();
+    }
+}
+
+/// Consumes blank lines and comment lines, returning the comments.
+/// This is how leading trivia gets attached to the item that follows it.
+fn collect_leading_comments<Ctx>(ctx: &mut Ctx) -> Vec<String>
+where
+    Ctx: ParseContext<ConfigToken>,
+{
+    let mut comments = Vec::new();
+    loop {
+        skip_whitespace(ctx);
+        match ctx.peek() {
+            Some(ConfigToken::Comment { text, .. }) => {
+                comments.push(text.clone());
+                ctx.advance();
+            }
+            Some(ConfigToken::Newline { .. }) => {
+                ctx.advance();
+            }
+            _ => break,
+        }
+    }
+    comments
+}
+
+/// Consumes the rest of a line: optional whitespace, an optional trailing
+/// comment, and the newline (or end of input). Returns `None` if anything else
+/// is left on the line.
+fn finish_line<Ctx>(ctx: &mut Ctx) -> Option<Option<String>>
+where
+    Ctx: ParseContext<ConfigToken>,
+{
+    skip_whitespace(ctx);
+    let trailing = match ctx.peek() {
+        Some(ConfigToken::Comment { text, .. }) => {
+            let text = text.clone();
+            ctx.advance();
+            Some(text)
+        }
+        _ => None,
+    };
+    match ctx.peek() {
+        Some(ConfigToken::Newline { .. }) => {
+            ctx.advance();
+            Some(trailing)
+        }
+        None => Some(trailing),
+        Some(_) => None,
+    }
+}
+
+fn token_position(token: Option<&ConfigToken>) -> Position {
+    token.and_then(LexToken::position).unwrap_or_default()
+}
+
+/// `[section]`
+pub struct SectionRule;
+
+impl<Ctx> ParsingRule<Ctx, ConfigToken, ConfigNode> for SectionRule
+where
+    Ctx: ParseContext<ConfigToken>,
+{
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<ConfigNode> {
+        let leading = collect_leading_comments(ctx);
+
+        let position = match ctx.peek()? {
+            ConfigToken::LeftBracket { position } => *position,
+            _ => return None,
+        };
+        ctx.advance();
+        skip_whitespace(ctx);
+
+        let name = match ctx.advance()? {
+            ConfigToken::Word { text, .. } => text,
+            _ => return None,
+        };
+        skip_whitespace(ctx);
+        if !matches!(ctx.advance()?, ConfigToken::RightBracket { .. }) {
+            return None;
+        }
+
+        let trailing = finish_line(ctx)?;
+        Some(ConfigNode::Section {
+            name,
+            comments: Trivia { leading, trailing },
+            position,
+        })
+    }
+
+    fn priority(&self) -> i32 {
+        30
+    }
+}
+
+/// `key = value`, where the value is a quoted string or a run of bare words.
+pub struct EntryRule;
+
+impl<Ctx> ParsingRule<Ctx, ConfigToken, ConfigNode> for EntryRule
+where
+    Ctx: ParseContext<ConfigToken>,
+{
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<ConfigNode> {
+        let leading = collect_leading_comments(ctx);
+
+        let (key, position) = match ctx.advance()? {
+            ConfigToken::Word { text, position } => (text, position),
+            _ => return None,
+        };
+        skip_whitespace(ctx);
+        if !matches!(ctx.advance()?, ConfigToken::Equals { .. }) {
+            return None;
+        }
+        skip_whitespace(ctx);
+
+        let value = match ctx.advance()? {
+            ConfigToken::String { value, .. } => Value::String(value),
+            ConfigToken::Word { text, .. } => {
+                // Bare values may span several words: `name = hello world`.
+                let mut words = vec![text];
+                loop {
+                    let checkpoint = ctx.checkpoint();
+                    skip_whitespace(ctx);
+                    match ctx.peek() {
+                        Some(ConfigToken::Word { text, .. }) => {
+                            words.push(text.clone());
+                            ctx.advance();
+                        }
+                        _ => {
+                            ctx.restore(checkpoint);
+                            break;
+                        }
+                    }
+                }
+                Value::Bare(words.join(" "))
+            }
+            _ => return None,
+        };
+
+        let trailing = finish_line(ctx)?;
+        Some(ConfigNode::Entry(Entry {
+            key,
+            value,
+            comments: Trivia { leading, trailing },
+            position,
+        }))
+    }
+
+    fn priority(&self) -> i32 {
+        20
+    }
+}
+
+/// Comments (and blank lines) at the end of the file that no item follows.
+pub struct DanglingCommentsRule;
+
+impl<Ctx> ParsingRule<Ctx, ConfigToken, ConfigNode> for DanglingCommentsRule
+where
+    Ctx: ParseContext<ConfigToken>,
+{
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<ConfigNode> {
+        let position = token_position(ctx.peek());
+        let comments = collect_leading_comments(ctx);
+        if ctx.peek().is_some() {
+            return None;
+        }
+        Some(ConfigNode::Dangling { comments, position })
+    }
+
+    fn priority(&self) -> i32 {
+        10
+    }
+}
+
+/// Error recovery: skips the rest of a malformed line and reports it.
+pub struct ErrorLineRule;
+
+impl<Ctx> ParsingRule<Ctx, ConfigToken, ConfigNode> for ErrorLineRule
+where
+    Ctx: ParseContext<ConfigToken>,
+{
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<ConfigNode> {
+        let comments = collect_leading_comments(ctx);
+        let mut position = token_position(ctx.peek());
+        let mut message = None;
+
+        while let Some(token) = ctx.advance() {
+            match token {
+                ConfigToken::Newline { .. } => break,
+                ConfigToken::Invalid {
+                    message: reason,
+                    position: at,
+                } if message.is_none() => {
+                    // Report lexical errors at the offending character.
+                    message = Some(reason);
+                    position = at;
+                }
+                _ => {}
+            }
+        }
+
+        Some(ConfigNode::Error {
+            message: message
+                .unwrap_or_else(|| "expected a `[section]` header or `key = value`".to_string()),
+            comments,
+            position,
+        })
+    }
+
+    fn priority(&self) -> i32 {
+        0
+    }
+}
+
+/// Builds the config parser rules.
+pub fn build_parser_rules<Ctx>() -> Vec<Box<dyn ParsingRule<Ctx, ConfigToken, ConfigNode>>>
+where
+    Ctx: ParseContext<ConfigToken>,
+{
+    vec![
+        Box::new(SectionRule),
+        Box::new(EntryRule),
+        Box::new(DanglingCommentsRule),
+        Box::new(ErrorLineRule),
+    ]
+}
@@ -0,0 +1,44 @@
+use common_framework::Position;
+use lexer_framework::LexToken;
+
+/// Tokens of the INI/TOML-like config format.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigToken {
+    LeftBracket { position: Position },
+    RightBracket { position: Position },
+    Equals { position: Position },
+    /// A bare word: section names, keys, and unquoted values (`42`, `true`, `a.b-c`).
+    Word { text: String, position: Position },
+    /// A double-quoted string with escapes already decoded.
+    String { value: String, position: Position },
+    /// `# ...` or `; ...` up to (not including) the end of the line.
+    Comment { text: String, position: Position },
+    Newline { position: Position },
+    Whitespace { position: Position },
+    /// Input no rule understands, e.g. a string with a bad escape.
+    Invalid { message: String, position: Position },
+}
+
+impl LexToken for ConfigToken {
+    fn position(&self) -> Option<Position> {
+        Some(match self {
+            ConfigToken::LeftBracket { position }
+            | ConfigToken::RightBracket { position }
+            | ConfigToken::Equals { position }
+            | ConfigToken::Word { position, .. }
+            | ConfigToken::String { position, .. }
+            | ConfigToken::Comment { position, .. }
+            | ConfigToken::Newline { position }
+            | ConfigToken::Whitespace { position }
+            | ConfigToken::Invalid { position, .. } => *position,
+        })
+    }
+
+    fn is_newline(&self) -> bool {
+        matches!(self, ConfigToken::Newline { .. })
+    }
+
+    fn is_whitespace(&self) -> bool {
+        matches!(self, ConfigToken::Whitespace { .. })
+    }
+}
@@ -0,0 +1,79 @@
+//! Config parsing tests: sections, comments and error recovery.
+
+use config_parser::{parse_config, Severity, Value};
+
+fn bare(value: &str) -> Option<Value> {
+    Some(Value::Bare(value.to_string()))
+}
+
+#[test]
+fn test_entries_are_grouped_into_sections() {
+    let (doc, diagnostics) =
+        parse_config("name = demo\n[server]\nport = 8080\n[db]\nhost = \"local\"\n");
+    assert!(diagnostics.is_empty());
+    let names: Vec<_> = doc.sections.iter().map(|s| s.name.as_deref()).collect();
+    assert_eq!(names, [None, Some("server"), Some("db")]);
+    assert_eq!(doc.get(None, "name").cloned(), bare("demo"));
+    assert_eq!(doc.get(Some("server"), "port").cloned(), bare("8080"));
+    assert_eq!(
+        doc.get(Some("db"), "host"),
+        Some(&Value::String("local".to_string()))
+    );
+    assert_eq!(doc.get(Some("server"), "host"), None);
+}
+
+#[test]
+fn test_duplicates_are_warnings_and_last_value_wins() {
+    let (doc, diagnostics) = parse_config("[a]\nk = 1\nk = 2\n[a]\n");
+    assert_eq!(doc.get(Some("a"), "k").cloned(), bare("2"));
+    let severities: Vec<_> = diagnostics.iter().map(|d| d.severity).collect();
+    assert_eq!(severities, [Severity::Warning, Severity::Warning]);
+}
+
+#[test]
+fn test_comments_become_trivia() {
+    let (doc, _) =
+        parse_config("# about a\n\n; more\n[a] # header\n# key\nk = v ; inline\n# end\n");
+    let section = doc.section(Some("a")).unwrap();
+    assert_eq!(section.comments.leading, ["about a", "more"]);
+    assert_eq!(section.comments.trailing.as_deref(), Some("header"));
+    let entry = &section.entries[0];
+    assert_eq!(entry.comments.leading, ["key"]);
+    assert_eq!(entry.comments.trailing.as_deref(), Some("inline"));
+    assert_eq!(doc.trailing_comments, ["end"]);
+}
+
+#[test]
+fn test_error_lines_are_skipped_and_reported() {
+    let (doc, diagnostics) = parse_config("[a]\n= missing key\nk = v\nbad \"\\q\"\n");
+    assert_eq!(doc.get(Some("a"), "k").cloned(), bare("v"));
+    let errors: Vec<_> = diagnostics
+        .iter()
+        .map(|d| (d.severity, d.position.line))
+        .collect();
+    assert_eq!(errors, [(Severity::Error, 2), (Severity::Error, 4)]);
+    assert_eq!(
+        diagnostics[0].message,
+        "expected a `[section]` header or `key = value`"
+    );
+}
+
+#[test]
+fn test_comments_above_an_error_line_go_to_the_next_item() {
+    let (doc, diagnostics) = parse_config("# c1\n= bad\n# c2\n[a]\n# c3\n!!\nk = v\n");
+    assert_eq!(diagnostics.len(), 2);
+    let section = doc.section(Some("a")).unwrap();
+    assert_eq!(section.comments.leading, ["c1", "c2"]);
+    assert_eq!(section.entries[0].comments.leading, ["c3"]);
+}
+
+#[test]
+fn test_comments_above_a_final_error_line_are_trailing() {
+    let (doc, diagnostics) = parse_config("k = v\n# c1\n= bad\n# end\n");
+    assert_eq!(diagnostics.len(), 1);
+    assert!(doc.get(None, "k").is_some());
+    assert_eq!(doc.trailing_comments, ["c1", "end"]);
+
+    let (doc, _) = parse_config("# c1\n= bad");
+    assert_eq!(doc.trailing_comments, ["c1"]);
+}
@@ -0,0 +1,72 @@
+//! Streams CSV (or TSV) from stdin and reports each record as it is parsed.
+//!
+//! ```bash
+//! printf 'id,name\n1,"Smith, J"\n' | cargo run -p csv-stream --example csv_stdin -- --print
+//! ```
+//!
+//! Options: `--tsv`, `--chunk <bytes>` (default 4096), `--print` to echo
+//! every record instead of only the summary.
+
+use csv_stream::{stream_records, CsvNode, Dialect};
+use std::io;
+
+fn main() {
+    let mut dialect = Dialect::Csv;
+    let mut chunk_size = 4096;
+    let mut print = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--tsv" => dialect = Dialect::Tsv,
+            "--print" => print = true,
+            "--chunk" => {
+                chunk_size = args
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .unwrap_or_else(|| {
+                        eprintln!("--chunk expects a byte count");
+                        std::process::exit(2);
+                    });
+            }
+            other => {
+                eprintln!("unknown argument: {}", other);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    let mut width = None;
+    let stdin = io::stdin().lock();
+    let result = stream_records(stdin, dialect, chunk_size, |node| match node {
+        CsvNode::Record { fields, position } => {
+            // The first record fixes the expected number of columns.
+            let expected = *width.get_or_insert(fields.len());
+            if fields.len() != expected {
+                eprintln!(
+                    "line {}: expected {} fields, found {}",
+                    position.line,
+                    expected,
+                    fields.len()
+                );
+            }
+            if print {
+                println!("{:>6}: {:?}", position.line, fields);
+            }
+        }
+        CsvNode::Error { message, position } => {
+            eprintln!("line {}:{}: {}", position.line, position.column, message);
+        }
+    });
+
+    match result {
+        Ok(stats) => eprintln!(
+            "{} records, {} errors, peak buffer {} bytes (chunk size {})",
+            stats.records, stats.errors, stats.peak_buffered, chunk_size
+        ),
+        Err(err) => {
+            eprintln!("read error: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
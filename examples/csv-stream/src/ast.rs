@@ -0,0 +1,24 @@
+use common_framework::Position;
+use parser_framework::AstNode;
+
+/// One parsed line of CSV input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvNode {
+    Record {
+        fields: Vec<String>,
+        position: Position,
+    },
+    /// A record that contained malformed input; the whole record is skipped.
+    Error {
+        message: String,
+        position: Position,
+    },
+}
+
+impl AstNode for CsvNode {
+    fn position(&self) -> Option<Position> {
+        match self {
+            CsvNode::Record { position, .. } | CsvNode::Error { position, .. } => Some(*position),
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use crate::token::CsvToken;
+use lexer_framework::{LexContext, LexingRule};
+
+/// Matches a double-quoted field. `""` inside the quotes is an escaped quote,
+/// and delimiters and newlines are taken literally.
+///
+/// When the buffered input ends before the closing quote the rule declines
+/// (returns `None`) unless the input is finished, so a reader-backed lexer
+/// refills and tries again instead of reporting a bogus error.
+pub struct QuotedFieldRule;
+
+impl<Ctx> LexingRule<Ctx, CsvToken> for QuotedFieldRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char == Some('"'))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<CsvToken> {
+        let position = ctx.position();
+        if ctx.advance()? != '"' {
+            return None;
+        }
+
+        let mut value = String::new();
+        loop {
+            match ctx.advance() {
+                Some('"') => {
+                    if ctx.peek() == Some('"') {
+                        ctx.advance();
+                        value.push('"');
+                    } else {
+                        return Some(CsvToken::Field {
+                            value,
+                            quoted: true,
+                            position,
+                        });
+                    }
+                }
+                Some(ch) => value.push(ch),
+                None if ctx.is_eof() => {
+                    return Some(CsvToken::Invalid {
+                        message: "unterminated quoted field".to_string(),
+                        position,
+                    })
+                }
+                None => return None,
+            }
+        }
+    }
+
+    fn priority(&self) -> i32 {
+        20
+    }
+}
+
+/// Matches an unquoted field: everything up to the next delimiter or newline.
+pub struct FieldRule {
+    pub delimiter: char,
+}
+
+impl<Ctx> LexingRule<Ctx, CsvToken> for FieldRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char.is_some_and(|ch| {
+            ch != self.delimiter && ch != '"' && ch != '\n' && ch != '\r'
+        }))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<CsvToken> {
+        let position = ctx.position();
+        let delimiter = self.delimiter;
+        let value = ctx.consume_while(|ch| ch != delimiter && ch != '\n' && ch != '\r');
+        if value.is_empty() {
+            return None;
+        }
+        Some(CsvToken::Field {
+            value: value.to_string(),
+            quoted: false,
+            position,
+        })
+    }
+
+    fn priority(&self) -> i32 {
+        10
+    }
+}
+
+/// Matches the field delimiter (`,` for CSV, `\t` for TSV).
+pub struct DelimiterRule {
+    pub delimiter: char,
+}
+
+impl<Ctx> LexingRule<Ctx, CsvToken> for DelimiterRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char == Some(self.delimiter))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<CsvToken> {
+        let position = ctx.position();
+        if ctx.advance()? != self.delimiter {
+            return None;
+        }
+        Some(CsvToken::Delimiter { position })
+    }
+
+    fn priority(&self) -> i32 {
+        15
+    }
+}
+
+/// Matches `\n`, `\r\n` and a lone `\r`.
+pub struct NewlineRule;
+
+impl<Ctx> LexingRule<Ctx, CsvToken> for NewlineRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(matches!(first_char, Some('\n' | '\r')))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<CsvToken> {
+        let position = ctx.position();
+        match ctx.advance()? {
+            '\n' => {}
+            '\r' => {
+                if ctx.peek() == Some('\n') {
+                    ctx.advance();
+                }
+            }
+            _ => return None,
+        }
+        Some(CsvToken::Newline { position })
+    }
+
+    fn priority(&self) -> i32 {
+        15
+    }
+}
+
+/// Builds the CSV lexer rules for the given field delimiter.
+pub fn build_lexer_rules<Ctx>(delimiter: char) -> Vec<Box<dyn LexingRule<Ctx, CsvToken>>>
+where
+    Ctx: LexContext,
+{
+    vec![
+        Box::new(QuotedFieldRule),
+        Box::new(DelimiterRule { delimiter }),
+        Box::new(NewlineRule),
+        Box::new(FieldRule { delimiter }),
+    ]
+}
@@ -0,0 +1,96 @@
+//! CSV/TSV parser built on the streaming pipeline.
+//!
+//! Input is pulled from any [`Read`] source in fixed-size chunks through
+//! [`Lexer::from_reader`], and every record is handed to a callback as soon as
+//! its terminating newline has been parsed. Consumed input and tokens are
+//! discarded along the way, so memory use stays bounded by the chunk size and
+//! the longest record rather than the size of the input.
+
+pub mod ast;
+pub mod lexer_rules;
+pub mod parser_rules;
+pub mod token;
+
+pub use ast::CsvNode;
+pub use lexer_rules::build_lexer_rules;
+pub use parser_rules::build_parser_rules;
+pub use token::CsvToken;
+
+use lexer_framework::{Lexer, ReaderLexContext};
+use parser_framework::{Parser, StreamingParseContext};
+use pipeline_core::StreamingPipeline;
+use std::io::{self, Read};
+
+/// Field separator flavour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    #[default]
+    Csv,
+    Tsv,
+}
+
+impl Dialect {
+    pub fn delimiter(self) -> char {
+        match self {
+            Dialect::Csv => ',',
+            Dialect::Tsv => '\t',
+        }
+    }
+}
+
+/// Summary of a [`stream_records`] run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StreamStats {
+    pub records: usize,
+    pub errors: usize,
+    /// Largest number of input bytes the lexer held in memory at once.
+    pub peak_buffered: usize,
+}
+
+/// Parses `reader` record by record, calling `on_node` for each record (or
+/// error) as soon as it is complete. At most `chunk_size` bytes are read from
+/// the source at a time.
+pub fn stream_records<R, F>(
+    reader: R,
+    dialect: Dialect,
+    chunk_size: usize,
+    mut on_node: F,
+) -> io::Result<StreamStats>
+where
+    R: Read,
+    F: FnMut(CsvNode),
+{
+    let context = ReaderLexContext::with_chunk_size(reader, chunk_size);
+    let lexer = Lexer::new(context, build_lexer_rules(dialect.delimiter()));
+    let parser = Parser::new(StreamingParseContext::new(), build_parser_rules());
+    let mut pipeline = StreamingPipeline::new(lexer, parser);
+
+    let mut stats = StreamStats::default();
+    pipeline.run_with(|node| {
+        match node {
+            CsvNode::Record { .. } => stats.records += 1,
+            CsvNode::Error { .. } => stats.errors += 1,
+        }
+        on_node(node);
+    });
+
+    let context = pipeline.lexer_mut().context_mut();
+    if let Some(err) = context.take_error() {
+        return Err(err);
+    }
+    stats.peak_buffered = context.peak_buffered();
+    Ok(stats)
+}
+
+/// Parses an in-memory string and returns all records.
+pub fn parse_str(input: &str, dialect: Dialect) -> Vec<CsvNode> {
+    let mut nodes = Vec::new();
+    // Reading from a byte slice cannot fail.
+    let _ = stream_records(
+        input.as_bytes(),
+        dialect,
+        lexer_framework::reader::DEFAULT_CHUNK_SIZE,
+        |node| nodes.push(node),
+    );
+    nodes
+}
@@ -0,0 +1,53 @@
+use crate::ast::CsvNode;
+use crate::token::CsvToken;
+use lexer_framework::LexToken;
+use parser_framework::{ParseContext, ParsingRule};
+
+/// Parses one record: fields separated by delimiters, ended by a newline or
+/// the end of input. Empty fields (`a,,b`) become empty strings, and blank
+/// lines are skipped.
+///
+/// A record is only emitted once its terminating newline has arrived (or the
+/// stream is finished), so in a streaming pipeline each record is produced
+/// as soon as it is complete.
+pub struct RecordRule;
+
+impl<Ctx> ParsingRule<Ctx, CsvToken, CsvNode> for RecordRule
+where
+    Ctx: ParseContext<CsvToken>,
+{
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<CsvNode> {
+        while matches!(ctx.peek(), Some(CsvToken::Newline { .. })) {
+            ctx.advance();
+        }
+        let position = ctx.peek()?.position().unwrap_or_default();
+
+        let mut fields = Vec::new();
+        let mut current = String::new();
+        let mut error = None;
+        loop {
+            match ctx.advance() {
+                Some(CsvToken::Field { value, .. }) => current.push_str(&value),
+                Some(CsvToken::Delimiter { .. }) => fields.push(std::mem::take(&mut current)),
+                Some(CsvToken::Newline { .. }) => break,
+                Some(CsvToken::Invalid { message, position }) => {
+                    error.get_or_insert(CsvNode::Error { message, position });
+                }
+                // Wait for the rest of the record.
+                None if !ctx.is_eof() => return None,
+                None => break,
+            }
+        }
+        fields.push(current);
+
+        Some(error.unwrap_or(CsvNode::Record { fields, position }))
+    }
+}
+
+/// Builds the CSV parser rules.
+pub fn build_parser_rules<Ctx>() -> Vec<Box<dyn ParsingRule<Ctx, CsvToken, CsvNode>>>
+where
+    Ctx: ParseContext<CsvToken>,
+{
+    vec![Box::new(RecordRule)]
+}
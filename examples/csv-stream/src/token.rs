@@ -0,0 +1,40 @@
+use common_framework::Position;
+use lexer_framework::LexToken;
+
+/// CSV/TSV tokens.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CsvToken {
+    /// A field value; quoted fields have their quotes removed and `""` unescaped.
+    Field {
+        value: String,
+        quoted: bool,
+        position: Position,
+    },
+    Delimiter {
+        position: Position,
+    },
+    /// `\n`, `\r\n` or a lone `\r`.
+    Newline {
+        position: Position,
+    },
+    /// Malformed input, e.g. a quoted field that is never closed.
+    Invalid {
+        message: String,
+        position: Position,
+    },
+}
+
+impl LexToken for CsvToken {
+    fn position(&self) -> Option<Position> {
+        Some(match self {
+            CsvToken::Field { position, .. }
+            | CsvToken::Delimiter { position }
+            | CsvToken::Newline { position }
+            | CsvToken::Invalid { position, .. } => *position,
+        })
+    }
+
+    fn is_newline(&self) -> bool {
+        matches!(self, CsvToken::Newline { .. })
+    }
+}
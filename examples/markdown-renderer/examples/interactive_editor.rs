@@ -1,4 +1,4 @@
-use markdown_renderer::{MarkdownNode, MarkdownRenderer, RenderItem, RenderResult};
+use markdown_renderer::{MarkdownNode, MarkdownRenderer, RenderItem, RenderListItem, RenderResult};
 
 fn main() {
     let mut renderer = MarkdownRenderer::new();
@@ -24,6 +24,12 @@ fn main() {
     );
 
     run_single_case(&mut renderer, "场景4：列表", "- Item 1\n- Item 2\n");
+
+    run_single_case(
+        &mut renderer,
+        "场景5：嵌套列表",
+        "- Fruits\n  - Apple\n  - Banana\n    - Cavendish\n- Vegetables\n",
+    );
 }
 
 fn run_progressive_case(renderer: &mut MarkdownRenderer, title: &str, inputs: &[&str]) {
@@ -71,9 +77,7 @@ fn display_result(result: &RenderResult) {
             }
             RenderItem::List(items) => {
                 println!("  [LIST]");
-                for item in items {
-                    println!("    - {}", item);
-                }
+                print_list_items(items, 1);
             }
             RenderItem::CodeBlock { language, code } => {
                 if let Some(lang) = language {
@@ -91,3 +95,10 @@ fn display_result(result: &RenderResult) {
         }
     }
 }
+
+fn print_list_items(items: &[RenderListItem], depth: usize) {
+    for item in items {
+        println!("  {}- {}", "  ".repeat(depth), item.text);
+        print_list_items(&item.children, depth + 1);
+    }
+}
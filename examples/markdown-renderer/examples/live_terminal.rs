@@ -1,6 +1,6 @@
 use std::io::{self, Write};
 
-use markdown_renderer::{MarkdownNode, MarkdownRenderer, RenderItem, RenderResult};
+use markdown_renderer::{MarkdownNode, MarkdownRenderer, RenderItem, RenderListItem, RenderResult};
 
 fn main() {
     println!("=== Markdown 实时渲染终端 ===");
@@ -93,9 +93,7 @@ fn display_render_items(result: &RenderResult) {
             }
             RenderItem::List(items) => {
                 println!("  [LIST]");
-                for entry in items {
-                    println!("    - {}", entry);
-                }
+                print_list_items(items, 1);
             }
             RenderItem::CodeBlock { language, code } => {
                 if let Some(lang) = language {
@@ -113,3 +111,10 @@ fn display_render_items(result: &RenderResult) {
         }
     }
 }
+
+fn print_list_items(items: &[RenderListItem], depth: usize) {
+    for item in items {
+        println!("  {}- {}", "  ".repeat(depth), item.text);
+        print_list_items(&item.children, depth + 1);
+    }
+}
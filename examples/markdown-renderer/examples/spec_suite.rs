@@ -0,0 +1,34 @@
+//! 运行 CommonMark 规范测试并打印通过情况
+//!
+//! ```bash
+//! cargo run --example spec_suite --manifest-path examples/markdown-renderer/Cargo.toml -- spec.json
+//! ```
+
+use markdown_renderer::run_spec_suite;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let Some(path) = args.next() else {
+        eprintln!("usage: spec_suite <spec.json> [--failures]");
+        std::process::exit(2);
+    };
+    let show_failures = args.any(|arg| arg == "--failures");
+
+    let report = match run_spec_suite(&path) {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!("cannot read {}: {}", path, err);
+            std::process::exit(1);
+        }
+    };
+
+    if show_failures {
+        for failure in &report.failures {
+            println!("== example {} ({})", failure.example, failure.section);
+            println!("-- markdown\n{}", failure.markdown);
+            println!("-- expected\n{}", failure.expected);
+            println!("-- actual\n{}", failure.actual);
+        }
+    }
+    print!("{}", report);
+}
@@ -17,7 +17,7 @@ pub enum MarkdownNode {
         state: ContentState,
     },
     List {
-        items: Vec<Vec<Inline>>,
+        items: Vec<ListItem>,
         position: Position,
         state: ContentState,
     },
@@ -34,6 +34,14 @@ pub enum MarkdownNode {
     },
 }
 
+/// 列表项：行内内容加上缩进更深的子列表
+#[derive(Debug, Clone)]
+pub struct ListItem {
+    pub content: Vec<Inline>,
+    /// 嵌套的子列表（均为 `MarkdownNode::List`）
+    pub children: Vec<MarkdownNode>,
+}
+
 /// 行内元素
 #[derive(Debug, Clone)]
 pub enum Inline {
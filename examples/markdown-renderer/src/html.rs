@@ -0,0 +1,112 @@
+use crate::ast::{Inline, ListItem, MarkdownNode};
+
+/// 把 AST 节点渲染为 HTML，格式与 CommonMark 参考实现的输出一致
+/// （每个块级元素后换行），用于与规范测试的期望输出比较
+pub fn to_html(nodes: &[MarkdownNode]) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        write_node(node, &mut out);
+    }
+    out
+}
+
+fn write_node(node: &MarkdownNode, out: &mut String) {
+    match node {
+        MarkdownNode::Heading { level, content, .. } => {
+            out.push_str(&format!("<h{}>", level));
+            write_inlines(content, out);
+            out.push_str(&format!("</h{}>\n", level));
+        }
+        MarkdownNode::Paragraph { content, .. } => {
+            out.push_str("<p>");
+            write_inlines(content, out);
+            out.push_str("</p>\n");
+        }
+        MarkdownNode::List { items, .. } => write_list(items, out),
+        MarkdownNode::CodeBlock { language, code, .. } => {
+            match language {
+                Some(lang) => {
+                    out.push_str("<pre><code class=\"language-");
+                    escape_into(lang, out);
+                    out.push_str("\">");
+                }
+                None => out.push_str("<pre><code>"),
+            }
+            escape_into(code, out);
+            if !code.is_empty() && !code.ends_with('\n') {
+                out.push('\n');
+            }
+            out.push_str("</code></pre>\n");
+        }
+        // 未确定的内容按普通段落输出
+        MarkdownNode::RawText { text, .. } => {
+            let text = text.trim_end_matches('\n');
+            if !text.is_empty() {
+                out.push_str("<p>");
+                escape_into(text, out);
+                out.push_str("</p>\n");
+            }
+        }
+    }
+}
+
+fn write_list(items: &[ListItem], out: &mut String) {
+    out.push_str("<ul>\n");
+    for item in items {
+        out.push_str("<li>");
+        write_inlines(&item.content, out);
+        if !item.children.is_empty() {
+            out.push('\n');
+            for child in &item.children {
+                write_node(child, out);
+            }
+        }
+        out.push_str("</li>\n");
+    }
+    out.push_str("</ul>\n");
+}
+
+/// 输出块内的行内内容，去掉首尾空白（CommonMark 同样会去掉）
+fn write_inlines(inlines: &[Inline], out: &mut String) {
+    let mut html = String::new();
+    for inline in inlines {
+        match inline {
+            Inline::Text(text) => escape_into(text, &mut html),
+            Inline::Bold(text) => {
+                html.push_str("<strong>");
+                escape_into(text, &mut html);
+                html.push_str("</strong>");
+            }
+            Inline::Italic(text) => {
+                html.push_str("<em>");
+                escape_into(text, &mut html);
+                html.push_str("</em>");
+            }
+            Inline::Code(text) => {
+                html.push_str("<code>");
+                escape_into(text, &mut html);
+                html.push_str("</code>");
+            }
+            Inline::Link { text, url } => {
+                html.push_str("<a href=\"");
+                escape_into(url, &mut html);
+                html.push_str("\">");
+                escape_into(text, &mut html);
+                html.push_str("</a>");
+            }
+        }
+    }
+    out.push_str(html.trim());
+}
+
+fn escape_into(text: &str, out: &mut String) {
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            ch => out.push(ch),
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use crate::token::MarkdownToken;
-use lexer_framework::{DefaultContext, LexContext, LexingRule};
+use lexer_framework::{DefaultContext, Diagnostic, LexContext, LexingRule};
 
 /// 匹配 # 符号（标题）
 pub struct HashRule;
@@ -19,6 +19,14 @@ impl LexingRule<DefaultContext, MarkdownToken> for HashRule {
             ctx.advance();
         }
 
+        // 超过 6 个 # 仍可解析，但标题最多只有 6 级
+        if count == 6 && ctx.peek() == Some('#') {
+            ctx.report(Diagnostic::warning(
+                (position, ctx.position()),
+                "more than 6 `#`s; headings only go down to level 6",
+            ));
+        }
+
         if count > 0 {
             Some(MarkdownToken::Hash { count, position })
         } else {
@@ -31,6 +39,43 @@ impl LexingRule<DefaultContext, MarkdownToken> for HashRule {
     }
 }
 
+/// 匹配行首缩进（用于嵌套列表等上下文相关的结构）
+pub struct IndentRule;
+
+impl LexingRule<DefaultContext, MarkdownToken> for IndentRule {
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(matches!(first_char, Some(' ' | '\t')))
+    }
+
+    fn try_match(&mut self, ctx: &mut DefaultContext) -> Option<MarkdownToken> {
+        // 只有行首的空白才是缩进
+        if ctx.position().column != 1 {
+            return None;
+        }
+
+        let position = ctx.position();
+        let mut width = 0;
+        while let Some(ch) = ctx.peek() {
+            match ch {
+                ' ' => width += 1,
+                '\t' => width += 4,
+                _ => break,
+            }
+            ctx.advance();
+        }
+
+        if width > 0 {
+            Some(MarkdownToken::Indent { width, position })
+        } else {
+            None
+        }
+    }
+
+    fn priority(&self) -> i32 {
+        55
+    }
+}
+
 /// 匹配换行符
 pub struct NewlineRule;
 
@@ -226,9 +271,9 @@ impl LexingRule<DefaultContext, MarkdownToken> for TextRule {
             )
         });
 
-        if !text.as_ref().is_empty() {
+        if !text.is_empty() {
             Some(MarkdownToken::Text {
-                content: text.as_ref().to_string(),
+                content: text.to_string(),
                 position,
             })
         } else {
@@ -264,6 +309,7 @@ impl LexingRule<DefaultContext, MarkdownToken> for EofRule {
 pub fn build_lexer_rules() -> Vec<Box<dyn LexingRule<DefaultContext, MarkdownToken>>> {
     vec![
         Box::new(HashRule),
+        Box::new(IndentRule),
         Box::new(BacktickRule),
         Box::new(NewlineRule),
         Box::new(StarRule),
@@ -1,13 +1,17 @@
 pub mod ast;
+pub mod html;
 pub mod lexer_rules;
 pub mod parser_rules;
 pub mod renderer;
+pub mod spec;
 pub mod state;
 pub mod token;
 
-pub use ast::{Inline, MarkdownNode};
+pub use ast::{Inline, ListItem, MarkdownNode};
+pub use html::to_html;
 pub use lexer_rules::build_lexer_rules;
 pub use parser_rules::build_parser_rules;
-pub use renderer::{MarkdownRenderer, RenderItem, RenderResult};
+pub use renderer::{MarkdownRenderer, RenderItem, RenderListItem, RenderResult};
+pub use spec::{run_spec_examples, run_spec_suite, SpecExample, SpecFailure, SpecReport};
 pub use state::ContentState;
 pub use token::MarkdownToken;
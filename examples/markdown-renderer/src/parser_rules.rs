@@ -1,6 +1,7 @@
-use crate::ast::{Inline, MarkdownNode};
+use crate::ast::{Inline, ListItem, MarkdownNode};
 use crate::state::ContentState;
 use crate::token::MarkdownToken;
+use lexer_framework::LexToken;
 use parser_framework::{DefaultContext, ParseContext, ParsingRule};
 
 /// 标题解析规则
@@ -139,6 +140,10 @@ impl ParsingRule<DefaultContext<MarkdownToken>, MarkdownToken, MarkdownNode> for
                     code.push('\n');
                     ctx.advance();
                 }
+                MarkdownToken::Indent { width, .. } => {
+                    code.push_str(&" ".repeat(*width));
+                    ctx.advance();
+                }
                 MarkdownToken::Eof { .. } => break,
                 _ => {
                     // 其他token也作为代码内容
@@ -168,87 +173,141 @@ impl ParsingRule<DefaultContext<MarkdownToken>, MarkdownToken, MarkdownNode> for
 }
 
 /// 列表解析规则
+///
+/// 通过行首的 `Indent` token 追踪缩进层级：缩进更深的列表项构成上一项的子列表，
+/// 缩进回退时结束当前层级。
 pub struct ListRule;
 
-impl ParsingRule<DefaultContext<MarkdownToken>, MarkdownToken, MarkdownNode> for ListRule {
-    fn quick_check(&self, current_token: Option<&MarkdownToken>) -> Option<bool> {
-        Some(matches!(
-            current_token,
+impl ListRule {
+    fn is_marker(token: Option<&MarkdownToken>) -> bool {
+        matches!(
+            token,
             Some(MarkdownToken::Dash { .. } | MarkdownToken::Star { count: 1, .. })
-        ))
+        )
     }
 
-    fn try_parse(&mut self, ctx: &mut DefaultContext<MarkdownToken>) -> Option<MarkdownNode> {
-        let checkpoint = ctx.checkpoint();
-        let position = ctx.position();
-
-        // 检查是否以-或*开头（列表标记）
-        let is_list_marker = matches!(
-            ctx.peek(),
-            Some(MarkdownToken::Dash { .. } | MarkdownToken::Star { count: 1, .. })
-        );
-
-        if !is_list_marker {
-            ctx.restore(checkpoint);
-            return None;
-        }
-        ctx.advance();
-
-        // 跳过空格
-        if matches!(ctx.peek(), Some(MarkdownToken::Text { content, .. }) if content.trim().is_empty())
-        {
-            ctx.advance();
+    /// 检查下一行是否以列表标记开头，返回该行的缩进宽度
+    fn next_line_indent(ctx: &mut DefaultContext<MarkdownToken>) -> Option<usize> {
+        match ctx.peek() {
+            Some(MarkdownToken::Indent { width, .. }) => {
+                let width = *width;
+                Self::is_marker(ctx.peek_at(1)).then_some(width)
+            }
+            token if Self::is_marker(token) => Some(0),
+            _ => None,
         }
+    }
 
-        // 收集列表项
+    /// 解析同一缩进层级的所有列表项，调用时 ctx 位于第一个列表标记处
+    fn parse_items(
+        ctx: &mut DefaultContext<MarkdownToken>,
+        indent: usize,
+    ) -> (Vec<ListItem>, bool) {
         let mut items = Vec::new();
-        let mut current_item = Vec::new();
         let mut has_newline = false;
 
-        while let Some(token) = ctx.peek() {
-            match token {
-                MarkdownToken::Newline { .. } => {
-                    has_newline = true;
-                    ctx.advance();
-                    if !current_item.is_empty() {
-                        items.push(std::mem::take(&mut current_item));
-                    }
-                    // 检查下一个是否是列表项
-                    if !matches!(
-                        ctx.peek(),
-                        Some(MarkdownToken::Dash { .. } | MarkdownToken::Star { count: 1, .. })
-                    ) {
+        'items: loop {
+            // 跳过列表标记和其后的空格
+            ctx.advance();
+            if matches!(ctx.peek(), Some(MarkdownToken::Text { content, .. }) if content.trim().is_empty())
+            {
+                ctx.advance();
+            }
+
+            // 收集本行内容
+            let mut item = ListItem {
+                content: Vec::new(),
+                children: Vec::new(),
+            };
+            let mut line_ended = false;
+            while let Some(token) = ctx.peek() {
+                match token {
+                    MarkdownToken::Newline { .. } => {
+                        ctx.advance();
+                        line_ended = true;
                         break;
                     }
-                    // 是列表项，继续
-                    ctx.advance();
-                    // 跳过空格
-                    if matches!(ctx.peek(), Some(MarkdownToken::Text { content, .. }) if content.trim().is_empty())
-                    {
+                    MarkdownToken::Text { content, .. } => {
+                        item.content.push(Inline::Text(content.clone()));
+                        ctx.advance();
+                    }
+                    MarkdownToken::Eof { .. } => break,
+                    _ => {
                         ctx.advance();
                     }
                 }
-                MarkdownToken::Text { content, .. } => {
-                    current_item.push(Inline::Text(content.clone()));
-                    ctx.advance();
+            }
+
+            if !line_ended {
+                if !item.content.is_empty() {
+                    items.push(item);
                 }
-                MarkdownToken::Eof { .. } => {
-                    if !current_item.is_empty() {
-                        items.push(std::mem::take(&mut current_item));
+                break;
+            }
+            has_newline = true;
+
+            // 根据下一行的缩进决定：子列表、同级项，或者结束当前层级
+            loop {
+                match Self::next_line_indent(ctx) {
+                    Some(width) if width > indent => {
+                        // 子列表的位置取自缩进 token
+                        let position = ctx
+                            .advance()
+                            .and_then(|token| token.position())
+                            .unwrap_or_else(|| ctx.position());
+                        let (children, child_complete) = Self::parse_items(ctx, width);
+                        if !children.is_empty() {
+                            item.children.push(MarkdownNode::List {
+                                items: children,
+                                position,
+                                state: if child_complete {
+                                    ContentState::Complete
+                                } else {
+                                    ContentState::Incomplete
+                                },
+                            });
+                        }
+                    }
+                    Some(width) if width == indent => {
+                        if width > 0 {
+                            ctx.advance(); // 跳过缩进
+                        }
+                        if !item.content.is_empty() || !item.children.is_empty() {
+                            items.push(item);
+                        }
+                        continue 'items;
+                    }
+                    _ => {
+                        if !item.content.is_empty() || !item.children.is_empty() {
+                            items.push(item);
+                        }
+                        break 'items;
                     }
-                    break;
-                }
-                _ => {
-                    ctx.advance();
                 }
             }
         }
 
-        // 如果循环结束时还有未完成的项，添加它
-        if !current_item.is_empty() {
-            items.push(current_item);
+        (items, has_newline)
+    }
+}
+
+impl ParsingRule<DefaultContext<MarkdownToken>, MarkdownToken, MarkdownNode> for ListRule {
+    fn quick_check(&self, current_token: Option<&MarkdownToken>) -> Option<bool> {
+        Some(Self::is_marker(current_token))
+    }
+
+    fn try_parse(&mut self, ctx: &mut DefaultContext<MarkdownToken>) -> Option<MarkdownNode> {
+        let checkpoint = ctx.checkpoint();
+        let position = ctx.position();
+
+        // 检查是否以-或*开头（列表标记）
+        if !Self::is_marker(ctx.peek()) {
+            ctx.restore(checkpoint);
+            return None;
         }
 
+        let (items, has_newline) = Self::parse_items(ctx, 0);
+
         if items.is_empty() {
             ctx.restore(checkpoint);
             return None;
@@ -285,6 +344,7 @@ impl ParsingRule<DefaultContext<MarkdownToken>, MarkdownToken, MarkdownNode> for
         while let Some(token) = ctx.peek() {
             let is_whitespace = match token {
                 MarkdownToken::Text { content, .. } => content.trim().is_empty(),
+                MarkdownToken::Indent { .. } => true,
                 MarkdownToken::Newline { .. } => {
                     // 空行表示段落结束
                     break;
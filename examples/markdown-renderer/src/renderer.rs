@@ -1,4 +1,4 @@
-use crate::ast::{Inline, MarkdownNode};
+use crate::ast::{Inline, ListItem, MarkdownNode};
 use crate::lexer_rules::build_lexer_rules;
 use crate::parser_rules::build_parser_rules;
 use crate::state::ContentState;
@@ -82,18 +82,11 @@ impl MarkdownRenderer {
                 } => {
                     match state {
                         ContentState::Complete => {
-                            let texts: Vec<String> =
-                                list_items.iter().map(|item| inline_to_text(item)).collect();
-                            items.push(RenderItem::List(texts));
+                            items.push(RenderItem::List(render_list_items(list_items)));
                         }
                         ContentState::Incomplete => {
                             // 显示原始文本
-                            for item in list_items {
-                                items.push(RenderItem::RawText(format!(
-                                    "- {}",
-                                    inline_to_text(item)
-                                )));
-                            }
+                            push_raw_list_items(&mut items, list_items, 0);
                         }
                     }
                 }
@@ -150,7 +143,7 @@ pub enum RenderItem {
         text: String,
     },
     Paragraph(String),
-    List(Vec<String>),
+    List(Vec<RenderListItem>),
     CodeBlock {
         language: Option<String>,
         code: String,
@@ -158,6 +151,45 @@ pub enum RenderItem {
     RawText(String), // 未确定的内容，显示原始文本
 }
 
+/// 渲染后的列表项，`children` 为嵌套的子列表项
+#[derive(Debug, Clone)]
+pub struct RenderListItem {
+    pub text: String,
+    pub children: Vec<RenderListItem>,
+}
+
+fn render_list_items(list_items: &[ListItem]) -> Vec<RenderListItem> {
+    list_items
+        .iter()
+        .map(|item| RenderListItem {
+            text: inline_to_text(&item.content),
+            children: item
+                .children
+                .iter()
+                .flat_map(|child| match child {
+                    MarkdownNode::List { items, .. } => render_list_items(items),
+                    _ => Vec::new(),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn push_raw_list_items(items: &mut Vec<RenderItem>, list_items: &[ListItem], depth: usize) {
+    for item in list_items {
+        items.push(RenderItem::RawText(format!(
+            "{}- {}",
+            "  ".repeat(depth),
+            inline_to_text(&item.content)
+        )));
+        for child in &item.children {
+            if let MarkdownNode::List { items: nested, .. } = child {
+                push_raw_list_items(items, nested, depth + 1);
+            }
+        }
+    }
+}
+
 fn inline_to_text(inlines: &[Inline]) -> String {
     inlines
         .iter()
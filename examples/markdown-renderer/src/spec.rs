@@ -0,0 +1,118 @@
+use crate::html::to_html;
+use crate::renderer::MarkdownRenderer;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// CommonMark 规范测试中的一个示例（`spec.json` 数组的一项）
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpecExample {
+    pub markdown: String,
+    pub html: String,
+    pub example: u32,
+    pub section: String,
+}
+
+/// 一个未通过的示例：输入、期望输出和实际输出
+#[derive(Debug, Clone)]
+pub struct SpecFailure {
+    pub example: u32,
+    pub section: String,
+    pub markdown: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// 规范测试的结果：总数、各章节的通过情况和未通过的示例
+#[derive(Debug, Clone, Default)]
+pub struct SpecReport {
+    pub passed: usize,
+    pub total: usize,
+    /// 章节名 -> (通过数, 总数)
+    pub sections: BTreeMap<String, (usize, usize)>,
+    pub failures: Vec<SpecFailure>,
+}
+
+impl SpecReport {
+    /// 返回未通过的示例数
+    pub fn failed(&self) -> usize {
+        self.total - self.passed
+    }
+
+    /// 返回通过率（0.0 到 1.0）
+    pub fn pass_rate(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.passed as f64 / self.total as f64
+        }
+    }
+}
+
+impl fmt::Display for SpecReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (section, (passed, total)) in &self.sections {
+            writeln!(f, "{:<40} {:>4}/{:<4}", section, passed, total)?;
+        }
+        writeln!(
+            f,
+            "{} of {} examples passed ({:.1}%)",
+            self.passed,
+            self.total,
+            self.pass_rate() * 100.0
+        )
+    }
+}
+
+/// 读取 CommonMark 的 `spec.json`（可由 `python3 test/spec_tests.py --dump-tests`
+/// 生成），逐个运行示例并统计通过情况
+pub fn run_spec_suite(path: impl AsRef<Path>) -> io::Result<SpecReport> {
+    let json = fs::read_to_string(path)?;
+    let examples: Vec<SpecExample> = serde_json::from_str(&json)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    Ok(run_spec_examples(&examples))
+}
+
+/// 运行已加载的示例：解析 Markdown、渲染为 HTML，并与期望输出比较
+pub fn run_spec_examples(examples: &[SpecExample]) -> SpecReport {
+    let mut renderer = MarkdownRenderer::new();
+    let mut report = SpecReport::default();
+
+    for example in examples {
+        let actual = to_html(&renderer.parse(&example.markdown));
+        let passed = normalize_html(&actual) == normalize_html(&example.html);
+
+        let section = report
+            .sections
+            .entry(example.section.clone())
+            .or_insert((0, 0));
+        section.1 += 1;
+        report.total += 1;
+        if passed {
+            section.0 += 1;
+            report.passed += 1;
+        } else {
+            report.failures.push(SpecFailure {
+                example: example.example,
+                section: example.section.clone(),
+                markdown: example.markdown.clone(),
+                expected: example.html.clone(),
+                actual,
+            });
+        }
+    }
+
+    report
+}
+
+/// 与规范测试脚本一样忽略块级元素之间的空白差异
+fn normalize_html(html: &str) -> String {
+    html.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -7,6 +7,7 @@ pub enum MarkdownToken {
     Hash { count: usize, position: Position }, // #, ##, ...
     Newline { position: Position },
     Text { content: String, position: Position },
+    Indent { width: usize, position: Position }, // 行首缩进（制表符按4列计算）
 
     // 列表
     Dash { position: Position },     // -
@@ -34,6 +35,7 @@ impl LexToken for MarkdownToken {
             MarkdownToken::Hash { position, .. }
             | MarkdownToken::Newline { position }
             | MarkdownToken::Text { position, .. }
+            | MarkdownToken::Indent { position, .. }
             | MarkdownToken::Dash { position }
             | MarkdownToken::Asterisk { position }
             | MarkdownToken::Backtick { position, .. }
@@ -55,11 +57,7 @@ impl LexToken for MarkdownToken {
         matches!(self, MarkdownToken::Newline { .. })
     }
 
-    fn is_whitespace(&self) -> bool {
-        false
-    }
-
     fn is_indent(&self) -> bool {
-        false
+        matches!(self, MarkdownToken::Indent { .. })
     }
 }
@@ -0,0 +1,69 @@
+//! Nested list parsing tests.
+
+use markdown_renderer::{Inline, ListItem, MarkdownNode, MarkdownRenderer};
+
+/// Writes lists as `[item [child] item]`, other nodes by kind.
+fn outline(nodes: &[MarkdownNode]) -> Vec<String> {
+    nodes.iter().map(node_outline).collect()
+}
+
+fn node_outline(node: &MarkdownNode) -> String {
+    match node {
+        MarkdownNode::List { items, .. } => {
+            let items: Vec<String> = items.iter().map(item_outline).collect();
+            format!("[{}]", items.join(" "))
+        }
+        MarkdownNode::Paragraph { content, .. } => format!("p({})", text(content)),
+        other => format!("{other:?}"),
+    }
+}
+
+fn item_outline(item: &ListItem) -> String {
+    let mut out = text(&item.content);
+    for child in &item.children {
+        out.push(' ');
+        out.push_str(&node_outline(child));
+    }
+    out
+}
+
+fn text(content: &[Inline]) -> String {
+    content
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text(text) => text.trim().to_string(),
+            other => format!("{other:?}"),
+        })
+        .collect::<Vec<_>>()
+        .join("")
+}
+
+fn parse(input: &str) -> Vec<String> {
+    outline(&MarkdownRenderer::new().parse(input))
+}
+
+#[test]
+fn test_two_space_nesting() {
+    assert_eq!(parse("- a\n  - b\n  - c\n- d\n"), ["[a [b c] d]"]);
+}
+
+#[test]
+fn test_four_space_nesting() {
+    assert_eq!(
+        parse("- a\n    - b\n        - c\n    - d\n- e\n"),
+        ["[a [b [c] d] e]"]
+    );
+}
+
+#[test]
+fn test_dedent_returns_to_parent() {
+    // `d` closes both nested levels and continues the top-level list.
+    assert_eq!(parse("- a\n  - b\n    - c\n- d\n"), ["[a [b [c]] d]"]);
+}
+
+#[test]
+fn test_mixed_markers() {
+    // `-` and `*` items share a list; ordered items are not supported, so
+    // `1.` ends the list and starts a paragraph.
+    assert_eq!(parse("- a\n* b\n  - c\n1. x\n"), ["[a b [c]]", "p(1. x)"]);
+}
@@ -1,26 +1,19 @@
-use common_framework::{Inbound, Outbound, Position, StreamingSignal};
-use lexer_framework::{
-    LexContext, LexToken, Lexer, LexingRule, StreamingLexContext, TokenProducer,
-};
-use parser_framework::{AstNode, ParseContext, Parser, ParsingRule, StreamingParseContext};
-use pipeline_core::Pipeline;
+use common_framework::Position;
+use lexer_framework::{LexContext, LexToken, LexingRule, StreamingLexContext};
+use parser_framework::{AstNode, ParseContext, ParsingRule, StreamingParseContext};
+use pipeline_core::PipelineBuilder;
 
 fn main() {
     let input = "3 + 4 * (2 - 1) / 5";
     println!("Input: {input}");
 
-    let lexer_rules = build_lexer_rules();
-    let parser_rules = build_parser_rules();
-
-    // Create lexer with streaming context, similar to parser
-    let lexer_context = StreamingLexContext::from(input);
-    let lexer = FilteringTokenProducer::new(Lexer::new(lexer_context, lexer_rules));
-
-    // Create parser with streaming context
-    let parser = Parser::new(StreamingParseContext::new(), parser_rules);
-
-    let pipeline = Pipeline::new(lexer, parser);
-    let asts = pipeline.run();
+    let asts = PipelineBuilder::new()
+        .input(input)
+        .lexer(build_lexer_rules())
+        .filter(|token: &CalcToken| !token.is_whitespace())
+        .parser(build_parser_rules())
+        .build()
+        .run();
 
     println!("ASTs produced by the streaming pipeline:");
     for (idx, ast) in asts.iter().enumerate() {
@@ -69,10 +62,6 @@ impl LexToken for CalcToken {
     fn is_whitespace(&self) -> bool {
         matches!(self, CalcToken::Whitespace { .. })
     }
-
-    fn is_indent(&self) -> bool {
-        false
-    }
 }
 
 fn build_lexer_rules() -> Vec<Box<dyn LexingRule<StreamingLexContext, CalcToken>>> {
@@ -235,6 +224,11 @@ where
     fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Expr> {
         let checkpoint = ctx.checkpoint();
         match parse_expression(ctx, 0) {
+            // Streaming: the expression may still continue once more tokens arrive.
+            Some(_) if ctx.peek().is_none() && !ctx.is_eof() => {
+                ctx.restore(checkpoint);
+                None
+            }
             Some(expr) => Some(expr),
             None => {
                 ctx.restore(checkpoint);
@@ -311,64 +305,3 @@ fn binary_op_from_token(token: &CalcToken) -> Option<BinaryOp> {
         _ => None,
     }
 }
-
-/// A token producer that filters out whitespace tokens.
-/// This is a convenience wrapper that uses `LexToken::is_whitespace()` to filter tokens.
-///
-/// Note: This is a separate concern from streaming contexts. It filters tokens
-/// after they are produced, which is useful when you want to skip whitespace
-/// tokens in the pipeline.
-pub struct FilteringTokenProducer<L> {
-    inner: L,
-}
-
-impl<L> FilteringTokenProducer<L> {
-    /// Creates a new filtering token producer that wraps the given producer.
-    pub fn new(inner: L) -> Self {
-        Self { inner }
-    }
-}
-
-impl<L, Tok> TokenProducer<Tok> for FilteringTokenProducer<L>
-where
-    L: TokenProducer<Tok>,
-    Tok: LexToken,
-{
-    fn poll_token(&mut self) -> Option<Tok> {
-        while let Some(token) = self.inner.poll_token() {
-            if !token.is_whitespace() {
-                return Some(token);
-            }
-        }
-        None
-    }
-}
-
-impl<L, Tok, Ast> Outbound<Tok, Ast> for FilteringTokenProducer<L>
-where
-    L: Outbound<Tok, Ast>,
-    Tok: LexToken,
-{
-    fn next_signal(&mut self) -> Option<StreamingSignal<Tok, Ast>> {
-        while let Some(signal) = self.inner.next_signal() {
-            match signal {
-                StreamingSignal::SupplyToken(token) => {
-                    if !token.is_whitespace() {
-                        return Some(StreamingSignal::SupplyToken(token));
-                    }
-                }
-                other => return Some(other),
-            }
-        }
-        None
-    }
-}
-
-impl<L, Tok, Ast> Inbound<Tok, Ast> for FilteringTokenProducer<L>
-where
-    L: Inbound<Tok, Ast>,
-{
-    fn handle_signal(&mut self, signal: StreamingSignal<Tok, Ast>) {
-        self.inner.handle_signal(signal);
-    }
-}
@@ -0,0 +1,38 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use sexpr::parse;
+
+fn nested(depth: usize) -> String {
+    format!("{}x{}", "(".repeat(depth), ")".repeat(depth))
+}
+
+fn wide(forms: usize) -> String {
+    "(define (f a b) (+ (* a a) (* b b) 1.5 \"s\"))\n".repeat(forms)
+}
+
+fn bench_deep_nesting(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sexpr_deep_nesting");
+    for depth in [100, 1_000, 10_000] {
+        let input = nested(depth);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::new("parse", depth), &input, |b, input| {
+            b.iter(|| parse(input))
+        });
+
+        let program = parse(&input);
+        group.bench_with_input(BenchmarkId::new("walk", depth), &program, |b, program| {
+            b.iter(|| program.max_depth())
+        });
+    }
+    group.finish();
+}
+
+fn bench_wide(c: &mut Criterion) {
+    let input = wide(1_000);
+    let mut group = c.benchmark_group("sexpr_wide");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("parse_1000_forms", |b| b.iter(|| parse(&input)));
+    group.finish();
+}
+
+criterion_group!(benches, bench_deep_nesting, bench_wide);
+criterion_main!(benches);
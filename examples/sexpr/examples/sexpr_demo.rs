@@ -0,0 +1,45 @@
+use sexpr::parse;
+
+const SOURCE: &str = r#"
+; Factorial, with a quoted list and a string.
+(define (fact n)
+  (if (<= n 1)
+      1
+      (* n (fact (- n 1)))))
+
+(display "fact 5 =" (fact 5))
+'(1 2.5 three)
+
+(unclosed (list
+"#;
+
+fn main() {
+    let program = parse(SOURCE);
+
+    println!("Forms:");
+    for &form in &program.forms {
+        println!("  {}", program.print(form));
+    }
+
+    println!("\nArena nodes: {}", program.arena.len());
+    println!("Max nesting depth: {}", program.max_depth());
+    if let Some(id) = program.find_symbol("fact") {
+        let position = program.arena[id].position;
+        println!("First `fact` at {}:{}", position.line, position.column);
+    }
+
+    println!("\nErrors:");
+    for (message, position) in &program.errors {
+        println!("  {} at {}:{}", message, position.line, position.column);
+    }
+
+    // Deep nesting: the parser and the visitors use explicit stacks.
+    let depth = 100_000;
+    let deep = format!("{}x{}", "(".repeat(depth), ")".repeat(depth));
+    let program = parse(&deep);
+    println!(
+        "\nParsed {} nested lists, depth {}",
+        depth,
+        program.max_depth()
+    );
+}
@@ -0,0 +1,50 @@
+use common_framework::Position;
+use parser_framework::{ArenaNode, AstNode, NodeId};
+
+/// An S-expression stored in an [`Arena`](parser_framework::Arena).
+/// Lists refer to their elements by id, so nesting depth costs no boxing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sexpr {
+    Number(f64),
+    String(String),
+    Symbol(String),
+    List(Vec<NodeId<Node>>),
+}
+
+/// An arena node: an S-expression and where it starts in the source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub expr: Sexpr,
+    pub position: Position,
+}
+
+impl ArenaNode for Node {
+    fn children(&self) -> &[NodeId<Node>] {
+        match &self.expr {
+            Sexpr::List(items) => items,
+            _ => &[],
+        }
+    }
+}
+
+/// What the parser emits for each top-level form. The form itself lives in
+/// the arena; this is only a handle to it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TopLevel {
+    Datum {
+        root: NodeId<Node>,
+        position: Position,
+    },
+    Error {
+        message: String,
+        position: Position,
+    },
+}
+
+impl AstNode for TopLevel {
+    fn position(&self) -> Option<Position> {
+        match self {
+            TopLevel::Datum { position, .. } | TopLevel::Error { position, .. } => Some(*position),
+        }
+    }
+}
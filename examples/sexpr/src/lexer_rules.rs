@@ -0,0 +1,165 @@
+use crate::token::SexprToken;
+use lexer_framework::{decode_escape, LexContext, LexingRule};
+
+/// Characters that end a symbol or number.
+fn is_delimiter(ch: char) -> bool {
+    ch.is_whitespace() || matches!(ch, '(' | ')' | '\'' | '"' | ';')
+}
+
+/// Matches `(`, `)` and `'`.
+pub struct PunctuationRule;
+
+impl<Ctx> LexingRule<Ctx, SexprToken> for PunctuationRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(matches!(first_char, Some('(' | ')' | '\'')))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<SexprToken> {
+        let position = ctx.position();
+        let token = match ctx.advance()? {
+            '(' => SexprToken::LeftParen { position },
+            ')' => SexprToken::RightParen { position },
+            '\'' => SexprToken::Quote { position },
+            _ => return None,
+        };
+        Some(token)
+    }
+
+    fn priority(&self) -> i32 {
+        20
+    }
+}
+
+/// Matches double-quoted strings with JSON-style escapes.
+pub struct StringRule;
+
+impl<Ctx> LexingRule<Ctx, SexprToken> for StringRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char == Some('"'))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<SexprToken> {
+        let position = ctx.position();
+        if ctx.advance()? != '"' {
+            return None;
+        }
+
+        let mut value = String::new();
+        loop {
+            match ctx.peek() {
+                None => {
+                    return Some(SexprToken::Invalid {
+                        message: "unterminated string".to_string(),
+                        position,
+                    })
+                }
+                Some('"') => {
+                    ctx.advance();
+                    return Some(SexprToken::String { value, position });
+                }
+                Some('\\') => match decode_escape(ctx) {
+                    Ok(ch) => value.push(ch),
+                    Err(err) => {
+                        ctx.consume_while(|ch| ch != '"');
+                        ctx.advance();
+                        return Some(SexprToken::Invalid {
+                            message: err.kind.to_string(),
+                            position: err.position,
+                        });
+                    }
+                },
+                Some(ch) => {
+                    value.push(ch);
+                    ctx.advance();
+                }
+            }
+        }
+    }
+
+    fn priority(&self) -> i32 {
+        15
+    }
+}
+
+/// Matches symbols and numbers: any run of non-delimiter characters. Runs
+/// that parse as a number (`42`, `-1.5`) become numbers.
+pub struct AtomRule;
+
+impl<Ctx> LexingRule<Ctx, SexprToken> for AtomRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char.is_some_and(|ch| !is_delimiter(ch)))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<SexprToken> {
+        let position = ctx.position();
+        let text = ctx.consume_while(|ch| !is_delimiter(ch));
+        if text.is_empty() {
+            return None;
+        }
+        // Symbols such as `+`, `-` or `inf` must not be read as numbers.
+        let numeric = text
+            .trim_start_matches(['+', '-'])
+            .starts_with(|ch: char| ch.is_ascii_digit() || ch == '.');
+        match text.parse::<f64>() {
+            Ok(value) if numeric => Some(SexprToken::Number { value, position }),
+            _ => Some(SexprToken::Symbol {
+                name: text.to_string(),
+                position,
+            }),
+        }
+    }
+
+    fn priority(&self) -> i32 {
+        10
+    }
+}
+
+/// Matches whitespace and `;` line comments.
+pub struct TriviaRule;
+
+impl<Ctx> LexingRule<Ctx, SexprToken> for TriviaRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char.is_some_and(|ch| ch.is_whitespace() || ch == ';'))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<SexprToken> {
+        let position = ctx.position();
+        if ctx.peek()? == ';' {
+            ctx.consume_while(|ch| ch != '\n');
+            return Some(SexprToken::Comment { position });
+        }
+        if ctx.consume_while(char::is_whitespace).is_empty() {
+            return None;
+        }
+        Some(SexprToken::Whitespace { position })
+    }
+
+    fn priority(&self) -> i32 {
+        5
+    }
+}
+
+/// Builds the S-expression lexer rules.
+pub fn build_lexer_rules<Ctx>() -> Vec<Box<dyn LexingRule<Ctx, SexprToken>>>
+where
+    Ctx: LexContext,
+{
+    vec![
+        Box::new(PunctuationRule),
+        Box::new(StringRule),
+        Box::new(AtomRule),
+        Box::new(TriviaRule),
+    ]
+}
@@ -0,0 +1,90 @@
+//! S-expression example built on the arena and visitor APIs.
+//!
+//! Forms are parsed into an [`Arena`] of [`Node`]s whose lists hold child
+//! [`NodeId`]s instead of boxes; the parser only emits a [`TopLevel`] handle
+//! per form. Everything downstream (printing, depth measurement, search) is
+//! written as a [`Visitor`](parser_framework::Visitor) driven by
+//! [`walk`], which is iterative, so very deep nesting is fine.
+
+pub mod ast;
+pub mod lexer_rules;
+pub mod parser_rules;
+pub mod token;
+pub mod visitors;
+
+pub use ast::{Node, Sexpr, TopLevel};
+pub use lexer_rules::build_lexer_rules;
+pub use parser_rules::{build_parser_rules, SharedArena};
+pub use token::SexprToken;
+pub use visitors::{DepthCounter, FindSymbol, Printer};
+
+use common_framework::Position;
+use parser_framework::{walk, Arena, NodeId};
+use pipeline_core::BatchPipeline;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A parsed source file: the arena, its top-level forms and any errors.
+pub struct Program {
+    pub arena: Arena<Node>,
+    pub forms: Vec<NodeId<Node>>,
+    pub errors: Vec<(String, Position)>,
+}
+
+impl Program {
+    /// Renders a form as canonical S-expression text.
+    pub fn print(&self, id: NodeId<Node>) -> String {
+        let mut printer = Printer::default();
+        walk(&self.arena, id, &mut printer);
+        printer.output
+    }
+
+    /// Returns the deepest list nesting across all forms.
+    pub fn max_depth(&self) -> usize {
+        let mut counter = DepthCounter::default();
+        for &form in &self.forms {
+            walk(&self.arena, form, &mut counter);
+        }
+        counter.max_depth
+    }
+
+    /// Returns the first occurrence of the symbol `name`, if any.
+    pub fn find_symbol(&self, name: &str) -> Option<NodeId<Node>> {
+        let mut finder = FindSymbol { name, found: None };
+        for &form in &self.forms {
+            if !walk(&self.arena, form, &mut finder) {
+                break;
+            }
+        }
+        finder.found
+    }
+}
+
+/// Parses `input` into a [`Program`].
+pub fn parse(input: &str) -> Program {
+    let arena: SharedArena = Rc::new(RefCell::new(Arena::new()));
+    let nodes = BatchPipeline::<SexprToken, TopLevel>::run(
+        input,
+        build_lexer_rules(),
+        build_parser_rules(&arena),
+    );
+
+    let mut forms = Vec::new();
+    let mut errors = Vec::new();
+    for node in nodes {
+        match node {
+            TopLevel::Datum { root, .. } => forms.push(root),
+            TopLevel::Error { message, position } => errors.push((message, position)),
+        }
+    }
+
+    // The parser (and with it the rule's handle) is gone by now.
+    let arena = Rc::try_unwrap(arena)
+        .map(RefCell::into_inner)
+        .unwrap_or_else(|shared| shared.borrow().clone());
+    Program {
+        arena,
+        forms,
+        errors,
+    }
+}
@@ -0,0 +1,174 @@
+use crate::ast::{Node, Sexpr, TopLevel};
+use crate::token::SexprToken;
+use common_framework::Position;
+use lexer_framework::LexToken;
+use parser_framework::{Arena, NodeId, ParseContext, ParsingRule};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// An arena shared between the parser rule and the code that reads the
+/// result once parsing is done.
+pub type SharedArena = Rc<RefCell<Arena<Node>>>;
+
+/// A partially parsed form.
+enum Frame {
+    List {
+        items: Vec<NodeId<Node>>,
+        position: Position,
+    },
+    Quote {
+        position: Position,
+    },
+}
+
+/// Parses one top-level form into the shared arena.
+///
+/// Nesting is tracked with an explicit stack rather than recursion, so deeply
+/// nested input cannot overflow the call stack. Lexical errors inside a form
+/// are reported once the form is complete, which keeps the following forms
+/// aligned.
+pub struct DatumRule {
+    pub arena: SharedArena,
+}
+
+impl<Ctx> ParsingRule<Ctx, SexprToken, TopLevel> for DatumRule
+where
+    Ctx: ParseContext<SexprToken>,
+{
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<TopLevel> {
+        skip_trivia(ctx);
+        let start = ctx.peek()?.position().unwrap_or_default();
+
+        let mut arena = self.arena.borrow_mut();
+        let mut stack: Vec<Frame> = Vec::new();
+        let mut error: Option<(String, Position)> = None;
+
+        loop {
+            skip_trivia(ctx);
+            let Some(token) = ctx.advance() else {
+                // End of input inside an open form.
+                let position = match stack.last() {
+                    Some(Frame::List { position, .. } | Frame::Quote { position }) => *position,
+                    None => start,
+                };
+                let message = match stack.last() {
+                    Some(Frame::Quote { .. }) => "expected a form after `'`",
+                    _ => "unclosed '('",
+                };
+                return Some(TopLevel::Error {
+                    message: message.to_string(),
+                    position,
+                });
+            };
+
+            let position = token.position().unwrap_or_default();
+            let mut done = match token {
+                SexprToken::LeftParen { position } => {
+                    stack.push(Frame::List {
+                        items: Vec::new(),
+                        position,
+                    });
+                    None
+                }
+                SexprToken::Quote { position } => {
+                    stack.push(Frame::Quote { position });
+                    None
+                }
+                SexprToken::RightParen { .. } => match stack.pop() {
+                    Some(Frame::List { items, position }) => Some(arena.alloc(Node {
+                        expr: Sexpr::List(items),
+                        position,
+                    })),
+                    Some(Frame::Quote { position }) => {
+                        return Some(TopLevel::Error {
+                            message: "expected a form after `'`".to_string(),
+                            position,
+                        })
+                    }
+                    None => {
+                        return Some(TopLevel::Error {
+                            message: "unexpected ')'".to_string(),
+                            position,
+                        })
+                    }
+                },
+                SexprToken::Number { value, .. } => Some(arena.alloc(Node {
+                    expr: Sexpr::Number(value),
+                    position,
+                })),
+                SexprToken::String { value, .. } => Some(arena.alloc(Node {
+                    expr: Sexpr::String(value),
+                    position,
+                })),
+                SexprToken::Symbol { name, .. } => Some(arena.alloc(Node {
+                    expr: Sexpr::Symbol(name),
+                    position,
+                })),
+                SexprToken::Invalid { message, .. } => {
+                    error.get_or_insert((message, position));
+                    if stack.is_empty() {
+                        break;
+                    }
+                    None
+                }
+                SexprToken::Whitespace { .. } | SexprToken::Comment { .. } => None,
+            };
+
+            // Hand the finished form to its enclosing frame; `'x` wraps into
+            // `(quote x)` as soon as `x` is complete.
+            while let Some(id) = done.take() {
+                match stack.last_mut() {
+                    None => {
+                        if error.is_none() {
+                            return Some(TopLevel::Datum {
+                                root: id,
+                                position: start,
+                            });
+                        }
+                    }
+                    Some(Frame::List { items, .. }) => items.push(id),
+                    Some(Frame::Quote { position }) => {
+                        let position = *position;
+                        stack.pop();
+                        let quote = arena.alloc(Node {
+                            expr: Sexpr::Symbol("quote".to_string()),
+                            position,
+                        });
+                        done = Some(arena.alloc(Node {
+                            expr: Sexpr::List(vec![quote, id]),
+                            position,
+                        }));
+                    }
+                }
+            }
+
+            if stack.is_empty() && error.is_some() {
+                break;
+            }
+        }
+
+        let (message, position) = error?;
+        Some(TopLevel::Error { message, position })
+    }
+}
+
+fn skip_trivia<Ctx>(ctx: &mut Ctx)
+where
+    Ctx: ParseContext<SexprToken>,
+{
+    while ctx.peek().is_some_and(SexprToken::is_trivia) {
+        ctx.advance();
+    }
+}
+
+/// Builds the S-expression parser rules, allocating into `arena`.
+pub fn build_parser_rules<Ctx>(
+    arena: &SharedArena,
+) -> Vec<Box<dyn ParsingRule<Ctx, SexprToken, TopLevel>>>
+where
+    Ctx: ParseContext<SexprToken>,
+{
+    vec![Box::new(DatumRule {
+        arena: Rc::clone(arena),
+    })]
+}
@@ -0,0 +1,71 @@
+use common_framework::Position;
+use lexer_framework::LexToken;
+
+/// S-expression tokens.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SexprToken {
+    LeftParen {
+        position: Position,
+    },
+    RightParen {
+        position: Position,
+    },
+    /// `'`, shorthand for `(quote ...)`.
+    Quote {
+        position: Position,
+    },
+    Number {
+        value: f64,
+        position: Position,
+    },
+    String {
+        value: String,
+        position: Position,
+    },
+    Symbol {
+        name: String,
+        position: Position,
+    },
+    Whitespace {
+        position: Position,
+    },
+    /// `; ...` up to the end of the line.
+    Comment {
+        position: Position,
+    },
+    /// Input no rule understands, e.g. an unterminated string.
+    Invalid {
+        message: String,
+        position: Position,
+    },
+}
+
+impl SexprToken {
+    /// Whitespace and comments, which the parser skips.
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self,
+            SexprToken::Whitespace { .. } | SexprToken::Comment { .. }
+        )
+    }
+}
+
+impl LexToken for SexprToken {
+    fn position(&self) -> Option<Position> {
+        Some(match self {
+            SexprToken::LeftParen { position }
+            | SexprToken::RightParen { position }
+            | SexprToken::Quote { position }
+            | SexprToken::Number { position, .. }
+            | SexprToken::String { position, .. }
+            | SexprToken::Symbol { position, .. }
+            | SexprToken::Whitespace { position }
+            | SexprToken::Comment { position }
+            | SexprToken::Invalid { position, .. } => *position,
+        })
+    }
+
+    fn is_whitespace(&self) -> bool {
+        matches!(self, SexprToken::Whitespace { .. })
+    }
+}
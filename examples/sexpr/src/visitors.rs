@@ -0,0 +1,80 @@
+//! Visitors over the arena tree, built on [`parser_framework::walk`].
+
+use crate::ast::{Node, Sexpr};
+use parser_framework::{NodeId, VisitControl, Visitor};
+
+/// Renders a form back to canonical S-expression text.
+#[derive(Default)]
+pub struct Printer {
+    pub output: String,
+    /// Whether the next element needs a separating space.
+    needs_space: bool,
+}
+
+impl Visitor<Node> for Printer {
+    fn enter(&mut self, _id: NodeId<Node>, node: &Node) -> VisitControl {
+        if self.needs_space {
+            self.output.push(' ');
+        }
+        match &node.expr {
+            Sexpr::Number(value) => self.output.push_str(&value.to_string()),
+            Sexpr::String(value) => self.output.push_str(&format!("{:?}", value)),
+            Sexpr::Symbol(name) => self.output.push_str(name),
+            Sexpr::List(_) => {
+                self.output.push('(');
+                self.needs_space = false;
+                return VisitControl::Continue;
+            }
+        }
+        self.needs_space = true;
+        VisitControl::Continue
+    }
+
+    fn leave(&mut self, _id: NodeId<Node>, node: &Node) {
+        if let Sexpr::List(_) = node.expr {
+            self.output.push(')');
+            self.needs_space = true;
+        }
+    }
+}
+
+/// Measures the maximum list nesting depth.
+#[derive(Default)]
+pub struct DepthCounter {
+    depth: usize,
+    pub max_depth: usize,
+}
+
+impl Visitor<Node> for DepthCounter {
+    fn enter(&mut self, _id: NodeId<Node>, node: &Node) -> VisitControl {
+        if let Sexpr::List(_) = node.expr {
+            self.depth += 1;
+            self.max_depth = self.max_depth.max(self.depth);
+        }
+        VisitControl::Continue
+    }
+
+    fn leave(&mut self, _id: NodeId<Node>, node: &Node) {
+        if let Sexpr::List(_) = node.expr {
+            self.depth -= 1;
+        }
+    }
+}
+
+/// Finds the first use of a symbol, stopping the walk as soon as it is seen.
+pub struct FindSymbol<'a> {
+    pub name: &'a str,
+    pub found: Option<NodeId<Node>>,
+}
+
+impl Visitor<Node> for FindSymbol<'_> {
+    fn enter(&mut self, id: NodeId<Node>, node: &Node) -> VisitControl {
+        match &node.expr {
+            Sexpr::Symbol(name) if name == self.name => {
+                self.found = Some(id);
+                VisitControl::Stop
+            }
+            _ => VisitControl::Continue,
+        }
+    }
+}
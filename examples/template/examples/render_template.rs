@@ -0,0 +1,47 @@
+use template::{parse, render, Node, Value};
+
+const TEMPLATE: &str = r#"Hello, {{ user.name | upper }}!
+{% if user.admin and not user.suspended %}You have admin rights.
+{% else %}Regular account.
+{% endif %}Orders ({{ orders | length }}):
+{% for order in orders %}  - {{ order.item }}{% if order.gift == true %} (gift){% endif %}
+{% endfor %}"#;
+
+fn main() {
+    let nodes = parse(TEMPLATE);
+    println!("Parsed {} top-level nodes", nodes.len());
+
+    let data = Value::map([
+        (
+            "user",
+            Value::map([
+                ("name", Value::from("Ada")),
+                ("admin", Value::from(true)),
+                ("suspended", Value::from(false)),
+            ]),
+        ),
+        (
+            "orders",
+            Value::List(vec![
+                Value::map([("item", Value::from("keyboard")), ("gift", false.into())]),
+                Value::map([("item", Value::from("teapot")), ("gift", true.into())]),
+            ]),
+        ),
+    ]);
+
+    match render(&nodes, &data) {
+        Ok(output) => println!("---\n{}---", output),
+        Err(err) => println!("render error: {:?}", err),
+    }
+
+    // Malformed tags are reported and skipped; the rest still parses.
+    let broken = "a {{ user. }} b {% frobnicate %} c {% if x %}unclosed";
+    for node in parse(broken) {
+        if let Node::Error { message, position } = node {
+            println!(
+                "error at {}:{}: {}",
+                position.line, position.column, message
+            );
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use common_framework::Position;
+use parser_framework::AstNode;
+
+/// A template node.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Text {
+        text: String,
+        position: Position,
+    },
+    /// `{{ expr }}`
+    Output {
+        expr: Expr,
+        position: Position,
+    },
+    /// `{% if cond %} ... {% else %} ... {% endif %}`
+    If {
+        condition: Expr,
+        then_body: Vec<Node>,
+        else_body: Vec<Node>,
+        position: Position,
+    },
+    /// `{% for var in iterable %} ... {% endfor %}`
+    For {
+        var: String,
+        iterable: Expr,
+        body: Vec<Node>,
+        position: Position,
+    },
+    /// Produced by error recovery; the malformed tag was skipped.
+    Error {
+        message: String,
+        position: Position,
+    },
+}
+
+impl AstNode for Node {
+    fn position(&self) -> Option<Position> {
+        match self {
+            Node::Text { position, .. }
+            | Node::Output { position, .. }
+            | Node::If { position, .. }
+            | Node::For { position, .. }
+            | Node::Error { position, .. } => Some(*position),
+        }
+    }
+}
+
+/// An expression inside `{{ }}` or a tag.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Var {
+        name: String,
+        position: Position,
+    },
+    Str {
+        value: String,
+        position: Position,
+    },
+    Number {
+        value: f64,
+        position: Position,
+    },
+    Bool {
+        value: bool,
+        position: Position,
+    },
+    /// `object.field`
+    Attr {
+        object: Box<Expr>,
+        field: String,
+        position: Position,
+    },
+    /// `value | filter`
+    Filter {
+        value: Box<Expr>,
+        filter: String,
+        position: Position,
+    },
+    Not {
+        operand: Box<Expr>,
+        position: Position,
+    },
+    Binary {
+        op: BinaryOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+        position: Position,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Eq,
+    NotEq,
+    And,
+    Or,
+}
+
+impl AstNode for Expr {
+    fn position(&self) -> Option<Position> {
+        match self {
+            Expr::Var { position, .. }
+            | Expr::Str { position, .. }
+            | Expr::Number { position, .. }
+            | Expr::Bool { position, .. }
+            | Expr::Attr { position, .. }
+            | Expr::Filter { position, .. }
+            | Expr::Not { position, .. }
+            | Expr::Binary { position, .. } => Some(*position),
+        }
+    }
+}
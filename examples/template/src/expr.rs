@@ -0,0 +1,125 @@
+//! The expression sub-language, parsed with the framework's Pratt driver.
+
+use crate::ast::{BinaryOp, Expr};
+use crate::token::TemplateToken;
+use lexer_framework::LexToken;
+use parser_framework::{parse_pratt, ParseContext, PrattConfig, PrattSpan};
+
+/// Binding powers, loosest first: `or`, `and`, `not`, `==`/`!=`, `|`, `.`.
+/// As in Jinja, `not a == b` means `not (a == b)`.
+const NOT_BP: u8 = 5;
+
+/// Pratt configuration for template expressions.
+pub struct ExprConfig;
+
+impl<Ctx> PrattConfig<Ctx, TemplateToken, Expr> for ExprConfig
+where
+    Ctx: ParseContext<TemplateToken>,
+{
+    fn prefix_op(&self, token: &TemplateToken) -> Option<((), u8)> {
+        token.is_keyword("not").then_some(((), NOT_BP))
+    }
+
+    fn infix_op(&self, token: &TemplateToken) -> Option<(u8, u8)> {
+        match token {
+            TemplateToken::Ident { name, .. } if name == "or" => Some((1, 2)),
+            TemplateToken::Ident { name, .. } if name == "and" => Some((3, 4)),
+            TemplateToken::EqEq { .. } | TemplateToken::NotEq { .. } => Some((5, 6)),
+            TemplateToken::Pipe { .. } => Some((9, 10)),
+            TemplateToken::Dot { .. } => Some((11, 12)),
+            _ => None,
+        }
+    }
+
+    fn parse_prefix<F>(
+        &self,
+        token: TemplateToken,
+        _span: PrattSpan,
+        ctx: &mut Ctx,
+        parser: &F,
+    ) -> Option<Expr>
+    where
+        F: Fn(&mut Ctx, u8) -> Option<Expr>,
+    {
+        let position = token.position().unwrap_or_default();
+        match token {
+            TemplateToken::Ident { name, .. } => Some(match name.as_str() {
+                "not" => Expr::Not {
+                    operand: Box::new(parser(ctx, NOT_BP)?),
+                    position,
+                },
+                "true" | "false" => Expr::Bool {
+                    value: name == "true",
+                    position,
+                },
+                _ => Expr::Var { name, position },
+            }),
+            TemplateToken::Str { value, .. } => Some(Expr::Str { value, position }),
+            TemplateToken::Number { value, .. } => Some(Expr::Number { value, position }),
+            TemplateToken::LeftParen { .. } => {
+                let inner = parser(ctx, 0)?;
+                match ctx.advance()? {
+                    TemplateToken::RightParen { .. } => Some(inner),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_infix<F>(
+        &self,
+        left: Expr,
+        token: TemplateToken,
+        _span: PrattSpan,
+        r_bp: u8,
+        ctx: &mut Ctx,
+        parser: &F,
+    ) -> Option<Expr>
+    where
+        F: Fn(&mut Ctx, u8) -> Option<Expr>,
+    {
+        let position = token.position().unwrap_or_default();
+        let op = match token {
+            // `.` and `|` take a bare name on the right, not an expression.
+            TemplateToken::Dot { .. } | TemplateToken::Pipe { .. } => {
+                let name = match ctx.advance()? {
+                    TemplateToken::Ident { name, .. } => name,
+                    _ => return None,
+                };
+                let value = Box::new(left);
+                return Some(match token {
+                    TemplateToken::Dot { .. } => Expr::Attr {
+                        object: value,
+                        field: name,
+                        position,
+                    },
+                    _ => Expr::Filter {
+                        value,
+                        filter: name,
+                        position,
+                    },
+                });
+            }
+            TemplateToken::EqEq { .. } => BinaryOp::Eq,
+            TemplateToken::NotEq { .. } => BinaryOp::NotEq,
+            TemplateToken::Ident { name, .. } if name == "and" => BinaryOp::And,
+            TemplateToken::Ident { name, .. } if name == "or" => BinaryOp::Or,
+            _ => return None,
+        };
+        Some(Expr::Binary {
+            op,
+            left: Box::new(left),
+            right: Box::new(parser(ctx, r_bp)?),
+            position,
+        })
+    }
+}
+
+/// Parses a full expression at the current position.
+pub fn parse_expr<Ctx>(ctx: &mut Ctx) -> Option<Expr>
+where
+    Ctx: ParseContext<TemplateToken>,
+{
+    parse_pratt(ctx, &ExprConfig, 0)
+}
@@ -0,0 +1,288 @@
+use crate::token::{Mode, TemplateToken};
+use lexer_framework::{decode_escape, InMode, LexContext, LexingRule, ModeContext};
+
+/// Raw text up to the next `{{` or `{%`.
+pub struct TextRule;
+
+impl<Ctx> LexingRule<Ctx, TemplateToken> for TextRule
+where
+    Ctx: LexContext,
+{
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<TemplateToken> {
+        let position = ctx.position();
+        let mut text = String::new();
+        loop {
+            text.push_str(&ctx.consume_while(|ch| ch != '{'));
+            let checkpoint = ctx.checkpoint();
+            match ctx.advance() {
+                None => break,
+                Some(_) if matches!(ctx.peek(), Some('{' | '%')) => {
+                    ctx.restore(checkpoint);
+                    break;
+                }
+                Some(ch) => text.push(ch),
+            }
+        }
+        if text.is_empty() {
+            return None;
+        }
+        Some(TemplateToken::Text { text, position })
+    }
+
+    fn priority(&self) -> i32 {
+        0
+    }
+}
+
+/// `{{` and `{%`: enter the output or tag mode.
+pub struct OpenRule;
+
+impl<Ctx> LexingRule<Ctx, TemplateToken> for OpenRule
+where
+    Ctx: ModeContext<Mode>,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char == Some('{'))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<TemplateToken> {
+        let position = ctx.position();
+        ctx.advance()?;
+        let (mode, token) = match ctx.advance()? {
+            '{' => (Mode::Output, TemplateToken::OutputOpen { position }),
+            '%' => (Mode::Tag, TemplateToken::TagOpen { position }),
+            _ => return None,
+        };
+        ctx.push_mode(mode);
+        Some(token)
+    }
+
+    fn priority(&self) -> i32 {
+        10
+    }
+}
+
+/// `}}` or `%}`, whichever closes the current mode.
+pub struct CloseRule;
+
+impl<Ctx> LexingRule<Ctx, TemplateToken> for CloseRule
+where
+    Ctx: ModeContext<Mode>,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(matches!(first_char, Some('}' | '%')))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<TemplateToken> {
+        let position = ctx.position();
+        let token = match (*ctx.mode(), ctx.advance()?, ctx.advance()?) {
+            (Mode::Output, '}', '}') => TemplateToken::OutputClose { position },
+            (Mode::Tag, '%', '}') => TemplateToken::TagClose { position },
+            _ => return None,
+        };
+        ctx.pop_mode();
+        Some(token)
+    }
+
+    fn priority(&self) -> i32 {
+        30
+    }
+}
+
+/// Identifiers and keywords (`if`, `for`, `and`, ...).
+pub struct IdentRule;
+
+impl<Ctx> LexingRule<Ctx, TemplateToken> for IdentRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char.is_some_and(|ch| ch.is_alphabetic() || ch == '_'))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<TemplateToken> {
+        let position = ctx.position();
+        let name = ctx.consume_while(|ch| ch.is_alphanumeric() || ch == '_');
+        if name.is_empty() {
+            return None;
+        }
+        Some(TemplateToken::Ident {
+            name: name.to_string(),
+            position,
+        })
+    }
+
+    fn priority(&self) -> i32 {
+        20
+    }
+}
+
+/// Integer and decimal literals.
+pub struct NumberRule;
+
+impl<Ctx> LexingRule<Ctx, TemplateToken> for NumberRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char.is_some_and(|ch| ch.is_ascii_digit()))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<TemplateToken> {
+        let position = ctx.position();
+        let text = ctx.consume_while(|ch| ch.is_ascii_digit() || ch == '.');
+        let value = text.parse().ok()?;
+        Some(TemplateToken::Number { value, position })
+    }
+
+    fn priority(&self) -> i32 {
+        20
+    }
+}
+
+/// Single- or double-quoted strings with escapes.
+pub struct StringRule;
+
+impl<Ctx> LexingRule<Ctx, TemplateToken> for StringRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(matches!(first_char, Some('"' | '\'')))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<TemplateToken> {
+        let position = ctx.position();
+        let quote = ctx.advance()?;
+        let mut value = String::new();
+        loop {
+            match ctx.peek() {
+                None => {
+                    return Some(TemplateToken::Invalid {
+                        message: "unterminated string".to_string(),
+                        position,
+                    })
+                }
+                Some(ch) if ch == quote => {
+                    ctx.advance();
+                    return Some(TemplateToken::Str { value, position });
+                }
+                Some('\\') => match decode_escape(ctx) {
+                    Ok(ch) => value.push(ch),
+                    Err(err) => {
+                        return Some(TemplateToken::Invalid {
+                            message: err.kind.to_string(),
+                            position: err.position,
+                        })
+                    }
+                },
+                Some(ch) => {
+                    value.push(ch);
+                    ctx.advance();
+                }
+            }
+        }
+    }
+
+    fn priority(&self) -> i32 {
+        20
+    }
+}
+
+/// Operators and punctuation inside tags.
+pub struct SymbolRule;
+
+impl<Ctx> LexingRule<Ctx, TemplateToken> for SymbolRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(matches!(
+            first_char,
+            Some('.' | '|' | '=' | '!' | '(' | ')')
+        ))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<TemplateToken> {
+        let position = ctx.position();
+        let token = match ctx.advance()? {
+            '.' => TemplateToken::Dot { position },
+            '|' => TemplateToken::Pipe { position },
+            '(' => TemplateToken::LeftParen { position },
+            ')' => TemplateToken::RightParen { position },
+            '=' if ctx.advance()? == '=' => TemplateToken::EqEq { position },
+            '!' if ctx.advance()? == '=' => TemplateToken::NotEq { position },
+            _ => return None,
+        };
+        Some(token)
+    }
+
+    fn priority(&self) -> i32 {
+        15
+    }
+}
+
+/// Whitespace inside tags.
+pub struct WhitespaceRule;
+
+impl<Ctx> LexingRule<Ctx, TemplateToken> for WhitespaceRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char.is_some_and(char::is_whitespace))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<TemplateToken> {
+        let position = ctx.position();
+        if ctx.consume_while(char::is_whitespace).is_empty() {
+            return None;
+        }
+        Some(TemplateToken::Whitespace { position })
+    }
+
+    fn priority(&self) -> i32 {
+        5
+    }
+}
+
+/// Fallback inside tags: any other character is reported.
+pub struct InvalidCharRule;
+
+impl<Ctx> LexingRule<Ctx, TemplateToken> for InvalidCharRule
+where
+    Ctx: LexContext,
+{
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<TemplateToken> {
+        let position = ctx.position();
+        let ch = ctx.advance()?;
+        Some(TemplateToken::Invalid {
+            message: format!("unexpected character {:?} in tag", ch),
+            position,
+        })
+    }
+
+    fn priority(&self) -> i32 {
+        -10
+    }
+}
+
+/// Builds the template lexer rules. Text-mode and tag-mode rules are
+/// registered together and gated on the current mode with [`InMode`].
+pub fn build_lexer_rules<Ctx>() -> Vec<Box<dyn LexingRule<Ctx, TemplateToken>>>
+where
+    Ctx: ModeContext<Mode>,
+{
+    const TAGS: [Mode; 2] = [Mode::Output, Mode::Tag];
+    vec![
+        Box::new(InMode::new(Mode::Text, OpenRule)),
+        Box::new(InMode::new(Mode::Text, TextRule)),
+        Box::new(InMode::any_of(TAGS, CloseRule)),
+        Box::new(InMode::any_of(TAGS, IdentRule)),
+        Box::new(InMode::any_of(TAGS, NumberRule)),
+        Box::new(InMode::any_of(TAGS, StringRule)),
+        Box::new(InMode::any_of(TAGS, SymbolRule)),
+        Box::new(InMode::any_of(TAGS, WhitespaceRule)),
+        Box::new(InMode::any_of(TAGS, InvalidCharRule)),
+    ]
+}
@@ -0,0 +1,49 @@
+//! Jinja/Handlebars-like template example using lexer modes.
+//!
+//! The lexer runs on a [`ModalContext`]: text-mode rules produce raw text
+//! until `{{` or `{%`, which push the [`Mode::Output`] or [`Mode::Tag`] mode,
+//! where expression rules take over until the matching `}}` / `%}` pops back.
+//! The resulting mixed token stream feeds two parser rule sets: block-level
+//! [`ParsingRule`](parser_framework::ParsingRule)s for text and tags, and a
+//! [`PrattConfig`](parser_framework::PrattConfig) for the expressions inside
+//! them.
+
+pub mod ast;
+pub mod expr;
+pub mod lexer_rules;
+pub mod parser_rules;
+pub mod render;
+pub mod token;
+pub mod value;
+
+pub use ast::{BinaryOp, Expr, Node};
+pub use lexer_rules::build_lexer_rules;
+pub use parser_rules::build_parser_rules;
+pub use render::{render, RenderError};
+pub use token::{Mode, TemplateToken};
+pub use value::Value;
+
+use lexer_framework::{DefaultContext, Lexer, ModalContext};
+use parser_framework::Parser;
+use pipeline_core::BatchPipeline;
+
+/// Parses a template. Malformed tags become [`Node::Error`] nodes.
+pub fn parse(source: &str) -> Vec<Node> {
+    let context = ModalContext::new(DefaultContext::new(source), Mode::Text);
+    let lexer = Lexer::new(context, build_lexer_rules());
+    // Whitespace only separates tokens inside tags; text keeps its own.
+    BatchPipeline::run_custom(lexer, |tokens| {
+        let tokens = tokens
+            .into_iter()
+            .filter(|token| !matches!(token, TemplateToken::Whitespace { .. }));
+        Parser::<parser_framework::DefaultContext<TemplateToken>, _, _>::from_tokens(
+            tokens,
+            build_parser_rules(),
+        )
+    })
+}
+
+/// Parses and renders a template in one step.
+pub fn render_str(source: &str, data: &Value) -> Result<String, RenderError> {
+    render(&parse(source), data)
+}
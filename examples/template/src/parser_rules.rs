@@ -0,0 +1,328 @@
+//! Document-level rules: text, `{{ }}` output and `{% %}` block tags.
+//!
+//! Block bodies are parsed with the same node functions the rules use, and
+//! expressions inside tags are handed off to the Pratt-based rule set in
+//! [`crate::expr`].
+
+use crate::ast::{Expr, Node};
+use crate::expr::parse_expr;
+use crate::token::TemplateToken;
+use common_framework::Position;
+use lexer_framework::LexToken;
+use parser_framework::{ParseContext, ParsingRule};
+
+type ParseResult<T> = Result<T, (String, Position)>;
+
+fn current_position<Ctx>(ctx: &mut Ctx) -> Position
+where
+    Ctx: ParseContext<TemplateToken>,
+{
+    ctx.peek()
+        .and_then(LexToken::position)
+        .unwrap_or_else(|| ctx.position())
+}
+
+fn error_at<Ctx, T>(ctx: &mut Ctx, message: &str) -> ParseResult<T>
+where
+    Ctx: ParseContext<TemplateToken>,
+{
+    Err((message.to_string(), current_position(ctx)))
+}
+
+fn expect_expr<Ctx>(ctx: &mut Ctx) -> ParseResult<Expr>
+where
+    Ctx: ParseContext<TemplateToken>,
+{
+    match ctx.speculate(parse_expr) {
+        Some(expr) => Ok(expr),
+        None => error_at(ctx, "expected an expression"),
+    }
+}
+
+fn expect_tag_close<Ctx>(ctx: &mut Ctx) -> ParseResult<()>
+where
+    Ctx: ParseContext<TemplateToken>,
+{
+    match ctx.peek() {
+        Some(TemplateToken::TagClose { .. }) => {
+            ctx.advance();
+            Ok(())
+        }
+        _ => error_at(ctx, "expected `%}`"),
+    }
+}
+
+/// Skips to just past the closing delimiter of the current tag, so parsing
+/// resumes in text after a malformed tag.
+fn recover<Ctx>(ctx: &mut Ctx)
+where
+    Ctx: ParseContext<TemplateToken>,
+{
+    while let Some(token) = ctx.advance() {
+        if matches!(
+            token,
+            TemplateToken::OutputClose { .. } | TemplateToken::TagClose { .. }
+        ) {
+            break;
+        }
+    }
+}
+
+/// Returns the keyword of the upcoming `{% keyword ... %}` tag, if any.
+fn peek_tag_keyword<Ctx>(ctx: &mut Ctx) -> Option<String>
+where
+    Ctx: ParseContext<TemplateToken>,
+{
+    if !matches!(ctx.peek(), Some(TemplateToken::TagOpen { .. })) {
+        return None;
+    }
+    match ctx.peek_at(1) {
+        Some(TemplateToken::Ident { name, .. }) => Some(name.clone()),
+        _ => None,
+    }
+}
+
+/// Parses nodes until a `{% keyword %}` tag with one of `terminators`, which
+/// is consumed and returned.
+fn parse_body<Ctx>(
+    ctx: &mut Ctx,
+    opener: (&str, Position),
+    terminators: &[&str],
+) -> ParseResult<(Vec<Node>, String)>
+where
+    Ctx: ParseContext<TemplateToken>,
+{
+    let mut body = Vec::new();
+    loop {
+        if let Some(keyword) = peek_tag_keyword(ctx) {
+            if terminators.contains(&keyword.as_str()) {
+                ctx.advance();
+                ctx.advance();
+                expect_tag_close(ctx)?;
+                return Ok((body, keyword));
+            }
+        }
+        match parse_node(ctx) {
+            Some(node) => body.push(node),
+            None => {
+                let (tag, position) = opener;
+                return Err((format!("unclosed `{{% {} %}}`", tag), position));
+            }
+        }
+    }
+}
+
+/// Parses `{{ expr }}`, the `{{` already peeked.
+fn parse_output<Ctx>(ctx: &mut Ctx, position: Position) -> ParseResult<Node>
+where
+    Ctx: ParseContext<TemplateToken>,
+{
+    ctx.advance();
+    let expr = expect_expr(ctx)?;
+    match ctx.peek() {
+        Some(TemplateToken::OutputClose { .. }) => {
+            ctx.advance();
+            Ok(Node::Output { expr, position })
+        }
+        _ => error_at(ctx, "expected `}}`"),
+    }
+}
+
+/// Parses a block tag, the `{%` already peeked.
+fn parse_tag<Ctx>(ctx: &mut Ctx, position: Position) -> ParseResult<Node>
+where
+    Ctx: ParseContext<TemplateToken>,
+{
+    ctx.advance();
+    let keyword = match ctx.advance() {
+        Some(TemplateToken::Ident { name, .. }) => name,
+        _ => return Err(("expected a tag name".to_string(), position)),
+    };
+
+    match keyword.as_str() {
+        "if" => {
+            let condition = expect_expr(ctx)?;
+            expect_tag_close(ctx)?;
+            let (then_body, end) = parse_body(ctx, ("if", position), &["else", "endif"])?;
+            let else_body = if end == "else" {
+                parse_body(ctx, ("if", position), &["endif"])?.0
+            } else {
+                Vec::new()
+            };
+            Ok(Node::If {
+                condition,
+                then_body,
+                else_body,
+                position,
+            })
+        }
+        "for" => {
+            let var = match ctx.advance() {
+                Some(TemplateToken::Ident { name, .. }) => name,
+                _ => return error_at(ctx, "expected a loop variable"),
+            };
+            if !ctx.peek().is_some_and(|token| token.is_keyword("in")) {
+                return error_at(ctx, "expected `in`");
+            }
+            ctx.advance();
+            let iterable = expect_expr(ctx)?;
+            expect_tag_close(ctx)?;
+            let (body, _) = parse_body(ctx, ("for", position), &["endfor"])?;
+            Ok(Node::For {
+                var,
+                iterable,
+                body,
+                position,
+            })
+        }
+        other => Err((format!("unknown or misplaced tag `{}`", other), position)),
+    }
+}
+
+/// Turns a failed parse into an error node, skipping the rest of the tag.
+fn recover_from<Ctx>(ctx: &mut Ctx, result: ParseResult<Node>) -> Node
+where
+    Ctx: ParseContext<TemplateToken>,
+{
+    result.unwrap_or_else(|(message, position)| {
+        recover(ctx);
+        Node::Error { message, position }
+    })
+}
+
+/// Parses a token that cannot start a node.
+fn parse_unexpected<Ctx>(ctx: &mut Ctx, token: TemplateToken, position: Position) -> Node
+where
+    Ctx: ParseContext<TemplateToken>,
+{
+    let message = match token {
+        TemplateToken::Invalid { message, .. } => message,
+        _ => "unexpected token".to_string(),
+    };
+    recover_from(ctx, Err((message, position)))
+}
+
+/// Parses any single node; `None` at end of input. Used for block bodies.
+fn parse_node<Ctx>(ctx: &mut Ctx) -> Option<Node>
+where
+    Ctx: ParseContext<TemplateToken>,
+{
+    let token = ctx.peek()?.clone();
+    let position = token.position().unwrap_or_default();
+    Some(match token {
+        TemplateToken::Text { text, .. } => {
+            ctx.advance();
+            Node::Text { text, position }
+        }
+        TemplateToken::OutputOpen { .. } => {
+            let result = parse_output(ctx, position);
+            recover_from(ctx, result)
+        }
+        TemplateToken::TagOpen { .. } => {
+            let result = parse_tag(ctx, position);
+            recover_from(ctx, result)
+        }
+        token => parse_unexpected(ctx, token, position),
+    })
+}
+
+/// Raw text.
+pub struct TextRule;
+
+impl<Ctx> ParsingRule<Ctx, TemplateToken, Node> for TextRule
+where
+    Ctx: ParseContext<TemplateToken>,
+{
+    fn quick_check(&self, current_token: Option<&TemplateToken>) -> Option<bool> {
+        Some(matches!(current_token, Some(TemplateToken::Text { .. })))
+    }
+
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Node> {
+        match ctx.advance()? {
+            TemplateToken::Text { text, position } => Some(Node::Text { text, position }),
+            _ => None,
+        }
+    }
+
+    fn priority(&self) -> i32 {
+        10
+    }
+}
+
+/// `{{ expr }}`
+pub struct OutputRule;
+
+impl<Ctx> ParsingRule<Ctx, TemplateToken, Node> for OutputRule
+where
+    Ctx: ParseContext<TemplateToken>,
+{
+    fn quick_check(&self, current_token: Option<&TemplateToken>) -> Option<bool> {
+        Some(matches!(
+            current_token,
+            Some(TemplateToken::OutputOpen { .. })
+        ))
+    }
+
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Node> {
+        let position = ctx.peek()?.position().unwrap_or_default();
+        let result = parse_output(ctx, position);
+        Some(recover_from(ctx, result))
+    }
+
+    fn priority(&self) -> i32 {
+        10
+    }
+}
+
+/// `{% if %}` and `{% for %}` blocks, including their bodies.
+pub struct BlockRule;
+
+impl<Ctx> ParsingRule<Ctx, TemplateToken, Node> for BlockRule
+where
+    Ctx: ParseContext<TemplateToken>,
+{
+    fn quick_check(&self, current_token: Option<&TemplateToken>) -> Option<bool> {
+        Some(matches!(current_token, Some(TemplateToken::TagOpen { .. })))
+    }
+
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Node> {
+        let position = ctx.peek()?.position().unwrap_or_default();
+        let result = parse_tag(ctx, position);
+        Some(recover_from(ctx, result))
+    }
+
+    fn priority(&self) -> i32 {
+        10
+    }
+}
+
+/// Error recovery for anything else, e.g. a stray `%}` or a bad character.
+pub struct ErrorRule;
+
+impl<Ctx> ParsingRule<Ctx, TemplateToken, Node> for ErrorRule
+where
+    Ctx: ParseContext<TemplateToken>,
+{
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Node> {
+        let token = ctx.peek()?.clone();
+        let position = token.position().unwrap_or_default();
+        Some(parse_unexpected(ctx, token, position))
+    }
+
+    fn priority(&self) -> i32 {
+        0
+    }
+}
+
+/// Builds the document-level parser rules.
+pub fn build_parser_rules<Ctx>() -> Vec<Box<dyn ParsingRule<Ctx, TemplateToken, Node>>>
+where
+    Ctx: ParseContext<TemplateToken>,
+{
+    vec![
+        Box::new(TextRule),
+        Box::new(OutputRule),
+        Box::new(BlockRule),
+        Box::new(ErrorRule),
+    ]
+}
@@ -0,0 +1,141 @@
+use crate::ast::{BinaryOp, Expr, Node};
+use crate::value::Value;
+use common_framework::Position;
+use parser_framework::AstNode;
+use std::collections::BTreeMap;
+
+/// A failure while rendering (unknown filter, non-iterable loop, ...).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderError {
+    pub message: String,
+    pub position: Position,
+}
+
+/// Variable scopes, innermost last.
+struct Scope<'a> {
+    frames: Vec<BTreeMap<String, Value>>,
+    globals: &'a Value,
+}
+
+impl Scope<'_> {
+    fn lookup(&self, name: &str) -> Value {
+        for frame in self.frames.iter().rev() {
+            if let Some(value) = frame.get(name) {
+                return value.clone();
+            }
+        }
+        match self.globals {
+            Value::Map(entries) => entries.get(name).cloned().unwrap_or(Value::Null),
+            _ => Value::Null,
+        }
+    }
+}
+
+/// Renders `nodes` with `data` (normally a [`Value::Map`]) as the global scope.
+/// Undefined variables and attributes render as empty, as in Jinja.
+pub fn render(nodes: &[Node], data: &Value) -> Result<String, RenderError> {
+    let mut scope = Scope {
+        frames: Vec::new(),
+        globals: data,
+    };
+    let mut output = String::new();
+    render_nodes(nodes, &mut scope, &mut output)?;
+    Ok(output)
+}
+
+fn render_nodes(nodes: &[Node], scope: &mut Scope, output: &mut String) -> Result<(), RenderError> {
+    for node in nodes {
+        match node {
+            Node::Text { text, .. } => output.push_str(text),
+            Node::Output { expr, .. } => output.push_str(&eval(expr, scope)?.to_string()),
+            Node::If {
+                condition,
+                then_body,
+                else_body,
+                ..
+            } => {
+                let body = if eval(condition, scope)?.is_truthy() {
+                    then_body
+                } else {
+                    else_body
+                };
+                render_nodes(body, scope, output)?;
+            }
+            Node::For {
+                var,
+                iterable,
+                body,
+                position,
+            } => {
+                let items = match eval(iterable, scope)? {
+                    Value::List(items) => items,
+                    Value::Null => Vec::new(),
+                    other => {
+                        return Err(RenderError {
+                            message: format!("cannot iterate over {}", other),
+                            position: *position,
+                        })
+                    }
+                };
+                for item in items {
+                    scope.frames.push(BTreeMap::from([(var.clone(), item)]));
+                    let result = render_nodes(body, scope, output);
+                    scope.frames.pop();
+                    result?;
+                }
+            }
+            Node::Error { message, position } => {
+                return Err(RenderError {
+                    message: message.clone(),
+                    position: *position,
+                })
+            }
+        }
+    }
+    Ok(())
+}
+
+fn eval(expr: &Expr, scope: &Scope) -> Result<Value, RenderError> {
+    Ok(match expr {
+        Expr::Var { name, .. } => scope.lookup(name),
+        Expr::Str { value, .. } => Value::Str(value.clone()),
+        Expr::Number { value, .. } => Value::Number(*value),
+        Expr::Bool { value, .. } => Value::Bool(*value),
+        Expr::Attr { object, field, .. } => match eval(object, scope)? {
+            Value::Map(mut entries) => entries.remove(field).unwrap_or(Value::Null),
+            _ => Value::Null,
+        },
+        Expr::Filter { value, filter, .. } => apply_filter(filter, eval(value, scope)?)
+            .ok_or_else(|| RenderError {
+                message: format!("unknown filter `{}`", filter),
+                position: expr.position().unwrap_or_default(),
+            })?,
+        Expr::Not { operand, .. } => Value::Bool(!eval(operand, scope)?.is_truthy()),
+        Expr::Binary {
+            op, left, right, ..
+        } => {
+            let left = eval(left, scope)?;
+            match op {
+                BinaryOp::And if !left.is_truthy() => left,
+                BinaryOp::Or if left.is_truthy() => left,
+                BinaryOp::And | BinaryOp::Or => eval(right, scope)?,
+                BinaryOp::Eq => Value::Bool(left == eval(right, scope)?),
+                BinaryOp::NotEq => Value::Bool(left != eval(right, scope)?),
+            }
+        }
+    })
+}
+
+fn apply_filter(name: &str, value: Value) -> Option<Value> {
+    Some(match name {
+        "upper" => Value::Str(value.to_string().to_uppercase()),
+        "lower" => Value::Str(value.to_string().to_lowercase()),
+        "trim" => Value::Str(value.to_string().trim().to_string()),
+        "length" => Value::Number(match &value {
+            Value::List(items) => items.len(),
+            Value::Map(entries) => entries.len(),
+            other => other.to_string().chars().count(),
+        } as f64),
+        _ => return None,
+    })
+}
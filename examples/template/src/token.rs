@@ -0,0 +1,108 @@
+use common_framework::Position;
+use lexer_framework::LexToken;
+
+/// Lexer modes: raw template text, or the inside of a `{{ }}` / `{% %}` tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Text,
+    Output,
+    Tag,
+}
+
+/// Template tokens. Text-mode and expression-mode tokens share one stream.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateToken {
+    /// Raw text between tags.
+    Text {
+        text: String,
+        position: Position,
+    },
+    /// `{{`
+    OutputOpen {
+        position: Position,
+    },
+    /// `}}`
+    OutputClose {
+        position: Position,
+    },
+    /// `{%`
+    TagOpen {
+        position: Position,
+    },
+    /// `%}`
+    TagClose {
+        position: Position,
+    },
+    Ident {
+        name: String,
+        position: Position,
+    },
+    Str {
+        value: String,
+        position: Position,
+    },
+    Number {
+        value: f64,
+        position: Position,
+    },
+    Dot {
+        position: Position,
+    },
+    Pipe {
+        position: Position,
+    },
+    EqEq {
+        position: Position,
+    },
+    NotEq {
+        position: Position,
+    },
+    LeftParen {
+        position: Position,
+    },
+    RightParen {
+        position: Position,
+    },
+    Whitespace {
+        position: Position,
+    },
+    /// Input no rule understands inside a tag.
+    Invalid {
+        message: String,
+        position: Position,
+    },
+}
+
+impl TemplateToken {
+    /// Returns the identifier name if this is the given keyword.
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, TemplateToken::Ident { name, .. } if name == keyword)
+    }
+}
+
+impl LexToken for TemplateToken {
+    fn position(&self) -> Option<Position> {
+        Some(match self {
+            TemplateToken::Text { position, .. }
+            | TemplateToken::OutputOpen { position }
+            | TemplateToken::OutputClose { position }
+            | TemplateToken::TagOpen { position }
+            | TemplateToken::TagClose { position }
+            | TemplateToken::Ident { position, .. }
+            | TemplateToken::Str { position, .. }
+            | TemplateToken::Number { position, .. }
+            | TemplateToken::Dot { position }
+            | TemplateToken::Pipe { position }
+            | TemplateToken::EqEq { position }
+            | TemplateToken::NotEq { position }
+            | TemplateToken::LeftParen { position }
+            | TemplateToken::RightParen { position }
+            | TemplateToken::Whitespace { position }
+            | TemplateToken::Invalid { position, .. } => *position,
+        })
+    }
+
+    fn is_whitespace(&self) -> bool {
+        matches!(self, TemplateToken::Whitespace { .. })
+    }
+}
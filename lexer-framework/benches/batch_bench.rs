@@ -1,11 +1,13 @@
 use criterion::{criterion_group, criterion_main, Criterion, Throughput};
-use lexer_framework::{DefaultContext, LexContext, LexToken, Lexer, LexingRule, Position};
+use lexer_framework::{
+    CompactText, DefaultContext, LexContext, LexToken, Lexer, LexingRule, Position,
+};
 
 // --- Token Definition ---
 #[derive(Debug, Clone, PartialEq)]
 enum BenchToken {
     Number(i64),
-    Identifier(String),
+    Identifier(CompactText),
     Operator(char),
     Whitespace,
     Unknown(char),
@@ -92,7 +94,7 @@ impl LexingRule<DefaultContext, BenchToken> for IdentifierRule {
 
         let slice = ctx.consume_while(is_ident_continue);
         if !slice.is_empty() {
-            Some(BenchToken::Identifier(slice.to_string()))
+            Some(BenchToken::Identifier(slice.into()))
         } else {
             None
         }
@@ -305,5 +307,57 @@ fn bench_lexer(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_lexer);
+fn bench_rules() -> Vec<Box<dyn LexingRule<DefaultContext, BenchToken>>> {
+    vec![
+        Box::new(WhitespaceRule),
+        Box::new(NumberRule),
+        Box::new(IdentifierRule),
+        Box::new(OperatorRule),
+        Box::new(UnknownRule),
+    ]
+}
+
+// Compares a fresh Vec per run against reusing one token buffer.
+fn bench_buffer_reuse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lexer_buffer_reuse");
+    let text = generate_mixed(100);
+    group.throughput(Throughput::Bytes(text.len() as u64));
+
+    group.bench_function("tokenize", |b| {
+        b.iter(|| {
+            let mut lexer = Lexer::from_str(text.as_str(), bench_rules());
+            let tokens = lexer.tokenize();
+            tokens.len()
+        })
+    });
+
+    let mut buffer = Vec::new();
+    group.bench_function("tokenize_into", |b| {
+        b.iter(|| {
+            buffer.clear();
+            let mut lexer = Lexer::from_str(text.as_str(), bench_rules());
+            lexer.tokenize_into(&mut buffer)
+        })
+    });
+
+    let mut chunk = Vec::with_capacity(256);
+    group.bench_function("next_chunk_into_256", |b| {
+        b.iter(|| {
+            let mut lexer = Lexer::from_str(text.as_str(), bench_rules());
+            let mut total = 0;
+            loop {
+                chunk.clear();
+                let added = lexer.next_chunk_into(256, &mut chunk);
+                if added == 0 {
+                    break total;
+                }
+                total += added;
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_lexer, bench_buffer_reuse);
 criterion_main!(benches);
@@ -0,0 +1,168 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use lexer_framework::{
+    CompactPos, CompactToken, DefaultContext, LexContext, LexToken, Lexer, LexingRule, LineIndex,
+    Position,
+};
+
+// Same token shape twice: once with a full `Position` in every variant and
+// once with a 4-byte `CompactPos`.
+#[derive(Debug, Clone, PartialEq)]
+enum FullToken {
+    Number(i64, Position),
+    Identifier(Position),
+    Operator(char, Position),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum SmallToken {
+    Number(i64, CompactPos),
+    Identifier(CompactPos),
+    Operator(char, CompactPos),
+}
+
+impl LexToken for FullToken {
+    fn position(&self) -> Option<Position> {
+        Some(match self {
+            FullToken::Number(_, pos)
+            | FullToken::Identifier(pos)
+            | FullToken::Operator(_, pos) => *pos,
+        })
+    }
+    fn is_eof(&self) -> bool {
+        false
+    }
+    fn is_newline(&self) -> bool {
+        false
+    }
+    fn is_whitespace(&self) -> bool {
+        false
+    }
+    fn is_indent(&self) -> bool {
+        false
+    }
+}
+
+impl LexToken for SmallToken {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+    fn is_eof(&self) -> bool {
+        false
+    }
+    fn is_newline(&self) -> bool {
+        false
+    }
+    fn is_whitespace(&self) -> bool {
+        false
+    }
+    fn is_indent(&self) -> bool {
+        false
+    }
+}
+
+impl CompactToken for SmallToken {
+    fn compact_pos(&self) -> Option<CompactPos> {
+        Some(match self {
+            SmallToken::Number(_, pos)
+            | SmallToken::Identifier(pos)
+            | SmallToken::Operator(_, pos) => *pos,
+        })
+    }
+}
+
+// One rule per token type: skips whitespace, then lexes a number, an
+// identifier or a single operator character.
+struct FullRule;
+impl LexingRule<DefaultContext, FullToken> for FullRule {
+    fn try_match(&mut self, ctx: &mut DefaultContext) -> Option<FullToken> {
+        ctx.consume_while(char::is_whitespace);
+        let pos = ctx.position();
+        let ch = ctx.peek()?;
+        if ch.is_ascii_digit() {
+            let num = ctx
+                .consume_while(|c| c.is_ascii_digit())
+                .parse()
+                .unwrap_or(0);
+            Some(FullToken::Number(num, pos))
+        } else if ch.is_alphabetic() || ch == '_' {
+            ctx.consume_while(|c| c.is_alphanumeric() || c == '_');
+            Some(FullToken::Identifier(pos))
+        } else {
+            ctx.advance();
+            Some(FullToken::Operator(ch, pos))
+        }
+    }
+}
+
+struct CompactRule;
+impl LexingRule<DefaultContext, SmallToken> for CompactRule {
+    fn try_match(&mut self, ctx: &mut DefaultContext) -> Option<SmallToken> {
+        ctx.consume_while(char::is_whitespace);
+        let pos = ctx.compact_pos();
+        let ch = ctx.peek()?;
+        if ch.is_ascii_digit() {
+            let num = ctx
+                .consume_while(|c| c.is_ascii_digit())
+                .parse()
+                .unwrap_or(0);
+            Some(SmallToken::Number(num, pos))
+        } else if ch.is_alphabetic() || ch == '_' {
+            ctx.consume_while(|c| c.is_alphanumeric() || c == '_');
+            Some(SmallToken::Identifier(pos))
+        } else {
+            ctx.advance();
+            Some(SmallToken::Operator(ch, pos))
+        }
+    }
+}
+
+fn generate_text(size_kb: usize) -> String {
+    let line = "let x_Variable = foo(12345, bar) + 7;\n";
+    line.repeat(size_kb * 1024 / line.len() + 1)
+}
+
+fn bench_compact_pos(c: &mut Criterion) {
+    println!(
+        "token size: Position = {} bytes, CompactPos = {} bytes",
+        std::mem::size_of::<FullToken>(),
+        std::mem::size_of::<SmallToken>()
+    );
+
+    let text = generate_text(100);
+    let mut group = c.benchmark_group("compact_pos");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+
+    group.bench_function("position_tokens", |b| {
+        b.iter(|| {
+            let mut lexer = Lexer::from_str(text.as_str(), vec![Box::new(FullRule)]);
+            black_box(lexer.tokenize())
+        })
+    });
+
+    group.bench_function("compact_pos_tokens", |b| {
+        b.iter(|| {
+            let mut lexer = Lexer::from_str(text.as_str(), vec![Box::new(CompactRule)]);
+            black_box(lexer.tokenize())
+        })
+    });
+
+    // Resolving every position afterwards, the worst case for the compact
+    // representation.
+    group.bench_function("compact_pos_tokens_resolved", |b| {
+        b.iter(|| {
+            let mut lexer = Lexer::from_str(text.as_str(), vec![Box::new(CompactRule)]);
+            let tokens = lexer.tokenize();
+            let index = LineIndex::new(text.as_str());
+            tokens
+                .iter()
+                .filter_map(|token| token.compact_pos())
+                .map(|pos| index.position(pos).column)
+                .sum::<usize>()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_compact_pos);
+criterion_main!(benches);
@@ -1,12 +1,13 @@
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkGroup, Criterion, Throughput};
 use lexer_framework::{
-    DefaultContext, LexContext, LexToken, Lexer, LexingRule, Position, StreamingLexContext,
+    CompactText, DefaultContext, LexContext, LexToken, Lexer, LexingRule, Position,
+    StreamingLexContext,
 };
 
 #[derive(Debug, Clone, PartialEq)]
 enum BenchToken {
     Number(i64),
-    Identifier(String),
+    Identifier(CompactText),
     Operator(char),
     Whitespace,
     Unknown(char),
@@ -96,7 +97,7 @@ where
         if slice.is_empty() {
             None
         } else {
-            Some(BenchToken::Identifier(slice.to_string()))
+            Some(BenchToken::Identifier(slice.into()))
         }
     }
 
@@ -1,11 +1,13 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
 use lexer_framework::streaming::StreamingLexContext;
-use lexer_framework::{DefaultContext, LexContext, LexToken, Lexer, LexingRule, Position};
+use lexer_framework::{
+    CompactText, DefaultContext, LexContext, LexToken, Lexer, LexingRule, Position,
+};
 
 #[derive(Debug, Clone, PartialEq)]
 enum BenchToken {
     Number(i64),
-    Identifier(String),
+    Identifier(CompactText),
     Operator(char),
     Whitespace,
     Unknown(char),
@@ -101,7 +103,7 @@ where
 
         let slice = ctx.consume_while(is_ident_continue);
         if !slice.is_empty() {
-            Some(BenchToken::Identifier(slice.to_string()))
+            Some(BenchToken::Identifier(slice.into()))
         } else {
             None
         }
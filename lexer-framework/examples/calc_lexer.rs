@@ -38,17 +38,9 @@ impl LexToken for CalcToken {
         matches!(self, CalcToken::Eof { .. })
     }
 
-    fn is_newline(&self) -> bool {
-        false
-    }
-
     fn is_whitespace(&self) -> bool {
         matches!(self, CalcToken::Whitespace { .. })
     }
-
-    fn is_indent(&self) -> bool {
-        false
-    }
 }
 
 /// Matches floating-point numbers.
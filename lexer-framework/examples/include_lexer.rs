@@ -0,0 +1,207 @@
+//! Include preprocessor example.
+//! Demonstrates splicing `#include "file"` directives into one token stream with a SourceMap.
+
+use lexer_framework::{
+    DefaultContext, Directive, LexContext, LexToken, LexingRule, Position, Preprocessor, SourceMap,
+};
+use std::collections::HashMap;
+use std::io;
+
+/// Token definition for a tiny C-like language with `#include` directives.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Include { path: String, position: Position },
+    Ident { name: String, position: Position },
+    Punct { ch: char, position: Position },
+    Whitespace { position: Position },
+}
+
+impl LexToken for Token {
+    fn position(&self) -> Option<Position> {
+        Some(match self {
+            Token::Include { position, .. }
+            | Token::Ident { position, .. }
+            | Token::Punct { position, .. }
+            | Token::Whitespace { position } => *position,
+        })
+    }
+
+    fn is_whitespace(&self) -> bool {
+        matches!(self, Token::Whitespace { .. })
+    }
+
+    fn relocated(self, base: Position) -> Self {
+        match self {
+            Token::Include { path, position } => Token::Include {
+                path,
+                position: position.rebase(base),
+            },
+            Token::Ident { name, position } => Token::Ident {
+                name,
+                position: position.rebase(base),
+            },
+            Token::Punct { ch, position } => Token::Punct {
+                ch,
+                position: position.rebase(base),
+            },
+            Token::Whitespace { position } => Token::Whitespace {
+                position: position.rebase(base),
+            },
+        }
+    }
+}
+
+/// Matches `#include "path"`.
+pub struct IncludeRule;
+
+impl<Ctx> LexingRule<Ctx, Token> for IncludeRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char? == '#')
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Token> {
+        let position = ctx.position();
+        ctx.advance();
+        if ctx.consume_while(|c| c.is_ascii_alphabetic()).as_ref() != "include" {
+            return None;
+        }
+        ctx.consume_while(|c| c == ' ');
+        if ctx.advance() != Some('"') {
+            return None;
+        }
+        let path = ctx.consume_while(|c| c != '"' && c != '\n').to_string();
+        if ctx.advance() != Some('"') {
+            return None;
+        }
+        Some(Token::Include { path, position })
+    }
+
+    fn priority(&self) -> i32 {
+        10
+    }
+}
+
+/// Matches identifiers.
+pub struct IdentRule;
+
+impl<Ctx> LexingRule<Ctx, Token> for IdentRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char?.is_alphabetic() || first_char? == '_')
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Token> {
+        let position = ctx.position();
+        let name = ctx.consume_while(|c| c.is_alphanumeric() || c == '_');
+        if name.is_empty() {
+            return None;
+        }
+        Some(Token::Ident {
+            name: name.to_string(),
+            position,
+        })
+    }
+}
+
+/// Matches single punctuation characters.
+pub struct PunctRule;
+
+impl<Ctx> LexingRule<Ctx, Token> for PunctRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(matches!(first_char?, ';' | '(' | ')' | '{' | '}' | ','))
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Token> {
+        let position = ctx.position();
+        let ch = ctx.advance()?;
+        Some(Token::Punct { ch, position })
+    }
+}
+
+/// Matches whitespace, including newlines.
+pub struct WhitespaceRule;
+
+impl<Ctx> LexingRule<Ctx, Token> for WhitespaceRule
+where
+    Ctx: LexContext,
+{
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char?.is_whitespace())
+    }
+
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Token> {
+        let position = ctx.position();
+        if ctx.consume_while(|c| c.is_whitespace()).is_empty() {
+            return None;
+        }
+        Some(Token::Whitespace { position })
+    }
+}
+
+fn rules() -> Vec<Box<dyn LexingRule<DefaultContext, Token>>> {
+    vec![
+        Box::new(IncludeRule),
+        Box::new(IdentRule),
+        Box::new(PunctRule),
+        Box::new(WhitespaceRule),
+    ]
+}
+
+fn main() {
+    // In-memory "file system"; a real driver would read from disk.
+    let files: HashMap<&str, &str> = HashMap::from([
+        ("types.h", "typedef int size;\n"),
+        ("util.h", "#include \"types.h\"\nsize len(buffer b);\n"),
+    ]);
+
+    let mut sources = SourceMap::new();
+    let main = sources.add(
+        "main.c",
+        "#include \"util.h\"\n#include \"types.h\"\nsize main() { return len(input); }\n",
+    );
+
+    let tokens = Preprocessor::new(&mut sources, rules, |name, _from| {
+        files
+            .get(name)
+            .map(|text| text.to_string())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no such file"))
+    })
+    .run(main, |token| match token {
+        Token::Include { path, .. } => Directive::Include(path),
+        Token::Whitespace { .. } => Directive::Drop,
+        token => Directive::Keep(token),
+    });
+
+    let tokens = match tokens {
+        Ok(tokens) => tokens,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            return;
+        }
+    };
+
+    for token in &tokens {
+        let offset = token.position().map_or(0, |position| position.offset);
+        let (id, local) = sources.lookup(offset).expect("token lies in a source");
+        let text = match token {
+            Token::Ident { name, .. } => name.clone(),
+            Token::Punct { ch, .. } => ch.to_string(),
+            _ => continue,
+        };
+        println!(
+            "{:>8}:{}:{:<3} {}",
+            sources.file(id).name(),
+            local.line,
+            local.column,
+            text
+        );
+    }
+}
@@ -1,23 +1,58 @@
 //! JSON lexer example.
 //! Demonstrates how to define JSON-style tokens and rules with lexer-framework.
 
-use lexer_framework::{DefaultContext, LexContext, LexToken, Lexer, LexingRule, Position};
+use lexer_framework::{
+    decode_escape, DefaultContext, LexContext, LexToken, Lexer, LexingRule, Position, TokenKind,
+};
 
 /// JSON token definitions.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum JsonToken {
-    String { value: String, position: Position },
-    Number { value: String, position: Position },
-    Boolean { value: bool, position: Position },
-    Null { position: Position },
-    LeftBrace { position: Position },
-    RightBrace { position: Position },
-    LeftBracket { position: Position },
-    RightBracket { position: Position },
-    Comma { position: Position },
-    Colon { position: Position },
-    Whitespace { value: String, position: Position },
-    Eof { position: Position },
+    String {
+        value: String,
+        position: Position,
+    },
+    Number {
+        value: String,
+        position: Position,
+    },
+    Boolean {
+        value: bool,
+        position: Position,
+    },
+    Null {
+        position: Position,
+    },
+    LeftBrace {
+        position: Position,
+    },
+    RightBrace {
+        position: Position,
+    },
+    LeftBracket {
+        position: Position,
+    },
+    RightBracket {
+        position: Position,
+    },
+    Comma {
+        position: Position,
+    },
+    Colon {
+        position: Position,
+    },
+    Whitespace {
+        value: String,
+        position: Position,
+    },
+    /// A malformed literal, e.g. a string with an invalid escape sequence.
+    Error {
+        message: String,
+        position: Position,
+    },
+    Eof {
+        position: Position,
+    },
 }
 
 impl LexToken for JsonToken {
@@ -34,28 +69,25 @@ impl LexToken for JsonToken {
             | JsonToken::Comma { position }
             | JsonToken::Colon { position }
             | JsonToken::Whitespace { position, .. }
+            | JsonToken::Error { position, .. }
             | JsonToken::Eof { position } => *position,
         })
     }
 
-    fn is_eof(&self) -> bool {
-        matches!(self, JsonToken::Eof { .. })
-    }
-
-    fn is_newline(&self) -> bool {
-        false
-    }
-
-    fn is_whitespace(&self) -> bool {
-        matches!(self, JsonToken::Whitespace { .. })
-    }
-
-    fn is_indent(&self) -> bool {
-        false
+    fn kind(&self) -> Option<TokenKind> {
+        match self {
+            JsonToken::Eof { .. } => Some(TokenKind::Eof),
+            JsonToken::Whitespace { .. } => Some(TokenKind::Whitespace),
+            _ => None,
+        }
     }
 }
 
 /// Matches JSON strings, including escaped characters.
+///
+/// Escapes (including `\uXXXX` and surrogate pairs) are decoded with
+/// [`decode_escape`]; an invalid escape turns the whole literal into an
+/// [`JsonToken::Error`] token so the problem is reported instead of passed through.
 pub struct StringRule;
 
 impl<Ctx> LexingRule<Ctx, JsonToken> for StringRule
@@ -71,42 +103,36 @@ where
         ctx.advance(); // consume opening quote
 
         let mut value = String::new();
-        let mut escape = false;
+        let mut error = None;
 
         loop {
             match ctx.peek() {
                 None => return None, // Unterminated string
-                Some('"') if !escape => {
+                Some('"') => {
                     ctx.advance(); // consume closing quote
                     break;
                 }
-                Some('\\') if !escape => {
-                    escape = true;
-                    ctx.advance();
-                }
-                Some(ch) => {
-                    if escape {
-                        match ch {
-                            'n' => value.push('\n'),
-                            't' => value.push('\t'),
-                            'r' => value.push('\r'),
-                            '\\' => value.push('\\'),
-                            '"' => value.push('"'),
-                            _ => {
-                                value.push('\\');
-                                value.push(ch);
-                            }
-                        }
-                        escape = false;
-                    } else {
-                        value.push(ch);
+                Some('\\') => match decode_escape(ctx) {
+                    Ok(ch) => value.push(ch),
+                    Err(err) => {
+                        // Keep scanning to the closing quote, reporting the first error.
+                        error.get_or_insert(err);
                     }
+                },
+                Some(ch) => {
+                    value.push(ch);
                     ctx.advance();
                 }
             }
         }
 
-        Some(JsonToken::String { value, position })
+        match error {
+            Some(err) => Some(JsonToken::Error {
+                message: err.to_string(),
+                position,
+            }),
+            None => Some(JsonToken::String { value, position }),
+        }
     }
 
     fn priority(&self) -> i32 {
@@ -337,7 +363,9 @@ fn main() {
         "age": 30,
         "active": true,
         "tags": ["developer", "rust"],
-        "address": null
+        "address": null,
+        "greeting": "caf\u00e9 \ud83d\ude00",
+        "broken": "\ud83d"
     }"#;
 
     println!("Tokenizing JSON:");
@@ -0,0 +1,541 @@
+use crate::context::LexContext;
+use crate::lexer::Lexer;
+use std::marker::PhantomData;
+use std::sync::mpsc::Sender;
+
+/// A token producer that transforms every token with a closure.
+///
+/// Created by [`Lexer::map_tokens`]. Besides being an [`Iterator`], it
+/// implements `TokenProducer`, `Outbound` and `Inbound` for the mapped token
+/// type (with the `streaming` feature), so it can be handed to a
+/// `StreamingPipeline` in place of the lexer.
+pub struct MapTokens<L, Tok, F> {
+    inner: L,
+    f: F,
+    _marker: PhantomData<fn(Tok)>,
+}
+
+impl<L, Tok, F> MapTokens<L, Tok, F> {
+    /// Wraps `inner`, passing each of its tokens through `f`.
+    pub fn new(inner: L, f: F) -> Self {
+        Self {
+            inner,
+            f,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the wrapped producer.
+    pub fn inner(&self) -> &L {
+        &self.inner
+    }
+
+    /// Returns the wrapped producer mutably, e.g. to inspect its context.
+    pub fn inner_mut(&mut self) -> &mut L {
+        &mut self.inner
+    }
+
+    /// Unwraps the adapter.
+    pub fn into_inner(self) -> L {
+        self.inner
+    }
+}
+
+impl<L, Tok, F, U> Iterator for MapTokens<L, Tok, F>
+where
+    L: Iterator<Item = Tok>,
+    F: FnMut(Tok) -> U,
+{
+    type Item = U;
+
+    fn next(&mut self) -> Option<U> {
+        self.inner.next().map(&mut self.f)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// A token producer that calls a closure on every token before passing it
+/// on unchanged.
+///
+/// Created by [`Lexer::inspect_tokens`]; useful for logging, counting or
+/// collecting statistics without touching the pipeline.
+pub struct InspectTokens<L, F> {
+    inner: L,
+    f: F,
+}
+
+impl<L, F> InspectTokens<L, F> {
+    /// Wraps `inner`, showing each of its tokens to `f`.
+    pub fn new(inner: L, f: F) -> Self {
+        Self { inner, f }
+    }
+
+    /// Returns the wrapped producer.
+    pub fn inner(&self) -> &L {
+        &self.inner
+    }
+
+    /// Returns the wrapped producer mutably, e.g. to inspect its context.
+    pub fn inner_mut(&mut self) -> &mut L {
+        &mut self.inner
+    }
+
+    /// Unwraps the adapter.
+    pub fn into_inner(self) -> L {
+        self.inner
+    }
+}
+
+impl<L, F> Iterator for InspectTokens<L, F>
+where
+    L: Iterator,
+    F: FnMut(&L::Item),
+{
+    type Item = L::Item;
+
+    fn next(&mut self) -> Option<L::Item> {
+        let token = self.inner.next()?;
+        (self.f)(&token);
+        Some(token)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// A token producer that drops the tokens a predicate rejects.
+///
+/// Created by [`Lexer::filter_tokens`], e.g. to keep whitespace and
+/// comments away from the parser. Use [`TeeProducer`] instead if the
+/// dropped tokens are still needed.
+pub struct FilterTokens<L, F> {
+    inner: L,
+    keep: F,
+}
+
+impl<L, F> FilterTokens<L, F> {
+    /// Wraps `inner`, passing on only the tokens for which `keep` returns
+    /// `true`.
+    pub fn new(inner: L, keep: F) -> Self {
+        Self { inner, keep }
+    }
+
+    /// Returns the wrapped producer.
+    pub fn inner(&self) -> &L {
+        &self.inner
+    }
+
+    /// Returns the wrapped producer mutably, e.g. to inspect its context.
+    pub fn inner_mut(&mut self) -> &mut L {
+        &mut self.inner
+    }
+
+    /// Unwraps the adapter.
+    pub fn into_inner(self) -> L {
+        self.inner
+    }
+}
+
+impl<L, F> Iterator for FilterTokens<L, F>
+where
+    L: Iterator,
+    F: FnMut(&L::Item) -> bool,
+{
+    type Item = L::Item;
+
+    fn next(&mut self) -> Option<L::Item> {
+        loop {
+            let token = self.inner.next()?;
+            if (self.keep)(&token) {
+                return Some(token);
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inner.size_hint().1)
+    }
+}
+
+/// Destination for the raw token stream of a [`TeeProducer`].
+pub trait TokenSink<Tok> {
+    /// Receives one token.
+    fn accept(&mut self, token: Tok);
+}
+
+impl<Tok> TokenSink<Tok> for Vec<Tok> {
+    fn accept(&mut self, token: Tok) {
+        self.push(token);
+    }
+}
+
+impl<Tok> TokenSink<Tok> for &mut Vec<Tok> {
+    fn accept(&mut self, token: Tok) {
+        self.push(token);
+    }
+}
+
+impl<Tok> TokenSink<Tok> for Sender<Tok> {
+    fn accept(&mut self, token: Tok) {
+        // A dropped receiver only means nobody wants the raw stream anymore.
+        let _ = self.send(token);
+    }
+}
+
+/// A token producer that copies every token into a sink and forwards only
+/// the ones accepted by a predicate.
+///
+/// Created by [`Lexer::tee`]. One lexer pass yields both the full raw stream
+/// (including whitespace and comments, for formatters or highlighters) in the
+/// sink, and the significant tokens for the parser.
+pub struct TeeProducer<L, S, F> {
+    inner: L,
+    sink: S,
+    forward: F,
+}
+
+impl<L, S, F> TeeProducer<L, S, F> {
+    /// Wraps `inner`, copying its tokens into `sink` and forwarding those for
+    /// which `forward` returns `true`.
+    pub fn new(inner: L, sink: S, forward: F) -> Self {
+        Self {
+            inner,
+            sink,
+            forward,
+        }
+    }
+
+    /// Returns the wrapped producer.
+    pub fn inner(&self) -> &L {
+        &self.inner
+    }
+
+    /// Returns the wrapped producer mutably, e.g. to inspect its context.
+    pub fn inner_mut(&mut self) -> &mut L {
+        &mut self.inner
+    }
+
+    /// Returns the sink holding the raw tokens seen so far.
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    /// Returns the sink mutably, e.g. to drain it between runs.
+    pub fn sink_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    /// Unwraps the adapter into the producer and the sink.
+    pub fn into_parts(self) -> (L, S) {
+        (self.inner, self.sink)
+    }
+
+    /// Sends `token` to the sink and returns it if it should be forwarded.
+    fn split<Tok>(&mut self, token: Tok) -> Option<Tok>
+    where
+        Tok: Clone,
+        S: TokenSink<Tok>,
+        F: FnMut(&Tok) -> bool,
+    {
+        if (self.forward)(&token) {
+            self.sink.accept(token.clone());
+            Some(token)
+        } else {
+            self.sink.accept(token);
+            None
+        }
+    }
+}
+
+impl<L, S, F> Iterator for TeeProducer<L, S, F>
+where
+    L: Iterator,
+    L::Item: Clone,
+    S: TokenSink<L::Item>,
+    F: FnMut(&L::Item) -> bool,
+{
+    type Item = L::Item;
+
+    fn next(&mut self) -> Option<L::Item> {
+        loop {
+            let token = self.inner.next()?;
+            if let Some(token) = self.split(token) {
+                return Some(token);
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inner.size_hint().1)
+    }
+}
+
+impl<Ctx, Tok> Lexer<Ctx, Tok>
+where
+    Ctx: LexContext,
+{
+    /// Copies every token into `sink` and forwards only those for which
+    /// `forward` returns `true`.
+    pub fn tee<S, F>(self, sink: S, forward: F) -> TeeProducer<Self, S, F>
+    where
+        Tok: Clone,
+        S: TokenSink<Tok>,
+        F: FnMut(&Tok) -> bool,
+    {
+        TeeProducer::new(self, sink, forward)
+    }
+
+    /// Transforms every token with `f`, e.g. to attach extra data or to
+    /// convert into the token type a parser expects.
+    pub fn map_tokens<U, F>(self, f: F) -> MapTokens<Self, Tok, F>
+    where
+        F: FnMut(Tok) -> U,
+    {
+        MapTokens::new(self, f)
+    }
+
+    /// Calls `f` on every token as it is produced.
+    pub fn inspect_tokens<F>(self, f: F) -> InspectTokens<Self, F>
+    where
+        F: FnMut(&Tok),
+    {
+        InspectTokens::new(self, f)
+    }
+
+    /// Passes on only the tokens for which `keep` returns `true`.
+    pub fn filter_tokens<F>(self, keep: F) -> FilterTokens<Self, F>
+    where
+        F: FnMut(&Tok) -> bool,
+    {
+        FilterTokens::new(self, keep)
+    }
+}
+
+#[cfg(feature = "streaming")]
+mod streaming_impls {
+    use super::{FilterTokens, InspectTokens, MapTokens, TeeProducer, TokenSink};
+    use crate::streaming::TokenProducer;
+    use common_framework::{Inbound, Outbound, Position, StreamingSignal};
+
+    /// Re-types a signal, converting carried tokens with `f`. Signals with a
+    /// token that cannot be converted are dropped.
+    fn map_signal<A, B, Ast>(
+        signal: StreamingSignal<A, Ast>,
+        mut f: impl FnMut(A) -> Option<B>,
+    ) -> Option<StreamingSignal<B, Ast>> {
+        Some(match signal {
+            StreamingSignal::SupplyToken(token) => StreamingSignal::SupplyToken(f(token)?),
+            StreamingSignal::SupplyTokens(tokens) => {
+                StreamingSignal::SupplyTokens(tokens.into_iter().map(f).collect::<Option<_>>()?)
+            }
+            StreamingSignal::RequestToken(n) => StreamingSignal::RequestToken(n),
+            StreamingSignal::RequestTokens(n) => StreamingSignal::RequestTokens(n),
+            StreamingSignal::TokenDelivered => StreamingSignal::TokenDelivered,
+            StreamingSignal::Produced(nodes) => StreamingSignal::Produced(nodes),
+            StreamingSignal::NeedToken(n) => StreamingSignal::NeedToken(n),
+            StreamingSignal::Finished(nodes) => StreamingSignal::Finished(nodes),
+            StreamingSignal::Blocked(reason) => StreamingSignal::Blocked(reason),
+            StreamingSignal::EndOfInput => StreamingSignal::EndOfInput,
+            StreamingSignal::Resume => StreamingSignal::Resume,
+            StreamingSignal::NeedInput => StreamingSignal::NeedInput,
+            StreamingSignal::SupplyInput(text) => StreamingSignal::SupplyInput(text),
+            StreamingSignal::Abort(reason) => StreamingSignal::Abort(reason),
+            StreamingSignal::Feedback(feedback) => StreamingSignal::Feedback(feedback),
+            StreamingSignal::Progress(progress) => StreamingSignal::Progress(progress),
+        })
+    }
+
+    impl<L, Tok, F, U> TokenProducer<U> for MapTokens<L, Tok, F>
+    where
+        L: TokenProducer<Tok>,
+        F: FnMut(Tok) -> U,
+    {
+        fn poll_token(&mut self) -> Option<U> {
+            self.inner.poll_token().map(&mut self.f)
+        }
+
+        fn input_position(&self) -> Option<Position> {
+            self.inner.input_position()
+        }
+    }
+
+    impl<L, Tok, F, U, Ast> Outbound<U, Ast> for MapTokens<L, Tok, F>
+    where
+        L: Outbound<Tok, Ast>,
+        F: FnMut(Tok) -> U,
+    {
+        fn next_signal(&mut self) -> Option<StreamingSignal<U, Ast>> {
+            let signal = self.inner.next_signal()?;
+            map_signal(signal, |token| Some((self.f)(token)))
+        }
+    }
+
+    impl<L, Tok, F, U, Ast> Inbound<U, Ast> for MapTokens<L, Tok, F>
+    where
+        L: Inbound<Tok, Ast>,
+    {
+        fn handle_signal(&mut self, signal: StreamingSignal<U, Ast>) {
+            // Mapped tokens cannot be turned back into the inner token type,
+            // so only control signals are forwarded.
+            if let Some(signal) = map_signal(signal, |_| None) {
+                self.inner.handle_signal(signal);
+            }
+        }
+    }
+
+    impl<L, F, Tok> TokenProducer<Tok> for InspectTokens<L, F>
+    where
+        L: TokenProducer<Tok>,
+        F: FnMut(&Tok),
+    {
+        fn poll_token(&mut self) -> Option<Tok> {
+            let token = self.inner.poll_token()?;
+            (self.f)(&token);
+            Some(token)
+        }
+
+        fn input_position(&self) -> Option<Position> {
+            self.inner.input_position()
+        }
+    }
+
+    impl<L, F, Tok, Ast> Outbound<Tok, Ast> for InspectTokens<L, F>
+    where
+        L: Outbound<Tok, Ast>,
+        F: FnMut(&Tok),
+    {
+        fn next_signal(&mut self) -> Option<StreamingSignal<Tok, Ast>> {
+            let signal = self.inner.next_signal()?;
+            match &signal {
+                StreamingSignal::SupplyToken(token) => (self.f)(token),
+                StreamingSignal::SupplyTokens(tokens) => tokens.iter().for_each(&mut self.f),
+                _ => {}
+            }
+            Some(signal)
+        }
+    }
+
+    impl<L, F, Tok, Ast> Inbound<Tok, Ast> for InspectTokens<L, F>
+    where
+        L: Inbound<Tok, Ast>,
+    {
+        fn handle_signal(&mut self, signal: StreamingSignal<Tok, Ast>) {
+            self.inner.handle_signal(signal);
+        }
+    }
+
+    impl<L, F, Tok> TokenProducer<Tok> for FilterTokens<L, F>
+    where
+        L: TokenProducer<Tok>,
+        F: FnMut(&Tok) -> bool,
+    {
+        fn poll_token(&mut self) -> Option<Tok> {
+            loop {
+                let token = self.inner.poll_token()?;
+                if (self.keep)(&token) {
+                    return Some(token);
+                }
+            }
+        }
+
+        fn input_position(&self) -> Option<Position> {
+            self.inner.input_position()
+        }
+    }
+
+    impl<L, F, Tok, Ast> Outbound<Tok, Ast> for FilterTokens<L, F>
+    where
+        L: Outbound<Tok, Ast>,
+        F: FnMut(&Tok) -> bool,
+    {
+        fn next_signal(&mut self) -> Option<StreamingSignal<Tok, Ast>> {
+            loop {
+                match self.inner.next_signal()? {
+                    StreamingSignal::SupplyToken(token) => {
+                        if (self.keep)(&token) {
+                            return Some(StreamingSignal::SupplyToken(token));
+                        }
+                    }
+                    StreamingSignal::SupplyTokens(mut tokens) => {
+                        tokens.retain(|token| (self.keep)(token));
+                        if !tokens.is_empty() {
+                            return Some(StreamingSignal::SupplyTokens(tokens));
+                        }
+                    }
+                    signal => return Some(signal),
+                }
+            }
+        }
+    }
+
+    impl<L, F, Tok, Ast> Inbound<Tok, Ast> for FilterTokens<L, F>
+    where
+        L: Inbound<Tok, Ast>,
+    {
+        fn handle_signal(&mut self, signal: StreamingSignal<Tok, Ast>) {
+            self.inner.handle_signal(signal);
+        }
+    }
+
+    impl<L, S, F, Tok> TokenProducer<Tok> for TeeProducer<L, S, F>
+    where
+        L: TokenProducer<Tok>,
+        Tok: Clone,
+        S: TokenSink<Tok>,
+        F: FnMut(&Tok) -> bool,
+    {
+        fn poll_token(&mut self) -> Option<Tok> {
+            loop {
+                let token = self.inner.poll_token()?;
+                if let Some(token) = self.split(token) {
+                    return Some(token);
+                }
+            }
+        }
+
+        fn input_position(&self) -> Option<Position> {
+            self.inner.input_position()
+        }
+    }
+
+    impl<L, S, F, Tok, Ast> Outbound<Tok, Ast> for TeeProducer<L, S, F>
+    where
+        L: Outbound<Tok, Ast>,
+        Tok: Clone,
+        S: TokenSink<Tok>,
+        F: FnMut(&Tok) -> bool,
+    {
+        fn next_signal(&mut self) -> Option<StreamingSignal<Tok, Ast>> {
+            loop {
+                match self.inner.next_signal()? {
+                    StreamingSignal::SupplyToken(token) => {
+                        if let Some(token) = self.split(token) {
+                            return Some(StreamingSignal::SupplyToken(token));
+                        }
+                    }
+                    StreamingSignal::SupplyTokens(tokens) => {
+                        let forwarded: Vec<_> =
+                            tokens.into_iter().filter_map(|t| self.split(t)).collect();
+                        if !forwarded.is_empty() {
+                            return Some(StreamingSignal::SupplyTokens(forwarded));
+                        }
+                    }
+                    signal => return Some(signal),
+                }
+            }
+        }
+    }
+
+    impl<L, S, F, Tok, Ast> Inbound<Tok, Ast> for TeeProducer<L, S, F>
+    where
+        L: Inbound<Tok, Ast>,
+    {
+        fn handle_signal(&mut self, signal: StreamingSignal<Tok, Ast>) {
+            self.inner.handle_signal(signal);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::context::LexContext;
+use crate::lexer::Lexer;
+use common_framework::RuleId;
+use std::fmt;
+
+/// Two rules with the same priority that can both start at the same
+/// character.
+///
+/// Such ties are resolved by registration order (see
+/// [`Lexer::registration_order`]), which is easy to break by reordering a
+/// rule list. Giving the rules distinct priorities makes the intent explicit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriorityConflict {
+    /// The shared priority.
+    pub priority: i32,
+    /// Name of the rule that wins the tie (registered first).
+    pub winner: String,
+    /// Id of the winning rule.
+    pub winner_id: RuleId,
+    /// Name of the rule that is shadowed on the overlapping characters.
+    pub shadowed: String,
+    /// Id of the shadowed rule.
+    pub shadowed_id: RuleId,
+    /// ASCII characters both rules accept in `quick_check`.
+    pub chars: Vec<char>,
+}
+
+impl fmt::Display for PriorityConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "rules `{}` and `{}` share priority {} and both accept ",
+            self.winner, self.shadowed, self.priority
+        )?;
+        if self.chars.len() == 128 {
+            write!(f, "any ASCII character")?;
+        } else {
+            let chars: Vec<String> = self.chars.iter().map(|ch| format!("{:?}", ch)).collect();
+            write!(f, "{}", chars.join(", "))?;
+        }
+        write!(f, "; `{}` wins by registration order", self.winner)
+    }
+}
+
+impl<Ctx, Tok> Lexer<Ctx, Tok>
+where
+    Ctx: LexContext,
+{
+    /// Reports pairs of enabled rules with equal priority whose `quick_check`
+    /// accepts a common ASCII first character.
+    ///
+    /// Rules without a `quick_check` accept every character, so two of them
+    /// at the same priority always conflict. Non-ASCII input is not analysed.
+    pub fn priority_conflicts(&self) -> Vec<PriorityConflict> {
+        let active: Vec<usize> = (0..self.rules().len())
+            .filter(|&idx| self.is_rule_active(idx))
+            .collect();
+
+        let mut conflicts = Vec::new();
+        for (i, &a) in active.iter().enumerate() {
+            for &b in &active[i + 1..] {
+                let (first, second) = (&self.rules()[a], &self.rules()[b]);
+                if first.priority() != second.priority() {
+                    continue;
+                }
+                let chars: Vec<char> = (0..128u8)
+                    .map(char::from)
+                    .filter(|&ch| {
+                        first.quick_check(Some(ch)) != Some(false)
+                            && second.quick_check(Some(ch)) != Some(false)
+                    })
+                    .collect();
+                if chars.is_empty() {
+                    continue;
+                }
+                // `rules()` is sorted by (priority, registration order), so
+                // `first` is the one tried first.
+                conflicts.push(PriorityConflict {
+                    priority: first.priority(),
+                    winner: first.name().to_string(),
+                    winner_id: first.id(),
+                    shadowed: second.name().to_string(),
+                    shadowed_id: second.id(),
+                    chars,
+                });
+            }
+        }
+        conflicts
+    }
+}
@@ -0,0 +1,106 @@
+use crate::context::{DefaultContext, LexContext};
+use crate::lexer::Lexer;
+use crate::traits::LexingRule;
+use common_framework::{AllocStats, BenchReport, Channel, RuleStats};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Lexes `input` `iterations` times with fresh rules from `rules` and
+/// reports throughput and per-rule counts.
+///
+/// Only the lexing itself is timed, not building the rules. The per-rule
+/// counts come from one extra, untimed run in which every rule is wrapped to
+/// count its attempts, so the wrapping does not skew the timings. With the
+/// `alloc-stats` feature and `CountingAllocator` installed, that run also
+/// counts allocations, per rule and in total.
+///
+/// Meant for downstream crates that want to compare rule sets or guard
+/// against regressions in ordinary tests, without criterion:
+///
+/// ```ignore
+/// let report = bench_lexer(&source, my_rules, 20);
+/// assert!(report.speedup_over(&bench_lexer(&source, baseline_rules, 20)) > 0.9);
+/// ```
+pub fn bench_lexer<Tok: 'static, F>(input: &str, mut rules: F, iterations: u32) -> BenchReport
+where
+    F: FnMut() -> Vec<Box<dyn LexingRule<DefaultContext, Tok>>>,
+{
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..iterations {
+        let lexer = Lexer::from_str(input, rules());
+        let start = Instant::now();
+        std::hint::black_box(lexer.count());
+        elapsed += start.elapsed();
+    }
+
+    let stats = Rc::new(RefCell::new(Vec::new()));
+    let mut priorities = Vec::new();
+    let counted = rules()
+        .into_iter()
+        .enumerate()
+        .map(|(index, inner)| {
+            stats.borrow_mut().push(RuleStats::new(inner.name()));
+            priorities.push(inner.priority());
+            Box::new(CountedRule {
+                inner,
+                index,
+                stats: stats.clone(),
+            }) as Box<dyn LexingRule<DefaultContext, Tok>>
+        })
+        .collect();
+    let lexer = Lexer::from_str(input, counted);
+    let (output, allocations) = AllocStats::measure(|| lexer.count());
+
+    // Report rules in the order the lexer tries them: by priority, then
+    // registration order.
+    let mut rules: Vec<_> = priorities.into_iter().zip(stats.take()).collect();
+    rules.sort_by_key(|(priority, _)| std::cmp::Reverse(*priority));
+    let rules = rules.into_iter().map(|(_, stats)| stats).collect();
+
+    BenchReport {
+        iterations,
+        elapsed,
+        input: input.len(),
+        output,
+        rules,
+        allocations,
+    }
+}
+
+/// Counts the attempts, matches and allocations of the rule it wraps.
+struct CountedRule<Ctx, Tok> {
+    inner: Box<dyn LexingRule<Ctx, Tok>>,
+    index: usize,
+    stats: Rc<RefCell<Vec<RuleStats>>>,
+}
+
+impl<Ctx, Tok> LexingRule<Ctx, Tok> for CountedRule<Ctx, Tok>
+where
+    Ctx: LexContext,
+{
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        let (token, allocations) = AllocStats::measure(|| self.inner.try_match(ctx));
+        let stats = &mut self.stats.borrow_mut()[self.index];
+        stats.attempts += 1;
+        stats.allocations += allocations;
+        stats.matches += u64::from(token.is_some());
+        token
+    }
+
+    fn priority(&self) -> i32 {
+        self.inner.priority()
+    }
+
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        self.inner.quick_check(first_char)
+    }
+
+    fn channel(&self) -> Channel {
+        self.inner.channel()
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
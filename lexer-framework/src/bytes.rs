@@ -0,0 +1,517 @@
+use common_framework::{
+    short_type_name, Checkpoint, ContextId, Diagnostic, Diagnostics, Position, RuleId, StateMap,
+};
+use std::cmp::Reverse;
+use std::sync::Arc;
+
+/// A cursor over raw bytes, for inputs that are not (entirely) UTF-8 text,
+/// such as network protocols with length-prefixed binary payloads.
+///
+/// Positions count bytes: every byte advances the column, and `\n` starts a
+/// new line.
+#[derive(Debug, Clone)]
+pub struct ByteCursor {
+    buffer: Arc<[u8]>,
+    current: usize,
+    position: Position,
+    id: ContextId,
+}
+
+impl ByteCursor {
+    /// Creates a cursor over `input`.
+    pub fn new<B: Into<Vec<u8>>>(input: B) -> Self {
+        Self::with_arc(Arc::from(input.into()))
+    }
+
+    /// Creates a cursor from an existing shared buffer.
+    pub fn with_arc(buffer: Arc<[u8]>) -> Self {
+        Self {
+            buffer,
+            current: 0,
+            position: Position::new(),
+            id: ContextId::fresh(),
+        }
+    }
+
+    /// Returns the current position in the source.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Returns the current offset in bytes.
+    pub fn offset(&self) -> usize {
+        self.current
+    }
+
+    /// Returns true if the cursor is at the end of the input.
+    pub fn is_eof(&self) -> bool {
+        self.current >= self.buffer.len()
+    }
+
+    /// Returns the next byte without advancing the cursor.
+    pub fn peek_byte(&self) -> Option<u8> {
+        self.buffer.get(self.current).copied()
+    }
+
+    /// Returns up to `n` bytes from the current position without advancing.
+    pub fn peek_bytes(&self, n: usize) -> &[u8] {
+        let end = (self.current + n).min(self.buffer.len());
+        &self.buffer[self.current..end]
+    }
+
+    /// Advances the cursor by one byte.
+    pub fn advance_byte(&mut self) -> Option<u8> {
+        let byte = self.peek_byte()?;
+        if byte == b'\n' {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+        self.position.offset += 1;
+        self.current += 1;
+        Some(byte)
+    }
+
+    /// Advances the cursor by up to `n` bytes and returns the bytes skipped.
+    pub fn advance_bytes(&mut self, n: usize) -> &[u8] {
+        let start = self.current;
+        for _ in 0..n {
+            if self.advance_byte().is_none() {
+                break;
+            }
+        }
+        &self.buffer[start..self.current]
+    }
+
+    /// Consumes bytes while the predicate returns true.
+    pub fn consume_while_bytes<F>(&mut self, mut predicate: F) -> &[u8]
+    where
+        F: FnMut(u8) -> bool,
+    {
+        let start = self.current;
+        while let Some(byte) = self.peek_byte() {
+            if !predicate(byte) {
+                break;
+            }
+            self.advance_byte();
+        }
+        &self.buffer[start..self.current]
+    }
+
+    /// Returns the input from byte offset `start` up to the current
+    /// position.
+    pub fn bytes_from(&self, start: usize) -> &[u8] {
+        debug_assert!(start <= self.current, "slice start is ahead of the cursor");
+        &self.buffer[start..self.current]
+    }
+
+    /// Returns the remaining input from the current position.
+    pub fn remaining(&self) -> &[u8] {
+        &self.buffer[self.current..]
+    }
+
+    /// Creates a checkpoint that can be restored later.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint::new(self.current, self.position).with_context(self.id)
+    }
+
+    /// Restores the cursor to a previous checkpoint.
+    pub fn restore(&mut self, checkpoint: Checkpoint) {
+        checkpoint.debug_assert_context(self.id);
+        self.current = checkpoint.current();
+        self.position = checkpoint.position();
+    }
+}
+
+/// The byte-level counterpart of [`LexContext`](crate::LexContext).
+///
+/// Rules see raw `u8`s instead of `char`s, so they can lex protocols such
+/// as HTTP or RESP whose headers are text but whose payloads may not be
+/// valid UTF-8.
+pub trait ByteLexContext {
+    /// Returns a reference to the cursor.
+    fn byte_cursor(&self) -> &ByteCursor;
+
+    /// Returns a mutable reference to the cursor.
+    fn byte_cursor_mut(&mut self) -> &mut ByteCursor;
+
+    /// Returns the current position.
+    fn position(&self) -> Position {
+        self.byte_cursor().position()
+    }
+
+    /// Returns true if at end of input.
+    fn is_eof(&self) -> bool {
+        self.byte_cursor().is_eof()
+    }
+
+    /// Returns the current byte offset in the input.
+    fn offset(&self) -> usize {
+        self.byte_cursor().offset()
+    }
+
+    /// Peeks at the next byte without advancing.
+    fn peek_byte(&self) -> Option<u8> {
+        self.byte_cursor().peek_byte()
+    }
+
+    /// Peeks at up to `n` bytes without advancing.
+    fn peek_bytes(&self, n: usize) -> &[u8] {
+        self.byte_cursor().peek_bytes(n)
+    }
+
+    /// Advances the cursor and returns the byte.
+    fn advance_byte(&mut self) -> Option<u8> {
+        self.byte_cursor_mut().advance_byte()
+    }
+
+    /// Advances by up to `n` bytes, e.g. over a length-prefixed payload,
+    /// and returns them.
+    fn advance_bytes(&mut self, n: usize) -> &[u8] {
+        self.byte_cursor_mut().advance_bytes(n)
+    }
+
+    /// Consumes bytes while the predicate returns true.
+    fn consume_while_bytes<F>(&mut self, predicate: F) -> &[u8]
+    where
+        F: FnMut(u8) -> bool,
+    {
+        self.byte_cursor_mut().consume_while_bytes(predicate)
+    }
+
+    /// Consumes `expected` if the input continues with it.
+    fn consume_bytes(&mut self, expected: &[u8]) -> bool {
+        if self.peek_bytes(expected.len()) != expected {
+            return false;
+        }
+        self.advance_bytes(expected.len());
+        true
+    }
+
+    /// Returns the input between `start` (an earlier [`offset`](Self::offset))
+    /// and the current position.
+    fn bytes_from(&self, start: usize) -> &[u8] {
+        self.byte_cursor().bytes_from(start)
+    }
+
+    /// Creates a checkpoint of the current state.
+    fn checkpoint(&self) -> Checkpoint {
+        self.byte_cursor().checkpoint()
+    }
+
+    /// Restores the cursor to a checkpoint.
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        self.byte_cursor_mut().restore(checkpoint);
+    }
+
+    /// Runs `f` speculatively: if it returns `None`, everything it consumed
+    /// is rolled back.
+    fn speculate<T, F>(&mut self, f: F) -> Option<T>
+    where
+        Self: Sized,
+        F: FnOnce(&mut Self) -> Option<T>,
+    {
+        let checkpoint = self.checkpoint();
+        let result = f(self);
+        if result.is_none() {
+            self.restore(checkpoint);
+        }
+        result
+    }
+
+    /// Returns the number of bytes left, if known.
+    fn remaining_len(&self) -> Option<usize> {
+        Some(self.byte_cursor().remaining().len())
+    }
+
+    /// Returns the context's user state map, if it has one.
+    fn state_map(&mut self) -> Option<&mut StateMap> {
+        None
+    }
+
+    /// Reports a warning or error about the input. See
+    /// [`LexContext::report`](crate::LexContext::report).
+    fn report(&mut self, diagnostic: Diagnostic) {
+        if let Some(state) = self.state_map() {
+            state.get_or_default::<Diagnostics>().report(diagnostic);
+        }
+    }
+
+    /// Routes [`report`](Self::report) through `diagnostics` from now on.
+    /// See [`LexContext::set_diagnostics`](crate::LexContext::set_diagnostics).
+    fn set_diagnostics(&mut self, diagnostics: Diagnostics) {
+        if let Some(state) = self.state_map() {
+            state.insert(diagnostics);
+        }
+    }
+
+    /// Removes and returns the diagnostics collected so far, oldest first.
+    fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        self.state_map()
+            .and_then(|state| state.get::<Diagnostics>().map(Diagnostics::take))
+            .unwrap_or_default()
+    }
+
+    /// Returns the rule-shared state of type `T`, creating it with
+    /// `T::default()` on first use. See [`LexContext::state`](crate::LexContext::state).
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`state_map`](Self::state_map) returns `None`.
+    fn state<T: Default + Send + 'static>(&mut self) -> &mut T
+    where
+        Self: Sized,
+    {
+        self.state_map()
+            .expect("this context has no state map; override `state_map`")
+            .get_or_default()
+    }
+}
+
+/// A simple byte context over an in-memory buffer.
+#[derive(Debug)]
+pub struct DefaultByteContext {
+    cursor: ByteCursor,
+    state: StateMap,
+}
+
+impl DefaultByteContext {
+    pub fn new<B: Into<Vec<u8>>>(input: B) -> Self {
+        Self::from_cursor(ByteCursor::new(input))
+    }
+
+    pub fn from_cursor(cursor: ByteCursor) -> Self {
+        Self {
+            cursor,
+            state: StateMap::new(),
+        }
+    }
+}
+
+impl ByteLexContext for DefaultByteContext {
+    fn byte_cursor(&self) -> &ByteCursor {
+        &self.cursor
+    }
+
+    fn byte_cursor_mut(&mut self) -> &mut ByteCursor {
+        &mut self.cursor
+    }
+
+    fn state_map(&mut self) -> Option<&mut StateMap> {
+        Some(&mut self.state)
+    }
+}
+
+/// A lexing rule over a [`ByteLexContext`]; the byte-level counterpart of
+/// [`LexingRule`](crate::LexingRule).
+pub trait ByteLexingRule<Ctx, Tok>
+where
+    Ctx: ByteLexContext,
+{
+    /// Attempts to match and consume a token from the context.
+    /// The cursor should only be advanced if a token is successfully matched.
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok>;
+
+    /// Returns the priority of this rule. Higher priority rules are tried first.
+    fn priority(&self) -> i32 {
+        0
+    }
+
+    /// Quick check on the first byte; see
+    /// [`LexingRule::quick_check`](crate::LexingRule::quick_check).
+    #[inline]
+    fn quick_check(&self, first_byte: Option<u8>) -> Option<bool> {
+        let _ = first_byte;
+        None
+    }
+
+    /// Returns a human-readable name used in traces and diagnostics.
+    fn name(&self) -> &str {
+        short_type_name::<Self>()
+    }
+
+    /// Returns a stable identifier for this rule, derived from its name.
+    fn id(&self) -> RuleId {
+        RuleId::of(self.name())
+    }
+}
+
+/// A lexer driving [`ByteLexingRule`]s, in priority order.
+///
+/// It behaves like [`Lexer`](crate::Lexer) (an iterator of tokens and, with
+/// the `streaming` feature, a `TokenProducer`), so byte-level lexers plug
+/// into the same pipelines as text lexers.
+pub struct ByteLexer<Ctx, Tok>
+where
+    Ctx: ByteLexContext,
+{
+    context: Ctx,
+    rules: Vec<Box<dyn ByteLexingRule<Ctx, Tok>>>,
+    // Indices into `rules` that might match each first byte.
+    lookup: Vec<Vec<usize>>,
+    last_rule: Option<usize>,
+}
+
+impl<Ctx, Tok> ByteLexer<Ctx, Tok>
+where
+    Ctx: ByteLexContext,
+{
+    /// Creates a new lexer with the given context and rules.
+    ///
+    /// Rules are tried by descending priority, then in the order given.
+    pub fn new(context: Ctx, mut rules: Vec<Box<dyn ByteLexingRule<Ctx, Tok>>>) -> Self {
+        // Stable sort keeps registration order among equal priorities.
+        rules.sort_by_key(|rule| Reverse(rule.priority()));
+        let lookup = (0..=u8::MAX)
+            .map(|byte| {
+                (0..rules.len())
+                    .filter(|&idx| rules[idx].quick_check(Some(byte)) != Some(false))
+                    .collect()
+            })
+            .collect();
+        Self {
+            context,
+            rules,
+            lookup,
+            last_rule: None,
+        }
+    }
+
+    /// Sends the diagnostics of this lexer and its rules to `diagnostics`.
+    /// See [`ByteLexContext::set_diagnostics`].
+    pub fn with_diagnostics(mut self, diagnostics: Diagnostics) -> Self {
+        self.context.set_diagnostics(diagnostics);
+        self
+    }
+
+    /// Returns a reference to the context.
+    pub fn context(&self) -> &Ctx {
+        &self.context
+    }
+
+    /// Returns a mutable reference to the context.
+    pub fn context_mut(&mut self) -> &mut Ctx {
+        &mut self.context
+    }
+
+    /// Returns the rules in the order they are tried (highest priority first).
+    pub fn rules(&self) -> &[Box<dyn ByteLexingRule<Ctx, Tok>>] {
+        &self.rules
+    }
+
+    /// Returns the rule that matched the most recent token.
+    pub fn last_rule(&self) -> Option<&dyn ByteLexingRule<Ctx, Tok>> {
+        self.last_rule.map(|idx| self.rules[idx].as_ref())
+    }
+
+    /// Tries to match the next token using the rules whose `quick_check`
+    /// accepts the next byte.
+    pub fn next_token(&mut self) -> Option<Tok> {
+        let first = self.context.peek_byte()?;
+        for &idx in &self.lookup[usize::from(first)] {
+            let rule = &mut self.rules[idx];
+            if let Some(token) = self.context.speculate(|ctx| rule.try_match(ctx)) {
+                self.last_rule = Some(idx);
+                return Some(token);
+            }
+        }
+        None
+    }
+
+    /// Collects all tokens from the input.
+    pub fn tokenize(&mut self) -> Vec<Tok> {
+        self.by_ref().collect()
+    }
+}
+
+impl<Tok> ByteLexer<DefaultByteContext, Tok> {
+    /// Creates a new lexer with a default context over `input`.
+    pub fn from_bytes<B: Into<Vec<u8>>>(
+        input: B,
+        rules: Vec<Box<dyn ByteLexingRule<DefaultByteContext, Tok>>>,
+    ) -> Self {
+        Self::new(DefaultByteContext::new(input), rules)
+    }
+}
+
+impl<Ctx, Tok> Iterator for ByteLexer<Ctx, Tok>
+where
+    Ctx: ByteLexContext,
+{
+    type Item = Tok;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset_before = self.context.offset();
+        let token = self.next_token();
+        if self.context.offset() == offset_before {
+            if let Some(byte) = self.context.peek_byte() {
+                let message = format!(
+                    "no rule matched byte {:#04x} at offset {}",
+                    byte, offset_before
+                );
+                let position = self.context.position();
+                self.context
+                    .report(Diagnostic::error((position, position), message));
+            }
+            return None;
+        }
+        token
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.context.remaining_len())
+    }
+}
+
+#[cfg(feature = "streaming")]
+mod streaming_impls {
+    use super::{ByteLexContext, ByteLexer};
+    use crate::streaming::TokenProducer;
+    use common_framework::{Diagnostic, Inbound, Outbound, Position, StreamingSignal};
+
+    impl<Ctx, Tok> TokenProducer<Tok> for ByteLexer<Ctx, Tok>
+    where
+        Ctx: ByteLexContext,
+    {
+        fn poll_token(&mut self) -> Option<Tok> {
+            self.next()
+        }
+
+        fn input_position(&self) -> Option<Position> {
+            Some(self.context().position())
+        }
+    }
+
+    impl<Ctx, Tok, Ast> Outbound<Tok, Ast> for ByteLexer<Ctx, Tok>
+    where
+        Ctx: ByteLexContext,
+    {
+        fn next_signal(&mut self) -> Option<StreamingSignal<Tok, Ast>> {
+            if let Some(token) = self.poll_token() {
+                return Some(StreamingSignal::SupplyToken(token));
+            }
+
+            if self.context().is_eof() {
+                return Some(StreamingSignal::EndOfInput);
+            }
+
+            None
+        }
+    }
+
+    impl<Ctx, Tok, Ast> Inbound<Tok, Ast> for ByteLexer<Ctx, Tok>
+    where
+        Ctx: ByteLexContext,
+    {
+        fn handle_signal(&mut self, signal: StreamingSignal<Tok, Ast>) {
+            if let StreamingSignal::Abort(reason) = signal {
+                let position = self.context().position();
+                self.context_mut().report(Diagnostic::error(
+                    (position, position),
+                    format!("lexer received abort: {}", reason),
+                ));
+            }
+        }
+    }
+}
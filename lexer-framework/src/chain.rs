@@ -0,0 +1,171 @@
+use crate::streaming::TokenProducer;
+use crate::traits::LexToken;
+use common_framework::{Inbound, Outbound, Position, StreamingSignal};
+
+/// A token producer that yields all tokens of one producer, then all tokens
+/// of another, as a single stream.
+///
+/// Created by [`TokenProducer::chain_producer`]; useful for multi-part inputs such as
+/// front matter followed by a body, or concatenated files. Chains nest, so
+/// `a.chain_producer(b).chain_producer(c)` covers three parts.
+///
+/// At the boundary, the EOF token of the first part is dropped so only the
+/// last part ends the stream. The position where the first part ended (its
+/// EOF token, or else [`TokenProducer::input_position`]) marks where the second
+/// part starts, and tokens of the second part are shifted there with
+/// [`LexToken::relocated`]. If neither is known, the second part's positions
+/// are passed through unchanged.
+pub struct ChainedProducer<A, B> {
+    first: A,
+    second: B,
+    first_done: bool,
+    boundary: Option<Position>,
+}
+
+impl<A, B> ChainedProducer<A, B> {
+    /// Chains `first` and `second`.
+    pub fn new(first: A, second: B) -> Self {
+        Self {
+            first,
+            second,
+            first_done: false,
+            boundary: None,
+        }
+    }
+
+    /// Returns the first producer.
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    /// Returns the second producer.
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+
+    /// Returns true once the first producer is exhausted.
+    pub fn in_second(&self) -> bool {
+        self.first_done
+    }
+
+    /// Returns where the second part starts in the combined input, once the
+    /// first part has ended and if that position is known.
+    pub fn boundary(&self) -> Option<Position> {
+        self.boundary
+    }
+
+    /// Unwraps the chain into its two producers.
+    pub fn into_parts(self) -> (A, B) {
+        (self.first, self.second)
+    }
+
+    /// Marks the first part as exhausted, falling back to its reported
+    /// position if it emitted no EOF token.
+    fn finish_first<Tok>(&mut self)
+    where
+        A: TokenProducer<Tok>,
+    {
+        self.first_done = true;
+        if self.boundary.is_none() {
+            self.boundary = self.first.input_position();
+        }
+    }
+
+    /// Records the first part's EOF token, or returns a regular token.
+    fn accept_first<Tok: LexToken>(&mut self, token: Tok) -> Option<Tok> {
+        if token.is_eof() {
+            self.boundary = token.position();
+            None
+        } else {
+            Some(token)
+        }
+    }
+
+    /// Moves a token of the second part to its place in the combined input.
+    fn relocate_second<Tok: LexToken>(&self, token: Tok) -> Tok {
+        match self.boundary {
+            Some(base) => token.relocated(base),
+            None => token,
+        }
+    }
+}
+
+impl<A, B, Tok> TokenProducer<Tok> for ChainedProducer<A, B>
+where
+    A: TokenProducer<Tok>,
+    B: TokenProducer<Tok>,
+    Tok: LexToken,
+{
+    fn poll_token(&mut self) -> Option<Tok> {
+        while !self.first_done {
+            match self.first.poll_token() {
+                Some(token) => {
+                    if let Some(token) = self.accept_first(token) {
+                        return Some(token);
+                    }
+                }
+                None => self.finish_first(),
+            }
+        }
+        let token = self.second.poll_token()?;
+        Some(self.relocate_second(token))
+    }
+
+    fn input_position(&self) -> Option<Position> {
+        if !self.first_done {
+            return self.first.input_position();
+        }
+        let position = self.second.input_position()?;
+        Some(match self.boundary {
+            Some(base) => position.rebase(base),
+            None => position,
+        })
+    }
+}
+
+impl<A, B, Tok, Ast> Outbound<Tok, Ast> for ChainedProducer<A, B>
+where
+    A: Outbound<Tok, Ast> + TokenProducer<Tok>,
+    B: Outbound<Tok, Ast>,
+    Tok: LexToken,
+{
+    fn next_signal(&mut self) -> Option<StreamingSignal<Tok, Ast>> {
+        while !self.first_done {
+            // `None` means the first part is waiting for more input.
+            match self.first.next_signal()? {
+                StreamingSignal::SupplyToken(token) => {
+                    if let Some(token) = self.accept_first(token) {
+                        return Some(StreamingSignal::SupplyToken(token));
+                    }
+                }
+                StreamingSignal::EndOfInput => self.finish_first(),
+                signal => return Some(signal),
+            }
+        }
+        match self.second.next_signal()? {
+            StreamingSignal::SupplyToken(token) => {
+                Some(StreamingSignal::SupplyToken(self.relocate_second(token)))
+            }
+            signal => Some(signal),
+        }
+    }
+}
+
+impl<A, B, Tok, Ast> Inbound<Tok, Ast> for ChainedProducer<A, B>
+where
+    A: Inbound<Tok, Ast>,
+    B: Inbound<Tok, Ast>,
+{
+    fn handle_signal(&mut self, signal: StreamingSignal<Tok, Ast>) {
+        // Tokens are relocated one at a time, so batches are not passed down.
+        let signal = match signal {
+            StreamingSignal::RequestTokens(n) => StreamingSignal::RequestToken(n),
+            signal => signal,
+        };
+        if self.first_done {
+            self.second.handle_signal(signal);
+        } else {
+            self.first.handle_signal(signal);
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use crate::context::LexContext;
+use crate::lexer::Lexer;
+use crate::traits::LexToken;
+use common_framework::{Channel, Position};
+
+/// A token stream split into channels, as produced by
+/// [`Lexer::tokenize_channels`].
+///
+/// Tokens are kept in source order with the channel their rule routed them
+/// to. The parser reads [`default_tokens`](Self::default_tokens); the
+/// others are looked up around a default token by its index in that list,
+/// which is the index a parser reports as its position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelTokens<Tok> {
+    entries: Vec<(Channel, Tok)>,
+    // Index into `entries` of each default-channel token.
+    defaults: Vec<usize>,
+}
+
+impl<Tok> ChannelTokens<Tok> {
+    /// Creates an empty stream.
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            defaults: Vec::new(),
+        }
+    }
+
+    /// Appends `token` on `channel`.
+    pub fn push(&mut self, channel: Channel, token: Tok) {
+        if channel.is_default() {
+            self.defaults.push(self.entries.len());
+        }
+        self.entries.push((channel, token));
+    }
+
+    /// Returns the number of tokens on all channels.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if there are no tokens on any channel.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the number of default-channel tokens.
+    pub fn default_len(&self) -> usize {
+        self.defaults.len()
+    }
+
+    /// Iterates over all tokens in source order.
+    pub fn iter(&self) -> impl Iterator<Item = &(Channel, Tok)> {
+        self.entries.iter()
+    }
+
+    /// Iterates over the tokens on `channel`.
+    pub fn on_channel(&self, channel: Channel) -> impl Iterator<Item = &Tok> {
+        self.entries
+            .iter()
+            .filter(move |(ch, _)| *ch == channel)
+            .map(|(_, token)| token)
+    }
+
+    /// Iterates over the default-channel tokens, i.e. what the parser sees.
+    pub fn default_tokens(&self) -> impl Iterator<Item = &Tok> {
+        self.defaults.iter().map(|&i| &self.entries[i].1)
+    }
+
+    /// Returns the default-channel token at `index`.
+    pub fn get_default(&self, index: usize) -> Option<&Tok> {
+        self.defaults.get(index).map(|&i| &self.entries[i].1)
+    }
+
+    /// Returns the off-channel tokens between the default token at `index`
+    /// and the one before it. An `index` of
+    /// [`default_len`](Self::default_len) returns the tokens after the last
+    /// default token.
+    pub fn hidden_before(&self, index: usize) -> &[(Channel, Tok)] {
+        let end = match self.defaults.get(index) {
+            Some(&end) => end,
+            None if index == self.defaults.len() => self.entries.len(),
+            None => return &[],
+        };
+        let start = index
+            .checked_sub(1)
+            .map_or(0, |prev| self.defaults[prev] + 1);
+        &self.entries[start..end]
+    }
+
+    /// Returns the off-channel tokens between the default token at `index`
+    /// and the one after it.
+    pub fn hidden_after(&self, index: usize) -> &[(Channel, Tok)] {
+        match self.defaults.get(index) {
+            Some(&start) => {
+                let end = self
+                    .defaults
+                    .get(index + 1)
+                    .copied()
+                    .unwrap_or(self.entries.len());
+                &self.entries[start + 1..end]
+            }
+            None => &[],
+        }
+    }
+
+    /// Consumes the stream and returns the default-channel tokens.
+    pub fn into_default_tokens(self) -> Vec<Tok> {
+        self.entries
+            .into_iter()
+            .filter(|(channel, _)| channel.is_default())
+            .map(|(_, token)| token)
+            .collect()
+    }
+}
+
+impl<Tok: LexToken> ChannelTokens<Tok> {
+    /// Returns the index of the first default token at or after
+    /// `position`, or [`default_len`](Self::default_len) if there is none.
+    ///
+    /// Use it with [`hidden_before`](Self::hidden_before) to find, say, the
+    /// comments in front of the node a diagnostic points at. Tokens without
+    /// a position are treated as lying before it.
+    pub fn default_index_at(&self, position: Position) -> usize {
+        self.defaults.partition_point(|&i| {
+            self.entries[i]
+                .1
+                .position()
+                .is_none_or(|p| p.offset < position.offset)
+        })
+    }
+}
+
+impl<Tok> Default for ChannelTokens<Tok> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Ctx, Tok> Lexer<Ctx, Tok>
+where
+    Ctx: LexContext,
+{
+    /// Lexes all remaining tokens and records the channel each one was
+    /// routed to by [`LexingRule::channel`](crate::LexingRule::channel).
+    pub fn tokenize_channels(&mut self) -> ChannelTokens<Tok> {
+        let mut tokens = ChannelTokens::new();
+        while let Some(token) = self.next() {
+            let channel = self.last_rule().map_or(Channel::DEFAULT, |r| r.channel());
+            tokens.push(channel, token);
+        }
+        tokens
+    }
+
+    /// Lexes all remaining tokens and returns only those on
+    /// [`Channel::DEFAULT`], the stream a parser reads.
+    ///
+    /// Same as [`tokenize`](Self::tokenize) when no rule routes its tokens
+    /// elsewhere.
+    pub fn tokenize_default(&mut self) -> Vec<Tok> {
+        if self.rules().iter().all(|rule| rule.channel().is_default()) {
+            return self.tokenize();
+        }
+        self.tokenize_channels().into_default_tokens()
+    }
+}
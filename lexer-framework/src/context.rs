@@ -1,5 +1,7 @@
 use crate::cursor::Cursor;
-use common_framework::{Checkpoint, Position, TextSlice};
+use common_framework::{
+    Checkpoint, CompactPos, Diagnostic, Diagnostics, Feedback, Position, StateMap, TextSlice,
+};
 
 /// Context for lexing operations in CGP (Context-Generic Programming).
 /// This trait allows lexing rules to access contextual information
@@ -39,6 +41,34 @@ pub trait LexContext {
         self.cursor_mut().consume_while(predicate)
     }
 
+    /// Returns the input between `start` (an earlier [`offset`](Self::offset))
+    /// and the current position, without copying.
+    ///
+    /// Contexts that do not use a [`Cursor`] must override this.
+    fn slice_from(&mut self, start: usize) -> TextSlice {
+        self.cursor().slice_from(start)
+    }
+
+    /// Runs `f` and returns the input it consumed as a zero-copy slice, so
+    /// rules can advance character by character without building a
+    /// `String`:
+    ///
+    /// ```ignore
+    /// let ident = ctx.capture(|ctx| {
+    ///     ctx.advance();
+    ///     ctx.consume_while(|ch| ch.is_alphanumeric() || ch == '_');
+    /// });
+    /// ```
+    fn capture<F>(&mut self, f: F) -> TextSlice
+    where
+        Self: Sized,
+        F: FnOnce(&mut Self),
+    {
+        let start = self.offset();
+        f(self);
+        self.slice_from(start)
+    }
+
     /// Creates a checkpoint of the current state.
     fn checkpoint(&self) -> Checkpoint {
         self.cursor().checkpoint()
@@ -49,34 +79,207 @@ pub trait LexContext {
         self.cursor_mut().restore(checkpoint);
     }
 
+    /// Runs `f` speculatively: if it returns `None`, everything it consumed
+    /// is rolled back, so no early-return path can forget to restore.
+    fn speculate<T, F>(&mut self, f: F) -> Option<T>
+    where
+        Self: Sized,
+        F: FnOnce(&mut Self) -> Option<T>,
+    {
+        let checkpoint = self.checkpoint();
+        let result = f(self);
+        if result.is_none() {
+            self.restore(checkpoint);
+        }
+        result
+    }
+
     /// Returns the current byte offset in the input.
     /// This is a convenience method that avoids direct cursor access.
     fn offset(&self) -> usize {
         self.cursor().offset()
     }
 
+    /// Returns the current offset as a [`CompactPos`], for tokens that
+    /// resolve positions through a `LineIndex` (see [`CompactToken`](crate::CompactToken)).
+    fn compact_pos(&self) -> CompactPos {
+        CompactPos::new(self.offset())
+    }
+
     /// Returns the estimated remaining length of the input.
     /// Returns None if unknown (e.g. streaming).
     fn remaining_len(&self) -> Option<usize> {
         None
     }
+
+    /// Pulls more input from the underlying source, if there is one.
+    /// Returns true if the context changed (new characters arrived or the
+    /// source reached its end). Contexts over a fixed input return false.
+    fn refill(&mut self) -> bool {
+        false
+    }
+
+    /// Returns false while more input may still be pushed or read, so that
+    /// running out of buffered input is not the end of the input. Contexts
+    /// over a fixed input always return true.
+    fn is_input_finished(&self) -> bool {
+        true
+    }
+
+    /// Tells the lexer that the current rule ran into the end of the
+    /// buffered input and cannot decide without more of it, e.g. on an
+    /// unterminated string literal in a chunked stream. Returns `None`, so
+    /// rules can `return ctx.pending();`.
+    ///
+    /// Unless the [input is finished](Self::is_input_finished), the lexer
+    /// then discards whatever the other rules match at that position,
+    /// rolls back to the start of the token and tries again once more input
+    /// arrives. Otherwise this is the same as returning `None`. The flag is
+    /// kept in the state map, so contexts without one ignore it.
+    fn pending<T>(&mut self) -> Option<T>
+    where
+        Self: Sized,
+    {
+        if !self.is_input_finished() {
+            if let Some(state) = self.state_map() {
+                state.insert(PendingMatch);
+            }
+        }
+        None
+    }
+
+    /// Appends `text` to the input of a context that is fed incrementally,
+    /// e.g. in answer to [`StreamingSignal::NeedInput`]. Contexts over a
+    /// fixed input, or that read their own source, ignore it.
+    ///
+    /// [`StreamingSignal::NeedInput`]: common_framework::StreamingSignal::NeedInput
+    fn push_input(&mut self, text: &str) {
+        let _ = text;
+    }
+
+    /// Marks the input as complete, so [`is_eof`](Self::is_eof) holds once
+    /// the buffered input is consumed. Ignored like
+    /// [`push_input`](Self::push_input).
+    fn finish_input(&mut self) {}
+
+    /// Returns the context's user state map, if it has one.
+    ///
+    /// All built-in contexts do; custom contexts can override this to
+    /// support [`state`](Self::state).
+    fn state_map(&mut self) -> Option<&mut StateMap> {
+        None
+    }
+
+    /// Returns the rule-shared state of type `T`, creating it with
+    /// `T::default()` on first use.
+    ///
+    /// State is not rolled back by [`restore`](Self::restore), so rules
+    /// should update it only after a match is certain.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`state_map`](Self::state_map) returns `None`.
+    fn state<T: Default + Send + 'static>(&mut self) -> &mut T
+    where
+        Self: Sized,
+    {
+        self.state_map()
+            .expect("this context has no state map; override `state_map`")
+            .get_or_default()
+    }
+
+    /// Records feedback sent back by the parser (see
+    /// [`StreamingSignal::Feedback`](common_framework::StreamingSignal::Feedback)).
+    ///
+    /// The default keeps it in the state map for [`feedback`](Self::feedback)
+    /// and drops it if the context has none.
+    fn receive_feedback(&mut self, feedback: Feedback) {
+        if let Some(state) = self.state_map() {
+            state.get_or_default::<FeedbackLog>().0.push(feedback);
+        }
+    }
+
+    /// Returns the feedback payloads of type `T` received so far, oldest
+    /// first.
+    ///
+    /// The parser only sends feedback once it has consumed the tokens that
+    /// triggered it, so rules see it from the next token they lex.
+    fn feedback<T: 'static>(&mut self) -> impl Iterator<Item = &T>
+    where
+        Self: Sized,
+    {
+        self.state_map()
+            .and_then(|state| state.get::<FeedbackLog>())
+            .into_iter()
+            .flat_map(|log| log.0.iter())
+            .filter_map(Feedback::downcast_ref)
+    }
+
+    /// Called by the lexer just before it tries the rule named `rule` at the
+    /// current position. The default does nothing; decorators such as
+    /// [`ValidatingContext`](crate::ValidatingContext) use it to attribute
+    /// what happens next to the rule.
+    fn enter_rule(&mut self, rule: &str) {
+        let _ = rule;
+    }
+
+    /// Reports a warning or error about the input, e.g. a suspicious but
+    /// valid construct, to be returned alongside the tokens.
+    ///
+    /// The default passes it to the context's [`Diagnostics`], kept in its
+    /// state map (see [`set_diagnostics`](Self::set_diagnostics)), and
+    /// drops it if the context has none. Like [`state`](Self::state) it is
+    /// not rolled back by [`restore`](Self::restore), so report only once a
+    /// match is certain.
+    fn report(&mut self, diagnostic: Diagnostic) {
+        if let Some(state) = self.state_map() {
+            state.get_or_default::<Diagnostics>().report(diagnostic);
+        }
+    }
+
+    /// Routes [`report`](Self::report) through `diagnostics` from now on,
+    /// e.g. to print diagnostics as they arrive, filter them by severity,
+    /// or cap them. Without this, they are collected for
+    /// [`take_diagnostics`](Self::take_diagnostics).
+    fn set_diagnostics(&mut self, diagnostics: Diagnostics) {
+        if let Some(state) = self.state_map() {
+            state.insert(diagnostics);
+        }
+    }
+
+    /// Removes and returns the diagnostics collected so far, oldest first.
+    /// Empty if the sink does not keep them.
+    fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        self.state_map()
+            .and_then(|state| state.get::<Diagnostics>().map(Diagnostics::take))
+            .unwrap_or_default()
+    }
 }
 
+/// Feedback received by a context, kept in its state map.
+#[derive(Default)]
+struct FeedbackLog(Vec<Feedback>);
+
+/// Set in the state map by [`LexContext::pending`] until the lexer sees it.
+pub(crate) struct PendingMatch;
+
 /// A simple default context implementation.
 #[derive(Debug)]
 pub struct DefaultContext {
     cursor: Cursor,
+    state: StateMap,
 }
 
 impl DefaultContext {
     pub fn new<S: Into<String>>(input: S) -> Self {
-        Self {
-            cursor: Cursor::new(input),
-        }
+        Self::from_cursor(Cursor::new(input))
     }
 
     pub fn from_cursor(cursor: Cursor) -> Self {
-        Self { cursor }
+        Self {
+            cursor,
+            state: StateMap::new(),
+        }
     }
 }
 
@@ -97,4 +300,8 @@ impl LexContext for DefaultContext {
             Some(self.cursor.remaining().len())
         }
     }
+
+    fn state_map(&mut self) -> Option<&mut StateMap> {
+        Some(&mut self.state)
+    }
 }
@@ -1,4 +1,4 @@
-use common_framework::{Checkpoint, Position, TextSlice};
+use common_framework::{Checkpoint, ContextId, Position, TextSlice};
 use std::sync::Arc;
 
 /// A cursor for traversing input text during lexing.
@@ -9,6 +9,9 @@ pub struct Cursor {
     buffer: Arc<str>,
     current: usize,
     position: Position,
+    // False when positions are resolved later from a `SourceText`.
+    track_lines: bool,
+    id: ContextId,
 }
 
 impl Cursor {
@@ -25,9 +28,30 @@ impl Cursor {
             current: 0,
             position: Position::new(),
             buffer,
+            track_lines: true,
+            id: ContextId::fresh(),
         }
     }
 
+    /// Creates a cursor that only tracks the byte offset.
+    ///
+    /// Skipping the per-character line and column bookkeeping makes
+    /// lexing cheaper when positions are resolved afterwards through a
+    /// [`SourceText`](common_framework::SourceText) or `LineIndex`.
+    /// [`position`](Self::position) then reports line and column 0.
+    pub fn untracked(buffer: Arc<str>) -> Self {
+        Self {
+            position: Position::at(0, 0, 0),
+            track_lines: false,
+            ..Self::with_arc(buffer)
+        }
+    }
+
+    /// Returns false for cursors created with [`untracked`](Self::untracked).
+    pub fn tracks_lines(&self) -> bool {
+        self.track_lines
+    }
+
     /// Returns the current position in the source.
     pub fn position(&self) -> Position {
         self.position
@@ -84,22 +108,21 @@ impl Cursor {
         let b = self.buffer.as_bytes()[self.current];
         if b < 128 {
             let ch = b as char;
-            if ch == '\n' {
-                self.position.line += 1;
-                self.position.column = 1;
-            } else {
-                self.position.column += 1;
-            }
-            self.position.offset += 1;
-            self.current += 1;
+            self.step(ch, 1);
             return Some(ch);
         }
 
         let ch = self.peek()?;
-        let len = ch.len_utf8();
+        self.step(ch, ch.len_utf8());
+        Some(ch)
+    }
 
-        // Update position
-        if ch == '\n' {
+    /// Moves past `ch`, which is `len` bytes long, updating the position.
+    #[inline]
+    fn step(&mut self, ch: char, len: usize) {
+        if !self.track_lines {
+            // Line and column stay 0.
+        } else if ch == '\n' {
             self.position.line += 1;
             self.position.column = 1;
         } else {
@@ -107,8 +130,6 @@ impl Cursor {
         }
         self.position.offset += len;
         self.current += len;
-
-        Some(ch)
     }
 
     /// Advances the cursor by n characters.
@@ -138,6 +159,13 @@ impl Cursor {
         TextSlice::new(self.buffer.clone(), start, self.current)
     }
 
+    /// Returns the input from byte offset `start` up to the current
+    /// position.
+    pub fn slice_from(&self, start: usize) -> TextSlice {
+        debug_assert!(start <= self.current, "slice start is ahead of the cursor");
+        TextSlice::new(self.buffer.clone(), start, self.current)
+    }
+
     /// Returns the remaining input from the current position.
     pub fn remaining(&self) -> TextSlice {
         TextSlice::new(self.buffer.clone(), self.current, self.buffer.len())
@@ -146,16 +174,21 @@ impl Cursor {
     /// Resets the cursor to the beginning.
     pub fn reset(&mut self) {
         self.current = 0;
-        self.position = Position::new();
+        self.position = if self.track_lines {
+            Position::new()
+        } else {
+            Position::at(0, 0, 0)
+        };
     }
 
     /// Creates a checkpoint that can be restored later.
     pub fn checkpoint(&self) -> Checkpoint {
-        Checkpoint::new(self.current, self.position)
+        Checkpoint::new(self.current, self.position).with_context(self.id)
     }
 
     /// Restores the cursor to a previous checkpoint.
     pub fn restore(&mut self, checkpoint: Checkpoint) {
+        checkpoint.debug_assert_context(self.id);
         self.current = checkpoint.current();
         self.position = checkpoint.position();
     }
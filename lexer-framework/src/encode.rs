@@ -0,0 +1,325 @@
+use crate::traits::LexToken;
+use common_framework::{LineIndex, Position, TextSlice};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Tokens that can be stored as `(kind, start, len)` against their source
+/// text, see [`EncodedTokens`].
+///
+/// The start is taken from [`LexToken::position`], so encoded tokens must
+/// report one.
+pub trait EncodeToken: LexToken {
+    /// Identifies the token's kind. Ids are written to the cache, so they
+    /// must stay the same across builds.
+    fn kind_id(&self) -> u32;
+
+    /// Returns the length in bytes of the token's text in the source.
+    fn text_len(&self) -> usize;
+}
+
+/// One encoded token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenRecord {
+    pub kind: u32,
+    pub start: usize,
+    pub len: usize,
+}
+
+/// Why a token stream could not be encoded or decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenCodecError {
+    /// The token at `index` has no position.
+    MissingPosition { index: usize },
+    /// The span of the token at `index` is outside the source or not on
+    /// character boundaries.
+    InvalidSpan {
+        index: usize,
+        start: usize,
+        len: usize,
+    },
+    /// No factory is registered for the kind of the token at `index`.
+    UnknownKind { index: usize, kind: u32 },
+    /// The tokens were encoded against a source of a different length.
+    SourceMismatch {
+        expected_len: usize,
+        actual_len: usize,
+    },
+    /// The bytes are not an encoded token stream.
+    Malformed(&'static str),
+}
+
+impl std::fmt::Display for TokenCodecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenCodecError::MissingPosition { index } => {
+                write!(f, "token {} has no position", index)
+            }
+            TokenCodecError::InvalidSpan { index, start, len } => write!(
+                f,
+                "token {} spans {}..{}, which is not a valid range of the source",
+                index,
+                start,
+                start + len
+            ),
+            TokenCodecError::UnknownKind { index, kind } => {
+                write!(f, "token {} has unregistered kind {}", index, kind)
+            }
+            TokenCodecError::SourceMismatch {
+                expected_len,
+                actual_len,
+            } => write!(
+                f,
+                "tokens were encoded against {} bytes of source, got {}",
+                expected_len, actual_len
+            ),
+            TokenCodecError::Malformed(reason) => {
+                write!(f, "malformed token stream: {}", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TokenCodecError {}
+
+const MAGIC: &[u8; 4] = b"TOKS";
+const VERSION: u8 = 1;
+
+/// A token stream stored as `(kind, start, len)` records against its source
+/// text, for build caches.
+///
+/// Token text and positions are not stored; [`TokenDecoder`] rebuilds them
+/// from the source. [`to_bytes`](Self::to_bytes) writes the records as
+/// variable-length integers with each start relative to the previous one, so
+/// a typical token takes three to four bytes.
+///
+/// The encoding records the source length and the decoder rejects a source
+/// of another length, but it does not detect other edits: key the cache by
+/// the source's content hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedTokens {
+    source_len: usize,
+    records: Vec<TokenRecord>,
+}
+
+impl EncodedTokens {
+    /// Encodes `tokens` lexed from `source`.
+    pub fn encode<Tok: EncodeToken>(tokens: &[Tok], source: &str) -> Result<Self, TokenCodecError> {
+        let records = tokens
+            .iter()
+            .enumerate()
+            .map(|(index, token)| {
+                let start = token
+                    .position()
+                    .ok_or(TokenCodecError::MissingPosition { index })?
+                    .offset;
+                let record = TokenRecord {
+                    kind: token.kind_id(),
+                    start,
+                    len: token.text_len(),
+                };
+                check_span(source, index, &record)?;
+                Ok(record)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            source_len: source.len(),
+            records,
+        })
+    }
+
+    /// Returns the encoded tokens.
+    pub fn records(&self) -> &[TokenRecord] {
+        &self.records
+    }
+
+    /// Returns the number of tokens.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns `true` if there are no tokens.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Returns the length of the source the tokens were encoded against.
+    pub fn source_len(&self) -> usize {
+        self.source_len
+    }
+
+    /// Serializes the records.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(16 + self.records.len() * 4);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        write_varint(&mut bytes, self.source_len as u64);
+        write_varint(&mut bytes, self.records.len() as u64);
+        let mut prev_start = 0i64;
+        for record in &self.records {
+            let delta = record.start as i64 - prev_start;
+            prev_start = record.start as i64;
+            write_varint(&mut bytes, record.kind as u64);
+            // Zigzag, so tokens out of source order still encode.
+            write_varint(&mut bytes, ((delta << 1) ^ (delta >> 63)) as u64);
+            write_varint(&mut bytes, record.len as u64);
+        }
+        bytes
+    }
+
+    /// Deserializes records written by [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, TokenCodecError> {
+        let rest = bytes
+            .strip_prefix(MAGIC)
+            .ok_or(TokenCodecError::Malformed("missing header"))?;
+        let (&version, mut rest) = rest
+            .split_first()
+            .ok_or(TokenCodecError::Malformed("missing version"))?;
+        if version != VERSION {
+            return Err(TokenCodecError::Malformed("unsupported version"));
+        }
+        let source_len = read_varint(&mut rest)? as usize;
+        let count = read_varint(&mut rest)? as usize;
+        // Every record takes at least three bytes.
+        if count > rest.len() / 3 {
+            return Err(TokenCodecError::Malformed("truncated records"));
+        }
+        let mut records = Vec::with_capacity(count);
+        let mut prev_start = 0i64;
+        for _ in 0..count {
+            let kind = u32::try_from(read_varint(&mut rest)?)
+                .map_err(|_| TokenCodecError::Malformed("kind out of range"))?;
+            let zigzag = read_varint(&mut rest)?;
+            let delta = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+            let start = prev_start
+                .checked_add(delta)
+                .filter(|start| *start >= 0)
+                .ok_or(TokenCodecError::Malformed("start out of range"))?;
+            prev_start = start;
+            let len = read_varint(&mut rest)? as usize;
+            records.push(TokenRecord {
+                kind,
+                start: start as usize,
+                len,
+            });
+        }
+        if !rest.is_empty() {
+            return Err(TokenCodecError::Malformed("trailing bytes"));
+        }
+        Ok(Self {
+            source_len,
+            records,
+        })
+    }
+}
+
+type Factory<Tok> = Box<dyn Fn(TextSlice, Position) -> Tok>;
+
+/// Rebuilds tokens from [`EncodedTokens`] with one factory per token kind.
+///
+/// ```ignore
+/// let decoder = TokenDecoder::new()
+///     .register(IDENT, |text, pos| Token::Ident(text, pos))
+///     .register(NUMBER, |text, pos| Token::Number(text.parse().unwrap(), pos));
+/// let tokens = decoder.decode(source, &EncodedTokens::from_bytes(&cached)?)?;
+/// ```
+pub struct TokenDecoder<Tok> {
+    factories: HashMap<u32, Factory<Tok>>,
+}
+
+impl<Tok> TokenDecoder<Tok> {
+    /// Creates a decoder without factories.
+    pub fn new() -> Self {
+        Self {
+            factories: HashMap::new(),
+        }
+    }
+
+    /// Registers the factory for tokens of `kind`. It receives the token's
+    /// text as a slice of the source and its position.
+    pub fn register(
+        mut self,
+        kind: u32,
+        factory: impl Fn(TextSlice, Position) -> Tok + 'static,
+    ) -> Self {
+        self.factories.insert(kind, Box::new(factory));
+        self
+    }
+
+    /// Rebuilds the tokens encoded against `source`.
+    pub fn decode(
+        &self,
+        source: impl Into<Arc<str>>,
+        encoded: &EncodedTokens,
+    ) -> Result<Vec<Tok>, TokenCodecError> {
+        let source = source.into();
+        if source.len() != encoded.source_len {
+            return Err(TokenCodecError::SourceMismatch {
+                expected_len: encoded.source_len,
+                actual_len: source.len(),
+            });
+        }
+        let index = LineIndex::new(source.clone());
+        encoded
+            .records
+            .iter()
+            .enumerate()
+            .map(|(i, record)| {
+                let factory =
+                    self.factories
+                        .get(&record.kind)
+                        .ok_or(TokenCodecError::UnknownKind {
+                            index: i,
+                            kind: record.kind,
+                        })?;
+                check_span(&source, i, record)?;
+                let text = TextSlice::new(source.clone(), record.start, record.start + record.len);
+                Ok(factory(text, index.position_at(record.start)))
+            })
+            .collect()
+    }
+}
+
+impl<Tok> Default for TokenDecoder<Tok> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn check_span(source: &str, index: usize, record: &TokenRecord) -> Result<(), TokenCodecError> {
+    let valid = record
+        .start
+        .checked_add(record.len)
+        .is_some_and(|end| source.get(record.start..end).is_some());
+    if valid {
+        Ok(())
+    } else {
+        Err(TokenCodecError::InvalidSpan {
+            index,
+            start: record.start,
+            len: record.len,
+        })
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Result<u64, TokenCodecError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes
+            .split_first()
+            .ok_or(TokenCodecError::Malformed("truncated records"))?;
+        *bytes = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(TokenCodecError::Malformed("varint too long"))
+}
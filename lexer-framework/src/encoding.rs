@@ -0,0 +1,235 @@
+use crate::context::DefaultContext;
+use crate::cursor::Cursor;
+use common_framework::Position;
+use std::sync::Arc;
+
+pub use encoding_rs::Encoding;
+
+/// Input bytes transcoded to UTF-8 (`encoding` feature).
+///
+/// The lexer only works on UTF-8, so inputs in UTF-16 or Latin-1 are
+/// decoded up front. The decoded text shares its buffer with the cursors
+/// built from it, and an [`OffsetMap`] translates offsets in the decoded
+/// text back to offsets in the original bytes for diagnostics:
+///
+/// ```ignore
+/// let input = DecodedInput::detect(&bytes);
+/// let mut lexer = Lexer::new(input.context(), rules);
+/// let token = lexer.next().unwrap();
+/// let original = input.original_position(token.position().unwrap());
+/// ```
+#[derive(Debug, Clone)]
+pub struct DecodedInput {
+    text: Arc<str>,
+    encoding: &'static Encoding,
+    offsets: OffsetMap,
+}
+
+impl DecodedInput {
+    /// Decodes `bytes`, guessing the encoding.
+    ///
+    /// A byte order mark wins; otherwise input with zero bytes in every
+    /// other position is taken as BOM-less UTF-16 (such text is usually
+    /// also valid UTF-8), valid UTF-8 as UTF-8, and anything else as
+    /// Latin-1 (`windows-1252`, its common superset).
+    pub fn detect(bytes: &[u8]) -> Self {
+        if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+            return Self::decode(&bytes[bom_len..], encoding, bom_len);
+        }
+        let encoding = guess_utf16(bytes).unwrap_or_else(|| {
+            if std::str::from_utf8(bytes).is_ok() {
+                encoding_rs::UTF_8
+            } else {
+                encoding_rs::WINDOWS_1252
+            }
+        });
+        Self::decode(bytes, encoding, 0)
+    }
+
+    /// Decodes `bytes` as `encoding`, skipping a byte order mark if one for
+    /// that encoding is present. Malformed sequences become U+FFFD.
+    pub fn with_encoding(bytes: &[u8], encoding: &'static Encoding) -> Self {
+        match Encoding::for_bom(bytes) {
+            Some((bom, bom_len)) if bom == encoding => {
+                Self::decode(&bytes[bom_len..], encoding, bom_len)
+            }
+            _ => Self::decode(bytes, encoding, 0),
+        }
+    }
+
+    fn decode(bytes: &[u8], encoding: &'static Encoding, base: usize) -> Self {
+        let mut offsets = OffsetMap::new(base);
+        let text = if encoding == encoding_rs::UTF_8 {
+            let mut text = String::with_capacity(bytes.len());
+            for chunk in bytes.utf8_chunks() {
+                text.push_str(chunk.valid());
+                offsets.push_run(chunk.valid().len(), chunk.valid().len());
+                if !chunk.invalid().is_empty() {
+                    text.push(char::REPLACEMENT_CHARACTER);
+                    offsets.push_run(
+                        char::REPLACEMENT_CHARACTER.len_utf8(),
+                        chunk.invalid().len(),
+                    );
+                }
+            }
+            text
+        } else {
+            // Decode a byte at a time to learn how many bytes each character
+            // came from; encodings such as Shift_JIS mix widths.
+            let mut decoder = encoding.new_decoder_without_bom_handling();
+            let mut text = String::with_capacity(bytes.len());
+            let mut pending = 0usize;
+            for (i, byte) in bytes.iter().enumerate() {
+                let start = text.len();
+                text.reserve(decoder.max_utf8_buffer_length(1).unwrap_or(16));
+                let (result, _, _) = decoder.decode_to_string(
+                    std::slice::from_ref(byte),
+                    &mut text,
+                    i + 1 == bytes.len(),
+                );
+                debug_assert_eq!(result, encoding_rs::CoderResult::InputEmpty);
+                pending += 1;
+                let decoded = &text[start..];
+                let count = decoded.chars().count();
+                if count == 0 {
+                    continue;
+                }
+                // A malformed sequence can come out together with the
+                // character after it; that one takes a single byte.
+                let first = pending.saturating_sub(count - 1).max(1);
+                for (j, ch) in decoded.chars().enumerate() {
+                    offsets.push_run(ch.len_utf8(), if j == 0 { first } else { 1 });
+                }
+                pending = 0;
+            }
+            text
+        };
+        Self {
+            text: Arc::from(text),
+            encoding,
+            offsets,
+        }
+    }
+
+    /// Returns the encoding the input was decoded from.
+    pub fn encoding(&self) -> &'static Encoding {
+        self.encoding
+    }
+
+    /// Returns the decoded text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the offset mapping back to the original bytes.
+    pub fn offsets(&self) -> &OffsetMap {
+        &self.offsets
+    }
+
+    /// Returns a cursor over the decoded text, sharing its buffer.
+    pub fn cursor(&self) -> Cursor {
+        Cursor::with_arc(self.text.clone())
+    }
+
+    /// Returns a lex context over the decoded text.
+    pub fn context(&self) -> DefaultContext {
+        DefaultContext::from_cursor(self.cursor())
+    }
+
+    /// Translates a byte offset in the decoded text to one in the original
+    /// bytes.
+    pub fn original_offset(&self, offset: usize) -> usize {
+        self.offsets.original(offset)
+    }
+
+    /// Returns `position` with its offset translated to the original bytes.
+    /// Line and column count characters, so they are unchanged.
+    pub fn original_position(&self, position: Position) -> Position {
+        Position {
+            offset: self.original_offset(position.offset),
+            ..position
+        }
+    }
+}
+
+/// Guesses BOM-less UTF-16 from where the zero bytes are: ASCII-heavy
+/// UTF-16LE text has them at odd offsets, UTF-16BE at even ones.
+fn guess_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    if bytes.len() < 2 || !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let pairs = bytes.len() / 2;
+    let even = bytes.iter().step_by(2).filter(|&&b| b == 0).count();
+    let odd = bytes.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+    if odd * 2 >= pairs && even == 0 {
+        Some(encoding_rs::UTF_16LE)
+    } else if even * 2 >= pairs && odd == 0 {
+        Some(encoding_rs::UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Maps byte offsets in transcoded UTF-8 text to offsets in the original
+/// input.
+///
+/// Only the points where the two drift apart are stored, so UTF-8 input
+/// (with or without a BOM) costs a single entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OffsetMap {
+    // (decoded offset, original offset) pairs, sorted. Between two anchors
+    // both offsets advance together, or the span is one character whose
+    // encoded widths differ.
+    anchors: Vec<(usize, usize)>,
+    decoded_len: usize,
+    original_len: usize,
+}
+
+impl OffsetMap {
+    fn new(base: usize) -> Self {
+        Self {
+            anchors: vec![(0, base)],
+            decoded_len: 0,
+            original_len: base,
+        }
+    }
+
+    /// Appends `decoded` bytes of output that came from `original` bytes of
+    /// input.
+    fn push_run(&mut self, decoded: usize, original: usize) {
+        let start = (self.decoded_len, self.original_len);
+        self.decoded_len += decoded;
+        self.original_len += original;
+        if decoded != original {
+            if self.anchors.last() != Some(&start) {
+                self.anchors.push(start);
+            }
+            self.anchors.push((self.decoded_len, self.original_len));
+        }
+    }
+
+    /// Translates a decoded offset; offsets inside a transcoded character
+    /// map to the start of that character in the original input.
+    pub fn original(&self, offset: usize) -> usize {
+        let index = self
+            .anchors
+            .partition_point(|&(decoded, _)| decoded <= offset)
+            - 1;
+        let (decoded, original) = self.anchors[index];
+        match self.anchors.get(index + 1) {
+            // A segment whose lengths differ holds a single transcoded
+            // character.
+            Some(&(next_decoded, next_original))
+                if next_decoded - decoded != next_original - original =>
+            {
+                original
+            }
+            _ => (original + (offset - decoded)).min(self.original_len),
+        }
+    }
+
+    /// Returns the number of stored anchor points.
+    pub fn anchor_count(&self) -> usize {
+        self.anchors.len()
+    }
+}
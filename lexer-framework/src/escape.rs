@@ -0,0 +1,122 @@
+use crate::context::LexContext;
+use common_framework::Position;
+
+/// Reasons an escape sequence inside a string literal can be rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EscapeErrorKind {
+    /// The character after `\` is not a recognised escape.
+    Unknown(char),
+    /// `\u` was not followed by four hexadecimal digits.
+    InvalidUnicode,
+    /// A UTF-16 surrogate that is not part of a valid high/low pair.
+    LoneSurrogate(u32),
+    /// Input ended in the middle of the escape sequence.
+    UnexpectedEof,
+}
+
+/// An invalid escape sequence together with the position of its backslash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EscapeError {
+    pub kind: EscapeErrorKind,
+    pub position: Position,
+}
+
+impl std::fmt::Display for EscapeErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EscapeErrorKind::Unknown(ch) => write!(f, "unknown escape sequence '\\{}'", ch),
+            EscapeErrorKind::InvalidUnicode => write!(f, "expected 4 hex digits after '\\u'"),
+            EscapeErrorKind::LoneSurrogate(unit) => {
+                write!(f, "unpaired UTF-16 surrogate '\\u{:04X}'", unit)
+            }
+            EscapeErrorKind::UnexpectedEof => write!(f, "unterminated escape sequence"),
+        }
+    }
+}
+
+impl std::fmt::Display for EscapeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at {}:{}",
+            self.kind, self.position.line, self.position.column
+        )
+    }
+}
+
+impl std::error::Error for EscapeError {}
+
+/// Decodes a single JSON-style escape sequence.
+///
+/// The context must be positioned on the backslash. On success the whole
+/// sequence is consumed and the decoded character returned. `\uXXXX` escapes
+/// are decoded as UTF-16 code units, so a high surrogate must be immediately
+/// followed by a `\uXXXX` low surrogate to form one `char`.
+///
+/// On failure the cursor is left where decoding stopped, so callers can
+/// continue scanning to the closing quote and report the error: after an
+/// unknown escape character, after a high surrogate missing its low half,
+/// and *on* the first character of a `\u` escape that is not a hex digit,
+/// which is not consumed in case it is the closing quote.
+pub fn decode_escape<Ctx>(ctx: &mut Ctx) -> Result<char, EscapeError>
+where
+    Ctx: LexContext,
+{
+    let position = ctx.position();
+    let error = |kind| EscapeError { kind, position };
+
+    if ctx.advance() != Some('\\') {
+        return Err(error(EscapeErrorKind::UnexpectedEof));
+    }
+
+    let ch = ctx
+        .advance()
+        .ok_or_else(|| error(EscapeErrorKind::UnexpectedEof))?;
+    match ch {
+        'n' => Ok('\n'),
+        't' => Ok('\t'),
+        'r' => Ok('\r'),
+        'b' => Ok('\u{0008}'),
+        'f' => Ok('\u{000C}'),
+        '\\' | '"' | '/' => Ok(ch),
+        'u' => {
+            let high = read_hex4(ctx).map_err(error)?;
+            match high {
+                0xD800..=0xDBFF => {
+                    // A high surrogate must be followed by `\uDC00`..`\uDFFF`.
+                    let checkpoint = ctx.checkpoint();
+                    if ctx.advance() != Some('\\') || ctx.advance() != Some('u') {
+                        ctx.restore(checkpoint);
+                        return Err(error(EscapeErrorKind::LoneSurrogate(high)));
+                    }
+                    let low = read_hex4(ctx).map_err(error)?;
+                    if !(0xDC00..=0xDFFF).contains(&low) {
+                        return Err(error(EscapeErrorKind::LoneSurrogate(high)));
+                    }
+                    let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                    char::from_u32(code).ok_or_else(|| error(EscapeErrorKind::InvalidUnicode))
+                }
+                0xDC00..=0xDFFF => Err(error(EscapeErrorKind::LoneSurrogate(high))),
+                _ => char::from_u32(high).ok_or_else(|| error(EscapeErrorKind::InvalidUnicode)),
+            }
+        }
+        other => Err(error(EscapeErrorKind::Unknown(other))),
+    }
+}
+
+/// Reads exactly four hexadecimal digits and returns their value.
+fn read_hex4<Ctx>(ctx: &mut Ctx) -> Result<u32, EscapeErrorKind>
+where
+    Ctx: LexContext,
+{
+    let mut value = 0;
+    for _ in 0..4 {
+        let digit = match ctx.peek() {
+            None => return Err(EscapeErrorKind::UnexpectedEof),
+            Some(ch) => ch.to_digit(16).ok_or(EscapeErrorKind::InvalidUnicode)?,
+        };
+        ctx.advance();
+        value = value * 16 + digit;
+    }
+    Ok(value)
+}
@@ -0,0 +1,216 @@
+use crate::context::LexContext;
+use crate::traits::{LexToken, LexingRule, TokenKind};
+use common_framework::{CompactText, Position, TextSlice};
+use std::fmt;
+
+/// A token whose kind is a string, for standing up a grammar before it has
+/// a token enum.
+///
+/// The kinds `"eof"`, `"newline"`, `"whitespace"` and `"indent"` map to the
+/// matching [`TokenKind`], so filters and parsers that skip trivia work
+/// unchanged. Once the grammar settles, a `map_tokens` pass can turn these
+/// into typed tokens one kind at a time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenericToken {
+    pub kind: CompactText,
+    pub text: TextSlice,
+    /// Where the token starts and where it ends.
+    pub span: (Position, Position),
+}
+
+impl GenericToken {
+    /// Creates a token of `kind` with the text `text` spanning `span`.
+    pub fn new(kind: impl Into<CompactText>, text: TextSlice, span: (Position, Position)) -> Self {
+        Self {
+            kind: kind.into(),
+            text,
+            span,
+        }
+    }
+
+    /// Returns true if the token is of `kind`.
+    pub fn is(&self, kind: &str) -> bool {
+        self.kind == kind
+    }
+}
+
+impl fmt::Display for GenericToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({:?})", self.kind, self.text.as_ref())
+    }
+}
+
+impl LexToken for GenericToken {
+    fn position(&self) -> Option<Position> {
+        Some(self.span.0)
+    }
+
+    fn kind(&self) -> Option<TokenKind> {
+        match self.kind.as_str() {
+            "eof" => Some(TokenKind::Eof),
+            "newline" => Some(TokenKind::Newline),
+            "whitespace" => Some(TokenKind::Whitespace),
+            "indent" => Some(TokenKind::Indent),
+            _ => None,
+        }
+    }
+
+    fn relocated(mut self, base: Position) -> Self {
+        self.span = (self.span.0.rebase(base), self.span.1.rebase(base));
+        self
+    }
+}
+
+enum Matcher {
+    Literal(String),
+    Chars {
+        first: Box<dyn Fn(char) -> bool>,
+        rest: Box<dyn Fn(char) -> bool>,
+    },
+}
+
+/// A rule producing [`GenericToken`]s of one kind, from a literal or from
+/// character classes.
+///
+/// ```
+/// use lexer_framework::{GenericRule, GenericToken, Lexer, LexingRule, DefaultContext};
+///
+/// let rules: Vec<Box<dyn LexingRule<DefaultContext, GenericToken>>> = vec![
+///     Box::new(GenericRule::literal("let", "let").with_priority(1)),
+///     Box::new(GenericRule::identifier()),
+///     Box::new(GenericRule::number()),
+///     Box::new(GenericRule::literal("=", "=")),
+///     Box::new(GenericRule::whitespace()),
+/// ];
+/// let kinds: Vec<_> = Lexer::from_str("let x = 42", rules)
+///     .filter(|token| !token.is("whitespace"))
+///     .map(|token| token.kind.to_string())
+///     .collect();
+/// assert_eq!(kinds, ["let", "ident", "=", "number"]);
+/// ```
+///
+/// Rules are tried in the lexer's usual order, so give keywords a higher
+/// priority than the identifier rule and longer operators a higher one than
+/// their prefixes. A literal keyword also matches the start of a longer
+/// identifier.
+pub struct GenericRule {
+    kind: CompactText,
+    matcher: Matcher,
+    priority: i32,
+}
+
+impl GenericRule {
+    /// Matches exactly `text`.
+    pub fn literal(kind: impl Into<CompactText>, text: impl Into<String>) -> Self {
+        Self {
+            kind: kind.into(),
+            matcher: Matcher::Literal(text.into()),
+            priority: 0,
+        }
+    }
+
+    /// Matches a character accepted by `first` followed by any number of
+    /// characters accepted by `rest`.
+    pub fn chars(
+        kind: impl Into<CompactText>,
+        first: impl Fn(char) -> bool + 'static,
+        rest: impl Fn(char) -> bool + 'static,
+    ) -> Self {
+        Self {
+            kind: kind.into(),
+            matcher: Matcher::Chars {
+                first: Box::new(first),
+                rest: Box::new(rest),
+            },
+            priority: 0,
+        }
+    }
+
+    /// Matches one or more characters accepted by `predicate`.
+    pub fn matching(
+        kind: impl Into<CompactText>,
+        predicate: impl Fn(char) -> bool + Clone + 'static,
+    ) -> Self {
+        Self::chars(kind, predicate.clone(), predicate)
+    }
+
+    /// `ident`: a letter or `_`, then letters, digits and `_`.
+    pub fn identifier() -> Self {
+        Self::chars(
+            "ident",
+            |c| c.is_alphabetic() || c == '_',
+            |c| c.is_alphanumeric() || c == '_',
+        )
+    }
+
+    /// `number`: ASCII digits.
+    pub fn number() -> Self {
+        Self::matching("number", |c| c.is_ascii_digit())
+    }
+
+    /// `whitespace`: whitespace other than line breaks.
+    pub fn whitespace() -> Self {
+        Self::matching("whitespace", |c| c.is_whitespace() && c != '\n')
+    }
+
+    /// `newline`: a `\n`.
+    pub fn newline() -> Self {
+        Self::literal("newline", "\n")
+    }
+
+    /// Sets the rule's priority; 0 by default.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Returns the kind of the tokens this rule produces.
+    pub fn kind(&self) -> &str {
+        self.kind.as_str()
+    }
+}
+
+impl<Ctx: LexContext> LexingRule<Ctx, GenericToken> for GenericRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<GenericToken> {
+        let start = ctx.position();
+        let offset = ctx.offset();
+        match &self.matcher {
+            Matcher::Literal(text) => {
+                if text.is_empty() {
+                    return None;
+                }
+                let matched = |ctx: &mut Ctx| text.chars().all(|c| ctx.advance() == Some(c));
+                ctx.speculate(|ctx| matched(ctx).then_some(()))?;
+            }
+            Matcher::Chars { first, rest } => {
+                if !first(ctx.peek()?) {
+                    return None;
+                }
+                ctx.advance();
+                ctx.consume_while(rest);
+            }
+        }
+        let text = ctx.slice_from(offset);
+        Some(GenericToken::new(
+            self.kind.clone(),
+            text,
+            (start, ctx.position()),
+        ))
+    }
+
+    fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        let ch = first_char?;
+        Some(match &self.matcher {
+            Matcher::Literal(text) => text.starts_with(ch),
+            Matcher::Chars { first, .. } => first(ch),
+        })
+    }
+
+    fn name(&self) -> &str {
+        self.kind.as_str()
+    }
+}
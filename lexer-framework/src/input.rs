@@ -0,0 +1,147 @@
+use crate::reader::{decode_utf8, DEFAULT_CHUNK_SIZE};
+use std::io::{self, Read};
+use std::sync::mpsc::Receiver;
+
+/// A source of input text for a lexer fed through
+/// [`SupplyInput`](common_framework::StreamingSignal::SupplyInput), such as a
+/// channel of editor edits or a socket.
+///
+/// A controller calls [`next_chunk`](Self::next_chunk) whenever the lexer
+/// signals [`NeedInput`](common_framework::StreamingSignal::NeedInput), and
+/// signals `EndOfInput` once it returns `None`. Chunks may end anywhere,
+/// including inside a token.
+pub trait InputProducer {
+    /// Returns the next chunk of input, blocking until one is available, or
+    /// `None` once the input has ended.
+    fn next_chunk(&mut self) -> Option<String>;
+}
+
+impl<P: InputProducer + ?Sized> InputProducer for Box<P> {
+    fn next_chunk(&mut self) -> Option<String> {
+        (**self).next_chunk()
+    }
+}
+
+/// Receives chunks until every sender has been dropped.
+impl InputProducer for Receiver<String> {
+    fn next_chunk(&mut self) -> Option<String> {
+        self.recv().ok()
+    }
+}
+
+/// An [`InputProducer`] over an iterator of chunks, such as file reads or
+/// network frames already split into strings.
+///
+/// ```
+/// use lexer_framework::{InputProducer, IterInput};
+///
+/// let mut input = IterInput::new(["let x", " = 1"]);
+/// assert_eq!(input.next_chunk().as_deref(), Some("let x"));
+/// assert_eq!(input.next_chunk().as_deref(), Some(" = 1"));
+/// assert_eq!(input.next_chunk(), None);
+/// ```
+pub struct IterInput<I> {
+    chunks: I,
+}
+
+impl<I> IterInput<I>
+where
+    I: Iterator,
+    I::Item: Into<String>,
+{
+    /// Produces the items of `chunks`, in order.
+    pub fn new<C>(chunks: C) -> Self
+    where
+        C: IntoIterator<IntoIter = I>,
+    {
+        Self {
+            chunks: chunks.into_iter(),
+        }
+    }
+}
+
+impl<I> InputProducer for IterInput<I>
+where
+    I: Iterator,
+    I::Item: Into<String>,
+{
+    fn next_chunk(&mut self) -> Option<String> {
+        self.chunks.next().map(Into::into)
+    }
+}
+
+/// An [`InputProducer`] reading chunks from an [`io::Read`] source.
+///
+/// Unlike [`ReaderLexContext`](crate::ReaderLexContext), which reads on its
+/// own, this leaves the reads to the controller, so the lexer can stay on a
+/// plain [`StreamingLexContext`](crate::StreamingLexContext).
+pub struct ReaderInput<R> {
+    reader: R,
+    chunk_size: usize,
+    /// Trailing bytes of an incomplete UTF-8 sequence from the last read.
+    pending: Vec<u8>,
+    done: bool,
+    error: Option<io::Error>,
+}
+
+impl<R: Read> ReaderInput<R> {
+    /// Reads [`DEFAULT_CHUNK_SIZE`] bytes at a time.
+    pub fn new(reader: R) -> Self {
+        Self::with_chunk_size(reader, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Reads at most `chunk_size` bytes at a time.
+    pub fn with_chunk_size(reader: R, chunk_size: usize) -> Self {
+        Self {
+            reader,
+            chunk_size: chunk_size.max(1),
+            pending: Vec::new(),
+            done: false,
+            error: None,
+        }
+    }
+
+    /// Takes the I/O error that ended the input early, if any.
+    ///
+    /// Like [`ReaderLexContext`](crate::ReaderLexContext), a read error is
+    /// treated as end of input; callers should check this afterwards.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+}
+
+impl<R: Read> InputProducer for ReaderInput<R> {
+    /// Invalid UTF-8 becomes U+FFFD. A sequence split across reads is held
+    /// back until it is complete, so a chunk may be empty.
+    fn next_chunk(&mut self) -> Option<String> {
+        if self.done {
+            return None;
+        }
+        let mut chunk = vec![0; self.chunk_size];
+        let read = loop {
+            match self.reader.read(&mut chunk) {
+                Ok(read) => break read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    self.error = Some(err);
+                    break 0;
+                }
+            }
+        };
+
+        let mut text = String::new();
+        if read == 0 {
+            self.done = true;
+            if self.pending.is_empty() {
+                return None;
+            }
+            // The source ended inside a multi-byte sequence.
+            self.pending.clear();
+            text.push(char::REPLACEMENT_CHARACTER);
+        } else {
+            self.pending.extend_from_slice(&chunk[..read]);
+            decode_utf8(&mut self.pending, &mut text);
+        }
+        Some(text)
+    }
+}
@@ -1,7 +1,18 @@
-use crate::context::{DefaultContext, LexContext};
-use crate::traits::LexingRule;
+use crate::context::{DefaultContext, LexContext, PendingMatch};
+use crate::cursor::Cursor;
+use crate::traits::{LexToken, LexingRule};
+use common_framework::{Diagnostic, Diagnostics, RuleGroups, SourceText, TextSlice};
+use smallvec::SmallVec;
 use std::cmp::Reverse;
 
+/// Number of tokens [`Lexer::tokenize_into`] lexes before reserving space
+/// for the rest of the input, when no [`Lexer::reserve_hint`] is set.
+const DENSITY_SAMPLE_TOKENS: usize = 64;
+
+/// Tokens returned by [`Lexer::next_chunk`]. Chunks of up to 16 tokens are
+/// stored inline; larger ones spill to the heap.
+pub type TokenChunk<Tok> = SmallVec<[Tok; 16]>;
+
 /// A lexer that applies rules in priority order.
 /// This is the main orchestrator in the CGP design.
 pub struct Lexer<Ctx, Tok>
@@ -10,9 +21,36 @@ where
 {
     context: Ctx,
     rules: Vec<Box<dyn LexingRule<Ctx, Tok>>>,
+    // Group of each rule in `rules` (None for ungrouped rules).
+    rule_groups: Vec<Option<usize>>,
+    // Registration order of each rule in `rules`; breaks priority ties.
+    rule_orders: Vec<usize>,
+    groups: RuleGroups,
     // Optimization: lookup table for ASCII characters (0-127)
     // Maps an ASCII char to a list of indices into `rules` that might match it.
+    // Only enabled rules are listed.
     ascii_lookup: [Option<Vec<usize>>; 128],
+    // `groups.version()` the lookup table was built for; None when stale.
+    dispatch_version: Option<u64>,
+    // Token density statistics used to estimate the remaining token count.
+    tokens_lexed: usize,
+    bytes_lexed: usize,
+    avg_token_len: Option<usize>,
+    // Index into `rules` of the rule that matched the last token.
+    last_rule: Option<usize>,
+    // Whether the last token was held back until more input arrives.
+    suspended: bool,
+    // Whether rule boundaries are checked; see `with_strict`.
+    #[cfg(debug_assertions)]
+    strict: bool,
+    // Batch size asked for by the last `RequestTokens` signal.
+    #[cfg(feature = "streaming")]
+    pub(crate) requested_batch: Option<usize>,
+    // Tokens between `Progress` signals, and the count at the last one.
+    #[cfg(feature = "streaming")]
+    pub(crate) progress_interval: Option<usize>,
+    #[cfg(feature = "streaming")]
+    pub(crate) progress_reported: usize,
 }
 
 impl<Ctx, Tok> Lexer<Ctx, Tok>
@@ -20,11 +58,45 @@ where
     Ctx: LexContext,
 {
     /// Creates a new lexer with the given context and rules.
+    ///
+    /// Rules are tried by descending priority. Rules with equal priority are
+    /// tried in the order they appear in `rules`; see
+    /// [`registration_order`](Self::registration_order).
     pub fn new(context: Ctx, rules: Vec<Box<dyn LexingRule<Ctx, Tok>>>) -> Self {
-        // Sort rules by priority (highest first)
-        let mut sorted_rules = rules;
-        sorted_rules.sort_by_key(|rule| Reverse(rule.priority()));
+        // Sort rules by priority (highest first), then by registration order
+        let mut entries: Vec<_> = rules.into_iter().enumerate().collect();
+        entries.sort_by_key(|(order, rule)| (Reverse(rule.priority()), *order));
+        let (rule_orders, sorted_rules): (Vec<_>, Vec<_>) = entries.into_iter().unzip();
+        let rule_groups = vec![None; sorted_rules.len()];
 
+        let mut lexer = Self {
+            context,
+            rules: sorted_rules,
+            rule_groups,
+            rule_orders,
+            groups: RuleGroups::new(),
+            ascii_lookup: std::array::from_fn(|_| None),
+            dispatch_version: None,
+            tokens_lexed: 0,
+            bytes_lexed: 0,
+            avg_token_len: None,
+            last_rule: None,
+            suspended: false,
+            #[cfg(debug_assertions)]
+            strict: false,
+            #[cfg(feature = "streaming")]
+            requested_batch: None,
+            #[cfg(feature = "streaming")]
+            progress_interval: None,
+            #[cfg(feature = "streaming")]
+            progress_reported: 0,
+        };
+        lexer.rebuild_dispatch();
+        lexer
+    }
+
+    /// Rebuilds the ASCII lookup table from the currently enabled rules.
+    fn rebuild_dispatch(&mut self) {
         // Build ASCII lookup table
         // Initialize with None to save memory if not used
         let mut ascii_lookup: [Option<Vec<usize>>; 128] = std::array::from_fn(|_| None);
@@ -34,7 +106,10 @@ where
             let ch = char::from_u32(char_code).unwrap();
             let mut applicable_indices = Vec::new();
 
-            for (idx, rule) in sorted_rules.iter().enumerate() {
+            for (idx, rule) in self.rules.iter().enumerate() {
+                if !self.groups.is_active(self.rule_groups[idx]) {
+                    continue;
+                }
                 // If quick_check returns Some(false), the rule definitely doesn't match.
                 // Otherwise (Some(true) or None), it might match.
                 if rule.quick_check(Some(ch)) != Some(false) {
@@ -51,11 +126,99 @@ where
             }
         }
 
-        Self {
-            context,
-            rules: sorted_rules,
-            ascii_lookup,
+        self.ascii_lookup = ascii_lookup;
+        self.dispatch_version = Some(self.groups.version());
+    }
+
+    /// Adds `rules` to the group called `name`, creating the group (enabled)
+    /// if needed.
+    ///
+    /// Grouped rules can be switched on and off together with
+    /// [`set_group_enabled`](Self::set_group_enabled), e.g. for
+    /// feature-flagged dialect extensions.
+    pub fn add_group(
+        &mut self,
+        name: &str,
+        rules: Vec<Box<dyn LexingRule<Ctx, Tok>>>,
+    ) -> &mut Self {
+        let group = self.groups.get_or_insert(name);
+        let registered = self.rules.len();
+        let mut entries: Vec<_> = self
+            .rules
+            .drain(..)
+            .zip(self.rule_groups.drain(..))
+            .zip(self.rule_orders.drain(..))
+            .chain(
+                rules
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, rule)| ((rule, Some(group)), registered + i)),
+            )
+            .collect();
+        entries.sort_by_key(|((rule, _), order)| (Reverse(rule.priority()), *order));
+        let (rules, rule_orders): (Vec<_>, Vec<_>) = entries.into_iter().unzip();
+        (self.rules, self.rule_groups) = rules.into_iter().unzip();
+        self.rule_orders = rule_orders;
+
+        self.last_rule = None;
+        self.dispatch_version = None;
+        self
+    }
+
+    /// Enables or disables the rules of group `name`. The dispatch table is
+    /// rebuilt lazily before the next token. Returns false if there is no
+    /// such group.
+    pub fn set_group_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        self.groups.set_enabled(name, enabled)
+    }
+
+    /// Returns whether group `name` is enabled, or `None` if it does not exist.
+    pub fn is_group_enabled(&self, name: &str) -> Option<bool> {
+        self.groups.is_enabled(name)
+    }
+
+    /// Returns the rule groups registered with [`add_group`](Self::add_group).
+    pub fn groups(&self) -> &RuleGroups {
+        &self.groups
+    }
+
+    /// Sends the diagnostics of this lexer and its rules to `diagnostics`.
+    /// See [`LexContext::set_diagnostics`].
+    pub fn with_diagnostics(mut self, diagnostics: Diagnostics) -> Self {
+        self.context.set_diagnostics(diagnostics);
+        self
+    }
+
+    /// Turns on strict mode: after every rule the lexer tries, it checks
+    /// that a match consumed input and a failure left the context where the
+    /// rule started, and panics with the rule's name and position if not.
+    ///
+    /// Strict mode only exists in builds with debug assertions; in release
+    /// builds this does nothing.
+    #[cfg_attr(not(debug_assertions), allow(unused_mut))]
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        #[cfg(debug_assertions)]
+        {
+            self.strict = strict;
         }
+        #[cfg(not(debug_assertions))]
+        let _ = strict;
+        self
+    }
+
+    /// Returns true if strict mode is on.
+    pub fn is_strict(&self) -> bool {
+        #[cfg(debug_assertions)]
+        return self.strict;
+        #[cfg(not(debug_assertions))]
+        false
+    }
+
+    /// Returns true if the lexer stopped at a token that may continue past
+    /// the buffered input, and will lex it once more input arrives or the
+    /// input is finished. See [`LexContext::pending`].
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
     }
 
     /// Returns a reference to the context.
@@ -68,6 +231,63 @@ where
         &mut self.context
     }
 
+    /// Returns the rules in the order they are tried (highest priority first).
+    pub fn rules(&self) -> &[Box<dyn LexingRule<Ctx, Tok>>] {
+        &self.rules
+    }
+
+    /// Returns the registration order of each rule in [`rules`](Self::rules).
+    ///
+    /// Rules passed to [`new`](Self::new) are numbered from 0 in the order
+    /// given; rules added with [`add_group`](Self::add_group) continue the
+    /// count. Among rules with equal priority, the lower number is tried first.
+    pub fn registration_order(&self) -> &[usize] {
+        &self.rule_orders
+    }
+
+    /// Returns whether the rule at `idx` in [`rules`](Self::rules) is enabled.
+    pub(crate) fn is_rule_active(&self, idx: usize) -> bool {
+        self.groups.is_active(self.rule_groups[idx])
+    }
+
+    /// Returns the rule that matched the most recent token.
+    pub fn last_rule(&self) -> Option<&dyn LexingRule<Ctx, Tok>> {
+        self.last_rule.map(|idx| self.rules[idx].as_ref())
+    }
+
+    /// Sets the expected average token length in bytes.
+    ///
+    /// Batch methods use it to reserve output space before lexing. Without a
+    /// hint the average is measured from the tokens lexed so far.
+    pub fn reserve_hint(&mut self, avg_token_len: usize) -> &mut Self {
+        self.avg_token_len = Some(avg_token_len.max(1));
+        self
+    }
+
+    /// Estimates how many tokens are left, based on the remaining input
+    /// length and the average token length.
+    ///
+    /// Returns `None` when the remaining length is unknown (streaming) or
+    /// when there is neither a [`reserve_hint`](Self::reserve_hint) nor any
+    /// lexed token to measure. Unlike `size_hint`, this is not a bound.
+    pub fn estimated_remaining(&self) -> Option<usize> {
+        let remaining = self.context.remaining_len()?;
+        let estimate = match self.avg_token_len {
+            Some(avg) => remaining.div_ceil(avg),
+            None if self.tokens_lexed > 0 => {
+                // remaining / (bytes_lexed / tokens_lexed), rounded up.
+                (remaining * self.tokens_lexed).div_ceil(self.bytes_lexed.max(1))
+            }
+            None => return None,
+        };
+        Some(estimate)
+    }
+
+    /// Returns the number of tokens produced so far.
+    pub fn tokens_lexed(&self) -> usize {
+        self.tokens_lexed
+    }
+
     /// Tries to match the next token using the rules.
     ///
     /// This method optimizes rule matching by:
@@ -78,7 +298,12 @@ where
             return None;
         }
 
+        if self.dispatch_version != Some(self.groups.version()) {
+            self.rebuild_dispatch();
+        }
+
         let first_char = self.context.peek();
+        let strict = self.is_strict();
 
         // Determine which rules to try
         let candidate_indices: &[usize] = match first_char {
@@ -117,24 +342,25 @@ where
                     // Actually, rule.try_match() does the real work.
 
                     // Try match
-                    let checkpoint = self.context.checkpoint();
-                    if let Some(token) = rule.try_match(&mut self.context) {
+                    if let Some(token) = Self::try_rule(&mut self.context, rule, strict) {
+                        self.last_rule = Some(idx);
                         return Some(token);
                     }
-                    self.context.restore(checkpoint);
                 }
             } else {
                 // Slow path for non-ASCII
-                for rule in &mut self.rules {
+                for (idx, rule) in self.rules.iter_mut().enumerate() {
+                    if !self.groups.is_active(self.rule_groups[idx]) {
+                        continue;
+                    }
                     if let Some(false) = rule.quick_check(first_char) {
                         continue;
                     }
 
-                    let checkpoint = self.context.checkpoint();
-                    if let Some(token) = rule.try_match(&mut self.context) {
+                    if let Some(token) = Self::try_rule(&mut self.context, rule, strict) {
+                        self.last_rule = Some(idx);
                         return Some(token);
                     }
-                    self.context.restore(checkpoint);
                 }
             }
         } else {
@@ -162,12 +388,148 @@ where
         None
     }
 
+    /// Tries `rule` at the current position, rolling back if it fails. In
+    /// strict mode, panics if the rule broke the rule contract.
+    fn try_rule(
+        context: &mut Ctx,
+        rule: &mut Box<dyn LexingRule<Ctx, Tok>>,
+        strict: bool,
+    ) -> Option<Tok> {
+        context.enter_rule(rule.name());
+        let (start, position) = (context.offset(), context.position());
+        let token = context.speculate(|ctx| rule.try_match(ctx));
+        if strict {
+            let end = context.offset();
+            let violation = match &token {
+                Some(_) if end == start => Some("returned a token without consuming input"),
+                Some(_) if end < start => Some("returned a token after moving backwards"),
+                None if end != start || context.position() != position => {
+                    Some("failed without leaving the input where it started")
+                }
+                _ => None,
+            };
+            if let Some(violation) = violation {
+                panic!(
+                    "strict mode: rule `{}` {} at {}:{} (offset {})",
+                    rule.name(),
+                    violation,
+                    position.line,
+                    position.column,
+                    start
+                );
+            }
+        }
+        token
+    }
+
+    /// Lists the names of the rules whose `quick_check` accepts `first_char`.
+    fn candidate_names(&self, first_char: Option<char>) -> String {
+        let names: Vec<&str> = self
+            .rules
+            .iter()
+            .zip(&self.rule_groups)
+            .filter(|(rule, group)| {
+                self.groups.is_active(**group) && rule.quick_check(first_char) != Some(false)
+            })
+            .map(|(rule, _)| rule.name())
+            .collect();
+        if names.is_empty() {
+            "none".to_string()
+        } else {
+            names.join(", ")
+        }
+    }
+
+    /// Like `next_token`, but tops up refillable contexts first.
+    ///
+    /// A token that runs into the end of the buffered input may have been
+    /// cut short by a chunk boundary (or a rule may have declined a partial
+    /// match, or reported it [pending](LexContext::pending)), so the context
+    /// is refilled and the token is lexed again. If it cannot be refilled
+    /// but more input may still be pushed, the lexer suspends and returns
+    /// `None` with the context back at the token's start. Finished input
+    /// cannot grow, so its first result stands and rules run only once.
+    fn next_complete_token(&mut self) -> Option<Tok> {
+        self.suspended = false;
+        loop {
+            let checkpoint = self.context.checkpoint();
+            let token = self.next_token();
+            let pending = !self.context.is_input_finished() && self.take_pending();
+            let complete = !pending
+                && (self.context.is_eof() || (token.is_some() && self.context.peek().is_some()));
+            if complete || self.context.is_input_finished() {
+                return token;
+            }
+
+            self.context.restore(checkpoint);
+            if self.context.refill() {
+                continue;
+            }
+            // Wait for more input if a rule matched or is pending. Otherwise
+            // no rule matched, and rules leave the input where it started on
+            // failure, so lexing again would only repeat that.
+            self.suspended = pending || token.is_some();
+            return None;
+        }
+    }
+
+    /// Returns and clears the flag set by [`LexContext::pending`].
+    fn take_pending(&mut self) -> bool {
+        self.context
+            .state_map()
+            .and_then(|state| state.remove::<PendingMatch>())
+            .is_some()
+    }
+
     /// Collects all tokens from the input.
     ///
     /// Uses iterator internally for better code reuse and allows the lexer
-    /// to be used as an iterator directly.
+    /// to be used as an iterator directly. The output is pre-sized from the
+    /// measured token density; see [`tokenize_into`](Self::tokenize_into).
     pub fn tokenize(&mut self) -> Vec<Tok> {
-        self.collect()
+        let mut tokens = Vec::new();
+        self.tokenize_into(&mut tokens);
+        tokens
+    }
+
+    /// Appends all remaining tokens to `buffer` and returns how many were added.
+    ///
+    /// Unlike [`tokenize`](Self::tokenize), this reuses the buffer's existing
+    /// allocation, so a buffer cleared between inputs stops regrowing once it
+    /// has reached the size of the largest token stream.
+    ///
+    /// Space for the remaining tokens is reserved up front from
+    /// [`estimated_remaining`](Self::estimated_remaining); without a
+    /// [`reserve_hint`](Self::reserve_hint), a short sample is lexed first to
+    /// measure the token density of this input.
+    pub fn tokenize_into(&mut self, buffer: &mut Vec<Tok>) -> usize {
+        let before = buffer.len();
+        if self.avg_token_len.is_none() && self.tokens_lexed < DENSITY_SAMPLE_TOKENS {
+            let sample = DENSITY_SAMPLE_TOKENS - self.tokens_lexed;
+            buffer.extend(self.by_ref().take(sample));
+        }
+        if let Some(estimate) = self.estimated_remaining() {
+            buffer.reserve(estimate);
+        }
+        buffer.extend(self.by_ref());
+        buffer.len() - before
+    }
+
+    /// Lexes up to `n` tokens. Fewer are returned only at the end of input.
+    pub fn next_chunk(&mut self, n: usize) -> TokenChunk<Tok> {
+        let mut chunk = TokenChunk::with_capacity(n);
+        chunk.extend(self.by_ref().take(n));
+        chunk
+    }
+
+    /// Appends up to `n` tokens to `buffer` and returns how many were added.
+    ///
+    /// A return value of zero means the input is exhausted. Clearing and
+    /// refilling the same buffer keeps hot loops free of allocations.
+    pub fn next_chunk_into(&mut self, n: usize, buffer: &mut Vec<Tok>) -> usize {
+        let before = buffer.len();
+        buffer.extend(self.by_ref().take(n));
+        buffer.len() - before
     }
 }
 
@@ -179,6 +541,40 @@ impl<Tok> Lexer<DefaultContext, Tok> {
     ) -> Self {
         Self::new(DefaultContext::new(input), rules)
     }
+
+    /// Creates a lexer over `source` that skips line and column tracking.
+    ///
+    /// Positions reported by the context carry only the byte offset (line
+    /// and column are 0); resolve them with [`SourceText::resolve`] or
+    /// [`SourceText::position_at`]. Use this when the line table is built
+    /// anyway, e.g. for diagnostics, to avoid counting lines twice.
+    pub fn from_source(
+        source: &SourceText,
+        rules: Vec<Box<dyn LexingRule<DefaultContext, Tok>>>,
+    ) -> Self {
+        let cursor = Cursor::untracked(source.shared_text());
+        Self::new(DefaultContext::from_cursor(cursor), rules)
+    }
+
+    /// Lexes a slice captured by an outer lexer with a different rule set.
+    ///
+    /// Used for embedded syntax such as code fences, doc comments or string
+    /// interpolations. Token positions are rebased with
+    /// [`LexToken::relocated`] so they point into the outer input rather
+    /// than into the slice.
+    pub fn sublex(
+        slice: TextSlice,
+        rules: Vec<Box<dyn LexingRule<DefaultContext, Tok>>>,
+    ) -> Vec<Tok>
+    where
+        Tok: LexToken,
+    {
+        let base = slice.start_position();
+        let context = DefaultContext::new(slice.as_ref());
+        Lexer::new(context, rules)
+            .map(|token| token.relocated(base))
+            .collect()
+    }
 }
 
 /// Make Lexer implement Iterator for stream-like processing.
@@ -196,24 +592,46 @@ where
 
         let offset_before = self.context.offset();
 
-        if let Some(token) = self.next_token() {
+        if let Some(token) = self.next_complete_token() {
             // Check if we made progress
             if self.context.offset() == offset_before {
                 // No progress made, this indicates a bug in the rule
-                eprintln!("Warning: No progress made at offset {}", offset_before);
+                let rule = self.last_rule().map_or("?", |rule| rule.name());
+                let message = format!(
+                    "no progress made at offset {} (rule `{}`)",
+                    offset_before, rule
+                );
+                let position = self.context.position();
+                self.context
+                    .report(Diagnostic::warning((position, position), message));
                 return None;
             }
+            self.tokens_lexed += 1;
+            self.bytes_lexed += self.context.offset() - offset_before;
             Some(token)
+        } else if self.suspended {
+            // Waiting for the rest of a token that reaches past the buffer.
+            None
         } else if self.context.offset() == offset_before {
             // Stuck - no rule matched and cursor didn't advance
-            eprintln!(
-                "Error: No rule matched character at offset {}",
-                offset_before
-            );
-            // Try to peek at the next character for error reporting
-            if let Some(ch) = self.context.peek() {
-                eprintln!("Current character: {:?}", ch);
+            let ch = self.context.peek();
+            if ch.is_none() && !self.context.is_eof() {
+                // Out of buffered input; more may still be pushed.
+                return None;
             }
+            let found = match ch {
+                Some(ch) => format!("character {:?}", ch),
+                None => "end of input".to_string(),
+            };
+            let message = format!(
+                "no rule matched {} at offset {} (rules tried: {})",
+                found,
+                offset_before,
+                self.candidate_names(ch)
+            );
+            let position = self.context.position();
+            self.context
+                .report(Diagnostic::error((position, position), message));
             None
         } else {
             // Progress was made but no token returned (unusual case)
@@ -229,6 +647,9 @@ where
         // - In the worst case, each character could be a token (upper bound)
         // - In practice, tokens are often multi-character (identifiers, numbers, strings)
         // - We use character count as a conservative upper bound
+        //
+        // Both bounds must hold for every input, so the density estimate is
+        // not used here; batch methods reserve from `estimated_remaining`.
 
         if let Some(len) = self.context.remaining_len() {
             // If we know the remaining bytes, we can use that as an upper bound
@@ -1,14 +1,74 @@
+pub mod adapter;
+pub mod analyze;
+pub mod bench_support;
+pub mod bytes;
+#[cfg(feature = "streaming")]
+pub mod chain;
+pub mod channel;
 pub mod context;
 pub mod cursor;
+pub mod encode;
+#[cfg(feature = "encoding")]
+pub mod encoding;
+pub mod escape;
+pub mod generic;
+#[cfg(feature = "streaming")]
+pub mod input;
 pub mod lexer;
+pub mod located;
+#[cfg(feature = "streaming")]
+pub mod merge;
+pub mod mode;
+pub mod preprocess;
+#[cfg(feature = "streaming")]
+pub mod reader;
+pub mod rewrite;
 #[cfg(feature = "streaming")]
 pub mod streaming;
+pub mod testing;
 pub mod traits;
+pub mod validate;
+pub mod words;
 
-pub use common_framework::{Checkpoint, Position, TextSlice};
+pub use adapter::{FilterTokens, InspectTokens, MapTokens, TeeProducer, TokenSink};
+pub use analyze::PriorityConflict;
+pub use bench_support::bench_lexer;
+pub use bytes::{ByteCursor, ByteLexContext, ByteLexer, ByteLexingRule, DefaultByteContext};
+#[cfg(feature = "streaming")]
+pub use chain::ChainedProducer;
+pub use channel::ChannelTokens;
+#[cfg(feature = "alloc-stats")]
+pub use common_framework::CountingAllocator;
+pub use common_framework::{
+    pos_seq, positions_of, AllocStats, BenchReport, CallbackSink, Channel, Checkpoint, CompactPos,
+    CompactText, Diagnostic, DiagnosticSink, Diagnostics, Feedback, IgnoreSink, LineIndex,
+    PinnedCheckpoint, Position, PositionAssigner, RuleId, RuleStats, Severity, SourceFile,
+    SourceId, SourceMap, SourceText, StateMap, TextSlice,
+};
 pub use context::{DefaultContext, LexContext};
 pub use cursor::Cursor;
-pub use lexer::Lexer;
+pub use encode::{EncodeToken, EncodedTokens, TokenCodecError, TokenDecoder, TokenRecord};
+#[cfg(feature = "encoding")]
+pub use encoding::{DecodedInput, Encoding, OffsetMap};
+pub use escape::{decode_escape, EscapeError, EscapeErrorKind};
+pub use generic::{GenericRule, GenericToken};
+#[cfg(feature = "streaming")]
+pub use input::{InputProducer, IterInput, ReaderInput};
+pub use lexer::{Lexer, TokenChunk};
+pub use located::{CompactToken, Located};
+#[cfg(feature = "streaming")]
+pub use merge::{MergeChooser, MergePolicy, MergeProducer};
+pub use mode::{InMode, ModalContext, ModeContext};
+pub use preprocess::{Directive, PreprocessError, PreprocessHandler, Preprocessor};
+#[cfg(feature = "streaming")]
+pub use reader::ReaderLexContext;
+pub use rewrite::{Lookahead, Rewrite, RewriteError, TokenRewrite, TokenRewriter};
 #[cfg(feature = "streaming")]
 pub use streaming::{StreamingLexContext, TokenProducer};
-pub use traits::{LexToken, LexingRule};
+pub use testing::{lex_all, lex_single};
+pub use traits::{LexToken, LexingRule, TokenKind};
+pub use validate::{RuleViolation, ValidatingContext, ViolationKind};
+pub use words::{WordRule, WordToken};
+
+/// This crate's version, as recorded in pipeline run metadata.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
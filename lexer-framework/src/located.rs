@@ -0,0 +1,70 @@
+use crate::traits::LexToken;
+use common_framework::{CompactPos, LineIndex, Position};
+use std::sync::Arc;
+
+/// A token that stores a [`CompactPos`] instead of a full [`Position`].
+///
+/// Such tokens usually return `None` from [`LexToken::position`]; wrapping
+/// them in [`Located`] restores it through a [`LineIndex`].
+pub trait CompactToken: LexToken {
+    /// Returns the compact position of this token.
+    fn compact_pos(&self) -> Option<CompactPos>;
+}
+
+/// A [`CompactToken`] paired with the [`LineIndex`] of its input, so that
+/// [`LexToken::position`] reports full line/column positions again.
+///
+/// Keep tokens compact while they are buffered and wrap them only where
+/// positions are read, e.g. with `tokens.into_iter().map(|t| Located::new(t, index.clone()))`.
+#[derive(Debug, Clone)]
+pub struct Located<Tok> {
+    token: Tok,
+    index: Arc<LineIndex>,
+}
+
+impl<Tok> Located<Tok> {
+    /// Pairs `token` with the index of the input it was lexed from.
+    pub fn new(token: Tok, index: Arc<LineIndex>) -> Self {
+        Self { token, index }
+    }
+
+    /// Returns the wrapped token.
+    pub fn token(&self) -> &Tok {
+        &self.token
+    }
+
+    /// Returns the line index.
+    pub fn index(&self) -> &Arc<LineIndex> {
+        &self.index
+    }
+
+    /// Unwraps the token.
+    pub fn into_inner(self) -> Tok {
+        self.token
+    }
+}
+
+impl<Tok: CompactToken> LexToken for Located<Tok> {
+    fn position(&self) -> Option<Position> {
+        self.token
+            .compact_pos()
+            .map(|pos| self.index.position(pos))
+            .or_else(|| self.token.position())
+    }
+
+    fn is_eof(&self) -> bool {
+        self.token.is_eof()
+    }
+
+    fn is_newline(&self) -> bool {
+        self.token.is_newline()
+    }
+
+    fn is_whitespace(&self) -> bool {
+        self.token.is_whitespace()
+    }
+
+    fn is_indent(&self) -> bool {
+        self.token.is_indent()
+    }
+}
@@ -0,0 +1,254 @@
+use crate::streaming::TokenProducer;
+use crate::traits::LexToken;
+use common_framework::{Inbound, Outbound, Position, StreamingSignal};
+use std::fmt;
+
+/// Picks the next source for a [`MergeProducer`] from the indices of the
+/// sources that have not ended yet.
+pub type MergeChooser = Box<dyn FnMut(&[usize]) -> usize>;
+
+/// The order in which a [`MergeProducer`] takes tokens from its sources.
+#[derive(Default)]
+pub enum MergePolicy {
+    /// Drain each source before moving on to the next. The default.
+    #[default]
+    Sequential,
+    /// Take one token from each source in turn.
+    RoundRobin,
+    /// Ask a callback before every token. It gets the indices of the
+    /// sources still running, in order, and returns one of them.
+    Custom(MergeChooser),
+}
+
+impl MergePolicy {
+    /// Creates a [`Custom`](Self::Custom) policy.
+    pub fn custom<F>(choose: F) -> Self
+    where
+        F: FnMut(&[usize]) -> usize + 'static,
+    {
+        Self::Custom(Box::new(choose))
+    }
+}
+
+impl fmt::Debug for MergePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sequential => f.write_str("Sequential"),
+            Self::RoundRobin => f.write_str("RoundRobin"),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// A token producer that merges the tokens of several producers into one
+/// stream, e.g. multiplexed channels or a list of concatenated files.
+///
+/// The [`MergePolicy`] decides which source the next token comes from.
+/// Unlike [`ChainedProducer`](crate::ChainedProducer), positions are passed
+/// through unchanged; [`last_source`](Self::last_source) tells which source
+/// the latest token came from.
+///
+/// The stream ends once every source has: EOF tokens are dropped except
+/// the one from the source that ends last, and
+/// [`EndOfInput`](StreamingSignal::EndOfInput) is only signalled after
+/// every source has signalled it. A source with no token ready (`None`
+/// from `next_signal`) stalls the whole merge until it has one.
+pub struct MergeProducer<P> {
+    sources: Vec<P>,
+    ended: Vec<bool>,
+    policy: MergePolicy,
+    // Where round-robin resumes.
+    turn: usize,
+    last_source: Option<usize>,
+    // The source that signalled `NeedInput`, which gets the answer and is
+    // polled again next.
+    waiting: Option<usize>,
+}
+
+impl<P> MergeProducer<P> {
+    /// Merges `sources` with `policy`.
+    pub fn new(sources: Vec<P>, policy: MergePolicy) -> Self {
+        Self {
+            ended: vec![false; sources.len()],
+            sources,
+            policy,
+            turn: 0,
+            last_source: None,
+            waiting: None,
+        }
+    }
+
+    /// Merges `sources` one after the other.
+    pub fn sequential(sources: Vec<P>) -> Self {
+        Self::new(sources, MergePolicy::Sequential)
+    }
+
+    /// Merges `sources` one token at a time, in turn.
+    pub fn round_robin(sources: Vec<P>) -> Self {
+        Self::new(sources, MergePolicy::RoundRobin)
+    }
+
+    /// Returns the sources.
+    pub fn sources(&self) -> &[P] {
+        &self.sources
+    }
+
+    /// Returns the index of the source the latest token came from.
+    pub fn last_source(&self) -> Option<usize> {
+        self.last_source
+    }
+
+    /// Returns true once every source has ended.
+    pub fn is_finished(&self) -> bool {
+        self.ended.iter().all(|&ended| ended)
+    }
+
+    /// Unwraps the merge into its sources.
+    pub fn into_sources(self) -> Vec<P> {
+        self.sources
+    }
+
+    /// Returns the index of the source to take the next token from, or
+    /// `None` once all have ended.
+    fn choose(&mut self) -> Option<usize> {
+        let running: Vec<usize> = (0..self.sources.len())
+            .filter(|&index| !self.ended[index])
+            .collect();
+        let first = *running.first()?;
+        let index = match &mut self.policy {
+            MergePolicy::Sequential => first,
+            MergePolicy::RoundRobin => {
+                let index = running
+                    .iter()
+                    .copied()
+                    .find(|&index| index >= self.turn)
+                    .unwrap_or(first);
+                self.turn = index + 1;
+                index
+            }
+            MergePolicy::Custom(choose) => {
+                let index = choose(&running);
+                debug_assert!(
+                    running.contains(&index),
+                    "MergePolicy::Custom chose a source that has ended"
+                );
+                if running.contains(&index) {
+                    index
+                } else {
+                    first
+                }
+            }
+        };
+        Some(index)
+    }
+
+    /// Marks source `index` as ended.
+    fn end(&mut self, index: usize) {
+        self.ended[index] = true;
+    }
+
+    /// Returns true if source `index` is the only one still running.
+    fn is_last(&self, index: usize) -> bool {
+        self.ended
+            .iter()
+            .enumerate()
+            .all(|(other, &ended)| ended || other == index)
+    }
+
+    /// Records that `token` came from source `index`, or drops it if it is
+    /// an EOF token and other sources are still running.
+    fn accept<Tok: LexToken>(&mut self, index: usize, token: Tok) -> Option<Tok> {
+        if token.is_eof() && !self.is_last(index) {
+            return None;
+        }
+        self.last_source = Some(index);
+        Some(token)
+    }
+}
+
+impl<P, Tok> TokenProducer<Tok> for MergeProducer<P>
+where
+    P: TokenProducer<Tok>,
+    Tok: LexToken,
+{
+    fn poll_token(&mut self) -> Option<Tok> {
+        while let Some(index) = self.choose() {
+            match self.sources[index].poll_token() {
+                Some(token) => {
+                    if let Some(token) = self.accept(index, token) {
+                        return Some(token);
+                    }
+                }
+                None => self.end(index),
+            }
+        }
+        None
+    }
+
+    fn input_position(&self) -> Option<Position> {
+        self.sources[self.last_source?].input_position()
+    }
+}
+
+impl<P, Tok, Ast> Outbound<Tok, Ast> for MergeProducer<P>
+where
+    P: Outbound<Tok, Ast>,
+    Tok: LexToken,
+{
+    fn next_signal(&mut self) -> Option<StreamingSignal<Tok, Ast>> {
+        while let Some(index) = self.waiting.take().or_else(|| self.choose()) {
+            // `None` means the source is waiting for more input.
+            match self.sources[index].next_signal()? {
+                StreamingSignal::SupplyToken(token) => {
+                    if let Some(token) = self.accept(index, token) {
+                        return Some(StreamingSignal::SupplyToken(token));
+                    }
+                }
+                StreamingSignal::EndOfInput => self.end(index),
+                StreamingSignal::NeedInput => {
+                    self.waiting = Some(index);
+                    return Some(StreamingSignal::NeedInput);
+                }
+                signal => return Some(signal),
+            }
+        }
+        Some(StreamingSignal::EndOfInput)
+    }
+}
+
+impl<P, Tok, Ast> Inbound<Tok, Ast> for MergeProducer<P>
+where
+    P: Inbound<Tok, Ast>,
+{
+    /// Aborts go to every source, and input to the source that asked for
+    /// it. Anything else, such as feedback, goes to the source of the
+    /// latest token, which is what the parser reacted to.
+    fn handle_signal(&mut self, signal: StreamingSignal<Tok, Ast>) {
+        let target = self
+            .last_source
+            .or_else(|| self.ended.iter().position(|&ended| !ended));
+        match signal {
+            StreamingSignal::Abort(reason) => {
+                for source in &mut self.sources {
+                    source.handle_signal(StreamingSignal::Abort(reason.clone()));
+                }
+            }
+            StreamingSignal::SupplyInput(_) | StreamingSignal::EndOfInput => {
+                if let Some(index) = self.waiting {
+                    self.sources[index].handle_signal(signal);
+                }
+            }
+            // Sources are interleaved one token at a time.
+            StreamingSignal::RequestTokens(n) => {
+                if let Some(index) = target {
+                    self.sources[index].handle_signal(StreamingSignal::RequestToken(n));
+                }
+            }
+            signal => {
+                if let Some(index) = target {
+                    self.sources[index].handle_signal(signal);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use crate::context::LexContext;
+use crate::cursor::Cursor;
+use crate::traits::LexingRule;
+use common_framework::{Channel, Checkpoint, Position, RuleId, StateMap, TextSlice};
+
+/// A lex context with a stack of lexer modes.
+///
+/// Modes let one lexer handle languages that embed another syntax, such as
+/// template text with `{{ expression }}` islands: rules check the current
+/// mode (usually via [`InMode`]) and push or pop modes when they match a
+/// delimiter.
+pub trait ModeContext<M>: LexContext {
+    /// Returns the mode on top of the stack.
+    fn mode(&self) -> &M;
+
+    /// Enters `mode` until the matching [`pop_mode`](Self::pop_mode).
+    fn push_mode(&mut self, mode: M);
+
+    /// Leaves the current mode and returns it. The initial mode is never
+    /// popped; `None` is returned instead.
+    fn pop_mode(&mut self) -> Option<M>;
+
+    /// Returns the number of modes on the stack, including the initial one.
+    fn mode_depth(&self) -> usize;
+}
+
+enum ModeChange<M> {
+    Pushed,
+    Popped(M),
+}
+
+/// Wraps any [`LexContext`] with a mode stack.
+///
+/// Mode changes are journaled with the offset at which they happened, and
+/// `restore` undoes those made after the checkpoint, so a token that is
+/// matched and then backtracked over does not leave a stale mode behind.
+/// Rules should therefore change modes only after consuming the text that
+/// triggers the change.
+pub struct ModalContext<Ctx, M> {
+    inner: Ctx,
+    stack: Vec<M>,
+    journal: Vec<(usize, ModeChange<M>)>,
+}
+
+impl<Ctx, M> ModalContext<Ctx, M>
+where
+    Ctx: LexContext,
+{
+    /// Creates a context starting in `initial` mode.
+    pub fn new(inner: Ctx, initial: M) -> Self {
+        Self {
+            inner,
+            stack: vec![initial],
+            journal: Vec::new(),
+        }
+    }
+
+    /// Returns the wrapped context.
+    pub fn inner(&self) -> &Ctx {
+        &self.inner
+    }
+
+    /// Returns the whole mode stack, bottom first.
+    pub fn modes(&self) -> &[M] {
+        &self.stack
+    }
+}
+
+impl<Ctx, M> ModeContext<M> for ModalContext<Ctx, M>
+where
+    Ctx: LexContext,
+    M: Clone,
+{
+    fn mode(&self) -> &M {
+        self.stack
+            .last()
+            .expect("mode stack always holds the initial mode")
+    }
+
+    fn push_mode(&mut self, mode: M) {
+        self.journal.push((self.inner.offset(), ModeChange::Pushed));
+        self.stack.push(mode);
+    }
+
+    fn pop_mode(&mut self) -> Option<M> {
+        if self.stack.len() == 1 {
+            return None;
+        }
+        let mode = self.stack.pop()?;
+        self.journal
+            .push((self.inner.offset(), ModeChange::Popped(mode.clone())));
+        Some(mode)
+    }
+
+    fn mode_depth(&self) -> usize {
+        self.stack.len()
+    }
+}
+
+impl<Ctx, M> LexContext for ModalContext<Ctx, M>
+where
+    Ctx: LexContext,
+{
+    fn cursor(&self) -> &Cursor {
+        self.inner.cursor()
+    }
+
+    fn cursor_mut(&mut self) -> &mut Cursor {
+        self.inner.cursor_mut()
+    }
+
+    fn position(&self) -> Position {
+        self.inner.position()
+    }
+
+    fn is_eof(&self) -> bool {
+        self.inner.is_eof()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.inner.peek()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        self.inner.advance()
+    }
+
+    fn consume_while<F>(&mut self, predicate: F) -> TextSlice
+    where
+        F: FnMut(char) -> bool,
+    {
+        self.inner.consume_while(predicate)
+    }
+
+    fn checkpoint(&self) -> Checkpoint {
+        self.inner.checkpoint()
+    }
+
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        let offset = checkpoint.current();
+        while let Some((at, _)) = self.journal.last() {
+            if *at <= offset {
+                break;
+            }
+            match self.journal.pop() {
+                Some((_, ModeChange::Pushed)) => {
+                    self.stack.pop();
+                }
+                Some((_, ModeChange::Popped(mode))) => self.stack.push(mode),
+                None => break,
+            }
+        }
+        self.inner.restore(checkpoint);
+    }
+
+    fn offset(&self) -> usize {
+        self.inner.offset()
+    }
+
+    fn remaining_len(&self) -> Option<usize> {
+        self.inner.remaining_len()
+    }
+
+    fn refill(&mut self) -> bool {
+        self.inner.refill()
+    }
+
+    fn push_input(&mut self, text: &str) {
+        self.inner.push_input(text);
+    }
+
+    fn finish_input(&mut self) {
+        self.inner.finish_input();
+    }
+
+    fn is_input_finished(&self) -> bool {
+        self.inner.is_input_finished()
+    }
+
+    fn state_map(&mut self) -> Option<&mut StateMap> {
+        self.inner.state_map()
+    }
+
+    fn slice_from(&mut self, start: usize) -> TextSlice {
+        self.inner.slice_from(start)
+    }
+
+    fn enter_rule(&mut self, rule: &str) {
+        self.inner.enter_rule(rule);
+    }
+}
+
+/// Restricts a rule to the given modes.
+///
+/// Rules for every mode live in the same [`Lexer`](crate::Lexer); wrapping
+/// them in `InMode` makes each one decline outside its modes.
+pub struct InMode<M, R> {
+    modes: Vec<M>,
+    rule: R,
+}
+
+impl<M, R> InMode<M, R> {
+    /// Enables `rule` only while `mode` is on top of the stack.
+    pub fn new(mode: M, rule: R) -> Self {
+        Self {
+            modes: vec![mode],
+            rule,
+        }
+    }
+
+    /// Enables `rule` in any of `modes`.
+    pub fn any_of(modes: impl IntoIterator<Item = M>, rule: R) -> Self {
+        Self {
+            modes: modes.into_iter().collect(),
+            rule,
+        }
+    }
+}
+
+impl<Ctx, Tok, M, R> LexingRule<Ctx, Tok> for InMode<M, R>
+where
+    Ctx: ModeContext<M>,
+    M: PartialEq,
+    R: LexingRule<Ctx, Tok>,
+{
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        if !self.modes.contains(ctx.mode()) {
+            return None;
+        }
+        self.rule.try_match(ctx)
+    }
+
+    fn priority(&self) -> i32 {
+        self.rule.priority()
+    }
+
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        self.rule.quick_check(first_char)
+    }
+
+    fn channel(&self) -> Channel {
+        self.rule.channel()
+    }
+
+    fn name(&self) -> &str {
+        self.rule.name()
+    }
+
+    fn id(&self) -> RuleId {
+        self.rule.id()
+    }
+}
@@ -0,0 +1,237 @@
+use crate::context::DefaultContext;
+use crate::lexer::Lexer;
+use crate::traits::{LexToken, LexingRule};
+use common_framework::{Position, SourceId, SourceMap};
+use std::fmt;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+type RulesFn<'a, Tok> = Box<dyn FnMut() -> Vec<Box<dyn LexingRule<DefaultContext, Tok>>> + 'a>;
+type LoaderFn<'a> = Box<dyn FnMut(&str, &str) -> io::Result<String> + 'a>;
+type ResolveFn<'a> = Box<dyn FnMut(&str, &str) -> String + 'a>;
+
+/// What a [`PreprocessHandler`] does with a token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Directive<Tok> {
+    /// Passes the token on.
+    Keep(Tok),
+    /// Removes the token from the stream.
+    Drop,
+    /// Replaces the token with the tokens of the named source.
+    Include(String),
+}
+
+/// Inspects every token of a [`Preprocessor`] run and decides whether it is
+/// kept, dropped or replaced by an included source.
+///
+/// Implemented for closures `FnMut(Tok) -> Directive<Tok>`.
+pub trait PreprocessHandler<Tok> {
+    fn handle(&mut self, token: Tok) -> Directive<Tok>;
+}
+
+impl<Tok, F> PreprocessHandler<Tok> for F
+where
+    F: FnMut(Tok) -> Directive<Tok>,
+{
+    fn handle(&mut self, token: Tok) -> Directive<Tok> {
+        self(token)
+    }
+}
+
+/// Lexes a source and splices in the tokens of the sources it includes,
+/// recursively, as decided by a [`PreprocessHandler`].
+///
+/// An included name is first resolved against the including source, by
+/// default as a path relative to its directory (see
+/// [`with_resolver`](Preprocessor::with_resolver)). Included sources are
+/// added to the [`SourceMap`] under the resolved name (or reused if a
+/// source of that name is already there) and lexed with a fresh rule set.
+/// Their EOF tokens are dropped, so only the root source's end remains.
+/// Token positions are relocated to the file's global offsets, so
+/// [`SourceMap::lookup`] maps any token back to its file, line and column.
+///
+/// ```ignore
+/// let mut sources = SourceMap::new();
+/// let main = sources.add("main.c", fs::read_to_string("main.c")?);
+/// let tokens = Preprocessor::new(&mut sources, c_rules, |name, _| fs::read_to_string(name))
+///     .run(main, |token| match token {
+///         CToken::Include { path, .. } => Directive::Include(path),
+///         token => Directive::Keep(token),
+///     })?;
+/// ```
+pub struct Preprocessor<'a, Tok> {
+    sources: &'a mut SourceMap,
+    rules: RulesFn<'a, Tok>,
+    loader: LoaderFn<'a>,
+    resolve: ResolveFn<'a>,
+    max_depth: usize,
+}
+
+impl<'a, Tok: LexToken> Preprocessor<'a, Tok> {
+    /// Creates a preprocessor that lexes with rules built by `rules` and
+    /// reads included sources with `loader`.
+    ///
+    /// `loader` receives the resolved name of the source to read and the
+    /// name of the including source.
+    pub fn new(
+        sources: &'a mut SourceMap,
+        rules: impl FnMut() -> Vec<Box<dyn LexingRule<DefaultContext, Tok>>> + 'a,
+        loader: impl FnMut(&str, &str) -> io::Result<String> + 'a,
+    ) -> Self {
+        Self {
+            sources,
+            rules: Box::new(rules),
+            loader: Box::new(loader),
+            resolve: Box::new(resolve_relative),
+            max_depth: 64,
+        }
+    }
+
+    /// Replaces how an included name is resolved: `resolve` receives the
+    /// requested name and the name of the including source, and returns
+    /// the name the source is loaded and reused under, e.g. a canonical
+    /// path found through include directories.
+    pub fn with_resolver(mut self, resolve: impl FnMut(&str, &str) -> String + 'a) -> Self {
+        self.resolve = Box::new(resolve);
+        self
+    }
+
+    /// Limits how deeply includes may nest. Defaults to 64.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Lexes the source `root` and everything it includes into one stream.
+    pub fn run<H>(&mut self, root: SourceId, mut handler: H) -> Result<Vec<Tok>, PreprocessError>
+    where
+        H: PreprocessHandler<Tok>,
+    {
+        let mut tokens = Vec::new();
+        let mut stack = Vec::new();
+        self.expand(root, &mut handler, &mut stack, &mut tokens)?;
+        Ok(tokens)
+    }
+
+    fn expand<H>(
+        &mut self,
+        id: SourceId,
+        handler: &mut H,
+        stack: &mut Vec<SourceId>,
+        tokens: &mut Vec<Tok>,
+    ) -> Result<(), PreprocessError>
+    where
+        H: PreprocessHandler<Tok>,
+    {
+        stack.push(id);
+        let file = self.sources.file(id);
+        let base = file.to_global(Position::new());
+        let lexer = Lexer::from_str(file.text(), (self.rules)());
+
+        let included_file = stack.len() > 1;
+        for token in lexer {
+            if included_file && token.is_eof() {
+                continue;
+            }
+            let name = match handler.handle(token.relocated(base)) {
+                Directive::Keep(token) => {
+                    tokens.push(token);
+                    continue;
+                }
+                Directive::Drop => continue,
+                Directive::Include(name) => name,
+            };
+
+            let from = self.sources.file(id).name().to_string();
+            let name = (self.resolve)(&name, &from);
+            let included = match self.sources.find(&name) {
+                Some(included) => included,
+                None => match (self.loader)(&name, &from) {
+                    Ok(text) => self.sources.add(name.as_str(), text),
+                    Err(error) => return Err(PreprocessError::NotFound { name, from, error }),
+                },
+            };
+            if let Some(start) = stack.iter().position(|&open| open == included) {
+                let mut cycle: Vec<String> = stack[start..]
+                    .iter()
+                    .map(|&open| self.sources.file(open).name().to_string())
+                    .collect();
+                cycle.push(name);
+                return Err(PreprocessError::Cycle(cycle));
+            }
+            if stack.len() >= self.max_depth {
+                return Err(PreprocessError::TooDeep {
+                    name,
+                    max_depth: self.max_depth,
+                });
+            }
+            self.expand(included, handler, stack, tokens)?;
+        }
+
+        stack.pop();
+        Ok(())
+    }
+}
+
+/// Resolves `name` relative to the directory of `from`, unless it is
+/// absolute, and removes `.` and `..` components where it can.
+fn resolve_relative(name: &str, from: &str) -> String {
+    let dir = Path::new(from).parent().unwrap_or(Path::new(""));
+    let mut resolved = PathBuf::new();
+    for component in dir.join(name).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    resolved.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                resolved.pop();
+            }
+            component => resolved.push(component),
+        }
+    }
+    resolved.to_string_lossy().into_owned()
+}
+
+/// Why a [`Preprocessor`] run failed.
+#[derive(Debug)]
+pub enum PreprocessError {
+    /// The loader could not read the source `name` included from `from`.
+    NotFound {
+        name: String,
+        from: String,
+        error: io::Error,
+    },
+    /// Sources include each other in a cycle; lists the cycle with its
+    /// first source repeated at the end.
+    Cycle(Vec<String>),
+    /// Including `name` would nest deeper than the configured limit.
+    TooDeep { name: String, max_depth: usize },
+}
+
+impl fmt::Display for PreprocessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PreprocessError::NotFound { name, from, error } => {
+                write!(f, "cannot include `{}` from `{}`: {}", name, from, error)
+            }
+            PreprocessError::Cycle(cycle) => write!(f, "include cycle: {}", cycle.join(" -> ")),
+            PreprocessError::TooDeep { name, max_depth } => write!(
+                f,
+                "including `{}` exceeds the maximum depth of {}",
+                name, max_depth
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PreprocessError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PreprocessError::NotFound { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
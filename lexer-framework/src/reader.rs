@@ -0,0 +1,194 @@
+use crate::context::LexContext;
+use crate::cursor::Cursor;
+use crate::lexer::Lexer;
+use crate::streaming::StreamingLexContext;
+use crate::traits::LexingRule;
+use common_framework::{Checkpoint, Position, StateMap, TextSlice};
+use std::io::{self, Read};
+
+/// Default number of bytes read from the source per refill.
+pub const DEFAULT_CHUNK_SIZE: usize = 8 * 1024;
+
+/// A lex context that pulls its input from an [`io::Read`] source in chunks.
+///
+/// Input is only read when the lexer runs out of buffered characters, and
+/// consumed input is discarded before each read, so memory use stays around
+/// one chunk plus the longest token regardless of the source size.
+pub struct ReaderLexContext<R> {
+    inner: StreamingLexContext,
+    reader: R,
+    chunk_size: usize,
+    /// Trailing bytes of an incomplete UTF-8 sequence from the last read.
+    pending: Vec<u8>,
+    peak_buffered: usize,
+    error: Option<io::Error>,
+}
+
+impl<R: Read> ReaderLexContext<R> {
+    /// Creates a context reading [`DEFAULT_CHUNK_SIZE`] bytes at a time.
+    pub fn new(reader: R) -> Self {
+        Self::with_chunk_size(reader, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Creates a context reading at most `chunk_size` bytes per refill.
+    pub fn with_chunk_size(reader: R, chunk_size: usize) -> Self {
+        Self {
+            inner: StreamingLexContext::new(),
+            reader,
+            chunk_size: chunk_size.max(1),
+            pending: Vec::new(),
+            peak_buffered: 0,
+            error: None,
+        }
+    }
+
+    /// Returns the largest number of bytes the buffer has held so far.
+    pub fn peak_buffered(&self) -> usize {
+        self.peak_buffered
+    }
+
+    /// Takes the I/O error that ended the input early, if any.
+    ///
+    /// A read error is treated as end of input so lexing can finish cleanly;
+    /// callers should check this afterwards.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    /// Appends the decodable prefix of `pending` to the buffer.
+    fn decode_pending(&mut self) {
+        let mut text = String::new();
+        decode_utf8(&mut self.pending, &mut text);
+        self.inner.push_str(&text);
+    }
+}
+
+/// Moves the decodable prefix of `pending` to `out`. Invalid sequences
+/// become U+FFFD; an incomplete trailing sequence is kept in `pending`.
+pub(crate) fn decode_utf8(pending: &mut Vec<u8>, out: &mut String) {
+    loop {
+        match std::str::from_utf8(pending) {
+            Ok(text) => {
+                out.push_str(text);
+                pending.clear();
+                return;
+            }
+            Err(err) => {
+                let valid = err.valid_up_to();
+                // Cannot fail: the first `valid` bytes were just checked.
+                out.push_str(std::str::from_utf8(&pending[..valid]).unwrap_or_default());
+                match err.error_len() {
+                    Some(len) => {
+                        out.push(char::REPLACEMENT_CHARACTER);
+                        pending.drain(..valid + len);
+                    }
+                    None => {
+                        pending.drain(..valid);
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<R: Read> LexContext for ReaderLexContext<R> {
+    fn cursor(&self) -> &Cursor {
+        self.inner.cursor()
+    }
+
+    fn cursor_mut(&mut self) -> &mut Cursor {
+        self.inner.cursor_mut()
+    }
+
+    fn state_map(&mut self) -> Option<&mut StateMap> {
+        self.inner.state_map()
+    }
+
+    fn slice_from(&mut self, start: usize) -> TextSlice {
+        self.inner.slice_from(start)
+    }
+
+    fn position(&self) -> Position {
+        self.inner.position()
+    }
+
+    fn is_eof(&self) -> bool {
+        self.inner.is_eof()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.inner.peek()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        self.inner.advance()
+    }
+
+    fn consume_while<F>(&mut self, predicate: F) -> TextSlice
+    where
+        F: FnMut(char) -> bool,
+    {
+        self.inner.consume_while(predicate)
+    }
+
+    fn checkpoint(&self) -> Checkpoint {
+        self.inner.checkpoint()
+    }
+
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        self.inner.restore(checkpoint);
+    }
+
+    fn offset(&self) -> usize {
+        self.inner.offset()
+    }
+
+    fn is_input_finished(&self) -> bool {
+        self.inner.is_finished()
+    }
+
+    fn refill(&mut self) -> bool {
+        if self.inner.is_finished() {
+            return false;
+        }
+        self.inner.discard_consumed();
+
+        let mut chunk = vec![0; self.chunk_size];
+        let read = loop {
+            match self.reader.read(&mut chunk) {
+                Ok(read) => break read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    self.error = Some(err);
+                    break 0;
+                }
+            }
+        };
+
+        if read == 0 {
+            if !self.pending.is_empty() {
+                // The source ended inside a multi-byte sequence.
+                self.pending.clear();
+                self.inner.push_char(char::REPLACEMENT_CHARACTER);
+            }
+            self.inner.mark_finished();
+        } else {
+            self.pending.extend_from_slice(&chunk[..read]);
+            self.decode_pending();
+        }
+
+        self.peak_buffered = self.peak_buffered.max(self.inner.buffered_len());
+        true
+    }
+}
+
+impl<R: Read, Tok> Lexer<ReaderLexContext<R>, Tok> {
+    /// Creates a lexer that reads its input incrementally from `reader`.
+    pub fn from_reader(
+        reader: R,
+        rules: Vec<Box<dyn LexingRule<ReaderLexContext<R>, Tok>>>,
+    ) -> Self {
+        Self::new(ReaderLexContext::new(reader), rules)
+    }
+}
@@ -0,0 +1,343 @@
+use crate::context::LexContext;
+use crate::lexer::Lexer;
+use crate::traits::LexToken;
+use common_framework::Position;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// What a [`TokenRewrite`] rule does with the tokens at the front of the
+/// stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rewrite<Tok> {
+    /// Passes the first token on unchanged.
+    Keep,
+    /// Removes the first `n` tokens, e.g. a directive or a disabled
+    /// `#ifdef` branch.
+    Drop(usize),
+    /// Replaces the first `consumed` tokens with `tokens`. The replacement
+    /// is rewritten again, so it may itself contain macro uses.
+    Replace { consumed: usize, tokens: Vec<Tok> },
+}
+
+/// A rule of a [`TokenRewriter`]: looks at the front of the stream, as far
+/// ahead as it needs, and decides how to rewrite it.
+///
+/// Implemented for closures `FnMut(&mut Lookahead<Tok>) -> Rewrite<Tok>`.
+pub trait TokenRewrite<Tok> {
+    fn rewrite(&mut self, input: &mut Lookahead<'_, Tok>) -> Rewrite<Tok>;
+}
+
+impl<Tok, F> TokenRewrite<Tok> for F
+where
+    F: FnMut(&mut Lookahead<'_, Tok>) -> Rewrite<Tok>,
+{
+    fn rewrite(&mut self, input: &mut Lookahead<'_, Tok>) -> Rewrite<Tok> {
+        self(input)
+    }
+}
+
+/// A token waiting in a [`TokenRewriter`], with the number of replacements
+/// that produced it.
+struct Pending<Tok> {
+    token: Tok,
+    depth: usize,
+}
+
+/// The front of a [`TokenRewriter`]'s stream, pulling further tokens from
+/// the wrapped producer on demand.
+pub struct Lookahead<'a, Tok> {
+    buffer: &'a mut VecDeque<Pending<Tok>>,
+    pull: &'a mut dyn FnMut() -> Option<Tok>,
+}
+
+impl<Tok> Lookahead<'_, Tok> {
+    /// Returns the first token, the one a [`Rewrite`] applies to.
+    pub fn first(&self) -> &Tok {
+        &self.buffer[0].token
+    }
+
+    /// Returns the token `n` places after the first, or `None` past the end
+    /// of input.
+    pub fn peek(&mut self, n: usize) -> Option<&Tok> {
+        while self.buffer.len() <= n {
+            let token = (self.pull)()?;
+            self.buffer.push_back(Pending { token, depth: 0 });
+        }
+        Some(&self.buffer[n].token)
+    }
+
+    /// Returns how many replacements produced the first token; `0` for
+    /// tokens straight from the input.
+    pub fn depth(&self) -> usize {
+        self.buffer[0].depth
+    }
+}
+
+impl<Tok: LexToken> Lookahead<'_, Tok> {
+    /// Returns the position of the first token. Replacement tokens usually
+    /// take this position, so diagnostics point at the macro use.
+    pub fn position(&self) -> Option<Position> {
+        self.first().position()
+    }
+}
+
+/// A token producer that rewrites its input with a [`TokenRewrite`] rule:
+/// macro expansion, conditional compilation and other define/ifdef-style
+/// transformations that replace runs of tokens.
+///
+/// Created by [`Lexer::rewrite_tokens`]. Tokens the rule keeps are passed on
+/// untouched, positions included. Replacements are rewritten again, up to
+/// [`max_depth`](Self::with_max_depth) nested replacements; a self-referential
+/// macro that exceeds it ends the stream, with the error available from
+/// [`take_error`](Self::take_error).
+///
+/// With the `streaming` feature it is also a `TokenProducer`, `Outbound` and
+/// `Inbound`, so it can stand in for the lexer of a `StreamingPipeline`.
+/// Lookahead there stops at the tokens available so far, so rules should not
+/// need to look past the end of a chunk of input.
+///
+/// ```ignore
+/// let tokens: Vec<_> = Lexer::from_str(source, rules())
+///     .rewrite_tokens(|input: &mut Lookahead<Tok>| match input.first() {
+///         Tok::Ident(name) if name == "PI" => Rewrite::Replace {
+///             consumed: 1,
+///             tokens: vec![Tok::Number(3.14, input.position())],
+///         },
+///         _ => Rewrite::Keep,
+///     })
+///     .collect();
+/// ```
+pub struct TokenRewriter<L, Tok, R> {
+    inner: L,
+    state: RewriteState<Tok, R>,
+}
+
+/// Everything of a [`TokenRewriter`] but the wrapped producer, so the
+/// producer can be borrowed separately while rewriting.
+struct RewriteState<Tok, R> {
+    rule: R,
+    pending: VecDeque<Pending<Tok>>,
+    max_depth: usize,
+    error: Option<RewriteError>,
+}
+
+impl<L, Tok, R> TokenRewriter<L, Tok, R> {
+    /// Wraps `inner`, rewriting its tokens with `rule`.
+    pub fn new(inner: L, rule: R) -> Self {
+        Self {
+            inner,
+            state: RewriteState {
+                rule,
+                pending: VecDeque::new(),
+                max_depth: 64,
+                error: None,
+            },
+        }
+    }
+
+    /// Limits how deeply replacements may nest. Defaults to 64.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.state.max_depth = max_depth;
+        self
+    }
+
+    /// Returns the wrapped producer.
+    pub fn inner(&self) -> &L {
+        &self.inner
+    }
+
+    /// Returns the rule, e.g. to read the macros it has collected.
+    pub fn rule(&self) -> &R {
+        &self.state.rule
+    }
+
+    /// Takes the error that ended the stream early, if any.
+    pub fn take_error(&mut self) -> Option<RewriteError> {
+        self.state.error.take()
+    }
+
+    /// Unwraps the adapter.
+    pub fn into_inner(self) -> L {
+        self.inner
+    }
+}
+
+impl<Tok, R> RewriteState<Tok, R>
+where
+    Tok: LexToken,
+    R: TokenRewrite<Tok>,
+{
+    /// Applies the rule until a token is kept; `pull` reads the wrapped
+    /// producer.
+    fn next_with(&mut self, pull: &mut dyn FnMut() -> Option<Tok>) -> Option<Tok> {
+        loop {
+            if self.error.is_some() {
+                return None;
+            }
+            if self.pending.is_empty() {
+                let token = pull()?;
+                self.pending.push_back(Pending { token, depth: 0 });
+            }
+            let mut input = Lookahead {
+                buffer: &mut self.pending,
+                pull: &mut *pull,
+            };
+            match self.rule.rewrite(&mut input) {
+                Rewrite::Keep => return self.pending.pop_front().map(|pending| pending.token),
+                Rewrite::Drop(n) => {
+                    assert!(n > 0, "Rewrite::Drop must remove at least one token");
+                    self.consume(n, pull);
+                }
+                Rewrite::Replace { consumed, tokens } => {
+                    assert!(
+                        consumed > 0,
+                        "Rewrite::Replace must consume at least one token"
+                    );
+                    let position = self.pending[0].token.position();
+                    let depth = self.consume(consumed, pull) + 1;
+                    if depth > self.max_depth {
+                        self.error = Some(RewriteError {
+                            position,
+                            max_depth: self.max_depth,
+                        });
+                        return None;
+                    }
+                    for token in tokens.into_iter().rev() {
+                        self.pending.push_front(Pending { token, depth });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Removes up to `n` tokens from the front, pulling them first if
+    /// needed, and returns the largest depth among them.
+    fn consume(&mut self, n: usize, pull: &mut dyn FnMut() -> Option<Tok>) -> usize {
+        while self.pending.len() < n {
+            match pull() {
+                Some(token) => self.pending.push_back(Pending { token, depth: 0 }),
+                None => break,
+            }
+        }
+        let n = n.min(self.pending.len());
+        self.pending
+            .drain(..n)
+            .map(|pending| pending.depth)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl<L, Tok, R> Iterator for TokenRewriter<L, Tok, R>
+where
+    L: Iterator<Item = Tok>,
+    Tok: LexToken,
+    R: TokenRewrite<Tok>,
+{
+    type Item = Tok;
+
+    fn next(&mut self) -> Option<Tok> {
+        self.state.next_with(&mut || self.inner.next())
+    }
+}
+
+/// Rewriting stopped because replacements nested deeper than the limit,
+/// typically a macro that expands to itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewriteError {
+    /// Position of the first token of the replacement that hit the limit.
+    pub position: Option<Position>,
+    pub max_depth: usize,
+}
+
+impl fmt::Display for RewriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "token rewriting exceeds the maximum depth of {}",
+            self.max_depth
+        )?;
+        if let Some(position) = self.position {
+            write!(f, " at {}:{}", position.line, position.column)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for RewriteError {}
+
+impl<Ctx, Tok> Lexer<Ctx, Tok>
+where
+    Ctx: LexContext,
+{
+    /// Rewrites the token stream with `rule`, e.g. to expand macros or
+    /// drop disabled conditional blocks.
+    pub fn rewrite_tokens<R>(self, rule: R) -> TokenRewriter<Self, Tok, R>
+    where
+        Tok: LexToken,
+        R: TokenRewrite<Tok>,
+    {
+        TokenRewriter::new(self, rule)
+    }
+}
+
+#[cfg(feature = "streaming")]
+mod streaming_impls {
+    use super::{Pending, TokenRewrite, TokenRewriter};
+    use crate::streaming::TokenProducer;
+    use crate::traits::LexToken;
+    use common_framework::{Inbound, Outbound, Position, StreamingSignal};
+
+    impl<L, Tok, R> TokenProducer<Tok> for TokenRewriter<L, Tok, R>
+    where
+        L: TokenProducer<Tok>,
+        Tok: LexToken,
+        R: TokenRewrite<Tok>,
+    {
+        fn poll_token(&mut self) -> Option<Tok> {
+            self.state.next_with(&mut || self.inner.poll_token())
+        }
+
+        fn input_position(&self) -> Option<Position> {
+            self.inner.input_position()
+        }
+    }
+
+    impl<L, Tok, R, Ast> Outbound<Tok, Ast> for TokenRewriter<L, Tok, R>
+    where
+        L: Outbound<Tok, Ast> + TokenProducer<Tok>,
+        Tok: LexToken,
+        R: TokenRewrite<Tok>,
+    {
+        fn next_signal(&mut self) -> Option<StreamingSignal<Tok, Ast>> {
+            if let Some(token) = self.poll_token() {
+                return Some(StreamingSignal::SupplyToken(token));
+            }
+            if let Some(error) = &self.state.error {
+                return Some(StreamingSignal::Abort(error.to_string()));
+            }
+            match self.inner.next_signal()? {
+                StreamingSignal::SupplyToken(token) => {
+                    self.state.pending.push_back(Pending { token, depth: 0 });
+                    self.poll_token().map(StreamingSignal::SupplyToken)
+                }
+                signal => Some(signal),
+            }
+        }
+    }
+
+    impl<L, Tok, R, Ast> Inbound<Tok, Ast> for TokenRewriter<L, Tok, R>
+    where
+        L: Inbound<Tok, Ast>,
+    {
+        fn handle_signal(&mut self, signal: StreamingSignal<Tok, Ast>) {
+            // Rewrites work token by token, so batches are not passed down.
+            match signal {
+                StreamingSignal::RequestTokens(n) => {
+                    self.inner.handle_signal(StreamingSignal::RequestToken(n))
+                }
+                signal => self.inner.handle_signal(signal),
+            }
+        }
+    }
+}
@@ -1,7 +1,11 @@
+use crate::chain::ChainedProducer;
 use crate::context::LexContext;
 use crate::cursor::Cursor;
 use crate::lexer::Lexer;
-use common_framework::{Checkpoint, Inbound, Outbound, Position, StreamingSignal};
+use common_framework::{
+    Checkpoint, CheckpointPins, ContextId, Diagnostic, Inbound, Outbound, PinnedCheckpoint,
+    Position, Progress, StateMap, StreamingSignal, TextSlice,
+};
 use std::sync::Arc;
 
 /// Streaming-friendly lex context that can be fed characters incrementally.
@@ -9,11 +13,16 @@ use std::sync::Arc;
 pub struct StreamingLexContext {
     buffer: String,
     current: usize,
+    /// Absolute offset of `buffer[0]`; grows as consumed input is discarded.
+    base_offset: usize,
     finished: bool,
     position: Position,
     buffer_version: u64,
     cached_version: u64,
     cached_arc: Option<Arc<str>>,
+    state: StateMap,
+    pins: CheckpointPins,
+    id: ContextId,
 }
 
 impl StreamingLexContext {
@@ -22,11 +31,15 @@ impl StreamingLexContext {
         Self {
             buffer: String::new(),
             current: 0,
+            base_offset: 0,
             finished: false,
             position: Position::default(),
             buffer_version: 0,
             cached_version: 0,
             cached_arc: None,
+            state: StateMap::new(),
+            pins: CheckpointPins::new(),
+            id: ContextId::fresh(),
         }
     }
 
@@ -49,6 +62,42 @@ impl StreamingLexContext {
         self.finished = true;
     }
 
+    /// Returns true once `mark_finished` has been called.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Returns the number of bytes currently held in the buffer.
+    pub fn buffered_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Drops input that has already been consumed, so long-running streams
+    /// only keep the unconsumed tail in memory. Offsets and positions stay
+    /// absolute; checkpoints taken before the discarded point can no longer
+    /// be restored, except [pinned](Self::pin_checkpoint) ones, whose input
+    /// is kept.
+    pub fn discard_consumed(&mut self) {
+        let mut keep_from = self.base_offset + self.current;
+        if let Some(floor) = self.pins.floor() {
+            keep_from = keep_from.min(floor);
+        }
+        if keep_from <= self.base_offset {
+            return;
+        }
+        let count = keep_from - self.base_offset;
+        self.buffer.drain(..count);
+        self.base_offset = keep_from;
+        self.current -= count;
+        self.buffer_version += 1;
+    }
+
+    /// Returns a checkpoint whose input [`discard_consumed`](Self::discard_consumed)
+    /// keeps until it is dropped.
+    pub fn pin_checkpoint(&self) -> PinnedCheckpoint {
+        self.pins.pin(self.checkpoint())
+    }
+
     fn shared_buffer(&mut self) -> Arc<str> {
         if self.cached_version != self.buffer_version {
             let arc = Arc::<str>::from(self.buffer.as_str());
@@ -77,11 +126,15 @@ impl From<String> for StreamingLexContext {
         Self {
             buffer: value,
             current: 0,
+            base_offset: 0,
             finished: true,
             position: Position::default(),
             buffer_version: 1,
             cached_version: 0,
             cached_arc: None,
+            state: StateMap::new(),
+            pins: CheckpointPins::new(),
+            id: ContextId::fresh(),
         }
     }
 }
@@ -91,11 +144,15 @@ impl From<&str> for StreamingLexContext {
         Self {
             buffer: value.to_string(),
             current: 0,
+            base_offset: 0,
             finished: true,
             position: Position::default(),
             buffer_version: 1,
             cached_version: 0,
             cached_arc: None,
+            state: StateMap::new(),
+            pins: CheckpointPins::new(),
+            id: ContextId::fresh(),
         }
     }
 }
@@ -113,6 +170,10 @@ impl LexContext for StreamingLexContext {
         panic!("StreamingLexContext::cursor_mut() should not be called directly. Use the LexContext trait methods instead.");
     }
 
+    fn state_map(&mut self) -> Option<&mut StateMap> {
+        Some(&mut self.state)
+    }
+
     fn peek(&self) -> Option<char> {
         if self.current >= self.buffer.len() {
             return None;
@@ -131,7 +192,7 @@ impl LexContext for StreamingLexContext {
         } else {
             self.position.column += 1;
         }
-        self.position.offset = self.current;
+        self.position.offset = self.base_offset + self.current;
         Some(ch)
     }
 
@@ -160,16 +221,43 @@ impl LexContext for StreamingLexContext {
     }
 
     fn checkpoint(&self) -> Checkpoint {
-        Checkpoint::new(self.current, self.position)
+        Checkpoint::new(self.base_offset + self.current, self.position).with_context(self.id)
     }
 
     fn restore(&mut self, checkpoint: Checkpoint) {
-        self.current = checkpoint.current();
+        checkpoint.debug_assert_context(self.id);
+        assert!(
+            checkpoint.current() >= self.base_offset,
+            "StreamingLexContext: checkpoint refers to discarded input"
+        );
+        self.current = checkpoint.current() - self.base_offset;
         self.position = checkpoint.position();
     }
 
     fn offset(&self) -> usize {
-        self.current
+        self.base_offset + self.current
+    }
+
+    fn push_input(&mut self, text: &str) {
+        self.push_str(text);
+    }
+
+    fn finish_input(&mut self) {
+        self.mark_finished();
+    }
+
+    fn is_input_finished(&self) -> bool {
+        self.finished
+    }
+
+    fn slice_from(&mut self, start: usize) -> TextSlice {
+        assert!(
+            start >= self.base_offset,
+            "StreamingLexContext: slice start refers to discarded input"
+        );
+        let start = start - self.base_offset;
+        debug_assert!(start <= self.current, "slice start is ahead of the cursor");
+        TextSlice::new(self.shared_buffer(), start, self.current)
     }
 }
 
@@ -178,6 +266,21 @@ impl LexContext for StreamingLexContext {
 pub trait TokenProducer<Tok> {
     /// Attempts to emit the next token from the underlying producer.
     fn poll_token(&mut self) -> Option<Tok>;
+
+    /// Returns the producer's current position in its input, if known.
+    fn input_position(&self) -> Option<Position> {
+        None
+    }
+
+    /// Yields the tokens of `self`, then those of `other`, as one stream.
+    /// See [`ChainedProducer`].
+    fn chain_producer<P>(self, other: P) -> ChainedProducer<Self, P>
+    where
+        Self: Sized,
+        P: TokenProducer<Tok>,
+    {
+        ChainedProducer::new(self, other)
+    }
 }
 
 impl<Ctx, Tok> TokenProducer<Tok> for Lexer<Ctx, Tok>
@@ -187,17 +290,84 @@ where
     fn poll_token(&mut self) -> Option<Tok> {
         self.next()
     }
+
+    fn input_position(&self) -> Option<Position> {
+        Some(self.context().position())
+    }
+}
+
+impl<Ctx, Tok> Lexer<Ctx, Tok>
+where
+    Ctx: LexContext,
+{
+    /// Makes [`next_signal`](Outbound::next_signal) emit a
+    /// [`Progress`](StreamingSignal::Progress) signal after every `tokens`
+    /// tokens, with the bytes consumed and tokens lexed so far.
+    pub fn report_progress_every(&mut self, tokens: usize) -> &mut Self {
+        self.progress_interval = Some(tokens.max(1));
+        self
+    }
+
+    /// Returns true if the buffered input is used up, the input has not
+    /// ended, and the context has no source of its own to refill from.
+    fn needs_input(&mut self) -> bool {
+        while !self.context().is_eof() && self.context().peek().is_none() {
+            if !self.context_mut().refill() {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Returns the progress to report, if another interval has passed.
+    fn due_progress(&mut self) -> Option<Progress> {
+        let interval = self.progress_interval?;
+        if self.tokens_lexed() < self.progress_reported + interval {
+            return None;
+        }
+        self.progress_reported = self.tokens_lexed();
+        Some(Progress {
+            bytes_consumed: self.context().offset(),
+            tokens_emitted: self.tokens_lexed(),
+            nodes_emitted: 0,
+        })
+    }
 }
 
 impl<Ctx, Tok, Ast> Outbound<Tok, Ast> for Lexer<Ctx, Tok>
 where
     Ctx: LexContext,
 {
+    /// Answers a pending [`RequestTokens`](StreamingSignal::RequestTokens)
+    /// with one `SupplyTokens` batch, and otherwise supplies single tokens.
+    /// Due progress reports go out first.
+    ///
+    /// Once the buffered input is used up but not finished, or the next
+    /// token may continue past it, and the context cannot
+    /// [`refill`](LexContext::refill) itself, this signals
+    /// [`NeedInput`](StreamingSignal::NeedInput) until the controller sends
+    /// [`SupplyInput`](StreamingSignal::SupplyInput) or
+    /// [`EndOfInput`](StreamingSignal::EndOfInput).
     fn next_signal(&mut self) -> Option<StreamingSignal<Tok, Ast>> {
-        if let Some(token) = self.poll_token() {
+        if let Some(progress) = self.due_progress() {
+            return Some(StreamingSignal::Progress(progress));
+        }
+        if self.needs_input() {
+            return Some(StreamingSignal::NeedInput);
+        }
+        if let Some(n) = self.requested_batch.take() {
+            let batch = self.next_chunk(n);
+            if !batch.is_empty() {
+                return Some(StreamingSignal::SupplyTokens(batch.into_vec()));
+            }
+        } else if let Some(token) = self.poll_token() {
             return Some(StreamingSignal::SupplyToken(token));
         }
 
+        if self.is_suspended() {
+            return Some(StreamingSignal::NeedInput);
+        }
+
         if self.context().is_eof() {
             return Some(StreamingSignal::EndOfInput);
         }
@@ -211,8 +381,19 @@ where
     Ctx: LexContext,
 {
     fn handle_signal(&mut self, signal: StreamingSignal<Tok, Ast>) {
-        if let StreamingSignal::Abort(reason) = signal {
-            eprintln!("Lexer received abort: {}", reason);
+        match signal {
+            StreamingSignal::RequestTokens(n) => self.requested_batch = Some(n.max(1)),
+            StreamingSignal::Abort(reason) => {
+                let position = self.context().position();
+                self.context_mut().report(Diagnostic::error(
+                    (position, position),
+                    format!("lexer received abort: {}", reason),
+                ));
+            }
+            StreamingSignal::Feedback(feedback) => self.context_mut().receive_feedback(feedback),
+            StreamingSignal::SupplyInput(text) => self.context_mut().push_input(&text),
+            StreamingSignal::EndOfInput => self.context_mut().finish_input(),
+            _ => {}
         }
     }
 }
@@ -0,0 +1,95 @@
+use crate::context::{DefaultContext, LexContext};
+use crate::lexer::Lexer;
+use crate::traits::LexingRule;
+
+/// Runs `rule` once on a fresh context over `input`, returning the token
+/// and the number of bytes it consumed, or `None` if it did not match.
+///
+/// The rule is tried even if its `quick_check` rejects the first
+/// character, so a test sees what `try_match` does on its own:
+///
+/// ```ignore
+/// assert_eq!(lex_single(&mut NumberRule, "42 + 1"), Some((Token::Number(42), 2)));
+/// ```
+pub fn lex_single<Tok, R>(rule: &mut R, input: &str) -> Option<(Tok, usize)>
+where
+    R: LexingRule<DefaultContext, Tok> + ?Sized,
+{
+    let mut ctx = DefaultContext::new(input);
+    let token = rule.try_match(&mut ctx)?;
+    Some((token, ctx.offset()))
+}
+
+/// Lexes `input` with `rules`, returning the tokens and the number of bytes
+/// consumed before the lexer stopped. Used by [`assert_lexes!`](crate::assert_lexes).
+pub fn lex_all<Tok>(
+    rules: Vec<Box<dyn LexingRule<DefaultContext, Tok>>>,
+    input: &str,
+) -> (Vec<Tok>, usize) {
+    let mut lexer = Lexer::from_str(input, rules);
+    let tokens: Vec<Tok> = lexer.by_ref().collect();
+    (tokens, lexer.context().offset())
+}
+
+/// Asserts that lexing an input consumes all of it and yields tokens
+/// matching the given patterns, in order.
+///
+/// The rules are a single rule, a bracketed list of rules, or `rules = expr`
+/// for a ready-made `Vec<Box<dyn LexingRule<DefaultContext, _>>>`. Patterns
+/// are ordinary Rust patterns, so fields can be ignored with `_`:
+///
+/// ```ignore
+/// assert_lexes!(NumberRule, "42", [Token::Number(42)]);
+/// assert_lexes!([NumberRule, PlusRule], "1+2", [Token::Number(1), Token::Plus, Token::Number(_)]);
+/// assert_lexes!(rules = build_lexer_rules(), "x = 1", [Token::Ident(_), Token::Eq, Token::Number(1)]);
+/// ```
+///
+/// On failure the message shows the tokens produced and where lexing
+/// stopped.
+#[macro_export]
+macro_rules! assert_lexes {
+    (rules = $rules:expr, $input:expr, [$($pattern:pat),* $(,)?]) => {{
+        let input: &str = $input;
+        let (tokens, consumed) = $crate::testing::lex_all($rules, input);
+        let patterns: &[&str] = &[$(stringify!($pattern)),*];
+        assert!(
+            consumed == input.len(),
+            "lexing {:?} stopped at offset {} of {}; tokens so far: {:?}",
+            input,
+            consumed,
+            input.len(),
+            tokens
+        );
+        assert!(
+            tokens.len() == patterns.len(),
+            "lexing {:?} produced {} tokens, expected {}: {:?}",
+            input,
+            tokens.len(),
+            patterns.len(),
+            tokens
+        );
+        let mut remaining = tokens.iter().enumerate();
+        $(
+            let (index, token) = remaining.next().unwrap();
+            assert!(
+                matches!(token, $pattern),
+                "token {} of {:?} is {:?}, expected {}; all tokens: {:?}",
+                index,
+                input,
+                token,
+                stringify!($pattern),
+                tokens
+            );
+        )*
+    }};
+    ([$($rule:expr),+ $(,)?], $input:expr, [$($pattern:pat),* $(,)?]) => {
+        $crate::assert_lexes!(
+            rules = vec![$(::std::boxed::Box::new($rule) as ::std::boxed::Box<dyn $crate::LexingRule<$crate::DefaultContext, _>>),+],
+            $input,
+            [$($pattern),*]
+        )
+    };
+    ($rule:expr, $input:expr, [$($pattern:pat),* $(,)?]) => {
+        $crate::assert_lexes!([$rule], $input, [$($pattern),*])
+    };
+}
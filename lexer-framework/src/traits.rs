@@ -1,24 +1,70 @@
 use crate::context::LexContext;
-use common_framework::Position;
+use common_framework::{short_type_name, Channel, Position, RuleId};
+
+/// The roles a token can play that the framework itself cares about, e.g.
+/// when filtering whitespace or joining token streams.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// End of input.
+    Eof,
+    /// A line break.
+    Newline,
+    /// Insignificant whitespace.
+    Whitespace,
+    /// Significant leading whitespace, as in indentation-sensitive languages.
+    Indent,
+}
 
 /// A token produced by the lexer.
 /// This is part of the CGP design, allowing tokens to be generic
 /// while maintaining a consistent interface.
+///
+/// Every method has a default, so a token only implements what it has:
+/// usually [`position`](Self::position) and a [`kind`](Self::kind) that
+/// picks out its EOF and whitespace variants. The `is_*` checks are
+/// answered from `kind`.
 pub trait LexToken: Clone + std::fmt::Debug {
-    /// Returns the position of this token in the source.
-    fn position(&self) -> Option<Position>;
+    /// Returns the position of this token in the source. Defaults to `None`.
+    fn position(&self) -> Option<Position> {
+        None
+    }
+
+    /// Returns the framework-level role of this token, or `None` for an
+    /// ordinary token. Defaults to `None`.
+    fn kind(&self) -> Option<TokenKind> {
+        None
+    }
 
     /// Returns true if this token represents end-of-file.
-    fn is_eof(&self) -> bool;
+    fn is_eof(&self) -> bool {
+        self.kind() == Some(TokenKind::Eof)
+    }
 
     /// Returns true if this token represents a newline.
-    fn is_newline(&self) -> bool;
+    fn is_newline(&self) -> bool {
+        self.kind() == Some(TokenKind::Newline)
+    }
 
     /// Returns true if this token represents whitespace.
-    fn is_whitespace(&self) -> bool;
+    fn is_whitespace(&self) -> bool {
+        self.kind() == Some(TokenKind::Whitespace)
+    }
 
     /// Returns true if this token represents indentation.
-    fn is_indent(&self) -> bool;
+    fn is_indent(&self) -> bool {
+        self.kind() == Some(TokenKind::Indent)
+    }
+
+    /// Returns this token with its position rebased onto `base` (see
+    /// [`Position::rebase`]).
+    ///
+    /// `ChainedProducer` (with the `streaming` feature) calls this on tokens
+    /// of later parts so positions refer to the combined input. The default
+    /// returns the token unchanged.
+    fn relocated(self, base: Position) -> Self {
+        let _ = base;
+        self
+    }
 }
 
 /// A lexing rule that operates on a context.
@@ -54,4 +100,26 @@ where
         let _ = first_char; // Suppress unused parameter warning
         None
     }
+
+    /// Returns the channel this rule's tokens go to. Parsers fed by
+    /// [`Lexer::tokenize_default`](crate::Lexer::tokenize_default) only
+    /// see [`Channel::DEFAULT`]; the others stay queryable through
+    /// [`Lexer::tokenize_channels`](crate::Lexer::tokenize_channels).
+    ///
+    /// Defaults to [`Channel::DEFAULT`].
+    fn channel(&self) -> Channel {
+        Channel::DEFAULT
+    }
+
+    /// Returns a human-readable name used in traces and diagnostics.
+    ///
+    /// Defaults to the rule's type name without module path or generics.
+    fn name(&self) -> &str {
+        short_type_name::<Self>()
+    }
+
+    /// Returns a stable identifier for this rule, derived from its name.
+    fn id(&self) -> RuleId {
+        RuleId::of(self.name())
+    }
 }
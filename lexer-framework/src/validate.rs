@@ -0,0 +1,235 @@
+use crate::context::LexContext;
+use crate::cursor::Cursor;
+use common_framework::{Checkpoint, Feedback, Position, StateMap, TextSlice};
+use std::fmt;
+
+/// A way a lexing rule broke the rule contract, found by a
+/// [`ValidatingContext`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// The rule returned a token without consuming anything, which would
+    /// stall the lexer.
+    NoProgress,
+    /// The rule consumed `consumed` bytes and then returned `None`. The
+    /// lexer rolls this back, but rules called directly by other rules
+    /// leave the input half-consumed.
+    ConsumedAndFailed { consumed: usize },
+    /// Restoring the checkpoint taken before a failed rule did not bring
+    /// the context back to where the rule started.
+    RestoreMismatch,
+}
+
+/// A contract violation by one rule at one position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleViolation {
+    /// Name of the rule, or `"?"` if the context was not told.
+    pub rule: String,
+    pub kind: ViolationKind,
+    /// Where the rule started.
+    pub position: Position,
+}
+
+impl fmt::Display for RuleViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` at {}:{}: ",
+            self.rule, self.position.line, self.position.column
+        )?;
+        match self.kind {
+            ViolationKind::NoProgress => f.write_str("returned a token without consuming input"),
+            ViolationKind::ConsumedAndFailed { consumed } => {
+                write!(f, "consumed {} bytes and then failed", consumed)
+            }
+            ViolationKind::RestoreMismatch => {
+                f.write_str("restoring after the failed match did not return to the start")
+            }
+        }
+    }
+}
+
+/// Wraps a [`LexContext`] to check every rule the lexer tries: rules must
+/// either consume input and return a token, or consume nothing and return
+/// `None`, and restoring after a failure must return to the start.
+///
+/// Meant for tests over user rule sets. Violations are recorded rather
+/// than fixed, so lexing continues as it would with the inner context:
+///
+/// ```ignore
+/// let mut lexer = Lexer::new(ValidatingContext::new(DefaultContext::new(input)), rules());
+/// let tokens: Vec<_> = lexer.by_ref().collect();
+/// lexer.context().assert_valid();
+/// ```
+///
+/// Checks hook into [`speculate`](LexContext::speculate), so the validating
+/// context must be the outermost one, e.g.
+/// `ValidatingContext<ModalContext<DefaultContext, Mode>>`.
+pub struct ValidatingContext<Ctx> {
+    inner: Ctx,
+    rule: Option<String>,
+    violations: Vec<RuleViolation>,
+    // Nesting of `speculate` calls; only the lexer's own, outermost, call
+    // is checked, since rules may roll back their lookahead with it.
+    depth: usize,
+}
+
+impl<Ctx> ValidatingContext<Ctx>
+where
+    Ctx: LexContext,
+{
+    /// Wraps `inner`.
+    pub fn new(inner: Ctx) -> Self {
+        Self {
+            inner,
+            rule: None,
+            violations: Vec::new(),
+            depth: 0,
+        }
+    }
+
+    /// Returns the wrapped context.
+    pub fn inner(&self) -> &Ctx {
+        &self.inner
+    }
+
+    /// Returns the violations found so far, in the order they happened.
+    pub fn violations(&self) -> &[RuleViolation] {
+        &self.violations
+    }
+
+    /// Panics with every violation found so far, if there are any.
+    #[track_caller]
+    pub fn assert_valid(&self) {
+        if !self.violations.is_empty() {
+            let list: Vec<String> = self.violations.iter().map(|v| v.to_string()).collect();
+            panic!("lexing rules broke their contract:\n{}", list.join("\n"));
+        }
+    }
+
+    fn violation(&mut self, kind: ViolationKind, position: Position) {
+        self.violations.push(RuleViolation {
+            rule: self.rule.clone().unwrap_or_else(|| "?".to_string()),
+            kind,
+            position,
+        });
+    }
+}
+
+impl<Ctx> LexContext for ValidatingContext<Ctx>
+where
+    Ctx: LexContext,
+{
+    fn cursor(&self) -> &Cursor {
+        self.inner.cursor()
+    }
+
+    fn cursor_mut(&mut self) -> &mut Cursor {
+        self.inner.cursor_mut()
+    }
+
+    fn position(&self) -> Position {
+        self.inner.position()
+    }
+
+    fn is_eof(&self) -> bool {
+        self.inner.is_eof()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.inner.peek()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        self.inner.advance()
+    }
+
+    fn consume_while<F>(&mut self, predicate: F) -> TextSlice
+    where
+        F: FnMut(char) -> bool,
+    {
+        self.inner.consume_while(predicate)
+    }
+
+    fn slice_from(&mut self, start: usize) -> TextSlice {
+        self.inner.slice_from(start)
+    }
+
+    fn checkpoint(&self) -> Checkpoint {
+        self.inner.checkpoint()
+    }
+
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        self.inner.restore(checkpoint);
+    }
+
+    fn speculate<T, F>(&mut self, f: F) -> Option<T>
+    where
+        Self: Sized,
+        F: FnOnce(&mut Self) -> Option<T>,
+    {
+        let checkpoint = self.checkpoint();
+        let (start, position) = (self.offset(), self.position());
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        if self.depth > 0 {
+            if result.is_none() {
+                self.restore(checkpoint);
+            }
+            return result;
+        }
+
+        let consumed = self.offset() - start;
+        if result.is_some() {
+            if consumed == 0 {
+                self.violation(ViolationKind::NoProgress, position);
+            }
+            return result;
+        }
+        if consumed > 0 {
+            self.violation(ViolationKind::ConsumedAndFailed { consumed }, position);
+        }
+        self.restore(checkpoint);
+        if self.offset() != start || self.position() != position {
+            self.violation(ViolationKind::RestoreMismatch, position);
+        }
+        None
+    }
+
+    fn offset(&self) -> usize {
+        self.inner.offset()
+    }
+
+    fn remaining_len(&self) -> Option<usize> {
+        self.inner.remaining_len()
+    }
+
+    fn refill(&mut self) -> bool {
+        self.inner.refill()
+    }
+
+    fn push_input(&mut self, text: &str) {
+        self.inner.push_input(text);
+    }
+
+    fn finish_input(&mut self) {
+        self.inner.finish_input();
+    }
+
+    fn is_input_finished(&self) -> bool {
+        self.inner.is_input_finished()
+    }
+
+    fn state_map(&mut self) -> Option<&mut StateMap> {
+        self.inner.state_map()
+    }
+
+    fn receive_feedback(&mut self, feedback: Feedback) {
+        self.inner.receive_feedback(feedback);
+    }
+
+    fn enter_rule(&mut self, rule: &str) {
+        self.rule = Some(rule.to_string());
+        self.inner.enter_rule(rule);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::context::{DefaultContext, LexContext};
+use crate::lexer::Lexer;
+use crate::traits::{LexToken, LexingRule};
+use common_framework::Position;
+use std::fmt;
+
+/// A whitespace-delimited word and where it starts, produced by
+/// [`Lexer::words`].
+///
+/// Meant for prototypes, tests and teaching, before a language has its own
+/// token enum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordToken {
+    pub text: String,
+    pub position: Position,
+}
+
+impl WordToken {
+    /// Returns the text of the word.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+impl fmt::Display for WordToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl LexToken for WordToken {
+    fn position(&self) -> Option<Position> {
+        Some(self.position)
+    }
+
+    fn relocated(mut self, base: Position) -> Self {
+        self.position = self.position.rebase(base);
+        self
+    }
+}
+
+/// Matches one run of non-whitespace characters and the whitespace after it.
+///
+/// Expects the input to start with a word; [`Lexer::words`] skips leading
+/// whitespace before the first token.
+pub struct WordRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, WordToken> for WordRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<WordToken> {
+        let position = ctx.position();
+        let text = ctx.consume_while(|c| !c.is_whitespace());
+        if text.is_empty() {
+            return None;
+        }
+        ctx.consume_while(char::is_whitespace);
+        Some(WordToken {
+            text: text.to_string(),
+            position,
+        })
+    }
+
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(!first_char?.is_whitespace())
+    }
+
+    fn name(&self) -> &str {
+        "word"
+    }
+}
+
+impl Lexer<DefaultContext, WordToken> {
+    /// Creates a lexer that splits `input` into whitespace-delimited words.
+    ///
+    /// ```
+    /// use lexer_framework::Lexer;
+    ///
+    /// let words: Vec<_> = Lexer::words("let x =\n  1").map(|w| w.text).collect();
+    /// assert_eq!(words, ["let", "x", "=", "1"]);
+    /// ```
+    pub fn words<S: Into<String>>(input: S) -> Self {
+        let mut context = DefaultContext::new(input);
+        context.consume_while(char::is_whitespace);
+        Lexer::new(context, vec![Box::new(WordRule)])
+    }
+}
@@ -0,0 +1,186 @@
+//! Token adapter tests (`Lexer::map_tokens`, `Lexer::inspect_tokens`,
+//! `Lexer::filter_tokens`).
+
+mod common;
+
+use common::{rules, Tok};
+use lexer_framework::{LexContext, Lexer};
+
+#[test]
+fn test_map_tokens_changes_token_type() {
+    let lengths: Vec<usize> = Lexer::from_str("ab cde f", rules())
+        .map_tokens(|tok| match tok {
+            Tok::Word(word) => word.len(),
+            Tok::Space => 0,
+        })
+        .collect();
+    assert_eq!(lengths, [2, 0, 3, 0, 1]);
+}
+
+#[test]
+fn test_inspect_tokens_sees_every_token_unchanged() {
+    let mut seen = 0;
+    let tokens: Vec<Tok> = Lexer::from_str("ab cd", rules())
+        .inspect_tokens(|_| seen += 1)
+        .collect();
+    assert_eq!(seen, 3);
+    assert_eq!(tokens[2], Tok::Word("cd".to_string()));
+}
+
+#[test]
+fn test_adapters_expose_inner_lexer() {
+    let mut mapped = Lexer::from_str("ab", rules()).map_tokens(|tok| tok);
+    assert_eq!(mapped.next(), Some(Tok::Word("ab".to_string())));
+    assert!(mapped.inner().context().is_eof());
+}
+
+#[test]
+fn test_filter_tokens_drops_rejected_tokens() {
+    let mut filtered = Lexer::from_str("ab  cd ", rules()).filter_tokens(|tok| *tok != Tok::Space);
+    assert_eq!(filtered.next(), Some(Tok::Word("ab".to_string())));
+    assert_eq!(filtered.next(), Some(Tok::Word("cd".to_string())));
+    assert_eq!(filtered.next(), None);
+    assert!(filtered.inner().context().is_eof());
+}
+
+#[test]
+fn test_tee_forwards_significant_and_keeps_raw() {
+    let mut tee = Lexer::from_str("ab  cd", rules()).tee(Vec::new(), |tok| *tok != Tok::Space);
+    let significant: Vec<Tok> = tee.by_ref().collect();
+    assert_eq!(
+        significant,
+        [Tok::Word("ab".to_string()), Tok::Word("cd".to_string())]
+    );
+
+    let (_, raw) = tee.into_parts();
+    assert_eq!(raw.len(), 3);
+    assert_eq!(raw[1], Tok::Space);
+}
+
+#[test]
+fn test_tee_into_channel() {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let forwarded = Lexer::from_str("a b", rules())
+        .tee(sender, |tok| *tok != Tok::Space)
+        .count();
+    assert_eq!(forwarded, 2);
+    assert_eq!(receiver.iter().count(), 3);
+}
+
+#[cfg(feature = "streaming")]
+mod streaming {
+    use super::*;
+    use common_framework::{Inbound, Outbound, StreamingSignal};
+    use lexer_framework::{InspectTokens, StreamingLexContext, TokenProducer};
+
+    fn finished_context(input: &str) -> StreamingLexContext {
+        let mut context = StreamingLexContext::new();
+        context.push_str(input);
+        context.mark_finished();
+        context
+    }
+
+    #[test]
+    fn test_mapped_producer_supplies_mapped_tokens() {
+        let lexer = Lexer::new(finished_context("ab c"), rules());
+        let mut mapped = lexer.map_tokens(|tok| matches!(tok, Tok::Word(_)));
+
+        let mut signals = Vec::new();
+        while let Some(signal) = Outbound::<bool, ()>::next_signal(&mut mapped) {
+            let end = matches!(signal, StreamingSignal::EndOfInput);
+            signals.push(signal);
+            if end {
+                break;
+            }
+        }
+        assert!(matches!(
+            signals.as_slice(),
+            [
+                StreamingSignal::SupplyToken(true),
+                StreamingSignal::SupplyToken(false),
+                StreamingSignal::SupplyToken(true),
+                StreamingSignal::EndOfInput,
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_adapters_compose_as_producers() {
+        let mut count = 0;
+        let lexer = Lexer::new(finished_context("ab cd"), rules());
+        let mut producer = InspectTokens::new(
+            lexer.map_tokens(|tok| format!("{:?}", tok)),
+            |_: &String| count += 1,
+        );
+        let mut last = None;
+        while let Some(token) = producer.poll_token() {
+            last = Some(token);
+        }
+        assert_eq!(last.as_deref(), Some("Word(\"cd\")"));
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn test_tee_skips_filtered_signals() {
+        let mut raw = Vec::new();
+        let lexer = Lexer::new(finished_context("ab cd"), rules());
+        let mut tee = lexer.tee(&mut raw, |tok| *tok != Tok::Space);
+
+        let mut supplied = 0;
+        while let Some(signal) = Outbound::<Tok, ()>::next_signal(&mut tee) {
+            match signal {
+                StreamingSignal::SupplyToken(tok) => {
+                    assert_ne!(tok, Tok::Space);
+                    supplied += 1;
+                }
+                StreamingSignal::EndOfInput => break,
+                _ => {}
+            }
+        }
+        drop(tee);
+        assert_eq!(supplied, 2);
+        assert_eq!(raw.len(), 3);
+    }
+
+    #[test]
+    fn test_requested_batches_pass_through_adapters() {
+        let mut raw = Vec::new();
+        let lexer = Lexer::new(finished_context("ab cd ef"), rules());
+        let mut tee = lexer.tee(&mut raw, |tok| *tok != Tok::Space);
+
+        Inbound::<Tok, ()>::handle_signal(&mut tee, StreamingSignal::RequestTokens(4));
+        let first = Outbound::<Tok, ()>::next_signal(&mut tee);
+        assert!(matches!(
+            first,
+            Some(StreamingSignal::SupplyTokens(ref tokens)) if tokens.len() == 2
+        ));
+        // Without a new request the lexer falls back to single tokens.
+        let next = Outbound::<Tok, ()>::next_signal(&mut tee);
+        assert!(matches!(
+            next,
+            Some(StreamingSignal::SupplyToken(Tok::Word(_)))
+        ));
+        drop(tee);
+        assert_eq!(raw.len(), 5);
+    }
+
+    #[test]
+    fn test_lexer_reports_progress_between_tokens() {
+        let mut lexer = Lexer::new(finished_context("ab cd ef"), rules());
+        lexer.report_progress_every(2);
+
+        let mut progress = Vec::new();
+        while let Some(signal) = Outbound::<Tok, ()>::next_signal(&mut lexer) {
+            match signal {
+                StreamingSignal::Progress(report) => progress.push(report),
+                StreamingSignal::EndOfInput => break,
+                _ => {}
+            }
+        }
+        let seen: Vec<_> = progress
+            .iter()
+            .map(|p| (p.bytes_consumed, p.tokens_emitted))
+            .collect();
+        assert_eq!(seen, [(3, 2), (6, 4)]);
+    }
+}
@@ -0,0 +1,32 @@
+//! Allocation counting tests (`alloc-stats` feature).
+#![cfg(feature = "alloc-stats")]
+
+mod common;
+
+use common::rules;
+use lexer_framework::{bench_lexer, AllocStats, CountingAllocator};
+
+#[global_allocator]
+static ALLOC: CountingAllocator = CountingAllocator;
+
+#[test]
+fn test_alloc_stats_measure_counts_this_thread() {
+    let (text, stats) = AllocStats::measure(|| "abc".repeat(10));
+    assert_eq!(text.len(), 30);
+    assert_eq!(stats.allocations, 1);
+    assert_eq!(stats.bytes, 30);
+}
+
+#[test]
+fn test_bench_lexer_reports_allocations_per_rule() {
+    let report = bench_lexer("alpha beta gamma", rules, 1);
+    assert!(report.allocations.allocations >= 3);
+
+    let word = report.rule("WordRule").unwrap();
+    assert_eq!(word.allocations.allocations, 3);
+    assert_eq!(word.allocations.bytes, 14);
+    assert_eq!(
+        report.rule("SpaceRule").unwrap().allocations,
+        AllocStats::default()
+    );
+}
@@ -0,0 +1,83 @@
+//! Benchmark harness tests (`bench_lexer`).
+
+use lexer_framework::{bench_lexer, DefaultContext, LexContext, LexToken, LexingRule, TokenKind};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Word,
+    Number,
+    Space,
+}
+
+impl LexToken for Tok {
+    fn kind(&self) -> Option<TokenKind> {
+        matches!(self, Tok::Space).then_some(TokenKind::Whitespace)
+    }
+}
+
+struct SpaceRule;
+
+impl LexingRule<DefaultContext, Tok> for SpaceRule {
+    fn try_match(&mut self, ctx: &mut DefaultContext) -> Option<Tok> {
+        (!ctx.consume_while(char::is_whitespace).is_empty()).then_some(Tok::Space)
+    }
+
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        first_char.map(char::is_whitespace)
+    }
+}
+
+/// No quick check, so it is tried at every token.
+struct NumberRule;
+
+impl LexingRule<DefaultContext, Tok> for NumberRule {
+    fn try_match(&mut self, ctx: &mut DefaultContext) -> Option<Tok> {
+        (!ctx.consume_while(|ch| ch.is_ascii_digit()).is_empty()).then_some(Tok::Number)
+    }
+
+    fn priority(&self) -> i32 {
+        10
+    }
+}
+
+struct WordRule;
+
+impl LexingRule<DefaultContext, Tok> for WordRule {
+    fn try_match(&mut self, ctx: &mut DefaultContext) -> Option<Tok> {
+        (!ctx.consume_while(char::is_alphanumeric).is_empty()).then_some(Tok::Word)
+    }
+}
+
+fn rules() -> Vec<Box<dyn LexingRule<DefaultContext, Tok>>> {
+    vec![
+        Box::new(SpaceRule),
+        Box::new(WordRule),
+        Box::new(NumberRule),
+    ]
+}
+
+#[test]
+fn test_bench_lexer_reports_sizes_and_rule_counts() {
+    let report = bench_lexer("ab 12 cd", rules, 3);
+    assert_eq!(report.iterations, 3);
+    assert_eq!(report.input, 8);
+    assert_eq!(report.output, 5);
+
+    // Highest priority first, then registration order.
+    let names: Vec<&str> = report.rules.iter().map(|rule| rule.name.as_str()).collect();
+    assert_eq!(names, vec!["NumberRule", "SpaceRule", "WordRule"]);
+
+    let number = report.rule("NumberRule").unwrap();
+    assert_eq!((number.attempts, number.matches), (5, 1));
+    let word = report.rule("WordRule").unwrap();
+    assert_eq!((word.attempts, word.matches), (2, 2));
+    assert_eq!(report.rule("SpaceRule").unwrap().matches, 2);
+}
+
+#[test]
+fn test_bench_lexer_compares_rule_sets() {
+    let report = bench_lexer("ab 12 cd", rules, 0);
+    assert_eq!(report.output, 5);
+    assert_eq!(report.input_per_sec(), 0.0);
+    assert_eq!(report.speedup_over(&report), 0.0);
+}
@@ -0,0 +1,109 @@
+//! Byte-level lexing tests (`ByteLexer`, `ByteLexContext`).
+
+use lexer_framework::{ByteLexContext, ByteLexer, ByteLexingRule, DefaultByteContext};
+
+/// A subset of the Redis serialization protocol (RESP).
+#[derive(Debug, Clone, PartialEq)]
+enum Resp {
+    Simple(String),
+    Integer(i64),
+    Bulk(Vec<u8>),
+    ArrayLen(usize),
+}
+
+/// Reads the text up to the next `\r\n` and consumes the terminator.
+fn line(ctx: &mut DefaultByteContext) -> Option<String> {
+    let text = ctx.consume_while_bytes(|b| b != b'\r').to_vec();
+    ctx.consume_bytes(b"\r\n").then(|| String::from_utf8(text).ok())?
+}
+
+struct SimpleRule;
+
+impl ByteLexingRule<DefaultByteContext, Resp> for SimpleRule {
+    fn try_match(&mut self, ctx: &mut DefaultByteContext) -> Option<Resp> {
+        ctx.consume_bytes(b"+").then_some(())?;
+        line(ctx).map(Resp::Simple)
+    }
+
+    fn quick_check(&self, first_byte: Option<u8>) -> Option<bool> {
+        Some(first_byte == Some(b'+'))
+    }
+}
+
+struct IntegerRule;
+
+impl ByteLexingRule<DefaultByteContext, Resp> for IntegerRule {
+    fn try_match(&mut self, ctx: &mut DefaultByteContext) -> Option<Resp> {
+        let kind = ctx.advance_byte()?;
+        let value = line(ctx)?;
+        match kind {
+            b':' => value.parse().ok().map(Resp::Integer),
+            b'*' => value.parse().ok().map(Resp::ArrayLen),
+            _ => None,
+        }
+    }
+
+    fn quick_check(&self, first_byte: Option<u8>) -> Option<bool> {
+        Some(matches!(first_byte, Some(b':' | b'*')))
+    }
+}
+
+/// `$<len>\r\n<len raw bytes>\r\n`; the payload may be any bytes.
+struct BulkRule;
+
+impl ByteLexingRule<DefaultByteContext, Resp> for BulkRule {
+    fn try_match(&mut self, ctx: &mut DefaultByteContext) -> Option<Resp> {
+        ctx.consume_bytes(b"$").then_some(())?;
+        let len: usize = line(ctx)?.parse().ok()?;
+        let payload = ctx.advance_bytes(len).to_vec();
+        (payload.len() == len && ctx.consume_bytes(b"\r\n")).then_some(Resp::Bulk(payload))
+    }
+
+    fn quick_check(&self, first_byte: Option<u8>) -> Option<bool> {
+        Some(first_byte == Some(b'$'))
+    }
+}
+
+fn rules() -> Vec<Box<dyn ByteLexingRule<DefaultByteContext, Resp>>> {
+    vec![Box::new(SimpleRule), Box::new(IntegerRule), Box::new(BulkRule)]
+}
+
+#[test]
+fn test_byte_lexer_reads_binary_payloads() {
+    let input = b"*2\r\n$3\r\n\xff\r\x00\r\n:42\r\n+OK\r\n".to_vec();
+    let tokens: Vec<_> = ByteLexer::from_bytes(input, rules()).collect();
+    assert_eq!(
+        tokens,
+        vec![
+            Resp::ArrayLen(2),
+            Resp::Bulk(vec![0xff, b'\r', 0x00]),
+            Resp::Integer(42),
+            Resp::Simple("OK".into()),
+        ]
+    );
+}
+
+#[test]
+fn test_byte_lexer_stops_at_unmatched_byte() {
+    let mut lexer = ByteLexer::from_bytes(&b"+OK\r\n?"[..], rules());
+    assert_eq!(lexer.tokenize(), vec![Resp::Simple("OK".into())]);
+    assert_eq!(lexer.context().offset(), 5);
+    assert_eq!(lexer.context().peek_byte(), Some(b'?'));
+}
+
+#[test]
+fn test_failed_rule_is_rolled_back() {
+    // The bulk string is truncated, so the rule must not consume anything.
+    let mut lexer = ByteLexer::from_bytes(&b"$5\r\nab"[..], rules());
+    assert_eq!(lexer.next(), None);
+    assert_eq!(lexer.context().offset(), 0);
+}
+
+#[test]
+fn test_byte_positions_count_lines() {
+    let mut ctx = DefaultByteContext::new(&b"ab\ncd"[..]);
+    ctx.consume_while_bytes(|b| b != b'c');
+    let position = ctx.position();
+    assert_eq!((position.line, position.column, position.offset), (2, 1, 3));
+    assert_eq!(ctx.bytes_from(1), b"b\n");
+}
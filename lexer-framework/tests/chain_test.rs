@@ -0,0 +1,178 @@
+//! Chained producer tests (`TokenProducer::chain_producer`, `ChainedProducer`).
+#![cfg(feature = "streaming")]
+
+use common_framework::{Outbound, StreamingSignal};
+use lexer_framework::{
+    LexContext, LexToken, Lexer, LexingRule, Position, StreamingLexContext, TokenKind,
+    TokenProducer,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Word(String, Position),
+    Newline,
+    Eof(Position),
+}
+
+impl LexToken for Tok {
+    fn position(&self) -> Option<Position> {
+        match self {
+            Tok::Word(_, position) | Tok::Eof(position) => Some(*position),
+            Tok::Newline => None,
+        }
+    }
+
+    fn kind(&self) -> Option<TokenKind> {
+        match self {
+            Tok::Eof(_) => Some(TokenKind::Eof),
+            Tok::Newline => Some(TokenKind::Newline),
+            _ => None,
+        }
+    }
+
+    fn relocated(self, base: Position) -> Self {
+        match self {
+            Tok::Word(word, position) => Tok::Word(word, position.rebase(base)),
+            Tok::Eof(position) => Tok::Eof(position.rebase(base)),
+            Tok::Newline => Tok::Newline,
+        }
+    }
+}
+
+struct WordRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, Tok> for WordRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        let position = ctx.position();
+        let word = ctx.consume_while(|ch| !ch.is_whitespace());
+        if word.is_empty() {
+            return None;
+        }
+        Some(Tok::Word(word.to_string(), position))
+    }
+}
+
+struct NewlineRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, Tok> for NewlineRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        if ctx.peek() != Some('\n') {
+            return None;
+        }
+        ctx.advance();
+        Some(Tok::Newline)
+    }
+
+    fn priority(&self) -> i32 {
+        1
+    }
+}
+
+fn lexer(input: &str) -> Lexer<StreamingLexContext, Tok> {
+    let mut context = StreamingLexContext::new();
+    context.push_str(input);
+    context.mark_finished();
+    Lexer::new(context, vec![Box::new(WordRule), Box::new(NewlineRule)])
+}
+
+fn drain<P: TokenProducer<Tok>>(producer: &mut P) -> Vec<Tok> {
+    let mut tokens = Vec::new();
+    while let Some(token) = producer.poll_token() {
+        tokens.push(token);
+    }
+    tokens
+}
+
+#[test]
+fn test_chain_offsets_positions_of_later_parts() {
+    let mut chained = lexer("ab\ncd\n").chain_producer(lexer("ef\ngh"));
+    let tokens = drain(&mut chained);
+
+    assert_eq!(tokens.len(), 7);
+    assert_eq!(
+        tokens[4],
+        Tok::Word("ef".to_string(), Position::at(3, 1, 6))
+    );
+    assert_eq!(
+        tokens[6],
+        Tok::Word("gh".to_string(), Position::at(4, 1, 9))
+    );
+    assert_eq!(chained.boundary(), Some(Position::at(3, 1, 6)));
+    assert_eq!(chained.input_position(), Some(Position::at(4, 3, 11)));
+}
+
+#[test]
+fn test_chain_nests_across_three_parts() {
+    let mut chained = lexer("a\n")
+        .chain_producer(lexer("b\n"))
+        .chain_producer(lexer("c"));
+    let words: Vec<Position> = drain(&mut chained)
+        .into_iter()
+        .filter_map(|token| match token {
+            Tok::Word(_, position) => Some(position),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        words,
+        [
+            Position::at(1, 1, 0),
+            Position::at(2, 1, 2),
+            Position::at(3, 1, 4)
+        ]
+    );
+}
+
+#[test]
+fn test_chain_drops_inner_eof_tokens() {
+    let parts: Vec<Vec<Tok>> = vec![
+        vec![
+            Tok::Word("a".to_string(), Position::new()),
+            Tok::Eof(Position::at(1, 2, 1)),
+        ],
+        vec![
+            Tok::Word("b".to_string(), Position::new()),
+            Tok::Eof(Position::at(1, 2, 1)),
+        ],
+    ];
+    struct Replay(std::vec::IntoIter<Tok>);
+    impl TokenProducer<Tok> for Replay {
+        fn poll_token(&mut self) -> Option<Tok> {
+            self.0.next()
+        }
+    }
+
+    let mut parts = parts.into_iter().map(|part| Replay(part.into_iter()));
+    let mut chained = parts.next().unwrap().chain_producer(parts.next().unwrap());
+    let tokens = drain(&mut chained);
+    assert_eq!(
+        tokens,
+        [
+            Tok::Word("a".to_string(), Position::new()),
+            Tok::Word("b".to_string(), Position::at(1, 2, 1)),
+            Tok::Eof(Position::at(1, 3, 2)),
+        ]
+    );
+}
+
+#[test]
+fn test_chain_signals_end_once() {
+    let mut chained = lexer("a").chain_producer(lexer("b"));
+    let mut signals = Vec::new();
+    while let Some(signal) = Outbound::<Tok, ()>::next_signal(&mut chained) {
+        let end = matches!(signal, StreamingSignal::EndOfInput);
+        signals.push(signal);
+        if end {
+            break;
+        }
+    }
+    assert!(matches!(
+        signals.as_slice(),
+        [
+            StreamingSignal::SupplyToken(Tok::Word(..)),
+            StreamingSignal::SupplyToken(Tok::Word(..)),
+            StreamingSignal::EndOfInput,
+        ]
+    ));
+    assert!(chained.in_second());
+}
@@ -0,0 +1,146 @@
+//! Token channel tests (`LexingRule::channel`, `ChannelTokens`).
+
+use lexer_framework::{Channel, LexContext, LexToken, Lexer, LexingRule, Position, TokenKind};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Word(String, Position),
+    Comment(String),
+    Space,
+}
+
+impl LexToken for Tok {
+    fn position(&self) -> Option<Position> {
+        match self {
+            Tok::Word(_, position) => Some(*position),
+            _ => None,
+        }
+    }
+
+    fn kind(&self) -> Option<TokenKind> {
+        matches!(self, Tok::Space).then_some(TokenKind::Whitespace)
+    }
+}
+
+struct WordRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, Tok> for WordRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        let position = ctx.position();
+        let word = ctx.consume_while(|ch| ch.is_alphanumeric());
+        if word.is_empty() {
+            return None;
+        }
+        Some(Tok::Word(word.to_string(), position))
+    }
+}
+
+/// `# ...` up to the end of the line, on the hidden channel.
+struct CommentRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, Tok> for CommentRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        if ctx.peek() != Some('#') {
+            return None;
+        }
+        let text = ctx.consume_while(|ch| ch != '\n');
+        Some(Tok::Comment(text.to_string()))
+    }
+
+    fn channel(&self) -> Channel {
+        Channel::HIDDEN
+    }
+}
+
+/// Whitespace, on a user-defined channel.
+struct SpaceRule;
+
+const LAYOUT: Channel = Channel::new(2);
+
+impl<Ctx: LexContext> LexingRule<Ctx, Tok> for SpaceRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        if ctx.consume_while(char::is_whitespace).is_empty() {
+            return None;
+        }
+        Some(Tok::Space)
+    }
+
+    fn channel(&self) -> Channel {
+        LAYOUT
+    }
+}
+
+fn lexer(input: &str) -> Lexer<lexer_framework::DefaultContext, Tok> {
+    Lexer::from_str(
+        input,
+        vec![
+            Box::new(WordRule),
+            Box::new(CommentRule),
+            Box::new(SpaceRule),
+        ],
+    )
+}
+
+fn words(tokens: impl IntoIterator<Item = Tok>) -> Vec<String> {
+    tokens
+        .into_iter()
+        .filter_map(|token| match token {
+            Tok::Word(word, _) => Some(word),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_default_stream_skips_other_channels() {
+    let tokens = lexer("# doc\nfoo bar # trailing").tokenize_default();
+    assert_eq!(tokens.len(), 2);
+    assert_eq!(words(tokens), ["foo", "bar"]);
+}
+
+#[test]
+fn test_rules_default_to_the_default_channel() {
+    let mut lexer = Lexer::from_str("a b", vec![Box::new(WordRule)]);
+    lexer.next();
+    assert_eq!(lexer.last_rule().unwrap().channel(), Channel::DEFAULT);
+}
+
+#[test]
+fn test_hidden_tokens_around_default_tokens() {
+    let tokens = lexer("# doc\nfoo bar # trailing").tokenize_channels();
+    assert_eq!(tokens.len(), 7);
+    assert_eq!(tokens.default_len(), 2);
+    assert_eq!(tokens.on_channel(Channel::HIDDEN).count(), 2);
+    assert_eq!(tokens.on_channel(LAYOUT).count(), 3);
+
+    let before_foo = tokens.hidden_before(0);
+    assert_eq!(
+        before_foo,
+        [
+            (Channel::HIDDEN, Tok::Comment("# doc".into())),
+            (LAYOUT, Tok::Space)
+        ]
+    );
+    assert_eq!(tokens.hidden_after(0), [(LAYOUT, Tok::Space)]);
+    assert_eq!(tokens.hidden_before(1), [(LAYOUT, Tok::Space)]);
+    assert_eq!(
+        tokens.hidden_after(1),
+        [
+            (LAYOUT, Tok::Space),
+            (Channel::HIDDEN, Tok::Comment("# trailing".into()))
+        ]
+    );
+    // Past the last default token: what trails it.
+    assert_eq!(tokens.hidden_before(2), tokens.hidden_after(1));
+    assert!(tokens.hidden_before(3).is_empty());
+    assert!(tokens.hidden_after(2).is_empty());
+}
+
+#[test]
+fn test_default_index_at_position() {
+    let tokens = lexer("# doc\nfoo bar").tokenize_channels();
+    let bar = tokens.get_default(1).and_then(Tok::position).unwrap();
+    assert_eq!(tokens.default_index_at(bar), 1);
+    assert_eq!(tokens.default_index_at(Position::new()), 0);
+    assert_eq!(words(tokens.into_default_tokens()), ["foo", "bar"]);
+}
@@ -0,0 +1,43 @@
+//! Shared fixture: whitespace-delimited words and the spaces between them,
+//! over any context.
+#![allow(dead_code)]
+
+use lexer_framework::{LexContext, LexToken, LexingRule, TokenKind};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tok {
+    Word(String),
+    Space,
+}
+
+impl LexToken for Tok {
+    fn kind(&self) -> Option<TokenKind> {
+        matches!(self, Tok::Space).then_some(TokenKind::Whitespace)
+    }
+}
+
+/// Copies every word into a `String`.
+pub struct WordRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, Tok> for WordRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        let word = ctx.consume_while(|ch| !ch.is_whitespace());
+        (!word.is_empty()).then(|| Tok::Word(String::from(&*word)))
+    }
+}
+
+pub struct SpaceRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, Tok> for SpaceRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        (!ctx.consume_while(char::is_whitespace).is_empty()).then_some(Tok::Space)
+    }
+
+    fn priority(&self) -> i32 {
+        1
+    }
+}
+
+pub fn rules<Ctx: LexContext>() -> Vec<Box<dyn LexingRule<Ctx, Tok>>> {
+    vec![Box::new(WordRule), Box::new(SpaceRule)]
+}
@@ -1,4 +1,4 @@
-use lexer_framework::{DefaultContext, LexContext, Position};
+use lexer_framework::{Checkpoint, DefaultContext, LexContext, Position};
 
 #[test]
 fn test_default_context_new() {
@@ -56,6 +56,59 @@ fn test_default_context_checkpoint_restore() {
     assert_eq!(ctx.position().column, 3);
 }
 
+#[test]
+fn test_speculate_keeps_progress_on_success() {
+    let mut ctx = DefaultContext::new("hello");
+    let matched = ctx.speculate(|ctx| {
+        ctx.advance();
+        ctx.advance()
+    });
+    assert_eq!(matched, Some('e'));
+    assert_eq!(ctx.peek(), Some('l'));
+}
+
+#[test]
+fn test_speculate_restores_on_early_return() {
+    let mut ctx = DefaultContext::new("hello");
+    let matched: Option<()> = ctx.speculate(|ctx| {
+        ctx.advance();
+        ctx.advance();
+        // Bails out with `?` after consuming input.
+        (ctx.peek()? == 'x').then_some(())
+    });
+    assert_eq!(matched, None);
+    assert_eq!(ctx.peek(), Some('h'));
+    assert_eq!(ctx.position().column, 1);
+}
+
+#[test]
+fn test_checkpoint_carries_context_and_label() {
+    let ctx = DefaultContext::new("hello");
+    let other = DefaultContext::new("hello");
+
+    let checkpoint = ctx.checkpoint().with_label("start");
+    assert_eq!(checkpoint.label(), Some("start"));
+    assert!(checkpoint.belongs_to(ctx.checkpoint().context()));
+    assert!(!checkpoint.belongs_to(other.checkpoint().context()));
+    assert!(checkpoint.to_string().starts_with("checkpoint 'start' @0"));
+}
+
+#[test]
+fn test_hand_built_checkpoint_restores_anywhere() {
+    let mut ctx = DefaultContext::new("hello");
+    ctx.restore(Checkpoint::new(1, Position::at(1, 2, 1)));
+    assert_eq!(ctx.peek(), Some('e'));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "restored into a different context")]
+fn test_restore_from_other_context_panics() {
+    let ctx = DefaultContext::new("hello");
+    let mut other = DefaultContext::new("world");
+    other.restore(ctx.checkpoint());
+}
+
 #[test]
 fn test_default_context_is_eof() {
     let mut ctx = DefaultContext::new("hi");
@@ -72,3 +125,124 @@ fn test_default_context_empty() {
     assert!(ctx.is_eof());
     assert_eq!(ctx.peek(), None);
 }
+
+#[derive(Debug, Default, PartialEq)]
+struct Depth(usize);
+
+#[test]
+fn test_context_state_defaults_and_persists() {
+    let mut ctx = DefaultContext::new("((a))");
+    assert_eq!(ctx.state::<Depth>(), &Depth(0));
+    while let Some(c) = ctx.advance() {
+        if c == '(' {
+            ctx.state::<Depth>().0 += 1;
+        }
+    }
+    assert_eq!(ctx.state::<Depth>(), &Depth(2));
+    assert!(!*ctx.state::<bool>());
+}
+
+#[test]
+fn test_context_state_survives_restore() {
+    let mut ctx = DefaultContext::new("abc");
+    let checkpoint = ctx.checkpoint();
+    ctx.advance();
+    ctx.state::<Depth>().0 = 3;
+    ctx.restore(checkpoint);
+    assert_eq!(ctx.state::<Depth>(), &Depth(3));
+}
+
+#[test]
+fn test_context_with_state_moves_to_another_thread() {
+    let mut ctx = DefaultContext::new("((a))");
+    ctx.state::<Depth>().0 = 1;
+    ctx.report(lexer_framework::Diagnostic::note(
+        (ctx.position(), ctx.position()),
+        "before the move",
+    ));
+    let depth = std::thread::spawn(move || {
+        ctx.advance();
+        ctx.state::<Depth>().0 += 1;
+        (ctx.state::<Depth>().0, ctx.take_diagnostics().len())
+    })
+    .join()
+    .unwrap();
+    assert_eq!(depth, (2, 1));
+}
+
+#[test]
+fn test_context_capture_returns_consumed_text() {
+    let mut ctx = DefaultContext::new("let x_1 = 2");
+    ctx.consume_while(|c| c.is_alphabetic());
+    ctx.advance();
+    let ident = ctx.capture(|ctx| {
+        ctx.advance();
+        ctx.consume_while(|c| c.is_alphanumeric() || c == '_');
+    });
+    assert_eq!(ident, "x_1");
+    assert_eq!((ident.start(), ident.end()), (4, 7));
+    assert_eq!(ctx.peek(), Some(' '));
+}
+
+#[test]
+fn test_context_capture_empty() {
+    let mut ctx = DefaultContext::new("abc");
+    let nothing = ctx.capture(|_| {});
+    assert!(nothing.is_empty());
+    assert_eq!(ctx.peek(), Some('a'));
+}
+
+#[cfg(feature = "streaming")]
+#[test]
+fn test_streaming_context_capture_after_discard() {
+    let mut ctx = lexer_framework::StreamingLexContext::new();
+    ctx.push_str("skip keep");
+    ctx.consume_while(|c| c != ' ');
+    ctx.advance();
+    ctx.discard_consumed();
+    let word = ctx.capture(|ctx| {
+        ctx.consume_while(|c| c.is_alphabetic());
+    });
+    assert_eq!(word, "keep");
+    assert_eq!(ctx.offset(), 9);
+}
+
+#[cfg(feature = "streaming")]
+#[test]
+fn test_streaming_context_keeps_input_of_pinned_checkpoints() {
+    let mut ctx = lexer_framework::StreamingLexContext::new();
+    ctx.push_str("one two three");
+    ctx.consume_while(|c| c != ' ');
+    let pinned = ctx.pin_checkpoint();
+    ctx.advance();
+    ctx.consume_while(|c| c != ' ');
+    ctx.discard_consumed();
+    assert_eq!(ctx.buffered_len(), 10);
+
+    ctx.restore(pinned.checkpoint());
+    assert_eq!(ctx.peek(), Some(' '));
+    drop(pinned);
+    ctx.advance();
+    ctx.discard_consumed();
+    assert_eq!(ctx.buffered_len(), 9);
+    assert_eq!(ctx.offset(), 4);
+}
+
+#[test]
+fn test_context_collects_reported_diagnostics() {
+    use lexer_framework::{Diagnostic, Severity};
+
+    let mut ctx = DefaultContext::new("####### title");
+    let start = ctx.position();
+    ctx.consume_while(|c| c == '#');
+    ctx.report(Diagnostic::warning(
+        (start, ctx.position()),
+        "too many `#`s",
+    ));
+
+    let diagnostics = ctx.take_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert_eq!(diagnostics[0].end.column, 8);
+    assert!(ctx.take_diagnostics().is_empty());
+}
@@ -15,22 +15,6 @@ impl LexToken for TestToken {
             TestToken::Token { position, .. } => *position,
         })
     }
-
-    fn is_eof(&self) -> bool {
-        false
-    }
-
-    fn is_newline(&self) -> bool {
-        false
-    }
-
-    fn is_whitespace(&self) -> bool {
-        false
-    }
-
-    fn is_indent(&self) -> bool {
-        false
-    }
 }
 
 struct CharRule;
@@ -0,0 +1,138 @@
+//! Token stream encoding tests (`EncodedTokens`, `TokenDecoder`).
+
+use lexer_framework::{
+    DefaultContext, EncodeToken, EncodedTokens, LexContext, LexToken, Lexer, LexingRule, Position,
+    TextSlice, TokenCodecError, TokenDecoder, TokenKind,
+};
+
+const IDENT: u32 = 0;
+const NUMBER: u32 = 1;
+const NEWLINE: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Ident(TextSlice, Position),
+    Number(i64, usize, Position),
+    Newline(Position),
+}
+
+impl LexToken for Tok {
+    fn position(&self) -> Option<Position> {
+        match self {
+            Tok::Ident(_, pos) | Tok::Number(_, _, pos) | Tok::Newline(pos) => Some(*pos),
+        }
+    }
+
+    fn kind(&self) -> Option<TokenKind> {
+        matches!(self, Tok::Newline(_)).then_some(TokenKind::Newline)
+    }
+}
+
+impl EncodeToken for Tok {
+    fn kind_id(&self) -> u32 {
+        match self {
+            Tok::Ident(..) => IDENT,
+            Tok::Number(..) => NUMBER,
+            Tok::Newline(_) => NEWLINE,
+        }
+    }
+
+    fn text_len(&self) -> usize {
+        match self {
+            Tok::Ident(text, _) => text.len(),
+            Tok::Number(_, len, _) => *len,
+            Tok::Newline(_) => 1,
+        }
+    }
+}
+
+struct TokRule;
+
+impl LexingRule<DefaultContext, Tok> for TokRule {
+    fn try_match(&mut self, ctx: &mut DefaultContext) -> Option<Tok> {
+        ctx.consume_while(|c| c == ' ');
+        let pos = ctx.position();
+        let ch = ctx.peek()?;
+        if ch == '\n' {
+            ctx.advance();
+            Some(Tok::Newline(pos))
+        } else if ch.is_ascii_digit() {
+            let digits = ctx.consume_while(|c| c.is_ascii_digit());
+            Some(Tok::Number(digits.parse().ok()?, digits.len(), pos))
+        } else {
+            Some(Tok::Ident(ctx.consume_while(|c| !c.is_whitespace()), pos))
+        }
+    }
+}
+
+fn decoder() -> TokenDecoder<Tok> {
+    TokenDecoder::new()
+        .register(IDENT, Tok::Ident)
+        .register(NUMBER, |text, pos| {
+            Tok::Number(text.parse().unwrap(), text.len(), pos)
+        })
+        .register(NEWLINE, |_, pos| Tok::Newline(pos))
+}
+
+const SOURCE: &str = "let 变量 = 42\n  print 变量 1000000\n";
+
+#[test]
+fn test_encoded_tokens_round_trip() {
+    let tokens = Lexer::from_str(SOURCE, vec![Box::new(TokRule)]).tokenize();
+    let encoded = EncodedTokens::encode(&tokens, SOURCE).unwrap();
+    assert_eq!(encoded.len(), tokens.len());
+
+    let bytes = encoded.to_bytes();
+    // Header, lengths and at most four bytes per token for this input.
+    assert!(bytes.len() <= 8 + tokens.len() * 4);
+    let restored = EncodedTokens::from_bytes(&bytes).unwrap();
+    assert_eq!(restored, encoded);
+
+    assert_eq!(decoder().decode(SOURCE, &restored).unwrap(), tokens);
+}
+
+#[test]
+fn test_decode_rejects_mismatches() {
+    let tokens = Lexer::from_str(SOURCE, vec![Box::new(TokRule)]).tokenize();
+    let encoded = EncodedTokens::encode(&tokens, SOURCE).unwrap();
+
+    assert_eq!(
+        decoder().decode("let", &encoded),
+        Err(TokenCodecError::SourceMismatch {
+            expected_len: SOURCE.len(),
+            actual_len: 3,
+        })
+    );
+    let partial = TokenDecoder::new().register(IDENT, Tok::Ident);
+    assert_eq!(
+        partial.decode(SOURCE, &encoded),
+        Err(TokenCodecError::UnknownKind {
+            index: 3,
+            kind: NUMBER,
+        })
+    );
+
+    let bytes = encoded.to_bytes();
+    assert!(matches!(
+        EncodedTokens::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(TokenCodecError::Malformed(_))
+    ));
+    assert!(matches!(
+        EncodedTokens::from_bytes(b"JSON"),
+        Err(TokenCodecError::Malformed(_))
+    ));
+}
+
+#[test]
+fn test_encode_rejects_invalid_spans() {
+    // Ends inside the multi-byte `变`.
+    let bad = vec![Tok::Number(0, 5, Position::at(1, 1, 0))];
+    assert_eq!(
+        EncodedTokens::encode(&bad, SOURCE),
+        Err(TokenCodecError::InvalidSpan {
+            index: 0,
+            start: 0,
+            len: 5,
+        })
+    );
+}
@@ -0,0 +1,95 @@
+//! Encoding detection and transcoding tests (`encoding` feature).
+#![cfg(feature = "encoding")]
+
+use lexer_framework::{DecodedInput, DefaultContext, LexContext, Lexer, LexingRule, Position};
+
+fn utf16le(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+fn utf16be(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(u16::to_be_bytes).collect()
+}
+
+/// Reports the position of every word.
+struct WordRule;
+
+impl LexingRule<DefaultContext, Position> for WordRule {
+    fn try_match(&mut self, ctx: &mut DefaultContext) -> Option<Position> {
+        ctx.consume_while(|ch| ch == ' ');
+        let position = ctx.position();
+        (!ctx.consume_while(|ch| ch != ' ').is_empty()).then_some(position)
+    }
+}
+
+fn word_offsets(input: &DecodedInput) -> Vec<usize> {
+    Lexer::new(input.context(), vec![Box::new(WordRule)])
+        .map(|position| input.original_position(position).offset)
+        .collect()
+}
+
+#[test]
+fn test_detects_utf16_by_bom() {
+    let mut bytes = vec![0xFF, 0xFE];
+    bytes.extend(utf16le("ab cd"));
+    let input = DecodedInput::detect(&bytes);
+    assert_eq!(input.encoding().name(), "UTF-16LE");
+    assert_eq!(input.text(), "ab cd");
+    // BOM (2) + "ab " (6).
+    assert_eq!(word_offsets(&input), vec![2, 8]);
+}
+
+#[test]
+fn test_detects_bomless_utf16() {
+    let input = DecodedInput::detect(&utf16be("x yz"));
+    assert_eq!(input.encoding().name(), "UTF-16BE");
+    assert_eq!(word_offsets(&input), vec![0, 4]);
+    assert_eq!(DecodedInput::detect(&utf16le("x yz")).text(), "x yz");
+}
+
+#[test]
+fn test_falls_back_to_latin1() {
+    // "café bar" in Latin-1: é is the single byte 0xE9.
+    let input = DecodedInput::detect(b"caf\xe9 bar");
+    assert_eq!(input.encoding().name(), "windows-1252");
+    assert_eq!(input.text(), "café bar");
+    assert_eq!(word_offsets(&input), vec![0, 5]);
+    // Offsets inside the two-byte UTF-8 `é` map to its single original byte.
+    assert_eq!(input.original_offset(4), 3);
+}
+
+#[test]
+fn test_multibyte_legacy_encoding() {
+    let shift_jis = lexer_framework::Encoding::for_label(b"shift_jis").unwrap();
+    // "日本 x": each kanji is two bytes in Shift_JIS, three in UTF-8.
+    let (bytes, _, _) = shift_jis.encode("日本 x");
+    assert_eq!(bytes.len(), 6);
+    let input = DecodedInput::with_encoding(&bytes, shift_jis);
+    assert_eq!(input.text(), "日本 x");
+    assert_eq!(word_offsets(&input), vec![0, 5]);
+    assert_eq!(input.original_offset(3), 2);
+    assert_eq!(input.original_offset(6), 4);
+    // A truncated lead byte becomes U+FFFD and keeps its one byte.
+    let input = DecodedInput::with_encoding(b"\x93 x", shift_jis);
+    assert_eq!(input.text(), "\u{FFFD} x");
+    assert_eq!(word_offsets(&input), vec![0, 2]);
+}
+
+#[test]
+fn test_utf8_maps_with_a_single_anchor() {
+    let input = DecodedInput::detect("héllo wörld".as_bytes());
+    assert_eq!(input.encoding().name(), "UTF-8");
+    assert_eq!(input.offsets().anchor_count(), 1);
+    assert_eq!(word_offsets(&input), vec![0, 7]);
+}
+
+#[test]
+fn test_malformed_utf8_is_replaced() {
+    let input = DecodedInput::with_encoding(
+        b"\xEF\xBB\xBFa\xC0b",
+        lexer_framework::Encoding::for_label(b"utf-8").unwrap(),
+    );
+    assert_eq!(input.text(), "a\u{FFFD}b");
+    assert_eq!(input.original_offset(0), 3);
+    assert_eq!(input.original_offset(4), 5);
+}
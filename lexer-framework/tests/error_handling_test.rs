@@ -15,22 +15,6 @@ impl LexToken for TestToken {
             TestToken::Char { position, .. } => *position,
         })
     }
-
-    fn is_eof(&self) -> bool {
-        false
-    }
-
-    fn is_newline(&self) -> bool {
-        false
-    }
-
-    fn is_whitespace(&self) -> bool {
-        false
-    }
-
-    fn is_indent(&self) -> bool {
-        false
-    }
 }
 
 // Rule that never matches
@@ -312,3 +296,81 @@ fn test_size_hint_updates() {
     assert!(upper2.is_some());
     assert!(upper2.unwrap() < 11); // Should be less after consuming
 }
+
+#[test]
+fn test_no_matching_rules_reports_error() {
+    use lexer_framework::Severity;
+
+    let rules: RuleSet<TestToken> = vec![Box::new(NeverMatchRule)];
+    let mut lexer = Lexer::from_str("hello", rules);
+    assert_eq!(lexer.next(), None);
+
+    let diagnostics = lexer.context_mut().take_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert!(diagnostics[0].message.contains("'h'"));
+}
+
+#[test]
+fn test_diagnostics_routed_to_configured_sink() {
+    use lexer_framework::{Diagnostic, Diagnostics, Severity};
+    use std::sync::{Arc, Mutex};
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = seen.clone();
+    let diagnostics = Diagnostics::callback(move |d: Diagnostic| log.lock().unwrap().push(d))
+        .with_min_severity(Severity::Error)
+        .with_limit(1);
+
+    let rules: RuleSet<TestToken> = vec![Box::new(BuggyRule)];
+    let mut lexer = Lexer::from_str("a", rules).with_diagnostics(diagnostics.clone());
+    assert_eq!(lexer.next(), None);
+    // The no-progress warning is below the minimum severity.
+    assert!(seen.lock().unwrap().is_empty());
+
+    for _ in 0..3 {
+        let rules: RuleSet<TestToken> = vec![Box::new(NeverMatchRule)];
+        let mut lexer = Lexer::from_str("x", rules).with_diagnostics(diagnostics.clone());
+        assert_eq!(lexer.next(), None);
+    }
+    assert_eq!(seen.lock().unwrap().len(), 1);
+    assert_eq!(diagnostics.suppressed(), 2);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(
+    expected = "strict mode: rule `BuggyRule` returned a token without consuming input at 1:1"
+)]
+fn test_strict_mode_panics_on_no_progress() {
+    let rules: RuleSet<TestToken> = vec![Box::new(BuggyRule)];
+    let mut lexer = Lexer::from_str("a", rules).with_strict(true);
+    assert!(lexer.is_strict());
+    lexer.next_token();
+}
+
+#[test]
+fn test_strict_mode_accepts_well_behaved_rules() {
+    let rules: RuleSet<TestToken> = vec![Box::new(NeverMatchRule), Box::new(NormalRule)];
+    let tokens: Vec<_> = Lexer::from_str("ab", rules).with_strict(true).collect();
+    assert_eq!(tokens.len(), 2);
+}
+
+/// Counts its attempts and never matches.
+struct CountingRule(std::rc::Rc<std::cell::Cell<usize>>);
+
+impl<Ctx: LexContext> LexingRule<Ctx, TestToken> for CountingRule {
+    fn try_match(&mut self, _ctx: &mut Ctx) -> Option<TestToken> {
+        self.0.set(self.0.get() + 1);
+        None
+    }
+}
+
+#[test]
+fn test_unmatched_input_is_lexed_once() {
+    let attempts = std::rc::Rc::new(std::cell::Cell::new(0));
+    let rules: RuleSet<TestToken> = vec![Box::new(CountingRule(attempts.clone()))];
+    let mut lexer = Lexer::from_str("hello", rules);
+    assert_eq!(lexer.next(), None);
+    assert_eq!(attempts.get(), 1);
+}
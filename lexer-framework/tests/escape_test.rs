@@ -0,0 +1,63 @@
+//! Escape sequence decoding tests.
+
+use lexer_framework::{decode_escape, DefaultContext, EscapeErrorKind, LexContext};
+
+fn decode(input: &str) -> (Result<char, EscapeErrorKind>, usize) {
+    let mut ctx = DefaultContext::new(input);
+    let result = decode_escape(&mut ctx).map_err(|err| err.kind);
+    (result, ctx.offset())
+}
+
+#[test]
+fn test_simple_escapes() {
+    assert_eq!(decode(r"\n").0, Ok('\n'));
+    assert_eq!(decode(r"\t").0, Ok('\t'));
+    assert_eq!(decode(r#"\""#).0, Ok('"'));
+    assert_eq!(decode(r"\/").0, Ok('/'));
+    assert_eq!(decode(r"\b").0, Ok('\u{0008}'));
+}
+
+#[test]
+fn test_unicode_escape() {
+    assert_eq!(decode(r"\u00e9rest"), (Ok('é'), 6));
+    assert_eq!(decode(r"\u4f60"), (Ok('你'), 6));
+}
+
+#[test]
+fn test_surrogate_pair() {
+    assert_eq!(decode(r"\ud83d\ude00"), (Ok('😀'), 12));
+}
+
+#[test]
+fn test_lone_high_surrogate_does_not_consume_next_char() {
+    let (result, offset) = decode(r"\ud83dx");
+    assert_eq!(result, Err(EscapeErrorKind::LoneSurrogate(0xD83D)));
+    assert_eq!(offset, 6);
+}
+
+#[test]
+fn test_lone_low_surrogate() {
+    assert_eq!(
+        decode(r"\ude00").0,
+        Err(EscapeErrorKind::LoneSurrogate(0xDE00))
+    );
+}
+
+#[test]
+fn test_invalid_escapes() {
+    assert_eq!(decode(r"\q").0, Err(EscapeErrorKind::Unknown('q')));
+    assert_eq!(decode(r"\u12g4").0, Err(EscapeErrorKind::InvalidUnicode));
+    assert_eq!(decode(r"\u12").0, Err(EscapeErrorKind::UnexpectedEof));
+    assert_eq!(decode("\\").0, Err(EscapeErrorKind::UnexpectedEof));
+}
+
+#[test]
+fn test_invalid_hex_digit_is_not_consumed() {
+    // The cursor stops on `g`, and on the closing quote of `"\u12"`.
+    assert_eq!(decode(r"\u12g4"), (Err(EscapeErrorKind::InvalidUnicode), 4));
+    assert_eq!(
+        decode(r#"\u12""#),
+        (Err(EscapeErrorKind::InvalidUnicode), 4)
+    );
+    assert_eq!(decode(r"\qx"), (Err(EscapeErrorKind::Unknown('q')), 2));
+}
@@ -0,0 +1,68 @@
+//! Parser-to-lexer feedback tests (`StreamingSignal::Feedback`, `LexContext::feedback`).
+#![cfg(feature = "streaming")]
+
+use common_framework::{Feedback, Inbound, StreamingSignal};
+use lexer_framework::{
+    DefaultContext, LexContext, LexToken, Lexer, LexingRule, StreamingLexContext,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Type(String),
+    Ident(String),
+}
+
+impl LexToken for Tok {}
+
+/// Feedback declaring a type name, as a parser would after `typedef T;`.
+struct TypeName(String);
+
+/// Lexes words, classifying those declared through feedback as types.
+struct WordRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, Tok> for WordRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        ctx.consume_while(|ch| ch == ' ');
+        let word = ctx.consume_while(|ch| ch != ' ').to_string();
+        if word.is_empty() {
+            return None;
+        }
+        if ctx.feedback::<TypeName>().any(|name| name.0 == word) {
+            Some(Tok::Type(word))
+        } else {
+            Some(Tok::Ident(word))
+        }
+    }
+}
+
+fn lexer(input: &str) -> Lexer<StreamingLexContext, Tok> {
+    let mut context = StreamingLexContext::new();
+    context.push_str(input);
+    context.mark_finished();
+    Lexer::new(context, vec![Box::new(WordRule)])
+}
+
+#[test]
+fn test_feedback_changes_later_tokens() {
+    let mut lexer = lexer("T x T");
+    assert_eq!(lexer.next(), Some(Tok::Ident("T".to_string())));
+
+    Inbound::<Tok, ()>::handle_signal(
+        &mut lexer,
+        StreamingSignal::Feedback(Feedback::new(TypeName("T".to_string()))),
+    );
+    assert_eq!(lexer.next(), Some(Tok::Ident("x".to_string())));
+    assert_eq!(lexer.next(), Some(Tok::Type("T".to_string())));
+}
+
+#[test]
+fn test_feedback_is_filtered_by_type() {
+    let mut ctx = DefaultContext::new("");
+    ctx.receive_feedback(Feedback::new(TypeName("a".to_string())));
+    ctx.receive_feedback(Feedback::new(42u32));
+    ctx.receive_feedback(Feedback::new(TypeName("b".to_string())));
+
+    let names: Vec<_> = ctx.feedback::<TypeName>().map(|n| n.0.as_str()).collect();
+    assert_eq!(names, ["a", "b"]);
+    assert_eq!(ctx.feedback::<u32>().count(), 1);
+}
@@ -0,0 +1,63 @@
+//! Tests for the prototyping token type and its rules.
+
+use lexer_framework::{
+    DefaultContext, GenericRule, GenericToken, LexToken, Lexer, LexingRule, Position, TokenKind,
+};
+
+fn rules() -> Vec<Box<dyn LexingRule<DefaultContext, GenericToken>>> {
+    vec![
+        Box::new(GenericRule::literal("let", "let").with_priority(1)),
+        Box::new(GenericRule::literal("==", "==").with_priority(1)),
+        Box::new(GenericRule::literal("=", "=")),
+        Box::new(GenericRule::identifier()),
+        Box::new(GenericRule::number()),
+        Box::new(GenericRule::whitespace()),
+        Box::new(GenericRule::newline()),
+    ]
+}
+
+#[test]
+fn test_generic_rules_kinds_and_text() {
+    let tokens: Vec<GenericToken> = Lexer::from_str("let x = 42\nx == 7", rules()).collect();
+    let pairs: Vec<(&str, &str)> = tokens
+        .iter()
+        .filter(|token| !token.is_whitespace())
+        .map(|token| (token.kind.as_str(), token.text.as_ref()))
+        .collect();
+    assert_eq!(
+        pairs,
+        [
+            ("let", "let"),
+            ("ident", "x"),
+            ("=", "="),
+            ("number", "42"),
+            ("newline", "\n"),
+            ("ident", "x"),
+            ("==", "=="),
+            ("number", "7"),
+        ]
+    );
+    assert!(tokens.iter().any(|token| token.is_newline()));
+}
+
+#[test]
+fn test_generic_token_span() {
+    let tokens: Vec<GenericToken> = Lexer::from_str("x\n  42", rules()).collect();
+    let number = tokens.iter().find(|token| token.is("number")).unwrap();
+    assert_eq!(number.span, (Position::at(2, 3, 4), Position::at(2, 5, 6)));
+    assert_eq!(number.position(), Some(Position::at(2, 3, 4)));
+    assert_eq!(tokens[1].kind(), Some(TokenKind::Newline));
+    assert_eq!(number.to_string(), "number(\"42\")");
+}
+
+#[test]
+fn test_generic_literal_rolls_back_partial_match() {
+    let rules: Vec<Box<dyn LexingRule<DefaultContext, GenericToken>>> = vec![
+        Box::new(GenericRule::literal("arrow", "->").with_priority(1)),
+        Box::new(GenericRule::literal("dash", "-")),
+    ];
+    let kinds: Vec<String> = Lexer::from_str("-->", rules)
+        .map(|token| token.kind.to_string())
+        .collect();
+    assert_eq!(kinds, ["dash", "arrow"]);
+}
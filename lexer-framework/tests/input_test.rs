@@ -0,0 +1,198 @@
+//! Lexers fed on demand (`NeedInput` / `SupplyInput`, `InputProducer`).
+#![cfg(feature = "streaming")]
+
+use common_framework::{Inbound, Outbound, StreamingSignal};
+use lexer_framework::{
+    GenericRule, GenericToken, InputProducer, LexContext, Lexer, LexingRule, ReaderInput,
+    StreamingLexContext, WordRule, WordToken,
+};
+use std::sync::mpsc;
+
+type Signal = StreamingSignal<WordToken, ()>;
+
+fn word_lexer() -> Lexer<StreamingLexContext, WordToken> {
+    Lexer::new(StreamingLexContext::new(), vec![Box::new(WordRule)])
+}
+
+/// Drives `lexer` to the end, answering `NeedInput` from `input`.
+fn drain(
+    lexer: &mut Lexer<StreamingLexContext, WordToken>,
+    input: &mut dyn InputProducer,
+) -> Vec<String> {
+    let mut words = Vec::new();
+    loop {
+        match Outbound::<WordToken, ()>::next_signal(lexer) {
+            Some(StreamingSignal::SupplyToken(token)) => words.push(token.text),
+            Some(StreamingSignal::NeedInput) => {
+                let answer: Signal = match input.next_chunk() {
+                    Some(text) => StreamingSignal::SupplyInput(text),
+                    None => StreamingSignal::EndOfInput,
+                };
+                lexer.handle_signal(answer);
+            }
+            Some(StreamingSignal::EndOfInput) => return words,
+            other => panic!("unexpected signal {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn test_lexer_asks_for_input_until_it_ends() {
+    let mut lexer = word_lexer();
+    assert!(matches!(
+        Outbound::<WordToken, ()>::next_signal(&mut lexer),
+        Some(StreamingSignal::NeedInput)
+    ));
+
+    lexer.handle_signal(Signal::SupplyInput("let x ".to_string()));
+    // `x ` reaches the end of the buffer, so it is held back in case more
+    // of it follows.
+    let first: Vec<_> = lexer.by_ref().map(|token| token.text).collect();
+    assert_eq!(first, ["let"]);
+    assert!(matches!(
+        Outbound::<WordToken, ()>::next_signal(&mut lexer),
+        Some(StreamingSignal::NeedInput)
+    ));
+
+    lexer.handle_signal(Signal::SupplyInput("= 1".to_string()));
+    lexer.handle_signal(Signal::EndOfInput);
+    let rest: Vec<_> = lexer.by_ref().map(|token| token.text).collect();
+    assert_eq!(rest, ["x", "=", "1"]);
+    assert!(matches!(
+        Outbound::<WordToken, ()>::next_signal(&mut lexer),
+        Some(StreamingSignal::EndOfInput)
+    ));
+    // Running out of buffered input is not an error.
+    assert!(lexer.context_mut().take_diagnostics().is_empty());
+}
+
+#[test]
+fn test_channel_input_ends_when_senders_are_dropped() {
+    let (sender, mut receiver) = mpsc::channel();
+    for chunk in ["alpha ", "beta\n", "gamma"] {
+        sender.send(chunk.to_string()).unwrap();
+    }
+    drop(sender);
+
+    let words = drain(&mut word_lexer(), &mut receiver);
+    assert_eq!(words, ["alpha", "beta", "gamma"]);
+    assert_eq!(receiver.next_chunk(), None);
+}
+
+#[test]
+fn test_reader_input_keeps_split_characters_whole() {
+    let input = "héllo wörld ok";
+    let mut reader = ReaderInput::with_chunk_size(input.as_bytes(), 1);
+    let mut text = String::new();
+    while let Some(chunk) = reader.next_chunk() {
+        text.push_str(&chunk);
+    }
+    assert_eq!(text, input);
+    assert!(reader.take_error().is_none());
+
+    let mut reader = ReaderInput::with_chunk_size("one two".as_bytes(), 4);
+    assert_eq!(drain(&mut word_lexer(), &mut reader), ["one", "two"]);
+}
+
+/// A `"`-delimited string that asks for more input when unterminated.
+struct StringRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, GenericToken> for StringRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<GenericToken> {
+        let (start, offset) = (ctx.position(), ctx.offset());
+        if ctx.advance()? != '"' {
+            return None;
+        }
+        ctx.consume_while(|c| c != '"');
+        if ctx.advance().is_none() {
+            return ctx.pending();
+        }
+        let text = ctx.slice_from(offset);
+        Some(GenericToken::new("string", text, (start, ctx.position())))
+    }
+
+    fn priority(&self) -> i32 {
+        1
+    }
+}
+
+fn kinds_and_texts(lexer: &mut Lexer<StreamingLexContext, GenericToken>) -> Vec<String> {
+    lexer
+        .by_ref()
+        .map(|token| format!("{}:{}", token.kind, token.text.as_ref()))
+        .collect()
+}
+
+#[test]
+fn test_token_split_across_chunks_is_lexed_whole() {
+    let rules: Vec<Box<dyn LexingRule<StreamingLexContext, GenericToken>>> = vec![
+        Box::new(GenericRule::number()),
+        Box::new(GenericRule::whitespace()),
+    ];
+    let mut lexer = Lexer::new(StreamingLexContext::new(), rules);
+    lexer.context_mut().push_input("7 12");
+    assert_eq!(kinds_and_texts(&mut lexer), ["number:7", "whitespace: "]);
+    assert!(lexer.is_suspended());
+
+    lexer.context_mut().push_input("34");
+    lexer.context_mut().finish_input();
+    assert_eq!(kinds_and_texts(&mut lexer), ["number:1234"]);
+    assert!(!lexer.is_suspended());
+    assert!(lexer.context_mut().take_diagnostics().is_empty());
+}
+
+#[test]
+fn test_pending_rule_suspends_instead_of_falling_back() {
+    let rules: Vec<Box<dyn LexingRule<StreamingLexContext, GenericToken>>> = vec![
+        Box::new(StringRule),
+        Box::new(GenericRule::literal("quote", "\"")),
+        Box::new(GenericRule::matching("text", |c| c != '"')),
+    ];
+    let mut lexer = Lexer::new(StreamingLexContext::new(), rules);
+    lexer.context_mut().push_input("\"ab");
+    // Without `pending`, the lone quote would be lexed as `quote`.
+    assert!(kinds_and_texts(&mut lexer).is_empty());
+    assert!(matches!(
+        Outbound::<GenericToken, ()>::next_signal(&mut lexer),
+        Some(StreamingSignal::NeedInput)
+    ));
+
+    lexer.handle_signal(StreamingSignal::<GenericToken, ()>::SupplyInput(
+        "c\" x".into(),
+    ));
+    lexer.handle_signal(StreamingSignal::<GenericToken, ()>::EndOfInput);
+    assert_eq!(kinds_and_texts(&mut lexer), ["string:\"abc\"", "text: x"]);
+
+    // Once the input is finished, `pending` is a plain failure.
+    let rules: Vec<Box<dyn LexingRule<StreamingLexContext, GenericToken>>> = vec![
+        Box::new(StringRule),
+        Box::new(GenericRule::literal("quote", "\"")),
+    ];
+    let mut lexer = Lexer::new(StreamingLexContext::from("\"ab"), rules);
+    assert_eq!(
+        lexer.next().map(|token| token.kind.to_string()),
+        Some("quote".into())
+    );
+}
+
+/// Counts its attempts and never matches.
+struct CountingRule(std::rc::Rc<std::cell::Cell<usize>>);
+
+impl<Ctx: LexContext> LexingRule<Ctx, GenericToken> for CountingRule {
+    fn try_match(&mut self, _ctx: &mut Ctx) -> Option<GenericToken> {
+        self.0.set(self.0.get() + 1);
+        None
+    }
+}
+
+#[test]
+fn test_unmatched_input_is_lexed_once_while_open() {
+    let attempts = std::rc::Rc::new(std::cell::Cell::new(0));
+    let rules: Vec<Box<dyn LexingRule<StreamingLexContext, GenericToken>>> =
+        vec![Box::new(CountingRule(attempts.clone()))];
+    let mut lexer = Lexer::new(StreamingLexContext::new(), rules);
+    lexer.context_mut().push_input("x");
+    assert_eq!(lexer.next(), None);
+    assert_eq!(attempts.get(), 1);
+    assert!(!lexer.is_suspended());
+}
@@ -1,6 +1,8 @@
 //! Integration tests that exercise the framework end-to-end.
 
-use lexer_framework::{DefaultContext, LexContext, LexToken, Lexer, LexingRule, Position};
+use lexer_framework::{
+    DefaultContext, LexContext, LexToken, Lexer, LexingRule, Position, TokenKind,
+};
 
 type RuleSet<Tok> = Vec<Box<dyn LexingRule<DefaultContext, Tok>>>;
 
@@ -26,20 +28,12 @@ impl LexToken for Token {
         })
     }
 
-    fn is_eof(&self) -> bool {
-        matches!(self, Token::Eof { .. })
-    }
-
-    fn is_newline(&self) -> bool {
-        false
-    }
-
-    fn is_whitespace(&self) -> bool {
-        matches!(self, Token::Whitespace { .. })
-    }
-
-    fn is_indent(&self) -> bool {
-        false
+    fn kind(&self) -> Option<TokenKind> {
+        match self {
+            Token::Eof { .. } => Some(TokenKind::Eof),
+            Token::Whitespace { .. } => Some(TokenKind::Whitespace),
+            _ => None,
+        }
     }
 }
 
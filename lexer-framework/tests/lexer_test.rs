@@ -1,4 +1,6 @@
-use lexer_framework::{DefaultContext, LexContext, LexToken, Lexer, LexingRule, Position};
+use lexer_framework::{
+    DefaultContext, LexContext, LexToken, Lexer, LexingRule, Position, TokenKind, WordToken,
+};
 
 type RuleSet<Tok> = Vec<Box<dyn LexingRule<DefaultContext, Tok>>>;
 
@@ -20,20 +22,8 @@ impl LexToken for TestToken {
         })
     }
 
-    fn is_eof(&self) -> bool {
-        matches!(self, TestToken::Eof { .. })
-    }
-
-    fn is_newline(&self) -> bool {
-        false
-    }
-
-    fn is_whitespace(&self) -> bool {
-        false
-    }
-
-    fn is_indent(&self) -> bool {
-        false
+    fn kind(&self) -> Option<TokenKind> {
+        matches!(self, TestToken::Eof { .. }).then_some(TokenKind::Eof)
     }
 }
 
@@ -112,6 +102,32 @@ where
     // No quick_check implementation - uses default None
 }
 
+// Rule that lexes 'a' as C, at the same priority as ARule
+struct ShadowARule;
+
+impl<Ctx> LexingRule<Ctx, TestToken> for ShadowARule
+where
+    Ctx: LexContext,
+{
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<TestToken> {
+        if ctx.peek() == Some('a') {
+            let position = ctx.position();
+            ctx.advance();
+            Some(TestToken::C { position })
+        } else {
+            None
+        }
+    }
+
+    fn priority(&self) -> i32 {
+        10 // Same priority as ARule
+    }
+
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char == Some('a'))
+    }
+}
+
 // EOF rule
 struct EofRule;
 
@@ -322,6 +338,58 @@ fn test_lexer_iterator_take() {
     );
 }
 
+#[test]
+fn test_lexer_tokenize_into_reuses_buffer() {
+    let mut buffer = Vec::new();
+
+    let rules: RuleSet<TestToken> = vec![Box::new(ARule), Box::new(BRule)];
+    let mut lexer = Lexer::from_str("abab", rules);
+    assert_eq!(lexer.tokenize_into(&mut buffer), 4);
+    let capacity = buffer.capacity();
+
+    buffer.clear();
+    let rules: RuleSet<TestToken> = vec![Box::new(ARule), Box::new(BRule)];
+    let mut lexer = Lexer::from_str("ba", rules);
+    assert_eq!(lexer.tokenize_into(&mut buffer), 2);
+    assert_eq!(buffer.capacity(), capacity);
+    assert!(matches!(buffer[0], TestToken::B { .. }));
+    assert!(matches!(buffer[1], TestToken::A { .. }));
+}
+
+#[test]
+fn test_lexer_next_chunk() {
+    let rules: RuleSet<TestToken> = vec![Box::new(ARule), Box::new(BRule)];
+    let mut lexer = Lexer::from_str("abababa", rules);
+
+    let first = lexer.next_chunk(3);
+    assert_eq!(first.len(), 3);
+    assert!(!first.spilled());
+    assert_eq!(lexer.next_chunk(3).len(), 3);
+    let last = lexer.next_chunk(3);
+    assert_eq!(last.len(), 1);
+    assert!(matches!(last[0], TestToken::A { .. }));
+    assert!(lexer.next_chunk(3).is_empty());
+}
+
+#[test]
+fn test_lexer_next_chunk_into() {
+    let rules: RuleSet<TestToken> = vec![Box::new(ARule), Box::new(BRule)];
+    let mut lexer = Lexer::from_str("abab", rules);
+    let mut buffer = Vec::with_capacity(2);
+
+    let mut total = 0;
+    loop {
+        buffer.clear();
+        let added = lexer.next_chunk_into(2, &mut buffer);
+        if added == 0 {
+            break;
+        }
+        assert_eq!(buffer.len(), added);
+        total += added;
+    }
+    assert_eq!(total, 4);
+}
+
 #[test]
 fn test_lexer_empty_input() {
     let rules: RuleSet<TestToken> = vec![Box::new(EofRule)];
@@ -382,3 +450,101 @@ fn test_lexer_checkpoint_restore() {
         })
     );
 }
+
+#[test]
+fn test_lexer_group_toggle() {
+    let rules: RuleSet<TestToken> = vec![Box::new(ARule)];
+    let mut lexer = Lexer::from_str("abab", rules);
+    lexer.add_group("b-extension", vec![Box::new(BRule)]);
+    assert_eq!(lexer.is_group_enabled("b-extension"), Some(true));
+
+    assert!(matches!(lexer.next_token(), Some(TestToken::A { .. })));
+    assert!(matches!(lexer.next_token(), Some(TestToken::B { .. })));
+
+    // Disabling the group takes effect on the next token.
+    assert!(lexer.set_group_enabled("b-extension", false));
+    assert!(matches!(lexer.next_token(), Some(TestToken::A { .. })));
+    assert_eq!(lexer.next_token(), None);
+
+    assert!(lexer.set_group_enabled("b-extension", true));
+    assert!(matches!(lexer.next_token(), Some(TestToken::B { .. })));
+}
+
+#[test]
+fn test_lexer_group_rules_keep_priority_order() {
+    let rules: RuleSet<TestToken> = vec![Box::new(CRule)];
+    let mut lexer = Lexer::from_str("a", rules);
+    lexer.add_group("extra", vec![Box::new(ARule), Box::new(BRule)]);
+
+    let names: Vec<&str> = lexer.rules().iter().map(|rule| rule.name()).collect();
+    assert_eq!(names, ["ARule", "BRule", "CRule"]);
+    assert_eq!(lexer.groups().iter().collect::<Vec<_>>(), [("extra", true)]);
+}
+
+#[test]
+fn test_lexer_unknown_group() {
+    let rules: RuleSet<TestToken> = vec![Box::new(ARule)];
+    let mut lexer = Lexer::from_str("a", rules);
+    assert!(!lexer.set_group_enabled("missing", false));
+    assert_eq!(lexer.is_group_enabled("missing"), None);
+}
+
+#[test]
+fn test_lexer_priority_tie_uses_registration_order() {
+    let rules: RuleSet<TestToken> = vec![Box::new(ShadowARule), Box::new(BRule), Box::new(ARule)];
+    let mut lexer = Lexer::from_str("a", rules);
+
+    // ShadowARule and ARule tie on priority; ShadowARule was registered first.
+    assert_eq!(lexer.registration_order(), [0, 2, 1]);
+    assert!(matches!(lexer.next_token(), Some(TestToken::C { .. })));
+
+    let rules: RuleSet<TestToken> = vec![Box::new(ARule), Box::new(ShadowARule)];
+    let mut lexer = Lexer::from_str("a", rules);
+    assert!(matches!(lexer.next_token(), Some(TestToken::A { .. })));
+}
+
+#[test]
+fn test_lexer_priority_conflicts() {
+    let rules: RuleSet<TestToken> = vec![Box::new(ARule), Box::new(BRule)];
+    let mut lexer = Lexer::from_str("a", rules);
+    assert!(lexer.priority_conflicts().is_empty());
+
+    lexer.add_group("shadow", vec![Box::new(ShadowARule)]);
+    let conflicts = lexer.priority_conflicts();
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].priority, 10);
+    assert_eq!(conflicts[0].winner, "ARule");
+    assert_eq!(conflicts[0].shadowed, "ShadowARule");
+    assert_eq!(conflicts[0].chars, ['a']);
+
+    // Disabled rules cannot shadow anything.
+    lexer.set_group_enabled("shadow", false);
+    assert!(lexer.priority_conflicts().is_empty());
+}
+
+#[test]
+fn test_lexer_words() {
+    let words: Vec<WordToken> = Lexer::words("  let x =\n\t42  ").collect();
+    assert_eq!(
+        words,
+        vec![
+            WordToken {
+                text: "let".to_string(),
+                position: Position::at(1, 3, 2)
+            },
+            WordToken {
+                text: "x".to_string(),
+                position: Position::at(1, 7, 6)
+            },
+            WordToken {
+                text: "=".to_string(),
+                position: Position::at(1, 9, 8)
+            },
+            WordToken {
+                text: "42".to_string(),
+                position: Position::at(2, 2, 11)
+            },
+        ]
+    );
+    assert_eq!(Lexer::words(" \n ").count(), 0);
+}
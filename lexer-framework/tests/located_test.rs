@@ -0,0 +1,76 @@
+//! Compact token position tests (`CompactPos`, `LineIndex`, `Located`).
+
+use lexer_framework::{
+    CompactPos, CompactToken, DefaultContext, LexContext, LexToken, Lexer, LexingRule, LineIndex,
+    Located, Position, TokenKind,
+};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Word(CompactPos),
+    Newline(CompactPos),
+}
+
+impl LexToken for Tok {
+    fn kind(&self) -> Option<TokenKind> {
+        matches!(self, Tok::Newline(_)).then_some(TokenKind::Newline)
+    }
+}
+
+impl CompactToken for Tok {
+    fn compact_pos(&self) -> Option<CompactPos> {
+        match self {
+            Tok::Word(pos) | Tok::Newline(pos) => Some(*pos),
+        }
+    }
+}
+
+/// Lexes words and newlines, skipping spaces, and records the cursor's full
+/// position of each token for comparison.
+struct WordRule(Rc<RefCell<Vec<Position>>>);
+
+impl LexingRule<DefaultContext, Tok> for WordRule {
+    fn try_match(&mut self, ctx: &mut DefaultContext) -> Option<Tok> {
+        ctx.consume_while(|c| c == ' ');
+        let first = ctx.peek()?;
+        self.0.borrow_mut().push(ctx.position());
+        let pos = ctx.compact_pos();
+        if first == '\n' {
+            ctx.advance();
+            return Some(Tok::Newline(pos));
+        }
+        ctx.consume_while(|c| !c.is_whitespace());
+        Some(Tok::Word(pos))
+    }
+}
+
+#[test]
+fn test_located_tokens_report_cursor_positions() {
+    let input = "let 变量 = 1\n  print 变量\n";
+    let expected = Rc::new(RefCell::new(Vec::new()));
+    let mut lexer = Lexer::from_str(input, vec![Box::new(WordRule(expected.clone()))]);
+    let tokens = lexer.tokenize();
+    assert_eq!(tokens.len(), 8);
+    assert!(tokens.iter().all(|token| token.position().is_none()));
+
+    let index = Arc::new(LineIndex::new(input));
+    let located: Vec<_> = tokens
+        .into_iter()
+        .map(|token| Located::new(token, index.clone()))
+        .collect();
+    let positions: Vec<_> = located.iter().filter_map(|t| t.position()).collect();
+
+    assert_eq!(positions, *expected.borrow());
+    assert_eq!(positions[5], Position::at(2, 3, 17));
+    assert_eq!(positions[6], Position::at(2, 9, 23));
+    assert!(located[4].is_newline());
+}
+
+#[test]
+fn test_token_size_with_compact_pos() {
+    assert_eq!(std::mem::size_of::<CompactPos>(), 4);
+    assert!(std::mem::size_of::<Tok>() < std::mem::size_of::<Position>());
+}
@@ -0,0 +1,145 @@
+//! Merged producer tests (`MergeProducer`, `MergePolicy`).
+#![cfg(feature = "streaming")]
+
+use common_framework::{Outbound, StreamingSignal};
+use lexer_framework::{LexToken, MergePolicy, MergeProducer, Position, TokenKind, TokenProducer};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Word(&'static str),
+    Eof(Position),
+}
+
+impl LexToken for Tok {
+    fn position(&self) -> Option<Position> {
+        match self {
+            Tok::Word(_) => None,
+            Tok::Eof(position) => Some(*position),
+        }
+    }
+
+    fn kind(&self) -> Option<TokenKind> {
+        matches!(self, Tok::Eof(_)).then_some(TokenKind::Eof)
+    }
+}
+
+/// Replays fixed tokens, then signals the end of its input.
+struct Replay(std::vec::IntoIter<Tok>);
+
+impl Replay {
+    fn new(words: &[&'static str], eof: Position) -> Self {
+        let mut tokens: Vec<Tok> = words.iter().map(|word| Tok::Word(word)).collect();
+        tokens.push(Tok::Eof(eof));
+        Replay(tokens.into_iter())
+    }
+}
+
+impl TokenProducer<Tok> for Replay {
+    fn poll_token(&mut self) -> Option<Tok> {
+        self.0.next()
+    }
+}
+
+impl Outbound<Tok, ()> for Replay {
+    fn next_signal(&mut self) -> Option<StreamingSignal<Tok, ()>> {
+        Some(match self.0.next() {
+            Some(token) => StreamingSignal::SupplyToken(token),
+            None => StreamingSignal::EndOfInput,
+        })
+    }
+}
+
+fn sources() -> Vec<Replay> {
+    vec![
+        Replay::new(&["a1", "a2", "a3"], Position::at(1, 9, 8)),
+        Replay::new(&["b1"], Position::at(2, 9, 8)),
+    ]
+}
+
+fn drain<P: TokenProducer<Tok>>(producer: &mut P) -> Vec<Tok> {
+    let mut tokens = Vec::new();
+    while let Some(token) = producer.poll_token() {
+        tokens.push(token);
+    }
+    tokens
+}
+
+#[test]
+fn test_merge_sequential_keeps_only_the_last_eof() {
+    let mut merged = MergeProducer::sequential(sources());
+    assert_eq!(
+        drain(&mut merged),
+        [
+            Tok::Word("a1"),
+            Tok::Word("a2"),
+            Tok::Word("a3"),
+            Tok::Word("b1"),
+            Tok::Eof(Position::at(2, 9, 8)),
+        ]
+    );
+    assert!(merged.is_finished());
+    assert_eq!(merged.last_source(), Some(1));
+}
+
+#[test]
+fn test_merge_round_robin_interleaves_sources() {
+    let mut merged = MergeProducer::round_robin(sources());
+    let mut tokens = Vec::new();
+    while let Some(token) = merged.poll_token() {
+        tokens.push((merged.last_source().unwrap(), token));
+    }
+    assert_eq!(
+        tokens,
+        [
+            (0, Tok::Word("a1")),
+            (1, Tok::Word("b1")),
+            (0, Tok::Word("a2")),
+            (0, Tok::Word("a3")),
+            (0, Tok::Eof(Position::at(1, 9, 8))),
+        ]
+    );
+}
+
+#[test]
+fn test_merge_custom_policy_picks_each_source() {
+    // Always prefer the last running source.
+    let policy = MergePolicy::custom(|running| *running.last().unwrap());
+    let mut merged = MergeProducer::new(sources(), policy);
+    assert_eq!(
+        drain(&mut merged),
+        [
+            Tok::Word("b1"),
+            Tok::Word("a1"),
+            Tok::Word("a2"),
+            Tok::Word("a3"),
+            Tok::Eof(Position::at(1, 9, 8)),
+        ]
+    );
+}
+
+#[test]
+fn test_merge_signals_end_once_all_sources_end() {
+    let mut merged = MergeProducer::round_robin(sources());
+    let mut signals = Vec::new();
+    loop {
+        let signal = Outbound::<Tok, ()>::next_signal(&mut merged).unwrap();
+        let end = matches!(signal, StreamingSignal::EndOfInput);
+        signals.push(signal);
+        if end {
+            break;
+        }
+    }
+    let supplied = signals
+        .iter()
+        .filter(|signal| matches!(signal, StreamingSignal::SupplyToken(_)))
+        .count();
+    assert_eq!(supplied, 5);
+    assert!(matches!(
+        signals.as_slice(),
+        [
+            ..,
+            StreamingSignal::SupplyToken(Tok::Eof(_)),
+            StreamingSignal::EndOfInput
+        ]
+    ));
+}
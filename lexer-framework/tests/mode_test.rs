@@ -0,0 +1,135 @@
+//! Lexer mode stack tests (`ModalContext`, `ModeContext`, `InMode`).
+
+use lexer_framework::{
+    DefaultContext, InMode, LexContext, Lexer, LexingRule, ModalContext, ModeContext,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Text,
+    Code,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Text(String),
+    Open,
+    Close,
+    Word(String),
+}
+
+type Ctx = ModalContext<DefaultContext, Mode>;
+
+/// `<` enters code mode, `>` leaves it.
+struct DelimiterRule;
+
+impl LexingRule<Ctx, Tok> for DelimiterRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        match ctx.advance()? {
+            '<' => {
+                ctx.push_mode(Mode::Code);
+                Some(Tok::Open)
+            }
+            '>' => {
+                ctx.pop_mode()?;
+                Some(Tok::Close)
+            }
+            _ => None,
+        }
+    }
+
+    fn priority(&self) -> i32 {
+        10
+    }
+}
+
+struct TextRule;
+
+impl LexingRule<Ctx, Tok> for TextRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        let text = ctx.consume_while(|ch| ch != '<');
+        (!text.is_empty()).then(|| Tok::Text(text.to_string()))
+    }
+}
+
+struct WordRule;
+
+impl LexingRule<Ctx, Tok> for WordRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        ctx.consume_while(|ch| ch == ' ');
+        let word = ctx.consume_while(|ch| ch.is_alphanumeric());
+        (!word.is_empty()).then(|| Tok::Word(word.to_string()))
+    }
+}
+
+fn lex(input: &str) -> Vec<Tok> {
+    let rules: Vec<Box<dyn LexingRule<Ctx, Tok>>> = vec![
+        Box::new(InMode::new(Mode::Text, TextRule)),
+        Box::new(InMode::new(Mode::Code, WordRule)),
+        Box::new(DelimiterRule),
+    ];
+    Lexer::new(ModalContext::new(DefaultContext::new(input), Mode::Text), rules).collect()
+}
+
+#[test]
+fn test_rules_follow_the_current_mode() {
+    assert_eq!(
+        lex("hi <a b> there"),
+        vec![
+            Tok::Text("hi ".into()),
+            Tok::Open,
+            Tok::Word("a".into()),
+            Tok::Word("b".into()),
+            Tok::Close,
+            Tok::Text(" there".into()),
+        ]
+    );
+}
+
+#[test]
+fn test_initial_mode_is_never_popped() {
+    let mut ctx = ModalContext::new(DefaultContext::new(""), Mode::Text);
+    assert_eq!(ctx.pop_mode(), None);
+    assert_eq!(*ctx.mode(), Mode::Text);
+    assert_eq!(ctx.mode_depth(), 1);
+}
+
+#[test]
+fn test_nested_modes() {
+    let mut ctx = ModalContext::new(DefaultContext::new(""), Mode::Text);
+    ctx.push_mode(Mode::Code);
+    ctx.push_mode(Mode::Text);
+    assert_eq!(ctx.modes(), &[Mode::Text, Mode::Code, Mode::Text]);
+    assert_eq!(ctx.pop_mode(), Some(Mode::Text));
+    assert_eq!(*ctx.mode(), Mode::Code);
+}
+
+#[test]
+fn test_restore_undoes_mode_changes() {
+    let mut ctx = ModalContext::new(DefaultContext::new("<>x"), Mode::Text);
+    let start = ctx.checkpoint();
+
+    ctx.advance();
+    ctx.push_mode(Mode::Code);
+    let inside = ctx.checkpoint();
+    ctx.advance();
+    ctx.pop_mode();
+    assert_eq!(*ctx.mode(), Mode::Text);
+
+    ctx.restore(inside);
+    assert_eq!(*ctx.mode(), Mode::Code);
+    ctx.restore(start);
+    assert_eq!(*ctx.mode(), Mode::Text);
+    assert_eq!(ctx.mode_depth(), 1);
+}
+
+#[test]
+fn test_restore_keeps_changes_made_before_checkpoint() {
+    let mut ctx = ModalContext::new(DefaultContext::new("<x"), Mode::Text);
+    ctx.advance();
+    ctx.push_mode(Mode::Code);
+    let after_open = ctx.checkpoint();
+    ctx.advance();
+    ctx.restore(after_open);
+    assert_eq!(*ctx.mode(), Mode::Code);
+}
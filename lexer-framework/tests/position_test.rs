@@ -1,4 +1,4 @@
-use lexer_framework::Position;
+use lexer_framework::{DefaultContext, LexContext, Lexer, LexingRule, Position, SourceText};
 
 #[test]
 fn test_position_new() {
@@ -46,3 +46,38 @@ fn test_position_copy() {
     };
     assert_ne!(pos1, pos3);
 }
+
+/// Reports the position of every word.
+struct WordRule;
+
+impl LexingRule<DefaultContext, Position> for WordRule {
+    fn try_match(&mut self, ctx: &mut DefaultContext) -> Option<Position> {
+        ctx.consume_while(char::is_whitespace);
+        let position = ctx.position();
+        (!ctx.consume_while(|ch| !ch.is_whitespace()).is_empty()).then_some(position)
+    }
+}
+
+#[test]
+fn test_untracked_lexer_positions_resolve_through_source_text() {
+    let source = SourceText::new("let 变量
+  = 1;
+");
+    let tracked: Vec<_> = Lexer::from_str(source.text(), vec![Box::new(WordRule)]).collect();
+    let untracked: Vec<_> = Lexer::from_source(&source, vec![Box::new(WordRule)]).collect();
+
+    assert_eq!(untracked[1], Position::at(0, 0, 4));
+    let resolved: Vec<_> = untracked.iter().map(|&pos| source.resolve(pos)).collect();
+    assert_eq!(resolved, tracked);
+}
+
+#[test]
+fn test_source_text_offset_round_trip() {
+    let source = SourceText::new("ab\n变量 x\n");
+    assert_eq!(source.position_at(10), Some(Position::at(2, 4, 10)));
+    assert_eq!(source.offset_at(2, 4), Some(10));
+    // Inside the three-byte `变`, and past the end.
+    assert_eq!(source.position_at(4), None);
+    assert_eq!(source.position_at(20), None);
+    assert_eq!(source.offset_at(2, 6), None);
+}
@@ -0,0 +1,263 @@
+//! Include preprocessing tests (`Preprocessor`, `SourceMap`).
+
+use lexer_framework::{
+    DefaultContext, Directive, LexContext, LexToken, LexingRule, Position, PreprocessError,
+    Preprocessor, SourceMap, TokenKind,
+};
+use std::collections::HashMap;
+use std::io;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Word(String, Position),
+    /// `@name` includes the source `name`.
+    Include(String),
+    Space,
+    /// `$` ends the source.
+    End,
+}
+
+impl LexToken for Tok {
+    fn position(&self) -> Option<Position> {
+        match self {
+            Tok::Word(_, position) => Some(*position),
+            _ => None,
+        }
+    }
+
+    fn kind(&self) -> Option<TokenKind> {
+        match self {
+            Tok::End => Some(TokenKind::Eof),
+            Tok::Space => Some(TokenKind::Whitespace),
+            _ => None,
+        }
+    }
+
+    fn relocated(self, base: Position) -> Self {
+        match self {
+            Tok::Word(word, position) => Tok::Word(word, position.rebase(base)),
+            other => other,
+        }
+    }
+}
+
+struct WordRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, Tok> for WordRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        let position = ctx.position();
+        let include = ctx.peek() == Some('@');
+        if include {
+            ctx.advance();
+        }
+        let word = ctx.consume_while(|c| c.is_alphanumeric() || c == '/' || c == '.');
+        if word.is_empty() {
+            return None;
+        }
+        Some(if include {
+            Tok::Include(word.to_string())
+        } else {
+            Tok::Word(word.to_string(), position)
+        })
+    }
+}
+
+struct SpaceRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, Tok> for SpaceRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        if ctx.consume_while(|c| c.is_whitespace()).is_empty() {
+            return None;
+        }
+        Some(Tok::Space)
+    }
+}
+
+struct EndRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, Tok> for EndRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        if ctx.peek() != Some('$') {
+            return None;
+        }
+        ctx.advance();
+        Some(Tok::End)
+    }
+}
+
+fn rules() -> Vec<Box<dyn LexingRule<DefaultContext, Tok>>> {
+    vec![Box::new(WordRule), Box::new(SpaceRule), Box::new(EndRule)]
+}
+
+fn preprocess(
+    sources: &mut SourceMap,
+    files: &[(&str, &str)],
+    root: &str,
+) -> Result<Vec<Tok>, PreprocessError> {
+    let files: HashMap<String, String> = files
+        .iter()
+        .map(|(name, text)| (name.to_string(), text.to_string()))
+        .collect();
+    let root = sources.add(root, files[root].as_str());
+    let mut preprocessor = Preprocessor::new(sources, rules, |name, _| {
+        files
+            .get(name)
+            .cloned()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    });
+    preprocessor.run(root, |token| match token {
+        Tok::Include(name) => Directive::Include(name),
+        Tok::Space => Directive::Drop,
+        token => Directive::Keep(token),
+    })
+}
+
+#[test]
+fn test_includes_are_spliced_with_source_positions() {
+    let mut sources = SourceMap::new();
+    let tokens = preprocess(
+        &mut sources,
+        &[
+            ("main", "start @lib\nend"),
+            ("lib", "one\n  @leaf two"),
+            ("leaf", "deep"),
+        ],
+        "main",
+    )
+    .unwrap();
+
+    let located: Vec<(String, &str, Position)> = tokens
+        .iter()
+        .map(|token| {
+            let Tok::Word(word, position) = token else {
+                panic!("unexpected token {:?}", token);
+            };
+            let (id, local) = sources.lookup(position.offset).unwrap();
+            assert_eq!((local.line, local.column), (position.line, position.column));
+            (word.clone(), sources.file(id).name(), local)
+        })
+        .collect();
+    assert_eq!(
+        located,
+        vec![
+            ("start".to_string(), "main", Position::at(1, 1, 0)),
+            ("one".to_string(), "lib", Position::at(1, 1, 0)),
+            ("deep".to_string(), "leaf", Position::at(1, 1, 0)),
+            ("two".to_string(), "lib", Position::at(2, 9, 12)),
+            ("end".to_string(), "main", Position::at(2, 1, 11)),
+        ]
+    );
+    assert_eq!(sources.files().count(), 3);
+}
+
+#[test]
+fn test_repeated_includes_reuse_the_source() {
+    let mut sources = SourceMap::new();
+    let tokens = preprocess(&mut sources, &[("main", "@lib @lib"), ("lib", "x")], "main").unwrap();
+    assert_eq!(tokens.len(), 2);
+    assert_eq!(tokens[0], tokens[1]);
+    assert_eq!(sources.files().count(), 2);
+}
+
+#[test]
+fn test_include_errors() {
+    let cycle = preprocess(
+        &mut SourceMap::new(),
+        &[("a", "@b"), ("b", "x @c"), ("c", "@b")],
+        "a",
+    );
+    match cycle {
+        Err(PreprocessError::Cycle(names)) => assert_eq!(names, ["b", "c", "b"]),
+        other => panic!("expected a cycle, got {:?}", other),
+    }
+
+    let missing = preprocess(&mut SourceMap::new(), &[("a", "x @gone")], "a");
+    match missing {
+        Err(error @ PreprocessError::NotFound { .. }) => {
+            assert!(error.to_string().contains("`gone` from `a`"))
+        }
+        other => panic!("expected a missing include, got {:?}", other),
+    }
+
+    // Every source includes a new one, so only the depth limit stops it.
+    let mut sources = SourceMap::new();
+    let root = sources.add("root", "@a");
+    let too_deep = Preprocessor::new(&mut sources, rules, |name, _| Ok(format!("@{}a", name)))
+        .with_max_depth(3)
+        .run(root, |token| match token {
+            Tok::Include(name) => Directive::Include(name),
+            token => Directive::Keep(token),
+        });
+    match too_deep {
+        Err(PreprocessError::TooDeep { name, max_depth }) => {
+            assert_eq!((name.as_str(), max_depth), ("aaa", 3))
+        }
+        other => panic!("expected the depth limit, got {:?}", other),
+    }
+}
+
+fn words(tokens: &[Tok]) -> Vec<&str> {
+    tokens
+        .iter()
+        .map(|token| match token {
+            Tok::Word(word, _) => word.as_str(),
+            Tok::End => "$",
+            other => panic!("unexpected token {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn test_same_name_from_two_directories() {
+    let mut sources = SourceMap::new();
+    let tokens = preprocess(
+        &mut sources,
+        &[
+            ("main", "@a/x @b/x @a/x"),
+            ("a/x", "@util"),
+            ("b/x", "@util @../a/util"),
+            ("a/util", "one"),
+            ("b/util", "two"),
+        ],
+        "main",
+    )
+    .unwrap();
+
+    assert_eq!(words(&tokens), ["one", "two", "one", "one"]);
+    let names: Vec<&str> = sources.files().map(|(_, file)| file.name()).collect();
+    assert_eq!(names, ["main", "a/x", "a/util", "b/x", "b/util"]);
+}
+
+#[test]
+fn test_included_eof_tokens_are_dropped() {
+    let tokens = preprocess(
+        &mut SourceMap::new(),
+        &[("main", "@lib y $"), ("lib", "x $")],
+        "main",
+    )
+    .unwrap();
+    assert_eq!(words(&tokens), ["x", "y", "$"]);
+}
+
+#[test]
+fn test_custom_resolver() {
+    // Every include resolves to one shared directory.
+    let mut sources = SourceMap::new();
+    let files = HashMap::from([("inc/lib", "x"), ("dir/main", "@lib @lib")]);
+    let root = sources.add("dir/main", files["dir/main"]);
+    let tokens = Preprocessor::new(&mut sources, rules, |name, _| {
+        files
+            .get(name)
+            .map(|text| text.to_string())
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    })
+    .with_resolver(|name, _| format!("inc/{}", name))
+    .run(root, |token| match token {
+        Tok::Include(name) => Directive::Include(name),
+        Tok::Space => Directive::Drop,
+        token => Directive::Keep(token),
+    })
+    .unwrap();
+    assert_eq!(words(&tokens), ["x", "x"]);
+    assert_eq!(sources.files().count(), 2);
+}
@@ -17,22 +17,6 @@ impl LexToken for TestToken {
             | TestToken::Other { position, .. } => *position,
         })
     }
-
-    fn is_eof(&self) -> bool {
-        false
-    }
-
-    fn is_newline(&self) -> bool {
-        false
-    }
-
-    fn is_whitespace(&self) -> bool {
-        false
-    }
-
-    fn is_indent(&self) -> bool {
-        false
-    }
 }
 
 // Rule with quick_check that returns Some(false) for non-digits
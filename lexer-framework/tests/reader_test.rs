@@ -0,0 +1,106 @@
+//! Reader-backed lexing tests (`Lexer::from_reader`, `ReaderLexContext`).
+#![cfg(feature = "streaming")]
+
+use lexer_framework::{LexContext, Lexer, LexingRule, Position, ReaderLexContext};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Word(String, Position),
+    Space,
+}
+
+struct WordRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, Tok> for WordRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        let position = ctx.position();
+        let word = ctx.consume_while(|ch| !ch.is_whitespace());
+        if word.is_empty() {
+            return None;
+        }
+        Some(Tok::Word(word.to_string(), position))
+    }
+}
+
+struct SpaceRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, Tok> for SpaceRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        if ctx.consume_while(char::is_whitespace).is_empty() {
+            return None;
+        }
+        Some(Tok::Space)
+    }
+
+    fn priority(&self) -> i32 {
+        1
+    }
+}
+
+fn rules<Ctx: LexContext>() -> Vec<Box<dyn LexingRule<Ctx, Tok>>> {
+    vec![Box::new(WordRule), Box::new(SpaceRule)]
+}
+
+fn words(tokens: &[Tok]) -> Vec<&str> {
+    tokens
+        .iter()
+        .filter_map(|tok| match tok {
+            Tok::Word(word, _) => Some(word.as_str()),
+            Tok::Space => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_tokens_are_not_split_at_chunk_boundaries() {
+    let input = "alpha beta\ngamma delta";
+    let context = ReaderLexContext::with_chunk_size(input.as_bytes(), 1);
+    let tokens: Vec<Tok> = Lexer::new(context, rules()).collect();
+    assert_eq!(words(&tokens), ["alpha", "beta", "gamma", "delta"]);
+}
+
+#[test]
+fn test_from_reader_matches_batch_lexing() {
+    let input = "one two  three\n\tfour";
+    let streamed: Vec<Tok> = Lexer::from_reader(input.as_bytes(), rules()).collect();
+    let batch: Vec<Tok> = Lexer::from_str(input, rules()).collect();
+    assert_eq!(streamed, batch);
+}
+
+#[test]
+fn test_positions_stay_absolute_after_discarding_input() {
+    let input = "ab cd\nef";
+    let context = ReaderLexContext::with_chunk_size(input.as_bytes(), 2);
+    let tokens: Vec<Tok> = Lexer::new(context, rules()).collect();
+    let Some(Tok::Word(word, position)) = tokens.last() else {
+        panic!("expected a trailing word");
+    };
+    assert_eq!(word, "ef");
+    assert_eq!((position.line, position.column, position.offset), (2, 1, 6));
+}
+
+#[test]
+fn test_multibyte_chars_split_across_reads() {
+    let input = "你好 😀";
+    let context = ReaderLexContext::with_chunk_size(input.as_bytes(), 1);
+    let tokens: Vec<Tok> = Lexer::new(context, rules()).collect();
+    assert_eq!(words(&tokens), ["你好", "😀"]);
+}
+
+#[test]
+fn test_invalid_utf8_is_replaced() {
+    let input: &[u8] = b"ok \xff bad\xe4";
+    let tokens: Vec<Tok> = Lexer::from_reader(input, rules()).collect();
+    assert_eq!(words(&tokens), ["ok", "\u{FFFD}", "bad\u{FFFD}"]);
+}
+
+#[test]
+fn test_buffer_stays_bounded() {
+    let input = "word ".repeat(10_000);
+    let mut lexer = Lexer::new(
+        ReaderLexContext::with_chunk_size(input.as_bytes(), 64),
+        rules(),
+    );
+    assert_eq!(lexer.by_ref().count(), 20_000);
+    assert!(lexer.context().peak_buffered() <= 64 + "word ".len());
+}
@@ -0,0 +1,152 @@
+//! Token rewriting tests (`Lexer::rewrite_tokens`).
+
+use lexer_framework::{
+    LexContext, LexToken, Lexer, LexingRule, Lookahead, Position, Rewrite, RewriteError,
+};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Tok(String, Position);
+
+impl LexToken for Tok {
+    fn position(&self) -> Option<Position> {
+        Some(self.1)
+    }
+}
+
+/// Matches a word and the whitespace after it.
+struct WordRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, Tok> for WordRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        let position = ctx.position();
+        let word = ctx.consume_while(|ch| !ch.is_whitespace());
+        if word.is_empty() {
+            return None;
+        }
+        ctx.consume_while(char::is_whitespace);
+        Some(Tok(word.to_string(), position))
+    }
+}
+
+fn rules<Ctx: LexContext>() -> Vec<Box<dyn LexingRule<Ctx, Tok>>> {
+    vec![Box::new(WordRule)]
+}
+
+/// A tiny preprocessor: `define NAME VALUE`, `ifdef NAME ... endif`, and
+/// expansion of defined names.
+#[derive(Default)]
+struct Macros {
+    defines: HashMap<String, String>,
+}
+
+impl Macros {
+    fn rewrite(&mut self, input: &mut Lookahead<'_, Tok>) -> Rewrite<Tok> {
+        let word = |input: &mut Lookahead<'_, Tok>, n| input.peek(n).map(|tok| tok.0.clone());
+        let name = input.first().0.clone();
+        match name.as_str() {
+            "define" => {
+                let (Some(key), Some(value)) = (word(input, 1), word(input, 2)) else {
+                    return Rewrite::Keep;
+                };
+                self.defines.insert(key, value);
+                Rewrite::Drop(3)
+            }
+            "ifdef" => {
+                let Some(key) = word(input, 1) else {
+                    return Rewrite::Keep;
+                };
+                if self.defines.contains_key(&key) {
+                    return Rewrite::Drop(2);
+                }
+                let mut n = 2;
+                while let Some(word) = word(input, n) {
+                    n += 1;
+                    if word == "endif" {
+                        break;
+                    }
+                }
+                Rewrite::Drop(n)
+            }
+            "endif" => Rewrite::Drop(1),
+            _ => match self.defines.get(&name) {
+                Some(value) => Rewrite::Replace {
+                    consumed: 1,
+                    tokens: vec![Tok(value.clone(), input.position().unwrap())],
+                },
+                None => Rewrite::Keep,
+            },
+        }
+    }
+}
+
+fn words(tokens: &[Tok]) -> Vec<(&str, usize)> {
+    tokens
+        .iter()
+        .map(|Tok(word, position)| (word.as_str(), position.offset))
+        .collect()
+}
+
+#[test]
+fn test_rewrite_expands_macros_at_the_use_site() {
+    let mut macros = Macros::default();
+    let tokens: Vec<Tok> = Lexer::from_str("define A B define B c x A y", rules())
+        .rewrite_tokens(|input: &mut Lookahead<'_, Tok>| macros.rewrite(input))
+        .collect();
+    // `A` expands to `B`, which is expanded again; the result keeps the
+    // position of `A`.
+    assert_eq!(words(&tokens), vec![("x", 22), ("c", 24), ("y", 26)]);
+}
+
+#[test]
+fn test_rewrite_drops_disabled_blocks() {
+    let mut macros = Macros::default();
+    let tokens: Vec<Tok> = Lexer::from_str(
+        "define ON 1 ifdef ON a endif ifdef OFF b c endif d",
+        rules(),
+    )
+    .rewrite_tokens(|input: &mut Lookahead<'_, Tok>| macros.rewrite(input))
+    .collect();
+    assert_eq!(words(&tokens), vec![("a", 21), ("d", 49)]);
+}
+
+#[test]
+fn test_rewrite_recursion_limit() {
+    let mut macros = Macros::default();
+    let mut rewriter = Lexer::from_str("ok define A A A never", rules())
+        .rewrite_tokens(|input: &mut Lookahead<'_, Tok>| macros.rewrite(input))
+        .with_max_depth(8);
+    let tokens: Vec<Tok> = rewriter.by_ref().collect();
+    assert_eq!(words(&tokens), vec![("ok", 0)]);
+    assert_eq!(
+        rewriter.take_error(),
+        Some(RewriteError {
+            position: Some(Position::at(1, 15, 14)),
+            max_depth: 8,
+        })
+    );
+}
+
+#[cfg(feature = "streaming")]
+#[test]
+fn test_rewrite_as_streaming_producer() {
+    use common_framework::{Outbound, StreamingSignal};
+
+    let mut macros = Macros::default();
+    let mut rewriter = Lexer::from_str("define A B A", rules())
+        .rewrite_tokens(|input: &mut Lookahead<'_, Tok>| macros.rewrite(input));
+    let mut signals = Vec::new();
+    while let Some(signal) = Outbound::<Tok, ()>::next_signal(&mut rewriter) {
+        let end = matches!(signal, StreamingSignal::EndOfInput);
+        signals.push(signal);
+        if end {
+            break;
+        }
+    }
+    match signals.as_slice() {
+        [StreamingSignal::SupplyToken(token), StreamingSignal::EndOfInput] => {
+            assert_eq!(token, &Tok("B".to_string(), Position::at(1, 12, 11)))
+        }
+        other => panic!("unexpected signals {:?}", other),
+    }
+}
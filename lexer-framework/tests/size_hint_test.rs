@@ -13,22 +13,6 @@ impl LexToken for TestToken {
             TestToken::Char { position, .. } => *position,
         })
     }
-
-    fn is_eof(&self) -> bool {
-        false
-    }
-
-    fn is_newline(&self) -> bool {
-        false
-    }
-
-    fn is_whitespace(&self) -> bool {
-        false
-    }
-
-    fn is_indent(&self) -> bool {
-        false
-    }
 }
 
 struct CharRule;
@@ -128,3 +112,37 @@ fn test_size_hint_mixed() {
     assert_eq!(lower, 0);
     assert_eq!(upper, Some(11)); // 11 characters total
 }
+
+#[test]
+fn test_estimated_remaining_needs_samples_or_hint() {
+    let rules: RuleSet<TestToken> = vec![Box::new(CharRule)];
+    let mut lexer = Lexer::from_str("hello world", rules);
+    assert_eq!(lexer.estimated_remaining(), None);
+
+    lexer.reserve_hint(2);
+    assert_eq!(lexer.estimated_remaining(), Some(6)); // ceil(11 / 2)
+}
+
+#[test]
+fn test_estimated_remaining_tracks_density() {
+    let rules: RuleSet<TestToken> = vec![Box::new(CharRule)];
+    let mut lexer = Lexer::from_str("你好世界", rules);
+
+    lexer.next();
+    // One 3-byte token seen, 9 bytes left.
+    assert_eq!(lexer.estimated_remaining(), Some(3));
+    // The bounds stay conservative.
+    assert_eq!(lexer.size_hint().0, 0);
+}
+
+#[test]
+fn test_tokenize_reserves_from_hint() {
+    let input = "a".repeat(1000);
+    let rules: RuleSet<TestToken> = vec![Box::new(CharRule)];
+    let mut lexer = Lexer::from_str(input, rules);
+    lexer.reserve_hint(1);
+
+    let tokens = lexer.tokenize();
+    assert_eq!(tokens.len(), 1000);
+    assert_eq!(tokens.capacity(), 1000);
+}
@@ -0,0 +1,112 @@
+//! Sub-lexing tests (`Lexer::sublex`).
+
+use lexer_framework::{LexContext, LexToken, Lexer, LexingRule, Position, TextSlice, TokenKind};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Fence(TextSlice),
+    Word(String, Position),
+    Space,
+}
+
+impl LexToken for Tok {
+    fn position(&self) -> Option<Position> {
+        match self {
+            Tok::Word(_, position) => Some(*position),
+            _ => None,
+        }
+    }
+
+    fn kind(&self) -> Option<TokenKind> {
+        matches!(self, Tok::Space).then_some(TokenKind::Whitespace)
+    }
+
+    fn relocated(self, base: Position) -> Self {
+        match self {
+            Tok::Word(word, position) => Tok::Word(word, position.rebase(base)),
+            other => other,
+        }
+    }
+}
+
+/// Captures the body of a `[...]` block without looking inside it.
+struct FenceRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, Tok> for FenceRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        if ctx.peek() != Some('[') {
+            return None;
+        }
+        ctx.advance();
+        let body = ctx.consume_while(|ch| ch != ']');
+        ctx.advance()?;
+        Some(Tok::Fence(body))
+    }
+
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char == Some('['))
+    }
+}
+
+struct WordRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, Tok> for WordRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        let position = ctx.position();
+        let word = ctx.consume_while(|ch| ch.is_alphanumeric());
+        if word.is_empty() {
+            return None;
+        }
+        Some(Tok::Word(word.to_string(), position))
+    }
+}
+
+struct SpaceRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, Tok> for SpaceRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        if ctx.consume_while(char::is_whitespace).is_empty() {
+            return None;
+        }
+        Some(Tok::Space)
+    }
+}
+
+fn fence_body(input: &str) -> TextSlice {
+    let outer = Lexer::from_str(
+        input,
+        vec![Box::new(FenceRule), Box::new(WordRule), Box::new(SpaceRule)],
+    );
+    outer
+        .filter_map(|tok| match tok {
+            Tok::Fence(body) => Some(body),
+            _ => None,
+        })
+        .next()
+        .expect("input contains a fence")
+}
+
+#[test]
+fn test_sublex_positions_point_into_outer_input() {
+    let body = fence_body("  [ab cd]");
+    let inner = Lexer::sublex(body, vec![Box::new(WordRule), Box::new(SpaceRule)]);
+    assert_eq!(
+        inner,
+        [
+            Tok::Word("ab".to_string(), Position::at(1, 4, 3)),
+            Tok::Space,
+            Tok::Word("cd".to_string(), Position::at(1, 7, 6)),
+        ]
+    );
+}
+
+#[test]
+fn test_sublex_across_lines_and_multibyte_text() {
+    let body = fence_body("\n é [x\ny]");
+    let words: Vec<Position> = Lexer::sublex(body, vec![Box::new(WordRule), Box::new(SpaceRule)])
+        .iter()
+        .filter_map(LexToken::position)
+        .collect();
+    // 'é' is two bytes but one column.
+    assert_eq!(words, [Position::at(2, 5, 6), Position::at(3, 1, 8)]);
+}
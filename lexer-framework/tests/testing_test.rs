@@ -0,0 +1,74 @@
+//! Tests for the rule unit-testing helpers.
+
+use lexer_framework::{assert_lexes, lex_single, DefaultContext, LexContext, LexingRule};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(u64),
+    Plus,
+}
+
+struct NumberRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, Token> for NumberRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Token> {
+        let digits = ctx.consume_while(|c| c.is_ascii_digit());
+        digits.parse().ok().map(Token::Number)
+    }
+
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        Some(first_char?.is_ascii_digit())
+    }
+}
+
+struct PlusRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, Token> for PlusRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Token> {
+        if ctx.peek() == Some('+') {
+            ctx.advance();
+            Some(Token::Plus)
+        } else {
+            None
+        }
+    }
+}
+
+#[test]
+fn test_lex_single_returns_token_and_length() {
+    assert_eq!(
+        lex_single(&mut NumberRule, "42+1"),
+        Some((Token::Number(42), 2))
+    );
+    assert_eq!(lex_single(&mut NumberRule, "+1"), None);
+}
+
+#[test]
+fn test_assert_lexes_forms() {
+    assert_lexes!(NumberRule, "7", [Token::Number(7)]);
+    assert_lexes!(
+        [NumberRule, PlusRule],
+        "1+23",
+        [Token::Number(1), Token::Plus, Token::Number(_)]
+    );
+
+    let rules: Vec<Box<dyn LexingRule<DefaultContext, Token>>> =
+        vec![Box::new(PlusRule), Box::new(NumberRule)];
+    assert_lexes!(rules = rules, "++", [Token::Plus, Token::Plus]);
+}
+
+#[test]
+#[should_panic(expected = "stopped at offset 1")]
+fn test_assert_lexes_reports_unlexed_input() {
+    assert_lexes!(NumberRule, "1+", [Token::Number(1)]);
+}
+
+#[test]
+#[should_panic(expected = "expected Token::Number(_)")]
+fn test_assert_lexes_reports_mismatched_token() {
+    assert_lexes!(
+        [NumberRule, PlusRule],
+        "1+",
+        [Token::Number(1), Token::Number(_)]
+    );
+}
@@ -1,6 +1,8 @@
 //! Trait tests covering LexToken and LexingRule behavior.
 
-use lexer_framework::{DefaultContext, LexContext, LexToken, LexingRule, Position};
+use lexer_framework::{
+    DefaultContext, LexContext, LexToken, Lexer, LexingRule, Position, RuleId, TokenKind,
+};
 
 type RuleSet<Tok> = Vec<Box<dyn LexingRule<DefaultContext, Tok>>>;
 
@@ -111,6 +113,37 @@ fn test_lex_token_is_indent() {
     assert!(token.is_indent());
 }
 
+#[test]
+fn test_lex_token_defaults_follow_kind() {
+    #[derive(Debug, Clone)]
+    struct Plain;
+    impl LexToken for Plain {}
+
+    #[derive(Debug, Clone)]
+    enum Simple {
+        Word,
+        Newline,
+        End,
+    }
+    impl LexToken for Simple {
+        fn kind(&self) -> Option<TokenKind> {
+            match self {
+                Simple::Word => None,
+                Simple::Newline => Some(TokenKind::Newline),
+                Simple::End => Some(TokenKind::Eof),
+            }
+        }
+    }
+
+    assert_eq!(Plain.position(), None);
+    assert_eq!(Plain.kind(), None);
+    assert!(!Plain.is_eof() && !Plain.is_newline() && !Plain.is_whitespace() && !Plain.is_indent());
+
+    assert!(!Simple::Word.is_newline());
+    assert!(Simple::Newline.is_newline() && !Simple::Newline.is_eof());
+    assert!(Simple::End.is_eof() && !Simple::End.is_whitespace());
+}
+
 // Test LexingRule trait
 struct SimpleRule {
     match_char: char,
@@ -242,3 +275,61 @@ fn test_lexing_rule_quick_check_implemented() {
     let token2 = lexer2.next_token();
     assert!(token2.is_none()); // quick_check should skip this rule
 }
+
+#[test]
+fn test_lexing_rule_default_name_and_id() {
+    let rule = SimpleRule { match_char: 'a' };
+    let rule: &dyn LexingRule<DefaultContext, TestToken> = &rule;
+    assert_eq!(rule.name(), "SimpleRule");
+    assert_eq!(rule.id(), RuleId::of("SimpleRule"));
+}
+
+#[test]
+fn test_lexing_rule_custom_name() {
+    struct NamedRule;
+
+    impl<Ctx> LexingRule<Ctx, TestToken> for NamedRule
+    where
+        Ctx: LexContext,
+    {
+        fn try_match(&mut self, _ctx: &mut Ctx) -> Option<TestToken> {
+            None
+        }
+
+        fn name(&self) -> &str {
+            "identifier"
+        }
+    }
+
+    let rule: &dyn LexingRule<DefaultContext, TestToken> = &NamedRule;
+    assert_eq!(rule.name(), "identifier");
+    assert_eq!(rule.id(), RuleId::of("identifier"));
+}
+
+#[test]
+fn test_lexer_rule_introspection() {
+    struct LowRule;
+
+    impl<Ctx> LexingRule<Ctx, TestToken> for LowRule
+    where
+        Ctx: LexContext,
+    {
+        fn try_match(&mut self, _ctx: &mut Ctx) -> Option<TestToken> {
+            None
+        }
+    }
+
+    let rules: RuleSet<TestToken> =
+        vec![Box::new(LowRule), Box::new(SimpleRule { match_char: 'a' })];
+    let mut lexer = Lexer::from_str("a", rules);
+
+    let names: Vec<&str> = lexer.rules().iter().map(|rule| rule.name()).collect();
+    assert_eq!(names, ["SimpleRule", "LowRule"]);
+
+    assert!(lexer.last_rule().is_none());
+    lexer.next_token();
+    assert_eq!(
+        lexer.last_rule().map(|rule| rule.name()),
+        Some("SimpleRule")
+    );
+}
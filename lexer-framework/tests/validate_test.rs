@@ -0,0 +1,125 @@
+//! Tests for the rule-contract checking context.
+
+use lexer_framework::{
+    DefaultContext, LexContext, Lexer, LexingRule, Position, ValidatingContext, ViolationKind,
+};
+
+type Ctx = ValidatingContext<DefaultContext>;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Word(String);
+
+/// Well-behaved: letters, or nothing.
+struct LetterRule;
+
+impl<C: LexContext> LexingRule<C, Word> for LetterRule {
+    fn try_match(&mut self, ctx: &mut C) -> Option<Word> {
+        let text = ctx.consume_while(char::is_alphabetic);
+        (!text.is_empty()).then(|| Word(text.to_string()))
+    }
+}
+
+/// Consumes digits, then fails unless they end in `!`.
+struct GreedyRule;
+
+impl<C: LexContext> LexingRule<C, Word> for GreedyRule {
+    fn try_match(&mut self, ctx: &mut C) -> Option<Word> {
+        let text = ctx.consume_while(|c| c.is_ascii_digit());
+        if ctx.peek() != Some('!') {
+            return None;
+        }
+        ctx.advance();
+        Some(Word(text.to_string()))
+    }
+
+    fn priority(&self) -> i32 {
+        1
+    }
+}
+
+/// Returns a token for a space without consuming it.
+struct StallRule;
+
+impl<C: LexContext> LexingRule<C, Word> for StallRule {
+    fn try_match(&mut self, ctx: &mut C) -> Option<Word> {
+        (ctx.peek()? == ' ').then(|| Word(String::new()))
+    }
+}
+
+/// Matches `ab`, rolling back its own lookahead when it fails.
+struct AbRule;
+
+impl<C: LexContext> LexingRule<C, Word> for AbRule {
+    fn try_match(&mut self, ctx: &mut C) -> Option<Word> {
+        ctx.speculate(|ctx| {
+            (ctx.advance() == Some('a') && ctx.advance() == Some('b')).then(|| Word("ab".into()))
+        })
+    }
+
+    fn priority(&self) -> i32 {
+        1
+    }
+}
+
+/// Consumes one digit.
+struct DigitRule;
+
+impl<C: LexContext> LexingRule<C, Word> for DigitRule {
+    fn try_match(&mut self, ctx: &mut C) -> Option<Word> {
+        let digit = ctx.advance().filter(char::is_ascii_digit)?;
+        Some(Word(digit.to_string()))
+    }
+}
+
+fn lex(input: &str, rules: Vec<Box<dyn LexingRule<Ctx, Word>>>) -> Lexer<Ctx, Word> {
+    let mut lexer = Lexer::new(ValidatingContext::new(DefaultContext::new(input)), rules);
+    lexer.by_ref().for_each(drop);
+    lexer
+}
+
+#[test]
+fn test_validating_context_accepts_well_behaved_rules() {
+    let lexer = lex("abc", vec![Box::new(AbRule), Box::new(LetterRule)]);
+    assert!(lexer.context().violations().is_empty());
+    lexer.context().assert_valid();
+}
+
+#[test]
+fn test_validating_context_flags_consumed_and_failed() {
+    let lexer = lex(
+        "ab12",
+        vec![
+            Box::new(GreedyRule),
+            Box::new(LetterRule),
+            Box::new(DigitRule),
+        ],
+    );
+    let violations = lexer.context().violations();
+    assert_eq!(violations.len(), 2);
+    assert_eq!(violations[0].rule, "GreedyRule");
+    assert_eq!(
+        violations[0].kind,
+        ViolationKind::ConsumedAndFailed { consumed: 2 }
+    );
+    assert_eq!(violations[0].position, Position::at(1, 3, 2));
+    // The lexer rolled back, so the digits were still lexed one by one.
+    assert_eq!(lexer.context().offset(), 4);
+    assert!(violations[1].to_string().contains("consumed 1 bytes"));
+}
+
+#[test]
+fn test_validating_context_flags_no_progress() {
+    let lexer = lex("a b", vec![Box::new(LetterRule), Box::new(StallRule)]);
+    let violations = lexer.context().violations();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].rule, "StallRule");
+    assert_eq!(violations[0].kind, ViolationKind::NoProgress);
+}
+
+#[test]
+#[should_panic(expected = "lexing rules broke their contract")]
+fn test_validating_context_assert_valid_panics() {
+    lex("a b", vec![Box::new(LetterRule), Box::new(StallRule)])
+        .context()
+        .assert_valid();
+}
@@ -1,6 +1,7 @@
 use criterion::{criterion_group, criterion_main, Criterion, Throughput};
 use parser_framework::{
     parse_pratt, AstNode, DefaultContext, ParseContext, Parser, ParsingRule, Position, PrattConfig,
+    PrattSpan,
 };
 
 // --- Types ---
@@ -55,6 +56,7 @@ impl PrattConfig<DefaultContext<BenchToken>, BenchToken, BenchAst> for ExprParse
     fn parse_prefix<F>(
         &self,
         token: BenchToken,
+        _span: PrattSpan,
         ctx: &mut DefaultContext<BenchToken>,
         parser: &F,
     ) -> Option<BenchAst>
@@ -100,6 +102,7 @@ impl PrattConfig<DefaultContext<BenchToken>, BenchToken, BenchAst> for ExprParse
         &self,
         left: BenchAst,
         token: BenchToken,
+        _span: PrattSpan,
         r_bp: u8,
         ctx: &mut DefaultContext<BenchToken>,
         parser: &F,
@@ -0,0 +1,151 @@
+//! Expression preset example.
+//!
+//! Builds a complete expression grammar from a handful of token
+//! classification closures with [`ExpressionPreset`]: precedence and
+//! associativity, unary minus, parentheses and function calls.
+
+use parser_framework::{
+    AstNode, BinaryOpInfo, DefaultContext, ExpressionPreset, ParseContext, Parser, ParsingRule,
+    Position,
+};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+    LParen,
+    RParen,
+    Comma,
+    Semi,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Number(f64),
+    Var(String),
+    Unary(char, Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+    Call(Box<Expr>, Vec<Expr>),
+}
+
+impl AstNode for Expr {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Number(n) => write!(f, "{}", n),
+            Expr::Var(name) => write!(f, "{}", name),
+            Expr::Unary(op, operand) => write!(f, "({} {})", op, operand),
+            Expr::Binary(op, left, right) => write!(f, "({} {} {})", op, left, right),
+            Expr::Call(callee, args) => {
+                write!(f, "(call {}", callee)?;
+                for arg in args {
+                    write!(f, " {}", arg)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+fn operator(token: Token) -> char {
+    match token {
+        Token::Op(op) => op,
+        _ => unreachable!("only operator tokens are classified as operators"),
+    }
+}
+
+fn expression() -> ExpressionPreset<Token, Expr> {
+    ExpressionPreset::new(|token: &Token| match token {
+        Token::Number(n) => Some(Expr::Number(*n)),
+        Token::Ident(name) => Some(Expr::Var(name.clone())),
+        _ => None,
+    })
+    .binary(
+        |token| match token {
+            Token::Op('+' | '-') => Some(BinaryOpInfo::left(1)),
+            Token::Op('*' | '/') => Some(BinaryOpInfo::left(2)),
+            Token::Op('^') => Some(BinaryOpInfo::right(4)),
+            _ => None,
+        },
+        |op, left, right| Expr::Binary(operator(op), Box::new(left), Box::new(right)),
+    )
+    .unary(
+        |token| (*token == Token::Op('-')).then_some(3),
+        |op, operand| Expr::Unary(operator(op), Box::new(operand)),
+    )
+    .parens(
+        |token| *token == Token::LParen,
+        |token| *token == Token::RParen,
+    )
+    .calls(
+        |token| *token == Token::Comma,
+        |callee, args| Expr::Call(Box::new(callee), args),
+    )
+}
+
+/// Wraps the preset so each statement also consumes its trailing `;`.
+struct StatementRule(ExpressionPreset<Token, Expr>);
+
+impl ParsingRule<DefaultContext<Token>, Token, Expr> for StatementRule {
+    fn try_parse(&mut self, ctx: &mut DefaultContext<Token>) -> Option<Expr> {
+        ctx.speculate(|ctx| {
+            let expr = self.0.parse_expr(ctx)?;
+            (ctx.advance()? == Token::Semi).then_some(expr)
+        })
+    }
+}
+
+fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(&ch) = chars.peek() {
+        if ch.is_whitespace() {
+            chars.next();
+        } else if ch.is_ascii_digit() {
+            let mut number = String::new();
+            while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit() || **d == '.') {
+                number.push(d);
+                chars.next();
+            }
+            tokens.push(Token::Number(number.parse().unwrap()));
+        } else if ch.is_alphabetic() {
+            let mut name = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_alphanumeric()) {
+                name.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Ident(name));
+        } else {
+            chars.next();
+            tokens.push(match ch {
+                '(' => Token::LParen,
+                ')' => Token::RParen,
+                ',' => Token::Comma,
+                ';' => Token::Semi,
+                op => Token::Op(op),
+            });
+        }
+    }
+    tokens
+}
+
+fn main() {
+    println!("=== Expression Preset Example ===\n");
+
+    let source = "1 + 2 * 3; 10 - 4 - 3; 2 ^ 3 ^ 2; -2 ^ 2; max(1, x + 1) * 2; (1 + 2) * f();";
+
+    let rules: Vec<Box<dyn ParsingRule<DefaultContext<Token>, Token, Expr>>> =
+        vec![Box::new(StatementRule(expression()))];
+    let mut parser = Parser::new(DefaultContext::new(tokenize(source)), rules);
+
+    for expr in parser.parse() {
+        println!("{}", expr);
+    }
+}
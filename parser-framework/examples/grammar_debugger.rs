@@ -0,0 +1,108 @@
+//! Grammar debugger example.
+//!
+//! Steps a small statement grammar through `Debugger` to find out why it
+//! stops early: the second assignment is missing its `;`.
+//!
+//! Runs a scripted session by default; pass `--interactive` to type the
+//! commands yourself, or `--dot` to print the trace as a Graphviz graph
+//! (`cargo run --example grammar_debugger -- --dot | dot -Tsvg`).
+
+use parser_framework::{
+    dump_dot, AstNode, Debugger, DefaultContext, ParseContext, Parser, ParsingRule, Position,
+};
+use std::io::{self, BufRead, Cursor};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(i64),
+    Eq,
+    Semi,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Stmt {
+    Assign(String, i64),
+    Expr(i64),
+}
+
+impl AstNode for Stmt {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+/// `name = number ;`
+struct AssignRule;
+
+impl<Ctx: ParseContext<Token>> ParsingRule<Ctx, Token, Stmt> for AssignRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Stmt> {
+        let Some(Token::Ident(name)) = ctx.advance() else {
+            return None;
+        };
+        if ctx.advance()? != Token::Eq {
+            return None;
+        }
+        let Some(Token::Number(value)) = ctx.advance() else {
+            return None;
+        };
+        (ctx.advance()? == Token::Semi).then_some(Stmt::Assign(name, value))
+    }
+
+    fn quick_check(&self, current_token: Option<&Token>) -> Option<bool> {
+        current_token.map(|token| matches!(token, Token::Ident(_)))
+    }
+}
+
+/// `number ;`
+struct ExprRule;
+
+impl<Ctx: ParseContext<Token>> ParsingRule<Ctx, Token, Stmt> for ExprRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Stmt> {
+        let Some(Token::Number(value)) = ctx.advance() else {
+            return None;
+        };
+        (ctx.advance()? == Token::Semi).then_some(Stmt::Expr(value))
+    }
+}
+
+fn main() -> io::Result<()> {
+    use Token::*;
+    let tokens = vec![
+        Ident("a".into()),
+        Eq,
+        Number(1),
+        Semi,
+        Number(2),
+        Semi,
+        Ident("b".into()),
+        Eq,
+        Number(3),
+        Ident("c".into()),
+    ];
+    let rules: Vec<Box<dyn ParsingRule<DefaultContext<Token>, Token, Stmt>>> =
+        vec![Box::new(AssignRule), Box::new(ExprRule)];
+    let mut debugger = Debugger::new(Parser::new(DefaultContext::new(tokens), rules));
+
+    if std::env::args().any(|arg| arg == "--dot") {
+        let mut steps = Vec::new();
+        while let Some(step) = debugger.step() {
+            let stuck = step.node.is_none();
+            steps.push(step);
+            if stuck {
+                break;
+            }
+        }
+        print!("{}", dump_dot(&steps));
+        Ok(())
+    } else if std::env::args().any(|arg| arg == "--interactive") {
+        debugger.repl(io::stdin().lock(), io::stdout())
+    } else {
+        let script = "step\nbreak AssignRule\ncontinue\nstep\ntokens 3\nhistory\nquit\n";
+        let input = Cursor::new(script);
+        for line in input.clone().lines() {
+            println!("# {}", line?);
+        }
+        debugger.repl(input, io::stdout())
+    }
+}
@@ -0,0 +1,59 @@
+//! Railroad diagram example.
+//!
+//! Describes a JSON-like value grammar and prints an HTML page with one
+//! railroad diagram per production:
+//! `cargo run --example railroad > grammar.html`.
+
+use parser_framework::{Grammar, GrammarItem as G};
+
+fn main() {
+    let grammar = Grammar::new()
+        .production(
+            "value",
+            G::choice(vec![
+                G::nonterminal("object"),
+                G::nonterminal("array"),
+                G::nonterminal("string"),
+                G::nonterminal("number"),
+                G::terminal("true"),
+                G::terminal("false"),
+                G::terminal("null"),
+            ]),
+        )
+        .production(
+            "object",
+            G::sequence(vec![
+                G::terminal("{"),
+                G::optional(G::separated(
+                    G::sequence(vec![
+                        G::nonterminal("string"),
+                        G::terminal(":"),
+                        G::nonterminal("value"),
+                    ]),
+                    G::terminal(","),
+                )),
+                G::terminal("}"),
+            ]),
+        )
+        .production(
+            "array",
+            G::sequence(vec![
+                G::terminal("["),
+                G::optional(G::separated(G::nonterminal("value"), G::terminal(","))),
+                G::terminal("]"),
+            ]),
+        )
+        .production(
+            "number",
+            G::sequence(vec![
+                G::optional(G::terminal("-")),
+                G::one_or_more(G::nonterminal("digit")),
+                G::optional(G::sequence(vec![
+                    G::terminal("."),
+                    G::one_or_more(G::nonterminal("digit")),
+                ])),
+            ]),
+        );
+
+    print!("{}", grammar.to_html());
+}
@@ -0,0 +1,160 @@
+//! Scope tracking example.
+//!
+//! In C-like languages `T * x;` declares a pointer if `T` names a type and
+//! multiplies two values otherwise. The parser can only tell which by
+//! remembering declarations, so the rules here run on a [`ScopedContext`]
+//! and consult its scope stack:
+//!
+//! - `typedef T;` declares `T` as a type name.
+//! - `A * B;` is a declaration of `B` if `A` is a type, else a product.
+//! - `{` / `}` open and close a scope; names declared inside shadow outer
+//!   ones until the scope closes.
+
+use parser_framework::{
+    AstNode, DefaultContext, ParseContext, Parser, ParsingRule, Position, ScopeContext,
+    ScopedContext,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Typedef,
+    Ident(String),
+    Star,
+    Semi,
+    LBrace,
+    RBrace,
+}
+
+/// What a name is bound to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Binding {
+    Type,
+    Variable,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Stmt {
+    Typedef(String),
+    Declare { ty: String, name: String },
+    Multiply(String, String),
+    Open,
+    Close,
+}
+
+impl AstNode for Stmt {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+type Ctx = ScopedContext<DefaultContext<Token>, String, Binding>;
+
+fn ident<C: ParseContext<Token>>(ctx: &mut C) -> Option<String> {
+    match ctx.advance()? {
+        Token::Ident(name) => Some(name),
+        _ => None,
+    }
+}
+
+fn expect<C: ParseContext<Token>>(ctx: &mut C, token: Token) -> Option<()> {
+    (ctx.advance()? == token).then_some(())
+}
+
+/// `typedef Name ;`
+struct TypedefRule;
+
+impl<C> ParsingRule<C, Token, Stmt> for TypedefRule
+where
+    C: ScopeContext<Token, String, Binding>,
+{
+    fn try_parse(&mut self, ctx: &mut C) -> Option<Stmt> {
+        expect(ctx, Token::Typedef)?;
+        let name = ident(ctx)?;
+        expect(ctx, Token::Semi)?;
+        ctx.declare(name.clone(), Binding::Type);
+        Some(Stmt::Typedef(name))
+    }
+}
+
+/// `A * B ;`, disambiguated by looking `A` up in the current scopes.
+struct StarRule;
+
+impl<C> ParsingRule<C, Token, Stmt> for StarRule
+where
+    C: ScopeContext<Token, String, Binding>,
+{
+    fn try_parse(&mut self, ctx: &mut C) -> Option<Stmt> {
+        let left = ident(ctx)?;
+        expect(ctx, Token::Star)?;
+        let right = ident(ctx)?;
+        expect(ctx, Token::Semi)?;
+
+        if ctx.lookup(&left) == Some(&Binding::Type) {
+            ctx.declare(right.clone(), Binding::Variable);
+            Some(Stmt::Declare {
+                ty: left,
+                name: right,
+            })
+        } else {
+            Some(Stmt::Multiply(left, right))
+        }
+    }
+}
+
+/// `{` and `}`.
+struct BlockRule;
+
+impl<C> ParsingRule<C, Token, Stmt> for BlockRule
+where
+    C: ScopeContext<Token, String, Binding>,
+{
+    fn try_parse(&mut self, ctx: &mut C) -> Option<Stmt> {
+        match ctx.advance()? {
+            Token::LBrace => {
+                ctx.enter_scope();
+                Some(Stmt::Open)
+            }
+            Token::RBrace => ctx.exit_scope().then_some(Stmt::Close),
+            _ => None,
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Vec<Token> {
+    source
+        .split_whitespace()
+        .map(|word| match word {
+            "typedef" => Token::Typedef,
+            "*" => Token::Star,
+            ";" => Token::Semi,
+            "{" => Token::LBrace,
+            "}" => Token::RBrace,
+            name => Token::Ident(name.to_string()),
+        })
+        .collect()
+}
+
+fn main() {
+    println!("=== Scoped Parser Example ===\n");
+
+    let source = "typedef T ; T * x ; { T * T ; T * y ; } T * z ;";
+    println!("Input: {}\n", source);
+
+    let rules: Vec<Box<dyn ParsingRule<Ctx, Token, Stmt>>> = vec![
+        Box::new(TypedefRule),
+        Box::new(StarRule),
+        Box::new(BlockRule),
+    ];
+    let context = ScopedContext::new(DefaultContext::new(tokenize(source)));
+    let mut parser = Parser::new(context, rules);
+
+    for stmt in parser.parse() {
+        match stmt {
+            Stmt::Typedef(name) => println!("type      {}", name),
+            Stmt::Declare { ty, name } => println!("declare   {} : {}*", name, ty),
+            Stmt::Multiply(left, right) => println!("multiply  {} * {}", left, right),
+            Stmt::Open => println!("{{"),
+            Stmt::Close => println!("}}"),
+        }
+    }
+}
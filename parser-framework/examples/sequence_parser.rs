@@ -0,0 +1,195 @@
+//! Sequence example.
+//!
+//! A program is a newline-separated list of statements, and `{ ... }`
+//! blocks nest such lists. Both are parsed with [`Sequence`]; the program
+//! level recovers from bad lines by turning them into error nodes.
+//! Assignments and calls both start with an identifier; their rules use
+//! `quick_check2` to look at the second token before being tried.
+
+use parser_framework::{
+    AstNode, DefaultContext, ParseContext, Parser, ParsingRule, Position, Sequence,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Let,
+    Ident(String),
+    Eq,
+    Number(i64),
+    Newline,
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+}
+
+#[derive(Debug, Clone)]
+enum Stmt {
+    Let(String, i64),
+    Assign(String, i64),
+    Call(String),
+    Block(Vec<Stmt>),
+    Error(Vec<Token>),
+}
+
+impl AstNode for Stmt {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+type Ctx = DefaultContext<Token>;
+
+/// `let name = number`
+struct LetRule;
+
+impl ParsingRule<Ctx, Token, Stmt> for LetRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Stmt> {
+        if ctx.advance()? != Token::Let {
+            return None;
+        }
+        let Token::Ident(name) = ctx.advance()? else {
+            return None;
+        };
+        if ctx.advance()? != Token::Eq {
+            return None;
+        }
+        let Token::Number(value) = ctx.advance()? else {
+            return None;
+        };
+        Some(Stmt::Let(name, value))
+    }
+
+    fn quick_check(&self, current_token: Option<&Token>) -> Option<bool> {
+        Some(current_token == Some(&Token::Let))
+    }
+}
+
+/// `name = number`
+struct AssignRule;
+
+impl ParsingRule<Ctx, Token, Stmt> for AssignRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Stmt> {
+        let Token::Ident(name) = ctx.advance()? else {
+            return None;
+        };
+        ctx.advance()?;
+        let Token::Number(value) = ctx.advance()? else {
+            return None;
+        };
+        Some(Stmt::Assign(name, value))
+    }
+
+    fn quick_check2(
+        &self,
+        current_token: Option<&Token>,
+        next_token: Option<&Token>,
+    ) -> Option<bool> {
+        Some(matches!(current_token, Some(Token::Ident(_))) && next_token == Some(&Token::Eq))
+    }
+}
+
+/// `name ( )`
+struct CallRule;
+
+impl ParsingRule<Ctx, Token, Stmt> for CallRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Stmt> {
+        let Token::Ident(name) = ctx.advance()? else {
+            return None;
+        };
+        ctx.advance()?;
+        if ctx.advance()? != Token::RParen {
+            return None;
+        }
+        Some(Stmt::Call(name))
+    }
+
+    fn quick_check2(
+        &self,
+        current_token: Option<&Token>,
+        next_token: Option<&Token>,
+    ) -> Option<bool> {
+        Some(matches!(current_token, Some(Token::Ident(_))) && next_token == Some(&Token::LParen))
+    }
+}
+
+/// `{ statements }`, possibly spanning several lines.
+struct BlockRule;
+
+impl ParsingRule<Ctx, Token, Stmt> for BlockRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Stmt> {
+        statements()
+            .delimited(|t| *t == Token::LBrace, |t| *t == Token::RBrace)
+            .parse(ctx)
+            .map(Stmt::Block)
+    }
+
+    fn quick_check(&self, current_token: Option<&Token>) -> Option<bool> {
+        Some(current_token == Some(&Token::LBrace))
+    }
+}
+
+fn statements() -> Sequence<Ctx, Token, Stmt> {
+    Sequence::new(
+        vec![
+            Box::new(LetRule),
+            Box::new(AssignRule),
+            Box::new(CallRule),
+            Box::new(BlockRule),
+        ],
+        |t| *t == Token::Newline,
+    )
+}
+
+fn print(stmt: &Stmt, depth: usize) {
+    let indent = "  ".repeat(depth);
+    match stmt {
+        Stmt::Let(name, value) => println!("{}let {} = {}", indent, name, value),
+        Stmt::Assign(name, value) => println!("{}{} = {}", indent, name, value),
+        Stmt::Call(name) => println!("{}call {}", indent, name),
+        Stmt::Block(body) => {
+            println!("{}block", indent);
+            body.iter().for_each(|stmt| print(stmt, depth + 1));
+        }
+        Stmt::Error(tokens) => println!("{}error: skipped {:?}", indent, tokens),
+    }
+}
+
+fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    for line in source.lines() {
+        for word in line.split_whitespace() {
+            tokens.push(match word {
+                "let" => Token::Let,
+                "=" => Token::Eq,
+                "{" => Token::LBrace,
+                "}" => Token::RBrace,
+                "(" => Token::LParen,
+                ")" => Token::RParen,
+                _ => match word.parse() {
+                    Ok(n) => Token::Number(n),
+                    Err(_) => Token::Ident(word.to_string()),
+                },
+            });
+        }
+        tokens.push(Token::Newline);
+    }
+    tokens
+}
+
+fn main() {
+    println!("=== Sequence Example ===\n");
+
+    let source = "let a = 1\n\n{ let b = 2\n  { b = 3\n    print ( ) } }\nlet = 4\nlet d = 5";
+    println!("Input:\n{}\n", source);
+
+    let program = statements()
+        .recover(|tokens, _| Stmt::Error(tokens))
+        .into_rule(|body, _| Stmt::Block(body));
+    let rules: Vec<Box<dyn ParsingRule<Ctx, Token, Stmt>>> = vec![Box::new(program)];
+    let mut parser = Parser::new(DefaultContext::new(tokenize(source)), rules);
+
+    for stmt in parser.parse() {
+        print(&stmt, 0);
+    }
+}
@@ -8,7 +8,9 @@
 //!
 //! The example parses simple arithmetic expressions with two numbers and one operator.
 
-use parser_framework::{AstNode, DefaultContext, ParseContext, Parser, ParsingRule, Position};
+use parser_framework::{
+    tokens, AstNode, DefaultContext, ParseContext, Parser, ParsingRule, Position,
+};
 
 type SimpleParserRules =
     Vec<Box<dyn ParsingRule<DefaultContext<SimpleToken>, SimpleToken, SimpleExpr>>>;
@@ -173,10 +175,8 @@ fn main() {
     println!("[Example 1] Parsing a single number:");
     println!("{}", "=".repeat(50));
 
-    let tokens1 = vec![SimpleToken::Number {
-        value: 42,
-        position: Position::new(),
-    }];
+    // `tokens!` fills in the `position` fields.
+    let tokens1 = tokens![SimpleToken; Number { value: 42 }];
 
     let rules1: SimpleParserRules = vec![Box::new(NumberRule)];
 
@@ -197,31 +197,7 @@ fn main() {
     println!("[Example 2] Parsing addition:");
     println!("{}", "=".repeat(50));
 
-    let tokens2 = vec![
-        SimpleToken::Number {
-            value: 3,
-            position: Position {
-                line: 1,
-                column: 1,
-                offset: 0,
-            },
-        },
-        SimpleToken::Plus {
-            position: Position {
-                line: 1,
-                column: 3,
-                offset: 2,
-            },
-        },
-        SimpleToken::Number {
-            value: 4,
-            position: Position {
-                line: 1,
-                column: 5,
-                offset: 4,
-            },
-        },
-    ];
+    let tokens2 = tokens![SimpleToken; Number { value: 3 }, Plus, Number { value: 4 }];
 
     let rules2: SimpleParserRules = vec![
         Box::new(BinaryRule::new(
@@ -265,31 +241,7 @@ fn main() {
     println!("[Example 3] Parsing subtraction:");
     println!("{}", "=".repeat(50));
 
-    let tokens3 = vec![
-        SimpleToken::Number {
-            value: 10,
-            position: Position {
-                line: 1,
-                column: 1,
-                offset: 0,
-            },
-        },
-        SimpleToken::Minus {
-            position: Position {
-                line: 1,
-                column: 4,
-                offset: 3,
-            },
-        },
-        SimpleToken::Number {
-            value: 3,
-            position: Position {
-                line: 1,
-                column: 6,
-                offset: 5,
-            },
-        },
-    ];
+    let tokens3 = tokens![SimpleToken @ "10 - 3"; Number { value: 10 }, Minus, Number { value: 3 }];
 
     let rules3: SimpleParserRules = vec![
         Box::new(BinaryRule::new(
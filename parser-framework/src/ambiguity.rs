@@ -0,0 +1,64 @@
+use common_framework::{Position, RuleId};
+use std::fmt;
+
+/// Several rules matched at the same token with different lengths.
+///
+/// Recorded by a [`Parser`](crate::Parser) with ambiguity reports enabled
+/// (see [`Parser::with_ambiguity_reports`](crate::Parser::with_ambiguity_reports)).
+/// The parser still picks one alternative according to its
+/// [`ChoicePolicy`](crate::ChoicePolicy); the report only shows what else
+/// could have matched, for debugging a grammar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ambiguity {
+    /// Index of the token where the alternatives start.
+    pub token_index: usize,
+    /// Every rule that matched there, in the order they were tried.
+    pub alternatives: Vec<Alternative>,
+}
+
+/// One rule that matched at the position of an [`Ambiguity`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alternative {
+    /// Name of the rule.
+    pub rule: String,
+    /// Id of the rule.
+    pub rule_id: RuleId,
+    /// Priority of the rule.
+    pub priority: i32,
+    /// Number of tokens the rule consumed.
+    pub consumed: usize,
+    /// Span of the node the rule produced, if the node reports one.
+    pub span: Option<(Position, Position)>,
+    /// Whether the parser kept this alternative.
+    pub chosen: bool,
+}
+
+impl Ambiguity {
+    /// Returns the alternative the parser kept, if any.
+    pub fn chosen(&self) -> Option<&Alternative> {
+        self.alternatives.iter().find(|alt| alt.chosen)
+    }
+}
+
+impl fmt::Display for Ambiguity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ambiguous parse at token {}: ", self.token_index)?;
+        for (i, alt) in self.alternatives.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "`{}` consumes {} token(s)", alt.rule, alt.consumed)?;
+            if let Some((start, end)) = alt.span {
+                write!(
+                    f,
+                    " ({}:{}-{}:{})",
+                    start.line, start.column, end.line, end.column
+                )?;
+            }
+            if alt.chosen {
+                write!(f, " [chosen]")?;
+            }
+        }
+        Ok(())
+    }
+}
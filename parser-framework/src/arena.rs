@@ -0,0 +1,137 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A typed index into an [`Arena`].
+///
+/// Ids are plain integers, so AST nodes can refer to their children without
+/// boxing, and whole trees are freed at once when the arena is dropped.
+pub struct NodeId<T> {
+    index: u32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> NodeId<T> {
+    /// Returns the position of the node in its arena.
+    pub fn index(self) -> usize {
+        self.index as usize
+    }
+}
+
+// Manual impls so `NodeId<T>` is `Copy`/`Eq`/`Hash` regardless of `T`.
+impl<T> Clone for NodeId<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for NodeId<T> {}
+
+impl<T> PartialEq for NodeId<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T> Eq for NodeId<T> {}
+
+impl<T> std::hash::Hash for NodeId<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for NodeId<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NodeId({})", self.index)
+    }
+}
+
+/// Append-only storage for AST nodes.
+///
+/// Nodes are allocated contiguously and addressed by [`NodeId`]; they are
+/// never removed individually.
+#[derive(Debug, Clone)]
+pub struct Arena<T> {
+    nodes: Vec<T>,
+}
+
+impl<T> Arena<T> {
+    /// Creates an empty arena.
+    pub fn new() -> Self {
+        Self { nodes: Vec::new() }
+    }
+
+    /// Creates an empty arena with room for `capacity` nodes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            nodes: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Stores `node` and returns its id.
+    pub fn alloc(&mut self, node: T) -> NodeId<T> {
+        let index = u32::try_from(self.nodes.len()).expect("arena holds at most u32::MAX nodes");
+        self.nodes.push(node);
+        NodeId {
+            index,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the node for `id`, or `None` if its index is past the end.
+    ///
+    /// Ids carry no arena identity, so an id from another arena of the same
+    /// type that is in range returns whichever node sits at that index.
+    pub fn get(&self, id: NodeId<T>) -> Option<&T> {
+        self.nodes.get(id.index())
+    }
+
+    /// Returns the node for `id` mutably, or `None` if its index is past the
+    /// end. See [`Arena::get`].
+    pub fn get_mut(&mut self, id: NodeId<T>) -> Option<&mut T> {
+        self.nodes.get_mut(id.index())
+    }
+
+    /// Returns the number of allocated nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if no nodes have been allocated.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Iterates over all nodes in allocation order.
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId<T>, &T)> {
+        self.nodes.iter().enumerate().map(|(index, node)| {
+            (
+                NodeId {
+                    index: index as u32,
+                    _marker: PhantomData,
+                },
+                node,
+            )
+        })
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> std::ops::Index<NodeId<T>> for Arena<T> {
+    type Output = T;
+
+    fn index(&self, id: NodeId<T>) -> &T {
+        &self.nodes[id.index()]
+    }
+}
+
+impl<T> std::ops::IndexMut<NodeId<T>> for Arena<T> {
+    fn index_mut(&mut self, id: NodeId<T>) -> &mut T {
+        &mut self.nodes[id.index()]
+    }
+}
@@ -0,0 +1,99 @@
+use crate::context::DefaultContext;
+use crate::observer::ParserObserver;
+use crate::parser::Parser;
+use crate::traits::{AstNode, ParsingRule};
+use common_framework::{AllocStats, BenchReport, RuleStats};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Parses `tokens` `iterations` times with fresh rules from `rules` and
+/// reports throughput and per-rule counts.
+///
+/// Only [`Parser::parse`] is timed, not building the rules or copying the
+/// tokens. The per-rule counts come from one extra, untimed run with a
+/// [`ParserObserver`] installed, so observing does not skew the timings.
+/// With the `alloc-stats` feature and `CountingAllocator` installed, that
+/// run also counts allocations, per rule and in total.
+///
+/// ```ignore
+/// let report = bench_parser(&tokens, my_rules, 20);
+/// println!("{}", report);
+/// ```
+pub fn bench_parser<Tok, Ast, F>(tokens: &[Tok], mut rules: F, iterations: u32) -> BenchReport
+where
+    Tok: Clone + std::fmt::Debug,
+    Ast: AstNode + 'static,
+    F: FnMut() -> Vec<Box<dyn ParsingRule<DefaultContext<Tok>, Tok, Ast>>>,
+{
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..iterations {
+        let mut parser = Parser::new(DefaultContext::new(tokens.to_vec()), rules());
+        let start = Instant::now();
+        std::hint::black_box(parser.parse());
+        elapsed += start.elapsed();
+    }
+
+    let mut parser = Parser::new(DefaultContext::new(tokens.to_vec()), rules());
+    let counter = Rc::new(RefCell::new(RuleCounter {
+        rules: parser
+            .rules()
+            .iter()
+            .map(|rule| RuleStats::new(rule.name()))
+            .collect(),
+        entered: None,
+    }));
+    parser.set_observer(counter.clone());
+    let (output, allocations) = AllocStats::measure(|| parser.parse().len());
+    let rules = std::mem::take(&mut counter.borrow_mut().rules);
+
+    BenchReport {
+        iterations,
+        elapsed,
+        input: tokens.len(),
+        output,
+        rules,
+        allocations,
+    }
+}
+
+/// Tallies observer events per rule name.
+struct RuleCounter {
+    rules: Vec<RuleStats>,
+    // Allocation totals when the running rule was entered. Rules the
+    // parser dispatches never nest, so one slot is enough.
+    entered: Option<AllocStats>,
+}
+
+impl RuleCounter {
+    fn get(&mut self, rule: &str) -> &mut RuleStats {
+        match self.rules.iter().position(|stats| stats.name == rule) {
+            Some(index) => &mut self.rules[index],
+            None => {
+                self.rules.push(RuleStats::new(rule));
+                self.rules.last_mut().unwrap()
+            }
+        }
+    }
+}
+
+impl<Ast> ParserObserver<Ast> for RuleCounter {
+    fn on_rule_enter(&mut self, rule: &str, _token_index: usize) {
+        self.get(rule).attempts += 1;
+        self.entered = Some(AllocStats::current());
+    }
+
+    fn on_rule_exit(&mut self, rule: &str, _token_index: usize, matched: bool) {
+        let allocations = match self.entered.take() {
+            Some(entered) => AllocStats::current().since(entered),
+            None => AllocStats::default(),
+        };
+        let stats = self.get(rule);
+        stats.matches += u64::from(matched);
+        stats.allocations += allocations;
+    }
+
+    fn on_backtrack(&mut self, rule: &str, _from: usize, _to: usize) {
+        self.get(rule).backtracks += 1;
+    }
+}
@@ -1,4 +1,10 @@
-use common_framework::{Checkpoint, Position};
+use crate::expect::Expected;
+use crate::keyword::IdentToken;
+use crate::node_id::{AstId, AstIdAllocator};
+use common_framework::{
+    Checkpoint, ContextId, Diagnostic, Diagnostics, Feedback, Position, StateMap, TriviaPolicy,
+};
+use lexer_framework::LexToken;
 
 /// Context for parsing operations in CGP (Context-Generic Programming).
 /// This trait allows parsing rules to access token stream information
@@ -31,9 +37,291 @@ where
     /// Restores the parser to a checkpoint.
     fn restore(&mut self, checkpoint: Checkpoint);
 
+    /// Runs `f` speculatively: if it returns `None`, every token it consumed
+    /// is put back, so no early-return path can forget to restore.
+    fn speculate<T, F>(&mut self, f: F) -> Option<T>
+    where
+        Self: Sized,
+        F: FnOnce(&mut Self) -> Option<T>,
+    {
+        let checkpoint = self.checkpoint();
+        let result = f(self);
+        if result.is_none() {
+            self.restore(checkpoint);
+        }
+        result
+    }
+
+    /// Returns `true` if the upcoming tokens match `predicates` one by one,
+    /// without consuming anything.
+    ///
+    /// Cheap disambiguation before committing to an alternative, e.g.
+    /// `ctx.lookahead(&[is_lparen, is_ident, is_rparen])` to tell an arrow
+    /// function's `(x) =>` from a parenthesized expression.
+    fn lookahead(&mut self, predicates: &[fn(&Tok) -> bool]) -> bool {
+        predicates
+            .iter()
+            .enumerate()
+            .all(|(offset, predicate)| self.peek_at(offset).is_some_and(predicate))
+    }
+
+    /// Runs `f` speculatively and always puts back the tokens it consumed,
+    /// returning whether it succeeded.
+    ///
+    /// Use it when a cheap token check is not enough, e.g.
+    /// `ctx.probe(|ctx| ArrowParams.parse(ctx).is_some() && ctx.peek().is_some_and(is_arrow))`.
+    /// The probe runs as a failed [`transaction`](Self::transaction), so
+    /// commits requested inside it never discard tokens it needs to put back.
+    fn probe<F>(&mut self, f: F) -> bool
+    where
+        Self: Sized,
+        F: FnOnce(&mut Self) -> bool,
+    {
+        let checkpoint = self.begin_transaction();
+        let matched = f(self);
+        self.end_transaction(checkpoint, false);
+        matched
+    }
+
+    /// Returns `true` if the current token is the identifier `keyword`,
+    /// for contextual keywords the lexer emits as identifiers.
+    fn peek_ident_as(&mut self, keyword: &str) -> bool
+    where
+        Tok: IdentToken,
+    {
+        self.peek().is_some_and(|token| token.is_ident(keyword))
+    }
+
+    /// Consumes the current token if it is the identifier `keyword`.
+    fn eat_ident_as(&mut self, keyword: &str) -> Option<Tok>
+    where
+        Tok: IdentToken,
+    {
+        if self.peek_ident_as(keyword) {
+            self.advance()
+        } else {
+            None
+        }
+    }
+
+    /// Consumes the current token if `matches` accepts it. Otherwise records
+    /// that `name` was expected here, for [`expected`](Self::expected), and
+    /// returns `None` without consuming anything.
+    ///
+    /// ```ignore
+    /// ctx.expect_named("closing parenthesis", |token| matches!(token, Token::RParen))?;
+    /// ```
+    fn expect_named<F>(&mut self, name: &str, matches: F) -> Option<Tok>
+    where
+        Self: Sized,
+        F: FnOnce(&Tok) -> bool,
+    {
+        if self.peek().is_some_and(matches) {
+            return self.advance();
+        }
+        let expected = Expected {
+            token_index: self.token_index(),
+            position: self.position(),
+            names: vec![name.to_string()],
+            found: self.peek().cloned(),
+        };
+        self.record_expected(expected);
+        None
+    }
+
+    /// Records a failed expectation, keeping the furthest one. Backtracking
+    /// does not undo it. The default drops it.
+    fn record_expected(&mut self, expected: Expected<Tok>) {
+        let _ = expected;
+    }
+
+    /// Returns the furthest failed expectation recorded so far.
+    fn expected(&self) -> Option<&Expected<Tok>> {
+        None
+    }
+
     /// Commits the current position, signaling that tokens before this point
     /// will never be revisited. Default implementation is a no-op.
     fn commit(&mut self) {}
+
+    /// Commits to the branch the calling rule is on, like PEG's cut: once a
+    /// rule is certain what it is parsing (say, after the keyword that
+    /// starts it), a later failure is an error in the input rather than a
+    /// reason to try another rule.
+    ///
+    /// Restores to checkpoints before the cut stop at the cut, even inside
+    /// transactions, so the built-in contexts drop the tokens before it as
+    /// they would after a commit. A [`Parser`](crate::Parser) whose rule
+    /// fails after cutting records a [`CutFailure`](crate::CutFailure) and
+    /// stops. Rules that cut should not be run speculatively, as
+    /// [`ChoicePolicy::LongestMatch`](crate::ChoicePolicy::LongestMatch)
+    /// and ambiguity reports do. The default does nothing.
+    fn cut(&mut self) {}
+
+    /// Returns the checkpoint of the latest [`cut`](Self::cut), if any.
+    fn last_cut(&self) -> Option<Checkpoint> {
+        None
+    }
+
+    /// Returns a fresh id for a node the calling rule is about to build.
+    ///
+    /// The built-in contexts number nodes from 0 per context. The default
+    /// falls back to [`AstId::fresh`], which is unique but not dense.
+    fn alloc_node_id(&mut self) -> AstId {
+        AstId::fresh()
+    }
+
+    /// Sends `feedback` back to the lexer, e.g. a type name declared by the
+    /// node just parsed (the C "lexer hack").
+    ///
+    /// Call it after consuming the tokens that trigger it: feedback sent
+    /// past a checkpoint is discarded when the rule backtracks. Only
+    /// [`StreamingParseContext`](crate::StreamingParseContext) has a lexer
+    /// to deliver to; the default drops it.
+    fn send_feedback(&mut self, feedback: Feedback) {
+        let _ = feedback;
+    }
+
+    /// Returns the context's user state map, if it has one.
+    ///
+    /// All built-in contexts do; custom contexts can override this to
+    /// support [`state`](Self::state).
+    fn state_map(&mut self) -> Option<&mut StateMap> {
+        None
+    }
+
+    /// Returns the rule-shared state of type `T`, creating it with
+    /// `T::default()` on first use.
+    ///
+    /// State is not rolled back by [`restore`](Self::restore), so rules
+    /// should update it only after a parse is certain.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`state_map`](Self::state_map) returns `None`.
+    fn state<T: Default + Send + 'static>(&mut self) -> &mut T
+    where
+        Self: Sized,
+    {
+        self.state_map()
+            .expect("this context has no state map; override `state_map`")
+            .get_or_default()
+    }
+
+    /// Consumes the tokens the [`TriviaPolicy`] given to
+    /// [`Parser::with_trivia`](crate::Parser::with_trivia) treats as
+    /// trivia, and returns how many. Does nothing without a policy.
+    ///
+    /// The parser already skips trivia before each rule it tries; rules
+    /// call this between the tokens they match themselves.
+    fn skip_trivia(&mut self) -> usize
+    where
+        Self: Sized,
+        Tok: 'static,
+    {
+        let policy = self
+            .state_map()
+            .and_then(|state| state.get::<TriviaPolicy<Tok>>().copied());
+        let Some(policy) = policy else {
+            return 0;
+        };
+        let mut skipped = 0;
+        while self.peek().is_some_and(|token| policy.is_trivia(token)) {
+            self.advance();
+            skipped += 1;
+        }
+        skipped
+    }
+
+    /// Reports a warning or error about the input, e.g. a suspicious but
+    /// valid construct, to be returned alongside the nodes.
+    ///
+    /// The default passes it to the context's [`Diagnostics`], kept in its
+    /// state map (see [`set_diagnostics`](Self::set_diagnostics)), and
+    /// drops it if the context has none. Like [`state`](Self::state) it is
+    /// not rolled back by [`restore`](Self::restore), so report only once a
+    /// parse is certain.
+    fn report(&mut self, diagnostic: Diagnostic) {
+        if let Some(state) = self.state_map() {
+            state.get_or_default::<Diagnostics>().report(diagnostic);
+        }
+    }
+
+    /// Routes [`report`](Self::report) through `diagnostics` from now on,
+    /// e.g. to print diagnostics as they arrive, filter them by severity,
+    /// or cap them. Without this, they are collected for
+    /// [`take_diagnostics`](Self::take_diagnostics).
+    fn set_diagnostics(&mut self, diagnostics: Diagnostics) {
+        if let Some(state) = self.state_map() {
+            state.insert(diagnostics);
+        }
+    }
+
+    /// Removes and returns the diagnostics collected so far, oldest first.
+    /// Empty if the sink does not keep them.
+    fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        self.state_map()
+            .and_then(|state| state.get::<Diagnostics>().map(Diagnostics::take))
+            .unwrap_or_default()
+    }
+
+    /// Runs `f` as a transaction: on `Some` the consumed tokens are committed,
+    /// on `None` they are put back.
+    ///
+    /// Transactions nest. Commits requested inside a transaction, including
+    /// by nested transactions, are deferred until the outermost one succeeds,
+    /// so a sub-parse can never discard tokens an enclosing rule may still
+    /// need to backtrack over.
+    fn transaction<T, F>(&mut self, f: F) -> Option<T>
+    where
+        Self: Sized,
+        F: FnOnce(&mut Self) -> Option<T>,
+    {
+        let checkpoint = self.begin_transaction();
+        let result = f(self);
+        self.end_transaction(checkpoint, result.is_some());
+        result
+    }
+
+    /// Opens a transaction and returns the checkpoint to roll back to.
+    ///
+    /// Contexts whose `commit` discards history must override this and
+    /// [`end_transaction`](Self::end_transaction) to track nesting.
+    fn begin_transaction(&mut self) -> Checkpoint {
+        self.checkpoint()
+    }
+
+    /// Closes the transaction opened at `checkpoint`, committing on success
+    /// and restoring otherwise.
+    fn end_transaction(&mut self, checkpoint: Checkpoint, success: bool) {
+        if success {
+            self.commit();
+        } else {
+            self.restore(checkpoint);
+        }
+    }
+}
+
+/// Reads the position a token starts at, so a context can report where
+/// parsing is.
+///
+/// The built-in contexts take one through `with_token_positions`; for
+/// tokens implementing the lexer's `LexToken`, `with_lex_positions` passes
+/// `LexToken::position`. Without one, [`ParseContext::position`] stays at
+/// the default position.
+pub type TokenPositionFn<Tok> = fn(&Tok) -> Option<Position>;
+
+/// The position reader contexts start with: tokens carry no positions.
+pub(crate) fn no_position<Tok>(_token: &Tok) -> Option<Position> {
+    None
+}
+
+/// Returns `cut` instead of `checkpoint` if the checkpoint lies before it.
+pub(crate) fn clamp_to_cut(checkpoint: Checkpoint, cut: Option<Checkpoint>) -> Checkpoint {
+    match cut {
+        Some(cut) if cut.token_index() > checkpoint.token_index() => cut,
+        _ => checkpoint,
+    }
 }
 
 /// A simple default context implementation that works with a token iterator.
@@ -45,6 +333,12 @@ where
     tokens: Vec<Tok>,
     current: usize,
     position: Position,
+    position_of: TokenPositionFn<Tok>,
+    cut: Option<Checkpoint>,
+    expected: Option<Expected<Tok>>,
+    node_ids: AstIdAllocator,
+    state: StateMap,
+    id: ContextId,
 }
 
 impl<Tok> DefaultContext<Tok>
@@ -53,22 +347,26 @@ where
 {
     /// Creates a new context from a vector of tokens.
     pub fn new(tokens: Vec<Tok>) -> Self {
-        let position = tokens
-            .first()
-            .and_then(|t| {
-                // Try to get position from token if it implements a position method
-                // This uses a helper trait to extract position
-                extract_position_from_token(t)
-            })
-            .unwrap_or_default();
-
         Self {
             tokens,
             current: 0,
-            position,
+            position: Position::default(),
+            position_of: no_position,
+            cut: None,
+            expected: None,
+            node_ids: AstIdAllocator::new(),
+            state: StateMap::new(),
+            id: ContextId::fresh(),
         }
     }
 
+    /// Tracks [`position`](ParseContext::position) through `position_of`,
+    /// e.g. `LexToken::position`.
+    pub fn with_token_positions(mut self, position_of: TokenPositionFn<Tok>) -> Self {
+        self.position_of = position_of;
+        self
+    }
+
     /// Creates a new context from an iterator of tokens.
     pub fn from_token_iter<I>(iter: I) -> Self
     where
@@ -78,16 +376,13 @@ where
     }
 }
 
-/// Helper function to extract position from tokens.
-/// This allows DefaultContext to work with tokens that may or may not
-/// implement a position method.
-///
-/// Users can implement a trait for their token types to provide position information,
-/// or use the lexer-framework's LexToken trait if available.
-pub(crate) fn extract_position_from_token<T>(_token: &T) -> Option<Position> {
-    // For now, return None - users should implement position extraction
-    // for their token types, or use a helper trait
-    None
+impl<Tok: LexToken> DefaultContext<Tok> {
+    /// Tracks [`position`](ParseContext::position) through
+    /// `LexToken::position`, like
+    /// [`with_token_positions(Tok::position)`](Self::with_token_positions).
+    pub fn with_lex_positions(self) -> Self {
+        self.with_token_positions(Tok::position)
+    }
 }
 
 impl<Tok> ParseContext<Tok> for DefaultContext<Tok>
@@ -103,31 +398,21 @@ where
     }
 
     fn advance(&mut self) -> Option<Tok> {
-        if self.current >= self.tokens.len() {
-            return None;
+        let token = self.tokens.get(self.current)?.clone();
+        if let Some(position) = (self.position_of)(&token) {
+            self.position = position;
         }
-
-        let token = self.tokens[self.current].clone();
-
-        // Update position based on token if possible
-        if let Some(new_position) = extract_position_from_token(&token) {
-            self.position = new_position;
-        }
-
         self.current += 1;
         Some(token)
     }
 
+    /// Returns the position of the current token, or of the last consumed
+    /// one at the end of input.
     fn position(&self) -> Position {
-        // Try to get position from current token if available
-        // Note: Since peek is now mutable, we can't easily use it here with &self.
-        // But DefaultContext has direct access to tokens, so we can implement it directly.
-        if let Some(token) = self.tokens.get(self.current) {
-            if let Some(token_position) = extract_position_from_token(token) {
-                return token_position;
-            }
-        }
-        self.position
+        self.tokens
+            .get(self.current)
+            .and_then(self.position_of)
+            .unwrap_or(self.position)
     }
 
     fn is_eof(&mut self) -> bool {
@@ -139,18 +424,37 @@ where
     }
 
     fn checkpoint(&self) -> Checkpoint {
-        Checkpoint::new(self.current, self.position)
+        Checkpoint::new(self.current, self.position).with_context(self.id)
     }
 
     fn restore(&mut self, checkpoint: Checkpoint) {
+        checkpoint.debug_assert_context(self.id);
+        let checkpoint = clamp_to_cut(checkpoint, self.cut);
         self.current = checkpoint.token_index();
         self.position = checkpoint.position();
+    }
 
-        // Try to update position from restored token if available
-        if let Some(token) = self.tokens.get(self.current) {
-            if let Some(token_position) = extract_position_from_token(token) {
-                self.position = token_position;
-            }
-        }
+    fn record_expected(&mut self, expected: Expected<Tok>) {
+        expected.merge_into(&mut self.expected);
+    }
+
+    fn expected(&self) -> Option<&Expected<Tok>> {
+        self.expected.as_ref()
+    }
+
+    fn cut(&mut self) {
+        self.cut = Some(self.checkpoint());
+    }
+
+    fn last_cut(&self) -> Option<Checkpoint> {
+        self.cut
+    }
+
+    fn alloc_node_id(&mut self) -> AstId {
+        self.node_ids.alloc()
+    }
+
+    fn state_map(&mut self) -> Option<&mut StateMap> {
+        Some(&mut self.state)
     }
 }
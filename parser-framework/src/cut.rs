@@ -0,0 +1,34 @@
+use common_framework::Position;
+use std::fmt;
+
+/// A rule failed after it [cut](crate::ParseContext::cut), committing to
+/// its branch.
+///
+/// Recorded by a [`Parser`](crate::Parser), which then stops instead of
+/// trying the remaining rules: the input is malformed, not merely
+/// something another rule should parse.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CutFailure {
+    /// Name of the rule that failed.
+    pub rule: String,
+    /// Index of the token the rule started at.
+    pub start: usize,
+    /// Index of the token at the latest cut.
+    pub cut: usize,
+    /// Index of the token where the rule gave up.
+    pub token_index: usize,
+    /// Position where the rule gave up.
+    pub position: Position,
+}
+
+impl fmt::Display for CutFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` failed at token {} (line {}, column {}) after committing at token {}",
+            self.rule, self.token_index, self.position.line, self.position.column, self.cut
+        )
+    }
+}
+
+impl std::error::Error for CutFailure {}
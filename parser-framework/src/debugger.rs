@@ -0,0 +1,513 @@
+use crate::context::ParseContext;
+use crate::observer::ParserObserver;
+use crate::parser::Parser;
+use crate::traits::AstNode;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Write};
+use std::rc::Rc;
+
+/// Number of upcoming tokens the REPL shows by default.
+const TOKEN_WINDOW: usize = 5;
+
+/// An event recorded by a [`Debugger`], as reported by [`ParserObserver`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DebugEvent {
+    /// A rule was tried at `token_index`.
+    Enter { rule: String, token_index: usize },
+    /// A rule returned after reading up to `token_index`.
+    Exit {
+        rule: String,
+        token_index: usize,
+        matched: bool,
+    },
+    /// The tokens a rule consumed were put back.
+    Backtrack {
+        rule: String,
+        from: usize,
+        to: usize,
+    },
+}
+
+/// How far one rule got during a [`Step`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attempt {
+    pub rule: String,
+    /// Token index the rule started at.
+    pub start: usize,
+    /// Token index the rule had read up to when it returned.
+    pub reached: usize,
+    pub matched: bool,
+}
+
+/// A backtrack in a [`Debugger`]'s history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backtrack {
+    /// Number of the step it happened in, starting at 1.
+    pub step: usize,
+    pub rule: String,
+    pub from: usize,
+    pub to: usize,
+}
+
+/// The outcome of one [`Debugger::step`].
+#[derive(Debug, Clone)]
+pub struct Step<Ast> {
+    /// Number of the step, starting at 1.
+    pub number: usize,
+    /// Token index the step started at.
+    pub token_index: usize,
+    /// The node produced, or `None` if no rule matched.
+    pub node: Option<Ast>,
+    /// The rule that produced the node.
+    pub rule: Option<String>,
+    /// Everything the parser reported during the step, in order.
+    pub events: Vec<DebugEvent>,
+}
+
+impl<Ast> Step<Ast> {
+    /// Returns every rule tried during the step with how far it got.
+    pub fn attempts(&self) -> Vec<Attempt> {
+        let mut attempts = Vec::new();
+        let mut open = Vec::new();
+        for event in &self.events {
+            match event {
+                DebugEvent::Enter { rule, token_index } => open.push((rule, *token_index)),
+                DebugEvent::Exit {
+                    token_index,
+                    matched,
+                    ..
+                } => {
+                    if let Some((rule, start)) = open.pop() {
+                        attempts.push(Attempt {
+                            rule: rule.clone(),
+                            start,
+                            reached: *token_index,
+                            matched: *matched,
+                        });
+                    }
+                }
+                DebugEvent::Backtrack { .. } => {}
+            }
+        }
+        attempts
+    }
+
+    /// Returns the failed attempt that read the most tokens: usually where
+    /// the grammar and the input part ways when no rule matches.
+    pub fn furthest_failure(&self) -> Option<Attempt> {
+        self.attempts()
+            .into_iter()
+            .filter(|attempt| !attempt.matched)
+            .max_by_key(|attempt| attempt.reached)
+    }
+}
+
+/// Collects observer events until the debugger drains them.
+#[derive(Default)]
+struct Recorder {
+    events: Vec<DebugEvent>,
+    node_rule: Option<String>,
+}
+
+impl<Ast> ParserObserver<Ast> for Recorder {
+    fn on_rule_enter(&mut self, rule: &str, token_index: usize) {
+        self.events.push(DebugEvent::Enter {
+            rule: rule.to_string(),
+            token_index,
+        });
+    }
+
+    fn on_rule_exit(&mut self, rule: &str, token_index: usize, matched: bool) {
+        self.events.push(DebugEvent::Exit {
+            rule: rule.to_string(),
+            token_index,
+            matched,
+        });
+    }
+
+    fn on_backtrack(&mut self, rule: &str, from: usize, to: usize) {
+        self.events.push(DebugEvent::Backtrack {
+            rule: rule.to_string(),
+            from,
+            to,
+        });
+    }
+
+    fn on_node(&mut self, rule: &str, _node: &Ast) {
+        self.node_rule = Some(rule.to_string());
+    }
+}
+
+/// Runs a [`Parser`] one node at a time and shows what its rules did.
+///
+/// Each [`step`](Self::step) parses one node and returns the rules that
+/// were tried, how far each got and which backtracked, which is what is
+/// needed when a rule set silently produces `None`.
+/// [`step_event`](Self::step_event) walks the same events one at a time,
+/// with [`rule_stack`](Self::rule_stack) holding the rules entered and not
+/// yet exited. Breakpoints on rule names stop
+/// [`run_to_breakpoint`](Self::run_to_breakpoint), and [`repl`](Self::repl)
+/// offers the same over a line-based terminal UI.
+///
+/// The debugger installs its own [`ParserObserver`], replacing any other.
+/// Only rules the parser dispatches itself are seen, not sub-rules they
+/// call.
+///
+/// ```ignore
+/// let mut debugger = Debugger::new(Parser::new(context, rules));
+/// while let Some(step) = debugger.step() {
+///     if step.node.is_none() {
+///         println!("stuck at token {}: {:?}", step.token_index, step.furthest_failure());
+///         break;
+///     }
+/// }
+/// ```
+pub struct Debugger<Ctx, Tok, Ast>
+where
+    Ctx: ParseContext<Tok>,
+    Tok: Clone + std::fmt::Debug,
+    Ast: AstNode,
+{
+    parser: Parser<Ctx, Tok, Ast>,
+    recorder: Rc<RefCell<Recorder>>,
+    history: Vec<Backtrack>,
+    breakpoints: BTreeSet<String>,
+    steps: usize,
+    /// The step [`step_event`](Self::step_event) is walking, with the index
+    /// of its next event.
+    walking: Option<(Step<Ast>, usize)>,
+    rule_stack: Vec<String>,
+    /// Set once the events of a step where no rule matched are used up.
+    stalled: bool,
+}
+
+impl<Ctx, Tok, Ast> Debugger<Ctx, Tok, Ast>
+where
+    Ctx: ParseContext<Tok>,
+    Tok: Clone + std::fmt::Debug,
+    Ast: AstNode + 'static,
+{
+    /// Wraps `parser`.
+    pub fn new(mut parser: Parser<Ctx, Tok, Ast>) -> Self {
+        let recorder = Rc::new(RefCell::new(Recorder::default()));
+        parser.set_observer(recorder.clone());
+        Self {
+            parser,
+            recorder,
+            history: Vec::new(),
+            breakpoints: BTreeSet::new(),
+            steps: 0,
+            walking: None,
+            rule_stack: Vec::new(),
+            stalled: false,
+        }
+    }
+
+    /// Parses the next node. Returns `None` at the end of input.
+    ///
+    /// A step whose `node` is `None` means no rule matched; the parser has
+    /// not moved, so stepping again gives the same result. If
+    /// [`step_event`](Self::step_event) is partway through a step, that
+    /// step is returned instead, with all of its events.
+    pub fn step(&mut self) -> Option<Step<Ast>> {
+        self.stalled = false;
+        if let Some((step, _)) = self.walking.take() {
+            self.rule_stack.clear();
+            return Some(step);
+        }
+        self.parse_step()
+    }
+
+    /// Returns the next event the parser reported, updating
+    /// [`rule_stack`](Self::rule_stack).
+    ///
+    /// Events come from [`step`](Self::step)s: once the current step's
+    /// events are used up, the next node is parsed and its events are
+    /// returned in turn. Parsing a node runs to completion first, so the
+    /// parser is already past it while its events are walked.
+    ///
+    /// Returns `None` at the end of input, and once after the events of a
+    /// step where no rule matched, since the parser has not moved and would
+    /// report the same events again.
+    pub fn step_event(&mut self) -> Option<DebugEvent> {
+        if std::mem::take(&mut self.stalled) {
+            return None;
+        }
+        let (step, next) = match self.walking.take() {
+            Some(walking) => walking,
+            None => loop {
+                let step = self.parse_step()?;
+                if !step.events.is_empty() {
+                    break (step, 0);
+                }
+                // A recovered node without events; a stuck step ends here.
+                step.node.as_ref()?;
+            },
+        };
+        let event = step.events[next].clone();
+        match &event {
+            DebugEvent::Enter { rule, .. } => self.rule_stack.push(rule.clone()),
+            DebugEvent::Exit { .. } => {
+                self.rule_stack.pop();
+            }
+            DebugEvent::Backtrack { .. } => {}
+        }
+        if next + 1 < step.events.len() {
+            self.walking = Some((step, next + 1));
+        } else {
+            self.stalled = step.node.is_none();
+        }
+        Some(event)
+    }
+
+    /// Returns the rules [`step_event`](Self::step_event) has seen entered
+    /// but not exited, outermost first. Empty between steps.
+    pub fn rule_stack(&self) -> &[String] {
+        &self.rule_stack
+    }
+
+    /// Parses the next node and records its events.
+    fn parse_step(&mut self) -> Option<Step<Ast>> {
+        if self.parser.context_mut().is_eof() {
+            return None;
+        }
+        let token_index = self.parser.context().token_index();
+        let node = self.parser.next_node();
+        let (events, rule) = {
+            let mut recorder = self.recorder.borrow_mut();
+            (
+                std::mem::take(&mut recorder.events),
+                recorder.node_rule.take(),
+            )
+        };
+        self.steps += 1;
+        for event in &events {
+            if let DebugEvent::Backtrack { rule, from, to } = event {
+                self.history.push(Backtrack {
+                    step: self.steps,
+                    rule: rule.clone(),
+                    from: *from,
+                    to: *to,
+                });
+            }
+        }
+        Some(Step {
+            number: self.steps,
+            token_index,
+            node,
+            rule,
+            events,
+        })
+    }
+
+    /// Steps until a step tries a rule with a breakpoint, no rule matches,
+    /// or the input ends. Returns the steps taken.
+    pub fn run_to_breakpoint(&mut self) -> Vec<Step<Ast>> {
+        let mut steps = Vec::new();
+        while let Some(step) = self.step() {
+            let stop = step.node.is_none()
+                || step.events.iter().any(|event| {
+                    matches!(event, DebugEvent::Enter { rule, .. } if self.breakpoints.contains(rule))
+                });
+            steps.push(step);
+            if stop {
+                break;
+            }
+        }
+        steps
+    }
+
+    /// Stops [`run_to_breakpoint`](Self::run_to_breakpoint) at steps that
+    /// try the rule named `rule`.
+    pub fn add_breakpoint(&mut self, rule: impl Into<String>) {
+        self.breakpoints.insert(rule.into());
+    }
+
+    /// Removes a breakpoint. Returns `false` if there was none.
+    pub fn remove_breakpoint(&mut self, rule: &str) -> bool {
+        self.breakpoints.remove(rule)
+    }
+
+    /// Returns the rule names with breakpoints.
+    pub fn breakpoints(&self) -> impl Iterator<Item = &str> {
+        self.breakpoints.iter().map(String::as_str)
+    }
+
+    /// Returns the index of the next token to parse.
+    pub fn token_index(&self) -> usize {
+        self.parser.context().token_index()
+    }
+
+    /// Returns up to `n` upcoming tokens without consuming them.
+    pub fn upcoming(&mut self, n: usize) -> Vec<Tok> {
+        let context = self.parser.context_mut();
+        (0..n)
+            .map_while(|offset| context.peek_at(offset).cloned())
+            .collect()
+    }
+
+    /// Returns every backtrack so far, oldest first.
+    pub fn history(&self) -> &[Backtrack] {
+        &self.history
+    }
+
+    /// Returns the wrapped parser.
+    pub fn parser(&self) -> &Parser<Ctx, Tok, Ast> {
+        &self.parser
+    }
+
+    /// Returns the wrapped parser mutably.
+    pub fn parser_mut(&mut self) -> &mut Parser<Ctx, Tok, Ast> {
+        &mut self.parser
+    }
+
+    /// Removes the debugger's observer and returns the parser.
+    pub fn into_parser(mut self) -> Parser<Ctx, Tok, Ast> {
+        self.parser.take_observer();
+        self.parser
+    }
+
+    /// Runs an interactive session, reading commands from `input` and
+    /// writing to `output` until `quit` or the end of `input`.
+    ///
+    /// Commands: `step [n]` (`s`), `into` (`i`, one event with the rule
+    /// stack), `continue` (`c`), `break <rule>` (`b`, toggles), `tokens [n]`
+    /// (`t`), `history` (`h`), `help` and `quit` (`q`). An empty line
+    /// repeats `step`.
+    pub fn repl(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        writeln!(output, "grammar debugger; `help` lists commands")?;
+        self.write_tokens(&mut output, TOKEN_WINDOW)?;
+        let mut lines = input.lines();
+        loop {
+            write!(output, "> ")?;
+            output.flush()?;
+            let Some(line) = lines.next().transpose()? else {
+                writeln!(output)?;
+                return Ok(());
+            };
+            let mut words = line.split_whitespace();
+            let command = words.next().unwrap_or("step");
+            let count = words.next().and_then(|word| word.parse().ok());
+            match command {
+                "s" | "step" => {
+                    for _ in 0..count.unwrap_or(1) {
+                        match self.step() {
+                            Some(step) => write_step(&mut output, &step)?,
+                            None => {
+                                writeln!(output, "end of input")?;
+                                break;
+                            }
+                        }
+                    }
+                }
+                "i" | "into" => match self.step_event() {
+                    Some(event) => {
+                        write_event(&mut output, &event)?;
+                        writeln!(output, "  stack: [{}]", self.rule_stack.join(" > "))?;
+                    }
+                    None => writeln!(output, "no more events")?,
+                },
+                "c" | "continue" => {
+                    let steps = self.run_to_breakpoint();
+                    match steps.last() {
+                        Some(step) => {
+                            writeln!(output, "ran {} step(s)", steps.len())?;
+                            write_step(&mut output, step)?;
+                        }
+                        None => writeln!(output, "end of input")?,
+                    }
+                }
+                "b" | "break" => match line.split_whitespace().nth(1) {
+                    Some(rule) if self.remove_breakpoint(rule) => {
+                        writeln!(output, "breakpoint on {} removed", rule)?
+                    }
+                    Some(rule) => {
+                        self.add_breakpoint(rule);
+                        writeln!(output, "breakpoint on {}", rule)?;
+                    }
+                    None => {
+                        let rules: Vec<&str> = self.breakpoints().collect();
+                        writeln!(output, "breakpoints: {}", rules.join(", "))?;
+                    }
+                },
+                "t" | "tokens" => self.write_tokens(&mut output, count.unwrap_or(TOKEN_WINDOW))?,
+                "h" | "history" => {
+                    for backtrack in &self.history {
+                        writeln!(
+                            output,
+                            "  step {}: {} backtracked {} -> {}",
+                            backtrack.step, backtrack.rule, backtrack.from, backtrack.to
+                        )?;
+                    }
+                }
+                "q" | "quit" => return Ok(()),
+                "help" => writeln!(
+                    output,
+                    "step [n], into, continue, break <rule>, tokens [n], history, quit"
+                )?,
+                other => writeln!(output, "unknown command `{}`; try `help`", other)?,
+            }
+        }
+    }
+
+    fn write_tokens(&mut self, output: &mut impl Write, n: usize) -> io::Result<()> {
+        let start = self.token_index();
+        let tokens = self.upcoming(n);
+        if tokens.is_empty() {
+            return writeln!(output, "  (end of input)");
+        }
+        for (offset, token) in tokens.iter().enumerate() {
+            writeln!(output, "  [{}] {:?}", start + offset, token)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_event(output: &mut impl Write, event: &DebugEvent) -> io::Result<()> {
+    match event {
+        DebugEvent::Enter { rule, token_index } => {
+            writeln!(output, "enter {} at token {}", rule, token_index)
+        }
+        DebugEvent::Exit {
+            rule,
+            token_index,
+            matched,
+        } => writeln!(
+            output,
+            "exit {} at token {}, {}",
+            rule,
+            token_index,
+            if *matched { "matched" } else { "failed" }
+        ),
+        DebugEvent::Backtrack { rule, from, to } => {
+            writeln!(output, "{} backtracked {} -> {}", rule, from, to)
+        }
+    }
+}
+
+fn write_step<Ast: std::fmt::Debug>(output: &mut impl Write, step: &Step<Ast>) -> io::Result<()> {
+    writeln!(output, "step {} at token {}", step.number, step.token_index)?;
+    for attempt in step.attempts() {
+        writeln!(
+            output,
+            "  {:<20} {}..{} {}",
+            attempt.rule,
+            attempt.start,
+            attempt.reached,
+            if attempt.matched { "matched" } else { "failed" }
+        )?;
+    }
+    match (&step.node, &step.rule) {
+        (Some(node), Some(rule)) => writeln!(output, "  => {:?} ({})", node, rule),
+        (Some(node), None) => writeln!(output, "  => {:?}", node),
+        (None, _) => match step.furthest_failure() {
+            Some(attempt) => writeln!(
+                output,
+                "  no rule matched; {} got furthest, to token {}",
+                attempt.rule, attempt.reached
+            ),
+            None => writeln!(output, "  no rule applies to this token"),
+        },
+    }
+}
@@ -0,0 +1,123 @@
+use crate::debugger::Step;
+use crate::dump::DumpNode;
+use std::fmt::Write;
+
+/// Values that [`dump_dot`] can draw: ASTs implementing [`DumpNode`] and
+/// [`Debugger`](crate::Debugger) traces (one [`Step`] or a list of them).
+pub trait ToDot {
+    /// Writes the graph's nodes and edges, without the `digraph` wrapper.
+    fn write_dot(&self, out: &mut String);
+}
+
+/// Writes `value` as a Graphviz DOT graph, for `dot -Tsvg`.
+///
+/// ASTs become one box per node labelled with its kind and attributes.
+/// Traces become one row of rule attempts per step: attempts that failed
+/// are greyed out, the one that produced the node is bold.
+pub fn dump_dot<T: ToDot + ?Sized>(value: &T) -> String {
+    let mut out = String::from("digraph {\n  node [shape=box, fontname=monospace];\n");
+    value.write_dot(&mut out);
+    out.push_str("}\n");
+    out
+}
+
+impl<N: DumpNode> ToDot for N {
+    fn write_dot(&self, out: &mut String) {
+        let mut next_id = 0;
+        write_ast_node(self, &mut next_id, out);
+    }
+}
+
+/// Writes `node` and its subtree, returning the DOT id of `node`.
+fn write_ast_node<N: DumpNode>(node: &N, next_id: &mut usize, out: &mut String) -> usize {
+    let id = *next_id;
+    *next_id += 1;
+
+    let mut label = node.kind().to_string();
+    for (name, value) in node.attributes() {
+        let _ = write!(label, "\n{}={}", name, value);
+    }
+    let _ = write!(out, "  n{} [label=", id);
+    write_label(&label, out);
+    out.push_str("];\n");
+
+    for child in node.children() {
+        let child_id = write_ast_node(child, next_id, out);
+        let _ = writeln!(out, "  n{} -> n{};", id, child_id);
+    }
+    id
+}
+
+impl<Ast> ToDot for Step<Ast> {
+    fn write_dot(&self, out: &mut String) {
+        write_step(self, out);
+    }
+}
+
+impl<Ast> ToDot for [Step<Ast>] {
+    fn write_dot(&self, out: &mut String) {
+        for (i, step) in self.iter().enumerate() {
+            write_step(step, out);
+            if i > 0 {
+                let _ = writeln!(
+                    out,
+                    "  s{} -> s{} [style=dotted, arrowhead=none];",
+                    self[i - 1].number,
+                    step.number
+                );
+            }
+        }
+    }
+}
+
+impl<Ast> ToDot for Vec<Step<Ast>> {
+    fn write_dot(&self, out: &mut String) {
+        self.as_slice().write_dot(out);
+    }
+}
+
+fn write_step<Ast>(step: &Step<Ast>, out: &mut String) {
+    let mut label = format!("step {}\ntoken {}", step.number, step.token_index);
+    if step.node.is_none() {
+        label.push_str("\nno match");
+    }
+    let _ = write!(out, "  s{} [shape=ellipse, label=", step.number);
+    write_label(&label, out);
+    out.push_str("];\n");
+
+    for (i, attempt) in step.attempts().iter().enumerate() {
+        let chosen = attempt.matched && step.rule.as_deref() == Some(attempt.rule.as_str());
+        let style = if chosen {
+            "style=bold"
+        } else if attempt.matched {
+            "style=solid"
+        } else {
+            "color=gray, fontcolor=gray"
+        };
+        let _ = write!(out, "  s{}a{} [{}, label=", step.number, i, style);
+        write_label(
+            &format!("{}\n{}..{}", attempt.rule, attempt.start, attempt.reached),
+            out,
+        );
+        out.push_str("];\n");
+        let _ = writeln!(
+            out,
+            "  s{} -> s{}a{} [{}];",
+            step.number, step.number, i, style
+        );
+    }
+}
+
+/// Writes `text` as a quoted DOT label.
+fn write_label(text: &str, out: &mut String) {
+    out.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+}
@@ -0,0 +1,121 @@
+use std::fmt::Write;
+
+/// AST nodes that expose their direct children.
+///
+/// The owned-tree counterpart of [`ArenaNode`](crate::ArenaNode).
+pub trait Children {
+    /// Returns the direct children of this node, in source order.
+    fn children(&self) -> Vec<&Self>;
+}
+
+/// AST nodes that can be written in the canonical dump formats of
+/// [`to_sexpr`] and [`to_json`].
+///
+/// Dumps leave out positions, so golden files survive whitespace edits in
+/// the inputs they were produced from.
+pub trait DumpNode: Children {
+    /// Returns the node's kind, usually its variant name (e.g. `Binary`).
+    fn kind(&self) -> &str;
+
+    /// Returns the node's scalar fields as `(name, value)` pairs, in a fixed
+    /// order. The default has none.
+    fn attributes(&self) -> Vec<(&str, String)> {
+        Vec::new()
+    }
+}
+
+/// Writes `node` as a single-line S-expression:
+/// `(Binary op=Add (Number value=1) (Number value=2))`.
+///
+/// Attribute values containing whitespace, parentheses, quotes or `=` are
+/// written as quoted strings.
+pub fn to_sexpr<N: DumpNode>(node: &N) -> String {
+    let mut out = String::new();
+    write_sexpr(node, &mut out);
+    out
+}
+
+fn write_sexpr<N: DumpNode>(node: &N, out: &mut String) {
+    out.push('(');
+    out.push_str(node.kind());
+    for (name, value) in node.attributes() {
+        let _ = write!(out, " {}=", name);
+        let bare = !value.is_empty()
+            && !value
+                .chars()
+                .any(|ch| ch.is_whitespace() || matches!(ch, '(' | ')' | '"' | '='));
+        if bare {
+            out.push_str(&value);
+        } else {
+            write_quoted(&value, out);
+        }
+    }
+    for child in node.children() {
+        out.push(' ');
+        write_sexpr(child, out);
+    }
+    out.push(')');
+}
+
+/// Writes `node` as compact JSON:
+/// `{"kind":"Number","attrs":{"value":"1"}}`.
+///
+/// Attribute values are always strings; `attrs` and `children` are omitted
+/// when empty.
+pub fn to_json<N: DumpNode>(node: &N) -> String {
+    let mut out = String::new();
+    write_json(node, &mut out);
+    out
+}
+
+fn write_json<N: DumpNode>(node: &N, out: &mut String) {
+    out.push_str("{\"kind\":");
+    write_quoted(node.kind(), out);
+
+    let attributes = node.attributes();
+    if !attributes.is_empty() {
+        out.push_str(",\"attrs\":{");
+        for (i, (name, value)) in attributes.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            write_quoted(name, out);
+            out.push(':');
+            write_quoted(value, out);
+        }
+        out.push('}');
+    }
+
+    let children = node.children();
+    if !children.is_empty() {
+        out.push_str(",\"children\":[");
+        for (i, child) in children.into_iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            write_json(child, out);
+        }
+        out.push(']');
+    }
+    out.push('}');
+}
+
+/// Writes `text` as a double-quoted string with JSON escapes, which are
+/// also valid in the S-expression format.
+fn write_quoted(text: &str, out: &mut String) {
+    out.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", ch as u32);
+            }
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+}
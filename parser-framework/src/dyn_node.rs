@@ -0,0 +1,118 @@
+use crate::dump::{Children, DumpNode};
+use crate::traits::AstNode;
+use common_framework::{Position, TextSlice};
+
+/// An untyped AST node: a kind name, child nodes and, for leaves, the text
+/// it was parsed from.
+///
+/// Rule sets loaded at runtime or written while the grammar is still moving
+/// can build these instead of a typed AST. It implements [`DumpNode`], so
+/// [`to_sexpr`], `dump_dot` and golden corpora work on it too:
+///
+/// ```
+/// use common_framework::TextSlice;
+/// use parser_framework::{to_sexpr, DynNode, Position};
+///
+/// let span = (Position::new(), Position::new());
+/// let one = DynNode::leaf("Number", TextSlice::from_arc("1".into()), span);
+/// let two = DynNode::leaf("Number", TextSlice::from_arc("2".into()), span);
+/// let sum = DynNode::branch("Add", vec![one, two]);
+/// assert_eq!(to_sexpr(&sum), "(Add (Number text=1) (Number text=2))");
+/// ```
+///
+/// [`to_sexpr`]: crate::to_sexpr
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DynNode {
+    pub kind: String,
+    pub children: Vec<DynNode>,
+    pub text: Option<TextSlice>,
+    /// Where the node starts and where it ends.
+    pub span: (Position, Position),
+}
+
+impl DynNode {
+    /// Creates a node of `kind` with no children or text.
+    pub fn new(kind: impl Into<String>, span: (Position, Position)) -> Self {
+        Self {
+            kind: kind.into(),
+            children: Vec::new(),
+            text: None,
+            span,
+        }
+    }
+
+    /// Creates a leaf of `kind` holding `text`, usually a token's text.
+    pub fn leaf(kind: impl Into<String>, text: TextSlice, span: (Position, Position)) -> Self {
+        Self::new(kind, span).with_text(text)
+    }
+
+    /// Creates a node of `kind` over `children`, spanning from the start of
+    /// the first child to the end of the last. Without children the span is
+    /// empty at the start of the input.
+    pub fn branch(kind: impl Into<String>, children: Vec<DynNode>) -> Self {
+        let span = match (children.first(), children.last()) {
+            (Some(first), Some(last)) => (first.span.0, last.span.1),
+            _ => (Position::new(), Position::new()),
+        };
+        Self {
+            children,
+            ..Self::new(kind, span)
+        }
+    }
+
+    /// Sets the node's text.
+    pub fn with_text(mut self, text: TextSlice) -> Self {
+        self.text = Some(text);
+        self
+    }
+
+    /// Appends `child`, widening the span to its end.
+    pub fn push(&mut self, child: DynNode) {
+        self.span.1 = child.span.1;
+        self.children.push(child);
+    }
+
+    /// Returns true if the node is of `kind`.
+    pub fn is(&self, kind: &str) -> bool {
+        self.kind == kind
+    }
+
+    /// Returns the first child of `kind`.
+    pub fn child(&self, kind: &str) -> Option<&DynNode> {
+        self.children.iter().find(|child| child.is(kind))
+    }
+
+    /// Returns the node's text, if it has any.
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+}
+
+impl AstNode for DynNode {
+    fn position(&self) -> Option<Position> {
+        Some(self.span.0)
+    }
+
+    fn span(&self) -> Option<(Position, Position)> {
+        Some(self.span)
+    }
+}
+
+impl Children for DynNode {
+    fn children(&self) -> Vec<&Self> {
+        self.children.iter().collect()
+    }
+}
+
+impl DumpNode for DynNode {
+    fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    fn attributes(&self) -> Vec<(&str, String)> {
+        self.text
+            .iter()
+            .map(|text| ("text", text.to_string()))
+            .collect()
+    }
+}
@@ -0,0 +1,62 @@
+use common_framework::Position;
+use std::fmt;
+
+/// What the parser expected at the furthest token a rule failed at.
+///
+/// Recorded by [`ParseContext::expect_named`](crate::ParseContext::expect_named).
+/// Only the furthest failure is kept: names expected at the same token are
+/// merged, and earlier failures are dropped, since the rule that got
+/// furthest usually tells best what went wrong. With a `Display` token
+/// type it reads as "expected closing parenthesis, found `+` at 1:7".
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expected<Tok> {
+    /// Index of the token where the expectation failed.
+    pub token_index: usize,
+    /// Position of that token.
+    pub position: Position,
+    /// What was expected there, in the order it was recorded.
+    pub names: Vec<String>,
+    /// The token found instead, or `None` at the end of input.
+    pub found: Option<Tok>,
+}
+
+impl<Tok> Expected<Tok> {
+    /// Folds `self` into `slot`, keeping whichever failure is further.
+    pub(crate) fn merge_into(self, slot: &mut Option<Self>) {
+        match slot {
+            Some(current) if current.token_index > self.token_index => {}
+            Some(current) if current.token_index == self.token_index => {
+                for name in self.names {
+                    if !current.names.contains(&name) {
+                        current.names.push(name);
+                    }
+                }
+            }
+            _ => *slot = Some(self),
+        }
+    }
+}
+
+impl<Tok> fmt::Display for Expected<Tok>
+where
+    Tok: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.names.as_slice() {
+            [] => write!(f, "unexpected input")?,
+            [name] => write!(f, "expected {}", name)?,
+            [init @ .., last] => {
+                write!(f, "expected ")?;
+                if init.len() > 1 {
+                    write!(f, "one of ")?;
+                }
+                write!(f, "{} or {}", init.join(", "), last)?;
+            }
+        }
+        match &self.found {
+            Some(token) => write!(f, ", found `{}`", token)?,
+            None => write!(f, ", found end of input")?,
+        }
+        write!(f, " at {}:{}", self.position.line, self.position.column)
+    }
+}
@@ -0,0 +1,412 @@
+use crate::context::ParseContext;
+use crate::pratt::{parse_pratt, PrattConfig, PrattSpan};
+use crate::traits::{AstNode, ParsingRule};
+use common_framework::Diagnostic;
+use std::cell::RefCell;
+
+/// Which side a chain of equal-precedence operators groups from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc {
+    /// `a - b - c` is `(a - b) - c`.
+    Left,
+    /// `a ^ b ^ c` is `a ^ (b ^ c)`.
+    Right,
+    /// `a < b < c` is an error. It is parsed as `(a < b) < c`, and a
+    /// diagnostic is reported once the whole expression parses.
+    NonAssoc,
+}
+
+/// Highest operator precedence the Pratt driver's `u8` binding powers can
+/// hold. Constructors panic on anything higher.
+pub const MAX_PRECEDENCE: u8 = 126;
+
+/// Panics if `precedence` is above [`MAX_PRECEDENCE`].
+pub(crate) fn check_precedence(precedence: u8) -> u8 {
+    assert!(
+        precedence <= MAX_PRECEDENCE,
+        "operator precedence too high: {precedence} > {MAX_PRECEDENCE}"
+    );
+    precedence
+}
+
+/// Binding power of a prefix operator of `precedence`, for
+/// [`PrattConfig::prefix_op`].
+pub(crate) fn prefix_binding_power(precedence: u8) -> u8 {
+    check_precedence(precedence) * 2
+}
+
+/// Precedence and associativity of a binary operator.
+///
+/// Higher precedences bind tighter, up to [`MAX_PRECEDENCE`]; the
+/// constructors panic above it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BinaryOpInfo {
+    pub precedence: u8,
+    pub assoc: Assoc,
+}
+
+impl BinaryOpInfo {
+    /// A left-associative operator.
+    pub fn left(precedence: u8) -> Self {
+        Self {
+            precedence: check_precedence(precedence),
+            assoc: Assoc::Left,
+        }
+    }
+
+    /// A right-associative operator.
+    pub fn right(precedence: u8) -> Self {
+        Self {
+            precedence: check_precedence(precedence),
+            assoc: Assoc::Right,
+        }
+    }
+
+    /// A non-associative operator, such as a comparison.
+    pub fn non_assoc(precedence: u8) -> Self {
+        Self {
+            precedence: check_precedence(precedence),
+            assoc: Assoc::NonAssoc,
+        }
+    }
+
+    /// Left and right binding powers for [`PrattConfig::infix_op`].
+    pub(crate) fn binding_power(self) -> (u8, u8) {
+        // Checked again: the fields are public.
+        let bp = check_precedence(self.precedence) * 2;
+        match self.assoc {
+            Assoc::Left | Assoc::NonAssoc => (bp, bp + 1),
+            Assoc::Right => (bp + 1, bp),
+        }
+    }
+}
+
+/// Diagnostics for chained non-associative operators, held back until the
+/// expression they are in parses, so a failed attempt such as `(a < b < c`
+/// reports nothing.
+#[derive(Default)]
+pub(crate) struct ChainErrors(RefCell<Vec<Diagnostic>>);
+
+impl ChainErrors {
+    /// Records an error if the operator at the current token continues a
+    /// chain after the non-associative operator `info`, as the second `<`
+    /// in `a < b < c`. Call it after parsing the right operand.
+    pub(crate) fn check<Ctx, Tok>(
+        &self,
+        info: BinaryOpInfo,
+        ctx: &mut Ctx,
+        classify: impl Fn(&Tok) -> Option<BinaryOpInfo>,
+    ) where
+        Ctx: ParseContext<Tok>,
+        Tok: Clone + std::fmt::Debug,
+    {
+        if info.assoc != Assoc::NonAssoc {
+            return;
+        }
+        let chained = ctx
+            .peek()
+            .and_then(classify)
+            .is_some_and(|next| next.precedence == info.precedence);
+        if chained {
+            let position = ctx.position();
+            self.0.borrow_mut().push(Diagnostic::error(
+                (position, position),
+                "non-associative operators cannot be chained; add parentheses",
+            ));
+        }
+    }
+
+    /// Runs `parse`, then reports the recorded errors if it succeeded and
+    /// drops them otherwise.
+    pub(crate) fn parse<Ctx, Tok, Ast>(
+        &self,
+        ctx: &mut Ctx,
+        parse: impl FnOnce(&mut Ctx) -> Option<Ast>,
+    ) -> Option<Ast>
+    where
+        Ctx: ParseContext<Tok>,
+        Tok: Clone + std::fmt::Debug,
+    {
+        let node = parse(ctx);
+        let errors = self.0.take();
+        if node.is_some() {
+            for error in errors {
+                ctx.report(error);
+            }
+        }
+        node
+    }
+}
+
+type Classify<Tok, T> = Box<dyn Fn(&Tok) -> Option<T>>;
+type Predicate<Tok> = Box<dyn Fn(&Tok) -> bool>;
+
+struct Binary<Tok, Ast> {
+    info: Classify<Tok, BinaryOpInfo>,
+    build: Box<dyn Fn(Tok, Ast, Ast) -> Ast>,
+}
+
+struct Unary<Tok, Ast> {
+    precedence: Classify<Tok, u8>,
+    build: Box<dyn Fn(Tok, Ast) -> Ast>,
+}
+
+struct Parens<Tok> {
+    open: Predicate<Tok>,
+    close: Predicate<Tok>,
+}
+
+struct Calls<Tok, Ast> {
+    separator: Predicate<Tok>,
+    build: Box<dyn Fn(Ast, Vec<Ast>) -> Ast>,
+}
+
+/// A ready-made expression grammar: atoms, prefix operators, binary
+/// operators with precedence and associativity, parentheses and function
+/// calls, driven by [`parse_pratt`].
+///
+/// The grammar is described by closures that classify tokens and build
+/// nodes, so it works with any token and AST type:
+///
+/// ```ignore
+/// let expr = ExpressionPreset::new(|tok: &Tok| match tok {
+///     Tok::Number(n) => Some(Expr::Number(*n)),
+///     Tok::Ident(name) => Some(Expr::Var(name.clone())),
+///     _ => None,
+/// })
+/// .binary(
+///     |tok| match tok {
+///         Tok::Plus | Tok::Minus => Some(BinaryOpInfo::left(1)),
+///         Tok::Star | Tok::Slash => Some(BinaryOpInfo::left(2)),
+///         Tok::Caret => Some(BinaryOpInfo::right(4)),
+///         _ => None,
+///     },
+///     |op, left, right| Expr::binary(op, left, right),
+/// )
+/// .unary(|tok| (*tok == Tok::Minus).then_some(3), Expr::unary)
+/// .parens(|tok| *tok == Tok::LParen, |tok| *tok == Tok::RParen)
+/// .calls(|tok| *tok == Tok::Comma, Expr::call);
+/// ```
+///
+/// The preset is a [`ParsingRule`] that parses one expression per node; use
+/// [`parse_expr`](Self::parse_expr) to parse sub-expressions inside other
+/// rules.
+pub struct ExpressionPreset<Tok, Ast> {
+    atom: Classify<Tok, Ast>,
+    binary: Option<Binary<Tok, Ast>>,
+    unary: Option<Unary<Tok, Ast>>,
+    parens: Option<Parens<Tok>>,
+    calls: Option<Calls<Tok, Ast>>,
+    chain_errors: ChainErrors,
+    priority: i32,
+}
+
+impl<Tok, Ast> ExpressionPreset<Tok, Ast> {
+    /// Creates a grammar whose leaves are the tokens `atom` turns into
+    /// nodes (numbers, names, literals).
+    pub fn new(atom: impl Fn(&Tok) -> Option<Ast> + 'static) -> Self {
+        Self {
+            atom: Box::new(atom),
+            binary: None,
+            unary: None,
+            parens: None,
+            calls: None,
+            chain_errors: ChainErrors::default(),
+            priority: 0,
+        }
+    }
+
+    /// Adds binary operators: `info` classifies operator tokens and `build`
+    /// combines the operator token with both operands.
+    pub fn binary(
+        mut self,
+        info: impl Fn(&Tok) -> Option<BinaryOpInfo> + 'static,
+        build: impl Fn(Tok, Ast, Ast) -> Ast + 'static,
+    ) -> Self {
+        self.binary = Some(Binary {
+            info: Box::new(info),
+            build: Box::new(build),
+        });
+        self
+    }
+
+    /// Adds prefix operators: `precedence` classifies operator tokens, and
+    /// binary operators of at least that precedence bind inside the operand
+    /// (so `-2^2` is `-(2^2)` if `^` binds tighter than unary minus).
+    /// Parsing panics if `precedence` returns more than [`MAX_PRECEDENCE`].
+    pub fn unary(
+        mut self,
+        precedence: impl Fn(&Tok) -> Option<u8> + 'static,
+        build: impl Fn(Tok, Ast) -> Ast + 'static,
+    ) -> Self {
+        self.unary = Some(Unary {
+            precedence: Box::new(precedence),
+            build: Box::new(build),
+        });
+        self
+    }
+
+    /// Adds grouping with the given open and close tokens.
+    pub fn parens(
+        mut self,
+        open: impl Fn(&Tok) -> bool + 'static,
+        close: impl Fn(&Tok) -> bool + 'static,
+    ) -> Self {
+        self.parens = Some(Parens {
+            open: Box::new(open),
+            close: Box::new(close),
+        });
+        self
+    }
+
+    /// Adds calls `callee(arg, ...)`, using the [`parens`](Self::parens)
+    /// tokens around arguments separated by `separator`. Has no effect
+    /// without parens.
+    pub fn calls(
+        mut self,
+        separator: impl Fn(&Tok) -> bool + 'static,
+        build: impl Fn(Ast, Vec<Ast>) -> Ast + 'static,
+    ) -> Self {
+        self.calls = Some(Calls {
+            separator: Box::new(separator),
+            build: Box::new(build),
+        });
+        self
+    }
+
+    /// Sets the priority of the rule.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    fn is_open(&self, token: &Tok) -> bool {
+        self.parens
+            .as_ref()
+            .is_some_and(|parens| (parens.open)(token))
+    }
+
+    fn is_close(&self, token: &Tok) -> bool {
+        self.parens
+            .as_ref()
+            .is_some_and(|parens| (parens.close)(token))
+    }
+
+    /// Parses one expression, putting the tokens back on failure.
+    pub fn parse_expr<Ctx>(&self, ctx: &mut Ctx) -> Option<Ast>
+    where
+        Ctx: ParseContext<Tok>,
+        Tok: Clone + std::fmt::Debug,
+        Ast: AstNode,
+    {
+        self.chain_errors
+            .parse(ctx, |ctx| ctx.speculate(|ctx| parse_pratt(ctx, self, 0)))
+    }
+
+    /// Parses the arguments of a call after its open token.
+    fn parse_args<Ctx, F>(&self, ctx: &mut Ctx, parser: &F) -> Option<Vec<Ast>>
+    where
+        Ctx: ParseContext<Tok>,
+        Tok: Clone + std::fmt::Debug,
+        F: Fn(&mut Ctx, u8) -> Option<Ast>,
+    {
+        let calls = self.calls.as_ref()?;
+        let mut args = Vec::new();
+        if ctx.peek().is_some_and(|token| self.is_close(token)) {
+            ctx.advance();
+            return Some(args);
+        }
+        loop {
+            args.push(parser(ctx, 0)?);
+            let token = ctx.advance()?;
+            if self.is_close(&token) {
+                return Some(args);
+            }
+            if !(calls.separator)(&token) {
+                return None;
+            }
+        }
+    }
+}
+
+impl<Ctx, Tok, Ast> PrattConfig<Ctx, Tok, Ast> for ExpressionPreset<Tok, Ast>
+where
+    Ctx: ParseContext<Tok>,
+    Tok: Clone + std::fmt::Debug,
+    Ast: AstNode,
+{
+    fn prefix_op(&self, token: &Tok) -> Option<((), u8)> {
+        let unary = self.unary.as_ref()?;
+        (unary.precedence)(token).map(|precedence| ((), prefix_binding_power(precedence)))
+    }
+
+    fn infix_op(&self, token: &Tok) -> Option<(u8, u8)> {
+        if self.calls.is_some() && self.is_open(token) {
+            // Calls bind tighter than any operator.
+            return Some((u8::MAX, u8::MAX));
+        }
+        let binary = self.binary.as_ref()?;
+        (binary.info)(token).map(BinaryOpInfo::binding_power)
+    }
+
+    fn parse_prefix<F>(
+        &self,
+        token: Tok,
+        _span: PrattSpan,
+        ctx: &mut Ctx,
+        parser: &F,
+    ) -> Option<Ast>
+    where
+        F: Fn(&mut Ctx, u8) -> Option<Ast>,
+    {
+        if let Some(atom) = (self.atom)(&token) {
+            return Some(atom);
+        }
+        if self.is_open(&token) {
+            let inner = parser(ctx, 0)?;
+            return self.is_close(&ctx.advance()?).then_some(inner);
+        }
+        let ((), bp) = <Self as PrattConfig<Ctx, Tok, Ast>>::prefix_op(self, &token)?;
+        let operand = parser(ctx, bp)?;
+        Some((self.unary.as_ref()?.build)(token, operand))
+    }
+
+    fn parse_infix<F>(
+        &self,
+        left: Ast,
+        token: Tok,
+        _span: PrattSpan,
+        r_bp: u8,
+        ctx: &mut Ctx,
+        parser: &F,
+    ) -> Option<Ast>
+    where
+        F: Fn(&mut Ctx, u8) -> Option<Ast>,
+    {
+        if self.is_open(&token) {
+            let args = self.parse_args(ctx, parser)?;
+            return Some((self.calls.as_ref()?.build)(left, args));
+        }
+        let binary = self.binary.as_ref()?;
+        let right = parser(ctx, r_bp)?;
+        if let Some(info) = (binary.info)(&token) {
+            self.chain_errors.check(info, ctx, &binary.info);
+        }
+        Some((binary.build)(token, left, right))
+    }
+}
+
+impl<Ctx, Tok, Ast> ParsingRule<Ctx, Tok, Ast> for ExpressionPreset<Tok, Ast>
+where
+    Ctx: ParseContext<Tok>,
+    Tok: Clone + std::fmt::Debug,
+    Ast: AstNode,
+{
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Ast> {
+        self.parse_expr(ctx)
+    }
+
+    fn priority(&self) -> i32 {
+        self.priority
+    }
+}
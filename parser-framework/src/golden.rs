@@ -0,0 +1,312 @@
+use crate::dump::{to_json, to_sexpr, DumpNode};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The dump format of a [`GoldenCorpus`]'s expected files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpFormat {
+    /// [`to_sexpr`], one top-level node per line, in `.sexpr` files.
+    Sexpr,
+    /// [`to_json`], one top-level node per line, in `.json` files.
+    Json,
+}
+
+impl DumpFormat {
+    /// Returns the extension of expected files in this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Sexpr => "sexpr",
+            Self::Json => "json",
+        }
+    }
+
+    /// Dumps `nodes`, one per line.
+    pub fn dump<N: DumpNode>(self, nodes: &[N]) -> String {
+        let mut out = String::new();
+        for node in nodes {
+            match self {
+                Self::Sexpr => out.push_str(&to_sexpr(node)),
+                Self::Json => out.push_str(&to_json(node)),
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// A directory of inputs with their expected AST dumps, for conformance
+/// suites.
+///
+/// Every file with the input extension, in any subdirectory, is a case;
+/// its expected dump sits next to it with the format's extension
+/// (`tests/golden/precedence.calc` is checked against
+/// `tests/golden/precedence.sexpr`):
+///
+/// ```ignore
+/// #[test]
+/// fn golden() {
+///     GoldenCorpus::new("tests/golden", "calc")
+///         .with_update(std::env::var_os("UPDATE_GOLDEN").is_some())
+///         .run(|input| calculator::parse(input))
+///         .unwrap()
+///         .assert_passed();
+/// }
+/// ```
+///
+/// In update mode, mismatched and missing dumps are (re)written instead of
+/// reported, which is how new cases are added.
+#[derive(Debug, Clone)]
+pub struct GoldenCorpus {
+    dir: PathBuf,
+    input_extension: String,
+    format: DumpFormat,
+    update: bool,
+    context_lines: usize,
+}
+
+impl GoldenCorpus {
+    /// Creates a corpus of the `.{input_extension}` files under `dir`,
+    /// compared as S-expressions.
+    pub fn new(dir: impl Into<PathBuf>, input_extension: impl Into<String>) -> Self {
+        Self {
+            dir: dir.into(),
+            input_extension: input_extension.into(),
+            format: DumpFormat::Sexpr,
+            update: false,
+            context_lines: 3,
+        }
+    }
+
+    /// Sets the format of the expected files.
+    pub fn with_format(mut self, format: DumpFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Writes the actual dumps instead of comparing them when `update` is
+    /// true.
+    pub fn with_update(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    /// Sets how many unchanged lines diffs show around each change; 3 by
+    /// default.
+    pub fn with_context_lines(mut self, lines: usize) -> Self {
+        self.context_lines = lines;
+        self
+    }
+
+    /// Returns the inputs of the corpus, sorted by path.
+    pub fn inputs(&self) -> io::Result<Vec<PathBuf>> {
+        let mut inputs = Vec::new();
+        self.collect_inputs(&self.dir, &mut inputs)?;
+        inputs.sort();
+        Ok(inputs)
+    }
+
+    fn collect_inputs(&self, dir: &Path, inputs: &mut Vec<PathBuf>) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                self.collect_inputs(&path, inputs)?;
+            } else if path
+                .extension()
+                .is_some_and(|ext| ext == self.input_extension.as_str())
+            {
+                inputs.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses every input with `parse` and compares the dumps with the
+    /// expected files. Fails only if the directory cannot be read; problems
+    /// with single cases are reported in the [`GoldenReport`].
+    pub fn run<N, F>(&self, mut parse: F) -> io::Result<GoldenReport>
+    where
+        N: DumpNode,
+        F: FnMut(&str) -> Vec<N>,
+    {
+        let mut cases = Vec::new();
+        for input in self.inputs()? {
+            let expected = input.with_extension(self.format.extension());
+            let outcome = match fs::read_to_string(&input) {
+                Ok(text) => self.check(&expected, &self.format.dump(&parse(&text))),
+                Err(err) => GoldenOutcome::Error(err.to_string()),
+            };
+            cases.push(GoldenCase { input, outcome });
+        }
+        Ok(GoldenReport { cases })
+    }
+
+    fn check(&self, expected_path: &Path, actual: &str) -> GoldenOutcome {
+        let expected = match fs::read_to_string(expected_path) {
+            Ok(expected) => Some(expected),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => return GoldenOutcome::Error(err.to_string()),
+        };
+        if expected.as_deref() == Some(actual) {
+            return GoldenOutcome::Passed;
+        }
+        if self.update {
+            return match fs::write(expected_path, actual) {
+                Ok(()) => GoldenOutcome::Updated,
+                Err(err) => GoldenOutcome::Error(err.to_string()),
+            };
+        }
+        match expected {
+            Some(expected) => GoldenOutcome::Mismatch {
+                diff: line_diff(&expected, actual, self.context_lines),
+            },
+            None => GoldenOutcome::MissingExpected {
+                actual: actual.to_string(),
+            },
+        }
+    }
+}
+
+/// What happened to one case of a [`GoldenCorpus`] run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GoldenOutcome {
+    Passed,
+    /// The dump differs; `diff` shows expected lines as `-` and actual
+    /// ones as `+`, with some unchanged lines around them.
+    Mismatch {
+        diff: String,
+    },
+    /// There is no expected file yet.
+    MissingExpected {
+        actual: String,
+    },
+    /// The expected file was written in update mode.
+    Updated,
+    /// A file could not be read or written.
+    Error(String),
+}
+
+/// One case of a [`GoldenCorpus`] run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldenCase {
+    pub input: PathBuf,
+    pub outcome: GoldenOutcome,
+}
+
+impl GoldenCase {
+    /// Returns true unless the case failed; updated cases count as passed.
+    pub fn passed(&self) -> bool {
+        matches!(self.outcome, GoldenOutcome::Passed | GoldenOutcome::Updated)
+    }
+}
+
+/// The results of a [`GoldenCorpus`] run. Its `Display` lists the failed
+/// cases with their diffs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldenReport {
+    pub cases: Vec<GoldenCase>,
+}
+
+impl GoldenReport {
+    /// Returns the cases that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &GoldenCase> {
+        self.cases.iter().filter(|case| !case.passed())
+    }
+
+    /// Returns true if every case passed.
+    pub fn passed(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// Panics with the report unless every case passed.
+    #[track_caller]
+    pub fn assert_passed(&self) {
+        if !self.passed() {
+            panic!("golden tests failed:\n{}", self);
+        }
+    }
+}
+
+impl fmt::Display for GoldenReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for case in self.failures() {
+            let input = case.input.display();
+            match &case.outcome {
+                GoldenOutcome::Mismatch { diff } => {
+                    writeln!(f, "--- {} (expected)\n+++ {} (actual)", input, input)?;
+                    f.write_str(diff)?;
+                }
+                GoldenOutcome::MissingExpected { actual } => {
+                    writeln!(f, "{}: no expected dump; actual:", input)?;
+                    f.write_str(actual)?;
+                }
+                GoldenOutcome::Error(err) => writeln!(f, "{}: {}", input, err)?,
+                GoldenOutcome::Passed | GoldenOutcome::Updated => {}
+            }
+        }
+        let failed = self.failures().count();
+        writeln!(
+            f,
+            "{} of {} golden cases passed",
+            self.cases.len() - failed,
+            self.cases.len()
+        )
+    }
+}
+
+/// Diffs `expected` and `actual` line by line, keeping `context` unchanged
+/// lines around each change. Each hunk starts with an `@@` line giving
+/// where it begins in the expected file.
+fn line_diff(expected: &str, actual: &str, context: usize) -> String {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+
+    // Longest common subsequence table, filled from the end.
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((' ', i + 1, old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(('-', i + 1, old[i]));
+            i += 1;
+        } else {
+            lines.push(('+', i + 1, new[j]));
+            j += 1;
+        }
+    }
+
+    let changed: Vec<usize> = (0..lines.len())
+        .filter(|&index| lines[index].0 != ' ')
+        .collect();
+    let mut out = String::new();
+    let mut shown_until = 0;
+    for (index, &(mark, line, text)) in lines.iter().enumerate() {
+        let near_change = changed
+            .iter()
+            .any(|&change| index + context >= change && index <= change + context);
+        if !near_change {
+            continue;
+        }
+        if out.is_empty() || index > shown_until {
+            out.push_str(&format!("@@ expected line {} @@\n", line));
+        }
+        out.push_str(&format!("{}{}\n", mark, text));
+        shown_until = index + 1;
+    }
+    out
+}
@@ -0,0 +1,79 @@
+use crate::context::ParseContext;
+use crate::traits::{AstNode, ParsingRule};
+
+/// A token that may be an identifier.
+///
+/// Implementing it lets rules treat identifiers as keywords in specific
+/// positions (contextual keywords such as `await`, `async` or `get`) while
+/// the lexer keeps emitting them as plain identifiers.
+pub trait IdentToken {
+    /// Returns the identifier's text, or `None` if the token is not an
+    /// identifier.
+    fn ident_text(&self) -> Option<&str>;
+
+    /// Returns `true` if the token is the identifier `keyword`.
+    fn is_ident(&self, keyword: &str) -> bool {
+        self.ident_text() == Some(keyword)
+    }
+}
+
+/// A rule that fires only when the current token is the identifier
+/// `keyword`, e.g. `await expr` in a language where `await` is otherwise an
+/// ordinary name.
+///
+/// After consuming the keyword, `parse` is called with the keyword token to
+/// parse the rest of the construct. If it returns `None`, the keyword is put
+/// back so other rules can read it as an identifier.
+pub struct ContextualKeywordRule<F> {
+    keyword: String,
+    priority: i32,
+    parse: F,
+}
+
+impl<F> ContextualKeywordRule<F> {
+    /// Creates a rule for `keyword` with priority 0.
+    pub fn new(keyword: impl Into<String>, parse: F) -> Self {
+        Self {
+            keyword: keyword.into(),
+            priority: 0,
+            parse,
+        }
+    }
+
+    /// Sets the priority. Give it a higher priority than the rule for plain
+    /// identifiers so the keyword reading is tried first.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Returns the keyword this rule matches.
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+}
+
+impl<F, Ctx, Tok, Ast> ParsingRule<Ctx, Tok, Ast> for ContextualKeywordRule<F>
+where
+    Ctx: ParseContext<Tok>,
+    Tok: IdentToken + Clone + std::fmt::Debug,
+    Ast: AstNode,
+    F: FnMut(&mut Ctx, Tok) -> Option<Ast>,
+{
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Ast> {
+        let keyword = &self.keyword;
+        let parse = &mut self.parse;
+        ctx.speculate(|ctx| {
+            let token = ctx.eat_ident_as(keyword)?;
+            parse(ctx, token)
+        })
+    }
+
+    fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    fn quick_check(&self, current_token: Option<&Tok>) -> Option<bool> {
+        Some(current_token.is_some_and(|token| token.is_ident(&self.keyword)))
+    }
+}
@@ -1,12 +1,39 @@
-use crate::context::ParseContext;
-use common_framework::{Checkpoint, Position};
+use crate::context::{clamp_to_cut, no_position, ParseContext, TokenPositionFn};
+use crate::expect::Expected;
+use crate::node_id::{AstId, AstIdAllocator};
+use common_framework::{Checkpoint, ContextId, Diagnostic, Position, StateMap};
+#[cfg(feature = "streaming")]
+use lexer_framework::streaming::TokenProducer;
+use lexer_framework::LexToken;
+use std::cell::Cell;
 use std::collections::VecDeque;
+use std::fmt;
+#[cfg(feature = "streaming")]
+use std::marker::PhantomData;
+
+/// Least history an adaptive [`LazyContext`] keeps behind the cursor.
+const MIN_ADAPTIVE_HISTORY: usize = 4;
 
 /// A parsing context that lazily consumes tokens from an iterator.
 ///
 /// It maintains a sliding window buffer to support limited lookahead and backtracking.
 /// Tokens are pulled from the iterator on demand.
 /// Old tokens are discarded when they fall out of the sliding window.
+///
+/// Restoring a checkpoint whose tokens were discarded fails with a
+/// [`BacktrackError`]: [`try_restore`](Self::try_restore) returns it, and
+/// [`restore`](ParseContext::restore) reports it as an error
+/// [diagnostic](ParseContext::report) and ends the token stream, so the
+/// parser stops instead of going on from the wrong token. The error is also
+/// kept for [`backtrack_error`](Self::backtrack_error). Use
+/// [`with_max_window`](Self::with_max_window) for grammars that backtrack
+/// far without committing.
+///
+/// With [`with_adaptive_window`](Self::with_adaptive_window), the history
+/// kept follows the checkpoints in use and the longest backtrack seen so
+/// far instead, and
+/// [`window_stats`](Self::window_stats) reports the high-water marks for
+/// tuning `window_size`.
 pub struct LazyContext<I, Tok>
 where
     I: Iterator<Item = Tok>,
@@ -18,12 +45,32 @@ where
     base_index: usize,
     /// The current position of the parser relative to the start of the buffer
     cursor_offset: usize,
-    /// Current position information
+    /// Position of the last consumed token
     position: Position,
+    position_of: TokenPositionFn<Tok>,
     /// Maximum size of the history window before pruning
     window_size: usize,
+    /// Upper bound the window may grow to while history is uncommitted.
+    max_window: Option<usize>,
+    /// Size the history to the backtracks observed so far.
+    adaptive: bool,
+    stats: WindowStats,
+    /// Token index of the oldest checkpoint taken since the last commit.
+    oldest_checkpoint: Cell<Option<usize>>,
+    /// Set by a failed restore; no more tokens are handed out after it.
+    error: Option<BacktrackError>,
     /// Tokens with index < committed_index will never be revisited.
     committed_index: usize,
+    /// Number of open transactions; commits are deferred while non-zero.
+    transaction_depth: usize,
+    /// Token index where the outermost open transaction started. History
+    /// from here on is never pruned, so the transaction can roll back.
+    transaction_floor: Option<usize>,
+    cut: Option<Checkpoint>,
+    expected: Option<Expected<Tok>>,
+    node_ids: AstIdAllocator,
+    state: StateMap,
+    id: ContextId,
 }
 
 impl<I, Tok> LazyContext<I, Tok>
@@ -38,9 +85,97 @@ where
             base_index: 0,
             cursor_offset: 0,
             position: Position::default(),
+            position_of: no_position,
             window_size,
+            max_window: None,
+            adaptive: false,
+            stats: WindowStats::default(),
+            oldest_checkpoint: Cell::new(None),
+            error: None,
             committed_index: 0,
+            transaction_depth: 0,
+            transaction_floor: None,
+            cut: None,
+            expected: None,
+            node_ids: AstIdAllocator::new(),
+            state: StateMap::new(),
+            id: ContextId::fresh(),
+        }
+    }
+
+    /// Tracks [`position`](ParseContext::position) through `position_of`,
+    /// e.g. `LexToken::position`.
+    pub fn with_token_positions(mut self, position_of: TokenPositionFn<Tok>) -> Self {
+        self.position_of = position_of;
+        self
+    }
+
+    /// Keeps all uncommitted history, up to `max` tokens, instead of half
+    /// the window: the buffer grows past `window_size` while rules have not
+    /// committed, and shrinks again as they do.
+    pub fn with_max_window(mut self, max: usize) -> Self {
+        self.max_window = Some(max);
+        self
+    }
+
+    /// Keeps the history since the oldest checkpoint taken after the last
+    /// commit, and at least twice the longest backtrack seen so far (and a
+    /// few tokens), instead of a fixed half window, up to the limit set by
+    /// `window_size` or [`with_max_window`](Self::with_max_window).
+    ///
+    /// Saves memory for grammars that commit often and mostly backtrack a
+    /// token or two, while any checkpoint within the limit can still be
+    /// restored, including the first long backtrack.
+    pub fn with_adaptive_window(mut self, enabled: bool) -> Self {
+        self.adaptive = enabled;
+        self
+    }
+
+    /// Returns the high-water marks of backtracking and buffering so far.
+    pub fn window_stats(&self) -> WindowStats {
+        self.stats
+    }
+
+    /// Returns how many tokens back from the current one can still be
+    /// restored.
+    pub fn safe_backtrack_distance(&self) -> usize {
+        self.cursor_offset
+    }
+
+    /// Returns whether `checkpoint` can still be restored.
+    pub fn can_restore(&self, checkpoint: &Checkpoint) -> bool {
+        clamp_to_cut(*checkpoint, self.cut).token_index() >= self.base_index
+    }
+
+    /// Restores `checkpoint`, or fails without changing anything if its
+    /// tokens have been discarded.
+    pub fn try_restore(&mut self, checkpoint: Checkpoint) -> Result<(), BacktrackError> {
+        checkpoint.debug_assert_context(self.id);
+        let checkpoint = clamp_to_cut(checkpoint, self.cut);
+        let target_index = checkpoint.token_index();
+        let distance = self.token_index().saturating_sub(target_index);
+        self.stats.max_backtrack = self.stats.max_backtrack.max(distance);
+        if target_index < self.base_index {
+            return Err(BacktrackError::Discarded {
+                target: target_index,
+                earliest: self.base_index,
+            });
         }
+        let new_offset = target_index - self.base_index;
+        if new_offset > self.buffer.len() {
+            return Err(BacktrackError::Ahead {
+                target: target_index,
+                end: self.base_index + self.buffer.len(),
+            });
+        }
+        self.cursor_offset = new_offset;
+        self.position = checkpoint.position();
+        Ok(())
+    }
+
+    /// Returns the error of the restore that ended the token stream, if any.
+    pub fn backtrack_error(&self) -> Option<&BacktrackError> {
+        self.error.as_ref()
     }
 
     /// Ensures that the buffer contains the token at the given relative offset.
@@ -49,6 +184,7 @@ where
         while self.cursor_offset + relative_offset >= self.buffer.len() {
             if let Some(token) = self.iter.next() {
                 self.buffer.push_back(token);
+                self.stats.peak_buffered = self.stats.peak_buffered.max(self.buffer.len());
             } else {
                 return false;
             }
@@ -56,6 +192,27 @@ where
         true
     }
 
+    /// Returns how many tokens to keep behind the cursor.
+    fn history_limit(&self) -> usize {
+        // Keep at least half the window size as history relative to cursor,
+        // or everything since the last commit when the window may grow.
+        let limit = match self.max_window {
+            Some(max) => max.max(self.window_size / 2),
+            None => self.window_size / 2,
+        };
+        if self.adaptive {
+            let in_use = self
+                .oldest_checkpoint
+                .get()
+                .map_or(0, |index| self.token_index().saturating_sub(index));
+            (self.stats.max_backtrack * 2)
+                .max(in_use)
+                .clamp(MIN_ADAPTIVE_HISTORY.min(limit), limit)
+        } else {
+            limit
+        }
+    }
+
     /// Prunes the buffer if the cursor has advanced far enough.
     fn maybe_prune(&mut self) {
         // First drop everything below committed_index
@@ -67,10 +224,12 @@ where
             }
         }
 
-        // Keep at least half the window size as history relative to cursor
-        let keep_history = self.window_size / 2;
+        let keep_history = self.history_limit();
         if self.cursor_offset > keep_history {
-            let prune_count = self.cursor_offset - keep_history;
+            let mut prune_count = self.cursor_offset - keep_history;
+            if let Some(floor) = self.transaction_floor {
+                prune_count = prune_count.min(floor.saturating_sub(self.base_index));
+            }
             for _ in 0..prune_count {
                 self.buffer.pop_front();
             }
@@ -80,6 +239,19 @@ where
     }
 }
 
+impl<I, Tok> LazyContext<I, Tok>
+where
+    I: Iterator<Item = Tok>,
+    Tok: LexToken,
+{
+    /// Tracks [`position`](ParseContext::position) through
+    /// `LexToken::position`, like
+    /// [`with_token_positions(Tok::position)`](Self::with_token_positions).
+    pub fn with_lex_positions(self) -> Self {
+        self.with_token_positions(Tok::position)
+    }
+}
+
 impl<I, Tok> ParseContext<Tok> for LazyContext<I, Tok>
 where
     I: Iterator<Item = Tok>,
@@ -90,6 +262,9 @@ where
     }
 
     fn peek_at(&mut self, offset: usize) -> Option<&Tok> {
+        if self.error.is_some() {
+            return None;
+        }
         if self.ensure_buffer(offset) {
             // buffer must have element at cursor_offset + offset
             // but we need to return a reference.
@@ -105,6 +280,9 @@ where
     }
 
     fn advance(&mut self) -> Option<Tok> {
+        if self.error.is_some() {
+            return None;
+        }
         // Ensure we have current token
         self.ensure_buffer(0);
 
@@ -113,9 +291,9 @@ where
 
             // Update internal state
             self.cursor_offset += 1;
-
-            // Try to update position (if Token supported it, but here we don't know Token type details easily unless we bound it)
-            // For now, simply return.
+            if let Some(position) = (self.position_of)(&token) {
+                self.position = position;
+            }
 
             self.maybe_prune();
             Some(token)
@@ -124,11 +302,19 @@ where
         }
     }
 
+    /// Returns the position of the current token if it has been pulled
+    /// from the iterator, and otherwise of the last consumed one.
     fn position(&self) -> Position {
-        self.position
+        self.buffer
+            .get(self.cursor_offset)
+            .and_then(self.position_of)
+            .unwrap_or(self.position)
     }
 
     fn is_eof(&mut self) -> bool {
+        if self.error.is_some() {
+            return true;
+        }
         // If we have tokens in buffer at cursor, not EOF
         if self.cursor_offset < self.buffer.len() {
             return false;
@@ -142,32 +328,186 @@ where
     }
 
     fn checkpoint(&self) -> Checkpoint {
-        Checkpoint::new(self.token_index(), self.position)
+        let index = self.token_index();
+        let oldest = self
+            .oldest_checkpoint
+            .get()
+            .map_or(index, |oldest| oldest.min(index));
+        self.oldest_checkpoint.set(Some(oldest));
+        Checkpoint::new(index, self.position).with_context(self.id)
     }
 
+    /// Restores `checkpoint`. If it cannot be restored, reports a
+    /// [`BacktrackError`] and ends the token stream instead.
     fn restore(&mut self, checkpoint: Checkpoint) {
-        let target_index = checkpoint.token_index();
-        if target_index < self.base_index {
-            panic!(
-                "LazyContext: Backtracking too far! Target {}, current base {}",
-                target_index, self.base_index
-            );
-        }
-        let new_offset = target_index - self.base_index;
-        if new_offset > self.buffer.len() {
-            // This shouldn't happen if checkpoint was valid and we haven't discarded future?
-            // We only discard past.
-            panic!("LazyContext: Invalid future restore?");
+        if let Err(error) = self.try_restore(checkpoint) {
+            if self.error.is_none() {
+                let position = self.position();
+                self.report(Diagnostic::error((position, position), error.to_string()));
+                self.error = Some(error);
+            }
         }
-        self.cursor_offset = new_offset;
-        self.position = checkpoint.position();
     }
 
     fn commit(&mut self) {
+        if self.transaction_depth > 0 {
+            // Deferred until the outermost transaction succeeds.
+            return;
+        }
         let current_index = self.token_index();
         if current_index > self.committed_index {
             self.committed_index = current_index;
         }
+        // Checkpoints before the commit can no longer be restored.
+        self.oldest_checkpoint.set(None);
         self.maybe_prune();
     }
+
+    /// Drops the history before the cut, even inside a transaction.
+    fn cut(&mut self) {
+        let index = self.token_index();
+        self.cut = Some(self.checkpoint());
+        self.committed_index = self.committed_index.max(index);
+        if let Some(floor) = &mut self.transaction_floor {
+            *floor = (*floor).max(index);
+        }
+        self.maybe_prune();
+    }
+
+    fn last_cut(&self) -> Option<Checkpoint> {
+        self.cut
+    }
+
+    fn record_expected(&mut self, expected: Expected<Tok>) {
+        expected.merge_into(&mut self.expected);
+    }
+
+    fn expected(&self) -> Option<&Expected<Tok>> {
+        self.expected.as_ref()
+    }
+
+    fn alloc_node_id(&mut self) -> AstId {
+        self.node_ids.alloc()
+    }
+
+    fn state_map(&mut self) -> Option<&mut StateMap> {
+        Some(&mut self.state)
+    }
+
+    fn begin_transaction(&mut self) -> Checkpoint {
+        if self.transaction_depth == 0 {
+            self.transaction_floor = Some(self.token_index());
+        }
+        self.transaction_depth += 1;
+        self.checkpoint()
+    }
+
+    fn end_transaction(&mut self, checkpoint: Checkpoint, success: bool) {
+        if !success {
+            self.restore(checkpoint);
+        }
+        self.transaction_depth -= 1;
+        if self.transaction_depth == 0 {
+            self.transaction_floor = None;
+            if success {
+                self.commit();
+            }
+        }
+    }
+}
+
+/// High-water marks of a [`LazyContext`], for tuning its window size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WindowStats {
+    /// Longest distance, in tokens, a checkpoint was restored across,
+    /// including restores that failed.
+    pub max_backtrack: usize,
+    /// Most tokens held in the buffer at once, history and lookahead.
+    pub peak_buffered: usize,
+}
+
+/// A checkpoint a [`LazyContext`] could not restore.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BacktrackError {
+    /// The checkpoint's tokens fell out of the window.
+    Discarded {
+        /// Token index of the checkpoint.
+        target: usize,
+        /// Index of the oldest token still held.
+        earliest: usize,
+    },
+    /// The checkpoint lies past the tokens read so far, e.g. it was made
+    /// for another context.
+    Ahead {
+        /// Token index of the checkpoint.
+        target: usize,
+        /// Index one past the last token read.
+        end: usize,
+    },
+}
+
+impl fmt::Display for BacktrackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Discarded { target, earliest } => write!(
+                f,
+                "cannot backtrack to token {target}: tokens before {earliest} were discarded"
+            ),
+            Self::Ahead { target, end } => write!(
+                f,
+                "cannot restore token {target}: only tokens before {end} were read"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BacktrackError {}
+
+/// Pulls the tokens of a [`TokenProducer`] as an iterator, for feeding a
+/// [`LazyContext`].
+#[cfg(feature = "streaming")]
+pub struct ProducerTokens<P, Tok> {
+    producer: P,
+    _marker: PhantomData<fn() -> Tok>,
+}
+
+#[cfg(feature = "streaming")]
+impl<P, Tok> ProducerTokens<P, Tok>
+where
+    P: TokenProducer<Tok>,
+{
+    /// Wraps `producer`.
+    pub fn new(producer: P) -> Self {
+        Self {
+            producer,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the producer.
+    pub fn producer(&self) -> &P {
+        &self.producer
+    }
+
+    /// Unwraps the adapter into its producer.
+    pub fn into_producer(self) -> P {
+        self.producer
+    }
 }
+
+#[cfg(feature = "streaming")]
+impl<P, Tok> Iterator for ProducerTokens<P, Tok>
+where
+    P: TokenProducer<Tok>,
+{
+    type Item = Tok;
+
+    fn next(&mut self) -> Option<Tok> {
+        self.producer.poll_token()
+    }
+}
+
+/// A [`LazyContext`] reading from a [`TokenProducer`], as created by
+/// [`Parser::from_producer`](crate::Parser::from_producer).
+#[cfg(feature = "streaming")]
+pub type ProducerContext<P, Tok> = LazyContext<ProducerTokens<P, Tok>, Tok>;
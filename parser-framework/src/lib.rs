@@ -1,16 +1,75 @@
+pub mod ambiguity;
+pub mod arena;
+pub mod bench_support;
 pub mod context;
+pub mod cut;
+pub mod debugger;
+pub mod dot;
+pub mod dump;
+pub mod dyn_node;
+pub mod expect;
+pub mod expr;
+pub mod golden;
+pub mod keyword;
 pub mod lazy_context;
+pub mod lower;
+pub mod node_id;
+pub mod observer;
 pub mod parser;
 pub mod pratt;
+pub mod pratt_table;
+pub mod railroad;
+#[cfg(feature = "streaming")]
+pub mod revision;
+pub mod scope;
+pub mod sequence;
 #[cfg(feature = "streaming")]
 pub mod streaming;
+pub mod testing;
 pub mod traits;
+pub mod visit;
 
-pub use common_framework::{Checkpoint, Position};
-pub use context::{DefaultContext, ParseContext};
-pub use lazy_context::LazyContext;
-pub use parser::Parser;
-pub use pratt::{parse_pratt, PrattConfig};
+pub use ambiguity::{Alternative, Ambiguity};
+pub use arena::{Arena, NodeId};
+pub use bench_support::bench_parser;
+#[cfg(feature = "alloc-stats")]
+pub use common_framework::CountingAllocator;
+pub use common_framework::{
+    pos_seq, positions_of, AllocStats, BenchReport, CallbackSink, Checkpoint, Diagnostic,
+    DiagnosticSink, Diagnostics, Feedback, IgnoreSink, PinnedCheckpoint, Position,
+    PositionAssigner, RuleId, RuleStats, ScopeStack, Severity, StateMap,
+};
+pub use context::{DefaultContext, ParseContext, TokenPositionFn};
+pub use cut::CutFailure;
+pub use debugger::{Attempt, Backtrack, DebugEvent, Debugger, Step};
+pub use dot::{dump_dot, ToDot};
+pub use dump::{to_json, to_sexpr, Children, DumpNode};
+pub use dyn_node::DynNode;
+pub use expect::Expected;
+pub use expr::{Assoc, BinaryOpInfo, ExpressionPreset, MAX_PRECEDENCE};
+pub use golden::{DumpFormat, GoldenCase, GoldenCorpus, GoldenOutcome, GoldenReport};
+pub use keyword::{ContextualKeywordRule, IdentToken};
+pub use lazy_context::{BacktrackError, LazyContext, WindowStats};
+#[cfg(feature = "streaming")]
+pub use lazy_context::{ProducerContext, ProducerTokens};
+pub use lower::{lower_all, FromDynNode, ShapeError};
+pub use node_id::{AstId, AstIdAllocator, HasAstId, ParentMap};
+pub use observer::ParserObserver;
+#[cfg(feature = "streaming")]
+pub use parser::PRODUCER_WINDOW;
+pub use parser::{ChoicePolicy, IncompleteParse, ParseIter, Parser};
+pub use pratt::{parse_pratt, PrattConfig, PrattSpan};
+pub use pratt_table::{TableDrivenPratt, TokenClass};
+pub use railroad::{Grammar, GrammarItem, Production};
 #[cfg(feature = "streaming")]
-pub use streaming::{StreamingParseContext, TokenConsumer};
-pub use traits::{AstNode, ParsingRule, StatefulNode};
+pub use revision::{NodeRevision, RevisionStream};
+pub use scope::{ScopeContext, ScopedContext};
+pub use sequence::{Sequence, SequenceRule};
+#[cfg(feature = "streaming")]
+pub use streaming::{Completeness, StreamingParseContext, TokenConsumer};
+pub use testing::{parse_all, parse_single, token_position};
+pub use traits::{AstNode, ParsingRule, RecoveryRule, StatefulNode};
+pub use visit::{walk, ArenaNode, VisitControl, Visitor};
+
+/// This crate's version, as recorded in pipeline run metadata.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
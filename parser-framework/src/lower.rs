@@ -0,0 +1,174 @@
+use crate::dyn_node::DynNode;
+use common_framework::{Diagnostic, Position};
+use std::fmt;
+use std::str::FromStr;
+
+/// Types that can be built from a [`DynNode`] tree, for moving a grammar
+/// prototyped with untyped nodes onto a typed AST.
+///
+/// Implementations match on the node's kind and use the checked accessors
+/// on `DynNode`, which turn shape mismatches into [`ShapeError`]s:
+///
+/// ```
+/// use common_framework::TextSlice;
+/// use parser_framework::{DynNode, FromDynNode, ShapeError};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Expr {
+///     Number(i64),
+///     Add(Box<Expr>, Box<Expr>),
+/// }
+///
+/// impl FromDynNode for Expr {
+///     fn from_dyn(node: &DynNode) -> Result<Self, ShapeError> {
+///         match node.kind.as_str() {
+///             "Number" => Ok(Expr::Number(node.parse_text()?)),
+///             "Add" => {
+///                 let [left, right] = node.expect_children()?;
+///                 Ok(Expr::Add(left.lower()?, right.lower()?))
+///             }
+///             _ => Err(ShapeError::unexpected_kind(node, &["Number", "Add"])),
+///         }
+///     }
+/// }
+///
+/// let span = (Default::default(), Default::default());
+/// let number = |text: &str| DynNode::leaf("Number", TextSlice::from_arc(text.into()), span);
+/// let sum = DynNode::branch("Add", vec![number("1"), number("2")]);
+/// assert_eq!(
+///     sum.lower::<Expr>(),
+///     Ok(Expr::Add(Box::new(Expr::Number(1)), Box::new(Expr::Number(2))))
+/// );
+/// assert!(number("x").lower::<Expr>().is_err());
+/// ```
+pub trait FromDynNode: Sized {
+    /// Builds `Self` from `node`, or describes why its shape does not fit.
+    fn from_dyn(node: &DynNode) -> Result<Self, ShapeError>;
+}
+
+impl FromDynNode for DynNode {
+    fn from_dyn(node: &DynNode) -> Result<Self, ShapeError> {
+        Ok(node.clone())
+    }
+}
+
+impl<T: FromDynNode> FromDynNode for Box<T> {
+    fn from_dyn(node: &DynNode) -> Result<Self, ShapeError> {
+        T::from_dyn(node).map(Box::new)
+    }
+}
+
+/// A [`DynNode`] whose shape did not fit the typed node it was lowered to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShapeError {
+    /// Kind of the offending node.
+    pub kind: String,
+    /// Span of the offending node.
+    pub span: (Position, Position),
+    pub message: String,
+}
+
+impl ShapeError {
+    /// Creates an error about `node`.
+    pub fn new(node: &DynNode, message: impl Into<String>) -> Self {
+        Self {
+            kind: node.kind.clone(),
+            span: node.span,
+            message: message.into(),
+        }
+    }
+
+    /// Creates an error for a node of none of the `expected` kinds.
+    pub fn unexpected_kind(node: &DynNode, expected: &[&str]) -> Self {
+        Self::new(
+            node,
+            format!("unexpected kind, expected one of: {}", expected.join(", ")),
+        )
+    }
+
+    /// Converts the error into an error diagnostic over the node's span.
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic::error(self.span, format!("{}: {}", self.kind, self.message))
+    }
+}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` node at {}:{}: {}",
+            self.kind, self.span.0.line, self.span.0.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ShapeError {}
+
+impl From<ShapeError> for Diagnostic {
+    fn from(error: ShapeError) -> Self {
+        error.to_diagnostic()
+    }
+}
+
+impl DynNode {
+    /// Lowers this node to `T`.
+    pub fn lower<T: FromDynNode>(&self) -> Result<T, ShapeError> {
+        T::from_dyn(self)
+    }
+
+    /// Lowers every child to `T`, stopping at the first mismatch.
+    pub fn lower_children<T: FromDynNode>(&self) -> Result<Vec<T>, ShapeError> {
+        self.children.iter().map(T::from_dyn).collect()
+    }
+
+    /// Returns the children if there are exactly `N` of them.
+    pub fn expect_children<const N: usize>(&self) -> Result<&[DynNode; N], ShapeError> {
+        self.children.as_slice().try_into().map_err(|_| {
+            ShapeError::new(
+                self,
+                format!("expected {} children, found {}", N, self.children.len()),
+            )
+        })
+    }
+
+    /// Returns the first child of `kind`, or an error if there is none.
+    pub fn expect_child(&self, kind: &str) -> Result<&DynNode, ShapeError> {
+        self.child(kind)
+            .ok_or_else(|| ShapeError::new(self, format!("missing `{}` child", kind)))
+    }
+
+    /// Returns the node's text, or an error if it has none.
+    pub fn expect_text(&self) -> Result<&str, ShapeError> {
+        self.text()
+            .ok_or_else(|| ShapeError::new(self, "expected text"))
+    }
+
+    /// Parses the node's text as `T`.
+    pub fn parse_text<T>(&self) -> Result<T, ShapeError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let text = self.expect_text()?;
+        text.parse()
+            .map_err(|err| ShapeError::new(self, format!("cannot parse {:?}: {}", text, err)))
+    }
+}
+
+/// Lowers every node of `nodes`, collecting the error of each node that
+/// does not fit rather than stopping at the first.
+pub fn lower_all<T: FromDynNode>(nodes: &[DynNode]) -> Result<Vec<T>, Vec<ShapeError>> {
+    let mut lowered = Vec::with_capacity(nodes.len());
+    let mut errors = Vec::new();
+    for node in nodes {
+        match T::from_dyn(node) {
+            Ok(value) => lowered.push(value),
+            Err(err) => errors.push(err),
+        }
+    }
+    if errors.is_empty() {
+        Ok(lowered)
+    } else {
+        Err(errors)
+    }
+}
@@ -0,0 +1,122 @@
+use crate::dump::Children;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A stable identity for an AST node.
+///
+/// Rules obtain ids from [`ParseContext::alloc_node_id`](crate::ParseContext::alloc_node_id)
+/// and store them in the nodes they build, so later passes (type checking,
+/// rename refactors) can refer to nodes without holding references into the
+/// tree. Unlike [`NodeId`](crate::NodeId), an `AstId` does not index an arena.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AstId(u64);
+
+impl AstId {
+    /// Creates an id with the given value.
+    pub const fn new(value: u64) -> Self {
+        AstId(value)
+    }
+
+    /// Returns the raw id value.
+    pub fn value(self) -> u64 {
+        self.0
+    }
+
+    /// Returns an id unique within the process. Used by contexts that do
+    /// not keep their own [`AstIdAllocator`].
+    pub fn fresh() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        AstId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl fmt::Display for AstId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "n{}", self.0)
+    }
+}
+
+/// Hands out monotonically increasing [`AstId`]s, starting from 0.
+///
+/// Ids are not reclaimed when a rule backtracks, so a parse may leave gaps,
+/// but no id is ever handed out twice.
+#[derive(Debug, Clone, Default)]
+pub struct AstIdAllocator {
+    next: u64,
+}
+
+impl AstIdAllocator {
+    /// Creates an allocator whose first id is 0.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the next id.
+    pub fn alloc(&mut self) -> AstId {
+        let id = AstId(self.next);
+        self.next += 1;
+        id
+    }
+
+    /// Returns how many ids have been handed out.
+    pub fn allocated(&self) -> u64 {
+        self.next
+    }
+}
+
+/// AST nodes that carry an [`AstId`].
+pub trait HasAstId {
+    /// Returns the node's id, or `None` for nodes built without one.
+    fn ast_id(&self) -> Option<AstId>;
+}
+
+/// Maps each node id to the id of its parent.
+///
+/// Nodes without an id are transparent: their children are attributed to
+/// the nearest ancestor that has one.
+#[derive(Debug, Clone, Default)]
+pub struct ParentMap {
+    parents: HashMap<AstId, AstId>,
+}
+
+impl ParentMap {
+    /// Builds the map for the trees rooted at `roots`.
+    pub fn build<N>(roots: &[N]) -> Self
+    where
+        N: Children + HasAstId,
+    {
+        let mut parents = HashMap::new();
+        // Each entry is a node plus the id of its nearest identified ancestor.
+        let mut stack: Vec<(&N, Option<AstId>)> = roots.iter().map(|root| (root, None)).collect();
+        while let Some((node, parent)) = stack.pop() {
+            let id = node.ast_id();
+            if let (Some(id), Some(parent)) = (id, parent) {
+                parents.insert(id, parent);
+            }
+            let scope = id.or(parent);
+            stack.extend(node.children().into_iter().map(|child| (child, scope)));
+        }
+        Self { parents }
+    }
+
+    /// Returns the parent of `id`, or `None` for roots and unknown ids.
+    pub fn parent(&self, id: AstId) -> Option<AstId> {
+        self.parents.get(&id).copied()
+    }
+
+    /// Iterates over the ancestors of `id`, nearest first.
+    pub fn ancestors(&self, id: AstId) -> impl Iterator<Item = AstId> + '_ {
+        std::iter::successors(self.parent(id), move |&id| self.parent(id))
+    }
+
+    /// Returns the number of nodes that have a parent.
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    /// Returns `true` if no node has a parent.
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+}
@@ -0,0 +1,66 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Receives events from a [`Parser`](crate::Parser) as it runs, for
+/// debuggers, progress reporting or tracing without touching the rules.
+///
+/// Every method has an empty default, so observers implement only what they
+/// need. Rules are identified by [`ParsingRule::name`](crate::ParsingRule::name)
+/// and positions in the token stream by token index. Only rules the parser
+/// dispatches itself are reported, not sub-rules they call.
+///
+/// Install one with [`Parser::set_observer`](crate::Parser::set_observer).
+/// To read what it collected afterwards, keep an `Rc<RefCell<_>>` of it:
+///
+/// ```ignore
+/// let progress = Rc::new(RefCell::new(Progress::default()));
+/// parser.set_observer(progress.clone());
+/// parser.parse();
+/// println!("{} nodes", progress.borrow().nodes);
+/// ```
+pub trait ParserObserver<Ast> {
+    /// Rule `rule` is about to be tried at `token_index`.
+    fn on_rule_enter(&mut self, rule: &str, token_index: usize) {
+        let _ = (rule, token_index);
+    }
+
+    /// Rule `rule` returned; `token_index` is where it stopped, before any
+    /// backtracking.
+    fn on_rule_exit(&mut self, rule: &str, token_index: usize, matched: bool) {
+        let _ = (rule, token_index, matched);
+    }
+
+    /// Tokens consumed by `rule` were put back, moving from token `from`
+    /// back to `to`: the rule failed, or it was only tried speculatively
+    /// (under [`ChoicePolicy::LongestMatch`](crate::ChoicePolicy) or with
+    /// ambiguity reports enabled).
+    fn on_backtrack(&mut self, rule: &str, from: usize, to: usize) {
+        let _ = (rule, from, to);
+    }
+
+    /// Rule `rule` produced `node`, which the parser is about to return.
+    fn on_node(&mut self, rule: &str, node: &Ast) {
+        let _ = (rule, node);
+    }
+}
+
+impl<Ast, O> ParserObserver<Ast> for Rc<RefCell<O>>
+where
+    O: ParserObserver<Ast>,
+{
+    fn on_rule_enter(&mut self, rule: &str, token_index: usize) {
+        self.borrow_mut().on_rule_enter(rule, token_index);
+    }
+
+    fn on_rule_exit(&mut self, rule: &str, token_index: usize, matched: bool) {
+        self.borrow_mut().on_rule_exit(rule, token_index, matched);
+    }
+
+    fn on_backtrack(&mut self, rule: &str, from: usize, to: usize) {
+        self.borrow_mut().on_backtrack(rule, from, to);
+    }
+
+    fn on_node(&mut self, rule: &str, node: &Ast) {
+        self.borrow_mut().on_node(rule, node);
+    }
+}
@@ -1,6 +1,49 @@
+use crate::ambiguity::{Alternative, Ambiguity};
 use crate::context::{DefaultContext, ParseContext};
-use crate::traits::{AstNode, ParsingRule};
+use crate::cut::CutFailure;
+use crate::expect::Expected;
+#[cfg(feature = "streaming")]
+use crate::lazy_context::{LazyContext, ProducerContext, ProducerTokens};
+use crate::observer::ParserObserver;
+use crate::traits::{AstNode, ParsingRule, RecoveryRule};
+use common_framework::{
+    Checkpoint, Diagnostic, Diagnostics, Position, RuleGroups, StateMap, TriviaMode, TriviaPolicy,
+};
+#[cfg(feature = "streaming")]
+use lexer_framework::streaming::TokenProducer;
+use lexer_framework::LexToken;
 use std::cmp::Reverse;
+use std::fmt;
+
+/// Number of unconsumed tokens shown by [`IncompleteParse`].
+const PREVIEW_LEN: usize = 5;
+
+/// Window size of the context created by [`Parser::from_producer`].
+#[cfg(feature = "streaming")]
+pub const PRODUCER_WINDOW: usize = 64;
+
+/// Puts the trivia policy a rule runs under into a context's state map, or
+/// removes it if nothing is trivia.
+type InstallTrivia<Tok> = fn(&mut StateMap, Option<TriviaPolicy<Tok>>);
+
+#[cfg(feature = "streaming")]
+type ProducerRules<P, Tok, Ast> = Vec<Box<dyn ParsingRule<ProducerContext<P, Tok>, Tok, Ast>>>;
+
+/// How a [`Parser`] picks among rules of equal priority that all match.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChoicePolicy {
+    /// Take the first matching rule (PEG-style ordered choice).
+    #[default]
+    FirstMatch,
+    /// Try every rule of the highest matching priority tier and take the
+    /// one that consumes the most tokens; ties go to the earlier rule.
+    /// Lower tiers are only tried if nothing in a higher tier matches.
+    ///
+    /// Candidates are parsed speculatively, in a transaction that is rolled
+    /// back so their commits keep the tokens, and the winner is parsed
+    /// again, so rules should not depend on being called only once.
+    LongestMatch,
+}
 
 /// A parser that applies rules in priority order.
 /// This is the main orchestrator in the CGP design.
@@ -12,6 +55,30 @@ where
 {
     context: Ctx,
     rules: Vec<Box<dyn ParsingRule<Ctx, Tok, Ast>>>,
+    recovery_rules: Vec<Box<dyn RecoveryRule<Ctx, Tok, Ast>>>,
+    // Group of each rule in `rules` (None for ungrouped rules).
+    rule_groups: Vec<Option<usize>>,
+    groups: RuleGroups,
+    // Index into `rules` of the rule that produced the last node.
+    last_rule: Option<usize>,
+    policy: ChoicePolicy,
+    report_ambiguities: bool,
+    ambiguities: Vec<Ambiguity>,
+    cut_failures: Vec<CutFailure>,
+    // Copy of the token after the current one, for `quick_check2`.
+    next_token: Option<Tok>,
+    observer: Option<Box<dyn ParserObserver<Ast>>>,
+    // Tokens skipped before every node; see `with_trivia`.
+    trivia: Option<TriviaPolicy<Tok>>,
+    install_trivia: Option<InstallTrivia<Tok>>,
+    // Whether any rule has its own `ParsingRule::trivia` mode.
+    trivia_overrides: bool,
+    // Where the current node starts before and after skipping trivia, while
+    // rules with their own trivia mode may need either.
+    node_start: Option<(Checkpoint, Checkpoint)>,
+    // Whether rule boundaries are checked; see `with_strict`.
+    #[cfg(debug_assertions)]
+    strict: bool,
 }
 
 impl<Ctx, Tok, Ast> Parser<Ctx, Tok, Ast>
@@ -25,11 +92,153 @@ where
         // Sort rules by priority (highest first)
         let mut sorted_rules = rules;
         sorted_rules.sort_by_key(|rule| Reverse(rule.priority()));
+        let trivia_overrides = has_trivia_overrides(&sorted_rules);
 
         Self {
             context,
+            rule_groups: vec![None; sorted_rules.len()],
             rules: sorted_rules,
+            recovery_rules: Vec::new(),
+            groups: RuleGroups::new(),
+            last_rule: None,
+            policy: ChoicePolicy::default(),
+            report_ambiguities: false,
+            ambiguities: Vec::new(),
+            cut_failures: Vec::new(),
+            next_token: None,
+            observer: None,
+            trivia: None,
+            install_trivia: None,
+            trivia_overrides,
+            node_start: None,
+            #[cfg(debug_assertions)]
+            strict: false,
+        }
+    }
+
+    /// Sets how the parser chooses among matching rules of equal priority.
+    pub fn with_choice_policy(mut self, policy: ChoicePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Changes the choice policy for subsequent nodes.
+    pub fn set_choice_policy(&mut self, policy: ChoicePolicy) {
+        self.policy = policy;
+    }
+
+    /// Returns the current choice policy.
+    pub fn choice_policy(&self) -> ChoicePolicy {
+        self.policy
+    }
+
+    /// Enables ambiguity reports: before each node, every enabled rule is
+    /// tried at the current token, and if matches of different lengths are
+    /// found an [`Ambiguity`] is recorded.
+    ///
+    /// This parses every position once per rule, so it is meant for
+    /// debugging grammars, not for production use.
+    pub fn with_ambiguity_reports(mut self, enabled: bool) -> Self {
+        self.report_ambiguities = enabled;
+        self
+    }
+
+    /// Turns on strict mode: after every rule the parser tries, it checks
+    /// that a match consumed tokens without moving backwards and that a
+    /// failure left the context at the rule's first token (or at its cut),
+    /// and panics with the rule's name and position if not.
+    ///
+    /// Strict mode only exists in builds with debug assertions; in release
+    /// builds this does nothing.
+    #[cfg_attr(not(debug_assertions), allow(unused_mut))]
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        #[cfg(debug_assertions)]
+        {
+            self.strict = strict;
         }
+        #[cfg(not(debug_assertions))]
+        let _ = strict;
+        self
+    }
+
+    /// Returns true if strict mode is on.
+    pub fn is_strict(&self) -> bool {
+        #[cfg(debug_assertions)]
+        return self.strict;
+        #[cfg(not(debug_assertions))]
+        false
+    }
+
+    /// Returns the ambiguities recorded so far.
+    pub fn ambiguities(&self) -> &[Ambiguity] {
+        &self.ambiguities
+    }
+
+    /// Returns and clears the ambiguities recorded so far.
+    pub fn take_ambiguities(&mut self) -> Vec<Ambiguity> {
+        std::mem::take(&mut self.ambiguities)
+    }
+
+    /// Returns what was expected where the parse failed: the furthest
+    /// [`expect_named`](ParseContext::expect_named) failure at or after the
+    /// current token.
+    pub fn expected(&self) -> Option<&Expected<Tok>> {
+        self.context
+            .expected()
+            .filter(|expected| expected.token_index >= self.context.token_index())
+    }
+
+    /// Returns the rules that failed after a [cut](ParseContext::cut), in
+    /// the order they failed. The parser stops at each one.
+    pub fn cut_failures(&self) -> &[CutFailure] {
+        &self.cut_failures
+    }
+
+    /// Returns and clears the cut failures recorded so far.
+    pub fn take_cut_failures(&mut self) -> Vec<CutFailure> {
+        std::mem::take(&mut self.cut_failures)
+    }
+
+    /// Installs `observer` to be told about rule entries and exits,
+    /// backtracks and produced nodes. Replaces any previous observer.
+    pub fn with_observer(mut self, observer: impl ParserObserver<Ast> + 'static) -> Self {
+        self.set_observer(observer);
+        self
+    }
+
+    /// Installs `observer` for subsequent nodes. Replaces any previous
+    /// observer.
+    pub fn set_observer(&mut self, observer: impl ParserObserver<Ast> + 'static) {
+        self.observer = Some(Box::new(observer));
+    }
+
+    /// Removes and returns the observer.
+    pub fn take_observer(&mut self) -> Option<Box<dyn ParserObserver<Ast>>> {
+        self.observer.take()
+    }
+
+    /// Skips the tokens `policy` treats as trivia before every node, so
+    /// every rule starts at a significant token.
+    ///
+    /// The policy is also put in the context's state map, where rules find
+    /// it through [`skip_trivia`](ParseContext::skip_trivia) to skip trivia
+    /// between their own tokens. Rules can override it with
+    /// [`ParsingRule::trivia`].
+    pub fn with_trivia(mut self, policy: TriviaPolicy<Tok>) -> Self
+    where
+        Tok: 'static,
+    {
+        if let Some(state) = self.context.state_map() {
+            install_trivia(state, Some(policy));
+        }
+        self.trivia = Some(policy);
+        self.install_trivia = Some(install_trivia::<Tok>);
+        self
+    }
+
+    /// Returns the trivia policy set by [`with_trivia`](Self::with_trivia).
+    pub fn trivia(&self) -> Option<&TriviaPolicy<Tok>> {
+        self.trivia.as_ref()
     }
 
     /// Creates a parser from a token iterator.
@@ -54,58 +263,572 @@ where
         &mut self.context
     }
 
+    /// Sends the diagnostics of this parser and its rules to `diagnostics`.
+    /// See [`ParseContext::set_diagnostics`].
+    pub fn with_diagnostics(mut self, diagnostics: Diagnostics) -> Self {
+        self.context.set_diagnostics(diagnostics);
+        self
+    }
+
+    /// Returns the rules in the order they are tried (highest priority first).
+    pub fn rules(&self) -> &[Box<dyn ParsingRule<Ctx, Tok, Ast>>] {
+        &self.rules
+    }
+
+    /// Adds an error production, tried only after every normal rule has
+    /// failed. See [`RecoveryRule`].
+    pub fn with_recovery_rule(mut self, rule: impl RecoveryRule<Ctx, Tok, Ast> + 'static) -> Self {
+        self.add_recovery_rule(rule);
+        self
+    }
+
+    /// Adds an error production for subsequent nodes.
+    pub fn add_recovery_rule(
+        &mut self,
+        rule: impl RecoveryRule<Ctx, Tok, Ast> + 'static,
+    ) -> &mut Self {
+        self.recovery_rules.push(Box::new(rule));
+        self.recovery_rules
+            .sort_by_key(|rule| Reverse(rule.priority()));
+        self
+    }
+
+    /// Returns the error productions in the order they are tried.
+    pub fn recovery_rules(&self) -> &[Box<dyn RecoveryRule<Ctx, Tok, Ast>>] {
+        &self.recovery_rules
+    }
+
+    /// Adds `rules` to the group called `name`, creating the group (enabled)
+    /// if needed.
+    ///
+    /// Grouped rules can be switched on and off together with
+    /// [`set_group_enabled`](Self::set_group_enabled), e.g. for
+    /// feature-flagged dialect extensions.
+    pub fn add_group(
+        &mut self,
+        name: &str,
+        rules: Vec<Box<dyn ParsingRule<Ctx, Tok, Ast>>>,
+    ) -> &mut Self {
+        let group = self.groups.get_or_insert(name);
+        let mut entries: Vec<_> = self
+            .rules
+            .drain(..)
+            .zip(self.rule_groups.drain(..))
+            .chain(rules.into_iter().map(|rule| (rule, Some(group))))
+            .collect();
+        entries.sort_by_key(|(rule, _)| Reverse(rule.priority()));
+        (self.rules, self.rule_groups) = entries.into_iter().unzip();
+        self.trivia_overrides = has_trivia_overrides(&self.rules);
+
+        self.last_rule = None;
+        self
+    }
+
+    /// Enables or disables the rules of group `name`. Returns false if there
+    /// is no such group.
+    pub fn set_group_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        self.groups.set_enabled(name, enabled)
+    }
+
+    /// Returns whether group `name` is enabled, or `None` if it does not exist.
+    pub fn is_group_enabled(&self, name: &str) -> Option<bool> {
+        self.groups.is_enabled(name)
+    }
+
+    /// Returns the rule groups registered with [`add_group`](Self::add_group).
+    pub fn groups(&self) -> &RuleGroups {
+        &self.groups
+    }
+
+    /// Returns the rule that produced the most recent node.
+    pub fn last_rule(&self) -> Option<&dyn ParsingRule<Ctx, Tok, Ast>> {
+        self.last_rule.map(|idx| self.rules[idx].as_ref())
+    }
+
     /// Tries to parse the next AST node using the rules.
     ///
     /// This method optimizes rule matching by:
-    /// 1. Using quick_check() to skip rules that definitely won't match
+    /// 1. Using quick_check2() to skip rules that definitely won't match
     /// 2. Only creating checkpoints when actually trying a rule
+    ///
+    /// If no rule matches, the [recovery rules](Self::add_recovery_rule)
+    /// get a chance to turn the input into an error node.
     pub fn next_node(&mut self) -> Option<Ast> {
-        for rule in &mut self.rules {
-            // Quick check: borrow the current token only within this block so the
-            // mutable borrow is released before try_parse needs &mut self.context.
-            let should_try = {
-                let token_ref = self.context.peek();
-                !matches!(rule.quick_check(token_ref), Some(false))
-            };
+        self.node_start = None;
+        if self.trivia_overrides && self.trivia.is_some() {
+            let raw = self.context.checkpoint();
+            self.skip_trivia();
+            self.node_start = Some((raw, self.context.checkpoint()));
+        } else {
+            self.skip_trivia();
+        }
+        self.next_token = self.context.peek_at(1).cloned();
+        let node = if self.report_ambiguities {
+            self.select_node_reporting()
+        } else {
+            self.select_node()
+        };
+        if let Some(node) = node {
+            if let (Some(observer), Some(idx)) = (&mut self.observer, self.last_rule) {
+                observer.on_node(self.rules[idx].name(), &node);
+            }
+            return Some(node);
+        }
+
+        // Recovery starts after the trivia, even if a raw rule failed last.
+        if let Some((_, start)) = self.node_start {
+            if self.context.token_index() < start.token_index() {
+                self.context.restore(start);
+            }
+        }
+        let (idx, node) = self.recover()?;
+        self.last_rule = None;
+        if let Some(observer) = &mut self.observer {
+            observer.on_node(self.recovery_rules[idx].name(), &node);
+        }
+        Some(node)
+    }
+
+    /// Returns the number of trivia tokens at the current token, under the
+    /// [trivia policy](Self::with_trivia), without consuming them.
+    fn trivia_len(&mut self) -> usize {
+        let Some(policy) = self.trivia else {
+            return 0;
+        };
+        let mut len = 0;
+        while self
+            .context
+            .peek_at(len)
+            .is_some_and(|token| policy.is_trivia(token))
+        {
+            len += 1;
+        }
+        len
+    }
+
+    /// Consumes the trivia tokens at the current token. They are committed
+    /// unless a rule with its own trivia mode may still want them.
+    fn skip_trivia(&mut self) {
+        let len = self.trivia_len();
+        for _ in 0..len {
+            self.context.advance();
+        }
+        if len > 0 && !self.trivia_overrides {
+            self.context.commit();
+        }
+    }
 
-            if !should_try {
+    /// Moves to where rule `idx` starts under its trivia mode: before the
+    /// trivia for a raw rule, after it otherwise.
+    fn enter_trivia_mode(&mut self, idx: usize) {
+        let (Some((raw, skipped)), Some(policy)) = (self.node_start, self.trivia) else {
+            return;
+        };
+        let mode = self.rules[idx].trivia();
+        let start = if mode == TriviaMode::Inherit {
+            skipped
+        } else {
+            raw
+        };
+        if self.context.token_index() != start.token_index() {
+            self.context.restore(start);
+        }
+        if mode == TriviaMode::KeepNewlines {
+            let policy = policy.with_newlines_skipped(false);
+            while self
+                .context
+                .peek()
+                .is_some_and(|token| policy.is_trivia(token))
+            {
+                self.context.advance();
+            }
+        }
+        self.next_token = self.context.peek_at(1).cloned();
+    }
+
+    /// Puts the trivia policy rule `idx` runs under into the context's state
+    /// map, for [`ParseContext::skip_trivia`]. Returns true if it differs
+    /// from the parser's, which [`reset_trivia`](Self::reset_trivia) puts
+    /// back.
+    fn install_rule_trivia(&mut self, idx: usize) -> bool {
+        let (Some(policy), Some(install)) = (self.trivia, self.install_trivia) else {
+            return false;
+        };
+        let mode = self.rules[idx].trivia();
+        if mode == TriviaMode::Inherit {
+            return false;
+        }
+        let Some(state) = self.context.state_map() else {
+            return false;
+        };
+        install(state, policy.for_mode(mode));
+        true
+    }
+
+    fn reset_trivia(&mut self) {
+        if let (Some(policy), Some(install)) = (self.trivia, self.install_trivia) {
+            if let Some(state) = self.context.state_map() {
+                install(state, Some(policy));
+            }
+        }
+    }
+
+    /// Tries the recovery rules at the current token. Returns the node and
+    /// the index of the rule that produced it.
+    fn recover(&mut self) -> Option<(usize, Ast)> {
+        if self.recovery_rules.is_empty() || self.context.peek().is_none() {
+            return None;
+        }
+        let expected = self.expected().cloned();
+        for idx in 0..self.recovery_rules.len() {
+            let rule = &mut self.recovery_rules[idx];
+            let node = self
+                .context
+                .transaction(|ctx| rule.recover(ctx, expected.as_ref()));
+            if let Some(node) = node {
+                return Some((idx, node));
+            }
+        }
+        None
+    }
+
+    /// [`select_node`](Self::select_node), recording an [`Ambiguity`] if
+    /// rules match with different lengths.
+    fn select_node_reporting(&mut self) -> Option<Ast> {
+        let token_index = self.context.token_index();
+        let alternatives = self.probe_alternatives();
+        let node = self.select_node();
+
+        let first_len = alternatives.first().map(|(_, alt)| alt.consumed);
+        if alternatives
+            .iter()
+            .any(|(_, alt)| Some(alt.consumed) != first_len)
+        {
+            let chosen = node.as_ref().and(self.last_rule);
+            let alternatives = alternatives
+                .into_iter()
+                .map(|(idx, alt)| Alternative {
+                    chosen: Some(idx) == chosen,
+                    ..alt
+                })
+                .collect();
+            self.ambiguities.push(Ambiguity {
+                token_index,
+                alternatives,
+            });
+        }
+        node
+    }
+
+    /// Tries every candidate rule at the current token and returns those
+    /// that matched, leaving the context unchanged.
+    fn probe_alternatives(&mut self) -> Vec<(usize, Alternative)> {
+        let mut alternatives = Vec::new();
+        for idx in 0..self.rules.len() {
+            if !self.should_try(idx) {
                 continue;
             }
+            let (node, consumed) = self.try_rule_speculatively(idx);
+            if let Some(node) = node {
+                let rule = &self.rules[idx];
+                alternatives.push((
+                    idx,
+                    Alternative {
+                        rule: rule.name().to_string(),
+                        rule_id: rule.id(),
+                        priority: rule.priority(),
+                        consumed,
+                        span: node.span(),
+                        chosen: false,
+                    },
+                ));
+            }
+        }
+        alternatives
+    }
 
-            let checkpoint = self.context.checkpoint();
-            if let Some(node) = rule.try_parse(&mut self.context) {
-                self.context.commit();
+    /// Picks the next node according to the choice policy.
+    fn select_node(&mut self) -> Option<Ast> {
+        if self.policy == ChoicePolicy::LongestMatch {
+            return self.next_longest_node();
+        }
+
+        let cut_failures = self.cut_failures.len();
+        for idx in 0..self.rules.len() {
+            if !self.should_try(idx) {
+                continue;
+            }
+
+            if let Some(node) = self.apply_rule(idx) {
                 return Some(node);
             }
-            // If rule didn't match, restore context
-            self.context.restore(checkpoint);
+            if self.cut_failures.len() > cut_failures {
+                // The rule committed to its branch; no alternatives.
+                return None;
+            }
         }
         None
     }
 
+    /// [`next_node`](Self::next_node) under [`ChoicePolicy::LongestMatch`].
+    fn next_longest_node(&mut self) -> Option<Ast> {
+        let mut tier_start = 0;
+        while tier_start < self.rules.len() {
+            let priority = self.rules[tier_start].priority();
+            let tier_end = self.rules[tier_start..]
+                .iter()
+                .position(|rule| rule.priority() != priority)
+                .map_or(self.rules.len(), |len| tier_start + len);
+
+            // (rule index, tokens consumed) of the longest match so far.
+            let mut best: Option<(usize, usize)> = None;
+            for idx in tier_start..tier_end {
+                if !self.should_try(idx) {
+                    continue;
+                }
+                let (node, consumed) = self.try_rule_speculatively(idx);
+                if node.is_some() && best.is_none_or(|(_, longest)| consumed > longest) {
+                    best = Some((idx, consumed));
+                }
+            }
+
+            if let Some((idx, _)) = best {
+                if let Some(node) = self.apply_rule(idx) {
+                    return Some(node);
+                }
+            }
+            tier_start = tier_end;
+        }
+        None
+    }
+
+    /// Returns whether rule `idx` is enabled and its `quick_check2` does not
+    /// rule out the current tokens.
+    fn should_try(&mut self, idx: usize) -> bool {
+        if !self.groups.is_active(self.rule_groups[idx]) {
+            return false;
+        }
+        self.enter_trivia_mode(idx);
+        self.rules[idx].quick_check2(self.context.peek(), self.next_token.as_ref()) != Some(false)
+    }
+
+    /// Runs rule `idx`, committing on a match and restoring the context
+    /// otherwise. Records a [`CutFailure`] if the rule cut before failing.
+    fn apply_rule(&mut self, idx: usize) -> Option<Ast> {
+        self.enter_trivia_mode(idx);
+        let start = self.context.token_index();
+        let start_position = self.context.position();
+        let cut_before = self.context.last_cut();
+        self.notify_enter(idx, start);
+        let own_trivia = self.install_rule_trivia(idx);
+        let rule = &mut self.rules[idx];
+        let mut end = start;
+        let mut end_position = Position::default();
+        let node = self.context.transaction(|ctx| {
+            let node = rule.try_parse(ctx);
+            end = ctx.token_index();
+            end_position = ctx.position();
+            node
+        });
+        if own_trivia {
+            self.reset_trivia();
+        }
+        self.notify_exit(idx, start, end, node.is_some(), node.is_none());
+        let new_cut = self
+            .context
+            .last_cut()
+            .filter(|&cut| Some(cut) != cut_before);
+        if self.is_strict() {
+            let after = self.context.token_index();
+            let violation = match &node {
+                Some(_) if end == start => Some("produced a node without consuming tokens"),
+                Some(_) if end < start => Some("produced a node after moving backwards"),
+                None if new_cut.is_none() && after != start => {
+                    Some("failed without leaving the input where it started")
+                }
+                _ => None,
+            };
+            if let Some(violation) = violation {
+                self.strict_violation(idx, violation, start, start_position);
+            }
+        }
+        if node.is_none() {
+            if let Some(cut) = new_cut {
+                self.cut_failures.push(CutFailure {
+                    rule: self.rules[idx].name().to_string(),
+                    start,
+                    cut: cut.token_index(),
+                    token_index: end,
+                    position: end_position,
+                });
+            }
+        }
+        let node = node?;
+        self.last_rule = Some(idx);
+        Some(node)
+    }
+
+    /// Runs rule `idx` and always puts back what it consumed. Returns the
+    /// node it produced and the number of tokens it consumed.
+    ///
+    /// The rule runs in a transaction that is rolled back, like
+    /// [`probe`](ParseContext::probe), so its commits discard nothing.
+    fn try_rule_speculatively(&mut self, idx: usize) -> (Option<Ast>, usize) {
+        let checkpoint = self.context.begin_transaction();
+        let start = checkpoint.token_index();
+        self.notify_enter(idx, start);
+        let own_trivia = self.install_rule_trivia(idx);
+        let node = self.rules[idx].try_parse(&mut self.context);
+        if own_trivia {
+            self.reset_trivia();
+        }
+        let end = self.context.token_index();
+        self.context.end_transaction(checkpoint, false);
+        self.notify_exit(idx, start, end, node.is_some(), true);
+        if self.is_strict() {
+            let violation = if node.is_some() && end <= start {
+                Some("produced a node without consuming tokens")
+            } else if self.context.token_index() != start {
+                Some("could not be rolled back to where it started")
+            } else {
+                None
+            };
+            if let Some(violation) = violation {
+                self.strict_violation(idx, violation, start, checkpoint.position());
+            }
+        }
+        (node, end - start)
+    }
+
+    /// Panics with a strict-mode violation by rule `idx`, which started at
+    /// token `start`.
+    fn strict_violation(&self, idx: usize, violation: &str, start: usize, position: Position) {
+        panic!(
+            "strict mode: rule `{}` {} at {}:{} (token {})",
+            self.rules[idx].name(),
+            violation,
+            position.line,
+            position.column,
+            start
+        );
+    }
+
+    fn notify_enter(&mut self, idx: usize, token_index: usize) {
+        if let Some(observer) = &mut self.observer {
+            observer.on_rule_enter(self.rules[idx].name(), token_index);
+        }
+    }
+
+    /// Reports that rule `idx` ran from token `start` to `end`, and a
+    /// backtrack if the context was `restored` to `start`.
+    fn notify_exit(&mut self, idx: usize, start: usize, end: usize, matched: bool, restored: bool) {
+        if let Some(observer) = &mut self.observer {
+            let rule = self.rules[idx].name();
+            observer.on_rule_exit(rule, end, matched);
+            if restored && end > start {
+                observer.on_backtrack(rule, end, start);
+            }
+        }
+    }
+
     /// Parses the entire input and returns all AST nodes.
     ///
     /// This method will continue parsing until EOF is reached or
     /// no progress can be made (indicating a parsing error).
+    /// Use [`iter`](Self::iter) to receive nodes one at a time instead.
     pub fn parse(&mut self) -> Vec<Ast> {
-        let mut nodes = Vec::new();
-        while !self.context.is_eof() {
-            let offset_before = self.context.token_index();
-            if let Some(node) = self.next_node() {
-                if self.context.token_index() == offset_before {
-                    eprintln!("Warning: No progress made at token index {}", offset_before);
-                    break;
-                }
-                nodes.push(node);
-            } else if self.context.token_index() == offset_before {
-                eprintln!("Error: No rule matched token at index {}", offset_before);
-                if let Some(token) = self.context.peek() {
-                    eprintln!("Current token: {:?}", token);
-                }
-                break;
-            }
+        self.iter().collect()
+    }
+
+    /// Like [`parse_complete`](Self::parse_complete), but leftover tokens
+    /// matching `is_eof` (explicit end-of-input tokens) are allowed. Works
+    /// for tokens that don't implement `LexToken`; pass `|_| false` if they
+    /// have no EOF token.
+    pub fn parse_complete_ignoring<F>(
+        &mut self,
+        is_eof: F,
+    ) -> Result<Vec<Ast>, IncompleteParse<Tok, Ast>>
+    where
+        F: Fn(&Tok) -> bool,
+    {
+        let nodes = self.parse();
+        let remaining = self.remaining_tokens();
+        if remaining.iter().all(is_eof) {
+            return Ok(nodes);
         }
-        nodes
+        Err(self.incomplete(nodes, remaining))
+    }
+
+    /// Builds the error for a parse that stopped before `remaining`.
+    fn incomplete(&self, nodes: Vec<Ast>, remaining: Vec<Tok>) -> IncompleteParse<Tok, Ast> {
+        IncompleteParse {
+            nodes,
+            token_index: self.context.token_index(),
+            position: self.context.position(),
+            remaining: remaining.len(),
+            expected: self.expected().cloned().map(Box::new),
+            preview: remaining.into_iter().take(PREVIEW_LEN).collect(),
+        }
+    }
+
+    /// Returns the tokens not consumed yet, without consuming them.
+    ///
+    /// Mostly useful in tests, e.g. to check where a rule stopped. With a
+    /// lazy or streaming context this pulls in the rest of the input.
+    pub fn remaining_tokens(&mut self) -> Vec<Tok> {
+        let mut tokens = Vec::new();
+        while let Some(token) = self.context.peek_at(tokens.len()) {
+            tokens.push(token.clone());
+        }
+        tokens
+    }
+
+    /// Returns an iterator that parses nodes lazily, one per `next()` call.
+    ///
+    /// It stops under the same conditions as [`parse`](Self::parse), so
+    /// downstream passes can stream nodes without holding the whole forest
+    /// in memory.
+    pub fn iter(&mut self) -> ParseIter<'_, Ctx, Tok, Ast> {
+        ParseIter {
+            parser: self,
+            done: false,
+        }
+    }
+
+    /// Lists the names of the rules whose `quick_check2` accepts `token`
+    /// followed by the token after it.
+    fn candidate_names(&self, token: Option<&Tok>) -> String {
+        let names: Vec<&str> = self
+            .rules
+            .iter()
+            .zip(&self.rule_groups)
+            .filter(|(rule, group)| {
+                self.groups.is_active(**group)
+                    && rule.quick_check2(token, self.next_token.as_ref()) != Some(false)
+            })
+            .map(|(rule, _)| rule.name())
+            .collect();
+        if names.is_empty() {
+            "none".to_string()
+        } else {
+            names.join(", ")
+        }
+    }
+
+    /// Describes the failure to match the current token, naming the rules
+    /// that were tried.
+    fn no_match_message(&mut self) -> String {
+        let token = self.context.peek().cloned();
+        let found = match &token {
+            Some(token) => format!("token {:?}", token),
+            None => "end of input".to_string(),
+        };
+        format!(
+            "no rule matched {} at index {} (rules tried: {})",
+            found,
+            self.context.token_index(),
+            self.candidate_names(token.as_ref())
+        )
     }
 
     /// Parses a single AST node and returns it, or None if no rule matches.
@@ -114,4 +837,427 @@ where
     pub fn parse_one(&mut self) -> Option<Ast> {
         self.next_node()
     }
+
+    /// Consumes the current token without parsing it and returns it, or
+    /// `None` at the end of the input.
+    ///
+    /// Together with [`parse_one`](Self::parse_one) and
+    /// [`synchronize`](Self::synchronize), this lets a REPL or an
+    /// error-tolerant tool decide what happens when no rule matches,
+    /// instead of stopping like [`parse`](Self::parse) does.
+    pub fn skip_token(&mut self) -> Option<Tok> {
+        let token = self.context.advance()?;
+        self.context.commit();
+        Some(token)
+    }
+
+    /// Skips tokens until the current one matches `is_sync`, and returns
+    /// the skipped tokens. The matching token is left in place.
+    ///
+    /// ```ignore
+    /// loop {
+    ///     match parser.parse_one() {
+    ///         Some(node) => nodes.push(node),
+    ///         None if parser.context_mut().is_eof() => break,
+    ///         None => {
+    ///             let skipped = parser.synchronize(|t| *t == Token::Semicolon);
+    ///             report_garbage(&skipped);
+    ///             parser.skip_token();
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn synchronize<F>(&mut self, mut is_sync: F) -> Vec<Tok>
+    where
+        F: FnMut(&Tok) -> bool,
+    {
+        let mut skipped = Vec::new();
+        while self.context.peek().is_some_and(|token| !is_sync(token)) {
+            skipped.extend(self.context.advance());
+        }
+        self.context.commit();
+        skipped
+    }
+
+    /// Parses nodes until the current token matches `is_end`, and returns
+    /// them. The matching token is left in place.
+    ///
+    /// Also stops, without reporting anything, at the end of the input and
+    /// wherever [`parse_one`](Self::parse_one) fails or makes no progress;
+    /// check [`context_mut`](Self::context_mut)`().peek()` to tell which.
+    pub fn parse_until<F>(&mut self, mut is_end: F) -> Vec<Ast>
+    where
+        F: FnMut(&Tok) -> bool,
+    {
+        let mut nodes = Vec::new();
+        loop {
+            let trivia = self.trivia_len();
+            if self.context.peek_at(trivia).is_none_or(&mut is_end) {
+                break;
+            }
+            let before = self.context.token_index();
+            match self.next_node() {
+                Some(node) if self.context.token_index() != before => nodes.push(node),
+                _ => break,
+            }
+        }
+        nodes
+    }
+
+    /// Parses one node per line, for line-oriented languages such as
+    /// assembly, config files or shell scripts. `is_newline` tells the
+    /// line breaks apart, e.g. `|t| t.kind() == Some(TokenKind::Newline)`
+    /// for lexer tokens.
+    ///
+    /// Unlike [`parse`](Self::parse), a bad line does not end the parse:
+    /// the rest of it is skipped, an error is reported through the
+    /// context's [`report`](ParseContext::report), and parsing resumes on
+    /// the next line. Blank lines are skipped.
+    pub fn parse_per_line<F>(&mut self, is_newline: F) -> Vec<Ast>
+    where
+        F: FnMut(&Tok) -> bool,
+    {
+        self.parse_separated(is_newline, "end of line", false)
+    }
+
+    /// Parses statements ended by a token matching `is_terminator`, such as
+    /// `;`, one node per statement.
+    ///
+    /// Recovers like [`parse_per_line`](Self::parse_per_line), skipping to
+    /// the next terminator. A statement that parses but is not followed by
+    /// a terminator is kept, with an error. Empty statements are skipped.
+    pub fn parse_statements<F>(&mut self, is_terminator: F) -> Vec<Ast>
+    where
+        F: FnMut(&Tok) -> bool,
+    {
+        self.parse_separated(is_terminator, "terminator", true)
+    }
+
+    /// Parses exactly one node that must span the whole input, as a
+    /// calculator or a `--eval` flag needs. Anything left over, or no
+    /// node at all, is an [`IncompleteParse`] holding what was parsed.
+    pub fn parse_single_expression_strict(&mut self) -> Result<Ast, IncompleteParse<Tok, Ast>> {
+        let node = self.next_node();
+        self.skip_trivia();
+        let remaining = self.remaining_tokens();
+        match node {
+            Some(node) if remaining.is_empty() => Ok(node),
+            node => Err(self.incomplete(node.into_iter().collect(), remaining)),
+        }
+    }
+
+    /// Parses one node between runs of tokens matching `is_end`, skipping
+    /// to the next such token after a failure. If `required`, a node must
+    /// be followed by one even at the end of the input.
+    fn parse_separated<F>(&mut self, mut is_end: F, end_name: &str, required: bool) -> Vec<Ast>
+    where
+        F: FnMut(&Tok) -> bool,
+    {
+        let mut nodes = Vec::new();
+        loop {
+            let mut trivia = self.trivia_len();
+            while self.context.peek_at(trivia).is_some_and(&mut is_end) {
+                self.skip_trivia();
+                self.skip_token();
+                trivia = self.trivia_len();
+            }
+            if self.context.peek_at(trivia).is_none() {
+                self.skip_trivia();
+                return nodes;
+            }
+
+            let before = self.context.token_index();
+            let cut_failures = self.cut_failures.len();
+            let parsed = match self.next_node() {
+                Some(node) if self.context.token_index() != before => {
+                    nodes.push(node);
+                    true
+                }
+                _ => false,
+            };
+            if !parsed {
+                let message = match self.cut_failures.get(cut_failures) {
+                    Some(failure) => failure.to_string(),
+                    None => self.no_match_message(),
+                };
+                let position = self.context.position();
+                let skipped = self.synchronize(&mut is_end);
+                self.report_at(
+                    position,
+                    format!(
+                        "{}; skipped {} token(s) up to the {}",
+                        message,
+                        skipped.len(),
+                        end_name
+                    ),
+                );
+                continue;
+            }
+
+            self.skip_trivia();
+            match self.context.peek().cloned() {
+                Some(token) if !is_end(&token) => {
+                    let position = self.context.position();
+                    let skipped = self.synchronize(&mut is_end);
+                    self.report_at(
+                        position,
+                        format!(
+                            "expected {}, found token {:?}; skipped {} token(s)",
+                            end_name,
+                            token,
+                            skipped.len()
+                        ),
+                    );
+                }
+                None if required && self.context.is_eof() => {
+                    let position = self.context.position();
+                    let message = format!("expected {}, found end of input", end_name);
+                    self.report_at(position, message);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Reports an error at `position`.
+    fn report_at(&mut self, position: Position, message: String) {
+        self.context
+            .report(Diagnostic::error((position, position), message));
+    }
+}
+
+impl<Tok, Ast> Parser<DefaultContext<Tok>, Tok, Ast>
+where
+    Tok: Clone + std::fmt::Debug,
+    Ast: AstNode,
+{
+    /// Parses a separate token stream with a different rule set.
+    ///
+    /// The parser-side counterpart of `Lexer::sublex`: a rule that captured
+    /// an embedded region re-lexes it with the embedded grammar and parses
+    /// the result here. Tokens keep the positions they were lexed with, so
+    /// nodes built from sub-lexed tokens point into the outer input.
+    pub fn reparse_tokens<I>(
+        tokens: I,
+        rules: Vec<Box<dyn ParsingRule<DefaultContext<Tok>, Tok, Ast>>>,
+    ) -> Vec<Ast>
+    where
+        I: IntoIterator<Item = Tok>,
+    {
+        Parser::new(DefaultContext::from_token_iter(tokens), rules).parse()
+    }
+}
+
+impl<Tok, Ast> Parser<DefaultContext<Tok>, Tok, Ast>
+where
+    Tok: LexToken,
+    Ast: AstNode,
+{
+    /// Creates a parser from lexer tokens, tracking positions through
+    /// `LexToken::position`. Use [`from_tokens`](Self::from_tokens) for
+    /// tokens that don't implement `LexToken`.
+    pub fn from_lex_tokens<I>(
+        tokens: I,
+        rules: Vec<Box<dyn ParsingRule<DefaultContext<Tok>, Tok, Ast>>>,
+    ) -> Self
+    where
+        I: IntoIterator<Item = Tok>,
+    {
+        let context = DefaultContext::from_token_iter(tokens).with_lex_positions();
+        Parser::new(context, rules)
+    }
+}
+
+#[cfg(feature = "streaming")]
+impl<P, Tok, Ast> Parser<ProducerContext<P, Tok>, Tok, Ast>
+where
+    P: TokenProducer<Tok>,
+    Tok: LexToken,
+    Ast: AstNode,
+{
+    /// Creates a parser that pulls tokens from `producer` as it needs them,
+    /// e.g. straight from a lexer, without collecting them first.
+    ///
+    /// Tokens are kept in a [`LazyContext`] window of [`PRODUCER_WINDOW`]
+    /// tokens, so rules may backtrack that far (or to the start of an open
+    /// transaction). Positions come from `LexToken::position`.
+    pub fn from_producer(producer: P, rules: ProducerRules<P, Tok, Ast>) -> Self {
+        let context =
+            LazyContext::new(ProducerTokens::new(producer), PRODUCER_WINDOW).with_lex_positions();
+        Parser::new(context, rules)
+    }
+}
+
+impl<Ctx, Tok, Ast> Parser<Ctx, Tok, Ast>
+where
+    Ctx: ParseContext<Tok>,
+    Tok: LexToken,
+    Ast: AstNode,
+{
+    /// Parses the entire input like [`parse`](Self::parse), but fails if
+    /// any tokens are left over because no rule matched them. Trailing EOF
+    /// tokens ([`LexToken::is_eof`]) don't count as leftovers.
+    pub fn parse_complete(&mut self) -> Result<Vec<Ast>, IncompleteParse<Tok, Ast>> {
+        self.parse_complete_ignoring(Tok::is_eof)
+    }
+}
+
+/// Returns true if any of `rules` has its own trivia mode.
+fn has_trivia_overrides<Ctx, Tok, Ast>(rules: &[Box<dyn ParsingRule<Ctx, Tok, Ast>>]) -> bool
+where
+    Ctx: ParseContext<Tok>,
+    Tok: Clone + std::fmt::Debug,
+    Ast: AstNode,
+{
+    rules
+        .iter()
+        .any(|rule| rule.trivia() != TriviaMode::Inherit)
+}
+
+fn install_trivia<Tok: 'static>(state: &mut StateMap, policy: Option<TriviaPolicy<Tok>>) {
+    match policy {
+        Some(policy) => {
+            state.insert(policy);
+        }
+        None => {
+            state.remove::<TriviaPolicy<Tok>>();
+        }
+    }
+}
+
+/// Returned by [`Parser::parse_complete`] when parsing stopped before the
+/// end of input.
+#[derive(Debug, Clone)]
+pub struct IncompleteParse<Tok, Ast> {
+    /// The nodes parsed before parsing stopped.
+    pub nodes: Vec<Ast>,
+    /// Index of the first unconsumed token.
+    pub token_index: usize,
+    /// Position reported by the context where parsing stopped.
+    pub position: Position,
+    /// Number of unconsumed tokens.
+    pub remaining: usize,
+    /// The first few unconsumed tokens.
+    pub preview: Vec<Tok>,
+    /// What the rules expected where parsing stopped, if they used
+    /// [`expect_named`](ParseContext::expect_named).
+    pub expected: Option<Box<Expected<Tok>>>,
+}
+
+impl<Tok, Ast> fmt::Display for IncompleteParse<Tok, Ast>
+where
+    Tok: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.remaining == 0 {
+            // Only `parse_single_expression_strict` fails with nothing left.
+            return write!(
+                f,
+                "expected a node at token {} (line {}, column {}), found end of input",
+                self.token_index, self.position.line, self.position.column
+            );
+        }
+        write!(
+            f,
+            "unparsed input at token {} (line {}, column {}): {} token(s) left, starting with {:?}",
+            self.token_index,
+            self.position.line,
+            self.position.column,
+            self.remaining,
+            self.preview
+        )?;
+        if self.remaining > self.preview.len() {
+            write!(f, " ...")?;
+        }
+        Ok(())
+    }
+}
+
+impl<Tok, Ast> std::error::Error for IncompleteParse<Tok, Ast>
+where
+    Tok: fmt::Debug,
+    Ast: fmt::Debug,
+{
+}
+
+/// Lazily parses AST nodes from a borrowed [`Parser`].
+///
+/// Created by [`Parser::iter`]. Once it returns `None` it stays exhausted.
+pub struct ParseIter<'a, Ctx, Tok, Ast>
+where
+    Ctx: ParseContext<Tok>,
+    Tok: Clone + std::fmt::Debug,
+    Ast: AstNode,
+{
+    parser: &'a mut Parser<Ctx, Tok, Ast>,
+    done: bool,
+}
+
+impl<Ctx, Tok, Ast> Iterator for ParseIter<'_, Ctx, Tok, Ast>
+where
+    Ctx: ParseContext<Tok>,
+    Tok: Clone + std::fmt::Debug,
+    Ast: AstNode,
+{
+    type Item = Ast;
+
+    fn next(&mut self) -> Option<Ast> {
+        while !self.done && !self.parser.context.is_eof() {
+            let offset_before = self.parser.context.token_index();
+            let cut_failures = self.parser.cut_failures.len();
+            if let Some(node) = self.parser.next_node() {
+                if self.parser.context.token_index() == offset_before {
+                    let rule = self.parser.last_rule().map_or("?", |rule| rule.name());
+                    let message = format!(
+                        "no progress made at token index {} (rule `{}`)",
+                        offset_before, rule
+                    );
+                    self.report(Diagnostic::warning(self.here(), message));
+                    self.done = true;
+                    return None;
+                }
+                return Some(node);
+            } else if self.parser.cut_failures.len() > cut_failures {
+                let message = self.parser.cut_failures[cut_failures].to_string();
+                self.report(Diagnostic::error(self.here(), message));
+                self.done = true;
+            } else if self.parser.context.token_index() == offset_before {
+                let message = self.parser.no_match_message();
+                self.report(Diagnostic::error(self.here(), message));
+                self.done = true;
+            }
+        }
+        None
+    }
+}
+
+impl<Ctx, Tok, Ast> ParseIter<'_, Ctx, Tok, Ast>
+where
+    Ctx: ParseContext<Tok>,
+    Tok: Clone + std::fmt::Debug,
+    Ast: AstNode,
+{
+    /// Returns an empty span at the current token.
+    fn here(&self) -> (Position, Position) {
+        let position = self.parser.context.position();
+        (position, position)
+    }
+
+    fn report(&mut self, diagnostic: Diagnostic) {
+        self.parser.context.report(diagnostic);
+    }
+}
+
+impl<'a, Ctx, Tok, Ast> IntoIterator for &'a mut Parser<Ctx, Tok, Ast>
+where
+    Ctx: ParseContext<Tok>,
+    Tok: Clone + std::fmt::Debug,
+    Ast: AstNode,
+{
+    type Item = Ast;
+    type IntoIter = ParseIter<'a, Ctx, Tok, Ast>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
 }
@@ -1,5 +1,36 @@
 use crate::context::ParseContext;
 use crate::traits::AstNode;
+use common_framework::Position;
+
+/// Where the parts of an expression are, as passed to the
+/// [`PrattConfig`] hooks.
+///
+/// Spans run from the start of their first token to the start of the token
+/// after them, i.e. the context's [`position`](ParseContext::position) once
+/// they are consumed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrattSpan {
+    /// Start of the expression being built: its first token.
+    pub start: Position,
+    /// Span of the token just consumed: the prefix token or atom in
+    /// [`PrattConfig::parse_prefix`], the operator in
+    /// [`PrattConfig::parse_infix`].
+    pub token: (Position, Position),
+}
+
+impl PrattSpan {
+    /// Returns the span of the completed left operand, which ends where the
+    /// operator starts. Empty in prefix hooks.
+    pub fn left(&self) -> (Position, Position) {
+        (self.start, self.token.0)
+    }
+
+    /// Returns the span from the expression's start to `end`; pass
+    /// `ctx.position()` after parsing the operands to span the whole node.
+    pub fn to(&self, end: Position) -> (Position, Position) {
+        (self.start, end)
+    }
+}
 
 /// Trait for defining operator precedence and parsing logic for Pratt parsing.
 ///
@@ -24,21 +55,31 @@ where
     fn infix_op(&self, token: &Tok) -> Option<(u8, u8)>;
 
     /// Parses a "null denotation" (prefix or atom).
-    /// `token` is the first token (already consumed).
+    /// `token` is the first token (already consumed), and `span.token` is
+    /// where it is.
     /// `parser` is a callback to recursively parse an expression with a given minimum binding power.
-    fn parse_prefix<F>(&self, token: Tok, ctx: &mut Ctx, parser: &F) -> Option<Ast>
+    fn parse_prefix<F>(
+        &self,
+        token: Tok,
+        span: PrattSpan,
+        ctx: &mut Ctx,
+        parser: &F,
+    ) -> Option<Ast>
     where
         F: Fn(&mut Ctx, u8) -> Option<Ast>;
 
     /// Parses a "left denotation" (infix or postfix).
     /// `left` is the expression already parsed on the left.
     /// `token` is the operator token (already consumed).
+    /// `span` locates the operator, and [`PrattSpan::left`] the left
+    /// expression.
     /// `r_bp` is the right binding power of the operator.
     /// `parser` is a callback to recursively parse the right-hand side.
     fn parse_infix<F>(
         &self,
         left: Ast,
         token: Tok,
+        span: PrattSpan,
         r_bp: u8,
         ctx: &mut Ctx,
         parser: &F,
@@ -58,14 +99,18 @@ where
     Config: PrattConfig<Ctx, Tok, Ast>,
 {
     // 1. Consume the first token (prefix or atom)
-    let token = ctx.peek().cloned()?;
-    ctx.advance();
+    let start = ctx.position();
+    let token = ctx.advance()?;
+    let span = PrattSpan {
+        start,
+        token: (start, ctx.position()),
+    };
 
     // 2. Parse the prefix part (nud)
     // We construct a recursive closure for the callback
     let recursive_parser = |c: &mut Ctx, bp: u8| parse_pratt(c, config, bp);
 
-    let mut left = config.parse_prefix(token, ctx, &recursive_parser)?;
+    let mut left = config.parse_prefix(token, span, ctx, &recursive_parser)?;
 
     // 3. Look ahead for an infix operator
     loop {
@@ -88,10 +133,17 @@ where
         }
 
         // 5. Consume operator and parse infix part (led)
+        let op_start = ctx.position();
         let op = ctx.advance().unwrap(); // Safe because we peeked
+        let span = PrattSpan {
+            start,
+            token: (op_start, ctx.position()),
+        };
 
         // Pass right_binding_power to recursive call indirectly via parse_infix
-        if let Some(new_left) = config.parse_infix(left.clone(), op, r_bp, ctx, &recursive_parser) {
+        if let Some(new_left) =
+            config.parse_infix(left.clone(), op, span, r_bp, ctx, &recursive_parser)
+        {
             left = new_left;
         } else {
             // If infix parse fails, maybe it wasn't an infix usage after all?
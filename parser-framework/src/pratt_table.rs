@@ -0,0 +1,298 @@
+use crate::context::ParseContext;
+use crate::expr::{check_precedence, prefix_binding_power, BinaryOpInfo, ChainErrors};
+use crate::pratt::{parse_pratt, PrattConfig, PrattSpan};
+use crate::traits::{AstNode, ParsingRule};
+use common_framework::Position;
+
+// A node's span, as passed to the fold closures; see `PrattSpan`.
+type Span = (Position, Position);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Delimiter {
+    Open,
+    Close,
+    Separator,
+}
+
+/// The roles a token plays in a [`TableDrivenPratt`] grammar.
+///
+/// A token may be both a prefix and an infix operator, like `-`:
+/// `TokenClass::prefix(5).with_infix(BinaryOpInfo::left(1))`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenClass {
+    atom: bool,
+    prefix: Option<u8>,
+    infix: Option<BinaryOpInfo>,
+    delimiter: Option<Delimiter>,
+}
+
+impl TokenClass {
+    /// A leaf of the expression, such as a number or a name.
+    pub fn atom() -> Self {
+        Self {
+            atom: true,
+            ..Self::default()
+        }
+    }
+
+    /// A prefix operator. Binary operators of at least `precedence` bind
+    /// inside its operand. Panics if `precedence` is above
+    /// [`MAX_PRECEDENCE`](crate::MAX_PRECEDENCE).
+    pub fn prefix(precedence: u8) -> Self {
+        Self::default().with_prefix(precedence)
+    }
+
+    /// A binary operator.
+    pub fn infix(info: BinaryOpInfo) -> Self {
+        Self::default().with_infix(info)
+    }
+
+    /// The token opening a group, and a call's arguments.
+    pub fn open() -> Self {
+        Self::delimiter(Delimiter::Open)
+    }
+
+    /// The token closing a group or a call's arguments.
+    pub fn close() -> Self {
+        Self::delimiter(Delimiter::Close)
+    }
+
+    /// The token between a call's arguments.
+    pub fn separator() -> Self {
+        Self::delimiter(Delimiter::Separator)
+    }
+
+    fn delimiter(delimiter: Delimiter) -> Self {
+        Self {
+            delimiter: Some(delimiter),
+            ..Self::default()
+        }
+    }
+
+    /// Also makes the token a prefix operator, like [`prefix`](Self::prefix).
+    pub fn with_prefix(mut self, precedence: u8) -> Self {
+        self.prefix = Some(check_precedence(precedence));
+        self
+    }
+
+    /// Also makes the token a binary operator.
+    pub fn with_infix(mut self, info: BinaryOpInfo) -> Self {
+        self.infix = Some(info);
+        self
+    }
+}
+
+type Classify<Tok> = Box<dyn Fn(&Tok) -> Option<TokenClass>>;
+type FoldAtom<Tok, Ast> = Box<dyn Fn(Tok, Span) -> Ast>;
+type FoldPrefix<Tok, Ast> = Box<dyn Fn(Tok, Ast, Span) -> Ast>;
+type FoldBinary<Tok, Ast> = Box<dyn Fn(Ast, Tok, Ast, Span) -> Ast>;
+type FoldCall<Ast> = Box<dyn Fn(Ast, Vec<Ast>, Span) -> Ast>;
+
+/// A [`PrattConfig`] described as data: one closure says what each token
+/// is, and fold closures build the nodes, each given the span it covers.
+///
+/// ```ignore
+/// let expr = TableDrivenPratt::new(
+///     |tok: &Tok| match tok {
+///         Tok::Number(_) | Tok::Ident(_) => Some(TokenClass::atom()),
+///         Tok::Plus => Some(TokenClass::infix(BinaryOpInfo::left(1))),
+///         Tok::Minus => Some(TokenClass::prefix(3).with_infix(BinaryOpInfo::left(1))),
+///         Tok::Star => Some(TokenClass::infix(BinaryOpInfo::left(2))),
+///         Tok::LParen => Some(TokenClass::open()),
+///         Tok::RParen => Some(TokenClass::close()),
+///         Tok::Comma => Some(TokenClass::separator()),
+///         _ => None,
+///     },
+///     Expr::atom,
+/// )
+/// .fold_binary(|lhs, op, rhs, span| Expr::binary(op, lhs, rhs, span))
+/// .fold_prefix(|op, operand, span| Expr::unary(op, operand, span))
+/// .fold_call(|callee, args, span| Expr::call(callee, args, span));
+/// ```
+///
+/// Roles without a fold are ignored: without `fold_binary` infix tokens end
+/// the expression, and without `fold_call` an open token only groups. Like
+/// [`ExpressionPreset`](crate::ExpressionPreset), it is a [`ParsingRule`]
+/// that parses one expression per node.
+pub struct TableDrivenPratt<Tok, Ast> {
+    classify: Classify<Tok>,
+    fold_atom: FoldAtom<Tok, Ast>,
+    fold_prefix: Option<FoldPrefix<Tok, Ast>>,
+    fold_binary: Option<FoldBinary<Tok, Ast>>,
+    fold_call: Option<FoldCall<Ast>>,
+    chain_errors: ChainErrors,
+    priority: i32,
+}
+
+impl<Tok, Ast> TableDrivenPratt<Tok, Ast> {
+    /// Creates a grammar from `classify`, which returns the roles of a
+    /// token or `None` if it cannot appear in an expression, and
+    /// `fold_atom`, which builds the leaves.
+    pub fn new(
+        classify: impl Fn(&Tok) -> Option<TokenClass> + 'static,
+        fold_atom: impl Fn(Tok, Span) -> Ast + 'static,
+    ) -> Self {
+        Self {
+            classify: Box::new(classify),
+            fold_atom: Box::new(fold_atom),
+            fold_prefix: None,
+            fold_binary: None,
+            fold_call: None,
+            chain_errors: ChainErrors::default(),
+            priority: 0,
+        }
+    }
+
+    /// Builds prefix operations from the operator and its operand.
+    pub fn fold_prefix(mut self, fold: impl Fn(Tok, Ast, Span) -> Ast + 'static) -> Self {
+        self.fold_prefix = Some(Box::new(fold));
+        self
+    }
+
+    /// Builds binary operations from both operands and the operator.
+    pub fn fold_binary(mut self, fold: impl Fn(Ast, Tok, Ast, Span) -> Ast + 'static) -> Self {
+        self.fold_binary = Some(Box::new(fold));
+        self
+    }
+
+    /// Builds calls `callee(arg, ...)` from the callee and its arguments.
+    pub fn fold_call(mut self, fold: impl Fn(Ast, Vec<Ast>, Span) -> Ast + 'static) -> Self {
+        self.fold_call = Some(Box::new(fold));
+        self
+    }
+
+    /// Sets the priority of the rule.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    fn class(&self, token: &Tok) -> TokenClass {
+        (self.classify)(token).unwrap_or_default()
+    }
+
+    fn is_delimiter(&self, token: &Tok, delimiter: Delimiter) -> bool {
+        self.class(token).delimiter == Some(delimiter)
+    }
+
+    /// Parses one expression, putting the tokens back on failure.
+    pub fn parse_expr<Ctx>(&self, ctx: &mut Ctx) -> Option<Ast>
+    where
+        Ctx: ParseContext<Tok>,
+        Tok: Clone + std::fmt::Debug,
+        Ast: AstNode,
+    {
+        self.chain_errors
+            .parse(ctx, |ctx| ctx.speculate(|ctx| parse_pratt(ctx, self, 0)))
+    }
+
+    /// Parses the arguments of a call after its open token.
+    fn parse_args<Ctx, F>(&self, ctx: &mut Ctx, parser: &F) -> Option<Vec<Ast>>
+    where
+        Ctx: ParseContext<Tok>,
+        Tok: Clone + std::fmt::Debug,
+        F: Fn(&mut Ctx, u8) -> Option<Ast>,
+    {
+        let mut args = Vec::new();
+        if ctx
+            .peek()
+            .is_some_and(|token| self.is_delimiter(token, Delimiter::Close))
+        {
+            ctx.advance();
+            return Some(args);
+        }
+        loop {
+            args.push(parser(ctx, 0)?);
+            match self.class(&ctx.advance()?).delimiter {
+                Some(Delimiter::Close) => return Some(args),
+                Some(Delimiter::Separator) => {}
+                _ => return None,
+            }
+        }
+    }
+}
+
+impl<Ctx, Tok, Ast> PrattConfig<Ctx, Tok, Ast> for TableDrivenPratt<Tok, Ast>
+where
+    Ctx: ParseContext<Tok>,
+    Tok: Clone + std::fmt::Debug,
+    Ast: AstNode,
+{
+    fn prefix_op(&self, token: &Tok) -> Option<((), u8)> {
+        self.fold_prefix.as_ref()?;
+        self.class(token)
+            .prefix
+            .map(|precedence| ((), prefix_binding_power(precedence)))
+    }
+
+    fn infix_op(&self, token: &Tok) -> Option<(u8, u8)> {
+        let class = self.class(token);
+        if self.fold_call.is_some() && class.delimiter == Some(Delimiter::Open) {
+            // Calls bind tighter than any operator.
+            return Some((u8::MAX, u8::MAX));
+        }
+        self.fold_binary.as_ref()?;
+        class.infix.map(BinaryOpInfo::binding_power)
+    }
+
+    fn parse_prefix<F>(&self, token: Tok, span: PrattSpan, ctx: &mut Ctx, parser: &F) -> Option<Ast>
+    where
+        F: Fn(&mut Ctx, u8) -> Option<Ast>,
+    {
+        let class = self.class(&token);
+        if class.atom {
+            return Some((self.fold_atom)(token, span.token));
+        }
+        if class.delimiter == Some(Delimiter::Open) {
+            let inner = parser(ctx, 0)?;
+            return self
+                .is_delimiter(&ctx.advance()?, Delimiter::Close)
+                .then_some(inner);
+        }
+        let ((), bp) = <Self as PrattConfig<Ctx, Tok, Ast>>::prefix_op(self, &token)?;
+        let operand = parser(ctx, bp)?;
+        let fold = self.fold_prefix.as_ref()?;
+        Some(fold(token, operand, span.to(ctx.position())))
+    }
+
+    fn parse_infix<F>(
+        &self,
+        left: Ast,
+        token: Tok,
+        span: PrattSpan,
+        r_bp: u8,
+        ctx: &mut Ctx,
+        parser: &F,
+    ) -> Option<Ast>
+    where
+        F: Fn(&mut Ctx, u8) -> Option<Ast>,
+    {
+        if self.is_delimiter(&token, Delimiter::Open) {
+            let args = self.parse_args(ctx, parser)?;
+            let fold = self.fold_call.as_ref()?;
+            return Some(fold(left, args, span.to(ctx.position())));
+        }
+        let right = parser(ctx, r_bp)?;
+        if let Some(info) = self.class(&token).infix {
+            self.chain_errors
+                .check(info, ctx, |next| self.class(next).infix);
+        }
+        let fold = self.fold_binary.as_ref()?;
+        Some(fold(left, token, right, span.to(ctx.position())))
+    }
+}
+
+impl<Ctx, Tok, Ast> ParsingRule<Ctx, Tok, Ast> for TableDrivenPratt<Tok, Ast>
+where
+    Ctx: ParseContext<Tok>,
+    Tok: Clone + std::fmt::Debug,
+    Ast: AstNode,
+{
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Ast> {
+        self.parse_expr(ctx)
+    }
+
+    fn priority(&self) -> i32 {
+        self.priority
+    }
+}
@@ -0,0 +1,400 @@
+use std::fmt::Write;
+
+/// Radius of the arcs that join branches and loops to the main line.
+const ARC: i32 = 10;
+/// Vertical space between stacked branches.
+const GAP: i32 = 10;
+/// Horizontal space between the items of a sequence.
+const STEP: i32 = 10;
+/// Height of a terminal or non-terminal box.
+const BOX_HEIGHT: i32 = 22;
+/// Approximate width of one character of box text.
+const CHAR_WIDTH: i32 = 8;
+/// Margin around a whole diagram.
+const PADDING: i32 = 20;
+
+/// A piece of a [`Production`]'s right-hand side.
+///
+/// Rules in this framework are plain code, so railroad diagrams are drawn
+/// from this separate description of what they accept rather than from the
+/// rules themselves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GrammarItem {
+    /// A literal token, drawn in a rounded box.
+    Terminal(String),
+    /// A reference to another production, drawn in a square box.
+    NonTerminal(String),
+    /// Items one after the other. An empty sequence matches nothing.
+    Sequence(Vec<GrammarItem>),
+    /// Exactly one of the alternatives.
+    Choice(Vec<GrammarItem>),
+    /// The item, or nothing.
+    Optional(Box<GrammarItem>),
+    /// The item one or more times, with `separator` between repetitions.
+    Repeat {
+        item: Box<GrammarItem>,
+        separator: Option<Box<GrammarItem>>,
+    },
+}
+
+impl GrammarItem {
+    pub fn terminal(text: impl Into<String>) -> Self {
+        Self::Terminal(text.into())
+    }
+
+    pub fn nonterminal(name: impl Into<String>) -> Self {
+        Self::NonTerminal(name.into())
+    }
+
+    pub fn sequence(items: Vec<GrammarItem>) -> Self {
+        Self::Sequence(items)
+    }
+
+    pub fn choice(alternatives: Vec<GrammarItem>) -> Self {
+        Self::Choice(alternatives)
+    }
+
+    pub fn optional(item: GrammarItem) -> Self {
+        Self::Optional(Box::new(item))
+    }
+
+    pub fn one_or_more(item: GrammarItem) -> Self {
+        Self::Repeat {
+            item: Box::new(item),
+            separator: None,
+        }
+    }
+
+    pub fn zero_or_more(item: GrammarItem) -> Self {
+        Self::optional(Self::one_or_more(item))
+    }
+
+    /// `item (separator item)*`, e.g. an argument list.
+    pub fn separated(item: GrammarItem, separator: GrammarItem) -> Self {
+        Self::Repeat {
+            item: Box::new(item),
+            separator: Some(Box::new(separator)),
+        }
+    }
+}
+
+/// A named rule of a [`Grammar`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Production {
+    pub name: String,
+    pub item: GrammarItem,
+}
+
+impl Production {
+    pub fn new(name: impl Into<String>, item: GrammarItem) -> Self {
+        Self {
+            name: name.into(),
+            item,
+        }
+    }
+
+    /// Returns the production's railroad diagram as a standalone SVG
+    /// document.
+    pub fn to_svg(&self) -> String {
+        let layout = Layout::of(&self.item);
+        // A short lead-in and lead-out line with a bar at each end.
+        let width = layout.width + 2 * (PADDING + STEP);
+        let height = layout.up + layout.down + 2 * PADDING;
+        let y = PADDING + layout.up;
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+            w = width,
+            h = height
+        );
+        out.push_str("<title>");
+        escape_xml(&self.name, &mut out);
+        out.push_str("</title>\n");
+        out.push_str(concat!(
+            "<style>path{fill:none;stroke:#333;stroke-width:2}",
+            "rect{fill:#eef;stroke:#333;stroke-width:2}",
+            "text{font:14px monospace;text-anchor:middle;dominant-baseline:central}</style>\n"
+        ));
+
+        let start = PADDING;
+        let end = PADDING + STEP + layout.width + STEP;
+        let _ = writeln!(
+            out,
+            "<path d=\"M{} {} v{} M{} {} v{}\"/>",
+            start,
+            y - ARC,
+            2 * ARC,
+            end,
+            y - ARC,
+            2 * ARC
+        );
+        line(start, y, STEP, &mut out);
+        draw(&self.item, &layout, start + STEP, y, &mut out);
+        line(end - STEP, y, STEP, &mut out);
+        out.push_str("</svg>\n");
+        out
+    }
+}
+
+/// A set of productions to document, e.g. the rules of a DSL.
+///
+/// ```
+/// use parser_framework::{Grammar, GrammarItem as G};
+///
+/// let grammar = Grammar::new().production(
+///     "call",
+///     G::sequence(vec![
+///         G::nonterminal("ident"),
+///         G::terminal("("),
+///         G::optional(G::separated(G::nonterminal("expr"), G::terminal(","))),
+///         G::terminal(")"),
+///     ]),
+/// );
+/// assert!(grammar.diagrams().next().unwrap().1.starts_with("<svg"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Grammar {
+    productions: Vec<Production>,
+}
+
+impl Grammar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a production.
+    pub fn production(mut self, name: impl Into<String>, item: GrammarItem) -> Self {
+        self.productions.push(Production::new(name, item));
+        self
+    }
+
+    pub fn productions(&self) -> &[Production] {
+        &self.productions
+    }
+
+    /// Returns each production's name with its SVG diagram, in order.
+    pub fn diagrams(&self) -> impl Iterator<Item = (&str, String)> {
+        self.productions
+            .iter()
+            .map(|production| (production.name.as_str(), production.to_svg()))
+    }
+
+    /// Returns an HTML page with a heading and diagram per production.
+    pub fn to_html(&self) -> String {
+        let mut out = String::from("<!DOCTYPE html>\n<html>\n<body>\n");
+        for (name, svg) in self.diagrams() {
+            out.push_str("<h2 id=\"");
+            escape_xml(name, &mut out);
+            out.push_str("\">");
+            escape_xml(name, &mut out);
+            out.push_str("</h2>\n");
+            out.push_str(&svg);
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+/// Size of an item's drawing relative to the line it sits on.
+struct Layout {
+    width: i32,
+    /// Height above the line.
+    up: i32,
+    /// Height below the line.
+    down: i32,
+    /// Layouts of the item's parts, plus the line offset of each branch.
+    parts: Vec<(Layout, i32)>,
+}
+
+impl Layout {
+    fn of(item: &GrammarItem) -> Self {
+        match item {
+            GrammarItem::Terminal(text) | GrammarItem::NonTerminal(text) => Self {
+                width: text.chars().count() as i32 * CHAR_WIDTH + 2 * ARC,
+                up: BOX_HEIGHT / 2,
+                down: BOX_HEIGHT / 2,
+                parts: Vec::new(),
+            },
+            GrammarItem::Sequence(items) => {
+                let parts: Vec<_> = items.iter().map(|item| (Self::of(item), 0)).collect();
+                Self {
+                    width: parts.iter().map(|(part, _)| part.width).sum::<i32>()
+                        + STEP * (parts.len() as i32 - 1).max(0),
+                    up: parts.iter().map(|(part, _)| part.up).max().unwrap_or(0),
+                    down: parts.iter().map(|(part, _)| part.down).max().unwrap_or(0),
+                    parts,
+                }
+            }
+            GrammarItem::Choice(alternatives) => {
+                Self::branches(alternatives.iter().map(Self::of).collect())
+            }
+            GrammarItem::Optional(item) => Self::branches(vec![
+                Self::of(&GrammarItem::Sequence(Vec::new())),
+                Self::of(item),
+            ]),
+            GrammarItem::Repeat { item, separator } => {
+                let item = Self::of(item);
+                let separator = match separator {
+                    Some(separator) => Self::of(separator),
+                    None => Self::of(&GrammarItem::Sequence(Vec::new())),
+                };
+                let offset = (item.down + GAP + separator.up).max(2 * ARC);
+                Self {
+                    width: item.width.max(separator.width) + 4 * ARC,
+                    up: item.up,
+                    down: offset + separator.down,
+                    parts: vec![(item, 0), (separator, offset)],
+                }
+            }
+        }
+    }
+
+    /// Stacks alternatives below the first one, which stays on the line.
+    fn branches(layouts: Vec<Layout>) -> Self {
+        let mut parts: Vec<(Layout, i32)> = Vec::with_capacity(layouts.len());
+        for layout in layouts {
+            let offset = match parts.last() {
+                Some((previous, offset)) => offset + (previous.down + GAP + layout.up).max(2 * ARC),
+                None => 0,
+            };
+            parts.push((layout, offset));
+        }
+        Self {
+            width: parts.iter().map(|(part, _)| part.width).max().unwrap_or(0) + 4 * ARC,
+            up: parts.first().map_or(0, |(part, _)| part.up),
+            down: parts.last().map_or(0, |(part, offset)| offset + part.down),
+            parts,
+        }
+    }
+}
+
+/// Draws `item` with its line entering at `(x, y)`.
+fn draw(item: &GrammarItem, layout: &Layout, x: i32, y: i32, out: &mut String) {
+    match item {
+        GrammarItem::Terminal(text) => draw_box(text, layout.width, ARC, x, y, out),
+        GrammarItem::NonTerminal(text) => draw_box(text, layout.width, 0, x, y, out),
+        GrammarItem::Sequence(items) => {
+            let mut x = x;
+            for (i, (item, (part, _))) in items.iter().zip(&layout.parts).enumerate() {
+                if i > 0 {
+                    line(x, y, STEP, out);
+                    x += STEP;
+                }
+                draw(item, part, x, y, out);
+                x += part.width;
+            }
+        }
+        GrammarItem::Choice(alternatives) => {
+            let items: Vec<&GrammarItem> = alternatives.iter().collect();
+            draw_branches(&items, layout, x, y, out);
+        }
+        GrammarItem::Optional(item) => {
+            let skip = GrammarItem::Sequence(Vec::new());
+            draw_branches(&[&skip, item], layout, x, y, out);
+        }
+        GrammarItem::Repeat { item, separator } => {
+            let inner = layout.width - 4 * ARC;
+            let (item_layout, _) = &layout.parts[0];
+            let (separator_layout, offset) = &layout.parts[1];
+            let (left, right) = (x + 2 * ARC, x + 2 * ARC + inner);
+
+            line(x, y, 2 * ARC, out);
+            draw(item, item_layout, left, y, out);
+            line(left + item_layout.width, y, inner - item_layout.width, out);
+            line(right, y, 2 * ARC, out);
+
+            // The loop back runs below the item, right to left.
+            let _ = writeln!(
+                out,
+                "<path d=\"M{} {} a{r} {r} 0 0 1 {r} {r} v{} a{r} {r} 0 0 1 -{r} {r}\"/>",
+                right,
+                y,
+                offset - 2 * ARC,
+                r = ARC
+            );
+            let _ = writeln!(
+                out,
+                "<path d=\"M{} {} a{r} {r} 0 0 1 -{r} -{r} v-{} a{r} {r} 0 0 1 {r} -{r}\"/>",
+                left,
+                y + offset,
+                offset - 2 * ARC,
+                r = ARC
+            );
+            let loop_y = y + offset;
+            let separator_x = left + (inner - separator_layout.width) / 2;
+            line(left, loop_y, separator_x - left, out);
+            if let Some(separator) = separator {
+                draw(separator, separator_layout, separator_x, loop_y, out);
+            }
+            let after = separator_x + separator_layout.width;
+            line(after, loop_y, right - after, out);
+        }
+    }
+}
+
+fn draw_branches(items: &[&GrammarItem], layout: &Layout, x: i32, y: i32, out: &mut String) {
+    let inner = layout.width - 4 * ARC;
+    let (left, right) = (x + 2 * ARC, x + 2 * ARC + inner);
+    for (item, (part, offset)) in items.iter().zip(&layout.parts) {
+        if *offset == 0 {
+            line(x, y, 2 * ARC, out);
+            line(right, y, 2 * ARC, out);
+        } else {
+            let _ = writeln!(
+                out,
+                "<path d=\"M{} {} a{r} {r} 0 0 1 {r} {r} v{} a{r} {r} 0 0 0 {r} {r}\"/>",
+                x,
+                y,
+                offset - 2 * ARC,
+                r = ARC
+            );
+            let _ = writeln!(
+                out,
+                "<path d=\"M{} {} a{r} {r} 0 0 0 {r} -{r} v-{} a{r} {r} 0 0 1 {r} -{r}\"/>",
+                right,
+                y + offset,
+                offset - 2 * ARC,
+                r = ARC
+            );
+        }
+        draw(item, part, left, y + offset, out);
+        line(left + part.width, y + offset, inner - part.width, out);
+    }
+}
+
+fn draw_box(text: &str, width: i32, radius: i32, x: i32, y: i32, out: &mut String) {
+    let _ = write!(
+        out,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\"/><text x=\"{}\" y=\"{}\">",
+        x,
+        y - BOX_HEIGHT / 2,
+        width,
+        BOX_HEIGHT,
+        radius,
+        x + width / 2,
+        y
+    );
+    escape_xml(text, out);
+    out.push_str("</text>\n");
+}
+
+/// Draws a horizontal line of `length` from `(x, y)`; nothing if empty.
+fn line(x: i32, y: i32, length: i32, out: &mut String) {
+    if length > 0 {
+        let _ = writeln!(out, "<path d=\"M{} {} h{}\"/>", x, y, length);
+    }
+}
+
+fn escape_xml(text: &str, out: &mut String) {
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            ch => out.push(ch),
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use crate::context::ParseContext;
+use crate::node_id::AstId;
+use crate::parser::Parser;
+use crate::streaming::{StreamingParseContext, TokenConsumer};
+use crate::traits::AstNode;
+use common_framework::{Inbound, Outbound, StreamingSignal};
+use std::fmt::Debug;
+
+/// A node reported by a [`RevisionStream`], possibly superseding one
+/// reported earlier.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeRevision<Ast> {
+    /// Identity of this revision.
+    pub id: AstId,
+    /// The provisional revision this one supersedes, to be replaced in
+    /// place rather than appended after.
+    pub replaces: Option<AstId>,
+    pub node: Ast,
+    /// True if a later revision may supersede this one; see
+    /// [`Completeness::Provisional`](crate::Completeness::Provisional).
+    pub provisional: bool,
+}
+
+/// A streaming parser that reports [`NodeRevision`]s instead of bare nodes,
+/// so a live preview can show the node still being typed and swap it for
+/// its successor instead of redrawing everything.
+///
+/// It is a [`TokenConsumer`] and speaks the signal protocol, with
+/// `Produced` and `Finished` carrying revisions, so it drops into a
+/// `StreamingPipeline` in place of the parser:
+///
+/// ```ignore
+/// let parser = Parser::new(StreamingParseContext::new(), rules()).into_revisions();
+/// StreamingPipeline::new(lexer, parser).run_with(|revision| match revision.replaces {
+///     Some(old) => view.replace(old, revision.id, &revision.node),
+///     None => view.append(revision.id, &revision.node),
+/// });
+/// ```
+///
+/// Only the last node reported can be provisional, and the next revision
+/// replaces it. Revision ids come from the parse context's
+/// [`AstIdAllocator`](crate::AstIdAllocator).
+pub struct RevisionStream<Tok, Ast>
+where
+    Tok: Clone + Debug,
+    Ast: AstNode,
+{
+    parser: Parser<StreamingParseContext<Tok>, Tok, Ast>,
+    provisional: Option<AstId>,
+}
+
+impl<Tok, Ast> RevisionStream<Tok, Ast>
+where
+    Tok: Clone + Debug,
+    Ast: AstNode,
+{
+    /// Wraps `parser`.
+    pub fn new(parser: Parser<StreamingParseContext<Tok>, Tok, Ast>) -> Self {
+        Self {
+            parser,
+            provisional: None,
+        }
+    }
+
+    /// Returns the wrapped parser.
+    pub fn parser(&self) -> &Parser<StreamingParseContext<Tok>, Tok, Ast> {
+        &self.parser
+    }
+
+    /// Returns the wrapped parser mutably.
+    pub fn parser_mut(&mut self) -> &mut Parser<StreamingParseContext<Tok>, Tok, Ast> {
+        &mut self.parser
+    }
+
+    /// Unwraps the parser.
+    pub fn into_inner(self) -> Parser<StreamingParseContext<Tok>, Tok, Ast> {
+        self.parser
+    }
+
+    /// Returns the id of the revision the next one will replace, if the
+    /// last node reported is provisional.
+    pub fn provisional(&self) -> Option<AstId> {
+        self.provisional
+    }
+
+    /// Parses what the buffered tokens allow and numbers the nodes.
+    fn drain_revisions(&mut self) -> Vec<NodeRevision<Ast>> {
+        let nodes = self.parser.drain_tracked_nodes();
+        nodes
+            .into_iter()
+            .map(|node| {
+                let id = self.parser.context_mut().alloc_node_id();
+                let replaces = self.provisional.take();
+                let provisional = !node.is_complete();
+                if provisional {
+                    self.provisional = Some(id);
+                }
+                NodeRevision {
+                    id,
+                    replaces,
+                    node: node.into_node(),
+                    provisional,
+                }
+            })
+            .collect()
+    }
+}
+
+impl<Tok, Ast> Parser<StreamingParseContext<Tok>, Tok, Ast>
+where
+    Tok: Clone + Debug,
+    Ast: AstNode,
+{
+    /// Wraps the parser in a [`RevisionStream`].
+    pub fn into_revisions(self) -> RevisionStream<Tok, Ast> {
+        RevisionStream::new(self)
+    }
+}
+
+impl<Tok, Ast> TokenConsumer<Tok, NodeRevision<Ast>> for RevisionStream<Tok, Ast>
+where
+    Tok: Clone + Debug,
+    Ast: AstNode,
+{
+    fn push_token(&mut self, token: Tok) -> Vec<NodeRevision<Ast>> {
+        self.parser.context_mut().push_token(token);
+        self.drain_revisions()
+    }
+
+    fn push_tokens(&mut self, tokens: Vec<Tok>) -> Vec<NodeRevision<Ast>> {
+        self.parser.context_mut().push_tokens(tokens);
+        self.drain_revisions()
+    }
+
+    fn finish(&mut self) -> Vec<NodeRevision<Ast>> {
+        self.parser.context_mut().mark_finished();
+        self.drain_revisions()
+    }
+}
+
+impl<Tok, Ast> Outbound<Tok, NodeRevision<Ast>> for RevisionStream<Tok, Ast>
+where
+    Tok: Clone + Debug,
+    Ast: AstNode,
+{
+    /// Like the parser's own signals, with revisions in `Produced`.
+    fn next_signal(&mut self) -> Option<StreamingSignal<Tok, NodeRevision<Ast>>> {
+        if let Some(feedback) = self.parser.context_mut().take_feedback() {
+            return Some(StreamingSignal::Feedback(feedback));
+        }
+
+        let produced = self.drain_revisions();
+        if !produced.is_empty() {
+            return Some(StreamingSignal::Produced(produced));
+        }
+
+        if self.parser.context_mut().is_eof() {
+            return Some(StreamingSignal::Finished(Vec::new()));
+        }
+
+        Some(StreamingSignal::NeedToken(1))
+    }
+}
+
+impl<Tok, Ast> Inbound<Tok, NodeRevision<Ast>> for RevisionStream<Tok, Ast>
+where
+    Tok: Clone + Debug,
+    Ast: AstNode,
+{
+    fn handle_signal(&mut self, signal: StreamingSignal<Tok, NodeRevision<Ast>>) {
+        let context = self.parser.context_mut();
+        match signal {
+            StreamingSignal::SupplyToken(token) => context.push_token(token),
+            StreamingSignal::SupplyTokens(tokens) => context.push_tokens(tokens),
+            StreamingSignal::EndOfInput | StreamingSignal::Abort(_) => context.mark_finished(),
+            StreamingSignal::Resume => context.resume(),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use crate::context::ParseContext;
+use crate::expect::Expected;
+use crate::node_id::AstId;
+use common_framework::{Checkpoint, Feedback, Position, ScopeStack, StateMap};
+
+/// A parse context with a stack of scopes, so rules can track declarations
+/// while parsing (e.g. to tell a type name from a variable in `T * x`).
+pub trait ScopeContext<Tok, K, V>: ParseContext<Tok>
+where
+    Tok: Clone + std::fmt::Debug,
+{
+    /// Returns the scope stack for lookups.
+    fn scopes(&self) -> &ScopeStack<K, V>;
+
+    /// Opens a new innermost scope.
+    fn enter_scope(&mut self);
+
+    /// Closes the innermost scope. Returns false if only the global scope
+    /// is open.
+    fn exit_scope(&mut self) -> bool;
+
+    /// Binds `key` in the innermost scope, shadowing earlier bindings.
+    fn declare(&mut self, key: K, value: V);
+
+    /// Returns the innermost binding of `key`.
+    fn lookup(&self, key: &K) -> Option<&V>;
+}
+
+enum ScopeChange<K, V> {
+    Entered,
+    Exited(Vec<(K, V)>),
+    Declared,
+}
+
+/// Wraps any [`ParseContext`] with a [`ScopeStack`].
+///
+/// Scope changes are journaled with the token index at which they happened,
+/// and `restore` undoes those made after the checkpoint, so a rule that
+/// declares a name and then backtracks does not leave the binding behind.
+/// Rules should therefore change scopes only after consuming the tokens
+/// that trigger the change. The journal is dropped on `commit` outside of
+/// transactions.
+pub struct ScopedContext<Ctx, K, V> {
+    inner: Ctx,
+    scopes: ScopeStack<K, V>,
+    journal: Vec<(usize, ScopeChange<K, V>)>,
+    transaction_depth: usize,
+}
+
+impl<Ctx, K, V> ScopedContext<Ctx, K, V>
+where
+    K: PartialEq,
+{
+    /// Wraps `inner` with an empty global scope.
+    pub fn new(inner: Ctx) -> Self {
+        Self::with_scopes(inner, ScopeStack::new())
+    }
+
+    /// Wraps `inner` with pre-populated `scopes`, e.g. builtin names.
+    pub fn with_scopes(inner: Ctx, scopes: ScopeStack<K, V>) -> Self {
+        Self {
+            inner,
+            scopes,
+            journal: Vec::new(),
+            transaction_depth: 0,
+        }
+    }
+
+    /// Returns the wrapped context.
+    pub fn inner(&self) -> &Ctx {
+        &self.inner
+    }
+
+    /// Unwraps the context into the inner context and the scope stack.
+    pub fn into_parts(self) -> (Ctx, ScopeStack<K, V>) {
+        (self.inner, self.scopes)
+    }
+
+    /// Undoes scope changes made after token index `index`.
+    fn unwind(&mut self, index: usize) {
+        while let Some((at, _)) = self.journal.last() {
+            if *at <= index {
+                break;
+            }
+            match self.journal.pop() {
+                Some((_, ScopeChange::Entered)) => {
+                    self.scopes.exit();
+                }
+                Some((_, ScopeChange::Exited(bindings))) => self.scopes.reenter(bindings),
+                Some((_, ScopeChange::Declared)) => {
+                    self.scopes.pop_binding();
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+impl<Ctx, Tok, K, V> ScopeContext<Tok, K, V> for ScopedContext<Ctx, K, V>
+where
+    Ctx: ParseContext<Tok>,
+    Tok: Clone + std::fmt::Debug,
+    K: PartialEq + Clone,
+    V: Clone,
+{
+    fn scopes(&self) -> &ScopeStack<K, V> {
+        &self.scopes
+    }
+
+    fn enter_scope(&mut self) {
+        self.scopes.enter();
+        self.journal
+            .push((self.inner.token_index(), ScopeChange::Entered));
+    }
+
+    fn exit_scope(&mut self) -> bool {
+        let Some(bindings) = self.scopes.exit() else {
+            return false;
+        };
+        self.journal
+            .push((self.inner.token_index(), ScopeChange::Exited(bindings)));
+        true
+    }
+
+    fn declare(&mut self, key: K, value: V) {
+        self.scopes.insert(key, value);
+        self.journal
+            .push((self.inner.token_index(), ScopeChange::Declared));
+    }
+
+    fn lookup(&self, key: &K) -> Option<&V> {
+        self.scopes.lookup(key)
+    }
+}
+
+impl<Ctx, Tok, K, V> ParseContext<Tok> for ScopedContext<Ctx, K, V>
+where
+    Ctx: ParseContext<Tok>,
+    Tok: Clone + std::fmt::Debug,
+    K: PartialEq,
+{
+    fn peek(&mut self) -> Option<&Tok> {
+        self.inner.peek()
+    }
+
+    fn peek_at(&mut self, offset: usize) -> Option<&Tok> {
+        self.inner.peek_at(offset)
+    }
+
+    fn advance(&mut self) -> Option<Tok> {
+        self.inner.advance()
+    }
+
+    fn position(&self) -> Position {
+        self.inner.position()
+    }
+
+    fn is_eof(&mut self) -> bool {
+        self.inner.is_eof()
+    }
+
+    fn token_index(&self) -> usize {
+        self.inner.token_index()
+    }
+
+    fn checkpoint(&self) -> Checkpoint {
+        self.inner.checkpoint()
+    }
+
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        let cut = self.inner.last_cut().map_or(0, |cut| cut.token_index());
+        self.unwind(checkpoint.token_index().max(cut));
+        self.inner.restore(checkpoint);
+    }
+
+    fn commit(&mut self) {
+        if self.transaction_depth == 0 {
+            self.journal.clear();
+        }
+        self.inner.commit();
+    }
+
+    fn cut(&mut self) {
+        self.inner.cut();
+    }
+
+    fn last_cut(&self) -> Option<Checkpoint> {
+        self.inner.last_cut()
+    }
+
+    fn record_expected(&mut self, expected: Expected<Tok>) {
+        self.inner.record_expected(expected);
+    }
+
+    fn expected(&self) -> Option<&Expected<Tok>> {
+        self.inner.expected()
+    }
+
+    fn alloc_node_id(&mut self) -> AstId {
+        self.inner.alloc_node_id()
+    }
+
+    fn state_map(&mut self) -> Option<&mut StateMap> {
+        self.inner.state_map()
+    }
+
+    fn send_feedback(&mut self, feedback: Feedback) {
+        self.inner.send_feedback(feedback);
+    }
+
+    fn begin_transaction(&mut self) -> Checkpoint {
+        self.transaction_depth += 1;
+        self.inner.begin_transaction()
+    }
+
+    fn end_transaction(&mut self, checkpoint: Checkpoint, success: bool) {
+        if !success {
+            let cut = self.inner.last_cut().map_or(0, |cut| cut.token_index());
+            self.unwind(checkpoint.token_index().max(cut));
+        }
+        self.transaction_depth -= 1;
+        if self.transaction_depth == 0 && success {
+            self.journal.clear();
+        }
+        self.inner.end_transaction(checkpoint, success);
+    }
+}
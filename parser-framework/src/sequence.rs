@@ -0,0 +1,228 @@
+use crate::context::ParseContext;
+use crate::traits::{AstNode, ParsingRule};
+use common_framework::Position;
+use std::cmp::Reverse;
+
+type Predicate<Tok> = Box<dyn Fn(&Tok) -> bool>;
+type Build<In, Ast> = Box<dyn Fn(In, Position) -> Ast>;
+
+/// Parses `item (sep item)* [sep]`: statement lists, argument lists, block
+/// bodies and whole programs.
+///
+/// Items are parsed by a set of rules tried in priority order, as
+/// [`Parser`](crate::Parser) does. Runs of separators are allowed, so blank
+/// lines between newline-separated statements need no special handling.
+///
+/// A sequence ends at the end of input, at a token matching
+/// [`until`](Self::until) (left unconsumed), or at the close token of
+/// [`delimited`](Self::delimited) (consumed). Without [`recover`](Self::recover)
+/// it also ends at the first token that neither starts an item nor follows
+/// one correctly; with it, such tokens are skipped up to the next separator
+/// and replaced by an error node.
+pub struct Sequence<Ctx, Tok, Ast> {
+    items: Vec<Box<dyn ParsingRule<Ctx, Tok, Ast>>>,
+    separator: Predicate<Tok>,
+    until: Option<Predicate<Tok>>,
+    delimiters: Option<(Predicate<Tok>, Predicate<Tok>)>,
+    recover: Option<Build<Vec<Tok>, Ast>>,
+}
+
+impl<Ctx, Tok, Ast> Sequence<Ctx, Tok, Ast>
+where
+    Ctx: ParseContext<Tok>,
+    Tok: Clone + std::fmt::Debug,
+    Ast: AstNode,
+{
+    /// Creates a sequence of `items` separated by tokens matching
+    /// `separator`.
+    pub fn new(
+        items: Vec<Box<dyn ParsingRule<Ctx, Tok, Ast>>>,
+        separator: impl Fn(&Tok) -> bool + 'static,
+    ) -> Self {
+        let mut items = items;
+        items.sort_by_key(|rule| Reverse(rule.priority()));
+        Self {
+            items,
+            separator: Box::new(separator),
+            until: None,
+            delimiters: None,
+            recover: None,
+        }
+    }
+
+    /// Ends the sequence before a token matching `terminator`, e.g. `end`
+    /// or `else`, leaving it for the enclosing rule.
+    pub fn until(mut self, terminator: impl Fn(&Tok) -> bool + 'static) -> Self {
+        self.until = Some(Box::new(terminator));
+        self
+    }
+
+    /// Requires the sequence to be wrapped in `open` ... `close`, e.g. a
+    /// `{ ... }` block. Both tokens are consumed, and the sequence fails if
+    /// the input ends before `close`.
+    pub fn delimited(
+        mut self,
+        open: impl Fn(&Tok) -> bool + 'static,
+        close: impl Fn(&Tok) -> bool + 'static,
+    ) -> Self {
+        self.delimiters = Some((Box::new(open), Box::new(close)));
+        self
+    }
+
+    /// Recovers from bad items: the tokens up to the next separator (or the
+    /// end of the sequence) are skipped and passed to `error` together with
+    /// the position of the first one, and the resulting node takes the
+    /// item's place.
+    pub fn recover(mut self, error: impl Fn(Vec<Tok>, Position) -> Ast + 'static) -> Self {
+        self.recover = Some(Box::new(error));
+        self
+    }
+
+    /// Wraps the sequence into a rule producing one node per sequence,
+    /// built by `block` from the items and the start position.
+    pub fn into_rule(
+        self,
+        block: impl Fn(Vec<Ast>, Position) -> Ast + 'static,
+    ) -> SequenceRule<Ctx, Tok, Ast> {
+        SequenceRule {
+            sequence: self,
+            block: Box::new(block),
+            priority: 0,
+        }
+    }
+
+    /// Parses the sequence and returns its items, putting the tokens back if
+    /// a delimited sequence is not closed.
+    pub fn parse(&mut self, ctx: &mut Ctx) -> Option<Vec<Ast>> {
+        ctx.speculate(|ctx| self.parse_inner(ctx))
+    }
+
+    fn parse_inner(&mut self, ctx: &mut Ctx) -> Option<Vec<Ast>> {
+        if let Some((open, _)) = &self.delimiters {
+            if !open(&ctx.advance()?) {
+                return None;
+            }
+        }
+
+        let mut items = Vec::new();
+        loop {
+            while ctx.peek().is_some_and(|token| (self.separator)(token)) {
+                ctx.advance();
+            }
+            if self.at_end(ctx) {
+                break;
+            }
+
+            match self.parse_item(ctx) {
+                Some(item) => items.push(item),
+                None if self.recover.is_some() => items.push(self.skip_bad_item(ctx)),
+                None => break,
+            }
+
+            // After an item comes a separator or the end of the sequence.
+            let separated = ctx.peek().is_some_and(|token| (self.separator)(token));
+            if !separated && !self.at_end(ctx) {
+                if self.recover.is_none() {
+                    break;
+                }
+                items.push(self.skip_bad_item(ctx));
+            }
+        }
+
+        if let Some((_, close)) = &self.delimiters {
+            if !close(&ctx.advance()?) {
+                return None;
+            }
+        }
+        Some(items)
+    }
+
+    /// Returns `true` at the end of input, a terminator or a close token.
+    fn at_end(&self, ctx: &mut Ctx) -> bool {
+        match ctx.peek() {
+            None => true,
+            Some(token) => {
+                self.until.as_ref().is_some_and(|until| until(token))
+                    || self
+                        .delimiters
+                        .as_ref()
+                        .is_some_and(|(_, close)| close(token))
+            }
+        }
+    }
+
+    /// Tries the item rules in priority order. Items that consume nothing
+    /// count as failures so the sequence always makes progress.
+    fn parse_item(&mut self, ctx: &mut Ctx) -> Option<Ast> {
+        let start = ctx.token_index();
+        let next_token = ctx.peek_at(1).cloned();
+        for rule in &mut self.items {
+            if rule.quick_check2(ctx.peek(), next_token.as_ref()) == Some(false) {
+                continue;
+            }
+            let item = ctx.speculate(|ctx| {
+                let item = rule.try_parse(ctx)?;
+                (ctx.token_index() > start).then_some(item)
+            });
+            if item.is_some() {
+                return item;
+            }
+        }
+        None
+    }
+
+    /// Skips to the next separator or the end of the sequence and builds an
+    /// error node from the skipped tokens. Only called with `recover` set.
+    fn skip_bad_item(&self, ctx: &mut Ctx) -> Ast {
+        let position = ctx.position();
+        let mut skipped = Vec::new();
+        while !self.at_end(ctx) && !ctx.peek().is_some_and(|token| (self.separator)(token)) {
+            skipped.extend(ctx.advance());
+        }
+        let error = self.recover.as_ref().expect("recovery is configured");
+        error(skipped, position)
+    }
+}
+
+/// A [`Sequence`] as a [`ParsingRule`] producing a user block node, created
+/// by [`Sequence::into_rule`].
+pub struct SequenceRule<Ctx, Tok, Ast> {
+    sequence: Sequence<Ctx, Tok, Ast>,
+    block: Build<Vec<Ast>, Ast>,
+    priority: i32,
+}
+
+impl<Ctx, Tok, Ast> SequenceRule<Ctx, Tok, Ast> {
+    /// Sets the priority of the rule.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+impl<Ctx, Tok, Ast> ParsingRule<Ctx, Tok, Ast> for SequenceRule<Ctx, Tok, Ast>
+where
+    Ctx: ParseContext<Tok>,
+    Tok: Clone + std::fmt::Debug,
+    Ast: AstNode,
+{
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Ast> {
+        let start = ctx.token_index();
+        let position = ctx.position();
+        let items = self.sequence.parse(ctx)?;
+        // An empty undelimited sequence is not a block.
+        if ctx.token_index() == start {
+            return None;
+        }
+        Some((self.block)(items, position))
+    }
+
+    fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    fn quick_check(&self, current_token: Option<&Tok>) -> Option<bool> {
+        let (open, _) = self.sequence.delimiters.as_ref()?;
+        Some(current_token.is_some_and(open))
+    }
+}
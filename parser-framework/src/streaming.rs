@@ -1,7 +1,14 @@
-use crate::context::{extract_position_from_token, ParseContext};
+use crate::context::{clamp_to_cut, no_position, ParseContext, TokenPositionFn};
+use crate::expect::Expected;
+use crate::node_id::{AstId, AstIdAllocator};
 use crate::parser::Parser;
 use crate::traits::AstNode;
-use common_framework::{Checkpoint, Inbound, Outbound, Position, StreamingSignal};
+use common_framework::{
+    Checkpoint, CheckpointPins, ContextId, Feedback, Inbound, Outbound, PinnedCheckpoint, Position,
+    StateMap, StreamingSignal,
+};
+use lexer_framework::LexToken;
+use std::collections::VecDeque;
 use std::fmt::Debug;
 
 /// Streaming-friendly parse context that can be fed tokens incrementally.
@@ -11,8 +18,39 @@ where
 {
     tokens: Vec<Tok>,
     current: usize,
+    /// Global index of `tokens[0]`; grows as committed tokens are dropped.
+    base_index: usize,
+    /// Global index up to which tokens have been committed. Tokens below
+    /// it are dropped once no pinned checkpoint needs them.
+    committed_index: usize,
+    pins: CheckpointPins,
     finished: bool,
     position: Position,
+    position_of: TokenPositionFn<Tok>,
+    /// Number of open transactions; commits are deferred while non-zero.
+    transaction_depth: usize,
+    cut: Option<Checkpoint>,
+    expected: Option<Expected<Tok>>,
+    node_ids: AstIdAllocator,
+    state: StateMap,
+    /// Feedback sent since the last commit, with the token index it was
+    /// sent at, so a restore can discard it.
+    pending_feedback: Vec<(usize, Feedback)>,
+    /// Committed feedback waiting to be sent to the lexer.
+    feedback: VecDeque<Feedback>,
+    /// Where the buffered tokens ended when a provisional node was last
+    /// reopened, so it is not parsed again until more tokens arrive.
+    reopened_end: Option<usize>,
+    id: ContextId,
+}
+
+impl<Tok: LexToken> StreamingParseContext<Tok> {
+    /// Tracks [`position`](ParseContext::position) through
+    /// `LexToken::position`, like
+    /// [`with_token_positions(Tok::position)`](Self::with_token_positions).
+    pub fn with_lex_positions(self) -> Self {
+        self.with_token_positions(Tok::position)
+    }
 }
 
 impl<Tok> Default for StreamingParseContext<Tok>
@@ -32,24 +70,124 @@ where
         Self {
             tokens: Vec::new(),
             current: 0,
+            base_index: 0,
+            committed_index: 0,
+            pins: CheckpointPins::new(),
             finished: false,
             position: Position::default(),
+            position_of: no_position,
+            transaction_depth: 0,
+            cut: None,
+            expected: None,
+            node_ids: AstIdAllocator::new(),
+            state: StateMap::new(),
+            pending_feedback: Vec::new(),
+            feedback: VecDeque::new(),
+            reopened_end: None,
+            id: ContextId::fresh(),
         }
     }
 
+    /// Tracks [`position`](ParseContext::position) through `position_of`,
+    /// e.g. `LexToken::position`.
+    pub fn with_token_positions(mut self, position_of: TokenPositionFn<Tok>) -> Self {
+        self.position_of = position_of;
+        self
+    }
+
     /// Pushes a new token into the context buffer.
+    ///
+    /// Committed tokens that were held back by pinned checkpoints since
+    /// dropped are released first.
     pub fn push_token(&mut self, token: Tok) {
-        if let Some(pos) = extract_position_from_token(&token) {
-            self.position = pos;
-        }
+        self.prune();
         self.tokens.push(token);
         self.finished = false;
     }
 
+    /// Returns a checkpoint that keeps its tokens buffered, even across
+    /// commits, until it is dropped.
+    ///
+    /// Use it for long speculative branches that span several top-level
+    /// nodes: only the history from the lowest live pin on is retained,
+    /// instead of everything since the last commit.
+    pub fn pin_checkpoint(&self) -> PinnedCheckpoint {
+        self.pins.pin(self.checkpoint())
+    }
+
+    /// Returns the number of pinned checkpoints still alive.
+    pub fn pinned_count(&self) -> usize {
+        self.pins.len()
+    }
+
+    /// Drops committed tokens below the lowest pinned checkpoint.
+    fn prune(&mut self) {
+        let mut target = self.committed_index;
+        if let Some(floor) = self.pins.floor() {
+            target = target.min(floor);
+        }
+        if target <= self.base_index {
+            return;
+        }
+        let count = target - self.base_index;
+        self.tokens.drain(..count);
+        self.base_index = target;
+        self.current -= count;
+    }
+
+    /// Pushes a batch of tokens into the context buffer.
+    pub fn push_tokens<I>(&mut self, tokens: I)
+    where
+        I: IntoIterator<Item = Tok>,
+    {
+        self.prune();
+        self.tokens.extend(tokens);
+        self.finished = false;
+    }
+
     /// Marks the context as finished, indicating no more tokens will arrive.
     pub fn mark_finished(&mut self) {
         self.finished = true;
     }
+
+    /// Reopens the token stream after [`mark_finished`](Self::mark_finished),
+    /// so tokens pushed afterwards are parsed rather than treated as trailing
+    /// input.
+    pub fn resume(&mut self) {
+        self.finished = false;
+    }
+
+    /// Returns the number of tokens currently buffered.
+    pub fn buffered_len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    /// Takes the oldest committed feedback for the lexer, if any.
+    pub fn take_feedback(&mut self) -> Option<Feedback> {
+        self.feedback.pop_front()
+    }
+
+    /// Returns true if every buffered token is consumed but more may
+    /// still arrive.
+    fn at_open_end(&self) -> bool {
+        !self.finished && self.current >= self.tokens.len()
+    }
+
+    /// Undoes the commit of a node parsed from `start`, so it is parsed
+    /// again, dropping the feedback it queued. The node's tokens must be
+    /// pinned.
+    fn reopen(&mut self, start: Checkpoint, feedback_len: usize) {
+        self.restore(start);
+        self.committed_index = start.token_index();
+        self.feedback.truncate(feedback_len);
+        self.reopened_end = Some(self.base_index + self.tokens.len());
+    }
+
+    /// Returns true if a provisional node was reopened and no token has
+    /// arrived since, so parsing it again would only repeat it.
+    fn awaiting_tokens(&self) -> bool {
+        !self.finished && self.reopened_end == Some(self.base_index + self.tokens.len())
+    }
 }
 
 impl<Tok> ParseContext<Tok> for StreamingParseContext<Tok>
@@ -66,15 +204,20 @@ where
 
     fn advance(&mut self) -> Option<Tok> {
         let token = self.tokens.get(self.current).cloned()?;
-        if let Some(pos) = extract_position_from_token(&token) {
-            self.position = pos;
+        if let Some(position) = (self.position_of)(&token) {
+            self.position = position;
         }
         self.current += 1;
         Some(token)
     }
 
+    /// Returns the position of the current token, or of the last consumed
+    /// one while waiting for more.
     fn position(&self) -> Position {
-        self.position
+        self.tokens
+            .get(self.current)
+            .and_then(self.position_of)
+            .unwrap_or(self.position)
     }
 
     fn is_eof(&mut self) -> bool {
@@ -82,16 +225,91 @@ where
     }
 
     fn token_index(&self) -> usize {
-        self.current
+        self.base_index + self.current
     }
 
     fn checkpoint(&self) -> Checkpoint {
-        Checkpoint::new(self.current, self.position)
+        Checkpoint::new(self.token_index(), self.position).with_context(self.id)
     }
 
     fn restore(&mut self, checkpoint: Checkpoint) {
-        self.current = checkpoint.token_index();
+        checkpoint.debug_assert_context(self.id);
+        let checkpoint = clamp_to_cut(checkpoint, self.cut);
+        assert!(
+            checkpoint.token_index() >= self.base_index,
+            "StreamingParseContext: checkpoint refers to committed tokens"
+        );
+        self.current = checkpoint.token_index() - self.base_index;
         self.position = checkpoint.position();
+        self.pending_feedback
+            .retain(|(index, _)| *index <= checkpoint.token_index());
+    }
+
+    /// Drops consumed tokens so the buffer only holds the pending tail, or
+    /// the history from the lowest pinned checkpoint on.
+    fn commit(&mut self) {
+        if self.transaction_depth > 0 {
+            // Deferred until the outermost transaction succeeds.
+            return;
+        }
+        self.committed_index = self.committed_index.max(self.token_index());
+        self.prune();
+        self.feedback.extend(
+            self.pending_feedback
+                .drain(..)
+                .map(|(_, feedback)| feedback),
+        );
+    }
+
+    /// Drops the tokens before the cut, even inside a transaction, unless
+    /// a pinned checkpoint still needs them.
+    ///
+    /// A rule that cuts and then waits for more tokens cannot be restarted
+    /// from its beginning, so cut only once the tokens the rest of the
+    /// branch needs have arrived, e.g. after peeking the closing token.
+    fn cut(&mut self) {
+        self.cut = Some(self.checkpoint());
+        self.committed_index = self.committed_index.max(self.token_index());
+        self.prune();
+    }
+
+    fn last_cut(&self) -> Option<Checkpoint> {
+        self.cut
+    }
+
+    fn record_expected(&mut self, expected: Expected<Tok>) {
+        expected.merge_into(&mut self.expected);
+    }
+
+    fn expected(&self) -> Option<&Expected<Tok>> {
+        self.expected.as_ref()
+    }
+
+    fn alloc_node_id(&mut self) -> AstId {
+        self.node_ids.alloc()
+    }
+
+    fn state_map(&mut self) -> Option<&mut StateMap> {
+        Some(&mut self.state)
+    }
+
+    fn send_feedback(&mut self, feedback: Feedback) {
+        self.pending_feedback.push((self.token_index(), feedback));
+    }
+
+    fn begin_transaction(&mut self) -> Checkpoint {
+        self.transaction_depth += 1;
+        self.checkpoint()
+    }
+
+    fn end_transaction(&mut self, checkpoint: Checkpoint, success: bool) {
+        if !success {
+            self.restore(checkpoint);
+        }
+        self.transaction_depth -= 1;
+        if self.transaction_depth == 0 && success {
+            self.commit();
+        }
     }
 }
 
@@ -100,6 +318,16 @@ pub trait TokenConsumer<Tok, Ast> {
     /// Pushes a single token and returns any AST nodes that became available.
     fn push_token(&mut self, token: Tok) -> Vec<Ast>;
 
+    /// Pushes a batch of tokens and returns any AST nodes that became
+    /// available. The default pushes them one by one.
+    fn push_tokens(&mut self, tokens: Vec<Tok>) -> Vec<Ast> {
+        let mut nodes = Vec::new();
+        for token in tokens {
+            nodes.extend(self.push_token(token));
+        }
+        nodes
+    }
+
     /// Signals the end of input and drains any remaining AST nodes.
     fn finish(&mut self) -> Vec<Ast>;
 }
@@ -114,17 +342,138 @@ where
         self.drain_ready_nodes()
     }
 
+    fn push_tokens(&mut self, tokens: Vec<Tok>) -> Vec<Ast> {
+        self.context_mut().push_tokens(tokens);
+        self.drain_ready_nodes()
+    }
+
     fn finish(&mut self) -> Vec<Ast> {
         self.context_mut().mark_finished();
         self.drain_ready_nodes()
     }
 }
 
+/// A node from a streaming parser, tagged with whether it can still
+/// change. Returned by [`Parser::push_tokens_tracked`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Completeness<Ast> {
+    /// The node is followed by another token or by the end of the input,
+    /// so it is final.
+    Complete(Ast),
+    /// The node runs up to the last token received while the input is
+    /// still open, so more tokens may extend it, e.g. a paragraph whose
+    /// next line has not arrived. The parser parses it again once they do,
+    /// and the next node it returns replaces this one.
+    Provisional(Ast),
+}
+
+impl<Ast> Completeness<Ast> {
+    /// Returns the node.
+    pub fn node(&self) -> &Ast {
+        match self {
+            Self::Complete(node) | Self::Provisional(node) => node,
+        }
+    }
+
+    /// Unwraps the node.
+    pub fn into_node(self) -> Ast {
+        match self {
+            Self::Complete(node) | Self::Provisional(node) => node,
+        }
+    }
+
+    /// Returns true for [`Complete`](Self::Complete) nodes.
+    pub fn is_complete(&self) -> bool {
+        matches!(self, Self::Complete(_))
+    }
+
+    /// Appends the node to `nodes`, replacing the provisional node at the
+    /// end of `nodes` if there is one. Pushing every node returned by
+    /// [`Parser::push_tokens_tracked`] this way keeps `nodes` a parse of
+    /// the input so far.
+    pub fn push_onto(self, nodes: &mut Vec<Completeness<Ast>>) {
+        if matches!(nodes.last(), Some(Self::Provisional(_))) {
+            nodes.pop();
+        }
+        nodes.push(self);
+    }
+}
+
 impl<Tok, Ast> Parser<StreamingParseContext<Tok>, Tok, Ast>
 where
     Tok: Clone + Debug,
     Ast: AstNode,
 {
+    /// Like [`push_tokens`](TokenConsumer::push_tokens), but also returns
+    /// the node that ends at the last token received, tagged
+    /// [`Provisional`](Completeness::Provisional) since more tokens may
+    /// change it.
+    ///
+    /// Instead of keeping such nodes back or guessing from their content
+    /// whether they are finished, a live view can show them right away and
+    /// let the parser correct them:
+    ///
+    /// ```ignore
+    /// let mut document = Vec::new();
+    /// for tokens in token_batches {
+    ///     for node in parser.push_tokens_tracked(tokens) {
+    ///         node.push_onto(&mut document);
+    ///     }
+    ///     render(&document);
+    /// }
+    /// for node in parser.finish_tracked() {
+    ///     node.push_onto(&mut document);
+    /// }
+    /// ```
+    ///
+    /// A provisional node is parsed again from its first token on the next
+    /// call, and feedback it sent is withdrawn. Diagnostics its rule
+    /// [reported](crate::ParseContext::report) are not, so they are
+    /// reported again by the reparse, and ids from
+    /// [`alloc_node_id`](crate::ParseContext::alloc_node_id) are not reused,
+    /// so the replacement has new ones. Nodes whose rule made a
+    /// [cut](crate::ParseContext::cut) cannot be restarted and are returned
+    /// as complete.
+    pub fn push_tokens_tracked(&mut self, tokens: Vec<Tok>) -> Vec<Completeness<Ast>> {
+        self.context_mut().push_tokens(tokens);
+        self.drain_tracked_nodes()
+    }
+
+    /// Like [`finish`](TokenConsumer::finish), for use with
+    /// [`push_tokens_tracked`](Self::push_tokens_tracked). Every node
+    /// returned is complete.
+    pub fn finish_tracked(&mut self) -> Vec<Completeness<Ast>> {
+        self.context_mut().mark_finished();
+        self.drain_tracked_nodes()
+    }
+
+    pub(crate) fn drain_tracked_nodes(&mut self) -> Vec<Completeness<Ast>> {
+        let mut nodes = Vec::new();
+        if self.context().awaiting_tokens() {
+            return nodes;
+        }
+        loop {
+            let context = self.context();
+            let (start, cut) = (context.pin_checkpoint(), context.last_cut());
+            let feedback_len = context.feedback.len();
+            let Some(node) = self.next_node() else {
+                break;
+            };
+            let context = self.context_mut();
+            if context.token_index() == start.checkpoint().token_index() {
+                nodes.push(Completeness::Complete(node));
+                break;
+            }
+            if context.at_open_end() && context.last_cut() == cut {
+                context.reopen(start.checkpoint(), feedback_len);
+                nodes.push(Completeness::Provisional(node));
+                break;
+            }
+            nodes.push(Completeness::Complete(node));
+        }
+        nodes
+    }
+
     fn drain_ready_nodes(&mut self) -> Vec<Ast> {
         let mut nodes = Vec::new();
         loop {
@@ -147,6 +496,11 @@ where
     Ast: AstNode,
 {
     fn next_signal(&mut self) -> Option<StreamingSignal<Tok, Ast>> {
+        // Feedback goes out before the lexer is asked for more tokens.
+        if let Some(feedback) = self.context_mut().take_feedback() {
+            return Some(StreamingSignal::Feedback(feedback));
+        }
+
         let produced = self.drain_ready_nodes();
         if !produced.is_empty() {
             return Some(StreamingSignal::Produced(produced));
@@ -169,7 +523,12 @@ where
     fn handle_signal(&mut self, signal: StreamingSignal<Tok, Ast>) {
         match signal {
             StreamingSignal::SupplyToken(token) => {
-                self.push_token(token);
+                // Only buffer the token; nodes are drained by `next_signal` so
+                // they are reported through `Produced` instead of being dropped.
+                self.context_mut().push_token(token);
+            }
+            StreamingSignal::SupplyTokens(tokens) => {
+                self.context_mut().push_tokens(tokens);
             }
             StreamingSignal::EndOfInput => {
                 self.context_mut().mark_finished();
@@ -177,6 +536,9 @@ where
             StreamingSignal::Abort(_) => {
                 self.context_mut().mark_finished();
             }
+            StreamingSignal::Resume => {
+                self.context_mut().resume();
+            }
             _ => {}
         }
     }
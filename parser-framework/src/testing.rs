@@ -0,0 +1,161 @@
+use crate::context::{DefaultContext, ParseContext};
+use crate::parser::Parser;
+use crate::traits::{AstNode, ParsingRule};
+use common_framework::Position;
+
+/// Returns the position [`tokens!`](crate::tokens) gives the token at
+/// `index`: line 1, offset `index`, as if every token were one character.
+pub fn token_position(index: usize) -> Position {
+    Position::at(1, index + 1, index)
+}
+
+/// Runs `rule` once on a fresh context over `tokens`, returning the node
+/// and the number of tokens it consumed, or `None` if it did not match.
+///
+/// The rule is tried even if its `quick_check` rejects the first token:
+///
+/// ```ignore
+/// let tokens = tokens![SimpleToken; Number { value: 1 }, Plus, Number { value: 2 }];
+/// let (node, consumed) = parse_single(&mut BinaryRule, tokens).unwrap();
+/// assert_eq!(consumed, 3);
+/// ```
+pub fn parse_single<Tok, Ast, R>(rule: &mut R, tokens: Vec<Tok>) -> Option<(Ast, usize)>
+where
+    Tok: Clone + std::fmt::Debug,
+    Ast: AstNode,
+    R: ParsingRule<DefaultContext<Tok>, Tok, Ast> + ?Sized,
+{
+    let mut ctx = DefaultContext::new(tokens);
+    let node = rule.try_parse(&mut ctx)?;
+    Some((node, ctx.token_index()))
+}
+
+/// Parses `tokens` with `rules`, returning the nodes and the number of
+/// tokens consumed before the parser stopped. Used by
+/// [`assert_parses!`](crate::assert_parses).
+pub fn parse_all<Tok, Ast>(
+    rules: Vec<Box<dyn ParsingRule<DefaultContext<Tok>, Tok, Ast>>>,
+    tokens: Vec<Tok>,
+) -> (Vec<Ast>, usize)
+where
+    Tok: Clone + std::fmt::Debug,
+    Ast: AstNode,
+{
+    let mut parser = Parser::new(DefaultContext::new(tokens), rules);
+    let nodes = parser.parse();
+    (nodes, parser.context().token_index())
+}
+
+/// Builds a token vector for parser tests.
+///
+/// With a token type in front, each item names a variant of it and gets a
+/// `position` field from [`token_position`], for token enums whose
+/// variants all carry one, as in the examples:
+///
+/// ```ignore
+/// let tokens = tokens![SimpleToken; Number { value: 3 }, Plus, Number { value: 4 }];
+/// // == vec![SimpleToken::Number { value: 3, position: token_position(0) },
+/// //         SimpleToken::Plus { position: token_position(1) }, ...]
+/// ```
+///
+/// To get the positions the tokens would have in real source, give the
+/// source with spaces between the tokens; each token takes the position of
+/// the next word (see [`positions_of`](crate::positions_of)):
+///
+/// ```ignore
+/// let tokens = tokens![SimpleToken @ "10 -\n  3"; Number { value: 10 }, Minus, Number { value: 3 }];
+/// ```
+///
+/// Without a type, the items are plain expressions, handy with a glob import
+/// of tokens that have no positions: `tokens![Num(1), Plus, Num(2)]`.
+#[macro_export]
+macro_rules! tokens {
+    ($ty:ident @ $source:expr; $($variant:ident $({ $($field:ident : $value:expr),* $(,)? })?),* $(,)?) => {{
+        let mut positions = $crate::positions_of($source)
+            .into_iter()
+            .chain(::std::iter::repeat_with(|| panic!("tokens!: more tokens than words in the source")));
+        let tokens: ::std::vec::Vec<$ty> = vec![$(
+            $ty::$variant {
+                $($($field: $value,)*)?
+                position: positions.next().unwrap(),
+            }
+        ),*];
+        tokens
+    }};
+    ($ty:ident; $($variant:ident $({ $($field:ident : $value:expr),* $(,)? })?),* $(,)?) => {{
+        let mut positions = (0..).map($crate::testing::token_position);
+        let tokens: ::std::vec::Vec<$ty> = vec![$(
+            $ty::$variant {
+                $($($field: $value,)*)?
+                position: positions.next().unwrap(),
+            }
+        ),*];
+        tokens
+    }};
+    ($($token:expr),* $(,)?) => {
+        vec![$($token),*]
+    };
+}
+
+/// Asserts that parsing tokens consumes all of them and yields nodes
+/// matching the given patterns, in order.
+///
+/// The rules are a single rule, a bracketed list of rules, or `rules = expr`
+/// for a ready-made rule vector over a `DefaultContext`. Patterns may have
+/// `if` guards, e.g. to look into boxed children:
+///
+/// ```ignore
+/// assert_parses!(
+///     [BinaryRule::new(Op::Add), NumberRule],
+///     tokens![SimpleToken; Number { value: 3 }, Plus, Number { value: 4 }],
+///     [SimpleExpr::Binary { op: Op::Add, left, .. } if matches!(**left, SimpleExpr::Number { value: 3, .. })]
+/// );
+/// ```
+///
+/// On failure the message shows the nodes produced and where parsing
+/// stopped.
+#[macro_export]
+macro_rules! assert_parses {
+    (rules = $rules:expr, $tokens:expr, [$($pattern:pat $(if $guard:expr)?),* $(,)?]) => {{
+        let tokens = $tokens;
+        let count = tokens.len();
+        let (nodes, consumed) = $crate::testing::parse_all($rules, tokens);
+        let patterns: &[&str] = &[$(stringify!($pattern)),*];
+        assert!(
+            consumed == count,
+            "parsing stopped at token {} of {}; nodes so far: {:?}",
+            consumed,
+            count,
+            nodes
+        );
+        assert!(
+            nodes.len() == patterns.len(),
+            "parsing produced {} nodes, expected {}: {:?}",
+            nodes.len(),
+            patterns.len(),
+            nodes
+        );
+        let mut remaining = nodes.iter().enumerate();
+        $(
+            let (index, node) = remaining.next().unwrap();
+            assert!(
+                matches!(node, $pattern $(if $guard)?),
+                "node {} is {:?}, expected {}; all nodes: {:?}",
+                index,
+                node,
+                stringify!($pattern $(if $guard)?),
+                nodes
+            );
+        )*
+    }};
+    ([$($rule:expr),+ $(,)?], $tokens:expr, [$($pattern:pat $(if $guard:expr)?),* $(,)?]) => {
+        $crate::assert_parses!(
+            rules = vec![$(::std::boxed::Box::new($rule) as ::std::boxed::Box<dyn $crate::ParsingRule<$crate::DefaultContext<_>, _, _>>),+],
+            $tokens,
+            [$($pattern $(if $guard)?),*]
+        )
+    };
+    ($rule:expr, $tokens:expr, [$($pattern:pat $(if $guard:expr)?),* $(,)?]) => {
+        $crate::assert_parses!([$rule], $tokens, [$($pattern $(if $guard)?),*])
+    };
+}
@@ -1,5 +1,6 @@
 use crate::context::ParseContext;
-use common_framework::Position;
+use crate::expect::Expected;
+use common_framework::{short_type_name, Position, RuleId, TriviaMode};
 
 /// An AST node produced by the parser.
 /// This is part of the CGP design, allowing AST nodes to be generic
@@ -75,4 +76,71 @@ where
         let _ = current_token; // Suppress unused parameter warning
         None
     }
+
+    /// Quick check on the current and the next token, for rules that are
+    /// only told apart by their second token (`ident (` starts a call,
+    /// `ident =` an assignment). Same contract as
+    /// [`quick_check`](Self::quick_check), which it defaults to.
+    ///
+    /// The parser always dispatches through this method, so a rule should
+    /// override one of the two, not both.
+    #[inline]
+    fn quick_check2(&self, current_token: Option<&Tok>, next_token: Option<&Tok>) -> Option<bool> {
+        let _ = next_token;
+        self.quick_check(current_token)
+    }
+
+    /// Returns how this rule treats the tokens the parser's
+    /// [trivia policy](crate::Parser::with_trivia) skips.
+    ///
+    /// With [`TriviaMode::Raw`] the rule starts right after the previous
+    /// node, trivia included, and [`skip_trivia`](ParseContext::skip_trivia)
+    /// skips nothing while it runs. Defaults to [`TriviaMode::Inherit`].
+    fn trivia(&self) -> TriviaMode {
+        TriviaMode::Inherit
+    }
+
+    /// Returns a human-readable name used in traces and diagnostics.
+    ///
+    /// Defaults to the rule's type name without module path or generics.
+    fn name(&self) -> &str {
+        short_type_name::<Self>()
+    }
+
+    /// Returns a stable identifier for this rule, derived from its name.
+    fn id(&self) -> RuleId {
+        RuleId::of(self.name())
+    }
+}
+
+/// An error production: a rule the parser only tries once every normal
+/// rule has failed at the current token, to consume a malformed construct
+/// and return an error node for it, e.g. "statement missing semicolon".
+///
+/// Recovery rules run in priority order like normal rules, and the first
+/// to return a node wins. Like normal rules they must not consume tokens
+/// when they return `None`. With a
+/// [`StreamingParseContext`](crate::StreamingParseContext) they only see
+/// the tokens that have arrived, so they should return `None` when they
+/// run out rather than guess.
+pub trait RecoveryRule<Ctx, Tok, Ast>
+where
+    Ctx: ParseContext<Tok>,
+    Tok: Clone + std::fmt::Debug,
+    Ast: AstNode,
+{
+    /// Parses the malformed input at the current token into an error node.
+    /// `expected` is what the normal rules expected here, if they recorded
+    /// it with [`expect_named`](ParseContext::expect_named).
+    fn recover(&mut self, ctx: &mut Ctx, expected: Option<&Expected<Tok>>) -> Option<Ast>;
+
+    /// Returns the priority of this rule. Higher priority rules are tried first.
+    fn priority(&self) -> i32 {
+        0
+    }
+
+    /// Returns a human-readable name used in traces and diagnostics.
+    fn name(&self) -> &str {
+        short_type_name::<Self>()
+    }
 }
@@ -0,0 +1,72 @@
+use crate::arena::{Arena, NodeId};
+
+/// What a [`Visitor`] wants to happen after entering a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisitControl {
+    /// Visit the node's children, then leave it.
+    Continue,
+    /// Leave the node without visiting its children.
+    SkipChildren,
+    /// Stop the whole traversal immediately.
+    Stop,
+}
+
+/// Arena-allocated AST nodes that expose their children for traversal.
+pub trait ArenaNode: Sized {
+    /// Returns the children of this node, in source order.
+    fn children(&self) -> &[NodeId<Self>];
+}
+
+/// Callbacks for a depth-first walk over an arena-allocated tree.
+///
+/// Both methods have no-op defaults, so visitors only implement what they need.
+pub trait Visitor<N> {
+    /// Called before the node's children are visited.
+    fn enter(&mut self, _id: NodeId<N>, _node: &N) -> VisitControl {
+        VisitControl::Continue
+    }
+
+    /// Called after the node's children have been visited (or skipped).
+    fn leave(&mut self, _id: NodeId<N>, _node: &N) {}
+}
+
+/// Walks the tree rooted at `root` depth-first, calling `visitor` for every
+/// node. Returns `false` if the visitor stopped the walk early.
+///
+/// The walk keeps its own stack instead of recursing, so arbitrarily deep
+/// trees cannot overflow the call stack.
+pub fn walk<N, V>(arena: &Arena<N>, root: NodeId<N>, visitor: &mut V) -> bool
+where
+    N: ArenaNode,
+    V: Visitor<N>,
+{
+    // Each frame is a node plus the index of its next child to visit.
+    let mut stack = Vec::new();
+
+    match visitor.enter(root, &arena[root]) {
+        VisitControl::Continue => stack.push((root, 0)),
+        VisitControl::SkipChildren => {
+            visitor.leave(root, &arena[root]);
+            return true;
+        }
+        VisitControl::Stop => return false,
+    }
+
+    while let Some((id, next_child)) = stack.last_mut() {
+        let node = &arena[*id];
+        let Some(&child) = node.children().get(*next_child) else {
+            let id = *id;
+            stack.pop();
+            visitor.leave(id, node);
+            continue;
+        };
+        *next_child += 1;
+
+        match visitor.enter(child, &arena[child]) {
+            VisitControl::Continue => stack.push((child, 0)),
+            VisitControl::SkipChildren => visitor.leave(child, &arena[child]),
+            VisitControl::Stop => return false,
+        }
+    }
+    true
+}
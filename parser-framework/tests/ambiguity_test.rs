@@ -0,0 +1,114 @@
+//! Ambiguity report tests (`Parser::with_ambiguity_reports`).
+
+#[cfg(feature = "streaming")]
+use parser_framework::StreamingParseContext;
+use parser_framework::{
+    Ambiguity, AstNode, ChoicePolicy, DefaultContext, ParseContext, Parser, ParsingRule, Position,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Pair,
+    Single,
+}
+
+impl AstNode for Node {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+/// Two tokens, parsed in a transaction that commits them on success.
+struct PairRule;
+
+impl<Ctx: ParseContext<u32>> ParsingRule<Ctx, u32, Node> for PairRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Node> {
+        ctx.transaction(|c| {
+            c.advance()?;
+            c.advance()?;
+            Some(Node::Pair)
+        })
+    }
+
+    fn name(&self) -> &str {
+        "pair"
+    }
+}
+
+/// One token.
+struct SingleRule;
+
+impl<Ctx: ParseContext<u32>> ParsingRule<Ctx, u32, Node> for SingleRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Node> {
+        ctx.advance()?;
+        Some(Node::Single)
+    }
+
+    fn name(&self) -> &str {
+        "single"
+    }
+}
+
+fn parser<Ctx: ParseContext<u32>>(ctx: Ctx, policy: ChoicePolicy) -> Parser<Ctx, u32, Node> {
+    Parser::new(ctx, vec![Box::new(SingleRule), Box::new(PairRule)])
+        .with_choice_policy(policy)
+        .with_ambiguity_reports(true)
+}
+
+/// The alternatives of `ambiguity` as (rule, tokens consumed, chosen).
+fn summary(ambiguity: &Ambiguity) -> Vec<(&str, usize, bool)> {
+    ambiguity
+        .alternatives
+        .iter()
+        .map(|alt| (alt.rule.as_str(), alt.consumed, alt.chosen))
+        .collect()
+}
+
+#[test]
+fn test_first_match_reports_the_first_rule_as_chosen() {
+    let mut parser = parser(DefaultContext::new(vec![1, 2, 3]), ChoicePolicy::FirstMatch);
+    assert_eq!(parser.parse(), [Node::Single, Node::Single, Node::Single]);
+
+    let ambiguities = parser.ambiguities();
+    assert_eq!(ambiguities.len(), 2);
+    assert_eq!(ambiguities[0].token_index, 0);
+    assert_eq!(
+        summary(&ambiguities[0]),
+        [("single", 1, true), ("pair", 2, false)]
+    );
+    assert_eq!(ambiguities[1].token_index, 1);
+    assert_eq!(
+        ambiguities[0].chosen().map(|alt| alt.rule.as_str()),
+        Some("single")
+    );
+}
+
+#[test]
+fn test_longest_match_reports_the_longer_rule_as_chosen() {
+    let mut parser = parser(
+        DefaultContext::new(vec![1, 2, 3]),
+        ChoicePolicy::LongestMatch,
+    );
+    assert_eq!(parser.parse(), [Node::Pair, Node::Single]);
+
+    let ambiguities = parser.ambiguities();
+    assert_eq!(ambiguities.len(), 1);
+    assert_eq!(
+        summary(&ambiguities[0]),
+        [("single", 1, false), ("pair", 2, true)]
+    );
+}
+
+#[cfg(feature = "streaming")]
+#[test]
+fn test_committing_rules_are_probed_on_streaming_context() {
+    let mut ctx = StreamingParseContext::new();
+    ctx.push_tokens(vec![1, 2, 3]);
+    ctx.mark_finished();
+    let mut parser = parser(ctx, ChoicePolicy::FirstMatch);
+    assert_eq!(parser.parse(), [Node::Single, Node::Single, Node::Single]);
+    assert_eq!(
+        summary(&parser.ambiguities()[0]),
+        [("single", 1, true), ("pair", 2, false)]
+    );
+}
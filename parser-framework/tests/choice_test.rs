@@ -0,0 +1,76 @@
+//! Choice policy tests (`ChoicePolicy::LongestMatch`).
+
+#[cfg(feature = "streaming")]
+use parser_framework::StreamingParseContext;
+use parser_framework::{
+    AstNode, ChoicePolicy, DefaultContext, LazyContext, ParseContext, Parser, ParsingRule, Position,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Pair,
+    Single,
+}
+
+impl AstNode for Node {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+/// Two tokens, parsed in a transaction that commits them on success.
+struct PairRule;
+
+impl<Ctx: ParseContext<u32>> ParsingRule<Ctx, u32, Node> for PairRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Node> {
+        ctx.transaction(|c| {
+            c.advance()?;
+            c.advance()?;
+            Some(Node::Pair)
+        })
+    }
+}
+
+/// One token, committed right away.
+struct SingleRule;
+
+impl<Ctx: ParseContext<u32>> ParsingRule<Ctx, u32, Node> for SingleRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Node> {
+        ctx.advance()?;
+        ctx.commit();
+        Some(Node::Single)
+    }
+}
+
+fn rules<Ctx: ParseContext<u32>>() -> Vec<Box<dyn ParsingRule<Ctx, u32, Node>>> {
+    vec![Box::new(SingleRule), Box::new(PairRule)]
+}
+
+fn parse_longest<Ctx: ParseContext<u32>>(ctx: Ctx) -> Vec<Node> {
+    Parser::new(ctx, rules())
+        .with_choice_policy(ChoicePolicy::LongestMatch)
+        .parse()
+}
+
+#[test]
+fn test_longest_match_picks_the_longer_rule() {
+    let nodes = parse_longest(DefaultContext::new(vec![1, 2, 3]));
+    assert_eq!(nodes, [Node::Pair, Node::Single]);
+}
+
+#[test]
+fn test_committing_rules_are_tried_speculatively_on_lazy_context() {
+    let mut parser = Parser::new(LazyContext::new(vec![1, 2, 3].into_iter(), 8), rules())
+        .with_choice_policy(ChoicePolicy::LongestMatch);
+    assert_eq!(parser.parse(), [Node::Pair, Node::Single]);
+    assert_eq!(parser.context().backtrack_error(), None);
+}
+
+#[cfg(feature = "streaming")]
+#[test]
+fn test_committing_rules_are_tried_speculatively_on_streaming_context() {
+    let mut ctx = StreamingParseContext::new();
+    ctx.push_tokens(vec![1, 2, 3]);
+    ctx.mark_finished();
+    assert_eq!(parse_longest(ctx), [Node::Pair, Node::Single]);
+}
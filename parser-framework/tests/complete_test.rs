@@ -0,0 +1,77 @@
+//! Complete-parse tests (`Parser::parse_complete`, `parse_complete_ignoring`).
+
+use lexer_framework::{LexToken, TokenKind};
+use parser_framework::{AstNode, DefaultContext, ParseContext, Parser, ParsingRule, Position};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Num(i64),
+    Plus,
+    Eof,
+}
+
+impl LexToken for Tok {
+    fn kind(&self) -> Option<TokenKind> {
+        matches!(self, Tok::Eof).then_some(TokenKind::Eof)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Num(i64);
+
+impl AstNode for Num {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+struct NumRule;
+
+impl<Ctx: ParseContext<Tok>> ParsingRule<Ctx, Tok, Num> for NumRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Num> {
+        match ctx.peek()? {
+            Tok::Num(n) => {
+                let n = *n;
+                ctx.advance();
+                Some(Num(n))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn parser(tokens: Vec<Tok>) -> Parser<DefaultContext<Tok>, Tok, Num> {
+    Parser::new(DefaultContext::new(tokens), vec![Box::new(NumRule)])
+}
+
+#[test]
+fn test_all_tokens_consumed() {
+    let nodes = parser(vec![Tok::Num(1), Tok::Num(2)]).parse_complete();
+    assert_eq!(nodes.unwrap(), vec![Num(1), Num(2)]);
+}
+
+#[test]
+fn test_trailing_eof_is_not_leftover() {
+    let nodes = parser(vec![Tok::Num(1), Tok::Eof]).parse_complete();
+    assert_eq!(nodes.unwrap(), vec![Num(1)]);
+}
+
+#[test]
+fn test_leftover_tokens_are_reported() {
+    let error = parser(vec![Tok::Num(1), Tok::Plus, Tok::Num(2), Tok::Eof])
+        .parse_complete()
+        .unwrap_err();
+    assert_eq!(error.nodes, vec![Num(1)]);
+    assert_eq!(error.token_index, 1);
+    assert_eq!(error.remaining, 3);
+    assert_eq!(error.preview, vec![Tok::Plus, Tok::Num(2), Tok::Eof]);
+}
+
+#[test]
+fn test_ignoring_nothing_counts_eof_as_leftover() {
+    let error = parser(vec![Tok::Num(1), Tok::Eof])
+        .parse_complete_ignoring(|_| false)
+        .unwrap_err();
+    assert_eq!(error.remaining, 1);
+    assert_eq!(error.preview, vec![Tok::Eof]);
+}
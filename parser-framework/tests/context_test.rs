@@ -0,0 +1,27 @@
+//! Parse context tests (`DefaultContext`).
+
+use parser_framework::{DefaultContext, Diagnostic, ParseContext, Position};
+
+#[derive(Debug, Default, PartialEq)]
+struct Depth(usize);
+
+#[test]
+fn test_context_with_state_moves_to_another_thread() {
+    fn assert_send<T: Send>() {}
+    assert_send::<DefaultContext<char>>();
+
+    let mut ctx = DefaultContext::new(vec!['(', 'a', ')']);
+    ctx.state::<Depth>().0 = 1;
+    ctx.report(Diagnostic::note(
+        (Position::new(), Position::new()),
+        "before the move",
+    ));
+    let result = std::thread::spawn(move || {
+        ctx.advance();
+        ctx.state::<Depth>().0 += 1;
+        (ctx.state::<Depth>().0, ctx.take_diagnostics().len())
+    })
+    .join()
+    .unwrap();
+    assert_eq!(result, (2, 1));
+}
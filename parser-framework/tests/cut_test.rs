@@ -0,0 +1,154 @@
+//! Cut tests (`ParseContext::cut`, `CutFailure`).
+
+#[cfg(feature = "streaming")]
+use parser_framework::StreamingParseContext;
+use parser_framework::{
+    AstNode, DefaultContext, LazyContext, ParseContext, Parser, ParsingRule, Position,
+};
+
+/// Cuts after the first token and restores to the start.
+fn restore_across_cut<Ctx: ParseContext<u32>>(ctx: &mut Ctx) {
+    let start = ctx.checkpoint();
+    ctx.advance();
+    ctx.cut();
+    ctx.advance();
+    ctx.restore(start);
+    assert_eq!(ctx.token_index(), 1);
+    assert_eq!(ctx.last_cut().map(|cut| cut.token_index()), Some(1));
+
+    // A failing transaction rolls back to the cut, not to its start.
+    let result: Option<()> = ctx.transaction(|c| {
+        c.advance();
+        c.cut();
+        c.advance();
+        None
+    });
+    assert_eq!(result, None);
+    assert_eq!(ctx.token_index(), 2);
+}
+
+#[test]
+fn test_cut_clamps_restore() {
+    restore_across_cut(&mut DefaultContext::new(vec![1, 2, 3, 4]));
+    restore_across_cut(&mut LazyContext::new(vec![1, 2, 3, 4].into_iter(), 8));
+}
+
+#[cfg(feature = "streaming")]
+#[test]
+fn test_cut_clamps_restore_streaming() {
+    let mut ctx = StreamingParseContext::new();
+    ctx.push_tokens(vec![1, 2, 3, 4]);
+    restore_across_cut(&mut ctx);
+}
+
+#[test]
+fn test_cut_frees_history_on_lazy_context() {
+    let mut ctx = LazyContext::new(vec![1, 2, 3, 4].into_iter(), 8);
+    ctx.transaction(|c| {
+        c.advance();
+        c.advance();
+        assert_eq!(c.safe_backtrack_distance(), 2);
+        c.cut();
+        // Dropped even though the transaction is still open.
+        assert_eq!(c.safe_backtrack_distance(), 0);
+        c.advance()
+    });
+}
+
+#[cfg(feature = "streaming")]
+#[test]
+fn test_cut_frees_history_on_streaming_context() {
+    let mut ctx = StreamingParseContext::new();
+    ctx.push_tokens(vec![1, 2, 3, 4]);
+    ctx.transaction(|c| {
+        c.advance();
+        c.advance();
+        c.cut();
+        // Dropped even though the transaction is still open.
+        assert_eq!(c.buffered_len(), 2);
+        c.advance()
+    });
+    assert_eq!(ctx.buffered_len(), 1);
+
+    // A pinned checkpoint keeps its tokens.
+    let mut ctx = StreamingParseContext::new();
+    ctx.push_tokens(vec![1, 2, 3, 4]);
+    let pin = ctx.pin_checkpoint();
+    ctx.advance();
+    ctx.cut();
+    assert_eq!(ctx.buffered_len(), 4);
+    drop(pin);
+    ctx.push_tokens(vec![5]);
+    assert_eq!(ctx.buffered_len(), 4);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Keyword,
+    Other(u32),
+}
+
+impl AstNode for Node {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+/// `0 9`: commits to the branch once it sees the `0`.
+struct KeywordRule;
+
+impl<Ctx: ParseContext<u32>> ParsingRule<Ctx, u32, Node> for KeywordRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Node> {
+        ctx.advance().filter(|&token| token == 0)?;
+        ctx.cut();
+        ctx.advance().filter(|&token| token == 9)?;
+        Some(Node::Keyword)
+    }
+
+    fn priority(&self) -> i32 {
+        1
+    }
+
+    fn name(&self) -> &str {
+        "keyword"
+    }
+}
+
+/// Any single token.
+struct OtherRule;
+
+impl<Ctx: ParseContext<u32>> ParsingRule<Ctx, u32, Node> for OtherRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Node> {
+        ctx.advance().map(Node::Other)
+    }
+}
+
+fn parser(tokens: Vec<u32>) -> Parser<DefaultContext<u32>, u32, Node> {
+    Parser::new(
+        DefaultContext::new(tokens),
+        vec![Box::new(KeywordRule), Box::new(OtherRule)],
+    )
+}
+
+#[test]
+fn test_failure_after_cut_stops_alternatives() {
+    let mut parser = parser(vec![7, 0, 9, 0, 1, 2]);
+    assert_eq!(parser.parse(), [Node::Other(7), Node::Keyword]);
+
+    // `OtherRule` would match the `0`, but the keyword rule committed.
+    let failures = parser.cut_failures();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].rule, "keyword");
+    assert_eq!(
+        (failures[0].start, failures[0].cut, failures[0].token_index),
+        (3, 4, 5)
+    );
+    assert_eq!(parser.context().token_index(), 4);
+}
+
+#[test]
+fn test_failure_before_cut_tries_alternatives() {
+    let mut parser = parser(vec![1, 0, 9]);
+    assert_eq!(parser.parse(), [Node::Other(1), Node::Keyword]);
+    assert!(parser.cut_failures().is_empty());
+}
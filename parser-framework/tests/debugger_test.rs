@@ -0,0 +1,211 @@
+//! Grammar debugger tests (`Debugger`, `Step`, `Debugger::repl`).
+
+use parser_framework::{
+    AstNode, DebugEvent, Debugger, DefaultContext, ParseContext, Parser, ParsingRule, Position,
+};
+use std::io::Cursor;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(&'static str),
+    Number(i64),
+    Eq,
+    Semi,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Stmt {
+    Assign(&'static str, i64),
+    Expr(i64),
+}
+
+impl AstNode for Stmt {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+/// `name = number ;`
+struct AssignRule;
+
+impl<Ctx: ParseContext<Token>> ParsingRule<Ctx, Token, Stmt> for AssignRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Stmt> {
+        let Some(Token::Ident(name)) = ctx.advance() else {
+            return None;
+        };
+        if ctx.advance()? != Token::Eq {
+            return None;
+        }
+        let Some(Token::Number(value)) = ctx.advance() else {
+            return None;
+        };
+        (ctx.advance()? == Token::Semi).then_some(Stmt::Assign(name, value))
+    }
+
+    fn quick_check(&self, current_token: Option<&Token>) -> Option<bool> {
+        current_token.map(|token| matches!(token, Token::Ident(_)))
+    }
+
+    fn name(&self) -> &str {
+        "assign"
+    }
+}
+
+/// `number ;`
+struct ExprRule;
+
+impl<Ctx: ParseContext<Token>> ParsingRule<Ctx, Token, Stmt> for ExprRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Stmt> {
+        let Some(Token::Number(value)) = ctx.advance() else {
+            return None;
+        };
+        (ctx.advance()? == Token::Semi).then_some(Stmt::Expr(value))
+    }
+
+    fn name(&self) -> &str {
+        "expr"
+    }
+}
+
+/// `a = 1 ; 2 ; b = 3 c`: the second assignment is missing its `;`.
+fn debugger() -> Debugger<DefaultContext<Token>, Token, Stmt> {
+    use Token::*;
+    let tokens = vec![
+        Ident("a"),
+        Eq,
+        Number(1),
+        Semi,
+        Number(2),
+        Semi,
+        Ident("b"),
+        Eq,
+        Number(3),
+        Ident("c"),
+    ];
+    let rules: Vec<Box<dyn ParsingRule<DefaultContext<Token>, Token, Stmt>>> =
+        vec![Box::new(AssignRule), Box::new(ExprRule)];
+    Debugger::new(Parser::new(DefaultContext::new(tokens), rules))
+}
+
+fn enter(rule: &str, token_index: usize) -> DebugEvent {
+    DebugEvent::Enter {
+        rule: rule.into(),
+        token_index,
+    }
+}
+
+fn exit(rule: &str, token_index: usize, matched: bool) -> DebugEvent {
+    DebugEvent::Exit {
+        rule: rule.into(),
+        token_index,
+        matched,
+    }
+}
+
+#[test]
+fn test_step_reports_attempts_and_furthest_failure() {
+    let mut debugger = debugger();
+    let first = debugger.step().unwrap();
+    assert_eq!(first.node, Some(Stmt::Assign("a", 1)));
+    assert_eq!(first.rule.as_deref(), Some("assign"));
+
+    let second = debugger.step().unwrap();
+    assert_eq!(second.token_index, 4);
+    assert_eq!(second.node, Some(Stmt::Expr(2)));
+
+    let stuck = debugger.step().unwrap();
+    assert_eq!(stuck.node, None);
+    let failure = stuck.furthest_failure().unwrap();
+    assert_eq!((failure.rule.as_str(), failure.reached), ("assign", 10));
+    assert_eq!(debugger.token_index(), 6);
+    assert_eq!(debugger.history().len(), 2);
+}
+
+#[test]
+fn test_step_event_tracks_rule_stack() {
+    let mut debugger = debugger();
+    assert_eq!(debugger.step_event(), Some(enter("assign", 0)));
+    assert_eq!(debugger.rule_stack(), ["assign"]);
+    assert_eq!(debugger.step_event(), Some(exit("assign", 4, true)));
+    assert!(debugger.rule_stack().is_empty());
+
+    // The next event parses the next node.
+    assert_eq!(debugger.step_event(), Some(enter("expr", 4)));
+    assert_eq!(debugger.rule_stack(), ["expr"]);
+
+    // `step` finishes the node being walked rather than parsing another.
+    let step = debugger.step().unwrap();
+    assert_eq!(step.node, Some(Stmt::Expr(2)));
+    assert!(debugger.rule_stack().is_empty());
+    assert_eq!(debugger.step_event(), Some(enter("assign", 6)));
+}
+
+#[test]
+fn test_step_event_stops_where_no_rule_matches() {
+    let mut debugger = debugger();
+    let mut events = 0;
+    while debugger.step_event().is_some() {
+        events += 1;
+        assert!(events < 100, "step_event does not stop at a stuck parser");
+    }
+    assert!(events > 4);
+}
+
+#[test]
+fn test_run_to_breakpoint() {
+    let mut debugger = debugger();
+    debugger.add_breakpoint("expr");
+    let steps = debugger.run_to_breakpoint();
+    assert_eq!(steps.len(), 2);
+    assert_eq!(steps[1].node, Some(Stmt::Expr(2)));
+
+    assert!(debugger.remove_breakpoint("expr"));
+    assert!(!debugger.remove_breakpoint("expr"));
+    // With no breakpoints it runs until no rule matches.
+    let steps = debugger.run_to_breakpoint();
+    assert_eq!(steps.len(), 1);
+    assert_eq!(steps[0].node, None);
+}
+
+fn repl(script: &str) -> String {
+    let mut output = Vec::new();
+    debugger().repl(Cursor::new(script), &mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_repl_steps_and_shows_tokens() {
+    let output = repl("step\ntokens 2\nquit\nstep\n");
+    assert!(output.contains("step 1 at token 0"));
+    assert!(output.contains("=> Assign(\"a\", 1) (assign)"));
+    assert!(output.contains("  [4] Number(2)\n  [5] Semi\n"));
+    assert!(!output.contains("step 2"), "commands after quit ran");
+}
+
+#[test]
+fn test_repl_breakpoints_and_history() {
+    let output = repl("break expr\ncontinue\nb\nb expr\ncontinue\nhistory\n");
+    assert!(output.contains("breakpoint on expr\n"));
+    assert!(output.contains("ran 2 step(s)\nstep 2 at token 4"));
+    assert!(output.contains("breakpoints: expr\n"));
+    assert!(output.contains("breakpoint on expr removed"));
+    assert!(output.contains("no rule matched; assign got furthest, to token 10"));
+    assert!(output.contains("step 3: assign backtracked 10 -> 6"));
+}
+
+#[test]
+fn test_repl_into_shows_rule_stack() {
+    let output = repl("into\ni\n");
+    assert!(output.contains("enter assign at token 0\n  stack: [assign]\n"));
+    assert!(output.contains("exit assign at token 4, matched\n  stack: []\n"));
+}
+
+#[test]
+fn test_repl_empty_line_steps_and_unknown_command() {
+    let output = repl("\n\n\n\nfrobnicate\n");
+    assert!(output.contains("step 3 at token 6"));
+    assert!(output.contains("step 4 at token 6"));
+    assert!(output.contains("unknown command `frobnicate`; try `help`"));
+    // The input ended without `quit`.
+    assert!(output.ends_with("> \n"));
+}
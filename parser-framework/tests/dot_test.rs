@@ -0,0 +1,135 @@
+//! Graphviz export tests (`dump_dot`, `ToDot`).
+
+use parser_framework::{
+    dump_dot, AstNode, Children, Debugger, DefaultContext, DumpNode, ParseContext, Parser,
+    ParsingRule, Position,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(i64),
+    Name(String),
+    Binary(char, Box<Expr>, Box<Expr>),
+}
+
+impl AstNode for Expr {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+impl Children for Expr {
+    fn children(&self) -> Vec<&Self> {
+        match self {
+            Expr::Binary(_, lhs, rhs) => vec![lhs, rhs],
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl DumpNode for Expr {
+    fn kind(&self) -> &str {
+        match self {
+            Expr::Number(_) => "Number",
+            Expr::Name(_) => "Name",
+            Expr::Binary(..) => "Binary",
+        }
+    }
+
+    fn attributes(&self) -> Vec<(&str, String)> {
+        match self {
+            Expr::Number(value) => vec![("value", value.to_string())],
+            Expr::Name(name) => vec![("name", name.clone())],
+            Expr::Binary(op, ..) => vec![("op", op.to_string())],
+        }
+    }
+}
+
+#[test]
+fn test_ast_nodes_and_edges() {
+    let expr = Expr::Binary(
+        '+',
+        Box::new(Expr::Number(1)),
+        Box::new(Expr::Name("x".into())),
+    );
+    assert_eq!(
+        dump_dot(&expr),
+        concat!(
+            "digraph {\n",
+            "  node [shape=box, fontname=monospace];\n",
+            "  n0 [label=\"Binary\\nop=+\"];\n",
+            "  n1 [label=\"Number\\nvalue=1\"];\n",
+            "  n0 -> n1;\n",
+            "  n2 [label=\"Name\\nname=x\"];\n",
+            "  n0 -> n2;\n",
+            "}\n",
+        )
+    );
+}
+
+#[test]
+fn test_labels_are_escaped() {
+    let dot = dump_dot(&Expr::Name("say \"hi\" \\ bye".into()));
+    assert!(dot.contains(r#"  n0 [label="Name\nname=say \"hi\" \\ bye"];"#));
+}
+
+/// Matches a single number token.
+struct NumberRule;
+
+impl ParsingRule<DefaultContext<i64>, i64, Expr> for NumberRule {
+    fn try_parse(&mut self, ctx: &mut DefaultContext<i64>) -> Option<Expr> {
+        let value = ctx.advance()?;
+        (value >= 0).then_some(Expr::Number(value))
+    }
+
+    fn name(&self) -> &str {
+        "number"
+    }
+}
+
+/// Matches two numbers in a row.
+struct PairRule;
+
+impl ParsingRule<DefaultContext<i64>, i64, Expr> for PairRule {
+    fn try_parse(&mut self, ctx: &mut DefaultContext<i64>) -> Option<Expr> {
+        let lhs = ctx.advance()?;
+        let rhs = ctx.advance()?;
+        Some(Expr::Binary(
+            ',',
+            Box::new(Expr::Number(lhs)),
+            Box::new(Expr::Number(rhs)),
+        ))
+    }
+
+    fn priority(&self) -> i32 {
+        1
+    }
+
+    fn name(&self) -> &str {
+        "pair"
+    }
+}
+
+#[test]
+fn test_debugger_trace() {
+    let rules: Vec<Box<dyn ParsingRule<DefaultContext<i64>, i64, Expr>>> =
+        vec![Box::new(PairRule), Box::new(NumberRule)];
+    let mut debugger = Debugger::new(Parser::new(DefaultContext::new(vec![1, 2, -3]), rules));
+    let steps = vec![debugger.step().unwrap(), debugger.step().unwrap()];
+
+    let dot = dump_dot(&steps);
+    assert!(dot.contains("  s1 [shape=ellipse, label=\"step 1\\ntoken 0\"];\n"));
+    assert!(dot.contains("  s1a0 [style=bold, label=\"pair\\n0..2\"];\n"));
+    assert!(dot.contains("  s1 -> s1a0 [style=bold];\n"));
+
+    // At `-3` the pair rule runs out of tokens and the number rule rejects it.
+    assert!(dot.contains("  s2 [shape=ellipse, label=\"step 2\\ntoken 2\\nno match\"];\n"));
+    assert!(dot.contains("  s2a0 [color=gray, fontcolor=gray, label=\"pair\\n2..3\"];\n"));
+    assert!(dot.contains("  s2a1 [color=gray, fontcolor=gray, label=\"number\\n2..3\"];\n"));
+    assert!(dot.contains("  s1 -> s2 [style=dotted, arrowhead=none];\n"));
+
+    // A single step draws without the links between steps.
+    let single = dump_dot(&steps[0]);
+    assert!(single.contains("s1a0"));
+    assert!(!single.contains("dotted"));
+}
@@ -0,0 +1,87 @@
+//! AST dump tests (`to_sexpr`, `to_json`, `DumpNode`).
+
+use parser_framework::{to_json, to_sexpr, Children, DumpNode};
+
+#[derive(Debug)]
+enum Expr {
+    Number(i64),
+    Name(String),
+    Binary(char, Box<Expr>, Box<Expr>),
+}
+
+impl Children for Expr {
+    fn children(&self) -> Vec<&Self> {
+        match self {
+            Expr::Binary(_, lhs, rhs) => vec![lhs, rhs],
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl DumpNode for Expr {
+    fn kind(&self) -> &str {
+        match self {
+            Expr::Number(_) => "Number",
+            Expr::Name(_) => "Name",
+            Expr::Binary(..) => "Binary",
+        }
+    }
+
+    fn attributes(&self) -> Vec<(&str, String)> {
+        match self {
+            Expr::Number(value) => vec![("value", value.to_string())],
+            Expr::Name(name) => vec![("name", name.clone())],
+            Expr::Binary(op, ..) => vec![("op", op.to_string())],
+        }
+    }
+}
+
+fn binary(op: char, lhs: Expr, rhs: Expr) -> Expr {
+    Expr::Binary(op, Box::new(lhs), Box::new(rhs))
+}
+
+#[test]
+fn test_sexpr_nests_children() {
+    let expr = binary(
+        '+',
+        Expr::Number(1),
+        binary('*', Expr::Number(2), Expr::Number(3)),
+    );
+    assert_eq!(
+        to_sexpr(&expr),
+        "(Binary op=+ (Number value=1) (Binary op=* (Number value=2) (Number value=3)))"
+    );
+}
+
+#[test]
+fn test_sexpr_quotes_awkward_values() {
+    let name = |name: &str| to_sexpr(&Expr::Name(name.into()));
+    assert_eq!(name("x"), "(Name name=x)");
+    assert_eq!(name(""), "(Name name=\"\")");
+    assert_eq!(name("a b"), "(Name name=\"a b\")");
+    assert_eq!(name("f(x)"), "(Name name=\"f(x)\")");
+    assert_eq!(name("a=b"), "(Name name=\"a=b\")");
+    assert_eq!(name("say \"hi\"\n"), "(Name name=\"say \\\"hi\\\"\\n\")");
+}
+
+#[test]
+fn test_json_omits_empty_fields() {
+    let expr = binary('-', Expr::Number(1), Expr::Name("x".into()));
+    assert_eq!(
+        to_json(&expr),
+        concat!(
+            r#"{"kind":"Binary","attrs":{"op":"-"},"children":["#,
+            r#"{"kind":"Number","attrs":{"value":"1"}},"#,
+            r#"{"kind":"Name","attrs":{"name":"x"}}]}"#,
+        )
+    );
+}
+
+#[test]
+fn test_json_escapes_strings() {
+    let expr = Expr::Name("tab\there\\ \u{1}".into());
+    assert_eq!(
+        to_json(&expr),
+        r#"{"kind":"Name","attrs":{"name":"tab\there\\ \u0001"}}"#
+    );
+}
@@ -0,0 +1,67 @@
+//! Untyped node tests (`DynNode`).
+
+use common_framework::TextSlice;
+use parser_framework::{to_json, to_sexpr, AstNode, DynNode, Position};
+
+fn span(start: usize, end: usize) -> (Position, Position) {
+    (
+        Position::at(1, start + 1, start),
+        Position::at(1, end + 1, end),
+    )
+}
+
+fn number(text: &str, start: usize) -> DynNode {
+    DynNode::leaf(
+        "Number",
+        TextSlice::from_arc(text.into()),
+        span(start, start + text.len()),
+    )
+}
+
+#[test]
+fn test_branch_spans_its_children() {
+    let sum = DynNode::branch("Add", vec![number("1", 0), number("23", 4)]);
+    assert_eq!(sum.span, span(0, 6));
+    assert_eq!(sum.position(), Some(span(0, 6).0));
+    assert_eq!(AstNode::span(&sum), Some(span(0, 6)));
+
+    let empty = DynNode::branch("Block", Vec::new());
+    assert_eq!(empty.span, (Position::new(), Position::new()));
+    assert!(empty.children.is_empty());
+}
+
+#[test]
+fn test_push_widens_span() {
+    let mut call = DynNode::new("Call", span(0, 1));
+    call.push(number("1", 2));
+    call.push(number("2", 5));
+    assert_eq!(call.span, span(0, 6));
+    assert_eq!(call.children.len(), 2);
+}
+
+#[test]
+fn test_lookup_by_kind() {
+    let name = DynNode::leaf("Name", TextSlice::from_arc("x".into()), span(0, 1));
+    let assign = DynNode::branch("Assign", vec![name, number("1", 4), number("2", 6)]);
+    assert!(assign.is("Assign"));
+    assert!(!assign.is("Name"));
+    assert_eq!(assign.child("Number").and_then(DynNode::text), Some("1"));
+    assert_eq!(assign.child("Name").and_then(DynNode::text), Some("x"));
+    assert!(assign.child("String").is_none());
+    assert_eq!(assign.text(), None);
+}
+
+#[test]
+fn test_dumps() {
+    let node = DynNode::new("Ident", span(0, 3)).with_text(TextSlice::from_arc("foo".into()));
+    let sum = DynNode::branch("Add", vec![node, number("1", 6)]);
+    assert_eq!(to_sexpr(&sum), "(Add (Ident text=foo) (Number text=1))");
+    assert_eq!(
+        to_json(&sum),
+        concat!(
+            r#"{"kind":"Add","children":["#,
+            r#"{"kind":"Ident","attrs":{"text":"foo"}},"#,
+            r#"{"kind":"Number","attrs":{"text":"1"}}]}"#,
+        )
+    );
+}
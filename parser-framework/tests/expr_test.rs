@@ -0,0 +1,148 @@
+//! Expression preset tests (`ExpressionPreset`, `BinaryOpInfo`).
+
+use parser_framework::testing::token_position;
+use parser_framework::{
+    Assoc, AstNode, BinaryOpInfo, DefaultContext, ExpressionPreset, ParseContext, Position,
+    MAX_PRECEDENCE,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Tok {
+    ch: char,
+    position: Position,
+}
+
+/// One token per character, at offset `i` for the `i`th.
+fn tokens(input: &str) -> Vec<Tok> {
+    input
+        .chars()
+        .enumerate()
+        .map(|(i, ch)| Tok {
+            ch,
+            position: token_position(i),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Expr(String);
+
+impl AstNode for Expr {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+fn preset() -> ExpressionPreset<Tok, Expr> {
+    ExpressionPreset::new(|tok: &Tok| {
+        tok.ch
+            .is_ascii_lowercase()
+            .then(|| Expr(tok.ch.to_string()))
+    })
+    .binary(
+        |tok| match tok.ch {
+            '<' | '=' => Some(BinaryOpInfo::non_assoc(1)),
+            '+' => Some(BinaryOpInfo::left(2)),
+            _ => None,
+        },
+        |op, lhs, rhs| Expr(format!("({} {} {})", op.ch, lhs.0, rhs.0)),
+    )
+    .parens(|tok| tok.ch == '(', |tok| tok.ch == ')')
+}
+
+/// Parses one expression, returning it and the offsets of the
+/// diagnostics reported.
+fn parse(input: &str) -> (Option<String>, Vec<usize>) {
+    let mut ctx = DefaultContext::new(tokens(input)).with_token_positions(|tok| Some(tok.position));
+    let node = preset().parse_expr(&mut ctx).map(|node| node.0);
+    let offsets = ctx
+        .take_diagnostics()
+        .iter()
+        .map(|diagnostic| diagnostic.start.offset)
+        .collect();
+    (node, offsets)
+}
+
+#[test]
+fn test_chained_non_assoc_is_reported() {
+    assert_eq!(parse("a<b<c"), (Some("(< (< a b) c)".into()), vec![3]));
+    assert_eq!(parse("a<b=c"), (Some("(= (< a b) c)".into()), vec![3]));
+    assert_eq!(
+        parse("a<b+c<d"),
+        (Some("(< (< a (+ b c)) d)".into()), vec![5])
+    );
+}
+
+#[test]
+fn test_grouped_non_assoc_is_not_reported() {
+    assert_eq!(parse("(a<b)<c"), (Some("(< (< a b) c)".into()), vec![]));
+    assert_eq!(parse("a<(b<c)"), (Some("(< a (< b c))".into()), vec![]));
+    assert_eq!(
+        parse("a+b+c<d"),
+        (Some("(< (+ (+ a b) c) d)".into()), vec![])
+    );
+}
+
+#[test]
+fn test_failed_parse_reports_nothing() {
+    assert_eq!(parse("(a<b<c"), (None, vec![]));
+    // A later successful parse does not pick up the dropped diagnostic.
+    let mut ctx =
+        DefaultContext::new(tokens("(a<b<c")).with_token_positions(|tok| Some(tok.position));
+    let preset = preset();
+    assert_eq!(preset.parse_expr(&mut ctx), None);
+    ctx.advance();
+    assert_eq!(
+        preset.parse_expr(&mut ctx),
+        Some(Expr("(< (< a b) c)".into()))
+    );
+    assert_eq!(ctx.take_diagnostics().len(), 1);
+}
+
+#[test]
+fn test_max_precedence_is_accepted() {
+    assert_eq!(
+        BinaryOpInfo::right(MAX_PRECEDENCE).precedence,
+        MAX_PRECEDENCE
+    );
+    let preset = preset().unary(
+        |tok| (tok.ch == '-').then_some(MAX_PRECEDENCE),
+        |op, operand| Expr(format!("({} {})", op.ch, operand.0)),
+    );
+    let mut ctx = DefaultContext::new(tokens("-a+b"));
+    assert_eq!(
+        preset.parse_expr(&mut ctx),
+        Some(Expr("(+ (- a) b)".into()))
+    );
+}
+
+#[test]
+#[should_panic(expected = "operator precedence too high")]
+fn test_binary_precedence_overflow() {
+    BinaryOpInfo::non_assoc(MAX_PRECEDENCE + 1);
+}
+
+#[test]
+#[should_panic(expected = "operator precedence too high")]
+fn test_binary_precedence_overflow_in_literal() {
+    let preset = ExpressionPreset::new(|tok: &Tok| Some(Expr(tok.ch.to_string()))).binary(
+        |_| {
+            Some(BinaryOpInfo {
+                precedence: 200,
+                assoc: Assoc::Left,
+            })
+        },
+        |op, lhs, rhs| Expr(format!("({} {} {})", op.ch, lhs.0, rhs.0)),
+    );
+    preset.parse_expr(&mut DefaultContext::new(tokens("a+b")));
+}
+
+#[test]
+#[should_panic(expected = "operator precedence too high")]
+fn test_unary_precedence_overflow() {
+    let preset = preset().unary(
+        |tok| (tok.ch == '-').then_some(200),
+        |op, operand| Expr(format!("({} {})", op.ch, operand.0)),
+    );
+    preset.parse_expr(&mut DefaultContext::new(tokens("-a")));
+}
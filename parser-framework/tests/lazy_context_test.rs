@@ -0,0 +1,102 @@
+//! Lazy context window tests (`LazyContext`).
+
+use parser_framework::{BacktrackError, Checkpoint, LazyContext, ParseContext, Position, Severity};
+
+type Context = LazyContext<std::ops::Range<u32>, u32>;
+
+fn adaptive(window: usize) -> Context {
+    LazyContext::new(0..100, window).with_adaptive_window(true)
+}
+
+#[test]
+fn test_adaptive_window_allows_a_long_first_backtrack() {
+    let mut ctx = adaptive(64);
+    let checkpoint = ctx.checkpoint();
+    for _ in 0..10 {
+        ctx.advance();
+    }
+    ctx.restore(checkpoint);
+    assert_eq!(ctx.backtrack_error(), None);
+    assert_eq!(ctx.advance(), Some(0));
+    assert_eq!(ctx.window_stats().max_backtrack, 10);
+}
+
+#[test]
+fn test_adaptive_window_shrinks_after_commits() {
+    let mut ctx = adaptive(64);
+    for _ in 0..20 {
+        ctx.checkpoint();
+        ctx.advance();
+        ctx.commit();
+    }
+    // Nothing is in use past a commit, so only the minimum history is kept
+    // while the cursor moves on without checkpoints.
+    for _ in 0..10 {
+        ctx.advance();
+    }
+    assert_eq!(ctx.safe_backtrack_distance(), 4);
+
+    // A checkpoint keeps everything after it, up to half the window.
+    let checkpoint = ctx.checkpoint();
+    for _ in 0..40 {
+        ctx.advance();
+    }
+    assert!(!ctx.can_restore(&checkpoint));
+    assert_eq!(ctx.safe_backtrack_distance(), 32);
+}
+
+#[test]
+fn test_adaptive_window_matches_fixed_window_limit() {
+    let mut fixed = LazyContext::new(0..100, 16);
+    let mut adaptive = adaptive(16);
+    for ctx in [&mut fixed, &mut adaptive] {
+        let checkpoint = ctx.checkpoint();
+        for _ in 0..8 {
+            ctx.advance();
+        }
+        ctx.restore(checkpoint);
+        assert_eq!(ctx.backtrack_error(), None);
+        for _ in 0..9 {
+            ctx.advance();
+        }
+        ctx.restore(checkpoint);
+        assert!(ctx.backtrack_error().is_some());
+    }
+}
+
+#[test]
+fn test_failed_restore_is_reported() {
+    let mut ctx = LazyContext::new(0..100, 8);
+    let checkpoint = ctx.checkpoint();
+    for _ in 0..10 {
+        ctx.advance();
+    }
+    ctx.restore(checkpoint);
+    let error = BacktrackError::Discarded {
+        target: 0,
+        earliest: 6,
+    };
+    assert_eq!(ctx.backtrack_error(), Some(&error));
+    assert_eq!(ctx.advance(), None);
+    assert!(ctx.is_eof());
+
+    // Later failures are not reported again.
+    ctx.restore(checkpoint);
+    let diagnostics = ctx.take_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert_eq!(diagnostics[0].message, error.to_string());
+}
+
+#[test]
+fn test_restore_past_the_tokens_read_fails() {
+    let mut ctx = LazyContext::new(0..100, 8);
+    let checkpoint = Checkpoint::new(5, Position::new());
+    assert_eq!(
+        ctx.try_restore(checkpoint),
+        Err(BacktrackError::Ahead { target: 5, end: 0 })
+    );
+    ctx.restore(checkpoint);
+    assert_eq!(ctx.take_diagnostics().len(), 1);
+    assert_eq!(ctx.advance(), None);
+}
@@ -0,0 +1,72 @@
+//! Lookahead tests (`ParseContext::lookahead`, `ParseContext::probe`).
+
+use parser_framework::{DefaultContext, LazyContext, ParseContext};
+
+fn is_lparen(c: &char) -> bool {
+    *c == '('
+}
+
+fn is_ident(c: &char) -> bool {
+    c.is_ascii_alphabetic()
+}
+
+fn is_rparen(c: &char) -> bool {
+    *c == ')'
+}
+
+fn tokens(source: &str) -> Vec<char> {
+    source.chars().collect()
+}
+
+#[test]
+fn test_lookahead_matches_without_consuming() {
+    let mut ctx = DefaultContext::new(tokens("(x) => x"));
+    assert!(ctx.lookahead(&[is_lparen, is_ident, is_rparen]));
+    assert!(!ctx.lookahead(&[is_lparen, is_rparen]));
+    assert!(ctx.lookahead(&[]));
+    assert_eq!(ctx.token_index(), 0);
+
+    ctx.advance();
+    assert!(ctx.lookahead(&[is_ident, is_rparen]));
+}
+
+#[test]
+fn test_lookahead_fails_past_end_of_input() {
+    let mut ctx = DefaultContext::new(tokens("(x"));
+    assert!(!ctx.lookahead(&[is_lparen, is_ident, is_rparen]));
+
+    let mut ctx = LazyContext::new(tokens("(x").into_iter(), 8);
+    assert!(ctx.lookahead(&[is_lparen, is_ident]));
+    assert!(!ctx.lookahead(&[is_lparen, is_ident, is_rparen]));
+}
+
+/// Consumes `( ident )` and returns whether all three matched.
+fn paren_ident<Ctx: ParseContext<char>>(ctx: &mut Ctx) -> bool {
+    [is_lparen, is_ident, is_rparen]
+        .iter()
+        .all(|predicate| ctx.advance().is_some_and(|c| predicate(&c)))
+}
+
+#[test]
+fn test_probe_always_puts_tokens_back() {
+    let mut ctx = DefaultContext::new(tokens("(x)=>"));
+    assert!(ctx.probe(paren_ident));
+    assert_eq!(ctx.token_index(), 0);
+
+    let mut ctx = DefaultContext::new(tokens("(1)"));
+    assert!(!ctx.probe(paren_ident));
+    assert_eq!(ctx.token_index(), 0);
+}
+
+#[test]
+fn test_probe_ignores_commits_on_lazy_context() {
+    let mut ctx = LazyContext::new(tokens("(x)=>").into_iter(), 8);
+    let matched = ctx.probe(|ctx| {
+        let matched = paren_ident(ctx);
+        ctx.commit();
+        matched
+    });
+    assert!(matched);
+    assert_eq!(ctx.token_index(), 0);
+    assert_eq!(ctx.advance(), Some('('));
+}
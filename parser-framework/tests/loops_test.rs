@@ -0,0 +1,166 @@
+//! Parse loop tests (`Parser::parse_per_line`, `parse_statements`,
+//! `parse_single_expression_strict`).
+
+use parser_framework::{AstNode, DefaultContext, ParseContext, Parser, ParsingRule, Position};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Ident(&'static str),
+    Num(i64),
+    Eq,
+    Newline,
+    Semi,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Stmt {
+    Assign(&'static str, i64),
+    Num(i64),
+}
+
+impl AstNode for Stmt {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+type Ctx = DefaultContext<Tok>;
+
+/// `ident = num`
+struct AssignRule;
+
+impl ParsingRule<Ctx, Tok, Stmt> for AssignRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Stmt> {
+        let Some(Tok::Ident(name)) = ctx.advance() else {
+            return None;
+        };
+        if ctx.advance()? != Tok::Eq {
+            return None;
+        }
+        let Some(Tok::Num(value)) = ctx.advance() else {
+            return None;
+        };
+        Some(Stmt::Assign(name, value))
+    }
+
+    fn name(&self) -> &str {
+        "assign"
+    }
+}
+
+/// `num`
+struct NumRule;
+
+impl ParsingRule<Ctx, Tok, Stmt> for NumRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Stmt> {
+        match ctx.advance()? {
+            Tok::Num(value) => Some(Stmt::Num(value)),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &str {
+        "num"
+    }
+}
+
+fn parser(tokens: Vec<Tok>) -> Parser<Ctx, Tok, Stmt> {
+    Parser::new(
+        DefaultContext::new(tokens),
+        vec![Box::new(AssignRule), Box::new(NumRule)],
+    )
+}
+
+fn messages(parser: &mut Parser<Ctx, Tok, Stmt>) -> Vec<String> {
+    parser
+        .context_mut()
+        .take_diagnostics()
+        .into_iter()
+        .map(|diagnostic| diagnostic.message)
+        .collect()
+}
+
+#[test]
+fn test_per_line_skips_bad_lines() {
+    use Tok::*;
+    let mut parser = parser(vec![
+        Newline,
+        Ident("a"),
+        Eq,
+        Num(1),
+        Newline,
+        Newline,
+        Ident("b"),
+        Num(2),
+        Num(3),
+        Newline,
+        Num(4),
+        Eq,
+        Newline,
+        Ident("c"),
+        Eq,
+        Num(5),
+    ]);
+    let nodes = parser.parse_per_line(|t| *t == Newline);
+    assert_eq!(
+        nodes,
+        [Stmt::Assign("a", 1), Stmt::Num(4), Stmt::Assign("c", 5)]
+    );
+    assert_eq!(
+        messages(&mut parser),
+        [
+            "no rule matched token Ident(\"b\") at index 6 (rules tried: assign, num); \
+             skipped 3 token(s) up to the end of line",
+            "expected end of line, found token Eq; skipped 1 token(s)",
+        ]
+    );
+}
+
+#[test]
+fn test_statements_require_terminators() {
+    use Tok::*;
+    let mut parser = parser(vec![Semi, Num(1), Semi, Semi, Eq, Num(2), Semi, Num(3)]);
+    let nodes = parser.parse_statements(|t| *t == Semi);
+    assert_eq!(nodes, [Stmt::Num(1), Stmt::Num(3)]);
+    assert_eq!(
+        messages(&mut parser),
+        [
+            "no rule matched token Eq at index 4 (rules tried: assign, num); \
+             skipped 2 token(s) up to the terminator",
+            "expected terminator, found end of input",
+        ]
+    );
+}
+
+#[test]
+fn test_per_line_accepts_missing_final_newline() {
+    use Tok::*;
+    let mut parser = parser(vec![Num(1), Newline, Num(2)]);
+    assert_eq!(
+        parser.parse_per_line(|t| *t == Newline),
+        [Stmt::Num(1), Stmt::Num(2)]
+    );
+    assert!(messages(&mut parser).is_empty());
+}
+
+#[test]
+fn test_single_expression_strict() {
+    use Tok::*;
+    let node = parser(vec![Ident("x"), Eq, Num(1)]).parse_single_expression_strict();
+    assert_eq!(node.unwrap(), Stmt::Assign("x", 1));
+
+    let error = parser(vec![Num(1), Num(2)])
+        .parse_single_expression_strict()
+        .unwrap_err();
+    assert_eq!(error.nodes, [Stmt::Num(1)]);
+    assert_eq!(error.preview, [Num(2)]);
+
+    let error = parser(Vec::new())
+        .parse_single_expression_strict()
+        .unwrap_err();
+    assert!(error.nodes.is_empty());
+    assert_eq!(
+        error.to_string(),
+        "expected a node at token 0 (line 1, column 1), found end of input"
+    );
+}
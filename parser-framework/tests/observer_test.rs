@@ -0,0 +1,161 @@
+//! Parser observer tests (`ParserObserver`, `Parser::set_observer`).
+
+use parser_framework::{
+    AstNode, ChoicePolicy, DefaultContext, ParseContext, Parser, ParserObserver, ParsingRule,
+    Position,
+};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Ident(&'static str),
+    Num(i64),
+    Eq,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Stmt {
+    Assign(&'static str, i64),
+    Name(&'static str),
+}
+
+impl AstNode for Stmt {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+type Ctx = DefaultContext<Tok>;
+
+/// `ident = num`
+struct AssignRule(i32);
+
+impl ParsingRule<Ctx, Tok, Stmt> for AssignRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Stmt> {
+        let Some(Tok::Ident(name)) = ctx.advance() else {
+            return None;
+        };
+        if ctx.advance()? != Tok::Eq {
+            return None;
+        }
+        let Some(Tok::Num(value)) = ctx.advance() else {
+            return None;
+        };
+        Some(Stmt::Assign(name, value))
+    }
+
+    fn priority(&self) -> i32 {
+        self.0
+    }
+
+    fn name(&self) -> &str {
+        "assign"
+    }
+}
+
+/// `ident`
+struct NameRule;
+
+impl ParsingRule<Ctx, Tok, Stmt> for NameRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Stmt> {
+        match ctx.advance()? {
+            Tok::Ident(name) => Some(Stmt::Name(name)),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &str {
+        "name"
+    }
+}
+
+/// Records every event as a line of text.
+#[derive(Default)]
+struct Recorder(Vec<String>);
+
+impl ParserObserver<Stmt> for Recorder {
+    fn on_rule_enter(&mut self, rule: &str, token_index: usize) {
+        self.0.push(format!("enter {} {}", rule, token_index));
+    }
+
+    fn on_rule_exit(&mut self, rule: &str, token_index: usize, matched: bool) {
+        self.0
+            .push(format!("exit {} {} {}", rule, token_index, matched));
+    }
+
+    fn on_backtrack(&mut self, rule: &str, from: usize, to: usize) {
+        self.0.push(format!("backtrack {} {} {}", rule, from, to));
+    }
+
+    fn on_node(&mut self, rule: &str, node: &Stmt) {
+        self.0.push(format!("node {} {:?}", rule, node));
+    }
+}
+
+/// `a = 1 b`
+fn parser(assign_priority: i32) -> Parser<Ctx, Tok, Stmt> {
+    use Tok::*;
+    let tokens = vec![Ident("a"), Eq, Num(1), Ident("b")];
+    let rules: Vec<Box<dyn ParsingRule<Ctx, Tok, Stmt>>> =
+        vec![Box::new(AssignRule(assign_priority)), Box::new(NameRule)];
+    Parser::new(DefaultContext::new(tokens), rules)
+}
+
+#[test]
+fn test_observer_sees_attempts_backtracks_and_nodes() {
+    let recorder = Rc::new(RefCell::new(Recorder::default()));
+    let mut parser = parser(1).with_observer(recorder.clone());
+    assert_eq!(parser.parse().len(), 2);
+    assert_eq!(
+        recorder.borrow().0,
+        [
+            "enter assign 0",
+            "exit assign 3 true",
+            "node assign Assign(\"a\", 1)",
+            "enter assign 3",
+            "exit assign 4 false",
+            "backtrack assign 4 3",
+            "enter name 3",
+            "exit name 4 true",
+            "node name Name(\"b\")",
+        ]
+    );
+}
+
+#[test]
+fn test_observer_sees_speculative_attempts() {
+    let recorder = Rc::new(RefCell::new(Recorder::default()));
+    let mut parser = parser(0)
+        .with_choice_policy(ChoicePolicy::LongestMatch)
+        .with_observer(recorder.clone());
+    assert_eq!(parser.next_node(), Some(Stmt::Assign("a", 1)));
+    assert_eq!(
+        recorder.borrow().0,
+        [
+            "enter assign 0",
+            "exit assign 3 true",
+            "backtrack assign 3 0",
+            "enter name 0",
+            "exit name 1 true",
+            "backtrack name 1 0",
+            "enter assign 0",
+            "exit assign 3 true",
+            "node assign Assign(\"a\", 1)",
+        ]
+    );
+}
+
+#[test]
+fn test_take_observer_stops_events() {
+    let recorder = Rc::new(RefCell::new(Recorder::default()));
+    let mut parser = parser(1);
+    parser.set_observer(recorder.clone());
+    parser.next_node();
+    let events = recorder.borrow().0.len();
+
+    assert!(parser.take_observer().is_some());
+    assert!(parser.take_observer().is_none());
+    parser.next_node();
+    assert_eq!(recorder.borrow().0.len(), events);
+}
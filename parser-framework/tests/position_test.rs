@@ -0,0 +1,93 @@
+//! Token position tests (`with_lex_positions`, `Parser::from_lex_tokens`).
+
+use lexer_framework::LexToken;
+use parser_framework::testing::token_position;
+use parser_framework::{AstNode, DefaultContext, ParseContext, Parser, ParsingRule, Position};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Num(i64, Position),
+    Plus(Position),
+}
+
+impl LexToken for Tok {
+    fn position(&self) -> Option<Position> {
+        match self {
+            Tok::Num(_, position) | Tok::Plus(position) => Some(*position),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Num(i64, Position);
+
+impl AstNode for Num {
+    fn position(&self) -> Option<Position> {
+        Some(self.1)
+    }
+}
+
+/// A number, stamped with the position the context reports for it.
+struct NumRule;
+
+impl<Ctx: ParseContext<Tok>> ParsingRule<Ctx, Tok, Num> for NumRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Num> {
+        let position = ctx.position();
+        match ctx.peek()? {
+            Tok::Num(n, _) => {
+                let n = *n;
+                ctx.advance();
+                Some(Num(n, position))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn tokens() -> Vec<Tok> {
+    vec![
+        Tok::Num(1, token_position(0)),
+        Tok::Num(2, token_position(1)),
+        Tok::Plus(token_position(2)),
+    ]
+}
+
+#[test]
+fn test_default_context_tracks_lex_positions() {
+    let mut ctx = DefaultContext::new(tokens()).with_lex_positions();
+    assert_eq!(ctx.position(), token_position(0));
+    ctx.advance();
+    assert_eq!(ctx.position(), token_position(1));
+    ctx.advance();
+    ctx.advance();
+    // At the end of input the last consumed token's position is kept.
+    assert_eq!(ctx.position(), token_position(2));
+}
+
+#[test]
+fn test_without_lex_positions_position_stays_default() {
+    let mut ctx = DefaultContext::new(tokens());
+    ctx.advance();
+    assert_eq!(ctx.position(), Position::default());
+}
+
+#[test]
+fn test_from_lex_tokens_reports_positions() {
+    let mut parser = Parser::from_lex_tokens(tokens(), vec![Box::new(NumRule)]);
+    assert_eq!(
+        parser.parse(),
+        vec![Num(1, token_position(0)), Num(2, token_position(1))]
+    );
+    assert_eq!(parser.context().position(), token_position(2));
+}
+
+#[cfg(feature = "streaming")]
+#[test]
+fn test_streaming_context_tracks_lex_positions() {
+    use parser_framework::StreamingParseContext;
+
+    let mut ctx = StreamingParseContext::new().with_lex_positions();
+    ctx.push_tokens(tokens());
+    ctx.advance();
+    assert_eq!(ctx.position(), token_position(1));
+}
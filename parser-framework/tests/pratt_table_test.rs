@@ -0,0 +1,213 @@
+//! Table-driven Pratt tests (`TableDrivenPratt`, `TokenClass`).
+
+use parser_framework::testing::token_position;
+use parser_framework::{
+    AstNode, BinaryOpInfo, DefaultContext, ParseContext, ParsingRule, Position, TableDrivenPratt,
+    TokenClass,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Tok {
+    ch: char,
+    position: Position,
+}
+
+/// One token per character, at offset `i` for the `i`th.
+fn tokens(input: &str) -> Vec<Tok> {
+    input
+        .chars()
+        .enumerate()
+        .map(|(i, ch)| Tok {
+            ch,
+            position: token_position(i),
+        })
+        .collect()
+}
+
+/// A node as an s-expression, with its span as offsets.
+#[derive(Debug, Clone, PartialEq)]
+struct Expr {
+    text: String,
+    span: (usize, usize),
+}
+
+impl AstNode for Expr {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+fn expr(text: String, span: (Position, Position)) -> Expr {
+    Expr {
+        text,
+        span: (span.0.offset, span.1.offset),
+    }
+}
+
+fn classify(tok: &Tok) -> Option<TokenClass> {
+    match tok.ch {
+        'a'..='z' => Some(TokenClass::atom()),
+        '+' => Some(TokenClass::infix(BinaryOpInfo::left(1))),
+        '-' => Some(TokenClass::prefix(3).with_infix(BinaryOpInfo::left(1))),
+        '<' | '=' => Some(TokenClass::infix(BinaryOpInfo::non_assoc(0))),
+        '*' => Some(TokenClass::infix(BinaryOpInfo::left(2))),
+        '(' => Some(TokenClass::open()),
+        ')' => Some(TokenClass::close()),
+        ',' => Some(TokenClass::separator()),
+        _ => None,
+    }
+}
+
+fn grammar() -> TableDrivenPratt<Tok, Expr> {
+    TableDrivenPratt::new(classify, |tok: Tok, span| expr(tok.ch.to_string(), span))
+        .fold_prefix(|op, operand, span| expr(format!("({} {})", op.ch, operand.text), span))
+        .fold_binary(|lhs, op, rhs, span| {
+            expr(format!("({} {} {})", op.ch, lhs.text, rhs.text), span)
+        })
+        .fold_call(|callee, args, span| {
+            let args: Vec<String> = args.into_iter().map(|arg| arg.text).collect();
+            expr(format!("(call {} [{}])", callee.text, args.join(" ")), span)
+        })
+}
+
+/// Parses one expression, returning it and the number of tokens consumed.
+fn parse(input: &str) -> (Option<Expr>, usize) {
+    let mut ctx = DefaultContext::new(tokens(input)).with_token_positions(|tok| Some(tok.position));
+    let node = grammar().try_parse(&mut ctx);
+    (node, ctx.token_index())
+}
+
+fn text(input: &str) -> Option<String> {
+    parse(input).0.map(|node| node.text)
+}
+
+fn expr_at(text: &str, span: (usize, usize)) -> Expr {
+    Expr {
+        text: text.into(),
+        span,
+    }
+}
+
+#[test]
+fn test_atom() {
+    assert_eq!(parse("a;"), (Some(expr_at("a", (0, 1))), 1));
+    assert_eq!(text(";"), None);
+}
+
+#[test]
+fn test_prefix_and_infix_minus() {
+    assert_eq!(text("-a").as_deref(), Some("(- a)"));
+    assert_eq!(text("a-b").as_deref(), Some("(- a b)"));
+    assert_eq!(text("-a-b").as_deref(), Some("(- (- a) b)"));
+    assert_eq!(text("a--b").as_deref(), Some("(- a (- b))"));
+    // The prefix binds tighter than `*`.
+    assert_eq!(text("-a*b").as_deref(), Some("(* (- a) b)"));
+}
+
+#[test]
+fn test_group() {
+    assert_eq!(text("(a+b)*c").as_deref(), Some("(* (+ a b) c)"));
+    assert_eq!(text("a*(b+c)").as_deref(), Some("(* a (+ b c))"));
+    assert_eq!(text("((a))").as_deref(), Some("a"));
+}
+
+#[test]
+fn test_calls() {
+    assert_eq!(text("f()").as_deref(), Some("(call f [])"));
+    assert_eq!(text("f(a)").as_deref(), Some("(call f [a])"));
+    assert_eq!(
+        text("f(a,b+c,-d)").as_deref(),
+        Some("(call f [a (+ b c) (- d)])")
+    );
+    assert_eq!(text("f(a)(b)").as_deref(), Some("(call (call f [a]) [b])"));
+    // Calls bind tighter than any operator.
+    assert_eq!(text("-f(a)*b").as_deref(), Some("(* (- (call f [a])) b)"));
+}
+
+#[test]
+fn test_missing_close_puts_tokens_back() {
+    assert_eq!(parse("(a+b"), (None, 0));
+    assert_eq!(parse("f(a,b"), (None, 0));
+    assert_eq!(parse("f(a;"), (None, 0));
+}
+
+#[test]
+fn test_fold_spans() {
+    // Each fold writes the span it was given into the node text.
+    let spans = TableDrivenPratt::new(classify, |tok: Tok, span| {
+        expr(
+            format!("{}{:?}", tok.ch, (span.0.offset, span.1.offset)),
+            span,
+        )
+    })
+    .fold_prefix(|op, operand, span| {
+        let span_text = (span.0.offset, span.1.offset);
+        expr(format!("({}{:?} {})", op.ch, span_text, operand.text), span)
+    })
+    .fold_binary(|lhs, op, rhs, span| {
+        let span_text = (span.0.offset, span.1.offset);
+        expr(
+            format!("({}{:?} {} {})", op.ch, span_text, lhs.text, rhs.text),
+            span,
+        )
+    })
+    .fold_call(|callee, args, span| {
+        let span_text = (span.0.offset, span.1.offset);
+        let args: Vec<String> = args.into_iter().map(|arg| arg.text).collect();
+        expr(
+            format!("(call{:?} {} [{}])", span_text, callee.text, args.join(" ")),
+            span,
+        )
+    });
+    let parse = |input: &str| {
+        let mut ctx =
+            DefaultContext::new(tokens(input)).with_token_positions(|tok| Some(tok.position));
+        spans.parse_expr(&mut ctx).map(|node| node.text)
+    };
+    assert_eq!(
+        parse("a+b*c;").as_deref(),
+        Some("(+(0, 5) a(0, 1) (*(2, 5) b(2, 3) c(4, 5)))")
+    );
+    assert_eq!(parse("-a;").as_deref(), Some("(-(0, 2) a(1, 2))"));
+    assert_eq!(
+        parse("f(a,b);").as_deref(),
+        Some("(call(0, 6) f(0, 1) [a(2, 3) b(4, 5)])")
+    );
+}
+
+/// Parses one expression, returning its text and the offsets of the
+/// diagnostics reported.
+fn diagnosed(input: &str) -> (Option<String>, Vec<usize>) {
+    let mut ctx = DefaultContext::new(tokens(input)).with_token_positions(|tok| Some(tok.position));
+    let node = grammar().parse_expr(&mut ctx).map(|node| node.text);
+    let offsets = ctx
+        .take_diagnostics()
+        .iter()
+        .map(|diagnostic| diagnostic.start.offset)
+        .collect();
+    (node, offsets)
+}
+
+#[test]
+fn test_chained_non_assoc() {
+    assert_eq!(diagnosed("a<b<c"), (Some("(< (< a b) c)".into()), vec![3]));
+    assert_eq!(diagnosed("a<b=c"), (Some("(= (< a b) c)".into()), vec![3]));
+    assert_eq!(diagnosed("(a<b)<c"), (Some("(< (< a b) c)".into()), vec![]));
+    assert_eq!(
+        diagnosed("f(a<b)<c"),
+        (Some("(< (call f [(< a b)]) c)".into()), vec![])
+    );
+    assert_eq!(diagnosed("(a<b<c"), (None, vec![]));
+}
+
+#[test]
+#[should_panic(expected = "operator precedence too high")]
+fn test_prefix_precedence_overflow() {
+    TokenClass::prefix(127);
+}
+
+#[test]
+#[should_panic(expected = "operator precedence too high")]
+fn test_with_prefix_precedence_overflow() {
+    TokenClass::infix(BinaryOpInfo::left(1)).with_prefix(200);
+}
@@ -0,0 +1,176 @@
+//! Two-token quick check tests (`ParsingRule::quick_check2`).
+
+use parser_framework::{
+    AstNode, DefaultContext, ParseContext, Parser, ParsingRule, Position, Sequence,
+};
+use std::cell::Cell;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Ident(&'static str),
+    Num(i64),
+    Eq,
+    LParen,
+    RParen,
+    Semi,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Stmt {
+    Assign(&'static str, i64),
+    Call(&'static str),
+    Name(&'static str),
+}
+
+impl AstNode for Stmt {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+type Ctx = DefaultContext<Tok>;
+
+/// `ident = num`, only tried when the second token is `=`.
+struct AssignRule {
+    attempts: Rc<Cell<usize>>,
+}
+
+impl ParsingRule<Ctx, Tok, Stmt> for AssignRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Stmt> {
+        self.attempts.set(self.attempts.get() + 1);
+        let Some(Tok::Ident(name)) = ctx.advance() else {
+            return None;
+        };
+        if ctx.advance()? != Tok::Eq {
+            return None;
+        }
+        let Some(Tok::Num(value)) = ctx.advance() else {
+            return None;
+        };
+        Some(Stmt::Assign(name, value))
+    }
+
+    fn quick_check2(&self, current: Option<&Tok>, next: Option<&Tok>) -> Option<bool> {
+        Some(matches!(
+            (current, next),
+            (Some(Tok::Ident(_)), Some(Tok::Eq))
+        ))
+    }
+
+    fn priority(&self) -> i32 {
+        2
+    }
+}
+
+/// `ident ( )`, only tried when the second token is `(`.
+struct CallRule {
+    attempts: Rc<Cell<usize>>,
+}
+
+impl ParsingRule<Ctx, Tok, Stmt> for CallRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Stmt> {
+        self.attempts.set(self.attempts.get() + 1);
+        let Some(Tok::Ident(name)) = ctx.advance() else {
+            return None;
+        };
+        (ctx.advance()? == Tok::LParen && ctx.advance()? == Tok::RParen).then_some(Stmt::Call(name))
+    }
+
+    fn quick_check2(&self, current: Option<&Tok>, next: Option<&Tok>) -> Option<bool> {
+        Some(matches!(
+            (current, next),
+            (Some(Tok::Ident(_)), Some(Tok::LParen))
+        ))
+    }
+
+    fn priority(&self) -> i32 {
+        1
+    }
+}
+
+/// A bare `ident`, using the one-token `quick_check` only.
+struct NameRule {
+    attempts: Rc<Cell<usize>>,
+}
+
+impl ParsingRule<Ctx, Tok, Stmt> for NameRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Stmt> {
+        self.attempts.set(self.attempts.get() + 1);
+        match ctx.advance()? {
+            Tok::Ident(name) => Some(Stmt::Name(name)),
+            _ => None,
+        }
+    }
+
+    fn quick_check(&self, current_token: Option<&Tok>) -> Option<bool> {
+        current_token.map(|token| matches!(token, Tok::Ident(_)))
+    }
+}
+
+/// Attempt counters for the assign, call and name rules.
+type Counters = [Rc<Cell<usize>>; 3];
+
+fn rules(counters: &Counters) -> Vec<Box<dyn ParsingRule<Ctx, Tok, Stmt>>> {
+    vec![
+        Box::new(AssignRule {
+            attempts: counters[0].clone(),
+        }),
+        Box::new(CallRule {
+            attempts: counters[1].clone(),
+        }),
+        Box::new(NameRule {
+            attempts: counters[2].clone(),
+        }),
+    ]
+}
+
+fn attempts(counters: &Counters) -> [usize; 3] {
+    [counters[0].get(), counters[1].get(), counters[2].get()]
+}
+
+fn program() -> Vec<Tok> {
+    use Tok::*;
+    vec![
+        Ident("a"),
+        Eq,
+        Num(1),
+        Semi,
+        Ident("f"),
+        LParen,
+        RParen,
+        Semi,
+        Ident("b"),
+    ]
+}
+
+#[test]
+fn test_parser_skips_rules_by_second_token() {
+    let counters = Counters::default();
+    let mut parser = Parser::new(DefaultContext::new(program()), rules(&counters));
+
+    assert_eq!(parser.next_node(), Some(Stmt::Assign("a", 1)));
+    assert_eq!(attempts(&counters), [1, 0, 0]);
+    parser.context_mut().advance();
+
+    assert_eq!(parser.next_node(), Some(Stmt::Call("f")));
+    assert_eq!(attempts(&counters), [1, 1, 0]);
+    parser.context_mut().advance();
+
+    // At the last token there is no second token, and the default
+    // `quick_check2` falls back to `quick_check`.
+    assert_eq!(parser.next_node(), Some(Stmt::Name("b")));
+    assert_eq!(attempts(&counters), [1, 1, 1]);
+}
+
+#[test]
+fn test_sequence_skips_rules_by_second_token() {
+    let counters = Counters::default();
+    let mut sequence = Sequence::new(rules(&counters), |token| *token == Tok::Semi);
+    let items = sequence.parse(&mut DefaultContext::new(program())).unwrap();
+    assert_eq!(
+        items,
+        [Stmt::Assign("a", 1), Stmt::Call("f"), Stmt::Name("b")]
+    );
+    assert_eq!(attempts(&counters), [1, 1, 1]);
+}
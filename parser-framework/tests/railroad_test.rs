@@ -0,0 +1,96 @@
+//! Railroad diagram tests (`Grammar`, `GrammarItem`, `Production::to_svg`).
+
+use parser_framework::{Grammar, GrammarItem as G, Production};
+
+/// Returns the `width` and `height` attributes of the root `<svg>`.
+fn size(svg: &str) -> (i32, i32) {
+    let attribute = |name: &str| {
+        let start = svg.find(&format!(" {}=\"", name)).unwrap() + name.len() + 3;
+        let end = start + svg[start..].find('"').unwrap();
+        svg[start..end].parse().unwrap()
+    };
+    (attribute("width"), attribute("height"))
+}
+
+#[test]
+fn test_builders() {
+    assert_eq!(G::terminal("("), G::Terminal("(".into()));
+    assert_eq!(G::nonterminal("expr"), G::NonTerminal("expr".into()));
+    assert_eq!(
+        G::zero_or_more(G::terminal("x")),
+        G::optional(G::one_or_more(G::terminal("x")))
+    );
+    assert_eq!(
+        G::separated(G::nonterminal("arg"), G::terminal(",")),
+        G::Repeat {
+            item: Box::new(G::nonterminal("arg")),
+            separator: Some(Box::new(G::terminal(","))),
+        }
+    );
+}
+
+#[test]
+fn test_terminal_and_nonterminal_boxes() {
+    let svg = Production::new(
+        "p",
+        G::sequence(vec![G::terminal("a"), G::nonterminal("b")]),
+    )
+    .to_svg();
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.ends_with("</svg>\n"));
+    assert!(svg.contains("<title>p</title>"));
+    // Terminals are rounded, non-terminals square.
+    assert!(svg.contains("rx=\"10\"/><text x=\"44\" y=\"31\">a</text>"));
+    assert!(svg.contains("rx=\"0\"/><text x=\"82\" y=\"31\">b</text>"));
+    // Two 28-wide boxes, a step between them and the lead-in and lead-out.
+    assert_eq!(size(&svg), (28 + 10 + 28 + 2 * 30, 62));
+}
+
+#[test]
+fn test_branches_grow_downwards() {
+    let single = Production::new("p", G::terminal("a")).to_svg();
+    assert_eq!(size(&single), (88, 62));
+
+    // The second alternative sits 32 below the line, plus half a box.
+    let choice = Production::new("p", G::choice(vec![G::terminal("a"), G::terminal("bcd")]));
+    assert_eq!(size(&choice.to_svg()), (44 + 40 + 60, 11 + 32 + 11 + 40));
+
+    // An optional item is a choice with an empty first branch on the line,
+    // so nothing rises above it and the item sits only 21 below.
+    let optional = Production::new("p", G::optional(G::terminal("a")));
+    assert_eq!(size(&optional.to_svg()), (28 + 40 + 60, 21 + 11 + 40));
+
+    // A repeat's loop runs below the item, through the separator.
+    let repeat = Production::new("p", G::separated(G::terminal("a"), G::terminal(",")));
+    let svg = repeat.to_svg();
+    assert_eq!(size(&svg), (28 + 40 + 60, 11 + 32 + 11 + 40));
+    assert!(svg.contains(">,</text>"));
+}
+
+#[test]
+fn test_text_is_escaped() {
+    let svg = Production::new("a<b", G::terminal("\"&\"")).to_svg();
+    assert!(svg.contains("<title>a&lt;b</title>"));
+    assert!(svg.contains(">&quot;&amp;&quot;</text>"));
+}
+
+#[test]
+fn test_grammar_diagrams_and_html() {
+    let grammar = Grammar::new()
+        .production(
+            "stmt",
+            G::choice(vec![G::nonterminal("expr"), G::terminal(";")]),
+        )
+        .production("expr", G::terminal("x"));
+    assert_eq!(grammar.productions().len(), 2);
+
+    let names: Vec<&str> = grammar.diagrams().map(|(name, _)| name).collect();
+    assert_eq!(names, ["stmt", "expr"]);
+
+    let html = grammar.to_html();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    let stmt = html.find("<h2 id=\"stmt\">stmt</h2>\n<svg").unwrap();
+    let expr = html.find("<h2 id=\"expr\">expr</h2>\n<svg").unwrap();
+    assert!(stmt < expr);
+    assert_eq!(html.matches("<svg").count(), 2);
+}
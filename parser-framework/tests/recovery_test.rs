@@ -0,0 +1,108 @@
+//! Error recovery tests (`RecoveryRule`, `Parser::with_recovery_rule`).
+
+use common_framework::{TriviaMode, TriviaPolicy};
+use parser_framework::{
+    AstNode, DefaultContext, Expected, ParseContext, Parser, ParsingRule, Position, RecoveryRule,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Stmt(String),
+    Error(String),
+}
+
+impl AstNode for Node {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+/// Consumes tokens up to and including the next `;`, returning what was
+/// before it, or `None` without a `;`.
+fn up_to_semicolon<Ctx: ParseContext<char>>(ctx: &mut Ctx) -> Option<String> {
+    let mut text = String::new();
+    loop {
+        match ctx.advance()? {
+            ';' => return Some(text),
+            ch => text.push(ch),
+        }
+    }
+}
+
+/// `word;` where the word is lowercase letters.
+struct StmtRule;
+
+impl<Ctx: ParseContext<char>> ParsingRule<Ctx, char, Node> for StmtRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Node> {
+        ctx.speculate(|ctx| {
+            let text = up_to_semicolon(ctx)?;
+            let valid = !text.is_empty() && text.chars().all(|ch| ch.is_ascii_lowercase());
+            valid.then_some(Node::Stmt(text))
+        })
+    }
+}
+
+/// `#` up to the end of the input, seeing trivia: fails on anything else.
+struct RawRule;
+
+impl<Ctx: ParseContext<char>> ParsingRule<Ctx, char, Node> for RawRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Node> {
+        ctx.speculate(|ctx| {
+            if ctx.advance()? != '#' {
+                return None;
+            }
+            let mut text = String::new();
+            while let Some(ch) = ctx.advance() {
+                text.push(ch);
+            }
+            Some(Node::Stmt(text))
+        })
+    }
+
+    fn trivia(&self) -> TriviaMode {
+        TriviaMode::Raw
+    }
+}
+
+/// Skips a malformed statement up to its `;`.
+struct SkipStatement;
+
+impl<Ctx: ParseContext<char>> RecoveryRule<Ctx, char, Node> for SkipStatement {
+    fn recover(&mut self, ctx: &mut Ctx, _expected: Option<&Expected<char>>) -> Option<Node> {
+        up_to_semicolon(ctx).map(Node::Error)
+    }
+}
+
+fn parser(input: &str) -> Parser<DefaultContext<char>, char, Node> {
+    Parser::new(
+        DefaultContext::new(input.chars().collect()),
+        vec![Box::new(StmtRule), Box::new(RawRule)],
+    )
+    .with_trivia(TriviaPolicy::new(|ch| *ch == ' ', |ch| *ch == '\n'))
+    .with_recovery_rule(SkipStatement)
+}
+
+#[test]
+fn test_recovery_rule_skips_to_semicolon() {
+    let mut parser = parser("ab; 1x; cd;");
+    assert_eq!(
+        parser.parse(),
+        [
+            Node::Stmt("ab".into()),
+            Node::Error("1x".into()),
+            Node::Stmt("cd".into())
+        ]
+    );
+    assert_eq!(parser.context().token_index(), 11);
+}
+
+#[test]
+fn test_failed_recovery_stops_after_trivia() {
+    let mut parser = parser("ab;  1x");
+    assert_eq!(parser.next_node(), Some(Node::Stmt("ab".into())));
+    // The raw rule runs last, from before the trivia, and fails; recovery
+    // then starts after the trivia, finds no `;` and fails too.
+    assert_eq!(parser.next_node(), None);
+    assert_eq!(parser.context().token_index(), 5);
+    assert_eq!(parser.context_mut().peek(), Some(&'1'));
+}
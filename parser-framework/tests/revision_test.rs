@@ -0,0 +1,120 @@
+//! Node revision tests (`RevisionStream`, `NodeRevision`).
+#![cfg(feature = "streaming")]
+
+use common_framework::{Inbound, Outbound, StreamingSignal};
+use parser_framework::{
+    AstNode, NodeRevision, ParseContext, Parser, ParsingRule, Position, RevisionStream,
+    StreamingParseContext, TokenConsumer,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Word(String);
+
+impl AstNode for Word {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+/// Letters up to an optional `;`, without waiting for more.
+struct WordRule;
+
+impl<Ctx: ParseContext<char>> ParsingRule<Ctx, char, Word> for WordRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Word> {
+        let mut word = String::new();
+        while ctx.peek().is_some_and(char::is_ascii_lowercase) {
+            word.push(ctx.advance()?);
+        }
+        if word.is_empty() {
+            return None;
+        }
+        if ctx.peek() == Some(&';') {
+            ctx.advance();
+        }
+        Some(Word(word))
+    }
+}
+
+fn stream() -> RevisionStream<char, Word> {
+    Parser::new(StreamingParseContext::new(), vec![Box::new(WordRule)]).into_revisions()
+}
+
+fn push(stream: &mut RevisionStream<char, Word>, input: &str) -> Vec<NodeRevision<Word>> {
+    stream.push_tokens(input.chars().collect())
+}
+
+/// The word and provisional flag of each revision.
+fn summary(revisions: &[NodeRevision<Word>]) -> Vec<(&str, bool)> {
+    revisions
+        .iter()
+        .map(|revision| (revision.node.0.as_str(), revision.provisional))
+        .collect()
+}
+
+#[test]
+fn test_revision_replaces_provisional_node() {
+    let mut stream = stream();
+
+    let first = push(&mut stream, "ab;c");
+    assert_eq!(summary(&first), [("ab", false), ("c", true)]);
+    assert_eq!(first[0].replaces, None);
+    assert_eq!(first[1].replaces, None);
+    assert_ne!(first[0].id, first[1].id);
+    assert_eq!(stream.provisional(), Some(first[1].id));
+
+    let second = push(&mut stream, "d");
+    assert_eq!(summary(&second), [("cd", true)]);
+    assert_eq!(second[0].replaces, Some(first[1].id));
+    assert_eq!(stream.provisional(), Some(second[0].id));
+
+    // A node ending at the last token stays provisional even after its `;`.
+    let third = push(&mut stream, ";e;");
+    assert_eq!(summary(&third), [("cd", false), ("e", true)]);
+    assert_eq!(third[0].replaces, Some(second[0].id));
+    assert_eq!(third[1].replaces, None);
+    assert_eq!(stream.provisional(), Some(third[1].id));
+}
+
+#[test]
+fn test_finish_completes_provisional_node() {
+    let mut stream = stream();
+    let first = push(&mut stream, "ab");
+    assert_eq!(summary(&first), [("ab", true)]);
+
+    let last = stream.finish();
+    assert_eq!(summary(&last), [("ab", false)]);
+    assert_eq!(last[0].replaces, Some(first[0].id));
+    assert_eq!(stream.provisional(), None);
+}
+
+#[test]
+fn test_signals_carry_revisions() {
+    let mut stream = stream();
+    assert!(matches!(
+        stream.next_signal(),
+        Some(StreamingSignal::NeedToken(1))
+    ));
+
+    stream.handle_signal(StreamingSignal::SupplyTokens("ab;".chars().collect()));
+    match stream.next_signal() {
+        Some(StreamingSignal::Produced(revisions)) => {
+            assert_eq!(summary(&revisions), [("ab", true)])
+        }
+        other => panic!("expected revisions, got {:?}", other),
+    }
+
+    stream.handle_signal(StreamingSignal::SupplyToken('c'));
+    stream.handle_signal(StreamingSignal::EndOfInput);
+    match stream.next_signal() {
+        Some(StreamingSignal::Produced(revisions)) => {
+            assert_eq!(summary(&revisions), [("ab", false), ("c", false)])
+        }
+        other => panic!("expected revisions, got {:?}", other),
+    }
+    assert!(
+        matches!(stream.next_signal(), Some(StreamingSignal::Finished(nodes)) if nodes.is_empty())
+    );
+
+    // The wrapped parser consumed everything.
+    assert!(stream.into_inner().context_mut().is_eof());
+}
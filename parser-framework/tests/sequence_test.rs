@@ -0,0 +1,191 @@
+//! Sequence tests (`Sequence`, `SequenceRule`).
+
+use parser_framework::{
+    AstNode, DefaultContext, ParseContext, Parser, ParsingRule, Position, Sequence,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Num(i64),
+    Semi,
+    LBrace,
+    RBrace,
+    End,
+    Bad,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Num(i64),
+    Block(Vec<Node>),
+    Error(Vec<Tok>),
+}
+
+impl AstNode for Node {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+type Ctx = DefaultContext<Tok>;
+
+struct NumRule;
+
+impl ParsingRule<Ctx, Tok, Node> for NumRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Node> {
+        match ctx.advance()? {
+            Tok::Num(n) => Some(Node::Num(n)),
+            _ => None,
+        }
+    }
+}
+
+/// A rule that always matches without consuming anything.
+struct EmptyRule;
+
+impl ParsingRule<Ctx, Tok, Node> for EmptyRule {
+    fn try_parse(&mut self, _ctx: &mut Ctx) -> Option<Node> {
+        Some(Node::Num(0))
+    }
+
+    fn priority(&self) -> i32 {
+        10
+    }
+}
+
+fn statements() -> Sequence<Ctx, Tok, Node> {
+    Sequence::new(vec![Box::new(NumRule)], |t| *t == Tok::Semi)
+}
+
+fn parse(mut sequence: Sequence<Ctx, Tok, Node>, tokens: Vec<Tok>) -> (Option<Vec<Node>>, usize) {
+    let mut ctx = DefaultContext::new(tokens);
+    let items = sequence.parse(&mut ctx);
+    (items, ctx.token_index())
+}
+
+#[test]
+fn test_separator_runs_and_trailing_separator() {
+    use Tok::*;
+    let (items, consumed) = parse(statements(), vec![Semi, Num(1), Semi, Semi, Num(2), Semi]);
+    assert_eq!(items.unwrap(), [Node::Num(1), Node::Num(2)]);
+    assert_eq!(consumed, 6);
+}
+
+#[test]
+fn test_stops_at_bad_item_without_recovery() {
+    use Tok::*;
+    let (items, consumed) = parse(statements(), vec![Num(1), Num(2), Semi, Num(3)]);
+    assert_eq!(items.unwrap(), [Node::Num(1)]);
+    assert_eq!(consumed, 1);
+
+    let (items, consumed) = parse(statements(), vec![Bad, Semi, Num(3)]);
+    assert_eq!(items.unwrap(), []);
+    assert_eq!(consumed, 0);
+}
+
+#[test]
+fn test_until_leaves_terminator() {
+    use Tok::*;
+    let sequence = statements().until(|t| *t == End);
+    let (items, consumed) = parse(sequence, vec![Num(1), Semi, End, Num(2)]);
+    assert_eq!(items.unwrap(), [Node::Num(1)]);
+    assert_eq!(consumed, 2);
+}
+
+#[test]
+fn test_delimited_consumes_both_ends() {
+    use Tok::*;
+    let block = || statements().delimited(|t| *t == LBrace, |t| *t == RBrace);
+
+    let (items, consumed) = parse(block(), vec![LBrace, Num(1), Semi, Num(2), RBrace, Num(3)]);
+    assert_eq!(items.unwrap(), [Node::Num(1), Node::Num(2)]);
+    assert_eq!(consumed, 5);
+
+    let (items, consumed) = parse(block(), vec![LBrace, RBrace]);
+    assert_eq!(items.unwrap(), []);
+    assert_eq!(consumed, 2);
+}
+
+#[test]
+fn test_unclosed_delimited_puts_tokens_back() {
+    use Tok::*;
+    let block = || statements().delimited(|t| *t == LBrace, |t| *t == RBrace);
+    assert_eq!(parse(block(), vec![LBrace, Num(1), Semi]), (None, 0));
+    assert_eq!(
+        parse(block(), vec![LBrace, Num(1), Num(2), RBrace]),
+        (None, 0)
+    );
+    assert_eq!(parse(block(), vec![Num(1), RBrace]), (None, 0));
+}
+
+#[test]
+fn test_recover_replaces_bad_items() {
+    use Tok::*;
+    let sequence = statements().recover(|skipped, _| Node::Error(skipped));
+    let (items, consumed) = parse(sequence, vec![Num(1), Bad, Bad, Semi, Num(2), Num(3), Semi]);
+    assert_eq!(
+        items.unwrap(),
+        [
+            Node::Num(1),
+            Node::Error(vec![Bad, Bad]),
+            Node::Num(2),
+            Node::Error(vec![Num(3)]),
+        ]
+    );
+    assert_eq!(consumed, 7);
+}
+
+#[test]
+fn test_recover_stops_at_close() {
+    use Tok::*;
+    let sequence = statements()
+        .delimited(|t| *t == LBrace, |t| *t == RBrace)
+        .recover(|skipped, _| Node::Error(skipped));
+    let (items, consumed) = parse(sequence, vec![LBrace, Bad, RBrace]);
+    assert_eq!(items.unwrap(), [Node::Error(vec![Bad])]);
+    assert_eq!(consumed, 3);
+}
+
+#[test]
+fn test_items_that_consume_nothing_fail() {
+    use Tok::*;
+    let sequence = Sequence::new(vec![Box::new(EmptyRule), Box::new(NumRule)], |t: &Tok| {
+        *t == Semi
+    });
+    // The higher-priority empty rule is skipped in favour of one that makes
+    // progress, and at a bad token the sequence ends instead of looping.
+    let (items, consumed) = parse(sequence, vec![Num(1), Semi, Bad]);
+    assert_eq!(items.unwrap(), [Node::Num(1)]);
+    assert_eq!(consumed, 2);
+}
+
+#[test]
+fn test_into_rule_builds_blocks() {
+    use Tok::*;
+    let block = statements()
+        .delimited(|t| *t == LBrace, |t| *t == RBrace)
+        .into_rule(|items, _| Node::Block(items))
+        .with_priority(1);
+    let rules: Vec<Box<dyn ParsingRule<Ctx, Tok, Node>>> = vec![Box::new(NumRule), Box::new(block)];
+    let tokens = vec![LBrace, Num(1), Semi, Num(2), RBrace, Num(3)];
+    let nodes = Parser::new(DefaultContext::new(tokens), rules).parse();
+    assert_eq!(
+        nodes,
+        [Node::Block(vec![Node::Num(1), Node::Num(2)]), Node::Num(3)]
+    );
+}
+
+#[test]
+fn test_empty_undelimited_rule_does_not_match() {
+    use Tok::*;
+    let mut rule = statements().into_rule(|items, _| Node::Block(items));
+    let mut ctx = DefaultContext::new(vec![Bad]);
+    assert_eq!(rule.try_parse(&mut ctx), None);
+    assert_eq!(ctx.token_index(), 0);
+
+    let mut ctx = DefaultContext::new(vec![Num(1), Semi, Bad]);
+    assert_eq!(
+        rule.try_parse(&mut ctx),
+        Some(Node::Block(vec![Node::Num(1)]))
+    );
+}
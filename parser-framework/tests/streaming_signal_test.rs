@@ -0,0 +1,118 @@
+//! Streaming parser signal tests (`Inbound` / `Outbound` on `Parser`).
+#![cfg(feature = "streaming")]
+
+use common_framework::{Inbound, Outbound, StreamingSignal};
+use parser_framework::{
+    AstNode, ParseContext, Parser, ParsingRule, Position, StreamingParseContext,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Num(i64),
+    Plus,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Sum(i64);
+
+impl AstNode for Sum {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+/// `n (+ n)*`, waiting while the sum may still continue.
+struct SumRule;
+
+impl<Ctx: ParseContext<Tok>> ParsingRule<Ctx, Tok, Sum> for SumRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Sum> {
+        let checkpoint = ctx.checkpoint();
+        let mut total = 0;
+        loop {
+            match ctx.advance() {
+                Some(Tok::Num(n)) => total += n,
+                _ => {
+                    ctx.restore(checkpoint);
+                    return None;
+                }
+            }
+            if ctx.peek().is_none() && !ctx.is_eof() {
+                ctx.restore(checkpoint);
+                return None;
+            }
+            if ctx.peek() != Some(&Tok::Plus) {
+                return Some(Sum(total));
+            }
+            ctx.advance();
+        }
+    }
+}
+
+type SumParser = Parser<StreamingParseContext<Tok>, Tok, Sum>;
+
+fn parser() -> SumParser {
+    Parser::new(StreamingParseContext::new(), vec![Box::new(SumRule)])
+}
+
+fn supply(parser: &mut SumParser, token: Tok) -> Option<StreamingSignal<Tok, Sum>> {
+    parser.handle_signal(StreamingSignal::SupplyToken(token));
+    parser.next_signal()
+}
+
+fn produced(signal: Option<StreamingSignal<Tok, Sum>>) -> Option<Vec<Sum>> {
+    match signal {
+        Some(StreamingSignal::Produced(nodes)) => Some(nodes),
+        _ => None,
+    }
+}
+
+fn needs_token(signal: Option<StreamingSignal<Tok, Sum>>) -> bool {
+    matches!(signal, Some(StreamingSignal::NeedToken(1)))
+}
+
+#[test]
+fn test_supplied_token_only_buffers() {
+    let mut parser = parser();
+    parser.handle_signal(StreamingSignal::SupplyToken(Tok::Num(1)));
+    assert_eq!(parser.context().buffered_len(), 1);
+    assert_eq!(parser.context().token_index(), 0);
+}
+
+#[test]
+fn test_nodes_are_reported_through_produced() {
+    let mut parser = parser();
+    assert!(needs_token(supply(&mut parser, Tok::Num(1))));
+    assert!(needs_token(supply(&mut parser, Tok::Plus)));
+    assert!(needs_token(supply(&mut parser, Tok::Num(2))));
+    assert_eq!(
+        produced(supply(&mut parser, Tok::Num(5))),
+        Some(vec![Sum(3)])
+    );
+}
+
+#[test]
+fn test_end_of_input_completes_the_waiting_node() {
+    let mut parser = parser();
+    assert!(needs_token(supply(&mut parser, Tok::Num(4))));
+    parser.handle_signal(StreamingSignal::EndOfInput);
+    assert_eq!(produced(parser.next_signal()), Some(vec![Sum(4)]));
+    assert!(
+        matches!(parser.next_signal(), Some(StreamingSignal::Finished(nodes)) if nodes.is_empty())
+    );
+}
+
+#[test]
+fn test_resume_reopens_after_end_of_input() {
+    let mut parser = parser();
+    parser.handle_signal(StreamingSignal::EndOfInput);
+    assert!(matches!(
+        parser.next_signal(),
+        Some(StreamingSignal::Finished(_))
+    ));
+
+    parser.handle_signal(StreamingSignal::Resume);
+    assert!(needs_token(parser.next_signal()));
+    assert!(needs_token(supply(&mut parser, Tok::Num(2))));
+    parser.handle_signal(StreamingSignal::EndOfInput);
+    assert_eq!(produced(parser.next_signal()), Some(vec![Sum(2)]));
+}
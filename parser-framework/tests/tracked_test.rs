@@ -0,0 +1,129 @@
+//! Provisional node tests (`Parser::push_tokens_tracked`, `Completeness`).
+#![cfg(feature = "streaming")]
+
+use common_framework::Feedback;
+use parser_framework::{
+    AstNode, Completeness, ParseContext, Parser, ParsingRule, Position, StreamingParseContext,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Word(String);
+
+impl AstNode for Word {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+/// Letters up to an optional `;`, without waiting for more. Sends the word
+/// as feedback, and cuts after its first letter if `cut` is set.
+struct WordRule {
+    cut: bool,
+}
+
+impl<Ctx: ParseContext<char>> ParsingRule<Ctx, char, Word> for WordRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Word> {
+        let mut word = String::new();
+        while ctx.peek().is_some_and(char::is_ascii_lowercase) {
+            word.push(ctx.advance()?);
+            if self.cut && word.len() == 1 {
+                ctx.cut();
+            }
+        }
+        if word.is_empty() {
+            return None;
+        }
+        if ctx.peek() == Some(&';') {
+            ctx.advance();
+        }
+        ctx.send_feedback(Feedback::new(word.clone()));
+        Some(Word(word))
+    }
+}
+
+type WordParser = Parser<StreamingParseContext<char>, char, Word>;
+
+fn parser(cut: bool) -> WordParser {
+    Parser::new(
+        StreamingParseContext::new(),
+        vec![Box::new(WordRule { cut })],
+    )
+}
+
+fn push(parser: &mut WordParser, input: &str) -> Vec<Completeness<Word>> {
+    parser.push_tokens_tracked(input.chars().collect())
+}
+
+fn complete(word: &str) -> Completeness<Word> {
+    Completeness::Complete(Word(word.into()))
+}
+
+fn provisional(word: &str) -> Completeness<Word> {
+    Completeness::Provisional(Word(word.into()))
+}
+
+fn feedback(parser: &mut WordParser) -> Vec<String> {
+    std::iter::from_fn(|| parser.context_mut().take_feedback())
+        .map(|feedback| feedback.downcast_ref::<String>().unwrap().clone())
+        .collect()
+}
+
+#[test]
+fn test_provisional_node_is_replaced() {
+    let mut parser = parser(false);
+    let mut document = Vec::new();
+
+    let nodes = push(&mut parser, "ab;c");
+    assert_eq!(nodes, [complete("ab"), provisional("c")]);
+    nodes
+        .into_iter()
+        .for_each(|node| node.push_onto(&mut document));
+
+    let nodes = push(&mut parser, "d;e");
+    assert_eq!(nodes, [complete("cd"), provisional("e")]);
+    nodes
+        .into_iter()
+        .for_each(|node| node.push_onto(&mut document));
+    assert_eq!(document, [complete("ab"), complete("cd"), provisional("e")]);
+
+    let nodes = parser.finish_tracked();
+    assert_eq!(nodes, [complete("e")]);
+    nodes
+        .into_iter()
+        .for_each(|node| node.push_onto(&mut document));
+    assert_eq!(document, [complete("ab"), complete("cd"), complete("e")]);
+}
+
+#[test]
+fn test_empty_push_does_not_repeat_provisional_node() {
+    let mut parser = parser(false);
+    assert_eq!(push(&mut parser, "ab"), [provisional("ab")]);
+    assert!(push(&mut parser, "").is_empty());
+    assert!(push(&mut parser, "").is_empty());
+    assert_eq!(push(&mut parser, "c"), [provisional("abc")]);
+}
+
+#[test]
+fn test_provisional_feedback_is_withdrawn() {
+    let mut parser = parser(false);
+    assert_eq!(
+        push(&mut parser, "ab;cd"),
+        [complete("ab"), provisional("cd")]
+    );
+    assert_eq!(feedback(&mut parser), ["ab"]);
+
+    assert_eq!(push(&mut parser, "e;"), [provisional("cde")]);
+    assert!(feedback(&mut parser).is_empty());
+
+    assert_eq!(parser.finish_tracked(), [complete("cde")]);
+    assert_eq!(feedback(&mut parser), ["cde"]);
+}
+
+#[test]
+fn test_cut_node_is_complete() {
+    let mut parser = parser(true);
+    assert_eq!(push(&mut parser, "ab"), [complete("ab")]);
+    assert_eq!(feedback(&mut parser), ["ab"]);
+    // The cut node's tokens are gone, so more letters start a new node.
+    assert_eq!(push(&mut parser, "c"), [complete("c")]);
+}
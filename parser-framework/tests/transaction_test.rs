@@ -0,0 +1,151 @@
+//! Transaction tests (`ParseContext::transaction`, `probe`, `commit`).
+
+#[cfg(feature = "streaming")]
+use parser_framework::StreamingParseContext;
+use parser_framework::{DefaultContext, LazyContext, ParseContext};
+
+type Lazy = LazyContext<std::vec::IntoIter<u32>, u32>;
+
+fn tokens() -> Vec<u32> {
+    vec![1, 2, 3, 4]
+}
+
+fn lazy() -> Lazy {
+    LazyContext::new(tokens().into_iter(), 8)
+}
+
+#[cfg(feature = "streaming")]
+fn streaming() -> StreamingParseContext<u32> {
+    let mut ctx = StreamingParseContext::new();
+    ctx.push_tokens(tokens());
+    ctx
+}
+
+/// Commits an inner transaction, then fails the outer one.
+fn fail_after_inner_success<Ctx: ParseContext<u32>>(ctx: &mut Ctx) -> Option<()> {
+    ctx.transaction(|c| {
+        c.advance()?;
+        c.transaction(|c| c.advance())?;
+        None
+    })
+}
+
+/// Consumes two tokens and commits, then fails.
+fn fail_after_commit<Ctx: ParseContext<u32>>(ctx: &mut Ctx) -> Option<()> {
+    ctx.transaction(|c| {
+        c.advance()?;
+        c.advance()?;
+        c.commit();
+        None
+    })
+}
+
+/// Consumes and commits in every way a rule can, inside a probe.
+fn probe_committing<Ctx: ParseContext<u32>>(ctx: &mut Ctx) -> bool {
+    ctx.probe(|c| {
+        c.advance();
+        c.transaction(|c| c.advance());
+        c.commit();
+        true
+    })
+}
+
+#[test]
+fn test_nested_success_commits_at_outermost_level() {
+    let mut ctx = lazy();
+    let start = ctx.checkpoint();
+    ctx.transaction(|c| {
+        c.advance()?;
+        c.transaction(|c| c.advance())?;
+        assert!(c.can_restore(&start));
+        Some(())
+    });
+    assert_eq!(ctx.token_index(), 2);
+    assert!(!ctx.can_restore(&start));
+}
+
+#[cfg(feature = "streaming")]
+#[test]
+fn test_nested_success_commits_at_outermost_level_streaming() {
+    let mut ctx = streaming();
+    ctx.transaction(|c| {
+        c.advance()?;
+        c.transaction(|c| c.advance())?;
+        assert_eq!(c.buffered_len(), 4);
+        Some(())
+    });
+    assert_eq!(ctx.token_index(), 2);
+    assert_eq!(ctx.buffered_len(), 2);
+}
+
+#[test]
+fn test_outer_failure_rolls_back_inner_success() {
+    let mut ctx = DefaultContext::new(tokens());
+    assert_eq!(fail_after_inner_success(&mut ctx), None);
+    assert_eq!(ctx.token_index(), 0);
+
+    let mut ctx = lazy();
+    assert_eq!(fail_after_inner_success(&mut ctx), None);
+    assert_eq!(ctx.token_index(), 0);
+    assert_eq!(ctx.backtrack_error(), None);
+    assert_eq!(ctx.advance(), Some(1));
+}
+
+#[cfg(feature = "streaming")]
+#[test]
+fn test_outer_failure_rolls_back_inner_success_streaming() {
+    let mut ctx = streaming();
+    assert_eq!(fail_after_inner_success(&mut ctx), None);
+    assert_eq!(ctx.token_index(), 0);
+    assert_eq!(ctx.buffered_len(), 4);
+    assert_eq!(ctx.advance(), Some(1));
+}
+
+#[test]
+fn test_commit_inside_transaction_keeps_history() {
+    let mut ctx = lazy();
+    assert_eq!(fail_after_commit(&mut ctx), None);
+    assert_eq!(ctx.token_index(), 0);
+    assert_eq!(ctx.backtrack_error(), None);
+
+    // Outside a transaction the same commit prunes.
+    let start = ctx.checkpoint();
+    ctx.advance();
+    ctx.commit();
+    assert!(!ctx.can_restore(&start));
+}
+
+#[cfg(feature = "streaming")]
+#[test]
+fn test_commit_inside_transaction_keeps_history_streaming() {
+    let mut ctx = streaming();
+    assert_eq!(fail_after_commit(&mut ctx), None);
+    assert_eq!(ctx.token_index(), 0);
+    assert_eq!(ctx.buffered_len(), 4);
+
+    ctx.advance();
+    ctx.commit();
+    assert_eq!(ctx.buffered_len(), 3);
+}
+
+#[test]
+fn test_probe_never_prunes() {
+    let mut ctx = DefaultContext::new(tokens());
+    assert!(probe_committing(&mut ctx));
+    assert_eq!(ctx.token_index(), 0);
+
+    let mut ctx = lazy();
+    let start = ctx.checkpoint();
+    assert!(probe_committing(&mut ctx));
+    assert_eq!(ctx.token_index(), 0);
+    assert!(ctx.can_restore(&start));
+}
+
+#[cfg(feature = "streaming")]
+#[test]
+fn test_probe_never_prunes_streaming() {
+    let mut ctx = streaming();
+    assert!(probe_committing(&mut ctx));
+    assert_eq!(ctx.token_index(), 0);
+    assert_eq!(ctx.buffered_len(), 4);
+}
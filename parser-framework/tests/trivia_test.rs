@@ -0,0 +1,223 @@
+//! Trivia tests (`Parser::with_trivia`, `ParseContext::skip_trivia`,
+//! `ParsingRule::trivia`).
+
+use common_framework::{TriviaMode, TriviaPolicy};
+use parser_framework::{AstNode, DefaultContext, ParseContext, Parser, ParsingRule, Position};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Assign(char, char),
+    Name(char),
+    Spaces(usize),
+    Str(String),
+    Line(char),
+}
+
+impl AstNode for Node {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+type Ctx = DefaultContext<char>;
+
+fn letter(ctx: &mut Ctx) -> Option<char> {
+    ctx.advance().filter(char::is_ascii_lowercase)
+}
+
+/// `x = y`, with trivia allowed around the `=`.
+struct AssignRule;
+
+impl ParsingRule<Ctx, char, Node> for AssignRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Node> {
+        let name = letter(ctx)?;
+        ctx.skip_trivia();
+        if ctx.advance()? != '=' {
+            return None;
+        }
+        ctx.skip_trivia();
+        Some(Node::Assign(name, letter(ctx)?))
+    }
+
+    fn priority(&self) -> i32 {
+        1
+    }
+}
+
+/// `x`
+struct NameRule;
+
+impl ParsingRule<Ctx, char, Node> for NameRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Node> {
+        letter(ctx).map(Node::Name)
+    }
+}
+
+fn policy() -> TriviaPolicy<char> {
+    TriviaPolicy::new(|c| *c == ' ', |c| *c == '\n')
+}
+
+fn parser(source: &str) -> Parser<Ctx, char, Node> {
+    Parser::new(
+        DefaultContext::new(source.chars().collect()),
+        vec![Box::new(AssignRule), Box::new(NameRule)],
+    )
+}
+
+#[test]
+fn test_without_policy_nothing_is_skipped() {
+    let mut parser = parser("a = b");
+    assert!(parser.trivia().is_none());
+    assert_eq!(parser.parse(), [Node::Name('a')]);
+}
+
+#[test]
+fn test_trivia_is_skipped_before_and_inside_rules() {
+    let mut parser = parser("  a  =  b c ").with_trivia(policy());
+    assert!(parser.trivia().is_some());
+    assert_eq!(parser.parse(), [Node::Assign('a', 'b'), Node::Name('c')]);
+}
+
+#[test]
+fn test_newlines_are_significant_by_default() {
+    let mut significant = parser("a =\nb").with_trivia(policy());
+    assert_eq!(significant.parse(), [Node::Name('a')]);
+
+    let mut skipped = parser("a =\n b").with_trivia(policy().with_newlines_skipped(true));
+    assert_eq!(skipped.parse(), [Node::Assign('a', 'b')]);
+}
+
+#[test]
+fn test_per_line_with_trivia() {
+    let mut parser = parser(" a = b \n\n  c \n d=e").with_trivia(policy());
+    let nodes = parser.parse_per_line(|c| *c == '\n');
+    assert_eq!(
+        nodes,
+        [
+            Node::Assign('a', 'b'),
+            Node::Name('c'),
+            Node::Assign('d', 'e')
+        ]
+    );
+    assert!(parser.context_mut().take_diagnostics().is_empty());
+}
+
+#[test]
+fn test_single_expression_ignores_trailing_trivia() {
+    let mut parser = parser(" a = b  ").with_trivia(policy());
+    assert_eq!(
+        parser.parse_single_expression_strict().unwrap(),
+        Node::Assign('a', 'b')
+    );
+}
+
+/// A run of spaces, which only a raw rule can see.
+struct SpacesRule;
+
+impl ParsingRule<Ctx, char, Node> for SpacesRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Node> {
+        let mut count = 0;
+        while ctx.peek() == Some(&' ') {
+            ctx.advance();
+            count += 1;
+        }
+        (count > 0).then_some(Node::Spaces(count))
+    }
+
+    fn priority(&self) -> i32 {
+        3
+    }
+
+    fn trivia(&self) -> TriviaMode {
+        TriviaMode::Raw
+    }
+}
+
+/// `'...'`, keeping the spaces inside.
+struct StrRule;
+
+impl ParsingRule<Ctx, char, Node> for StrRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Node> {
+        if ctx.advance()? != '\'' {
+            return None;
+        }
+        // Nothing is trivia inside a raw rule.
+        assert_eq!(ctx.skip_trivia(), 0);
+        let mut text = String::new();
+        loop {
+            match ctx.advance()? {
+                '\'' => return Some(Node::Str(text)),
+                c => text.push(c),
+            }
+        }
+    }
+
+    fn priority(&self) -> i32 {
+        3
+    }
+
+    fn trivia(&self) -> TriviaMode {
+        TriviaMode::Raw
+    }
+}
+
+/// `x` followed by a line break, in a grammar that otherwise skips them.
+struct LineRule;
+
+impl ParsingRule<Ctx, char, Node> for LineRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Node> {
+        let name = letter(ctx)?;
+        ctx.skip_trivia();
+        (ctx.advance()? == '\n').then_some(Node::Line(name))
+    }
+
+    fn priority(&self) -> i32 {
+        2
+    }
+
+    fn trivia(&self) -> TriviaMode {
+        TriviaMode::KeepNewlines
+    }
+}
+
+fn parser_with(
+    source: &str,
+    rule: impl ParsingRule<Ctx, char, Node> + 'static,
+) -> Parser<Ctx, char, Node> {
+    Parser::new(
+        DefaultContext::new(source.chars().collect()),
+        vec![Box::new(rule), Box::new(AssignRule), Box::new(NameRule)],
+    )
+}
+
+#[test]
+fn test_raw_rule_sees_trivia() {
+    let mut parser = parser_with("a  b = c   d", SpacesRule).with_trivia(policy());
+    assert_eq!(
+        parser.parse(),
+        [
+            Node::Name('a'),
+            Node::Spaces(2),
+            Node::Assign('b', 'c'),
+            Node::Spaces(3),
+            Node::Name('d'),
+        ]
+    );
+}
+
+#[test]
+fn test_raw_rule_skips_no_trivia_inside() {
+    let mut parser = parser_with("'a  b'c", StrRule).with_trivia(policy());
+    assert_eq!(parser.parse(), [Node::Str("a  b".into()), Node::Name('c')]);
+
+    // Other rules still skip trivia while a raw rule exists.
+    let mut parser = parser_with(" a = b", StrRule).with_trivia(policy());
+    assert_eq!(parser.parse(), [Node::Assign('a', 'b')]);
+}
+
+#[test]
+fn test_keep_newlines_rule_sees_line_breaks() {
+    let skip_newlines = policy().with_newlines_skipped(true);
+    let mut parser = parser_with("a \n\n b = \n c", LineRule).with_trivia(skip_newlines);
+    assert_eq!(parser.parse(), [Node::Line('a'), Node::Assign('b', 'c')]);
+}
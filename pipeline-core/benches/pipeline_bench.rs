@@ -2,7 +2,7 @@ use criterion::{criterion_group, criterion_main, Criterion, Throughput};
 use lexer_framework::{DefaultContext as LexDefaultContext, LexContext, Lexer, LexingRule};
 use parser_framework::{
     parse_pratt, AstNode, DefaultContext, LazyContext, ParseContext, Parser, ParsingRule, Position,
-    PrattConfig,
+    PrattConfig, PrattSpan,
 };
 use pipeline_core::BatchPipeline;
 use std::sync::Arc;
@@ -124,7 +124,13 @@ where
             _ => None,
         }
     }
-    fn parse_prefix<F>(&self, token: Token, ctx: &mut Ctx, parser: &F) -> Option<Ast>
+    fn parse_prefix<F>(
+        &self,
+        token: Token,
+        _span: PrattSpan,
+        ctx: &mut Ctx,
+        parser: &F,
+    ) -> Option<Ast>
     where
         F: Fn(&mut Ctx, u8) -> Option<Ast>,
     {
@@ -146,6 +152,7 @@ where
         &self,
         left: Ast,
         token: Token,
+        _span: PrattSpan,
         r_bp: u8,
         ctx: &mut Ctx,
         parser: &F,
@@ -0,0 +1,67 @@
+use std::fmt;
+use std::thread;
+use std::time::Duration;
+
+/// Callback deciding whether to retry a blocked stage.
+pub type BlockedCallback = Box<dyn FnMut(&str, usize) -> bool>;
+
+/// What a [`StreamingPipeline`](crate::StreamingPipeline) does when the
+/// lexer or parser reports `Blocked`, e.g. while waiting on network input.
+///
+/// The attempt count restarts whenever a token or node gets through, so
+/// `max` limits consecutive blocks, not blocks per run.
+#[derive(Default)]
+pub enum BlockedPolicy {
+    /// Abort the run on the first block. The default.
+    #[default]
+    Abort,
+    /// Sleep for `delay` and ask again, up to `max` times in a row, then
+    /// abort.
+    Retry { max: usize, delay: Duration },
+    /// Ask the callback, which gets the reason and the number of this
+    /// consecutive attempt (from 1); `true` retries immediately, `false`
+    /// aborts. The callback may sleep or poll for input itself.
+    Callback(BlockedCallback),
+}
+
+impl BlockedPolicy {
+    /// Creates a [`Callback`](Self::Callback) policy.
+    pub fn callback<F>(f: F) -> Self
+    where
+        F: FnMut(&str, usize) -> bool + 'static,
+    {
+        Self::Callback(Box::new(f))
+    }
+
+    /// Decides whether to retry after the `attempt`-th consecutive block,
+    /// waiting first if the policy says so.
+    pub(crate) fn should_retry(&mut self, reason: &str, attempt: usize) -> bool {
+        match self {
+            Self::Abort => false,
+            Self::Retry { max, delay } => {
+                if attempt > *max {
+                    return false;
+                }
+                if !delay.is_zero() {
+                    thread::sleep(*delay);
+                }
+                true
+            }
+            Self::Callback(f) => f(reason, attempt),
+        }
+    }
+}
+
+impl fmt::Debug for BlockedPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Abort => f.write_str("Abort"),
+            Self::Retry { max, delay } => f
+                .debug_struct("Retry")
+                .field("max", max)
+                .field("delay", delay)
+                .finish(),
+            Self::Callback(_) => f.write_str("Callback(..)"),
+        }
+    }
+}
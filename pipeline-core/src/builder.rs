@@ -0,0 +1,256 @@
+// Typed setup for streaming pipelines
+use crate::{BlockedPolicy, StreamingPipeline};
+use common_framework::{Diagnostics, TriviaPolicy};
+use lexer_framework::{
+    FilterTokens, InputProducer, IterInput, LexContext, LexToken, Lexer, LexingRule,
+    StreamingLexContext,
+};
+use parser_framework::{AstNode, ParseContext, Parser, ParsingRule, StreamingParseContext};
+use std::fmt::Debug;
+
+/// Lexer rules over a [`StreamingLexContext`].
+pub type StreamingLexRules<Tok> = Vec<Box<dyn LexingRule<StreamingLexContext, Tok>>>;
+
+/// Parser rules over a [`StreamingParseContext`].
+pub type StreamingParseRules<Tok, Ast> =
+    Vec<Box<dyn ParsingRule<StreamingParseContext<Tok>, Tok, Ast>>>;
+
+/// The lexer stage of a built pipeline: the lexer with its token filter.
+pub type BuiltLexer<Tok, F = fn(&Tok) -> bool> = FilterTokens<Lexer<StreamingLexContext, Tok>, F>;
+
+/// The pipeline [`PipelineBuilder::build`] returns.
+pub type BuiltPipeline<Tok, Ast, F = fn(&Tok) -> bool> =
+    StreamingPipeline<BuiltLexer<Tok, F>, Parser<StreamingParseContext<Tok>, Tok, Ast>, Tok, Ast>;
+
+/// Assembles a [`StreamingPipeline`] from rules, creating the streaming
+/// contexts and the token filter in between:
+///
+/// ```ignore
+/// let nodes = PipelineBuilder::new()
+///     .input("3 + 4 * 2")
+///     .lexer(build_lexer_rules())
+///     .filter(|token: &CalcToken| !token.is_whitespace())
+///     .parser(build_parser_rules())
+///     .build()
+///     .run();
+/// ```
+///
+/// The type parameters track which stages are set, so `build` only exists
+/// once both are, and parser rules must take the token type the lexer rules
+/// produce. Without [`input`](Self::input), the lexer context starts empty
+/// and is fed by an [`input_producer`](Self::input_producer) or through
+/// `pipeline.lexer_mut().inner_mut().context_mut()`.
+pub struct PipelineBuilder<LexRules = (), Filter = (), ParseRules = ()> {
+    input: Option<String>,
+    input_producer: Option<Box<dyn InputProducer>>,
+    lexer_rules: LexRules,
+    filter: Filter,
+    parser_rules: ParseRules,
+    batch_size: usize,
+    on_blocked: BlockedPolicy,
+    diagnostics: Option<Diagnostics>,
+}
+
+impl PipelineBuilder {
+    /// Creates a builder with no stages.
+    pub fn new() -> Self {
+        Self {
+            input: None,
+            input_producer: None,
+            lexer_rules: (),
+            filter: (),
+            parser_rules: (),
+            batch_size: 1,
+            on_blocked: BlockedPolicy::Abort,
+            diagnostics: None,
+        }
+    }
+
+    /// Sets the lexer rules. All tokens are kept until a
+    /// [`filter`](PipelineBuilder::filter) is set.
+    pub fn lexer<Tok>(
+        self,
+        rules: StreamingLexRules<Tok>,
+    ) -> PipelineBuilder<StreamingLexRules<Tok>, fn(&Tok) -> bool> {
+        PipelineBuilder {
+            input: self.input,
+            input_producer: self.input_producer,
+            lexer_rules: rules,
+            filter: |_| true,
+            parser_rules: (),
+            batch_size: self.batch_size,
+            on_blocked: self.on_blocked,
+            diagnostics: self.diagnostics,
+        }
+    }
+}
+
+impl Default for PipelineBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L, F, P> PipelineBuilder<L, F, P> {
+    /// Sets the whole input; the lexer context is marked finished unless an
+    /// [`input_producer`](Self::input_producer) supplies more.
+    pub fn input<S: Into<String>>(mut self, input: S) -> Self {
+        self.input = Some(input.into());
+        self
+    }
+
+    /// Pulls input from `producer` whenever the lexer runs out, after any
+    /// [`input`](Self::input), which is then not marked finished. See
+    /// [`StreamingPipeline::with_input_producer`].
+    pub fn input_producer<I>(mut self, producer: I) -> Self
+    where
+        I: InputProducer + 'static,
+    {
+        self.input_producer = Some(Box::new(producer));
+        self
+    }
+
+    /// See [`StreamingPipeline::with_batch_size`].
+    pub fn batch_size(mut self, n: usize) -> Self {
+        self.batch_size = n;
+        self
+    }
+
+    /// See [`StreamingPipeline::on_blocked`].
+    pub fn on_blocked(mut self, policy: BlockedPolicy) -> Self {
+        self.on_blocked = policy;
+        self
+    }
+
+    /// Sends the diagnostics of both stages to `diagnostics`. By default
+    /// each context collects its own.
+    pub fn diagnostics(mut self, diagnostics: Diagnostics) -> Self {
+        self.diagnostics = Some(diagnostics);
+        self
+    }
+}
+
+impl<Tok, F, P> PipelineBuilder<StreamingLexRules<Tok>, F, P> {
+    /// Drops the tokens `keep` rejects before they reach the parser, e.g.
+    /// whitespace. Replaces any earlier filter.
+    pub fn filter<G>(self, keep: G) -> PipelineBuilder<StreamingLexRules<Tok>, G, P>
+    where
+        G: FnMut(&Tok) -> bool,
+    {
+        PipelineBuilder {
+            input: self.input,
+            input_producer: self.input_producer,
+            lexer_rules: self.lexer_rules,
+            filter: keep,
+            parser_rules: self.parser_rules,
+            batch_size: self.batch_size,
+            on_blocked: self.on_blocked,
+            diagnostics: self.diagnostics,
+        }
+    }
+
+    /// Drops the tokens `policy` treats as trivia before they reach the
+    /// parser, so parser rules never see them. This is a
+    /// [`filter`](Self::filter) and replaces any earlier one.
+    pub fn trivia(
+        self,
+        policy: TriviaPolicy<Tok>,
+    ) -> PipelineBuilder<StreamingLexRules<Tok>, impl FnMut(&Tok) -> bool, P> {
+        self.filter(move |token: &Tok| policy.is_significant(token))
+    }
+}
+
+impl<Tok, F> PipelineBuilder<StreamingLexRules<Tok>, F, ()>
+where
+    Tok: Clone + Debug,
+{
+    /// Sets the parser rules, which must consume the lexer's token type.
+    pub fn parser<Ast>(
+        self,
+        rules: StreamingParseRules<Tok, Ast>,
+    ) -> PipelineBuilder<StreamingLexRules<Tok>, F, StreamingParseRules<Tok, Ast>>
+    where
+        Ast: AstNode,
+    {
+        PipelineBuilder {
+            input: self.input,
+            input_producer: self.input_producer,
+            lexer_rules: self.lexer_rules,
+            filter: self.filter,
+            parser_rules: rules,
+            batch_size: self.batch_size,
+            on_blocked: self.on_blocked,
+            diagnostics: self.diagnostics,
+        }
+    }
+}
+
+impl<Tok, Ast, F> PipelineBuilder<StreamingLexRules<Tok>, F, StreamingParseRules<Tok, Ast>>
+where
+    Tok: LexToken,
+    Ast: AstNode,
+    F: FnMut(&Tok) -> bool,
+{
+    /// Creates the contexts and stages and wires them into a pipeline. The
+    /// parse context tracks positions through `LexToken::position`.
+    pub fn build(self) -> BuiltPipeline<Tok, Ast, F> {
+        let mut lex_context = match self.input {
+            Some(input) if self.input_producer.is_none() => StreamingLexContext::from(input),
+            Some(input) => {
+                let mut context = StreamingLexContext::new();
+                context.push_str(&input);
+                context
+            }
+            None => StreamingLexContext::new(),
+        };
+        let mut parse_context = StreamingParseContext::new().with_lex_positions();
+        if let Some(diagnostics) = self.diagnostics {
+            lex_context.set_diagnostics(diagnostics.clone());
+            parse_context.set_diagnostics(diagnostics);
+        }
+        let lexer = Lexer::new(lex_context, self.lexer_rules).filter_tokens(self.filter);
+        let parser = Parser::new(parse_context, self.parser_rules);
+        let pipeline = StreamingPipeline::new(lexer, parser)
+            .with_batch_size(self.batch_size)
+            .on_blocked(self.on_blocked);
+        match self.input_producer {
+            Some(producer) => pipeline.with_input_producer(producer),
+            None => pipeline,
+        }
+    }
+}
+
+impl<Tok, Ast> BuiltPipeline<Tok, Ast>
+where
+    Tok: LexToken,
+    Ast: AstNode,
+{
+    /// Creates a pipeline that lexes `chunks` in order, pulling the next one
+    /// whenever the lexer runs out, e.g. to parse file reads or network
+    /// frames as they arrive:
+    ///
+    /// ```ignore
+    /// let frames = socket.frames().map(|frame| frame.text);
+    /// let nodes = StreamingPipeline::from_chunks(frames, lexer_rules(), parser_rules()).run();
+    /// ```
+    ///
+    /// Chunks may split tokens. This is [`PipelineBuilder`] with an
+    /// [`IterInput`] producer and no token filter; use the builder for more
+    /// settings.
+    pub fn from_chunks<I>(
+        chunks: I,
+        lexer_rules: StreamingLexRules<Tok>,
+        parser_rules: StreamingParseRules<Tok, Ast>,
+    ) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+        I::IntoIter: 'static,
+    {
+        PipelineBuilder::new()
+            .lexer(lexer_rules)
+            .parser(parser_rules)
+            .input_producer(IterInput::new(chunks))
+            .build()
+    }
+}
@@ -0,0 +1,299 @@
+// Content-addressed cache of pipeline results
+use crate::BatchPipeline;
+use lexer_framework::{DefaultContext as LexDefaultContext, LexToken, LexingRule};
+use parser_framework::{AstNode, DefaultContext as ParseDefaultContext, ParsingRule};
+use std::collections::HashMap;
+
+/// A stable 64-bit hash of an input's text and length.
+///
+/// Uses FNV-1a rather than `std`'s hasher, whose output may change between
+/// Rust releases, so hashes stay valid in caches written by older builds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContentHash(u64);
+
+impl ContentHash {
+    /// Hashes `text`.
+    pub fn of(text: &str) -> Self {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+        let hash = text.bytes().fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        });
+        Self(hash ^ (text.len() as u64).wrapping_mul(PRIME))
+    }
+
+    /// Returns the hash value.
+    pub fn value(self) -> u64 {
+        self.0
+    }
+}
+
+/// Hit and miss counts of a [`ParseCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+/// Caches pipeline results (AST nodes, tokens or anything derived from an
+/// input) by the [`ContentHash`] of the input, so unchanged inputs are not
+/// lexed and parsed again.
+///
+/// A cache holds the results of one grammar, identified by a fingerprint
+/// the caller picks, e.g. a hash of the rule set's version: a cache saved
+/// by another grammar is not loaded. Entries keep their input text, so a
+/// hash collision is a miss rather than another input's result.
+///
+/// Large documents can be cached per chunk with
+/// [`get_or_insert_chunks`](Self::get_or_insert_chunks), so an edit only
+/// re-parses the chunks it touches. With the `serde` feature the cache can be
+/// [saved](Self::save) and [loaded](Self::load) between runs.
+///
+/// ```ignore
+/// let mut cache = ParseCache::load("target/md-cache.json", GRAMMAR_VERSION)
+///     .unwrap_or_else(|_| ParseCache::new(GRAMMAR_VERSION));
+/// for page in pages {
+///     let ast = cache.run_batch(&page.source, lexer_rules, parser_rules);
+///     render(&ast);
+/// }
+/// cache.prune_unused();
+/// cache.save("target/md-cache.json")?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParseCache<V> {
+    grammar: u64,
+    entries: HashMap<ContentHash, Entry<V>>,
+    stats: CacheStats,
+}
+
+#[derive(Debug, Clone)]
+struct Entry<V> {
+    input: String,
+    value: V,
+    used: bool,
+}
+
+impl<V> ParseCache<V> {
+    /// Creates an empty cache for the grammar with fingerprint `grammar`.
+    pub fn new(grammar: u64) -> Self {
+        Self {
+            grammar,
+            entries: HashMap::new(),
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Returns the fingerprint of the grammar whose results are cached.
+    pub fn grammar(&self) -> u64 {
+        self.grammar
+    }
+
+    /// Returns the cached value for `input`, counting a hit or a miss.
+    pub fn get(&mut self, input: &str) -> Option<&V> {
+        let entry = self
+            .entries
+            .get_mut(&ContentHash::of(input))
+            .filter(|entry| entry.input == input);
+        match entry {
+            Some(entry) => {
+                self.stats.hits += 1;
+                entry.used = true;
+                Some(&entry.value)
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Returns `true` if a value for `input` is cached, without counting a
+    /// lookup.
+    pub fn contains(&self, input: &str) -> bool {
+        self.entries
+            .get(&ContentHash::of(input))
+            .is_some_and(|entry| entry.input == input)
+    }
+
+    /// Caches `value` as the result for `input`.
+    pub fn insert(&mut self, input: &str, value: V) {
+        let entry = Entry {
+            input: input.to_string(),
+            value,
+            used: true,
+        };
+        self.entries.insert(ContentHash::of(input), entry);
+    }
+
+    /// Drops the entries that were not looked up or inserted since the cache
+    /// was created, loaded or last pruned, e.g. pages deleted or edited
+    /// since the previous run. Returns how many entries were dropped.
+    pub fn prune_unused(&mut self) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|_, entry| entry.used);
+        self.entries
+            .values_mut()
+            .for_each(|entry| entry.used = false);
+        before - self.entries.len()
+    }
+
+    /// Returns the number of cached inputs.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if nothing is cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all entries and resets the statistics.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.stats = CacheStats::default();
+    }
+
+    /// Returns the hit and miss counts.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+}
+
+impl<V: Clone> ParseCache<V> {
+    /// Returns the cached value for `input`, computing and caching it with
+    /// `compute` on a miss.
+    pub fn get_or_insert_with<F>(&mut self, input: &str, compute: F) -> V
+    where
+        F: FnOnce(&str) -> V,
+    {
+        if let Some(value) = self.get(input) {
+            return value.clone();
+        }
+        let value = compute(input);
+        self.insert(input, value.clone());
+        value
+    }
+
+    /// Looks up each chunk separately, computing only the missing ones.
+    ///
+    /// Values computed for a chunk only see that chunk, so positions in them
+    /// are relative to the chunk's start.
+    pub fn get_or_insert_chunks<'a, I, F>(&mut self, chunks: I, mut compute: F) -> Vec<V>
+    where
+        I: IntoIterator<Item = &'a str>,
+        F: FnMut(&str) -> V,
+    {
+        chunks
+            .into_iter()
+            .map(|chunk| self.get_or_insert_with(chunk, &mut compute))
+            .collect()
+    }
+}
+
+impl<Ast: AstNode> ParseCache<Vec<Ast>> {
+    /// Runs [`BatchPipeline::run`] on `input` unless its nodes are cached.
+    ///
+    /// The rules are passed as constructors so they are only built on a
+    /// miss.
+    pub fn run_batch<Tok, L, P>(&mut self, input: &str, lexer_rules: L, parser_rules: P) -> Vec<Ast>
+    where
+        Tok: LexToken,
+        L: FnOnce() -> Vec<Box<dyn LexingRule<LexDefaultContext, Tok>>>,
+        P: FnOnce() -> Vec<Box<dyn ParsingRule<ParseDefaultContext<Tok>, Tok, Ast>>>,
+    {
+        self.get_or_insert_with(input, |input| {
+            BatchPipeline::<Tok, Ast>::run(input, lexer_rules(), parser_rules())
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+mod persist {
+    use super::{ContentHash, Entry, ParseCache};
+    use serde::de::DeserializeOwned;
+    use serde::{Deserialize, Serialize};
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+
+    const VERSION: u32 = 2;
+
+    #[derive(Serialize)]
+    struct FileRef<'a, V> {
+        version: u32,
+        grammar: u64,
+        entries: Vec<(&'a str, &'a V)>,
+    }
+
+    #[derive(Deserialize)]
+    struct Header {
+        version: u32,
+        grammar: Option<u64>,
+    }
+
+    #[derive(Deserialize)]
+    struct File<V> {
+        entries: Vec<(String, V)>,
+    }
+
+    impl<V: Serialize> ParseCache<V> {
+        /// Writes the entries to `path` as JSON. The file is replaced
+        /// atomically, so a crash never leaves a half-written cache.
+        pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+            let path = path.as_ref();
+            let file = FileRef {
+                version: VERSION,
+                grammar: self.grammar,
+                entries: self
+                    .entries
+                    .values()
+                    .map(|entry| (entry.input.as_str(), &entry.value))
+                    .collect(),
+            };
+            let json = serde_json::to_vec(&file).map_err(io::Error::other)?;
+            let tmp = path.with_extension("tmp");
+            fs::write(&tmp, json)?;
+            fs::rename(tmp, path)
+        }
+    }
+
+    impl<V: DeserializeOwned> ParseCache<V> {
+        /// Reads a cache for `grammar` written by [`save`](Self::save). A
+        /// missing file yields an empty cache; a file from another cache
+        /// version, another grammar or with another value type is an
+        /// [`io::ErrorKind::InvalidData`] error.
+        pub fn load(path: impl AsRef<Path>, grammar: u64) -> io::Result<Self> {
+            let json = match fs::read(path) {
+                Ok(json) => json,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::new(grammar)),
+                Err(err) => return Err(err),
+            };
+            let invalid = |err| io::Error::new(io::ErrorKind::InvalidData, err);
+            let header: Header = serde_json::from_slice(&json).map_err(invalid)?;
+            if header.version != VERSION {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unsupported parse cache version {}", header.version),
+                ));
+            }
+            if header.grammar != Some(grammar) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "parse cache was written for another grammar",
+                ));
+            }
+            let file: File<V> = serde_json::from_slice(&json).map_err(invalid)?;
+            let mut cache = Self::new(grammar);
+            for (input, value) in file.entries {
+                let entry = Entry {
+                    input,
+                    value,
+                    used: false,
+                };
+                cache.entries.insert(ContentHash::of(&entry.input), entry);
+            }
+            Ok(cache)
+        }
+    }
+}
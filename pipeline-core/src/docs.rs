@@ -0,0 +1,163 @@
+// Doc comments attached to the nodes that follow them
+use lexer_framework::{ChannelTokens, LexToken};
+use parser_framework::{AstId, AstNode, HasAstId};
+use std::collections::{HashMap, HashSet};
+
+/// How doc comments are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocMarker {
+    /// A line comment starting with the prefix, e.g. `///`, but not with
+    /// more of its last character, e.g. `////`.
+    Line(&'static str),
+    /// A block comment between the two delimiters, e.g. `/**` and `*/`.
+    Block(&'static str, &'static str),
+}
+
+impl DocMarker {
+    /// Returns the comment's text without its markers, or `None` if the
+    /// comment is not written this way.
+    fn strip<'a>(&self, comment: &'a str) -> Option<Vec<&'a str>> {
+        match *self {
+            DocMarker::Line(prefix) => {
+                let line = comment.strip_prefix(prefix)?;
+                if prefix.chars().last().is_some_and(|ch| line.starts_with(ch)) {
+                    return None;
+                }
+                let line = line.trim_end();
+                Some(vec![line.strip_prefix(' ').unwrap_or(line)])
+            }
+            DocMarker::Block(open, close) => {
+                if comment.len() < open.len() + close.len() {
+                    return None;
+                }
+                let body = comment.strip_prefix(open)?.strip_suffix(close)?;
+                let mut lines: Vec<&str> = body
+                    .lines()
+                    .map(|line| {
+                        let line = line.trim();
+                        let line = line.strip_prefix('*').unwrap_or(line);
+                        line.strip_prefix(' ').unwrap_or(line)
+                    })
+                    .collect();
+                while lines.first().is_some_and(|line| line.is_empty()) {
+                    lines.remove(0);
+                }
+                while lines.last().is_some_and(|line| line.is_empty()) {
+                    lines.pop();
+                }
+                Some(lines)
+            }
+        }
+    }
+}
+
+/// Finds doc comments among the off-channel tokens of a run and attaches
+/// each to the node that follows it.
+///
+/// ```ignore
+/// let (nodes, tokens) = BatchPipeline::run_with_channels(source, lexer_rules(), parser_rules());
+/// let docs = DocExtractor::new(Token::comment_text)
+///     .with_marker(DocMarker::Line("///"))
+///     .with_marker(DocMarker::Block("/**", "*/"))
+///     .extract(&tokens, &nodes);
+/// println!("{}", docs.docs_for(id).unwrap_or_default());
+/// ```
+pub struct DocExtractor<Tok> {
+    comment_text: fn(&Tok) -> Option<&str>,
+    markers: Vec<DocMarker>,
+}
+
+impl<Tok> DocExtractor<Tok> {
+    /// Reads comment text with `comment_text`, which returns `None` for
+    /// tokens that are not comments. Recognizes no doc comments until a
+    /// marker is added.
+    pub fn new(comment_text: fn(&Tok) -> Option<&str>) -> Self {
+        Self {
+            comment_text,
+            markers: Vec::new(),
+        }
+    }
+
+    /// Recognizes comments written with `marker` as doc comments.
+    pub fn with_marker(mut self, marker: DocMarker) -> Self {
+        self.markers.push(marker);
+        self
+    }
+
+    /// Returns the doc comment text of `token`, one entry per line.
+    fn doc_lines<'a>(&self, token: &'a Tok) -> Option<Vec<&'a str>> {
+        let comment = (self.comment_text)(token)?;
+        self.markers.iter().find_map(|marker| marker.strip(comment))
+    }
+}
+
+impl<Tok: LexToken> DocExtractor<Tok> {
+    /// Attaches the doc comments in `tokens` to `nodes`.
+    ///
+    /// A node gets the doc comments between its first token and the token
+    /// before it. Nodes are visited in the order given, so when several
+    /// start at the same token, e.g. a declaration and its name, the first
+    /// one gets the docs; pass nested nodes parents first. Nodes without
+    /// an id or position get none.
+    pub fn extract<'a, Ast>(
+        &self,
+        tokens: &ChannelTokens<Tok>,
+        nodes: impl IntoIterator<Item = &'a Ast>,
+    ) -> DocComments
+    where
+        Ast: AstNode + HasAstId + 'a,
+    {
+        let mut docs = HashMap::new();
+        let mut claimed = HashSet::new();
+        for node in nodes {
+            let (Some(id), Some(position)) = (node.ast_id(), node.position()) else {
+                continue;
+            };
+            let index = tokens.default_index_at(position);
+            if claimed.contains(&index) {
+                continue;
+            }
+            let lines: Vec<&str> = tokens
+                .hidden_before(index)
+                .iter()
+                .filter_map(|(_, token)| self.doc_lines(token))
+                .flatten()
+                .collect();
+            if !lines.is_empty() {
+                claimed.insert(index);
+                docs.insert(id, lines.join("\n"));
+            }
+        }
+        DocComments { docs }
+    }
+}
+
+/// Doc comments by the id of the node they document, as found by
+/// [`DocExtractor::extract`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocComments {
+    docs: HashMap<AstId, String>,
+}
+
+impl DocComments {
+    /// Returns the docs of the node with `id`, lines joined by `\n`.
+    pub fn docs_for(&self, id: AstId) -> Option<&str> {
+        self.docs.get(&id).map(String::as_str)
+    }
+
+    /// Returns the number of documented nodes.
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    /// Returns true if no node is documented.
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// Iterates over the documented nodes and their docs, in no particular
+    /// order.
+    pub fn iter(&self) -> impl Iterator<Item = (AstId, &str)> {
+        self.docs.iter().map(|(id, doc)| (*id, doc.as_str()))
+    }
+}
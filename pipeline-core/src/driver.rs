@@ -0,0 +1,190 @@
+// Reusable signal routing for streaming controllers
+use common_framework::{Inbound, Outbound, Progress, StreamingSignal};
+use lexer_framework::streaming::TokenProducer;
+use parser_framework::streaming::TokenConsumer;
+
+/// The signal-routing loop of [`StreamingPipeline`](crate::StreamingPipeline),
+/// for custom controllers that want to reuse it.
+///
+/// Implementors hand out their two stages and decide what happens to the
+/// nodes; [`drive`](Self::drive) then runs the stages to completion,
+/// calling the hooks along the way. Override the other hooks to change how
+/// tokens are requested, to count them, or to handle blocked stages:
+///
+/// ```ignore
+/// struct Prioritized { lexer: L, parser: P, urgent: Vec<Ast>, rest: Vec<Ast> }
+///
+/// impl PipelineDriver<Tok, Ast> for Prioritized {
+///     type Lexer = L;
+///     type Parser = P;
+///     fn lexer_mut(&mut self) -> &mut L { &mut self.lexer }
+///     fn parser_mut(&mut self) -> &mut P { &mut self.parser }
+///     fn on_produced(&mut self, nodes: Vec<Ast>) {
+///         for node in nodes {
+///             if node.is_urgent() { self.urgent.push(node) } else { self.rest.push(node) }
+///         }
+///     }
+/// }
+/// ```
+pub trait PipelineDriver<Tok, Ast> {
+    /// The stage producing tokens.
+    type Lexer: TokenProducer<Tok> + Inbound<Tok, Ast> + Outbound<Tok, Ast>;
+    /// The stage consuming tokens and producing nodes.
+    type Parser: TokenConsumer<Tok, Ast> + Inbound<Tok, Ast> + Outbound<Tok, Ast>;
+
+    /// Returns the lexer stage.
+    fn lexer_mut(&mut self) -> &mut Self::Lexer;
+
+    /// Returns the parser stage.
+    fn parser_mut(&mut self) -> &mut Self::Parser;
+
+    /// Receives nodes as the parser produces them, including the ones
+    /// drained when the input ends.
+    fn on_produced(&mut self, nodes: Vec<Ast>);
+
+    /// Returns the request to send the lexer when the parser needs at least
+    /// `min_needed` more tokens. The default asks for exactly that many,
+    /// one signal per token.
+    fn on_need_token(&mut self, min_needed: usize) -> StreamingSignal<Tok, Ast> {
+        StreamingSignal::RequestToken(min_needed)
+    }
+
+    /// Called after `count` tokens went from the lexer to the parser.
+    fn on_supplied(&mut self, count: usize) {
+        let _ = count;
+    }
+
+    /// Decides whether to try again after a stage reported `Blocked` for
+    /// the `attempt`-th time in a row (counting from 1). Returning `false`,
+    /// the default, aborts both stages.
+    fn on_blocked(&mut self, reason: &str, attempt: usize) -> bool {
+        let _ = (reason, attempt);
+        false
+    }
+
+    /// Decides what happens after the lexer signalled `NeedInput`. The
+    /// default ends the input, so lexers fed by hand lex what they were
+    /// given.
+    fn on_need_input(&mut self) -> InputAnswer {
+        InputAnswer::End
+    }
+
+    /// Receives the lexer's `Progress` reports.
+    fn on_progress(&mut self, progress: Progress) {
+        let _ = progress;
+    }
+
+    /// Routes signals between the stages until the parser finishes, the
+    /// input ends, a stage aborts, or [`on_need_input`](Self::on_need_input)
+    /// pauses. A paused run resumes where it stopped on the next call.
+    fn drive(&mut self)
+    where
+        Self: Sized,
+    {
+        let mut blocked_attempts = 0;
+
+        while let Some(signal) = self.parser_mut().next_signal() {
+            match signal {
+                StreamingSignal::Produced(nodes) => {
+                    blocked_attempts = 0;
+                    self.on_produced(nodes);
+                }
+                StreamingSignal::NeedToken(min_needed) => {
+                    let request = self.on_need_token(min_needed);
+                    self.lexer_mut().handle_signal(request);
+                    let token_signal = loop {
+                        match self.lexer_mut().next_signal() {
+                            Some(StreamingSignal::Progress(progress)) => self.on_progress(progress),
+                            Some(StreamingSignal::NeedInput) => {
+                                let answer = match self.on_need_input() {
+                                    InputAnswer::Supply(text) => StreamingSignal::SupplyInput(text),
+                                    InputAnswer::End => StreamingSignal::EndOfInput,
+                                    InputAnswer::Pause => return,
+                                };
+                                self.lexer_mut().handle_signal(answer);
+                            }
+                            signal => break signal,
+                        }
+                    };
+                    match token_signal {
+                        Some(StreamingSignal::SupplyToken(token)) => {
+                            blocked_attempts = 0;
+                            self.parser_mut()
+                                .handle_signal(StreamingSignal::SupplyToken(token));
+                            self.on_supplied(1);
+                        }
+                        Some(StreamingSignal::SupplyTokens(tokens)) => {
+                            blocked_attempts = 0;
+                            let count = tokens.len();
+                            self.parser_mut()
+                                .handle_signal(StreamingSignal::SupplyTokens(tokens));
+                            self.on_supplied(count);
+                        }
+                        Some(StreamingSignal::EndOfInput) | None => {
+                            self.parser_mut().handle_signal(StreamingSignal::EndOfInput);
+                            let nodes = self.parser_mut().finish();
+                            self.on_produced(nodes);
+                            break;
+                        }
+                        Some(StreamingSignal::Blocked(reason)) => {
+                            blocked_attempts += 1;
+                            if !self.on_blocked(&reason, blocked_attempts) {
+                                abort_stages(self, reason);
+                                break;
+                            }
+                        }
+                        Some(StreamingSignal::Abort(reason)) => {
+                            abort_stages(self, reason);
+                            break;
+                        }
+                        Some(_) => {}
+                    }
+                }
+                StreamingSignal::Feedback(feedback) => {
+                    self.lexer_mut()
+                        .handle_signal(StreamingSignal::Feedback(feedback));
+                }
+                StreamingSignal::Finished(nodes) => {
+                    self.on_produced(nodes);
+                    break;
+                }
+                StreamingSignal::Blocked(reason) => {
+                    blocked_attempts += 1;
+                    if !self.on_blocked(&reason, blocked_attempts) {
+                        abort_stages(self, reason);
+                        break;
+                    }
+                }
+                StreamingSignal::Abort(reason) => {
+                    abort_stages(self, reason);
+                    break;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// What a [`PipelineDriver`] does when the lexer needs more input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputAnswer {
+    /// Push this text to the lexer and carry on.
+    Supply(String),
+    /// End the input; the lexer lexes what it holds and the parser drains.
+    End,
+    /// Return from [`drive`](PipelineDriver::drive) until the caller has
+    /// pushed more input to the lexer.
+    Pause,
+}
+
+fn abort_stages<D, Tok, Ast>(driver: &mut D, reason: String)
+where
+    D: PipelineDriver<Tok, Ast>,
+{
+    driver
+        .parser_mut()
+        .handle_signal(StreamingSignal::Abort(reason.clone()));
+    driver
+        .lexer_mut()
+        .handle_signal(StreamingSignal::Abort(reason));
+}
@@ -0,0 +1,156 @@
+// Embedded language regions (e.g. JS inside HTML, SQL inside strings)
+use common_framework::{Position, TextSlice};
+use lexer_framework::{DefaultContext as LexDefaultContext, LexToken, Lexer, LexingRule};
+use parser_framework::{AstNode, DefaultContext as ParseDefaultContext, Parser, ParsingRule};
+use std::collections::HashMap;
+
+/// A span of the input written in another language.
+///
+/// Parser rules declare one by returning a placeholder node that carries the
+/// region (see [`EmbedHost::region`]). [`EmbeddedLanguages::resolve`] later
+/// hands the text to the pipeline registered for `language` and replaces the
+/// placeholder with an embedded node holding the results.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddedRegion {
+    /// The language id the region is registered under.
+    pub language: String,
+    /// The region's text, as a slice of the outer input.
+    pub text: TextSlice,
+}
+
+impl EmbeddedRegion {
+    /// Creates a region of `text` written in `language`.
+    pub fn new<S: Into<String>>(language: S, text: TextSlice) -> Self {
+        Self {
+            language: language.into(),
+            text,
+        }
+    }
+
+    /// Returns where the region starts in the outer input.
+    pub fn position(&self) -> Position {
+        self.text.start_position()
+    }
+}
+
+/// AST nodes that can declare embedded regions and hold their parsed
+/// contents.
+pub trait EmbedHost: AstNode + Sized {
+    /// Returns the region if this node is a placeholder still waiting to be
+    /// resolved.
+    fn region(&self) -> Option<&EmbeddedRegion>;
+
+    /// Builds the node that replaces a placeholder once its region has been
+    /// parsed into `nodes`.
+    fn embedded(region: EmbeddedRegion, nodes: Vec<Self>) -> Self;
+
+    /// Calls `f` on every direct child, so placeholders nested inside other
+    /// nodes are resolved too. The default visits nothing.
+    fn for_each_child_mut(&mut self, f: &mut dyn FnMut(&mut Self)) {
+        let _ = f;
+    }
+}
+
+type EmbeddedPipeline<Ast> = Box<dyn Fn(&EmbeddedRegion) -> Vec<Ast>>;
+
+/// Secondary pipelines for embedded regions, keyed by language id.
+pub struct EmbeddedLanguages<Ast> {
+    pipelines: HashMap<String, EmbeddedPipeline<Ast>>,
+}
+
+impl<Ast> EmbeddedLanguages<Ast>
+where
+    Ast: EmbedHost,
+{
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self {
+            pipelines: HashMap::new(),
+        }
+    }
+
+    /// Registers a function that parses regions of `language` into outer
+    /// AST nodes, replacing any previous registration.
+    pub fn register<F>(&mut self, language: &str, pipeline: F) -> &mut Self
+    where
+        F: Fn(&EmbeddedRegion) -> Vec<Ast> + 'static,
+    {
+        self.pipelines
+            .insert(language.to_string(), Box::new(pipeline));
+        self
+    }
+
+    /// Registers a lexer/parser pair for `language`.
+    ///
+    /// Rules are stateful, so fresh rule sets are built for every region.
+    /// The region is lexed with `Lexer::sublex`, so token positions point into
+    /// the outer input, parsed with `Parser::reparse_tokens`, and each node is
+    /// converted into the outer AST type with `convert`.
+    pub fn register_pipeline<Tok, Inner, L, P, C>(
+        &mut self,
+        language: &str,
+        lexer_rules: L,
+        parser_rules: P,
+        convert: C,
+    ) -> &mut Self
+    where
+        Tok: LexToken + 'static,
+        Inner: AstNode + 'static,
+        L: Fn() -> Vec<Box<dyn LexingRule<LexDefaultContext, Tok>>> + 'static,
+        P: Fn() -> Vec<Box<dyn ParsingRule<ParseDefaultContext<Tok>, Tok, Inner>>> + 'static,
+        C: Fn(Inner) -> Ast + 'static,
+    {
+        self.register(language, move |region| {
+            let tokens = Lexer::sublex(region.text.clone(), lexer_rules());
+            Parser::reparse_tokens(tokens, parser_rules())
+                .into_iter()
+                .map(&convert)
+                .collect()
+        })
+    }
+
+    /// Returns whether a pipeline is registered for `language`.
+    pub fn contains(&self, language: &str) -> bool {
+        self.pipelines.contains_key(language)
+    }
+
+    /// Replaces every placeholder in `nodes` (including nested ones and ones
+    /// produced by embedded pipelines) with an embedded node.
+    ///
+    /// Returns the regions whose language has no registered pipeline; their
+    /// placeholders are left in place.
+    pub fn resolve(&self, nodes: &mut [Ast]) -> Vec<EmbeddedRegion> {
+        let mut unresolved = Vec::new();
+        for node in nodes {
+            self.resolve_node(node, &mut unresolved);
+        }
+        unresolved
+    }
+
+    fn resolve_node(&self, node: &mut Ast, unresolved: &mut Vec<EmbeddedRegion>) {
+        if let Some(region) = node.region() {
+            match self.pipelines.get(&region.language) {
+                Some(pipeline) => {
+                    let region = region.clone();
+                    let mut inner = pipeline(&region);
+                    for child in &mut inner {
+                        self.resolve_node(child, unresolved);
+                    }
+                    *node = Ast::embedded(region, inner);
+                }
+                None => unresolved.push(region.clone()),
+            }
+            return;
+        }
+        node.for_each_child_mut(&mut |child| self.resolve_node(child, unresolved));
+    }
+}
+
+impl<Ast> Default for EmbeddedLanguages<Ast>
+where
+    Ast: EmbedHost,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
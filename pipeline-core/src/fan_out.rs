@@ -0,0 +1,173 @@
+// Broadcasting one token stream to several parsers
+use common_framework::{Inbound, Outbound, StreamingSignal};
+use lexer_framework::streaming::TokenProducer;
+use parser_framework::streaming::TokenConsumer;
+use std::marker::PhantomData;
+
+/// Drives one lexer and several parsers in a single pass, broadcasting
+/// every token to all of them and collecting each parser's nodes
+/// separately.
+///
+/// Useful when different views of the same input are needed at once, e.g.
+/// a cheap outline extractor next to a full AST builder. The consumers
+/// share one `Ast` type; give them an enum if their node types differ.
+///
+/// Each parser runs until it needs a token, and the lexer is only asked
+/// for the next token once every parser still running does. Feedback
+/// from any parser is forwarded to the shared lexer, so parsers that send
+/// feedback see the token stream it shapes for all of them.
+pub struct FanOutPipeline<L, P, Tok, Ast>
+where
+    L: TokenProducer<Tok> + Inbound<Tok, Ast> + Outbound<Tok, Ast>,
+    P: TokenConsumer<Tok, Ast> + Inbound<Tok, Ast> + Outbound<Tok, Ast>,
+{
+    lexer: L,
+    consumers: Vec<P>,
+    _marker: PhantomData<(Tok, Ast)>,
+}
+
+impl<L, P, Tok, Ast> FanOutPipeline<L, P, Tok, Ast>
+where
+    L: TokenProducer<Tok> + Inbound<Tok, Ast> + Outbound<Tok, Ast>,
+    P: TokenConsumer<Tok, Ast> + Inbound<Tok, Ast> + Outbound<Tok, Ast>,
+    Tok: Clone,
+{
+    /// Creates a pipeline with no consumers yet.
+    pub fn new(lexer: L) -> Self {
+        Self {
+            lexer,
+            consumers: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Adds a consumer; its nodes are reported under the next index.
+    pub fn with_consumer(mut self, consumer: P) -> Self {
+        self.add_consumer(consumer);
+        self
+    }
+
+    /// Adds a consumer and returns the index its nodes are reported under.
+    pub fn add_consumer(&mut self, consumer: P) -> usize {
+        self.consumers.push(consumer);
+        self.consumers.len() - 1
+    }
+
+    /// Returns a reference to the lexer stage.
+    pub fn lexer(&self) -> &L {
+        &self.lexer
+    }
+
+    /// Returns the consumers in the order they were added.
+    pub fn consumers(&self) -> &[P] {
+        &self.consumers
+    }
+
+    /// Runs the pipeline until every consumer finishes, returning each
+    /// consumer's nodes at its index.
+    pub fn run(mut self) -> Vec<Vec<Ast>> {
+        let mut results: Vec<Vec<Ast>> = self.consumers.iter().map(|_| Vec::new()).collect();
+        self.run_with(|index, node| results[index].push(node));
+        results
+    }
+
+    /// Runs the pipeline until every consumer finishes, handing each node
+    /// to `on_node` along with the index of the consumer that produced it.
+    pub fn run_with<F>(&mut self, mut on_node: F)
+    where
+        F: FnMut(usize, Ast),
+    {
+        let mut done = vec![false; self.consumers.len()];
+
+        loop {
+            for (index, consumer) in self.consumers.iter_mut().enumerate() {
+                if done[index] {
+                    continue;
+                }
+                // Let the consumer work until it asks for another token.
+                loop {
+                    match consumer.next_signal() {
+                        Some(StreamingSignal::Produced(nodes)) => {
+                            nodes.into_iter().for_each(|node| on_node(index, node));
+                        }
+                        Some(StreamingSignal::Feedback(feedback)) => {
+                            self.lexer
+                                .handle_signal(StreamingSignal::Feedback(feedback));
+                        }
+                        Some(StreamingSignal::Finished(nodes)) => {
+                            nodes.into_iter().for_each(|node| on_node(index, node));
+                            done[index] = true;
+                            break;
+                        }
+                        Some(StreamingSignal::Blocked(reason))
+                        | Some(StreamingSignal::Abort(reason)) => {
+                            // Only this consumer gives up; the others go on.
+                            consumer.handle_signal(StreamingSignal::Abort(reason));
+                            done[index] = true;
+                            break;
+                        }
+                        None => {
+                            done[index] = true;
+                            break;
+                        }
+                        Some(_) => break,
+                    }
+                }
+            }
+
+            if done.iter().all(|&finished| finished) {
+                break;
+            }
+
+            self.lexer.handle_signal(StreamingSignal::RequestToken(1));
+            let token_signal = loop {
+                match self.lexer.next_signal() {
+                    Some(StreamingSignal::Progress(_)) => {}
+                    signal => break signal,
+                }
+            };
+            match token_signal {
+                Some(StreamingSignal::SupplyToken(token)) => {
+                    self.broadcast(&done, || StreamingSignal::SupplyToken(token.clone()));
+                }
+                Some(StreamingSignal::SupplyTokens(tokens)) => {
+                    self.broadcast(&done, || StreamingSignal::SupplyTokens(tokens.clone()));
+                }
+                Some(StreamingSignal::EndOfInput) | None => {
+                    for (index, consumer) in self.consumers.iter_mut().enumerate() {
+                        if !done[index] {
+                            consumer.handle_signal(StreamingSignal::EndOfInput);
+                            consumer
+                                .finish()
+                                .into_iter()
+                                .for_each(|node| on_node(index, node));
+                        }
+                    }
+                    break;
+                }
+                // There is no input source to answer with, so the input ends.
+                Some(StreamingSignal::NeedInput) => {
+                    self.lexer.handle_signal(StreamingSignal::EndOfInput);
+                }
+                Some(StreamingSignal::Blocked(reason)) | Some(StreamingSignal::Abort(reason)) => {
+                    self.broadcast(&done, || StreamingSignal::Abort(reason.clone()));
+                    self.lexer.handle_signal(StreamingSignal::Abort(reason));
+                    break;
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    /// Sends a signal built by `signal` to every consumer still running.
+    fn broadcast<S>(&mut self, done: &[bool], mut signal: S)
+    where
+        S: FnMut() -> StreamingSignal<Tok, Ast>,
+    {
+        for (index, consumer) in self.consumers.iter_mut().enumerate() {
+            if !done[index] {
+                consumer.handle_signal(signal());
+            }
+        }
+    }
+}
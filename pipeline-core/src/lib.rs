@@ -1,5 +1,47 @@
+#[cfg(feature = "streaming")]
+pub mod blocked;
+#[cfg(feature = "streaming")]
+pub mod builder;
+pub mod cache;
+pub mod docs;
+#[cfg(feature = "streaming")]
+pub mod driver;
+pub mod embedded;
+#[cfg(feature = "streaming")]
+pub mod fan_out;
+pub mod metadata;
+pub mod project;
+pub mod timing;
+#[cfg(feature = "watch")]
+pub mod watch;
+
+#[cfg(feature = "streaming")]
+pub use blocked::{BlockedCallback, BlockedPolicy};
+#[cfg(feature = "streaming")]
+pub use builder::{
+    BuiltLexer, BuiltPipeline, PipelineBuilder, StreamingLexRules, StreamingParseRules,
+};
+pub use cache::{CacheStats, ContentHash, ParseCache};
+pub use docs::{DocComments, DocExtractor, DocMarker};
+#[cfg(feature = "streaming")]
+pub use driver::{InputAnswer, PipelineDriver};
+pub use embedded::{EmbedHost, EmbeddedLanguages, EmbeddedRegion};
+#[cfg(feature = "streaming")]
+pub use fan_out::FanOutPipeline;
+pub use metadata::{CrateVersions, PipelineOutput, RuleFingerprint, RunMetadata};
+pub use project::{Module, Project, ProjectError, ProjectPipeline};
+pub use timing::StageTimings;
+#[cfg(feature = "watch")]
+pub use watch::{DirWatcher, FileChange, WatchEvent};
+
+/// This crate's version, as recorded in pipeline run metadata.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
 // Non-streaming batch pipeline
-use lexer_framework::{DefaultContext as LexDefaultContext, Lexer, LexingRule};
+use common_framework::{Diagnostic, Diagnostics};
+use lexer_framework::{
+    ChannelTokens, DefaultContext as LexDefaultContext, LexToken, Lexer, LexingRule,
+};
 use parser_framework::{AstNode, DefaultContext as ParseDefaultContext, Parser, ParsingRule};
 
 /// A batch pipeline that processes input in two stages:
@@ -37,15 +79,162 @@ where
         input: S,
         lexer_rules: Vec<Box<dyn LexingRule<LexDefaultContext, Tok>>>,
         parser_rules: Vec<Box<dyn ParsingRule<ParseDefaultContext<Tok>, Tok, Ast>>>,
-    ) -> Vec<Ast> {
+    ) -> Vec<Ast>
+    where
+        Tok: LexToken,
+    {
         // Stage 1: Tokenize entire input
         let mut lexer = Lexer::from_str(input, lexer_rules);
-        let tokens: Vec<Tok> = lexer.tokenize();
+        let tokens: Vec<Tok> = lexer.tokenize_default();
 
         // Stage 2: Parse all tokens
-        let mut parser =
-            Parser::<ParseDefaultContext<Tok>, Tok, Ast>::from_tokens(tokens, parser_rules);
-        parser.parse()
+        Self::parse_tokens(tokens, parser_rules)
+    }
+
+    /// Like [`run`](Self::run), also returning the tokens on every channel.
+    ///
+    /// The parser reads only [`Channel::DEFAULT`](common_framework::Channel::DEFAULT);
+    /// comments and other tokens rules route elsewhere can then be found
+    /// around a node with [`ChannelTokens::hidden_before`] and
+    /// [`ChannelTokens::hidden_after`].
+    pub fn run_with_channels<S: Into<String>>(
+        input: S,
+        lexer_rules: Vec<Box<dyn LexingRule<LexDefaultContext, Tok>>>,
+        parser_rules: Vec<Box<dyn ParsingRule<ParseDefaultContext<Tok>, Tok, Ast>>>,
+    ) -> (Vec<Ast>, ChannelTokens<Tok>)
+    where
+        Tok: LexToken,
+    {
+        let channels = Lexer::from_str(input, lexer_rules).tokenize_channels();
+        let tokens = channels.default_tokens().cloned().collect();
+        (Self::parse_tokens(tokens, parser_rules), channels)
+    }
+
+    /// Like [`run`](Self::run), dropping the tokens `keep` rejects before
+    /// parsing and timing each stage. Pass `|_| true` to keep every token.
+    ///
+    /// ```ignore
+    /// let (nodes, timings) = BatchPipeline::run_timed(
+    ///     source,
+    ///     lexer_rules(),
+    ///     |token: &Token| !token.is_whitespace(),
+    ///     parser_rules(),
+    /// );
+    /// print!("{timings}");
+    /// ```
+    pub fn run_timed<S, F>(
+        input: S,
+        lexer_rules: Vec<Box<dyn LexingRule<LexDefaultContext, Tok>>>,
+        mut keep: F,
+        parser_rules: Vec<Box<dyn ParsingRule<ParseDefaultContext<Tok>, Tok, Ast>>>,
+    ) -> (Vec<Ast>, StageTimings)
+    where
+        S: Into<String>,
+        F: FnMut(&Tok) -> bool,
+        Tok: LexToken,
+    {
+        let input = input.into();
+        let (tokens, lex) = timing::time_stage(
+            input.len(),
+            || Lexer::from_str(input, lexer_rules).tokenize_default(),
+            Vec::len,
+        );
+        let (tokens, filter) = timing::time_stage(
+            tokens.len(),
+            || {
+                let mut tokens = tokens;
+                tokens.retain(|token| keep(token));
+                tokens
+            },
+            Vec::len,
+        );
+        let (nodes, parse) = timing::time_stage(
+            tokens.len(),
+            || Self::parse_tokens(tokens, parser_rules),
+            Vec::len,
+        );
+        (nodes, StageTimings { lex, filter, parse })
+    }
+
+    /// Parses `tokens`, tracking positions through `LexToken::position`.
+    fn parse_tokens(
+        tokens: Vec<Tok>,
+        parser_rules: Vec<Box<dyn ParsingRule<ParseDefaultContext<Tok>, Tok, Ast>>>,
+    ) -> Vec<Ast>
+    where
+        Tok: LexToken,
+    {
+        let context = ParseDefaultContext::new(tokens).with_lex_positions();
+        Parser::new(context, parser_rules).parse()
+    }
+
+    /// Like [`run`](Self::run), also returning the diagnostics the rules
+    /// and stages reported: the lexer's first, then the parser's.
+    pub fn run_with_diagnostics<S: Into<String>>(
+        input: S,
+        lexer_rules: Vec<Box<dyn LexingRule<LexDefaultContext, Tok>>>,
+        parser_rules: Vec<Box<dyn ParsingRule<ParseDefaultContext<Tok>, Tok, Ast>>>,
+    ) -> (Vec<Ast>, Vec<Diagnostic>)
+    where
+        Tok: LexToken,
+    {
+        let diagnostics = Diagnostics::collect();
+        let nodes = Self::run_with_sink(input, lexer_rules, parser_rules, &diagnostics);
+        (nodes, diagnostics.take())
+    }
+
+    /// Like [`run_with_diagnostics`](Self::run_with_diagnostics), also
+    /// recording the [`RunMetadata`] needed to triage a result reported
+    /// from another machine.
+    pub fn run_with_metadata<S: Into<String>>(
+        input: S,
+        lexer_rules: Vec<Box<dyn LexingRule<LexDefaultContext, Tok>>>,
+        parser_rules: Vec<Box<dyn ParsingRule<ParseDefaultContext<Tok>, Tok, Ast>>>,
+    ) -> PipelineOutput<Ast>
+    where
+        Tok: LexToken,
+    {
+        let input = input.into();
+        let metadata = RunMetadata::new(
+            &input,
+            RuleFingerprint::of(
+                lexer_rules
+                    .iter()
+                    .map(|rule| (rule.name(), rule.priority())),
+            ),
+            RuleFingerprint::of(
+                parser_rules
+                    .iter()
+                    .map(|rule| (rule.name(), rule.priority())),
+            ),
+        )
+        .with_config("pipeline", "batch");
+        let (nodes, diagnostics) = Self::run_with_diagnostics(input, lexer_rules, parser_rules);
+        PipelineOutput {
+            nodes,
+            diagnostics,
+            metadata,
+        }
+    }
+
+    /// Like [`run`](Self::run), sending the diagnostics of both stages to
+    /// `diagnostics`, e.g. to print them as they arrive or to cap them.
+    pub fn run_with_sink<S: Into<String>>(
+        input: S,
+        lexer_rules: Vec<Box<dyn LexingRule<LexDefaultContext, Tok>>>,
+        parser_rules: Vec<Box<dyn ParsingRule<ParseDefaultContext<Tok>, Tok, Ast>>>,
+        diagnostics: &Diagnostics,
+    ) -> Vec<Ast>
+    where
+        Tok: LexToken,
+    {
+        let mut lexer = Lexer::from_str(input, lexer_rules).with_diagnostics(diagnostics.clone());
+        let tokens: Vec<Tok> = lexer.tokenize_default();
+
+        let context = ParseDefaultContext::new(tokens).with_lex_positions();
+        Parser::new(context, parser_rules)
+            .with_diagnostics(diagnostics.clone())
+            .parse()
     }
 
     /// Runs the pipeline with a pre-created lexer, extracting tokens and creating a parser.
@@ -58,14 +247,13 @@ where
     ) -> Vec<Ast>
     where
         LCtx: lexer_framework::LexContext,
+        Tok: LexToken,
     {
         // Stage 1: Tokenize entire input
-        let tokens: Vec<Tok> = lexer.tokenize();
+        let tokens: Vec<Tok> = lexer.tokenize_default();
 
         // Stage 2: Create parser from tokens and parse
-        let mut parser =
-            Parser::<ParseDefaultContext<Tok>, Tok, Ast>::from_tokens(tokens, parser_rules);
-        parser.parse()
+        Self::parse_tokens(tokens, parser_rules)
     }
 
     /// Runs the pipeline with a pre-created lexer and a custom parser builder closure.
@@ -77,12 +265,33 @@ where
         F: FnOnce(Vec<Tok>) -> Parser<ParseDefaultContext<Tok>, Tok, Ast>,
     {
         // Stage 1: Tokenize entire input
-        let tokens: Vec<Tok> = lexer.tokenize();
+        let tokens: Vec<Tok> = lexer.tokenize_default();
 
         // Stage 2: Build parser and parse
         let mut parser = parser_builder(tokens);
         parser.parse()
     }
+
+    /// Like [`run`](Self::run), then resolves embedded regions declared by
+    /// parser rules with the pipelines registered in `languages`.
+    ///
+    /// Also returns the regions whose language has no registered pipeline,
+    /// as [`EmbeddedLanguages::resolve`] does; their placeholders are left
+    /// in the output.
+    pub fn run_embedded<S: Into<String>>(
+        input: S,
+        lexer_rules: Vec<Box<dyn LexingRule<LexDefaultContext, Tok>>>,
+        parser_rules: Vec<Box<dyn ParsingRule<ParseDefaultContext<Tok>, Tok, Ast>>>,
+        languages: &EmbeddedLanguages<Ast>,
+    ) -> (Vec<Ast>, Vec<EmbeddedRegion>)
+    where
+        Tok: LexToken,
+        Ast: EmbedHost,
+    {
+        let mut nodes = Self::run(input, lexer_rules, parser_rules);
+        let unresolved = languages.resolve(&mut nodes);
+        (nodes, unresolved)
+    }
 }
 
 impl<Tok, Ast> Default for BatchPipeline<Tok, Ast>
@@ -97,10 +306,12 @@ where
 
 // Streaming pipeline (only available with streaming feature)
 #[cfg(feature = "streaming")]
-use common_framework::{Inbound, Outbound, StreamingSignal};
+use common_framework::{Inbound, Outbound, Progress, StreamingSignal};
 #[cfg(feature = "streaming")]
 use lexer_framework::streaming::TokenProducer;
 #[cfg(feature = "streaming")]
+use lexer_framework::InputProducer;
+#[cfg(feature = "streaming")]
 use parser_framework::streaming::TokenConsumer;
 
 /// Drives a lexer and parser in lockstep, routing messages and tokens between
@@ -116,6 +327,9 @@ where
 {
     lexer: L,
     parser: P,
+    batch_size: usize,
+    on_blocked: BlockedPolicy,
+    input: Option<Box<dyn InputProducer>>,
     _marker: std::marker::PhantomData<(Tok, Ast)>,
 }
 
@@ -129,66 +343,249 @@ where
         Self {
             lexer,
             parser,
+            batch_size: 1,
+            on_blocked: BlockedPolicy::Abort,
+            input: None,
             _marker: std::marker::PhantomData,
         }
     }
 
+    /// Moves tokens from the lexer to the parser in batches of up to `n`
+    /// (`RequestTokens` / `SupplyTokens`) instead of one signal per token.
+    ///
+    /// Larger batches cut per-token overhead, but the lexer then runs up to
+    /// `n` tokens ahead of the parser, so parser feedback reaches it later.
+    /// Keep the default of 1 when lexer rules depend on feedback.
+    pub fn with_batch_size(mut self, n: usize) -> Self {
+        self.batch_size = n.max(1);
+        self
+    }
+
+    /// Sets what happens when a stage reports `Blocked`; by default the
+    /// run is aborted. See [`BlockedPolicy`].
+    pub fn on_blocked(mut self, policy: BlockedPolicy) -> Self {
+        self.on_blocked = policy;
+        self
+    }
+
+    /// Answers the lexer's `NeedInput` signals with chunks from `input`,
+    /// ending the input once it runs dry. Without one, the input ends as
+    /// soon as the lexer has used up what its context was given.
+    ///
+    /// The lexer context must accept pushed input, as
+    /// [`StreamingLexContext`](lexer_framework::StreamingLexContext) does.
+    pub fn with_input_producer<I>(mut self, input: I) -> Self
+    where
+        I: InputProducer + 'static,
+    {
+        self.input = Some(Box::new(input));
+        self
+    }
+
+    /// Returns a reference to the lexer stage.
+    pub fn lexer(&self) -> &L {
+        &self.lexer
+    }
+
+    /// Returns a mutable reference to the lexer stage, e.g. to inspect its
+    /// context after a run.
+    pub fn lexer_mut(&mut self) -> &mut L {
+        &mut self.lexer
+    }
+
+    /// Returns a reference to the parser stage.
+    pub fn parser(&self) -> &P {
+        &self.parser
+    }
+
     /// Runs the pipeline until parser finishes, returning all AST nodes.
     pub fn run(mut self) -> Vec<Ast> {
         let mut results = Vec::new();
+        self.run_with(|node| results.push(node));
+        results
+    }
 
-        while let Some(signal) = self.parser.next_signal() {
-            match signal {
-                StreamingSignal::Produced(mut nodes) => {
-                    results.append(&mut nodes);
-                    continue;
-                }
-                StreamingSignal::NeedToken(min_needed) => {
-                    self.lexer
-                        .handle_signal(StreamingSignal::RequestToken(min_needed));
-                    if let Some(token_signal) = self.lexer.next_signal() {
-                        match token_signal {
-                            StreamingSignal::SupplyToken(token) => {
-                                self.parser
-                                    .handle_signal(StreamingSignal::SupplyToken(token));
-                            }
-                            StreamingSignal::EndOfInput => {
-                                self.parser.handle_signal(StreamingSignal::EndOfInput);
-                                results.extend(self.parser.finish());
-                                break;
-                            }
-                            StreamingSignal::Blocked(reason) | StreamingSignal::Abort(reason) => {
-                                self.parser
-                                    .handle_signal(StreamingSignal::Abort(reason.clone()));
-                                self.lexer
-                                    .handle_signal(StreamingSignal::Abort(reason.clone()));
-                                break;
-                            }
-                            _ => {}
-                        }
-                    } else {
-                        self.parser.handle_signal(StreamingSignal::EndOfInput);
-                        results.extend(self.parser.finish());
-                        break;
-                    }
-                    continue;
-                }
-                StreamingSignal::Finished(mut nodes) => {
-                    results.append(&mut nodes);
-                    break;
-                }
-                StreamingSignal::Blocked(reason) | StreamingSignal::Abort(reason) => {
-                    self.parser
-                        .handle_signal(StreamingSignal::Abort(reason.clone()));
-                    self.lexer
-                        .handle_signal(StreamingSignal::Abort(reason.clone()));
-                    break;
-                }
-                _ => {}
-            }
+    /// Runs the pipeline until parser finishes, handing each AST node to
+    /// `on_node` as soon as it is produced.
+    ///
+    /// Tokens are only requested from the lexer when the parser needs them,
+    /// so combined with a refillable lexer context (e.g. `Lexer::from_reader`)
+    /// the input is consumed at the pace nodes are handled and never has to
+    /// be held in memory all at once.
+    pub fn run_with<F>(&mut self, on_node: F)
+    where
+        F: FnMut(Ast),
+    {
+        self.run_with_progress(on_node, |_| {});
+    }
+
+    /// Like [`run_with`](Self::run_with), also calling `on_progress` with
+    /// the work done so far, e.g. to drive a progress bar on long parses.
+    ///
+    /// Reports come from the lexer's `Progress` signals (see
+    /// `Lexer::report_progress_every`), with the nodes emitted so far
+    /// filled in by the pipeline, and once more when the run ends.
+    pub fn run_with_progress<F, G>(&mut self, on_node: F, on_progress: G)
+    where
+        F: FnMut(Ast),
+        G: FnMut(Progress),
+    {
+        let mut routing = Routing {
+            pipeline: self,
+            on_node,
+            on_progress,
+            tokens_emitted: 0,
+            nodes_emitted: 0,
+            pause_for_input: false,
+        };
+        routing.drive();
+        let progress = routing.progress();
+        (routing.on_progress)(progress);
+    }
+
+    /// Pushes `chunk` to the lexer and runs the pipeline until the lexer
+    /// needs more input, returning the nodes completed so far.
+    ///
+    /// This lets the pipeline be driven from an event loop (stdin lines,
+    /// socket frames, editor changes) instead of pulling input through an
+    /// [`InputProducer`]. A token that reaches the end of `chunk` is held
+    /// back until the next chunk or [`finish`](Self::finish), so chunks may
+    /// split tokens. The lexer context must accept pushed input, as
+    /// [`StreamingLexContext`](lexer_framework::StreamingLexContext) does.
+    pub fn feed_str(&mut self, chunk: &str) -> Vec<Ast> {
+        self.lexer
+            .handle_signal(StreamingSignal::SupplyInput(chunk.to_string()));
+        self.drive_fed(true)
+    }
+
+    /// Ends the input and runs the pipeline to completion, returning the
+    /// remaining nodes. The parser stays finished, so chunks fed afterwards
+    /// produce no nodes; use [`flush`](Self::flush) to keep the pipeline
+    /// open.
+    pub fn finish(&mut self) -> Vec<Ast> {
+        self.lexer.handle_signal(StreamingSignal::EndOfInput);
+        self.drive_fed(false)
+    }
+
+    /// Ends the input fed so far and runs the pipeline to completion, like
+    /// [`finish`](Self::finish), but keeps it open: the next chunk fed
+    /// starts a new stretch of input, e.g. the next line typed into a REPL.
+    pub fn flush(&mut self) -> Vec<Ast> {
+        let nodes = self.finish();
+        self.parser.handle_signal(StreamingSignal::Resume);
+        nodes
+    }
+
+    /// Drives the stages, collecting the nodes they produce.
+    fn drive_fed(&mut self, pause_for_input: bool) -> Vec<Ast> {
+        let mut nodes = Vec::new();
+        Routing {
+            pipeline: self,
+            on_node: |node| nodes.push(node),
+            on_progress: |_| {},
+            tokens_emitted: 0,
+            nodes_emitted: 0,
+            pause_for_input,
         }
+        .drive();
+        nodes
+    }
+}
 
-        results
+/// Runs a [`StreamingPipeline`] through [`PipelineDriver::drive`], passing
+/// nodes and progress on to the caller's callbacks.
+#[cfg(feature = "streaming")]
+struct Routing<'a, L, P, Tok, Ast, F, G>
+where
+    L: TokenProducer<Tok> + Inbound<Tok, Ast> + Outbound<Tok, Ast>,
+    P: TokenConsumer<Tok, Ast> + Inbound<Tok, Ast> + Outbound<Tok, Ast>,
+{
+    pipeline: &'a mut StreamingPipeline<L, P, Tok, Ast>,
+    on_node: F,
+    on_progress: G,
+    tokens_emitted: usize,
+    nodes_emitted: usize,
+    // Set by `feed_str`: hand control back instead of asking `input`.
+    pause_for_input: bool,
+}
+
+#[cfg(feature = "streaming")]
+impl<L, P, Tok, Ast, F, G> Routing<'_, L, P, Tok, Ast, F, G>
+where
+    L: TokenProducer<Tok> + Inbound<Tok, Ast> + Outbound<Tok, Ast>,
+    P: TokenConsumer<Tok, Ast> + Inbound<Tok, Ast> + Outbound<Tok, Ast>,
+{
+    /// Returns the work done so far.
+    fn progress(&self) -> Progress {
+        Progress {
+            bytes_consumed: self.pipeline.lexer.input_position().map_or(0, |p| p.offset),
+            tokens_emitted: self.tokens_emitted,
+            nodes_emitted: self.nodes_emitted,
+        }
+    }
+}
+
+#[cfg(feature = "streaming")]
+impl<L, P, Tok, Ast, F, G> PipelineDriver<Tok, Ast> for Routing<'_, L, P, Tok, Ast, F, G>
+where
+    L: TokenProducer<Tok> + Inbound<Tok, Ast> + Outbound<Tok, Ast>,
+    P: TokenConsumer<Tok, Ast> + Inbound<Tok, Ast> + Outbound<Tok, Ast>,
+    F: FnMut(Ast),
+    G: FnMut(Progress),
+{
+    type Lexer = L;
+    type Parser = P;
+
+    fn lexer_mut(&mut self) -> &mut L {
+        &mut self.pipeline.lexer
+    }
+
+    fn parser_mut(&mut self) -> &mut P {
+        &mut self.pipeline.parser
+    }
+
+    fn on_produced(&mut self, nodes: Vec<Ast>) {
+        self.nodes_emitted += nodes.len();
+        nodes.into_iter().for_each(&mut self.on_node);
+    }
+
+    fn on_need_token(&mut self, min_needed: usize) -> StreamingSignal<Tok, Ast> {
+        let batch_size = self.pipeline.batch_size;
+        if batch_size > 1 {
+            StreamingSignal::RequestTokens(batch_size.max(min_needed))
+        } else {
+            StreamingSignal::RequestToken(min_needed)
+        }
+    }
+
+    fn on_supplied(&mut self, count: usize) {
+        self.tokens_emitted += count;
+    }
+
+    fn on_blocked(&mut self, reason: &str, attempt: usize) -> bool {
+        self.pipeline.on_blocked.should_retry(reason, attempt)
+    }
+
+    fn on_need_input(&mut self) -> InputAnswer {
+        if self.pause_for_input {
+            return InputAnswer::Pause;
+        }
+        match self
+            .pipeline
+            .input
+            .as_mut()
+            .and_then(|input| input.next_chunk())
+        {
+            Some(text) => InputAnswer::Supply(text),
+            None => InputAnswer::End,
+        }
+    }
+
+    fn on_progress(&mut self, progress: Progress) {
+        (self.on_progress)(Progress {
+            nodes_emitted: self.nodes_emitted,
+            ..progress
+        });
     }
 }
 
@@ -0,0 +1,159 @@
+// Run metadata for reproducing pipeline results
+use crate::ContentHash;
+use common_framework::Diagnostic;
+use std::collections::BTreeMap;
+
+/// The versions of the framework crates that produced a run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CrateVersions {
+    pub common: String,
+    pub lexer: String,
+    pub parser: String,
+    pub pipeline: String,
+}
+
+impl CrateVersions {
+    /// Returns the versions of the crates this build links.
+    pub fn current() -> Self {
+        Self {
+            common: common_framework::VERSION.to_string(),
+            lexer: lexer_framework::VERSION.to_string(),
+            parser: parser_framework::VERSION.to_string(),
+            pipeline: crate::VERSION.to_string(),
+        }
+    }
+}
+
+/// A hash of a rule set's names and priorities, in registration order.
+///
+/// Two runs with the same fingerprint tried the same rules in the same
+/// order; what the rules do is not covered, so rename a rule or bump a
+/// version when its behaviour changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuleFingerprint {
+    pub hash: ContentHash,
+    pub rules: usize,
+}
+
+impl RuleFingerprint {
+    /// Fingerprints rules given as `(name, priority)` pairs.
+    pub fn of<'a>(rules: impl IntoIterator<Item = (&'a str, i32)>) -> Self {
+        let mut text = String::new();
+        let mut count = 0;
+        for (name, priority) in rules {
+            text.push_str(name);
+            text.push('\t');
+            text.push_str(&priority.to_string());
+            text.push('\n');
+            count += 1;
+        }
+        Self {
+            hash: ContentHash::of(&text),
+            rules: count,
+        }
+    }
+}
+
+/// Everything needed to tell whether two runs should have produced the
+/// same output: crate versions, rule set fingerprints, the input's hash
+/// and the configuration.
+///
+/// Runs are deterministic, so equal metadata with different output points
+/// at a bug rather than at the environment. `config` holds the pipeline's
+/// settings as strings; add application settings with
+/// [`with_config`](Self::with_config).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RunMetadata {
+    pub versions: CrateVersions,
+    pub lexer_rules: RuleFingerprint,
+    pub parser_rules: RuleFingerprint,
+    pub input: ContentHash,
+    pub input_len: usize,
+    pub config: BTreeMap<String, String>,
+}
+
+impl RunMetadata {
+    /// Records a run of `input` through the given rule sets with the
+    /// current crate versions and no configuration.
+    pub fn new(input: &str, lexer_rules: RuleFingerprint, parser_rules: RuleFingerprint) -> Self {
+        Self {
+            versions: CrateVersions::current(),
+            lexer_rules,
+            parser_rules,
+            input: ContentHash::of(input),
+            input_len: input.len(),
+            config: BTreeMap::new(),
+        }
+    }
+
+    /// Records a configuration setting.
+    pub fn with_config(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.config.insert(key.into(), value.to_string());
+        self
+    }
+
+    /// Returns the names of the fields that differ from `other`, e.g.
+    /// `["parser_rules", "config.tabs"]`, to narrow down why two runs
+    /// disagree.
+    pub fn differences(&self, other: &RunMetadata) -> Vec<String> {
+        let mut differences = Vec::new();
+        let fields = [
+            ("versions", self.versions != other.versions),
+            ("lexer_rules", self.lexer_rules != other.lexer_rules),
+            ("parser_rules", self.parser_rules != other.parser_rules),
+            (
+                "input",
+                self.input != other.input || self.input_len != other.input_len,
+            ),
+        ];
+        for (name, differs) in fields {
+            if differs {
+                differences.push(name.to_string());
+            }
+        }
+        let keys: std::collections::BTreeSet<&String> =
+            self.config.keys().chain(other.config.keys()).collect();
+        for key in keys {
+            if self.config.get(key) != other.config.get(key) {
+                differences.push(format!("config.{}", key));
+            }
+        }
+        differences
+    }
+}
+
+/// The result of [`BatchPipeline::run_with_metadata`](crate::BatchPipeline::run_with_metadata):
+/// the nodes, the diagnostics, and how the run came about.
+///
+/// With the `serde` feature (and a serializable `Ast`) the whole output
+/// can be saved with [`to_json`](Self::to_json) and attached to a bug
+/// report.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PipelineOutput<Ast> {
+    pub nodes: Vec<Ast>,
+    pub diagnostics: Vec<Diagnostic>,
+    pub metadata: RunMetadata,
+}
+
+#[cfg(feature = "serde")]
+impl<Ast> PipelineOutput<Ast> {
+    /// Serializes the output as pretty-printed JSON.
+    pub fn to_json(&self) -> serde_json::Result<String>
+    where
+        Ast: serde::Serialize,
+    {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Reads an output written by [`to_json`](Self::to_json).
+    pub fn from_json(json: &str) -> serde_json::Result<Self>
+    where
+        Ast: serde::de::DeserializeOwned,
+    {
+        serde_json::from_str(json)
+    }
+}
@@ -0,0 +1,334 @@
+// Multi-file projects: parallel parsing and dependency-ordered processing
+use std::collections::HashMap;
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+type ParseFn<Ast> = Box<dyn Fn(&str) -> Vec<Ast> + Sync>;
+type DepsFn<Ast> = Box<dyn Fn(&[Ast]) -> Vec<String> + Sync>;
+
+/// Parses the files of a multi-module project and orders them by their
+/// dependencies.
+///
+/// `parse` turns one file's source into nodes, typically by calling
+/// [`BatchPipeline::run`](crate::BatchPipeline::run). `dependencies` then
+/// lists the names of the modules a file imports. Files are parsed in
+/// parallel, since parsing one file never needs another. The resulting
+/// [`Project`] holds the modules in dependency order and runs later stages
+/// (name resolution, type checking, code generation) over them with
+/// [`Project::process`].
+///
+/// ```ignore
+/// let project = ProjectPipeline::new(
+///     |source| BatchPipeline::run(source, lexer_rules(), parser_rules()),
+///     |nodes| nodes.iter().filter_map(Item::import_name).collect(),
+/// )
+/// .file("util", util_source)
+/// .file("main", main_source)
+/// .run()?;
+/// let checked = project.process(|module, deps| check(&module.nodes, deps));
+/// ```
+pub struct ProjectPipeline<Ast> {
+    parse: ParseFn<Ast>,
+    dependencies: DepsFn<Ast>,
+    files: Vec<(String, String)>,
+    threads: usize,
+}
+
+impl<Ast: Send> ProjectPipeline<Ast> {
+    /// Creates a pipeline that parses files with `parse` and finds their
+    /// imports with `dependencies`.
+    pub fn new(
+        parse: impl Fn(&str) -> Vec<Ast> + Sync + 'static,
+        dependencies: impl Fn(&[Ast]) -> Vec<String> + Sync + 'static,
+    ) -> Self {
+        Self {
+            parse: Box::new(parse),
+            dependencies: Box::new(dependencies),
+            files: Vec::new(),
+            threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        }
+    }
+
+    /// Adds the module `name` with the given source.
+    pub fn file(mut self, name: impl Into<String>, source: impl Into<String>) -> Self {
+        self.files.push((name.into(), source.into()));
+        self
+    }
+
+    /// Limits the number of worker threads. Defaults to the available
+    /// parallelism; `1` runs everything on the calling thread.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Parses every file and orders the modules so each comes after its
+    /// dependencies. Among modules that do not depend on each other, the
+    /// order in which files were added is kept.
+    pub fn run(self) -> Result<Project<Ast>, ProjectError> {
+        let mut names = HashMap::new();
+        for (index, (name, _)) in self.files.iter().enumerate() {
+            if names.insert(name.clone(), index).is_some() {
+                return Err(ProjectError::DuplicateModule(name.clone()));
+            }
+        }
+
+        let parsed = parallel_map(&self.files, self.threads, |(_, source)| {
+            let nodes = (self.parse)(source);
+            let dependencies = (self.dependencies)(&nodes);
+            (nodes, dependencies)
+        });
+
+        let mut modules = Vec::with_capacity(parsed.len());
+        for ((name, source), (nodes, dependencies)) in self.files.into_iter().zip(parsed) {
+            let dep_indices = dependencies
+                .iter()
+                .map(|dependency| {
+                    names
+                        .get(dependency)
+                        .copied()
+                        .ok_or_else(|| ProjectError::MissingDependency {
+                            module: name.clone(),
+                            dependency: dependency.clone(),
+                        })
+                })
+                .collect::<Result<_, _>>()?;
+            modules.push(Module {
+                name,
+                source,
+                nodes,
+                dependencies,
+                dep_indices,
+            });
+        }
+
+        let levels = topological_levels(&modules)?;
+        // Reorder modules and remap dependency indices to the new order.
+        let order: Vec<usize> = levels.iter().flatten().copied().collect();
+        let mut new_index = vec![0; modules.len()];
+        for (new, &old) in order.iter().enumerate() {
+            new_index[old] = new;
+        }
+        let mut slots: Vec<Option<Module<Ast>>> = modules.into_iter().map(Some).collect();
+        let modules = order
+            .iter()
+            .map(|&old| {
+                let mut module = slots[old].take().expect("each module is ordered once");
+                module
+                    .dep_indices
+                    .iter_mut()
+                    .for_each(|dep| *dep = new_index[*dep]);
+                module
+            })
+            .collect();
+        let level_sizes = levels.iter().map(Vec::len).collect();
+
+        Ok(Project {
+            modules,
+            level_sizes,
+            threads: self.threads,
+        })
+    }
+}
+
+/// One parsed file of a [`Project`].
+#[derive(Debug, Clone)]
+pub struct Module<Ast> {
+    /// The name the file was added under, which other modules import it by.
+    pub name: String,
+    /// The file's source.
+    pub source: String,
+    /// The parsed nodes.
+    pub nodes: Vec<Ast>,
+    /// Names of the modules this one depends on, as extracted.
+    pub dependencies: Vec<String>,
+    dep_indices: Vec<usize>,
+}
+
+/// The modules of a project in dependency order, produced by
+/// [`ProjectPipeline::run`].
+#[derive(Debug, Clone)]
+pub struct Project<Ast> {
+    modules: Vec<Module<Ast>>,
+    /// Number of modules in each level; a level only depends on earlier ones.
+    level_sizes: Vec<usize>,
+    threads: usize,
+}
+
+impl<Ast> Project<Ast> {
+    /// Returns the modules, each after all of its dependencies.
+    pub fn modules(&self) -> &[Module<Ast>] {
+        &self.modules
+    }
+
+    /// Returns the module named `name`.
+    pub fn module(&self, name: &str) -> Option<&Module<Ast>> {
+        self.modules.iter().find(|module| module.name == name)
+    }
+
+    /// Returns the module names in dependency order.
+    pub fn order(&self) -> Vec<&str> {
+        self.modules
+            .iter()
+            .map(|module| module.name.as_str())
+            .collect()
+    }
+}
+
+impl<Ast: Sync> Project<Ast> {
+    /// Runs `f` on every module after it has run on the module's
+    /// dependencies, passing their results in the order of
+    /// [`Module::dependencies`]. Modules that do not depend on each other
+    /// are processed in parallel.
+    ///
+    /// Returns the results in the order of [`modules`](Self::modules).
+    pub fn process<R, F>(&self, f: F) -> Vec<R>
+    where
+        R: Send + Sync,
+        F: Fn(&Module<Ast>, &[&R]) -> R + Sync,
+    {
+        let mut results: Vec<R> = Vec::with_capacity(self.modules.len());
+        for &size in &self.level_sizes {
+            let start = results.len();
+            let level = &self.modules[start..start + size];
+            let done = &results;
+            let outputs = parallel_map(level, self.threads, |module| {
+                let deps: Vec<&R> = module.dep_indices.iter().map(|&dep| &done[dep]).collect();
+                f(module, &deps)
+            });
+            results.extend(outputs);
+        }
+        results
+    }
+}
+
+/// Why a project could not be ordered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProjectError {
+    /// Two files were added under the same name.
+    DuplicateModule(String),
+    /// `module` depends on a module that was not added.
+    MissingDependency { module: String, dependency: String },
+    /// The modules depend on each other in a cycle; lists the cycle with its
+    /// first module repeated at the end.
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProjectError::DuplicateModule(name) => write!(f, "module `{}` is defined twice", name),
+            ProjectError::MissingDependency { module, dependency } => write!(
+                f,
+                "module `{}` depends on unknown module `{}`",
+                module, dependency
+            ),
+            ProjectError::Cycle(cycle) => write!(f, "dependency cycle: {}", cycle.join(" -> ")),
+        }
+    }
+}
+
+impl std::error::Error for ProjectError {}
+
+/// Groups module indices into levels with Kahn's algorithm: every module's
+/// dependencies are in earlier levels.
+fn topological_levels<Ast>(modules: &[Module<Ast>]) -> Result<Vec<Vec<usize>>, ProjectError> {
+    let mut pending: Vec<usize> = modules
+        .iter()
+        .map(|module| module.dep_indices.len())
+        .collect();
+    let mut dependents = vec![Vec::new(); modules.len()];
+    for (index, module) in modules.iter().enumerate() {
+        for &dep in &module.dep_indices {
+            dependents[dep].push(index);
+        }
+    }
+
+    let mut levels = Vec::new();
+    let mut ready: Vec<usize> = (0..modules.len()).filter(|&i| pending[i] == 0).collect();
+    let mut ordered = 0;
+    while !ready.is_empty() {
+        ordered += ready.len();
+        let mut next = Vec::new();
+        for &index in &ready {
+            for &dependent in &dependents[index] {
+                pending[dependent] -= 1;
+                if pending[dependent] == 0 {
+                    next.push(dependent);
+                }
+            }
+        }
+        next.sort_unstable();
+        levels.push(std::mem::replace(&mut ready, next));
+    }
+
+    if ordered < modules.len() {
+        return Err(ProjectError::Cycle(find_cycle(modules, &pending)));
+    }
+    Ok(levels)
+}
+
+/// Follows unresolved dependencies from a module left over by Kahn's
+/// algorithm until one repeats.
+fn find_cycle<Ast>(modules: &[Module<Ast>], pending: &[usize]) -> Vec<String> {
+    let mut current = pending
+        .iter()
+        .position(|&count| count > 0)
+        .expect("a cycle leaves modules pending");
+    let mut path: Vec<usize> = Vec::new();
+    loop {
+        if let Some(start) = path.iter().position(|&index| index == current) {
+            let mut cycle: Vec<String> = path[start..]
+                .iter()
+                .map(|&i| modules[i].name.clone())
+                .collect();
+            cycle.push(modules[current].name.clone());
+            return cycle;
+        }
+        path.push(current);
+        current = *modules[current]
+            .dep_indices
+            .iter()
+            .find(|&&dep| pending[dep] > 0)
+            .expect("a pending module has a pending dependency");
+    }
+}
+
+/// Maps `f` over `items` on up to `threads` scoped threads, keeping the
+/// order of the results.
+fn parallel_map<T, R, F>(items: &[T], threads: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let threads = threads.min(items.len());
+    if threads <= 1 {
+        return items.iter().map(f).collect();
+    }
+    let next = AtomicUsize::new(0);
+    let slots: Vec<Mutex<Option<R>>> = items.iter().map(|_| Mutex::new(None)).collect();
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(index) else {
+                    break;
+                };
+                let result = f(item);
+                *slots[index].lock().expect("result slot poisoned") = Some(result);
+            });
+        }
+    });
+    slots
+        .into_iter()
+        .map(|slot| {
+            slot.into_inner()
+                .expect("result slot poisoned")
+                .expect("every item is mapped")
+        })
+        .collect()
+}
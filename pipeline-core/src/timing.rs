@@ -0,0 +1,75 @@
+// Per-stage timing of a batch run
+use common_framework::{AllocStats, BenchReport};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Wall time and item counts of each stage of one
+/// [`BatchPipeline::run_timed`](crate::BatchPipeline::run_timed) run.
+///
+/// Each stage is a single-iteration [`BenchReport`] without rule counts, so
+/// it can be compared with or printed like the reports of `bench_lexer` and
+/// `bench_parser`. `input` and `output` are bytes in and tokens out for the
+/// lexer, tokens in and out for the filter, and tokens in and nodes out for
+/// the parser.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageTimings {
+    pub lex: BenchReport,
+    pub filter: BenchReport,
+    pub parse: BenchReport,
+}
+
+impl StageTimings {
+    /// Returns the time of all three stages.
+    pub fn total(&self) -> Duration {
+        self.lex.elapsed + self.filter.elapsed + self.parse.elapsed
+    }
+
+    /// Returns the stages with their names, in the order they ran.
+    pub fn stages(&self) -> [(&'static str, &BenchReport); 3] {
+        [
+            ("lex", &self.lex),
+            ("filter", &self.filter),
+            ("parse", &self.parse),
+        ]
+    }
+}
+
+impl fmt::Display for StageTimings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total().as_secs_f64();
+        for (name, report) in self.stages() {
+            let share = if total == 0.0 {
+                0.0
+            } else {
+                report.elapsed.as_secs_f64() / total * 100.0
+            };
+            writeln!(
+                f,
+                "{:<8} {:>12?} {:>5.1}% {:>10} in {:>10} out",
+                name, report.elapsed, share, report.input, report.output
+            )?;
+        }
+        writeln!(f, "{:<8} {:>12?}", "total", self.total())
+    }
+}
+
+/// Runs one stage, returning its result and a report counting `input`
+/// items in and whatever `output` says came out.
+pub(crate) fn time_stage<T>(
+    input: usize,
+    stage: impl FnOnce() -> T,
+    output: impl FnOnce(&T) -> usize,
+) -> (T, BenchReport) {
+    let start = Instant::now();
+    let (result, allocations) = AllocStats::measure(stage);
+    let elapsed = start.elapsed();
+    let report = BenchReport {
+        iterations: 1,
+        elapsed,
+        input,
+        output: output(&result),
+        rules: Vec::new(),
+        allocations,
+    };
+    (result, report)
+}
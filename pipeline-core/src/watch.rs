@@ -0,0 +1,338 @@
+// Directory watching for doc generators and other long-running drivers
+use crate::cache::{ContentHash, ParseCache};
+use notify::{RecursiveMode, Watcher};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// What happened to a watched file.
+#[derive(Debug)]
+pub enum FileChange {
+    /// The file is new or its contents changed; holds the new contents.
+    Modified(PathBuf, String),
+    /// The file was deleted or renamed away.
+    Removed(PathBuf),
+    /// The file or directory could not be read. Files are retried once they
+    /// are modified again.
+    Error(PathBuf, io::Error),
+}
+
+/// An event reported by [`DirWatcher::run`] and [`DirWatcher::spawn`].
+#[derive(Debug)]
+pub enum WatchEvent<V> {
+    /// A file changed and was processed into `result`.
+    Processed { path: PathBuf, result: V },
+    /// A file was removed.
+    Removed { path: PathBuf },
+    /// A file or directory could not be read; watching continues.
+    Error { path: PathBuf, error: io::Error },
+}
+
+/// Watches a directory tree and reports files whose contents changed.
+///
+/// [`run`](Self::run) and [`spawn`](Self::spawn) scan the tree once, then
+/// wait for the platform's file notifications (through `notify`) and
+/// re-check only the paths they name. A re-check compares modification
+/// times and sizes, and re-reads only the files where they differ. Files
+/// that were touched but whose contents hash the same are not reported.
+/// [`poll`](Self::poll) rescans the whole tree without notifications.
+///
+/// ```ignore
+/// let mut cache = ParseCache::new(GRAMMAR_VERSION);
+/// DirWatcher::new("docs").with_extension("md").run(
+///     &mut cache,
+///     |_, source| BatchPipeline::run(source, lexer_rules(), parser_rules()),
+///     |event| {
+///         if let WatchEvent::Processed { path, result } = event {
+///             render(&path, &result);
+///         }
+///         ControlFlow::Continue(())
+///     },
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct DirWatcher {
+    root: PathBuf,
+    extension: Option<String>,
+    debounce: Duration,
+    files: HashMap<PathBuf, FileState>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FileState {
+    modified: SystemTime,
+    len: u64,
+    /// `None` if the file could not be read.
+    hash: Option<ContentHash>,
+}
+
+impl DirWatcher {
+    /// Watches every file below `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            extension: None,
+            debounce: Duration::from_millis(50),
+            files: HashMap::new(),
+        }
+    }
+
+    /// Only watches files with the given extension, e.g. `"md"`.
+    pub fn with_extension(mut self, extension: impl Into<String>) -> Self {
+        self.extension = Some(extension.into());
+        self
+    }
+
+    /// Sets how long [`run`](Self::run) and [`spawn`](Self::spawn) wait
+    /// for further notifications after one arrives, so a burst of writes
+    /// (an editor saving, a `git checkout`) is handled in one pass.
+    /// Defaults to 50 ms.
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Returns the watched directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Scans the directory once and returns the changes since the previous
+    /// scan: modified files and errors in path order, then removed files.
+    /// The first scan reports every file as modified.
+    pub fn poll(&mut self) -> Vec<FileChange> {
+        let mut changes = Vec::new();
+        let mut seen = HashSet::new();
+        let root = self.root.clone();
+        self.scan(&root, &mut changes, &mut seen);
+
+        let mut removed: Vec<_> = self
+            .files
+            .keys()
+            .filter(|path| !seen.contains(*path))
+            .cloned()
+            .collect();
+        removed.sort();
+        for path in removed {
+            self.files.remove(&path);
+            changes.push(FileChange::Removed(path));
+        }
+        changes
+    }
+
+    fn scan(&mut self, dir: &Path, changes: &mut Vec<FileChange>, seen: &mut HashSet<PathBuf>) {
+        let entries = fs::read_dir(dir).and_then(|entries| entries.collect::<io::Result<Vec<_>>>());
+        let mut entries = match entries {
+            Ok(entries) => entries,
+            Err(error) => {
+                changes.push(FileChange::Error(dir.to_path_buf(), error));
+                return;
+            }
+        };
+        entries.sort_by_key(|entry| entry.path());
+
+        for entry in entries {
+            let path = entry.path();
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(error) => {
+                    changes.push(FileChange::Error(path, error));
+                    continue;
+                }
+            };
+            if metadata.is_dir() {
+                self.scan(&path, changes, seen);
+                continue;
+            }
+            if self.matches(&path) {
+                seen.insert(path.clone());
+                self.check_file(path, &metadata, changes);
+            }
+        }
+    }
+
+    /// Re-reads the file at `path` if its modification time or size
+    /// changed, reporting it if its contents did too.
+    fn check_file(
+        &mut self,
+        path: PathBuf,
+        metadata: &fs::Metadata,
+        changes: &mut Vec<FileChange>,
+    ) {
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        let len = metadata.len();
+        let previous = self.files.get(&path).copied();
+        if previous.is_some_and(|state| state.modified == modified && state.len == len) {
+            return;
+        }
+        let contents = fs::read_to_string(&path);
+        let hash = contents.as_deref().ok().map(ContentHash::of);
+        self.files.insert(
+            path.clone(),
+            FileState {
+                modified,
+                len,
+                hash,
+            },
+        );
+        match contents {
+            Ok(_) if previous.is_some_and(|state| state.hash == hash) => {}
+            Ok(contents) => changes.push(FileChange::Modified(path, contents)),
+            Err(error) => changes.push(FileChange::Error(path, error)),
+        }
+    }
+
+    /// Re-checks the paths named by notifications: files are re-read as in
+    /// [`poll`](Self::poll), directories rescanned, and watched files that
+    /// no longer exist at or below a path reported removed.
+    fn refresh(&mut self, paths: BTreeSet<PathBuf>) -> Vec<FileChange> {
+        let mut changes = Vec::new();
+        for path in paths {
+            let mut seen = HashSet::new();
+            match fs::metadata(&path) {
+                Ok(metadata) if metadata.is_dir() => self.scan(&path, &mut changes, &mut seen),
+                Ok(metadata) => {
+                    if self.matches(&path) {
+                        seen.insert(path.clone());
+                        self.check_file(path.clone(), &metadata, &mut changes);
+                    }
+                }
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(error) => {
+                    changes.push(FileChange::Error(path, error));
+                    continue;
+                }
+            }
+            let mut removed: Vec<_> = self
+                .files
+                .keys()
+                .filter(|file| file.starts_with(&path) && !seen.contains(*file))
+                .cloned()
+                .collect();
+            removed.sort();
+            for file in removed {
+                self.files.remove(&file);
+                changes.push(FileChange::Removed(file));
+            }
+        }
+        changes
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        match &self.extension {
+            Some(extension) => path
+                .extension()
+                .is_some_and(|ext| ext == extension.as_str()),
+            None => true,
+        }
+    }
+
+    /// Watches until `on_event` returns [`ControlFlow::Break`], running
+    /// `process` on every changed file and passing the results to
+    /// `on_event`. Every file present at the start is reported first.
+    ///
+    /// Results are looked up in `cache` first, so files whose contents were
+    /// already processed (reverted edits, copies, entries loaded from a
+    /// saved cache) are not processed again. If notifications cannot be set
+    /// up for the directory, that is reported as an error and `run` returns.
+    pub fn run<V, P, E>(&mut self, cache: &mut ParseCache<V>, mut process: P, mut on_event: E)
+    where
+        V: Clone,
+        P: FnMut(&Path, &str) -> V,
+        E: FnMut(WatchEvent<V>) -> ControlFlow<()>,
+    {
+        let (sender, notifications) = mpsc::channel();
+        let watcher = notify::recommended_watcher(sender).and_then(|mut watcher| {
+            watcher.watch(&self.root, RecursiveMode::Recursive)?;
+            Ok(watcher)
+        });
+        // Kept alive for as long as the loop runs.
+        let _watcher = match watcher {
+            Ok(watcher) => watcher,
+            Err(error) => {
+                let _ = on_event(WatchEvent::Error {
+                    path: self.root.clone(),
+                    error: io::Error::other(error),
+                });
+                return;
+            }
+        };
+
+        let mut changes = self.poll();
+        loop {
+            for change in changes {
+                let event = match change {
+                    FileChange::Modified(path, contents) => {
+                        let result =
+                            cache.get_or_insert_with(&contents, |source| process(&path, source));
+                        WatchEvent::Processed { path, result }
+                    }
+                    FileChange::Removed(path) => WatchEvent::Removed { path },
+                    FileChange::Error(path, error) => WatchEvent::Error { path, error },
+                };
+                if on_event(event).is_break() {
+                    return;
+                }
+            }
+            let Ok(first) = notifications.recv() else {
+                return;
+            };
+            let mut batch = vec![first];
+            while let Ok(next) = notifications.recv_timeout(self.debounce) {
+                batch.push(next);
+            }
+            changes = self.changes_for(batch);
+        }
+    }
+
+    /// Turns a batch of notifications into changes. A notification that
+    /// asks for a rescan (e.g. because events were dropped) rescans the
+    /// whole tree.
+    fn changes_for(&mut self, batch: Vec<notify::Result<notify::Event>>) -> Vec<FileChange> {
+        let mut paths = BTreeSet::new();
+        let mut errors = Vec::new();
+        let mut rescan = false;
+        for notification in batch {
+            match notification {
+                Ok(event) => {
+                    rescan |= event.need_rescan();
+                    paths.extend(event.paths);
+                }
+                Err(error) => {
+                    let path = error.paths.first().unwrap_or(&self.root).clone();
+                    errors.push(FileChange::Error(path, io::Error::other(error)));
+                }
+            }
+        }
+        let mut changes = if rescan {
+            self.poll()
+        } else {
+            self.refresh(paths)
+        };
+        changes.extend(errors);
+        changes
+    }
+
+    /// Runs the watcher on a background thread and returns a channel of its
+    /// events. The thread stops at the first event after the receiver is
+    /// dropped.
+    pub fn spawn<V, P>(mut self, mut cache: ParseCache<V>, process: P) -> Receiver<WatchEvent<V>>
+    where
+        V: Clone + Send + 'static,
+        P: FnMut(&Path, &str) -> V + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            self.run(&mut cache, process, |event| match sender.send(event) {
+                Ok(()) => ControlFlow::Continue(()),
+                Err(_) => ControlFlow::Break(()),
+            });
+        });
+        receiver
+    }
+}
@@ -0,0 +1,81 @@
+//! Pipeline builder tests (`PipelineBuilder`, `BuiltPipeline::from_chunks`).
+#![cfg(feature = "streaming")]
+
+mod common;
+
+use common::{lexer_rules, parser_rules, words, Tok};
+use common_framework::{Diagnostics, TriviaPolicy};
+use lexer_framework::{IterInput, LexToken};
+use pipeline_core::{BuiltPipeline, PipelineBuilder};
+
+#[test]
+fn test_build_runs_with_filter() {
+    let nodes = PipelineBuilder::new()
+        .input("one two  three")
+        .lexer(lexer_rules())
+        .filter(|token: &Tok| *token != Tok::Space)
+        .parser(parser_rules())
+        .build()
+        .run();
+    assert_eq!(words(&nodes), ["one", "two", "three"]);
+}
+
+#[test]
+fn test_without_filter_parser_sees_every_token() {
+    let nodes = PipelineBuilder::new()
+        .input("one two")
+        .lexer(lexer_rules())
+        .parser(parser_rules())
+        .build()
+        .run();
+    // The parser's rule rejects the space, so it stops there.
+    assert_eq!(words(&nodes), ["one"]);
+}
+
+#[test]
+fn test_trivia_policy_filters_tokens() {
+    let policy = TriviaPolicy::new(Tok::is_whitespace, Tok::is_newline);
+    let mut pipeline = PipelineBuilder::new()
+        .lexer(lexer_rules())
+        .trivia(policy)
+        .parser(parser_rules())
+        .batch_size(8)
+        .build();
+    assert_eq!(words(&pipeline.feed_str("a b c")), ["a", "b"]);
+    assert_eq!(words(&pipeline.finish()), ["c"]);
+}
+
+#[test]
+fn test_input_producer_continues_after_input() {
+    let nodes = PipelineBuilder::new()
+        .input("one tw")
+        .input_producer(IterInput::new(vec!["o thr", "ee"]))
+        .lexer(lexer_rules())
+        .filter(|token: &Tok| *token != Tok::Space)
+        .parser(parser_rules())
+        .build()
+        .run();
+    assert_eq!(words(&nodes), ["one", "two", "three"]);
+}
+
+#[test]
+fn test_from_chunks() {
+    let pipeline = BuiltPipeline::from_chunks(vec!["al", "pha"], lexer_rules(), parser_rules());
+    assert_eq!(words(&pipeline.run()), ["alpha"]);
+}
+
+#[test]
+fn test_diagnostics_go_to_the_given_sink() {
+    let diagnostics = Diagnostics::collect();
+    PipelineBuilder::new()
+        .input("one ? two")
+        .lexer(lexer_rules())
+        .filter(|token: &Tok| *token != Tok::Space)
+        .parser(parser_rules())
+        .diagnostics(diagnostics.clone())
+        .build()
+        .run();
+    let reported = diagnostics.take();
+    assert_eq!(reported.len(), 1);
+    assert!(reported[0].message.contains("'?'"));
+}
@@ -0,0 +1,164 @@
+//! Parse cache tests (`ParseCache`, `ContentHash`).
+
+use lexer_framework::{DefaultContext as LexContext, LexContext as _, LexToken, LexingRule};
+use parser_framework::{
+    AstNode, DefaultContext as ParseContext, ParseContext as _, ParsingRule, Position,
+};
+use pipeline_core::{CacheStats, ContentHash, ParseCache};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Word(String);
+
+impl LexToken for Word {}
+
+impl AstNode for Word {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+/// Space-separated words.
+struct WordRule;
+
+impl LexingRule<LexContext, Word> for WordRule {
+    fn try_match(&mut self, ctx: &mut LexContext) -> Option<Word> {
+        ctx.consume_while(|ch| ch == ' ');
+        let word = ctx.consume_while(|ch| ch != ' ');
+        (!word.is_empty()).then(|| Word(word.to_string()))
+    }
+}
+
+/// Each word as a node, upper-cased if `upper` is set.
+struct NodeRule {
+    upper: bool,
+}
+
+impl ParsingRule<ParseContext<Word>, Word, Word> for NodeRule {
+    fn try_parse(&mut self, ctx: &mut ParseContext<Word>) -> Option<Word> {
+        let Word(word) = ctx.advance()?;
+        Some(Word(if self.upper {
+            word.to_uppercase()
+        } else {
+            word
+        }))
+    }
+}
+
+fn run(cache: &mut ParseCache<Vec<Word>>, input: &str, upper: bool) -> Vec<Word> {
+    cache.run_batch(
+        input,
+        || vec![Box::new(WordRule)],
+        || vec![Box::new(NodeRule { upper })],
+    )
+}
+
+#[test]
+fn test_content_hash_is_stable() {
+    assert_eq!(ContentHash::of("abc"), ContentHash::of("abc"));
+    assert_ne!(ContentHash::of("abc"), ContentHash::of("abd"));
+    assert_eq!(ContentHash::of("").value(), 0xcbf2_9ce4_8422_2325);
+}
+
+#[test]
+fn test_run_batch_only_parses_misses() {
+    let mut cache = ParseCache::new(1);
+    assert_eq!(
+        run(&mut cache, "a b", false),
+        [Word("a".into()), Word("b".into())]
+    );
+    // The rules changed but the grammar fingerprint did not, so the cached
+    // result is returned.
+    assert_eq!(
+        run(&mut cache, "a b", true),
+        [Word("a".into()), Word("b".into())]
+    );
+    assert_eq!(run(&mut cache, "c", true), [Word("C".into())]);
+    assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 2 });
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn test_lookups_compare_the_input() {
+    let mut cache = ParseCache::new(1);
+    cache.insert("a", 1);
+    assert!(cache.contains("a"));
+    assert!(!cache.contains("a "));
+    assert_eq!(cache.get("a"), Some(&1));
+    assert_eq!(cache.get("b"), None);
+    assert_eq!(cache.grammar(), 1);
+}
+
+#[test]
+fn test_chunks_and_pruning() {
+    let mut cache = ParseCache::new(1);
+    let mut computed = Vec::new();
+    let lens = cache.get_or_insert_chunks(["ab", "cde", "ab"], |chunk| {
+        computed.push(chunk.to_string());
+        chunk.len()
+    });
+    assert_eq!(lens, [2, 3, 2]);
+    assert_eq!(computed, ["ab", "cde"]);
+
+    assert_eq!(cache.prune_unused(), 0);
+    cache.get("ab");
+    assert_eq!(cache.prune_unused(), 1);
+    assert!(cache.contains("ab") && !cache.contains("cde"));
+}
+
+#[cfg(feature = "serde")]
+mod persist {
+    use super::*;
+    use std::io;
+    use std::path::PathBuf;
+
+    fn temp_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "pipeline-core-cache-{}-{}.json",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let path = temp_file("round-trip");
+        let mut cache = ParseCache::new(7);
+        cache.insert("a b", vec!["a".to_string(), "b".to_string()]);
+        cache.save(&path).unwrap();
+
+        let mut loaded = ParseCache::<Vec<String>>::load(&path, 7).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.get("a b").map(Vec::len), Some(2));
+        // Loaded entries count as unused until looked up.
+        assert_eq!(loaded.prune_unused(), 0);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_load_rejects_another_grammar() {
+        let path = temp_file("grammar");
+        let mut cache = ParseCache::new(7);
+        cache.insert("a", 1);
+        cache.save(&path).unwrap();
+
+        let err = ParseCache::<i32>::load(&path, 8).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("another grammar"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_load_rejects_old_versions_and_missing_files() {
+        let path = temp_file("version");
+        std::fs::write(&path, r#"{"version":1,"entries":[[123,1]]}"#).unwrap();
+        let err = ParseCache::<i32>::load(&path, 7).unwrap_err();
+        assert!(err.to_string().contains("version 1"));
+        std::fs::remove_file(&path).unwrap();
+
+        let cache = ParseCache::<i32>::load(&path, 7).unwrap();
+        assert!(cache.is_empty());
+        assert_eq!(cache.grammar(), 7);
+    }
+}
@@ -0,0 +1,79 @@
+//! Shared fixture: lexer rules for words and spaces and a parser rule
+//! turning each word into a node, over any context.
+#![allow(dead_code)]
+
+use common_framework::Position;
+use lexer_framework::{LexContext, LexToken, LexingRule, TokenKind};
+use parser_framework::{AstNode, ParseContext, ParsingRule};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tok {
+    Word(String),
+    Space,
+}
+
+impl LexToken for Tok {
+    fn kind(&self) -> Option<TokenKind> {
+        matches!(self, Tok::Space).then_some(TokenKind::Whitespace)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Word(pub String);
+
+impl AstNode for Word {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+pub struct WordRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, Tok> for WordRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        let word = ctx.consume_while(char::is_alphanumeric);
+        (!word.is_empty()).then(|| Tok::Word(word.to_string()))
+    }
+
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        first_char.map(char::is_alphanumeric)
+    }
+}
+
+pub struct SpaceRule;
+
+impl<Ctx: LexContext> LexingRule<Ctx, Tok> for SpaceRule {
+    fn try_match(&mut self, ctx: &mut Ctx) -> Option<Tok> {
+        let space = ctx.consume_while(char::is_whitespace);
+        (!space.is_empty()).then_some(Tok::Space)
+    }
+
+    fn quick_check(&self, first_char: Option<char>) -> Option<bool> {
+        first_char.map(char::is_whitespace)
+    }
+}
+
+/// Parses a word; spaces must be filtered out before the parser.
+pub struct WordNodeRule;
+
+impl<Ctx: ParseContext<Tok>> ParsingRule<Ctx, Tok, Word> for WordNodeRule {
+    fn try_parse(&mut self, ctx: &mut Ctx) -> Option<Word> {
+        match ctx.advance()? {
+            Tok::Word(word) => Some(Word(word)),
+            Tok::Space => None,
+        }
+    }
+}
+
+pub fn lexer_rules<Ctx: LexContext + 'static>() -> Vec<Box<dyn LexingRule<Ctx, Tok>>> {
+    vec![Box::new(WordRule), Box::new(SpaceRule)]
+}
+
+pub fn parser_rules<Ctx: ParseContext<Tok> + 'static>() -> Vec<Box<dyn ParsingRule<Ctx, Tok, Word>>>
+{
+    vec![Box::new(WordNodeRule)]
+}
+
+pub fn words(nodes: &[Word]) -> Vec<&str> {
+    nodes.iter().map(|Word(word)| word.as_str()).collect()
+}
@@ -0,0 +1,138 @@
+//! Doc comment tests (`DocExtractor`, `DocMarker`).
+
+use lexer_framework::{Channel, ChannelTokens, LexToken};
+use parser_framework::{AstId, AstNode, HasAstId, Position};
+use pipeline_core::{DocExtractor, DocMarker};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Word(Position),
+    Comment(&'static str),
+}
+
+impl LexToken for Tok {
+    fn position(&self) -> Option<Position> {
+        match self {
+            Tok::Word(position) => Some(*position),
+            Tok::Comment(_) => None,
+        }
+    }
+}
+
+impl Tok {
+    fn comment_text(&self) -> Option<&str> {
+        match self {
+            Tok::Comment(text) => Some(text),
+            Tok::Word(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    id: AstId,
+    position: Position,
+}
+
+impl AstNode for Node {
+    fn position(&self) -> Option<Position> {
+        Some(self.position)
+    }
+}
+
+impl HasAstId for Node {
+    fn ast_id(&self) -> Option<AstId> {
+        Some(self.id)
+    }
+}
+
+fn at(offset: usize) -> Position {
+    Position {
+        offset,
+        ..Position::new()
+    }
+}
+
+/// Comments on the hidden channel, words at offsets 10, 20, ... on the
+/// default one.
+fn tokens(items: &[Option<&'static str>]) -> ChannelTokens<Tok> {
+    let mut tokens = ChannelTokens::new();
+    let mut offset = 0;
+    for item in items {
+        match item {
+            Some(comment) => tokens.push(Channel::HIDDEN, Tok::Comment(comment)),
+            None => {
+                offset += 10;
+                tokens.push(Channel::DEFAULT, Tok::Word(at(offset)));
+            }
+        }
+    }
+    tokens
+}
+
+fn node(offset: usize) -> Node {
+    Node {
+        id: AstId::fresh(),
+        position: at(offset),
+    }
+}
+
+fn extractor() -> DocExtractor<Tok> {
+    DocExtractor::new(Tok::comment_text)
+        .with_marker(DocMarker::Line("///"))
+        .with_marker(DocMarker::Block("/**", "*/"))
+}
+
+#[test]
+fn test_line_doc_comments() {
+    let tokens = tokens(&[
+        Some("/// Adds two."),
+        Some("///"),
+        Some("///  Fast.  "),
+        None,
+    ]);
+    let nodes = [node(10)];
+    let docs = extractor().extract(&tokens, &nodes);
+    assert_eq!(docs.len(), 1);
+    assert_eq!(docs.docs_for(nodes[0].id), Some("Adds two.\n\n Fast."));
+}
+
+#[test]
+fn test_line_marker_rejects_longer_runs() {
+    let tokens = tokens(&[Some("//// ruler"), Some("////"), None]);
+    let docs = extractor().extract(&tokens, &[node(10)]);
+    assert!(docs.is_empty());
+}
+
+#[test]
+fn test_block_doc_comment_with_gutters() {
+    let tokens = tokens(&[Some("/**\n * Adds two.\n *\n * Fast.\n */"), None]);
+    let nodes = [node(10)];
+    let docs = extractor().extract(&tokens, &nodes);
+    assert_eq!(docs.docs_for(nodes[0].id), Some("Adds two.\n\nFast."));
+}
+
+#[test]
+fn test_first_node_at_a_token_claims_the_docs() {
+    let tokens = tokens(&[Some("/// The item."), None, None]);
+    let (item, name, next) = (node(10), node(10), node(20));
+    let docs = extractor().extract(&tokens, [&item, &name, &next]);
+    assert_eq!(docs.len(), 1);
+    assert_eq!(docs.docs_for(item.id), Some("The item."));
+    assert_eq!(docs.docs_for(name.id), None);
+    assert_eq!(docs.docs_for(next.id), None);
+}
+
+#[test]
+fn test_plain_comments_are_skipped() {
+    let tokens = tokens(&[
+        Some("/// First."),
+        Some("// not docs"),
+        Some("/* nor this */"),
+        Some("/// Second."),
+        None,
+    ]);
+    let nodes = [node(10)];
+    let docs = extractor().extract(&tokens, &nodes);
+    assert_eq!(docs.docs_for(nodes[0].id), Some("First.\nSecond."));
+}
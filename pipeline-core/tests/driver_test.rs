@@ -0,0 +1,127 @@
+//! Pipeline driver tests (`PipelineDriver`, `InputAnswer`).
+#![cfg(feature = "streaming")]
+
+mod common;
+
+use common::{lexer_rules, parser_rules, words, Tok, Word};
+use common_framework::StreamingSignal;
+use lexer_framework::{FilterTokens, Lexer, StreamingLexContext};
+use parser_framework::{Parser, StreamingParseContext};
+use pipeline_core::{InputAnswer, PipelineDriver};
+
+type WordLexer = FilterTokens<Lexer<StreamingLexContext, Tok>, fn(&Tok) -> bool>;
+type WordParser = Parser<StreamingParseContext<Tok>, Tok, Word>;
+
+/// Records what the hooks saw and answers `NeedInput` from `chunks`.
+struct Recorder {
+    lexer: WordLexer,
+    parser: WordParser,
+    nodes: Vec<Word>,
+    batch_size: usize,
+    requests: Vec<usize>,
+    supplied: Vec<usize>,
+    chunks: Vec<InputAnswer>,
+}
+
+impl Recorder {
+    fn new(context: StreamingLexContext) -> Self {
+        let keep: fn(&Tok) -> bool = |token| *token != Tok::Space;
+        Self {
+            lexer: Lexer::new(context, lexer_rules()).filter_tokens(keep),
+            parser: Parser::new(StreamingParseContext::new(), parser_rules()),
+            nodes: Vec::new(),
+            batch_size: 1,
+            requests: Vec::new(),
+            supplied: Vec::new(),
+            chunks: Vec::new(),
+        }
+    }
+}
+
+impl PipelineDriver<Tok, Word> for Recorder {
+    type Lexer = WordLexer;
+    type Parser = WordParser;
+
+    fn lexer_mut(&mut self) -> &mut WordLexer {
+        &mut self.lexer
+    }
+
+    fn parser_mut(&mut self) -> &mut WordParser {
+        &mut self.parser
+    }
+
+    fn on_produced(&mut self, nodes: Vec<Word>) {
+        self.nodes.extend(nodes);
+    }
+
+    fn on_need_token(&mut self, min_needed: usize) -> StreamingSignal<Tok, Word> {
+        self.requests.push(min_needed);
+        if self.batch_size > 1 {
+            StreamingSignal::RequestTokens(self.batch_size)
+        } else {
+            StreamingSignal::RequestToken(min_needed)
+        }
+    }
+
+    fn on_supplied(&mut self, count: usize) {
+        self.supplied.push(count);
+    }
+
+    fn on_need_input(&mut self) -> InputAnswer {
+        if self.chunks.is_empty() {
+            InputAnswer::End
+        } else {
+            self.chunks.remove(0)
+        }
+    }
+}
+
+#[test]
+fn test_drive_routes_tokens_and_nodes() {
+    let mut driver = Recorder::new(StreamingLexContext::from("one two three"));
+    driver.drive();
+    assert_eq!(words(&driver.nodes), ["one", "two", "three"]);
+    assert_eq!(driver.supplied, [1, 1, 1]);
+    assert!(driver.requests.iter().all(|&n| n == 1));
+}
+
+#[test]
+fn test_on_need_token_can_batch() {
+    let mut driver = Recorder::new(StreamingLexContext::from("a b c d e"));
+    driver.batch_size = 4;
+    driver.drive();
+    assert_eq!(words(&driver.nodes), ["a", "b", "c", "d", "e"]);
+    // Batches count lexed tokens, so filtered spaces make them smaller.
+    assert_eq!(driver.supplied.iter().sum::<usize>(), 5);
+    assert!(driver.supplied.len() < 5);
+}
+
+#[test]
+fn test_on_need_input_supplies_chunks() {
+    let mut driver = Recorder::new(StreamingLexContext::new());
+    driver.chunks = vec![
+        InputAnswer::Supply("al".into()),
+        InputAnswer::Supply("pha be".into()),
+        InputAnswer::Supply("ta".into()),
+    ];
+    driver.drive();
+    // Words split across chunks are put back together.
+    assert_eq!(words(&driver.nodes), ["alpha", "beta"]);
+}
+
+#[test]
+fn test_pause_returns_and_resumes() {
+    let mut driver = Recorder::new(StreamingLexContext::new());
+    driver.chunks = vec![InputAnswer::Supply("one tw".into()), InputAnswer::Pause];
+    driver.drive();
+    // `tw` may continue in the next chunk, so it is held back.
+    assert_eq!(words(&driver.nodes), ["one"]);
+
+    driver
+        .lexer_mut()
+        .inner_mut()
+        .context_mut()
+        .push_str("o three");
+    driver.drive();
+    assert_eq!(words(&driver.nodes), ["one", "two", "three"]);
+}
@@ -0,0 +1,127 @@
+//! Embedded language tests (`EmbeddedLanguages`, `BatchPipeline::run_embedded`).
+
+use common_framework::{Position, TextSlice};
+use lexer_framework::{DefaultContext as LexContext, LexContext as _, LexToken, LexingRule};
+use parser_framework::{AstNode, DefaultContext as ParseContext, ParseContext as _, ParsingRule};
+use pipeline_core::{BatchPipeline, EmbedHost, EmbeddedLanguages, EmbeddedRegion};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Word(String),
+    /// `[...]` is JavaScript, `{...}` is SQL.
+    Region(&'static str, TextSlice),
+}
+
+impl LexToken for Tok {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Word(String),
+    Placeholder(EmbeddedRegion),
+    Embedded(String, Vec<Node>),
+}
+
+impl AstNode for Node {
+    fn position(&self) -> Option<Position> {
+        None
+    }
+}
+
+impl EmbedHost for Node {
+    fn region(&self) -> Option<&EmbeddedRegion> {
+        match self {
+            Node::Placeholder(region) => Some(region),
+            _ => None,
+        }
+    }
+
+    fn embedded(region: EmbeddedRegion, nodes: Vec<Self>) -> Self {
+        Node::Embedded(region.language, nodes)
+    }
+}
+
+struct TokenRule;
+
+impl LexingRule<LexContext, Tok> for TokenRule {
+    fn try_match(&mut self, ctx: &mut LexContext) -> Option<Tok> {
+        ctx.consume_while(char::is_whitespace);
+        let (language, close) = match ctx.peek()? {
+            '[' => ("js", ']'),
+            '{' => ("sql", '}'),
+            _ => {
+                let word = ctx.consume_while(|ch| ch.is_alphanumeric());
+                return (!word.is_empty()).then(|| Tok::Word(word.to_string()));
+            }
+        };
+        ctx.advance();
+        let text = ctx.consume_while(|ch| ch != close);
+        ctx.advance()?;
+        Some(Tok::Region(language, text))
+    }
+}
+
+struct NodeRule;
+
+impl ParsingRule<ParseContext<Tok>, Tok, Node> for NodeRule {
+    fn try_parse(&mut self, ctx: &mut ParseContext<Tok>) -> Option<Node> {
+        Some(match ctx.advance()? {
+            Tok::Word(word) => Node::Word(word),
+            Tok::Region(language, text) => Node::Placeholder(EmbeddedRegion::new(language, text)),
+        })
+    }
+}
+
+fn run(input: &str, languages: &EmbeddedLanguages<Node>) -> (Vec<Node>, Vec<EmbeddedRegion>) {
+    BatchPipeline::run_embedded(
+        input,
+        vec![Box::new(TokenRule)],
+        vec![Box::new(NodeRule)],
+        languages,
+    )
+}
+
+fn word(word: &str) -> Node {
+    Node::Word(word.into())
+}
+
+/// Parses JavaScript regions into their words, upper-cased; SQL has no
+/// pipeline.
+fn languages() -> EmbeddedLanguages<Node> {
+    let mut languages = EmbeddedLanguages::new();
+    languages.register("js", |region| {
+        region
+            .text
+            .split_whitespace()
+            .map(|word| Node::Word(word.to_uppercase()))
+            .collect()
+    });
+    languages
+}
+
+#[test]
+fn test_registered_regions_are_resolved() {
+    let (nodes, unresolved) = run("a [b c] d", &languages());
+    assert_eq!(
+        nodes,
+        vec![
+            word("a"),
+            Node::Embedded("js".into(), vec![word("B"), word("C")]),
+            word("d"),
+        ]
+    );
+    assert!(unresolved.is_empty());
+}
+
+#[test]
+fn test_unregistered_regions_are_returned() {
+    let (nodes, unresolved) = run("a\n{select x} [b]", &languages());
+    assert_eq!(unresolved.len(), 1);
+    let region = &unresolved[0];
+    assert_eq!(region.language, "sql");
+    assert_eq!(region.text, "select x");
+    assert_eq!(region.position(), Position::at(2, 2, 3));
+
+    // The placeholder stays in the output.
+    assert_eq!(nodes[1], Node::Placeholder(region.clone()));
+    assert_eq!(nodes[2], Node::Embedded("js".into(), vec![word("B")]));
+}
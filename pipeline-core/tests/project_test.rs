@@ -0,0 +1,130 @@
+//! Multi-file project tests (`ProjectPipeline`, `Project::process`).
+
+use pipeline_core::{ProjectError, ProjectPipeline};
+
+/// Parses a file into its words; `@name` imports the module `name`.
+fn pipeline() -> ProjectPipeline<String> {
+    ProjectPipeline::new(
+        |source| source.split_whitespace().map(str::to_string).collect(),
+        |words| {
+            words
+                .iter()
+                .filter_map(|word| word.strip_prefix('@'))
+                .map(str::to_string)
+                .collect()
+        },
+    )
+}
+
+/// `count` modules, where module `i` imports modules `i / 2` and `i / 3`
+/// (if lower), added in reverse so ordering has work to do.
+fn layered(count: usize) -> ProjectPipeline<String> {
+    (0..count).rev().fold(pipeline(), |pipeline, i| {
+        let imports: Vec<String> = [i / 2, i / 3]
+            .iter()
+            .filter(|&&dep| dep < i)
+            .map(|dep| format!("@m{}", dep))
+            .collect();
+        pipeline.file(
+            format!("m{}", i),
+            format!("item{} {}", i, imports.join(" ")),
+        )
+    })
+}
+
+#[test]
+fn test_modules_come_after_their_dependencies() {
+    let project = pipeline()
+        .file("main", "@util @lib main")
+        .file("util", "@lib util")
+        .file("lib", "lib")
+        .file("extra", "extra")
+        .run()
+        .unwrap();
+
+    // Ties keep the order the files were added in.
+    assert_eq!(project.order(), ["lib", "extra", "util", "main"]);
+    assert_eq!(
+        project.module("main").unwrap().dependencies,
+        ["util", "lib"]
+    );
+}
+
+#[test]
+fn test_process_passes_dependency_results() {
+    let project = pipeline()
+        .file("main", "@util @lib")
+        .file("util", "@lib")
+        .file("lib", "")
+        .run()
+        .unwrap();
+
+    // Depth of each module in the dependency graph.
+    let depths =
+        project.process(|_, deps: &[&usize]| deps.iter().map(|&&d| d + 1).max().unwrap_or(0));
+    assert_eq!(depths, [0, 1, 2]);
+}
+
+#[test]
+fn test_cycle_is_reported() {
+    let error = pipeline()
+        .file("entry", "@a")
+        .file("a", "@b")
+        .file("b", "@c")
+        .file("c", "@a")
+        .run()
+        .unwrap_err();
+
+    // `entry` is stuck behind the cycle but not part of it.
+    assert_eq!(
+        error,
+        ProjectError::Cycle(vec!["a".into(), "b".into(), "c".into(), "a".into()])
+    );
+    assert_eq!(error.to_string(), "dependency cycle: a -> b -> c -> a");
+}
+
+#[test]
+fn test_self_import_is_a_cycle() {
+    let error = pipeline().file("a", "@a").run().unwrap_err();
+    assert_eq!(error, ProjectError::Cycle(vec!["a".into(), "a".into()]));
+}
+
+#[test]
+fn test_missing_and_duplicate_modules() {
+    assert_eq!(
+        pipeline().file("main", "@util").run().unwrap_err(),
+        ProjectError::MissingDependency {
+            module: "main".into(),
+            dependency: "util".into(),
+        }
+    );
+    assert_eq!(
+        pipeline().file("a", "").file("a", "").run().unwrap_err(),
+        ProjectError::DuplicateModule("a".into())
+    );
+}
+
+#[test]
+fn test_parallel_matches_sequential() {
+    let sequential = layered(60).with_threads(1).run().unwrap();
+    let parallel = layered(60).with_threads(4).run().unwrap();
+    assert_eq!(parallel.order(), sequential.order());
+    for (a, b) in parallel.modules().iter().zip(sequential.modules()) {
+        assert_eq!(a.nodes, b.nodes);
+    }
+
+    // Each module's result is its name plus its dependencies' results.
+    let summarize = |module: &pipeline_core::Module<String>, deps: &[&String]| {
+        let deps: Vec<&str> = deps.iter().map(|dep| dep.as_str()).collect();
+        format!("{}[{}]", module.name, deps.join(","))
+    };
+    let expected = sequential.process(summarize);
+    assert_eq!(parallel.process(summarize), expected);
+
+    let position = |name: &str| parallel.order().iter().position(|&n| n == name).unwrap();
+    for module in parallel.modules() {
+        for dependency in &module.dependencies {
+            assert!(position(dependency) < position(&module.name));
+        }
+    }
+}
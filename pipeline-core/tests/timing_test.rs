@@ -0,0 +1,65 @@
+//! Stage timing tests (`BatchPipeline::run_timed`, `StageTimings`).
+
+mod common;
+
+use common::{lexer_rules, parser_rules, words, Tok, Word};
+use pipeline_core::BatchPipeline;
+use std::time::Duration;
+
+#[test]
+fn test_run_timed_counts_each_stage() {
+    let (nodes, timings) = BatchPipeline::<Tok, Word>::run_timed(
+        "one two three",
+        lexer_rules(),
+        |token: &Tok| *token != Tok::Space,
+        parser_rules(),
+    );
+    assert_eq!(words(&nodes), ["one", "two", "three"]);
+
+    let counts: Vec<(&str, usize, usize)> = timings
+        .stages()
+        .iter()
+        .map(|(name, report)| (*name, report.input, report.output))
+        .collect();
+    assert_eq!(counts, [("lex", 13, 5), ("filter", 5, 3), ("parse", 3, 3)]);
+    assert!(timings
+        .stages()
+        .iter()
+        .all(|(_, report)| report.iterations == 1));
+    assert_eq!(
+        timings.total(),
+        timings.lex.elapsed + timings.filter.elapsed + timings.parse.elapsed
+    );
+}
+
+#[test]
+fn test_display_lists_stages_and_total() {
+    let (_, mut timings) = BatchPipeline::<Tok, Word>::run_timed(
+        "a b",
+        lexer_rules(),
+        |token: &Tok| *token != Tok::Space,
+        parser_rules(),
+    );
+    timings.lex.elapsed = Duration::from_millis(3);
+    timings.filter.elapsed = Duration::ZERO;
+    timings.parse.elapsed = Duration::from_millis(1);
+
+    let text = timings.to_string();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("lex ") && lines[0].contains(" 75.0% "));
+    assert!(lines[0].ends_with("3 in          3 out"));
+    assert!(lines[1].starts_with("filter ") && lines[1].contains("  0.0% "));
+    assert!(lines[2].starts_with("parse ") && lines[2].contains(" 25.0% "));
+    assert!(lines[3].starts_with("total ") && lines[3].ends_with("4ms"));
+}
+
+#[test]
+fn test_display_with_zero_total() {
+    let (_, mut timings) =
+        BatchPipeline::<Tok, Word>::run_timed("", lexer_rules(), |_: &Tok| true, parser_rules());
+    timings.lex.elapsed = Duration::ZERO;
+    timings.filter.elapsed = Duration::ZERO;
+    timings.parse.elapsed = Duration::ZERO;
+    assert!(!timings.to_string().contains("NaN"));
+}
@@ -0,0 +1,126 @@
+//! Directory watcher tests (`DirWatcher`).
+#![cfg(feature = "watch")]
+
+use pipeline_core::{DirWatcher, FileChange, ParseCache, WatchEvent};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+/// A fresh directory under the system temp dir, removed on drop.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "pipeline-core-watch-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    fn write(&self, name: &str, contents: &str) -> PathBuf {
+        let path = self.0.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, contents).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn modified(changes: &[FileChange]) -> Vec<(&Path, &str)> {
+    changes
+        .iter()
+        .filter_map(|change| match change {
+            FileChange::Modified(path, contents) => Some((path.as_path(), contents.as_str())),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_poll_reports_changed_contents() {
+    let dir = TempDir::new("poll");
+    let a = dir.write("a.md", "one");
+    let b = dir.write("sub/b.md", "two");
+    dir.write("skip.txt", "ignored");
+
+    let mut watcher = DirWatcher::new(&dir.0).with_extension("md");
+    let first = watcher.poll();
+    assert_eq!(
+        modified(&first),
+        [(a.as_path(), "one"), (b.as_path(), "two")]
+    );
+    assert!(watcher.poll().is_empty());
+
+    // Rewriting the same contents is not a change.
+    fs::write(&a, "one").unwrap();
+    assert!(modified(&watcher.poll()).is_empty());
+
+    fs::write(&a, "one, edited").unwrap();
+    fs::remove_file(&b).unwrap();
+    let changes = watcher.poll();
+    assert_eq!(modified(&changes), [(a.as_path(), "one, edited")]);
+    assert!(matches!(&changes[1], FileChange::Removed(path) if *path == b));
+}
+
+/// Waits for the next event, failing the test if none arrives in time.
+fn next(events: &Receiver<WatchEvent<usize>>) -> WatchEvent<usize> {
+    events
+        .recv_timeout(Duration::from_secs(10))
+        .expect("no watch event within 10 s")
+}
+
+#[test]
+fn test_spawn_reports_notified_changes() {
+    let dir = TempDir::new("spawn");
+    let a = dir.write("a.md", "one");
+
+    let events = DirWatcher::new(&dir.0)
+        .with_extension("md")
+        .with_debounce(Duration::from_millis(20))
+        .spawn(ParseCache::new(1), |_, source| source.len());
+
+    match next(&events) {
+        WatchEvent::Processed { path, result } => assert_eq!((path, result), (a.clone(), 3)),
+        other => panic!("expected the existing file, got {:?}", other),
+    }
+
+    // The initial scan runs after notifications are set up, so this is seen.
+    let b = dir.write("sub/b.md", "three");
+    match next(&events) {
+        WatchEvent::Processed { path, result } => assert_eq!((path, result), (b.clone(), 5)),
+        other => panic!("expected the new file, got {:?}", other),
+    }
+
+    fs::remove_file(&a).unwrap();
+    match next(&events) {
+        WatchEvent::Removed { path } => assert_eq!(path, a),
+        other => panic!("expected the removal, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_missing_root_is_reported() {
+    let dir = TempDir::new("missing");
+    let root = dir.0.join("gone");
+    let mut cache = ParseCache::new(1);
+    let mut events = Vec::new();
+    DirWatcher::new(&root).run(
+        &mut cache,
+        |_, source| source.len(),
+        |event| {
+            events.push(event);
+            std::ops::ControlFlow::Continue(())
+        },
+    );
+    assert!(matches!(&events[..], [WatchEvent::Error { path, .. }] if *path == root));
+}